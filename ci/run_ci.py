@@ -103,6 +103,11 @@ def _run_rust():
         "cargo doc",
         "cargo build --all-features --all-targets",
         "cargo test",
+        # the declared MSRV with the oldest allowed dependencies
+        "rustup toolchain install 1.70 --profile minimal",
+        "cargo +nightly -Z minimal-versions update",
+        "cargo +1.70 check -p fury-core -p fury -p fury-derive",
+        "cargo update",
         "cargo clean",
     )
     for cmd in cmds:
@@ -230,6 +230,11 @@ case $1 in
         echo "Executing fury rust tests failed"
         exit $testcode
       fi
+      # the declared MSRV with the oldest allowed dependencies
+      rustup toolchain install 1.70 --profile minimal
+      cargo +nightly -Z minimal-versions update
+      cargo +1.70 check -p fury-core -p fury -p fury-derive
+      cargo update
       cargo clean
       echo "Executing fury rust tests succeeds"
     ;;
@@ -0,0 +1,56 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! `order_echo serve <addr>` runs the service, `order_echo send <addr>` sends one order to it.
+
+use fury_order_service::{new_fury, round_trip, serve, Order};
+use std::env;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().collect();
+    let result = match (args.get(1).map(String::as_str), args.get(2)) {
+        (Some("serve"), Some(addr)) => TcpListener::bind(addr).and_then(serve),
+        (Some("send"), Some(addr)) => send(addr),
+        _ => {
+            eprintln!("usage: order_echo serve|send <addr>");
+            return ExitCode::FAILURE;
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn send(addr: &str) -> io::Result<()> {
+    let order = Order {
+        id: 1,
+        customer: "fury".to_string(),
+        items: vec!["book".to_string()],
+        quantities: vec![2],
+        note: None,
+    };
+    let echoed = round_trip(&new_fury(), &mut TcpStream::connect(addr)?, &order)?;
+    println!("{echoed:?}");
+    Ok(())
+}
@@ -0,0 +1,101 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! A small TCP echo service exchanging [`Order`]s, used as a cross-language smoke test.
+//!
+//! Every message is a 4 byte little endian length followed by one fury payload written in
+//! compatible mode, so peers share type metas instead of relying on identical declarations.
+//! A Java or Python peer interoperates by declaring the same fields and registering the type
+//! under [`ORDER_TYPE_ID`].
+
+use fury::prelude::*;
+use fury_core::fury::DeserializeOptions;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+pub const ORDER_TYPE_ID: u32 = 256;
+
+#[derive(Fury, Debug, Clone, PartialEq)]
+pub struct Order {
+    pub id: i64,
+    pub customer: String,
+    pub items: Vec<String>,
+    pub quantities: Vec<i32>,
+    pub note: Option<String>,
+}
+
+pub fn new_fury() -> Fury {
+    let mut fury = Fury::default()
+        .mode(Mode::Compatible)
+        .deserialize_options(DeserializeOptions::default().strict_trailing(true));
+    fury.register::<Order>(ORDER_TYPE_ID);
+    fury
+}
+
+pub fn write_frame(stream: &mut impl Write, payload: &[u8]) -> io::Result<()> {
+    stream.write_all(&(payload.len() as u32).to_le_bytes())?;
+    stream.write_all(payload)?;
+    stream.flush()
+}
+
+/// Returns `None` once the peer closed the connection.
+pub fn read_frame(stream: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    match stream.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let mut payload = vec![0u8; u32::from_le_bytes(len) as usize];
+    stream.read_exact(&mut payload)?;
+    Ok(Some(payload))
+}
+
+fn invalid_data(e: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+/// Decodes every order sent on `stream` and writes it back re-encoded, until the peer hangs up.
+pub fn echo(fury: &Fury, mut stream: TcpStream) -> io::Result<()> {
+    while let Some(payload) = read_frame(&mut stream)? {
+        let order: Order = fury.deserialize(&payload).map_err(invalid_data)?;
+        write_frame(&mut stream, &fury.serialize(&order))?;
+    }
+    Ok(())
+}
+
+/// Serves connections one thread each, forever.
+pub fn serve(listener: TcpListener) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        thread::spawn(move || {
+            if let Err(e) = echo(&new_fury(), stream) {
+                eprintln!("connection closed: {e}");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Sends `order` and returns what the service echoed back.
+pub fn round_trip(fury: &Fury, stream: &mut TcpStream, order: &Order) -> io::Result<Order> {
+    write_frame(stream, &fury.serialize(order))?;
+    let payload = read_frame(stream)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "service hung up"))?;
+    fury.deserialize(&payload).map_err(invalid_data)
+}
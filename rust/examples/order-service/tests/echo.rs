@@ -0,0 +1,40 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_order_service::{new_fury, round_trip, serve, Order};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+#[test]
+fn echo_orders() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || serve(listener));
+
+    let fury = new_fury();
+    let mut stream = TcpStream::connect(addr).unwrap();
+    for id in 0..3 {
+        let order = Order {
+            id,
+            customer: format!("customer {id}"),
+            items: vec!["apple".to_string(), "pear".to_string()],
+            quantities: vec![1, id as i32],
+            note: (id % 2 == 0).then(|| "fragile".to_string()),
+        };
+        assert_eq!(round_trip(&fury, &mut stream, &order).unwrap(), order);
+    }
+}
@@ -0,0 +1,59 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fury_core::resolver::ref_resolver::RefWriter;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// a graph where every node is reached twice, like shared children in a tree
+fn pointers(len: usize) -> (Vec<Rc<u64>>, Vec<*const ()>) {
+    let nodes: Vec<Rc<u64>> = (0..len as u64).map(Rc::new).collect();
+    let visits = nodes
+        .iter()
+        .chain(nodes.iter().rev())
+        .map(|node| Rc::as_ptr(node) as *const ())
+        .collect();
+    (nodes, visits)
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ref identity table");
+    for len in [64, 4096, 262_144] {
+        let (_nodes, visits) = pointers(len);
+        group.bench_with_input(BenchmarkId::new("RefWriter", len), &visits, |b, visits| {
+            b.iter(|| {
+                let mut refs = RefWriter::new();
+                for ptr in visits {
+                    black_box(refs.get_or_assign(*ptr));
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("HashMap", len), &visits, |b, visits| {
+            b.iter(|| {
+                let mut refs: HashMap<usize, u32> = HashMap::new();
+                for ptr in visits {
+                    let next = refs.len() as u32;
+                    black_box(*refs.entry(*ptr as usize).or_insert(next));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
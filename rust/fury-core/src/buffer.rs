@@ -15,30 +15,93 @@
 // specific language governing permissions and limitations
 // under the License.
 
+//! Fury's primitive encodings on their own, usable for custom framing without the object model.
+//!
+//! What [`Writer`] writes, [`Reader`] reads back:
+//! - fixed width numbers are little endian, whatever the host,
+//! - [`Writer::var_uint32`] and [`Writer::var_uint64`] are varints with 7 bits per byte, low bits
+//!   first, [`Writer::var_zigzag_int32`] and [`Writer::var_int64`] zigzag encode before that,
+//!   [`Writer::var_int32`] doesn't,
+//! - [`Writer::sli_int64`] takes 4 bytes for values of 31 bits and 9 for others,
+//! - bytes and strings are written as is, their length is up to the caller.
+//!
+//! Neither side tracks types or lengths: a [`Reader`] trusts the caller to read what was written.
+//! Reading past the end of its input fails with [`Error::Truncated`]. Lengths read from
+//! untrusted input are checked with [`Reader::check_remaining`] before allocating for them.
+
+use crate::error::Error;
 use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
-
+use std::borrow::Cow;
+use std::io::IoSlice;
+
+// range of the values [`Writer::sli_int64`] writes in 4 bytes
+const SLI_MIN: i64 = (i32::MIN / 2) as i64;
+const SLI_MAX: i64 = (i32::MAX / 2) as i64;
+// low bit of the first byte of a value written in 9 bytes
+const SLI_BIG_FLAG: u8 = 0b1;
+
+/// Growable buffer the encodings of this module are appended to.
+///
+/// Lengths and offsets count from where the writer started, which is after the bytes already
+/// in the buffer it was created [`from`](Writer::from_vec).
 #[derive(Default)]
 pub struct Writer {
     bf: Vec<u8>,
+    start: usize,
     reserved: usize,
 }
 
 impl Writer {
+    /// Appends to `bf`, keeping what it holds and its capacity.
+    pub fn from_vec(bf: Vec<u8>) -> Writer {
+        Writer {
+            start: bf.len(),
+            bf,
+            reserved: 0,
+        }
+    }
+
+    /// The buffer with everything written appended, see [`Writer::from_vec`].
+    pub fn into_vec(self) -> Vec<u8> {
+        self.bf
+    }
+
+    /// Copy of everything written so far.
     pub fn dump(&self) -> Vec<u8> {
-        self.bf.clone()
+        self.as_slice().to_vec()
     }
 
     pub fn len(&self) -> usize {
-        self.bf.len()
+        self.bf.len() - self.start
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bf[self.start..]
     }
 
     pub fn is_empty(&self) -> bool {
-        self.bf.is_empty()
+        self.len() == 0
+    }
+
+    /// Drops everything written so far but keeps the capacity, to write the next message.
+    pub fn clear(&mut self) {
+        self.bf.truncate(self.start);
+        self.reserved = 0;
+    }
+
+    /// Drops what was written after the first `len` bytes.
+    pub fn truncate(&mut self, len: usize) {
+        self.bf.truncate(self.start + len);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.bf.capacity()
     }
 
+    /// Grows the capacity to fit `additional` more bytes on top of all earlier reservations.
     pub fn reserve(&mut self, additional: usize) {
         self.reserved += additional;
-        if self.bf.capacity() < self.reserved {
+        if self.bf.capacity() - self.start < self.reserved {
             self.bf.reserve(self.reserved);
         }
     }
@@ -63,10 +126,16 @@ impl Writer {
         self.bf.write_u32::<LittleEndian>(value).unwrap();
     }
 
+    /// Leaves `len` zero bytes, to be filled later with [`Writer::set_bytes`].
     pub fn skip(&mut self, len: usize) {
         self.bf.resize(self.bf.len() + len, 0);
     }
 
+    /// Writes zero bytes until the length is a multiple of `alignment`.
+    pub fn pad_to(&mut self, alignment: usize) {
+        self.skip((alignment - self.len() % alignment) % alignment);
+    }
+
     pub fn i32(&mut self, value: i32) {
         self.bf.write_i32::<LittleEndian>(value).unwrap();
     }
@@ -87,31 +156,51 @@ impl Writer {
         self.bf.write_u64::<LittleEndian>(value).unwrap();
     }
 
+    /// Varint of the bits of `value`, from 1 byte below 128 to 5 bytes for negative values.
     pub fn var_int32(&mut self, value: i32) {
-        if value >> 7 == 0 {
-            self.u8(value as u8);
-        } else if value >> 14 == 0 {
-            let u1 = (value & 0x7F) | 0x80;
-            let u2 = value >> 7;
-            self.u16(((u1 << 8) | u2) as u16);
-        } else if value >> 21 == 0 {
-            let u1 = (value & 0x7F) | 0x80;
-            let u2 = value >> 7 | 0x80;
-            self.u16(((u1 << 8) | u2) as u16);
-            self.u8((value >> 14) as u8);
-        } else if value >> 28 == 0 {
-            let u1 = (value & 0x7F) | 0x80;
-            let u2 = value >> 7 | 0x80;
-            let u3 = value >> 14 | 0x80;
-            let u4 = value >> 21 | 0x80;
-            self.u32(((u1 << 24) | (u2 << 16) | (u3 << 8) | u4) as u32);
+        self.var_uint32(value as u32);
+    }
+
+    /// Unsigned varint32: 7 bits per byte, 1 to 5 bytes.
+    pub fn var_uint32(&mut self, mut value: u32) {
+        while value >= 0x80 {
+            self.u8((value & 0x7F) as u8 | 0x80);
+            value >>= 7;
+        }
+        self.u8(value as u8);
+    }
+
+    /// ZigZag encoded [`Writer::var_uint32`], Java's `writeVarInt32`.
+    pub fn var_zigzag_int32(&mut self, value: i32) {
+        self.var_uint32(((value << 1) ^ (value >> 31)) as u32);
+    }
+
+    /// Unsigned PVL varint64: 7 bits per byte, the 9th byte if any holds the last 8 bits.
+    pub fn var_uint64(&mut self, mut value: u64) {
+        for _ in 0..8 {
+            if value < 0x80 {
+                self.u8(value as u8);
+                return;
+            }
+            self.u8((value & 0x7F) as u8 | 0x80);
+            value >>= 7;
+        }
+        self.u8(value as u8);
+    }
+
+    /// ZigZag encoded [`Writer::var_uint64`], small negative numbers stay short.
+    pub fn var_int64(&mut self, value: i64) {
+        self.var_uint64(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    /// SLI int64 of the spec, Java's `writeSliInt64`: values of 31 bits as 4 bytes of the value
+    /// shifted left by one, others as a byte with the low bit set followed by 8 bytes.
+    pub fn sli_int64(&mut self, value: i64) {
+        if (SLI_MIN..=SLI_MAX).contains(&value) {
+            self.i32((value as i32) << 1);
         } else {
-            let u1 = (value & 0x7F) | 0x80;
-            let u2 = value >> 7 | 0x80;
-            let u3 = value >> 14 | 0x80;
-            let u4 = value >> 21 | 0x80;
-            self.u32(((u1 << 24) | (u2 << 16) | (u3 << 8) | u4) as u32);
-            self.u8((value >> 28) as u8);
+            self.u8(SLI_BIG_FLAG);
+            self.i64(value);
         }
     }
 
@@ -120,142 +209,359 @@ impl Writer {
         self.bf.extend_from_slice(v);
     }
 
+    /// Overwrites bytes already written at `offset`, panics if they extend past the end.
     pub fn set_bytes(&mut self, offset: usize, data: &[u8]) {
+        let offset = self.start + offset;
         self.bf
             .get_mut(offset..offset + data.len())
-            .expect("//todo")
+            .expect("set_bytes past the end of the written bytes")
             .copy_from_slice(data);
     }
 }
 
+/// The input of a [`Reader`], in order.
+#[derive(Clone, Copy)]
+enum Segments<'de> {
+    Pair([&'de [u8]; 2]),
+    Slices(&'de [&'de [u8]]),
+    IoSlices(&'de [IoSlice<'de>]),
+}
+
+impl<'de> Segments<'de> {
+    fn get(&self, index: usize) -> &'de [u8] {
+        match *self {
+            Segments::Pair(pair) => pair[index],
+            Segments::Slices(slices) => slices[index],
+            Segments::IoSlices(slices) => &slices[index],
+        }
+    }
+
+    fn count(&self) -> usize {
+        match self {
+            Segments::Pair(_) => 2,
+            Segments::Slices(slices) => slices.len(),
+            Segments::IoSlices(slices) => slices.len(),
+        }
+    }
+}
+
+/// Reads fury's wire format from one contiguous buffer or a list of segments, such as the
+/// readable region of a ring buffer or the header and body slices an RPC framework hands over.
+///
+/// `cursor` is a logical position over the segments one after the other; primitives that
+/// straddle a boundary are reassembled on the stack, nothing is copied up front.
+///
+/// Custom serializers implementing look-ahead formats can rely on [`Reader::offset`],
+/// [`Reader::remaining`] and [`Reader::peek_u8`], they never move the cursor.
+#[derive(Clone, Copy)]
 pub struct Reader<'de> {
-    bf: &'de [u8],
+    segments: Segments<'de>,
+    len: usize,
     cursor: usize,
+    // the segment the cursor is in, its index and the offset of its first byte
+    current: &'de [u8],
+    index: usize,
+    start: usize,
 }
 
 impl<'bf> Reader<'bf> {
-    pub fn new(bf: &[u8]) -> Reader {
-        Reader { bf, cursor: 0 }
+    pub fn new(bf: &[u8]) -> Reader<'_> {
+        Reader::from_segments(bf, &[])
+    }
+
+    pub fn from_segments(head: &'bf [u8], tail: &'bf [u8]) -> Reader<'bf> {
+        Reader::over(Segments::Pair([head, tail]), head.len() + tail.len())
+    }
+
+    /// Builds a reader over `segments` in order, which may be empty.
+    pub fn from_slices(segments: &'bf [&'bf [u8]]) -> Reader<'bf> {
+        if segments.is_empty() {
+            return Reader::new(&[]);
+        }
+        let len = segments.iter().map(|s| s.len()).sum();
+        Reader::over(Segments::Slices(segments), len)
+    }
+
+    /// Builds a reader over `segments` in order, which may be empty.
+    pub fn from_io_slices(segments: &'bf [IoSlice<'bf>]) -> Reader<'bf> {
+        if segments.is_empty() {
+            return Reader::new(&[]);
+        }
+        let len = segments.iter().map(|s| s.len()).sum();
+        Reader::over(Segments::IoSlices(segments), len)
+    }
+
+    fn over(segments: Segments<'bf>, len: usize) -> Reader<'bf> {
+        let mut reader = Reader {
+            segments,
+            len,
+            cursor: 0,
+            current: &[],
+            index: 0,
+            start: 0,
+        };
+        reader.seek();
+        reader
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Copies all segments into one buffer.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(self.len);
+        for index in 0..self.segments.count() {
+            result.extend_from_slice(self.segments.get(index));
+        }
+        result
+    }
+
+    /// Number of bytes read so far, the offset of the next one.
+    pub fn get_cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Offset of the next byte from the start of the input, same as [`Reader::get_cursor`].
+    pub fn offset(&self) -> usize {
+        self.cursor
+    }
+
+    /// Number of bytes after the cursor, zero once it reached or skipped past the end.
+    pub fn remaining(&self) -> usize {
+        self.len().saturating_sub(self.cursor)
+    }
+
+    /// The next byte without consuming it, `None` at the end of the input.
+    pub fn peek_u8(&self) -> Option<u8> {
+        if self.remaining() == 0 {
+            return None;
+        }
+        Some(self.slice_after_cursor()[0])
+    }
+
+    /// Returns a reader over the same segments positioned at the absolute `offset`.
+    pub fn at(&self, offset: usize) -> Reader<'bf> {
+        let mut reader = Reader {
+            cursor: offset,
+            ..*self
+        };
+        reader.seek();
+        reader
     }
 
     fn move_next(&mut self, additional: usize) {
         self.cursor += additional;
+        if self.cursor >= self.start + self.current.len() {
+            self.seek();
+        }
     }
 
-    fn slice_after_cursor(&self) -> &[u8] {
-        &self.bf[self.cursor..self.bf.len()]
+    /// Moves `current` to the segment the cursor is in, the last one once it reached the end.
+    fn seek(&mut self) {
+        if self.cursor < self.start {
+            self.index = 0;
+            self.start = 0;
+        }
+        loop {
+            let segment = self.segments.get(self.index);
+            let last = self.index + 1 == self.segments.count();
+            if last || self.cursor < self.start + segment.len() {
+                self.current = segment;
+                return;
+            }
+            self.start += segment.len();
+            self.index += 1;
+        }
     }
 
-    pub fn u8(&mut self) -> u8 {
-        let result = self.bf[self.cursor];
-        self.move_next(1);
-        result
+    /// Contiguous bytes from the cursor to the end of the current segment, none past the end.
+    fn slice_after_cursor(&self) -> &'bf [u8] {
+        self.current.get(self.cursor - self.start..).unwrap_or(&[])
+    }
+
+    /// Fills `out` with the bytes after the cursor, across as many segments as they span.
+    fn gather(&self, out: &mut [u8]) {
+        let mut filled = 0;
+        let mut index = self.index;
+        let mut offset = self.cursor - self.start;
+        while filled < out.len() {
+            let segment = &self.segments.get(index)[offset..];
+            let n = segment.len().min(out.len() - filled);
+            out[filled..filled + n].copy_from_slice(&segment[..n]);
+            filled += n;
+            index += 1;
+            offset = 0;
+        }
+    }
+
+    fn fixed<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        self.check_remaining(N)?;
+        let mut result = [0u8; N];
+        let current = self.slice_after_cursor();
+        if current.len() >= N {
+            result.copy_from_slice(&current[..N]);
+        } else {
+            self.gather(&mut result);
+        }
+        self.move_next(N);
+        Ok(result)
+    }
+
+    /// Whether the next `len` bytes lie in a single segment and can be borrowed.
+    pub fn contiguous(&self, len: usize) -> bool {
+        self.slice_after_cursor().len() >= len
     }
 
-    pub fn i8(&mut self) -> i8 {
-        let result = self.bf[self.cursor];
+    pub fn u8(&mut self) -> Result<u8, Error> {
+        self.check_remaining(1)?;
+        let result = self.slice_after_cursor()[0];
         self.move_next(1);
-        result as i8
+        Ok(result)
     }
 
-    pub fn u16(&mut self) -> u16 {
-        let result = LittleEndian::read_u16(self.slice_after_cursor());
-        self.move_next(2);
-        result
+    pub fn i8(&mut self) -> Result<i8, Error> {
+        Ok(self.u8()? as i8)
     }
 
-    pub fn i16(&mut self) -> i16 {
-        let result = LittleEndian::read_i16(self.slice_after_cursor());
-        self.move_next(2);
-        result
+    pub fn u16(&mut self) -> Result<u16, Error> {
+        Ok(LittleEndian::read_u16(&self.fixed::<2>()?))
     }
 
-    pub fn u32(&mut self) -> u32 {
-        let result = LittleEndian::read_u32(self.slice_after_cursor());
-        self.move_next(4);
-        result
+    pub fn i16(&mut self) -> Result<i16, Error> {
+        Ok(LittleEndian::read_i16(&self.fixed::<2>()?))
     }
 
-    pub fn i32(&mut self) -> i32 {
-        let result = LittleEndian::read_i32(self.slice_after_cursor());
-        self.move_next(4);
-        result
+    pub fn u32(&mut self) -> Result<u32, Error> {
+        Ok(LittleEndian::read_u32(&self.fixed::<4>()?))
     }
 
-    pub fn u64(&mut self) -> u64 {
-        let result = LittleEndian::read_u64(self.slice_after_cursor());
-        self.move_next(8);
-        result
+    pub fn i32(&mut self) -> Result<i32, Error> {
+        Ok(LittleEndian::read_i32(&self.fixed::<4>()?))
     }
 
-    pub fn i64(&mut self) -> i64 {
-        let result = LittleEndian::read_i64(self.slice_after_cursor());
-        self.move_next(8);
-        result
+    pub fn u64(&mut self) -> Result<u64, Error> {
+        Ok(LittleEndian::read_u64(&self.fixed::<8>()?))
     }
 
-    pub fn f32(&mut self) -> f32 {
-        let result = LittleEndian::read_f32(self.slice_after_cursor());
-        self.move_next(4);
-        result
+    pub fn i64(&mut self) -> Result<i64, Error> {
+        Ok(LittleEndian::read_i64(&self.fixed::<8>()?))
     }
 
-    pub fn f64(&mut self) -> f64 {
-        let result = LittleEndian::read_f64(self.slice_after_cursor());
-        self.move_next(8);
-        result
+    pub fn f32(&mut self) -> Result<f32, Error> {
+        Ok(LittleEndian::read_f32(&self.fixed::<4>()?))
+    }
+
+    pub fn f64(&mut self) -> Result<f64, Error> {
+        Ok(LittleEndian::read_f64(&self.fixed::<8>()?))
     }
 
-    pub fn var_int32(&mut self) -> i32 {
-        let mut byte_ = self.i8() as i32;
-        let mut result = byte_ & 0x7F;
-        if (byte_ & 0x80) != 0 {
-            byte_ = self.i8() as i32;
-            result |= (byte_ & 0x7F) << 7;
-            if (byte_ & 0x80) != 0 {
-                byte_ = self.i8() as i32;
-                result |= (byte_ & 0x7F) << 14;
-                if (byte_ & 0x80) != 0 {
-                    byte_ = self.i8() as i32;
-                    result |= (byte_ & 0x7F) << 21;
-                    if (byte_ & 0x80) != 0 {
-                        byte_ = self.i8() as i32;
-                        result |= (byte_ & 0x7F) << 28;
-                    }
-                }
+    pub fn var_int32(&mut self) -> Result<i32, Error> {
+        let mut result = 0;
+        for shift in (0..28).step_by(7) {
+            let byte = self.u8()? as i32;
+            result |= (byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
             }
         }
-        result
+        Ok(result | (self.u8()? as i32 & 0x7F) << 28)
     }
 
-    pub fn string(&mut self, len: usize) -> String {
-        let result = String::from_utf8_lossy(&self.bf[self.cursor..self.cursor + len]).to_string();
-        self.move_next(len);
-        result
+    pub fn var_uint32(&mut self) -> Result<u32, Error> {
+        Ok(self.var_int32()? as u32)
+    }
+
+    pub fn var_zigzag_int32(&mut self) -> Result<i32, Error> {
+        let value = self.var_uint32()?;
+        Ok(((value >> 1) as i32) ^ -((value & 1) as i32))
     }
 
-    pub fn skip(&mut self, len: u32) {
+    pub fn var_uint64(&mut self) -> Result<u64, Error> {
+        let mut result = 0;
+        for shift in (0..56).step_by(7) {
+            let byte = self.u8()?;
+            result |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+        Ok(result | (self.u8()? as u64) << 56)
+    }
+
+    pub fn var_int64(&mut self) -> Result<i64, Error> {
+        let value = self.var_uint64()?;
+        Ok(((value >> 1) as i64) ^ -((value & 1) as i64))
+    }
+
+    pub fn sli_int64(&mut self) -> Result<i64, Error> {
+        let first = self.u8()?;
+        if first & SLI_BIG_FLAG != 0 {
+            return self.i64();
+        }
+        let bytes = [first, self.u8()?, self.u8()?, self.u8()?];
+        Ok((i32::from_le_bytes(bytes) >> 1) as i64)
+    }
+
+    /// Reads `len` bytes as UTF-8, replacing invalid sequences.
+    pub fn string(&mut self, len: usize) -> Result<String, Error> {
+        Ok(String::from_utf8_lossy(&self.bytes(len)?).into_owned())
+    }
+
+    pub fn skip(&mut self, len: u32) -> Result<(), Error> {
+        self.check_remaining(len as usize)?;
         self.move_next(len as usize);
+        Ok(())
     }
 
-    pub fn slice(&self) -> &[u8] {
-        self.bf
+    /// Skips the padding written by [`Writer::pad_to`], up to the next multiple of `alignment`.
+    pub fn skip_padding(&mut self, alignment: usize) -> Result<(), Error> {
+        self.skip(((alignment - self.cursor % alignment) % alignment) as u32)
     }
 
-    pub fn bytes(&mut self, len: usize) -> &'bf [u8] {
-        let result = &self.bf[self.cursor..self.cursor + len];
+    /// Fails with [`Error::Truncated`] unless `len` more bytes follow the cursor, for lengths
+    /// read from the input before anything is allocated for them.
+    pub fn check_remaining(&self, len: usize) -> Result<(), Error> {
+        if len > self.remaining() {
+            return Err(Error::Truncated {
+                offset: self.cursor,
+                needed: len,
+                len: self.len,
+            });
+        }
+        Ok(())
+    }
+
+    /// Borrows the next `len` bytes, copying only when they straddle a segment boundary.
+    pub fn bytes(&mut self, len: usize) -> Result<Cow<'bf, [u8]>, Error> {
+        self.check_remaining(len)?;
+        let current = self.slice_after_cursor();
+        let result = if current.len() >= len {
+            Cow::Borrowed(&current[..len])
+        } else {
+            let mut owned = vec![0; len];
+            self.gather(&mut owned);
+            Cow::Owned(owned)
+        };
         self.move_next(len);
-        result
+        Ok(result)
     }
 
+    /// Returns a closure moving the cursor back to where it is now.
     pub fn reset_cursor_to_here(&self) -> impl FnOnce(&mut Self) {
         let raw_cursor = self.cursor;
         move |this: &mut Self| {
             this.cursor = raw_cursor;
+            this.seek();
         }
     }
 
+    /// Whether the next byte is in memory aligned for `T`.
     pub fn aligned<T>(&self) -> bool {
-        unsafe { (self.bf.as_ptr().add(self.cursor) as usize) % std::mem::align_of::<T>() == 0 }
+        (self.slice_after_cursor().as_ptr() as usize) % std::mem::align_of::<T>() == 0
     }
 }
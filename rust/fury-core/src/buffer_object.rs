@@ -0,0 +1,51 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Out-of-band buffers, see [`Fury::serialize_with_buffers`](crate::fury::Fury::serialize_with_buffers).
+//!
+//! In a message written with buffers, every byte or numeric array starts with a flag, `1` if its
+//! payload follows in band as usual, `0` if it was handed out of band. Readers take the payloads
+//! handed out of band from the buffers given, in the order they were written, like Java's
+//! `BufferObject`s.
+
+/// Flag of an array whose payload follows in the message.
+pub(crate) const IN_BAND_FLAG: u8 = 1;
+/// Flag of an array whose payload is the next out-of-band buffer.
+pub(crate) const OUT_OF_BAND_FLAG: u8 = 0;
+
+/// Payload of an array offered to the callback of
+/// [`Fury::serialize_with_buffers`](crate::fury::Fury::serialize_with_buffers), its elements in
+/// little-endian byte order.
+#[derive(Clone, Copy, Debug)]
+pub struct BufferObject<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> BufferObject<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> BufferObject<'a> {
+        BufferObject { bytes }
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// The payload, borrowed from the value being serialized.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
@@ -0,0 +1,158 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Memoized messages of values serialized again and again unchanged, see
+//! [`Fury::serialization_cache`](crate::fury::Fury::serialization_cache).
+
+use std::any::TypeId;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum CacheKey {
+    // a value identified by its address and a generation its owner bumps on changes
+    Address {
+        type_id: TypeId,
+        address: usize,
+        generation: u64,
+    },
+    // a value identified by a key of the user
+    User {
+        type_id: TypeId,
+        key: u64,
+    },
+}
+
+impl CacheKey {
+    pub(crate) fn of_value<T: 'static>(value: &T, generation: u64) -> CacheKey {
+        CacheKey::Address {
+            type_id: TypeId::of::<T>(),
+            address: value as *const T as usize,
+            generation,
+        }
+    }
+
+    pub(crate) fn of_user<T: 'static>(key: u64) -> CacheKey {
+        CacheKey::User {
+            type_id: TypeId::of::<T>(),
+            key,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Entries {
+    // messages and when they were last used
+    messages: HashMap<CacheKey, (Arc<[u8]>, u64)>,
+    // keys by last use, the least recently used is evicted first; a key used again is pushed
+    // again, the entries with an older use are stale
+    order: VecDeque<(CacheKey, u64)>,
+    uses: u64,
+}
+
+impl Entries {
+    fn touch(&mut self, key: CacheKey) -> Option<Arc<[u8]>> {
+        self.uses += 1;
+        let uses = self.uses;
+        let (message, used) = self.messages.get_mut(&key)?;
+        *used = uses;
+        let message = message.clone();
+        self.order.push_back((key, uses));
+        Some(message)
+    }
+
+    fn is_current(&self, (key, used): &(CacheKey, u64)) -> bool {
+        self.messages.get(key).is_some_and(|(_, last)| last == used)
+    }
+
+    // drops the stale keys of `order` once they outnumber the messages
+    fn compact(&mut self) {
+        if self.order.len() > 2 * self.messages.len() {
+            let order = std::mem::take(&mut self.order);
+            self.order = order
+                .into_iter()
+                .filter(|entry| self.is_current(entry))
+                .collect();
+        }
+    }
+}
+
+/// Messages by [`CacheKey`], at most `capacity` of them.
+pub(crate) struct SerializationCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+impl SerializationCache {
+    pub(crate) fn new(capacity: usize) -> SerializationCache {
+        SerializationCache {
+            capacity,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        // entries are only replaced whole, a panic while holding the lock leaves them consistent
+        self.entries.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// The message cached under `key`, else the one `serialize` writes, which is cached then.
+    pub(crate) fn get_or_insert(
+        &self,
+        key: CacheKey,
+        serialize: impl FnOnce() -> Vec<u8>,
+    ) -> Arc<[u8]> {
+        if let Some(message) = self.lock().touch(key) {
+            return message;
+        }
+        // written without the lock, other threads may serialize the same value meanwhile
+        let message: Arc<[u8]> = serialize().into();
+        if self.capacity == 0 {
+            return message;
+        }
+        let mut entries = self.lock();
+        entries.messages.insert(key, (message.clone(), 0));
+        entries.touch(key);
+        while entries.messages.len() > self.capacity {
+            let Some(oldest) = entries.order.pop_front() else {
+                break;
+            };
+            if entries.is_current(&oldest) {
+                entries.messages.remove(&oldest.0);
+            }
+        }
+        entries.compact();
+        message
+    }
+
+    /// Drops the messages whose key `matches`.
+    pub(crate) fn invalidate(&self, matches: impl Fn(&CacheKey) -> bool) {
+        let mut entries = self.lock();
+        entries.messages.retain(|key, _| !matches(key));
+        entries.compact();
+    }
+
+    pub(crate) fn clear(&self) {
+        let mut entries = self.lock();
+        entries.messages.clear();
+        entries.order.clear();
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.lock().messages.len()
+    }
+}
@@ -0,0 +1,68 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::clock::{Clock, SystemClock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Cooperative cancellation of a deserialization, see [`Fury::deserialize_with_cancel`](crate::fury::Fury::deserialize_with_cancel).
+///
+/// Clones share the flag, so a token can be handed to the worker while another thread keeps
+/// a clone to cancel it. The token is only looked at every [`CANCEL_CHECK_INTERVAL`] elements,
+/// cancelling doesn't interrupt reading a single large string or primitive array.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+/// Number of collection elements read between two looks at the [`CancelToken`].
+pub const CANCEL_CHECK_INTERVAL: u32 = 1024;
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Also cancels once `deadline` has passed, as told by the [`Clock`] of the `Fury`
+    /// reading.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn get_deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.is_cancelled_with(&SystemClock)
+    }
+
+    /// Like [`CancelToken::is_cancelled`], with the deadline checked against `clock`.
+    pub fn is_cancelled_with(&self, clock: &dyn Clock) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| clock.now() >= deadline)
+    }
+}
@@ -20,13 +20,194 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum Error {
-    #[error("Fury on Rust not support Ref type")]
+    #[error("References can only be read into `Rc`, `Arc` or `Weak`")]
     Ref,
 
+    #[error("{remaining} bytes left unread after deserialization")]
+    TrailingBytes { remaining: usize },
+
+    #[error("Value {value} doesn't fit in {target}")]
+    NumericOverflow { value: i128, target: &'static str },
+
+    /// A value written with another type than the one it's read as, by type id: a builtin
+    /// [`FieldType`](crate::types::FieldType) or the id a type is registered with.
+    #[error("Invalid field type, expected:{expected}, actual:{actual}")]
+    TypeMismatch { expected: i16, actual: i16 },
+
+    /// An error raised reading the body of a message, with where: the offset of the byte the
+    /// reader had got to, and the path of the value from the root, e.g.
+    /// `Person.animal[2].category`. [`Error::cause`] is the error itself.
+    #[error("{source} at byte {offset} in `{path}`")]
+    Read {
+        offset: usize,
+        path: String,
+        source: Box<Error>,
+    },
+
+    #[error("Deserialization was cancelled")]
+    Cancelled,
+
+    /// A panic while reading a message, caught as asked by
+    /// `DeserializeOptions::panics(PanicPolicy::Catch)`.
+    #[error("Reading `{type_name}` panicked: {message}")]
+    SerializerPanic {
+        // the root type of the message
+        type_name: &'static str,
+        message: String,
+    },
+
+    #[error(
+        "input does not look like a Fury payload (no magic header); first bytes: {}",
+        describe_bytes(.first_bytes)
+    )]
+    NotFuryPayload {
+        // at most `SNIFFED_BYTES` bytes from the start of the input
+        first_bytes: Vec<u8>,
+    },
+
+    /// A value of a type neither built in nor registered, e.g. one a Python peer could only
+    /// write pickled.
+    #[error("Unknown type id {type_id}")]
+    UnknownTypeId { type_id: i16 },
+
+    /// A field whose type has no serializer in this `Fury`, e.g. a struct deriving `Fury` that
+    /// isn't registered.
+    #[error("Field `{field}` of `{parent_type}` is a `{field_type}`, which isn't registered")]
+    MissingSerializer {
+        parent_type: &'static str,
+        field: &'static str,
+        field_type: &'static str,
+    },
+
+    /// Input beyond one of the limits set on `Fury`, e.g. a corrupt length or nesting deep
+    /// enough to overflow the stack.
+    #[error("{value} exceeds the limit of {max} set by `Fury::{limit}`")]
+    LimitExceeded {
+        // name of the `Fury` option
+        limit: &'static str,
+        value: usize,
+        max: usize,
+    },
+
+    /// Input ending before what is read from it, e.g. a cut off message or a corrupt length.
+    #[error(
+        "Reading {needed} bytes at byte {offset} goes past the end of the input of {len} bytes"
+    )]
+    Truncated {
+        offset: usize,
+        needed: usize,
+        len: usize,
+    },
+
+    #[error("The message needs {required} bytes, the output buffer has only {available}")]
+    BufferTooSmall { required: usize, available: usize },
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
+impl Error {
+    /// The error a read failed with, without the context of [`Error::Read`].
+    pub fn cause(&self) -> &Error {
+        match self {
+            Error::Read { source, .. } => source.cause(),
+            other => other,
+        }
+    }
+
+    /// Offset of the byte the reader had got to when the read failed, see [`Error::Read`].
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Error::Read { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Path of the value whose read failed, see [`Error::Read`].
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Error::Read { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    // prefixes the path of the error with `segment`, a field name or `[index]`, the offset is
+    // that of the innermost value
+    pub(crate) fn locate(self, offset: usize, segment: &str) -> Error {
+        match self {
+            // not about the bytes read, or naming the field already
+            Error::Cancelled
+            | Error::Io(_)
+            | Error::MissingSerializer { .. }
+            | Error::SerializerPanic { .. } => self,
+            Error::Read {
+                offset,
+                path,
+                source,
+            } => {
+                let separator = if path.starts_with('[') { "" } else { "." };
+                Error::Read {
+                    offset,
+                    path: format!("{segment}{separator}{path}"),
+                    source,
+                }
+            }
+            other => Error::Read {
+                offset,
+                path: segment.to_string(),
+                source: Box::new(other),
+            },
+        }
+    }
+}
+
+/// `type_name` without module paths, e.g. `Vec<Person>` rather than
+/// `alloc::vec::Vec<app::Person>`.
+pub(crate) fn short_type_name(type_name: &str) -> String {
+    let mut short = String::with_capacity(type_name.len());
+    let mut segment = String::new();
+    let mut chars = type_name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            segment.clear();
+        } else if c.is_alphanumeric() || c == '_' {
+            segment.push(c);
+        } else {
+            short.push_str(&segment);
+            segment.clear();
+            short.push(c);
+        }
+    }
+    short.push_str(&segment);
+    short
+}
+
+/// Number of leading bytes kept in [`Error::NotFuryPayload`].
+pub const SNIFFED_BYTES: usize = 16;
+
+// hex dump followed by the printable text, which makes JSON or other text formats obvious
+fn describe_bytes(bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+        return String::from("none, the input is empty");
+    }
+    let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    let text: String = bytes
+        .iter()
+        .map(|byte| {
+            if byte.is_ascii_graphic() || *byte == b' ' {
+                *byte as char
+            } else {
+                '.'
+            }
+        })
+        .collect();
+    format!("{} ({text:?})", hex.join(" "))
+}
+
 /// Works like anyhow's [ensure](https://docs.rs/anyhow/latest/anyhow/macro.ensure.html)
 /// But return `Return<T, ErrorFromAnyhow>`
 #[macro_export]
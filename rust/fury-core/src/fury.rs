@@ -16,16 +16,169 @@
 // under the License.
 
 use crate::buffer::{Reader, Writer};
-use crate::error::Error;
-use crate::resolver::class_resolver::{ClassInfo, ClassResolver};
+use crate::buffer_object::BufferObject;
+use crate::cache::{CacheKey, SerializationCache};
+use crate::cancel::CancelToken;
+use crate::clock::{Clock, SystemClock};
+use crate::ensure;
+use crate::error::{Error, SNIFFED_BYTES};
+use crate::manifest::Manifest;
+use crate::profile::SizeProfiler;
+use crate::progress::{ProgressListener, DEFAULT_PROGRESS_INTERVAL};
+use crate::resolver::class_resolver::{named_type_id, ClassInfo, ClassResolver};
 use crate::resolver::context::ReadContext;
-use crate::resolver::context::WriteContext;
-use crate::serializer::{Serializer, StructSerializer};
-use crate::types::{config_flags, Language, Mode, SIZE_OF_REF_AND_TYPE};
+use crate::resolver::context::{WriteContext, DEFAULT_MAX_DEPTH, DEFAULT_MAX_RLE_EXPANSION};
+use crate::resolver::meta_resolver::MetaContext;
+use crate::resolver::ref_resolver::DEFAULT_MAX_REF_COUNT;
+use crate::schema_event::SchemaEventListener;
+use crate::serializer::{
+    BorrowDeserialize, DynSerializer, FuryExternal, RemoteSerializer, Serializer, StructSerializer,
+    TypedDynSerializer, VersionSerializer, Versioned,
+};
+use crate::stats::StatsCollector;
+use crate::thread_safe::MAX_POOLED_BUFFER;
+use crate::type_def::TypeDef;
+use crate::types::{
+    compute_schema_fingerprint, config_flags, DecodeMode, FieldType, Language, Mode,
+    NarrowingPolicy, PanicPolicy, RefFlag, UnknownTypePolicy, MAGIC_NUMBER, SIZE_OF_REF_AND_TYPE,
+};
+use crate::usage::UsageTracker;
+use crate::value::Value;
+use anyhow::anyhow;
+use std::any::TypeId;
+use std::cell::RefCell;
+use std::io::{IoSlice, Read};
+use std::mem::MaybeUninit;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
 
+// the length of a frame, see `Fury::serialize_framed_to_writer`
+const FRAME_HEADER_SIZE: usize = 4;
+
+// flags of every message written here, `IS_EXPERIMENTAL_FLAG` is added by `finish_head`
+const HEAD_BITMAP: u8 = config_flags::IS_LITTLE_ENDIAN_FLAG | config_flags::IS_CROSS_LANGUAGE_FLAG;
+
+/// Options checked by [`Fury::deserialize`] and friends.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DeserializeOptions {
+    strict_trailing: bool,
+    shared_string_buffer: bool,
+    narrowing: NarrowingPolicy,
+    unknown_types: UnknownTypePolicy,
+    panics: PanicPolicy,
+}
+
+impl DeserializeOptions {
+    /// Fails with [`Error::TrailingBytes`] when the input isn't fully consumed, which usually
+    /// hides a framing bug.
+    pub fn strict_trailing(mut self, strict_trailing: bool) -> Self {
+        self.strict_trailing = strict_trailing;
+        self
+    }
+
+    pub fn is_strict_trailing(&self) -> bool {
+        self.strict_trailing
+    }
+
+    /// Copies the input once per message so that all [`FuryStr`](crate::serializer::FuryStr) values read from it share
+    /// that copy, which saves an allocation per string in string-heavy payloads.
+    ///
+    /// Every `FuryStr` keeps the whole copy alive, only use it when they are dropped together.
+    pub fn shared_string_buffer(mut self, shared_string_buffer: bool) -> Self {
+        self.shared_string_buffer = shared_string_buffer;
+        self
+    }
+
+    pub fn is_shared_string_buffer(&self) -> bool {
+        self.shared_string_buffer
+    }
+
+    /// How integers sent with a wider type than the field they're read into are narrowed.
+    pub fn narrowing(mut self, narrowing: NarrowingPolicy) -> Self {
+        self.narrowing = narrowing;
+        self
+    }
+
+    pub fn get_narrowing(&self) -> NarrowingPolicy {
+        self.narrowing
+    }
+
+    /// What `Box<dyn Any>` values do when their type isn't registered here. Fields marked
+    /// `#[fury(unknown_types = "...")]` override it.
+    ///
+    /// Skipping or capturing needs the fields of the value: it must be written with its type
+    /// meta in compatible mode, or with [`Fury::struct_length_guard`] otherwise.
+    pub fn unknown_types(mut self, policy: UnknownTypePolicy) -> Self {
+        self.unknown_types = policy;
+        self
+    }
+
+    pub fn get_unknown_types(&self) -> UnknownTypePolicy {
+        self.unknown_types
+    }
+
+    /// What a panic while reading a message does. [`PanicPolicy::Catch`] turns it into an
+    /// [`Error::SerializerPanic`], the panic hook still reports it.
+    ///
+    /// Values already built for the message are dropped, shared state a serializer left half
+    /// updated isn't repaired. Nothing is caught under `panic = "abort"`. This is for custom
+    /// serializers: the built-in ones fail with an error on corrupt input rather than panicking.
+    pub fn panics(mut self, policy: PanicPolicy) -> Self {
+        self.panics = policy;
+        self
+    }
+
+    pub fn get_panics(&self) -> PanicPolicy {
+        self.panics
+    }
+}
+
+// a deserialized root object and the extent of its message
+struct Root<T> {
+    value: T,
+    consumed: usize,
+    remaining: usize,
+}
+
+thread_local! {
+    // buffer of `Fury::with_scratch_message`, reused by the calls on this thread
+    static SCRATCH: RefCell<Writer> = RefCell::new(Writer::default());
+}
+
+/// Type metas and ref tables live in the per-message contexts and are dropped once
+/// `serialize` or `deserialize` returns, those shared across messages in a [`MetaContext`] of
+/// the connection, which is bounded by [`MetaContext::max_type_defs`]. A `Fury` holds its
+/// registrations and options, along with the bounded [`Fury::serialization_cache`] if enabled
+/// and the scratch buffers of [`Fury::serialize_to_slice`], which [`Fury::trim_caches`]
+/// releases.
 pub struct Fury {
     mode: Mode,
     class_resolver: ClassResolver,
+    max_ref_count: usize,
+    max_depth: usize,
+    max_collection_len: usize,
+    max_buffer_size: usize,
+    deserialize_options: DeserializeOptions,
+    schema_event_listener: Option<Arc<dyn SchemaEventListener>>,
+    aligned_arrays: bool,
+    compact_floats: bool,
+    packed_bools: bool,
+    compress_number: bool,
+    rle_arrays: bool,
+    max_rle_expansion: usize,
+    elide_declared_types: bool,
+    struct_length_guard: bool,
+    compact_elements: bool,
+    per_entry_maps: bool,
+    size_profiler: Option<Arc<SizeProfiler>>,
+    progress_listener: Option<Arc<dyn ProgressListener>>,
+    progress_interval: usize,
+    stats_collector: Option<Arc<dyn StatsCollector>>,
+    usage_tracker: Option<Arc<UsageTracker>>,
+    clock: Arc<dyn Clock>,
+    serialization_cache: Option<SerializationCache>,
+    #[cfg(feature = "testing")]
+    deterministic: bool,
 }
 
 impl Default for Fury {
@@ -33,6 +186,31 @@ impl Default for Fury {
         Fury {
             mode: Mode::SchemaConsistent,
             class_resolver: ClassResolver::default(),
+            max_ref_count: DEFAULT_MAX_REF_COUNT,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_collection_len: usize::MAX,
+            max_buffer_size: usize::MAX,
+            deserialize_options: DeserializeOptions::default(),
+            schema_event_listener: None,
+            aligned_arrays: false,
+            compact_floats: false,
+            packed_bools: false,
+            compress_number: false,
+            rle_arrays: false,
+            max_rle_expansion: DEFAULT_MAX_RLE_EXPANSION,
+            elide_declared_types: false,
+            struct_length_guard: false,
+            compact_elements: false,
+            per_entry_maps: false,
+            size_profiler: None,
+            progress_listener: None,
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+            stats_collector: None,
+            usage_tracker: None,
+            clock: Arc::new(SystemClock),
+            serialization_cache: None,
+            #[cfg(feature = "testing")]
+            deterministic: false,
         }
     }
 }
@@ -47,51 +225,1220 @@ impl Fury {
         &self.mode
     }
 
+    /// Caps the number of referencable objects a single payload may declare.
+    pub fn max_ref_count(mut self, max_ref_count: usize) -> Self {
+        self.max_ref_count = max_ref_count;
+        self
+    }
+
+    pub fn get_max_ref_count(&self) -> usize {
+        self.max_ref_count
+    }
+
+    /// Caps how deeply structs, enums and [`Value`]s may nest in a payload, so that a
+    /// malicious one can't overflow the stack. Reading deeper fails with
+    /// [`Error::LimitExceeded`].
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn get_max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Caps the number of elements or entries of a collection or array a payload may
+    /// declare, so that a corrupt length can't allocate gigabytes. Unlimited by default.
+    pub fn max_collection_len(mut self, max_collection_len: usize) -> Self {
+        self.max_collection_len = max_collection_len;
+        self
+    }
+
+    pub fn get_max_collection_len(&self) -> usize {
+        self.max_collection_len
+    }
+
+    /// Caps the size of the messages read, in bytes, checked before anything is decoded and,
+    /// by [`Fury::deserialize_from_reader`], before the frame is read. Unlimited by default.
+    pub fn max_buffer_size(mut self, max_buffer_size: usize) -> Self {
+        self.max_buffer_size = max_buffer_size;
+        self
+    }
+
+    pub fn get_max_buffer_size(&self) -> usize {
+        self.max_buffer_size
+    }
+
+    // fails if a message of `len` bytes is larger than `max_buffer_size`
+    fn check_buffer_size(&self, len: usize) -> Result<(), Error> {
+        ensure!(
+            len <= self.max_buffer_size,
+            Error::LimitExceeded {
+                limit: "max_buffer_size",
+                value: len,
+                max: self.max_buffer_size,
+            }
+        );
+        Ok(())
+    }
+
+    pub fn deserialize_options(mut self, options: DeserializeOptions) -> Self {
+        self.deserialize_options = options;
+        self
+    }
+
+    pub fn get_deserialize_options(&self) -> &DeserializeOptions {
+        &self.deserialize_options
+    }
+
+    /// Reports fields skipped by the compatible-mode reader because the local type doesn't have
+    /// them, to track schema drift during migrations.
+    pub fn schema_event_listener(mut self, listener: Arc<dyn SchemaEventListener>) -> Self {
+        self.schema_event_listener = Some(listener);
+        self
+    }
+
+    pub fn get_schema_event_listener(&self) -> Option<&dyn SchemaEventListener> {
+        self.schema_event_listener.as_deref()
+    }
+
+    /// Writes derived structs without their type id where the field or element type declares
+    /// them, in schema consistent mode. Both peers must agree on it, and such messages can't be
+    /// read or written as [`Value`].
+    pub fn elide_declared_types(mut self, elide_declared_types: bool) -> Self {
+        self.elide_declared_types = elide_declared_types;
+        self
+    }
+
+    pub fn is_elide_declared_types(&self) -> bool {
+        self.elide_declared_types
+    }
+
+    /// Writes the fields of derived structs behind their length in bytes, in schema consistent
+    /// mode, so that a reader whose struct lost or gained fields fails with an error naming it
+    /// instead of misreading what follows. Both peers must agree on it, and such messages can't
+    /// be read or written as [`Value`].
+    pub fn struct_length_guard(mut self, struct_length_guard: bool) -> Self {
+        self.struct_length_guard = struct_length_guard;
+        self
+    }
+
+    pub fn is_struct_length_guard(&self) -> bool {
+        self.struct_length_guard
+    }
+
+    /// Writes the elements of lists and sets of numbers, strings, booleans and dates without
+    /// their ref flag and type id, in schema consistent mode, saving three bytes per element.
+    /// Such elements are never null nor shared, and the reader knows their type from the schema.
+    /// Both peers must agree on it, messages tell with `IS_COMPACT_ELEMENTS_FLAG`, and they can't
+    /// be read or written as [`Value`].
+    pub fn compact_elements(mut self, compact_elements: bool) -> Self {
+        self.compact_elements = compact_elements;
+        self
+    }
+
+    pub fn is_compact_elements(&self) -> bool {
+        self.compact_elements
+    }
+
+    /// Writes and reads maps entry by entry, each key and value behind its own ref flag, instead
+    /// of in chunks behind a KV header. Java and Python write the maps of their cross-language
+    /// format this way, so both peers must agree on it when talking to them.
+    pub fn per_entry_maps(mut self, per_entry_maps: bool) -> Self {
+        self.per_entry_maps = per_entry_maps;
+        self
+    }
+
+    pub fn is_per_entry_maps(&self) -> bool {
+        self.per_entry_maps
+    }
+
+    /// Records the size of every message written by [`Fury::serialize`] per root type, see
+    /// [`SizeProfiler`].
+    pub fn size_profiler(mut self, profiler: Arc<SizeProfiler>) -> Self {
+        self.size_profiler = Some(profiler);
+        self
+    }
+
+    pub fn get_size_profiler(&self) -> Option<&SizeProfiler> {
+        self.size_profiler.as_deref()
+    }
+
+    /// Reports how many bytes of a message have been read while deserializing it, so that long
+    /// decodes can be observed, see [`ProgressListener`].
+    pub fn progress_listener(mut self, listener: Arc<dyn ProgressListener>) -> Self {
+        self.progress_listener = Some(listener);
+        self
+    }
+
+    pub fn get_progress_listener(&self) -> Option<&dyn ProgressListener> {
+        self.progress_listener.as_deref()
+    }
+
+    /// Reports what each message read held, its number of elements and structs and an estimate
+    /// of the memory its value takes, see [`StatsCollector`].
+    pub fn stats_collector(mut self, collector: Arc<dyn StatsCollector>) -> Self {
+        self.stats_collector = Some(collector);
+        self
+    }
+
+    pub fn get_stats_collector(&self) -> Option<&dyn StatsCollector> {
+        self.stats_collector.as_deref()
+    }
+
+    /// Records which registered types the messages written and read hold, to find those no
+    /// message uses, see [`UsageTracker`].
+    pub fn usage_tracker(mut self, tracker: Arc<UsageTracker>) -> Self {
+        self.usage_tracker = Some(tracker);
+        self
+    }
+
+    pub fn get_usage_tracker(&self) -> Option<&UsageTracker> {
+        self.usage_tracker.as_deref()
+    }
+
+    /// Minimum number of bytes read between two progress reports, e.g. `64 << 10` to report
+    /// every 64 KiB. Defaults to [`DEFAULT_PROGRESS_INTERVAL`].
+    pub fn progress_interval(mut self, bytes: usize) -> Self {
+        self.progress_interval = bytes;
+        self
+    }
+
+    pub fn get_progress_interval(&self) -> usize {
+        self.progress_interval
+    }
+
+    /// Where time-dependent features such as [`CancelToken::deadline`] get the current time
+    /// from, e.g. a [`ManualClock`](crate::clock::ManualClock) in tests. Defaults to the
+    /// [`SystemClock`].
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Keeps up to `capacity` messages of [`Fury::serialize_cached`] and
+    /// [`Fury::serialize_cached_with_key`] for values serialized again and again unchanged,
+    /// e.g. config objects. The least recently used message is dropped first once full.
+    ///
+    /// Messages are self-contained, their type metas included, so a cached one is valid for as
+    /// long as this `Fury` is. Cached messages aren't recorded by the size profiler again.
+    pub fn serialization_cache(mut self, capacity: usize) -> Self {
+        self.serialization_cache = Some(SerializationCache::new(capacity));
+        self
+    }
+
+    /// Number of messages in the serialization cache.
+    pub fn get_cached_count(&self) -> usize {
+        self.serialization_cache
+            .as_ref()
+            .map_or(0, SerializationCache::len)
+    }
+
+    pub fn get_clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Pads multi-byte primitive arrays so their elements start 8-byte aligned within the
+    /// message, letting borrowed reads view them in place. Both peers must agree on it.
+    pub fn aligned_arrays(mut self, aligned_arrays: bool) -> Self {
+        self.aligned_arrays = aligned_arrays;
+        self
+    }
+
+    pub fn is_aligned_arrays(&self) -> bool {
+        self.aligned_arrays
+    }
+
+    /// Writes `f64` values and arrays as `f32` when that loses nothing, behind a flag byte.
+    /// Both peers must agree on it. Experimental, see the `unstable-format` feature.
+    #[cfg(feature = "unstable-format")]
+    pub fn compact_floats(mut self, compact_floats: bool) -> Self {
+        self.compact_floats = compact_floats;
+        self
+    }
+
+    pub fn is_compact_floats(&self) -> bool {
+        self.compact_floats
+    }
+
+    /// Writes `Vec<bool>` and bit sets with one bit per element rather than one byte, under a
+    /// type id of their own which other languages can't read. Readers take either form. Both
+    /// peers must agree on it. Experimental, see the `unstable-format` feature.
+    #[cfg(feature = "unstable-format")]
+    pub fn packed_bools(mut self, packed_bools: bool) -> Self {
+        self.packed_bools = packed_bools;
+        self
+    }
+
+    pub fn is_packed_bools(&self) -> bool {
+        self.packed_bools
+    }
+
+    /// Writes `i64` values as the spec's SLI int64 and `u64` values as varints, like Java's
+    /// `withNumberCompressed(true)`, which suits ids and counters that are mostly small. Arrays
+    /// are written as they are. Both peers must agree on it.
+    pub fn compress_number(mut self, compress_number: bool) -> Self {
+        self.compress_number = compress_number;
+        self
+    }
+
+    pub fn is_compress_number(&self) -> bool {
+        self.compress_number
+    }
+
+    /// Writes integer arrays as runs of equal elements when that is shorter, behind a flag byte.
+    /// Both peers must agree on it. Experimental, see the `unstable-format` feature.
+    #[cfg(feature = "unstable-format")]
+    pub fn rle_arrays(mut self, rle_arrays: bool) -> Self {
+        self.rle_arrays = rle_arrays;
+        self
+    }
+
+    pub fn is_rle_arrays(&self) -> bool {
+        self.rle_arrays
+    }
+
+    /// Caps the size of a run-length array once decoded, as a multiple of the bytes left in
+    /// the message, so that a few bytes of runs can't allocate gigabytes. Reading a larger one
+    /// fails with [`Error::LimitExceeded`]. [`DEFAULT_MAX_RLE_EXPANSION`] by default.
+    #[cfg(feature = "unstable-format")]
+    pub fn max_rle_expansion(mut self, max_rle_expansion: usize) -> Self {
+        self.max_rle_expansion = max_rle_expansion;
+        self
+    }
+
+    pub fn get_max_rle_expansion(&self) -> usize {
+        self.max_rle_expansion
+    }
+
+    /// Writes map and set entries ordered by their encoded keys, for golden-byte tests.
+    #[cfg(feature = "testing")]
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    #[cfg(feature = "testing")]
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
     pub fn write_head<T: Serializer>(&self, writer: &mut Writer) -> usize {
-        const HEAD_SIZE: usize = 10;
-        writer.reserve(<T as Serializer>::reserved_space() + SIZE_OF_REF_AND_TYPE + HEAD_SIZE);
-        let mut bitmap = 0;
-        bitmap |= config_flags::IS_LITTLE_ENDIAN_FLAG;
-        bitmap |= config_flags::IS_CROSS_LANGUAGE_FLAG;
-        writer.u8(bitmap);
+        self.write_head_reserving(writer, <T as Serializer>::reserved_space())
+    }
+
+    fn write_head_reserving(&self, writer: &mut Writer, reserved_space: usize) -> usize {
+        const HEAD_SIZE: usize = 12;
+        writer.reserve(reserved_space + SIZE_OF_REF_AND_TYPE + HEAD_SIZE);
+        writer.u16(MAGIC_NUMBER);
+        writer.u8(HEAD_BITMAP);
         writer.u8(Language::Rust as u8);
         writer.skip(4); // meta offset
         writer.len() - 4
     }
 
-    fn read_head(&self, reader: &mut Reader) -> Result<u32, Error> {
-        let _bitmap = reader.u8();
-        let _language: Language = reader.u8().try_into()?;
-        Ok(reader.u32())
+    // completes the head written by `write_head` once the body is written
+    fn finish_head(context: &mut WriteContext, meta_offset: usize) {
+        // a null root is only the magic number and the bitmap with `IS_NULL_FLAG`, like Java's
+        if context.writer.as_slice()[meta_offset + 4..] == [RefFlag::Null as i8 as u8] {
+            context.writer.truncate(meta_offset - 1);
+            context
+                .writer
+                .set_bytes(meta_offset - 2, &[HEAD_BITMAP | config_flags::IS_NULL_FLAG]);
+            return;
+        }
+        let compatible = Mode::Compatible == *context.get_fury().get_mode();
+        let mut bitmap = HEAD_BITMAP;
+        if context.is_experimental() {
+            bitmap |= config_flags::IS_EXPERIMENTAL_FLAG;
+        }
+        if compatible && context.is_sharing_meta() {
+            bitmap |= config_flags::IS_META_SHARED_FLAG;
+        }
+        if context.has_buffer_callback() {
+            bitmap |= config_flags::IS_OUT_OF_BAND_FLAG;
+        }
+        if context.get_fury().writes_compact_elements() {
+            bitmap |= config_flags::IS_COMPACT_ELEMENTS_FLAG;
+        }
+        if bitmap != HEAD_BITMAP {
+            // the bitmap comes two bytes before the meta offset
+            context.writer.set_bytes(meta_offset - 2, &[bitmap]);
+        }
+        if compatible {
+            context.write_meta(meta_offset);
+        }
+    }
+
+    // the meta offset, `None` for a null root
+    fn read_head(&self, reader: &mut Reader) -> Result<Option<u32>, Error> {
+        // checked before anything else, so that misrouted data fails here rather than
+        // somewhere down in the body
+        if reader.at(0).u16().ok() != Some(MAGIC_NUMBER) {
+            let first_bytes = reader.at(0).bytes(reader.len().min(SNIFFED_BYTES))?;
+            return Err(Error::NotFuryPayload {
+                first_bytes: first_bytes.into_owned(),
+            });
+        }
+        ensure!(
+            reader.len() >= 3,
+            "Fury header is truncated, the input has only {} bytes",
+            reader.len()
+        );
+        reader.u16()?;
+        let bitmap = reader.u8()?;
+        if bitmap & config_flags::IS_NULL_FLAG != 0 {
+            return Ok(None);
+        }
+        ensure!(
+            reader.len() >= 8,
+            "Fury header is truncated, the input has only {} bytes",
+            reader.len()
+        );
+        ensure!(
+            bitmap & config_flags::IS_LITTLE_ENDIAN_FLAG != 0,
+            "The message was written big-endian, only little-endian messages can be read"
+        );
+        ensure!(
+            bitmap & config_flags::IS_CROSS_LANGUAGE_FLAG != 0,
+            "The message is in Java's native format, the peer must write it with `Language.XLANG`"
+        );
+        ensure!(
+            cfg!(feature = "unstable-format") || bitmap & config_flags::IS_EXPERIMENTAL_FLAG == 0,
+            "The message uses experimental encodings, reading it needs the `unstable-format` feature"
+        );
+        let compact_elements = bitmap & config_flags::IS_COMPACT_ELEMENTS_FLAG != 0;
+        ensure!(
+            compact_elements == self.writes_compact_elements(),
+            "The message was written {} compact elements, `Fury::compact_elements` must be the same on both peers",
+            if compact_elements { "with" } else { "without" }
+        );
+        let _language: Language = reader.u8()?.try_into()?;
+        Ok(Some(reader.u32()?))
     }
 
+    /// Reads the message in `bf` as a `T`.
+    ///
+    /// In [`Mode::Compatible`] structs are matched by field name, so `T` may be a view of the
+    /// written type: a derived struct with some of its fields, the others are skipped. Optional
+    /// fields the message lacks are `None`, other missing fields fail.
     pub fn deserialize<T: Serializer>(&self, bf: &[u8]) -> Result<T, Error> {
-        let mut reader = Reader::new(bf);
-        let meta_offset = self.read_head(&mut reader)?;
+        self.deserialize_from(Reader::new(bf))
+    }
+
+    /// Deserializes the message at the start of `bf` and returns it along with the number of
+    /// bytes it spans, for framed protocols. Whatever follows is not looked at, even with
+    /// [`DeserializeOptions::strict_trailing`].
+    pub fn deserialize_partial_from<T: Serializer>(&self, bf: &[u8]) -> Result<(T, usize), Error> {
+        let root = self.read_root(Reader::new(bf), DecodeMode::Owned, None, T::deserialize)?;
+        Ok((root.value, root.consumed))
+    }
+
+    /// Deserializes from a split view such as the two readable halves of a ring buffer,
+    /// without first copying them into one contiguous buffer.
+    pub fn deserialize_from_segments<T: Serializer>(
+        &self,
+        segments: &[IoSlice],
+    ) -> Result<T, Error> {
+        self.deserialize_from(Reader::from_io_slices(segments))
+    }
+
+    /// Deserializes a message delivered as several slices, e.g. a header and a body, without
+    /// first concatenating them. Values straddling two slices are the only ones copied.
+    pub fn deserialize_from_slices<T: Serializer>(&self, segments: &[&[u8]]) -> Result<T, Error> {
+        self.deserialize_from(Reader::from_slices(segments))
+    }
+
+    /// Reads one frame written by [`Fury::serialize_framed_to_writer`] off `source`, e.g. a
+    /// `TcpStream`, and deserializes its message. Only the bytes of that frame are pulled, the
+    /// next frame stays in `source`.
+    ///
+    /// The message is read into memory before it is decoded, as its type metas come after the
+    /// body in compatible mode. A stream closed between two frames fails with an
+    /// [`Error::Io`] of kind `UnexpectedEof`.
+    pub fn deserialize_from_reader<T: Serializer, R: Read>(
+        &self,
+        source: &mut R,
+    ) -> Result<T, Error> {
+        self.deserialize(&self.read_frame(source)?)
+    }
+
+    /// Like [`Fury::deserialize_from_reader`] for the frames of
+    /// [`Fury::serialize_framed_with_meta_context`], whose type defs may have come with the
+    /// earlier frames read with `meta_context`.
+    pub fn deserialize_from_reader_with_meta_context<T: Serializer, R: Read>(
+        &self,
+        source: &mut R,
+        meta_context: &mut MetaContext,
+    ) -> Result<T, Error> {
+        self.deserialize_with_meta_context(&self.read_frame(source)?, meta_context)
+    }
+
+    // reads the message of the next frame off `source`
+    fn read_frame<R: Read>(&self, source: &mut R) -> Result<Vec<u8>, Error> {
+        let mut len = [0; FRAME_HEADER_SIZE];
+        source.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as u64;
+        self.check_buffer_size(len as usize)?;
+        // grown as the bytes arrive, a corrupt length can't allocate much up front
+        let mut message = Vec::new();
+        source.by_ref().take(len).read_to_end(&mut message)?;
+        if (message.len() as u64) < len {
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Stream ended after {} of the {len} bytes of a frame",
+                    message.len()
+                ),
+            )));
+        }
+        Ok(message)
+    }
+
+    /// Deserializes a value which may borrow from `bf`, e.g. a struct holding `Cow<'bf, str>`.
+    ///
+    /// With [`DecodeMode::Borrowed`] strings point into `bf`, with [`DecodeMode::Owned`] they are
+    /// copied out just like [`Fury::deserialize`] does.
+    pub fn deserialize_with_mode<'bf, T: BorrowDeserialize<'bf>>(
+        &self,
+        bf: &'bf [u8],
+        mode: DecodeMode,
+    ) -> Result<T, Error> {
+        let root = self.read_root(Reader::new(bf), mode, None, T::deserialize_borrowed)?;
+        self.check_trailing(root)
+    }
+
+    /// Deserializes a value borrowing its strings and bytes from `bf`, i.e.
+    /// [`Fury::deserialize_with_mode`] with [`DecodeMode::Borrowed`]. Fields declared as `&str`
+    /// or `&[u8]` can only be read this way.
+    pub fn deserialize_borrowed<'bf, T: BorrowDeserialize<'bf>>(
+        &self,
+        bf: &'bf [u8],
+    ) -> Result<T, Error> {
+        self.deserialize_with_mode(bf, DecodeMode::Borrowed)
+    }
+
+    /// Like [`Fury::deserialize`], but gives up with [`Error::Cancelled`] once `cancel` is
+    /// cancelled or its deadline has passed, so that a pathological payload can't hold a
+    /// worker for long.
+    pub fn deserialize_with_cancel<T: Serializer>(
+        &self,
+        bf: &[u8],
+        cancel: &CancelToken,
+    ) -> Result<T, Error> {
+        if cancel.is_cancelled_with(self.get_clock()) {
+            return Err(Error::Cancelled);
+        }
+        let root = self.read_root(
+            Reader::new(bf),
+            DecodeMode::Owned,
+            Some(cancel),
+            T::deserialize,
+        )?;
+        self.check_trailing(root)
+    }
+
+    /// Reads a message without knowing its Rust type, from the type ids in it and the types
+    /// registered here, see [`Value`].
+    ///
+    /// `#[fury(presence_bitmap)]` structs written in schema consistent mode can't be read this way,
+    /// nor can types registered with the id of a built-in type, which fails right away.
+    pub fn deserialize_value(&self, bf: &[u8]) -> Result<Value, Error> {
+        self.check_dynamic_type_ids()?;
+        let root = self.read_root(Reader::new(bf), DecodeMode::Owned, None, |context| {
+            Value::read(context, None)
+        })?;
+        self.check_trailing(root)
+    }
+
+    // whether lists and sets leave out the ref flag and type id of plain elements
+    pub(crate) fn writes_compact_elements(&self) -> bool {
+        self.compact_elements && self.mode == Mode::SchemaConsistent
+    }
+
+    // a `Value` relies on the type id of every value and can't tell registered ids from built-in ones
+    pub(crate) fn check_dynamic_type_ids(&self) -> Result<(), Error> {
+        ensure!(
+            !self.elide_declared_types,
+            "Messages with elided declared types can't be read or written as `Value`"
+        );
+        ensure!(
+            !self.struct_length_guard,
+            "Messages with struct length guards can't be read or written as `Value`"
+        );
+        ensure!(
+            !self.compact_elements,
+            "Messages with compact elements can't be read or written as `Value`"
+        );
+        for (type_id, _) in self.class_resolver.registrations() {
+            if FieldType::try_from(type_id as i16).is_ok() {
+                return Err(anyhow!(
+                    "Type id {type_id} is also the id of a built-in type, types registered with it can't be read or written as `Value`"
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    fn deserialize_from<T: Serializer>(&self, reader: Reader) -> Result<T, Error> {
+        let root = self.read_root(reader, DecodeMode::Owned, None, T::deserialize)?;
+        self.check_trailing(root)
+    }
+
+    fn read_root<'bf, T>(
+        &self,
+        reader: Reader<'bf>,
+        decode_mode: DecodeMode,
+        cancel: Option<&CancelToken>,
+        read: impl FnOnce(&mut ReadContext<'_, 'bf>) -> Result<T, Error>,
+    ) -> Result<Root<T>, Error> {
+        self.read_root_sharing(reader, decode_mode, cancel, None, None, read)
+    }
+
+    fn read_root_sharing<'bf, T>(
+        &self,
+        mut reader: Reader<'bf>,
+        decode_mode: DecodeMode,
+        cancel: Option<&CancelToken>,
+        meta_context: Option<&mut MetaContext>,
+        buffers: Option<&[&'bf [u8]]>,
+        read: impl FnOnce(&mut ReadContext<'_, 'bf>) -> Result<T, Error>,
+    ) -> Result<Root<T>, Error> {
+        let len = reader.len();
+        self.check_buffer_size(len)?;
+        let Some(meta_offset) = self.read_head(&mut reader)? else {
+            // read as a lone null flag, which only types that may be null accept
+            let mut context = ReadContext::new(self, Reader::new(&[RefFlag::Null as i8 as u8]));
+            let value = read(&mut context)?;
+            context.report_stats::<T>(3);
+            return Ok(Root {
+                value,
+                consumed: 3,
+                remaining: len - 3,
+            });
+        };
+        let meta_offset = meta_offset as usize;
+        let bitmap = reader.at(2).u8()?;
+        let shared = bitmap & config_flags::IS_META_SHARED_FLAG != 0;
+        ensure!(
+            !shared || meta_context.is_some(),
+            "The message refers to type metas of earlier messages, it must be read with a `MetaContext`"
+        );
+        let out_of_band = bitmap & config_flags::IS_OUT_OF_BAND_FLAG != 0;
+        ensure!(
+            !out_of_band || buffers.is_some(),
+            "The message has out-of-band buffers, it must be read with `Fury::deserialize_with_buffers`"
+        );
         let mut context = ReadContext::new(self, reader);
-        if meta_offset > 0 {
-            context.load_meta(meta_offset as usize);
+        context.decode_mode = decode_mode;
+        context.cancel = cancel;
+        // only messages written with buffers flag their arrays
+        if let Some(buffers) = buffers.filter(|_| out_of_band) {
+            context.with_buffers(buffers);
+        }
+        if self.deserialize_options.shared_string_buffer {
+            context.shared_buffer = Some(Arc::from(context.reader.to_vec()));
+        }
+        // messages written without a context number their type metas from 0
+        let mut meta_context = meta_context.filter(|_| shared);
+        if let Some(meta_context) = meta_context.as_deref_mut() {
+            meta_context.swap_received(&mut context.meta_resolver);
+        }
+        let meta_end = (meta_offset > 0)
+            .then(|| context.load_meta(meta_offset))
+            .transpose();
+        let (meta_end, value) = match meta_end {
+            Ok(meta_end) => (
+                meta_end,
+                self.catch_panics::<T>(|| read(&mut context))
+                    .map_err(|err| context.root_error::<T>(err)),
+            ),
+            Err(err) => (None, Err(err)),
+        };
+        if let Some(meta_context) = meta_context {
+            meta_context.swap_received(&mut context.meta_resolver);
+        }
+        context.flush_schema_events();
+        context.report_usage();
+        let value = value?;
+        let body_end = context.reader.get_cursor();
+        // metas are written after the body, nothing should be left in between
+        let (consumed, unread) = match meta_end {
+            Some(meta_end) => (meta_end, meta_offset.saturating_sub(body_end)),
+            None => (body_end, 0),
+        };
+        if let Some(listener) = self.get_progress_listener() {
+            listener.on_progress(consumed, len);
+        }
+        context.report_stats::<T>(consumed);
+        Ok(Root {
+            value,
+            consumed,
+            remaining: unread + len.saturating_sub(consumed),
+        })
+    }
+
+    // runs `read`, which reads a `T`, turning its panics into errors if the options say so
+    fn catch_panics<T>(&self, read: impl FnOnce() -> Result<T, Error>) -> Result<T, Error> {
+        if self.deserialize_options.panics == PanicPolicy::Propagate {
+            return read();
         }
-        <T as Serializer>::deserialize(&mut context)
+        std::panic::catch_unwind(AssertUnwindSafe(read)).unwrap_or_else(|payload| {
+            let message = match payload.downcast::<String>() {
+                Ok(message) => *message,
+                Err(payload) => payload.downcast_ref::<&str>().map_or_else(
+                    || String::from("non-string panic payload"),
+                    |s| s.to_string(),
+                ),
+            };
+            Err(Error::SerializerPanic {
+                type_name: std::any::type_name::<T>(),
+                message,
+            })
+        })
+    }
+
+    fn check_trailing<T>(&self, root: Root<T>) -> Result<T, Error> {
+        if self.deserialize_options.strict_trailing && root.remaining > 0 {
+            return Err(Error::TrailingBytes {
+                remaining: root.remaining,
+            });
+        }
+        Ok(root.value)
     }
 
     pub fn serialize<T: Serializer>(&self, record: &T) -> Vec<u8> {
         let mut writer = Writer::default();
-        let meta_offset = self.write_head::<T>(&mut writer);
-        let mut context = WriteContext::new(self, &mut writer);
+        self.write_message(&mut writer, record);
+        writer.dump()
+    }
+
+    /// Like [`Fury::serialize`], but type defs already sent with earlier messages of
+    /// `meta_context` are referred to rather than written again, see [`MetaContext`]. Only
+    /// compatible mode writes type defs, messages of other modes are the same as without.
+    pub fn serialize_with_meta_context<T: Serializer>(
+        &self,
+        record: &T,
+        meta_context: &mut MetaContext,
+    ) -> Vec<u8> {
+        let mut writer = Writer::default();
+        self.write_message_sharing(&mut writer, record, Some(meta_context), None);
+        writer.dump()
+    }
+
+    /// Reads a message of [`Fury::serialize_with_meta_context`], whose type defs may have come
+    /// with the earlier messages read with `meta_context`. Messages written without a context are
+    /// read as by [`Fury::deserialize`].
+    pub fn deserialize_with_meta_context<T: Serializer>(
+        &self,
+        bf: &[u8],
+        meta_context: &mut MetaContext,
+    ) -> Result<T, Error> {
+        let root = self.read_root_sharing(
+            Reader::new(bf),
+            DecodeMode::Owned,
+            None,
+            Some(meta_context),
+            None,
+            T::deserialize,
+        )?;
+        self.check_trailing(root)
+    }
+
+    /// Like [`Fury::serialize`], but the payloads of byte and numeric arrays are offered to
+    /// `callback`, like with Java's `serialize(obj, BufferCallback)`. Those it returns `false`
+    /// for are left out of the message: the callback takes them, e.g. copying them straight to
+    /// shared memory, and the reader gets them back in the same order with
+    /// [`Fury::deserialize_with_buffers`]. Returning `true` keeps a payload in the message.
+    ///
+    /// The message is flagged as having out-of-band buffers even if all of them stayed in band,
+    /// [`Fury::deserialize`] rejects it.
+    pub fn serialize_with_buffers<T: Serializer>(
+        &self,
+        record: &T,
+        callback: &mut dyn FnMut(BufferObject) -> bool,
+    ) -> Vec<u8> {
+        let mut writer = Writer::default();
+        self.write_message_sharing(&mut writer, record, None, Some(callback));
+        writer.dump()
+    }
+
+    /// Reads a message of [`Fury::serialize_with_buffers`], taking the payloads handed out of
+    /// band from `buffers` in the order the callback got them. Messages written without buffers
+    /// are read as by [`Fury::deserialize`].
+    pub fn deserialize_with_buffers<T: Serializer>(
+        &self,
+        bf: &[u8],
+        buffers: &[&[u8]],
+    ) -> Result<T, Error> {
+        let root = self.read_root_sharing(
+            Reader::new(bf),
+            DecodeMode::Owned,
+            None,
+            None,
+            Some(buffers),
+            T::deserialize,
+        )?;
+        self.check_trailing(root)
+    }
+
+    /// Like [`Fury::serialize`], but the message of `record` is cached under its address and
+    /// `generation`, see [`Fury::serialization_cache`]. Values must not change without a new
+    /// `generation`, and an address may be reused once a value is dropped: invalidate its
+    /// messages with [`Fury::invalidate_cached_value`] before.
+    ///
+    /// Serializes every time without a serialization cache.
+    pub fn serialize_cached<T: Serializer + 'static>(
+        &self,
+        record: &T,
+        generation: u64,
+    ) -> Arc<[u8]> {
+        self.cached(CacheKey::of_value(record, generation), record)
+    }
+
+    /// Like [`Fury::serialize_cached`], but the message is cached under `key`, which must
+    /// identify the value among those of type `T` until invalidated with
+    /// [`Fury::invalidate_cached`].
+    pub fn serialize_cached_with_key<T: Serializer + 'static>(
+        &self,
+        record: &T,
+        key: u64,
+    ) -> Arc<[u8]> {
+        self.cached(CacheKey::of_user::<T>(key), record)
+    }
+
+    fn cached<T: Serializer>(&self, key: CacheKey, record: &T) -> Arc<[u8]> {
+        match &self.serialization_cache {
+            Some(cache) => cache.get_or_insert(key, || self.serialize(record)),
+            None => self.serialize(record).into(),
+        }
+    }
+
+    /// Drops the message of the `T` cached under `key` by [`Fury::serialize_cached_with_key`].
+    pub fn invalidate_cached<T: 'static>(&self, key: u64) {
+        if let Some(cache) = &self.serialization_cache {
+            let key = CacheKey::of_user::<T>(key);
+            cache.invalidate(|cached| *cached == key);
+        }
+    }
+
+    /// Drops the messages of `record` cached by [`Fury::serialize_cached`], of every generation.
+    pub fn invalidate_cached_value<T: 'static>(&self, record: &T) {
+        if let Some(cache) = &self.serialization_cache {
+            let address = record as *const T as usize;
+            let type_id = TypeId::of::<T>();
+            cache.invalidate(|cached| {
+                matches!(cached, CacheKey::Address { type_id: t, address: a, .. } if *t == type_id && *a == address)
+            });
+        }
+    }
+
+    /// Drops every cached message.
+    pub fn clear_serialization_cache(&self) {
+        if let Some(cache) = &self.serialization_cache {
+            cache.clear();
+        }
+    }
+
+    /// Releases memory under pressure: drops every cached message and the scratch buffer of the
+    /// calling thread only. Other threads keep theirs until they call this too, each holding at
+    /// most [`MAX_POOLED_BUFFER`] bytes between calls. The type defs built for registered types stay, there is one per type, and
+    /// [`MetaContext`]s belong to their connections, see [`MetaContext::reset`].
+    pub fn trim_caches(&self) {
+        self.clear_serialization_cache();
+        SCRATCH.with(|scratch| {
+            if let Ok(mut writer) = scratch.try_borrow_mut() {
+                *writer = Writer::default();
+            }
+        });
+    }
+
+    /// Appends the message of `record` to `bf`, e.g. to reuse one buffer for many messages or
+    /// to write it after a frame header. Returns the number of bytes appended.
+    pub fn serialize_into<T: Serializer>(&self, record: &T, bf: &mut Vec<u8>) -> usize {
+        let mut writer = Writer::from_vec(std::mem::take(bf));
+        self.write_message(&mut writer, record);
+        let len = writer.len();
+        *bf = writer.into_vec();
+        len
+    }
+
+    /// Writes the message of `record` to `sink`, such as a socket or a file. Returns the number
+    /// of bytes written.
+    ///
+    /// The message is encoded into memory first, its header points past the body to the type
+    /// metas in compatible mode.
+    pub fn serialize_to_writer<T: Serializer, W: std::io::Write>(
+        &self,
+        record: &T,
+        sink: &mut W,
+    ) -> std::io::Result<usize> {
+        let mut writer = Writer::default();
+        self.write_message(&mut writer, record);
+        sink.write_all(writer.as_slice())?;
+        Ok(writer.len())
+    }
+
+    /// Writes the message of `record` to `sink` as a frame, its length as a little endian `u32`
+    /// followed by the message, for [`Fury::deserialize_from_reader`] to read it back from a
+    /// stream. Returns the number of bytes written, including the length.
+    pub fn serialize_framed_to_writer<T: Serializer, W: std::io::Write>(
+        &self,
+        record: &T,
+        sink: &mut W,
+    ) -> std::io::Result<usize> {
+        self.write_frame(record, sink, None)
+    }
+
+    /// Like [`Fury::serialize_framed_to_writer`], but type defs already sent with earlier frames
+    /// of `meta_context` are referred to rather than written again, see [`MetaContext`]. A
+    /// connection carrying a long-lived stream of compatible mode messages thus sends each type
+    /// def once, with the first frame using the type, also when that frame comes late.
+    ///
+    /// The frames must be read with [`Fury::deserialize_from_reader_with_meta_context`], in the
+    /// order they were written.
+    pub fn serialize_framed_with_meta_context<T: Serializer, W: std::io::Write>(
+        &self,
+        record: &T,
+        sink: &mut W,
+        meta_context: &mut MetaContext,
+    ) -> std::io::Result<usize> {
+        self.write_frame(record, sink, Some(meta_context))
+    }
+
+    fn write_frame<T: Serializer, W: std::io::Write>(
+        &self,
+        record: &T,
+        sink: &mut W,
+        meta_context: Option<&mut MetaContext>,
+    ) -> std::io::Result<usize> {
+        // offsets within the message count from its start, after the length
+        let mut writer = Writer::from_vec(vec![0; FRAME_HEADER_SIZE]);
+        self.write_message_sharing(&mut writer, record, meta_context, None);
+        let len = u32::try_from(writer.len()).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Message of {} bytes is too long for a frame", writer.len()),
+            )
+        })?;
+        let mut frame = writer.into_vec();
+        frame[..FRAME_HEADER_SIZE].copy_from_slice(&len.to_le_bytes());
+        sink.write_all(&frame)?;
+        Ok(frame.len())
+    }
+
+    /// Writes the message of `record` to the start of `out`, e.g. a buffer on the stack, and
+    /// returns its length. Fails with [`Error::BufferTooSmall`] carrying the required length if
+    /// it doesn't fit, `out` is left untouched then.
+    ///
+    /// The message is encoded in a buffer kept per thread and reused by later calls, so once that
+    /// has grown to the size of the messages written, none of them allocates on the heap.
+    pub fn serialize_to_slice<T: Serializer>(
+        &self,
+        record: &T,
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        self.with_scratch_message(record, |message| {
+            fits(message, out.len())?;
+            out[..message.len()].copy_from_slice(message);
+            Ok(message.len())
+        })
+    }
+
+    /// Writes the message of `record` to the start of `out` and returns its length, taking
+    /// exactly as many bytes as the message needs, e.g. from the free space of a ring buffer.
+    /// Fails with [`Error::BufferTooSmall`] carrying the required length if it doesn't fit.
+    ///
+    /// The message is encoded once in the buffer kept per thread, then copied. The first call
+    /// on a thread grows that buffer, later ones only allocate for larger messages.
+    pub fn serialize_exact<T: Serializer>(
+        &self,
+        record: &T,
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        self.serialize_to_slice(record, out)
+    }
+
+    /// Length of the message of `record`. The message is encoded in full to measure it, so
+    /// rather than calling this before writing, hand [`Fury::serialize_exact`] the room there is.
+    pub fn serialized_size<T: Serializer>(&self, record: &T) -> usize {
+        self.with_scratch_message(record, <[u8]>::len)
+    }
+
+    /// Like [`Fury::serialize_to_slice`], for memory that hasn't been initialized such as buffers
+    /// handed out by io_uring or DPDK. The first bytes of `out`, as many as returned, are
+    /// initialized then and the rest is left alone, nothing is zeroed.
+    pub fn serialize_into_uninit<T: Serializer>(
+        &self,
+        record: &T,
+        out: &mut [MaybeUninit<u8>],
+    ) -> Result<usize, Error> {
+        self.with_scratch_message(record, |message| {
+            fits(message, out.len())?;
+            for (dst, src) in out.iter_mut().zip(message) {
+                dst.write(*src);
+            }
+            Ok(message.len())
+        })
+    }
+
+    // hands the message of `record` to `f`, encoded in a buffer reused by the calls on this thread
+    fn with_scratch_message<T: Serializer, R>(&self, record: &T, f: impl FnOnce(&[u8]) -> R) -> R {
+        SCRATCH.with(|scratch| match scratch.try_borrow_mut() {
+            Ok(mut writer) => {
+                self.write_message(&mut writer, record);
+                let result = f(writer.as_slice());
+                writer.clear();
+                if writer.capacity() > MAX_POOLED_BUFFER {
+                    *writer = Writer::default();
+                }
+                result
+            }
+            // called again while the scratch buffer is in use, from within a serializer
+            Err(_) => {
+                let mut writer = Writer::default();
+                self.write_message(&mut writer, record);
+                f(writer.as_slice())
+            }
+        })
+    }
+
+    // writes the message of `record` to the empty `writer`
+    pub(crate) fn write_message<T: Serializer>(&self, writer: &mut Writer, record: &T) {
+        self.write_message_sharing(writer, record, None, None)
+    }
+
+    fn write_message_sharing<T: Serializer>(
+        &self,
+        writer: &mut Writer,
+        record: &T,
+        mut meta_context: Option<&mut MetaContext>,
+        buffer_callback: Option<&mut dyn FnMut(BufferObject) -> bool>,
+    ) {
+        let meta_offset = self.write_head::<T>(writer);
+        let mut context = WriteContext::new(self, writer);
+        if let Some(meta_context) = meta_context.as_deref_mut() {
+            context.share_meta(meta_context);
+        }
+        if let Some(callback) = buffer_callback {
+            context.with_buffer_callback(callback);
+        }
         <T as Serializer>::serialize(record, &mut context);
-        if Mode::Compatible == self.mode {
-            context.write_meta(meta_offset);
+        Self::finish_head(&mut context, meta_offset);
+        if let Some(meta_context) = meta_context {
+            context.finish_meta_sharing(meta_context);
         }
-        writer.dump()
+        context.report_usage();
+        if let Some(profiler) = &self.size_profiler {
+            profiler.record(std::any::type_name::<T>(), writer.len());
+        }
+    }
+
+    /// Writes a [`Value`] as a message, which fails if it refers to types not registered here.
+    pub fn serialize_value(&self, value: &Value) -> Result<Vec<u8>, Error> {
+        self.check_dynamic_type_ids()?;
+        let mut writer = Writer::default();
+        let meta_offset = self.write_head_reserving(&mut writer, 0);
+        let mut context = WriteContext::new(self, &mut writer);
+        value.write(&mut context)?;
+        Self::finish_head(&mut context, meta_offset);
+        Ok(writer.dump())
+    }
+
+    /// Hash of the definitions of all registered types, including the variant to tag mapping of
+    /// enums. Peers with different fingerprints may not understand each other's data.
+    pub fn schema_fingerprint(&self) -> u32 {
+        compute_schema_fingerprint(self.class_resolver.type_defs(self).into_iter())
+    }
+
+    /// Definition of the registered type `T`, e.g. to push it into a schema registry, see
+    /// [`TypeDef`]. Fails if `T` isn't registered.
+    pub fn type_def<T: StructSerializer>(&self) -> Result<TypeDef, Error> {
+        let type_id = TypeId::of::<T>();
+        ensure!(
+            self.class_resolver.get_registered_id(type_id).is_some(),
+            "`{}` isn't registered with this Fury",
+            std::any::type_name::<T>()
+        );
+        let mut type_def =
+            TypeDef::from_type_meta(self.class_resolver.get_type_def(type_id, self))?;
+        for (field, field_type) in type_def.fields.iter_mut().zip(T::field_types(self)) {
+            field.field_type = field_type;
+        }
+        type_def.version = self
+            .class_resolver
+            .get_version_table(type_id)
+            .and_then(|versions| versions.versions().last());
+        Ok(type_def)
     }
 
     pub fn get_class_resolver(&self) -> &ClassResolver {
         &self.class_resolver
     }
 
+    /// Lists the mode and the registered types, for [`Fury::from_manifest`] on the peer.
+    pub fn export_manifest(&self) -> Vec<u8> {
+        Manifest::of(self).to_bytes()
+    }
+
+    /// A `Fury` in the mode of an exported manifest with the same types registered under the
+    /// same ids, through the hooks added with [`register_hook`](crate::manifest::register_hook).
+    ///
+    /// Fails if a type of the manifest has no hook, or if the registered types differ from those
+    /// of the exporting instance.
+    pub fn from_manifest(bytes: &[u8]) -> Result<Fury, Error> {
+        Manifest::from_bytes(bytes)?.to_fury()
+    }
+
+    /// Makes room for `additional` more registered types, e.g. before registering many at once.
+    pub fn reserve_types(&mut self, additional: usize) {
+        self.class_resolver.reserve(additional);
+    }
+
     pub fn register<T: 'static + StructSerializer>(&mut self, id: u32) {
-        let class_info = ClassInfo::new::<T>(self, id);
-        self.class_resolver.register::<T>(class_info, id);
+        self.class_resolver.register::<T>(ClassInfo::new(id), id);
+    }
+
+    /// Registers `T` by the name `name` in `namespace` instead of a numeric id, like types
+    /// registered by class name on the Java side.
+    ///
+    /// The type meta written in compatible mode carries the namespace and the name, which
+    /// readers look the type up by, so do values held by a `Box<dyn Any>` in schema-consistent
+    /// mode. Its id is a hash of both, see [`named_type_id`], which falls
+    /// in `0x4000..0x8000`: numeric ids should stay out of that range.
+    ///
+    /// Fails if the name is already registered, or if its id is taken by another type.
+    pub fn register_by_name<T: 'static + StructSerializer>(
+        &mut self,
+        namespace: &str,
+        name: &str,
+    ) -> Result<(), Error> {
+        let id = named_type_id(namespace, name);
+        if let Some(harness) = self.class_resolver.get_harness(id) {
+            return Err(anyhow!(
+                "Id {id} of the type name `{namespace}.{name}` is already taken by `{}`",
+                harness.get_type_name()
+            )
+            .into());
+        }
+        self.register::<T>(id);
+        self.name_type(id, namespace, name)
+    }
+
+    pub(crate) fn name_type(&mut self, id: u32, namespace: &str, name: &str) -> Result<(), Error> {
+        self.class_resolver.name_type(id, namespace, name)
+    }
+
+    /// Reads values as the registered `T` where `name` identifies a type, e.g. in
+    /// [`ClassResolver::get_type_id_by_name`] or [`Fury::register_dyn`], for types renamed
+    /// during a migration.
+    pub fn alias<T: StructSerializer>(&mut self, name: &str) -> Result<(), Error> {
+        let id = self.registered_id::<T>()?;
+        self.class_resolver.alias_name(name, id)
+    }
+
+    /// Reads values written with the type id `legacy_id` as the registered `T`, for payloads
+    /// stored before `T` got its current id. `T` is always written with its current id.
+    pub fn alias_id<T: StructSerializer>(&mut self, legacy_id: u32) -> Result<(), Error> {
+        let id = self.registered_id::<T>()?;
+        self.class_resolver.alias_id(legacy_id, id)
+    }
+
+    /// Only reads values of registered types and builtins, for untrusted input: a `Box<dyn Any>`
+    /// or trait object holding any other type fails the read, whatever the
+    /// [`UnknownTypePolicy`] or `#[fury(unknown_types = "...")]` of its field.
+    pub fn require_registration(mut self, require_registration: bool) -> Self {
+        self.class_resolver
+            .require_registration(require_registration);
+        self
+    }
+
+    pub fn is_require_registration(&self) -> bool {
+        self.class_resolver.is_require_registration()
+    }
+
+    /// Skips values of the type id `id` on read, e.g. retired message types that are no longer
+    /// modeled. They are read as `None` by an `Option` and as [`Value::Ignored`] by
+    /// [`Fury::deserialize_value`], which takes precedence over a type registered with `id`.
+    ///
+    /// Skipping needs the fields of the value: it must be written with its type meta in
+    /// compatible mode, or with [`Fury::struct_length_guard`] otherwise.
+    pub fn ignore_type_id(&mut self, id: u32) {
+        self.class_resolver.ignore_id(id);
+    }
+
+    fn registered_id<T: StructSerializer>(&self) -> Result<u32, Error> {
+        self.class_resolver
+            .get_registered_id(TypeId::of::<T>())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Type `{}` must be registered before aliasing it",
+                    std::any::type_name::<T>()
+                )
+                .into()
+            })
+    }
+
+    /// Registers a type through an object-safe serializer, e.g. one exported by a plugin.
+    ///
+    /// Fails if the type name or its `TypeId` is already registered with another id.
+    pub fn register_dyn(
+        &mut self,
+        id: u32,
+        serializer: Box<dyn DynSerializer>,
+    ) -> Result<(), Error> {
+        self.class_resolver
+            .register_dyn(ClassInfo::new(id), id, serializer)
+    }
+
+    /// Registers the serializer of `version` of `T`, whose encodings differ too much between
+    /// versions for compatible mode to bridge them. Values are written and read as
+    /// [`Versioned<T>`]: the newest registered version writes, each value is read by the
+    /// serializer of the version it was written with.
+    ///
+    /// Fails if `T` is already registered with another id or `version` is already registered.
+    pub fn register_versioned<T: 'static>(
+        &mut self,
+        id: u32,
+        version: u32,
+        serializer: impl VersionSerializer<T> + 'static,
+    ) -> Result<(), Error> {
+        match self
+            .class_resolver
+            .get_registered_id(TypeId::of::<Versioned<T>>())
+        {
+            Some(registered_id) => ensure!(
+                registered_id == id,
+                "`{}` is already registered with id {}, can't register a version of it with id {}",
+                std::any::type_name::<T>(),
+                registered_id,
+                id
+            ),
+            None => self.register::<Versioned<T>>(id),
+        }
+        self.class_resolver
+            .add_version::<T>(version, Box::new(serializer))
+    }
+
+    /// Registers a type from another crate through its `#[fury(remote = "...")]` mirror `R`.
+    ///
+    /// Values are written and read as [`FuryExternal<R>`], the foreign type's name is used as
+    /// its identity, so registering it twice under different ids fails.
+    pub fn register_external<R: RemoteSerializer>(&mut self, id: u32) -> Result<(), Error> {
+        let serializer =
+            TypedDynSerializer::<FuryExternal<R>>::new(std::any::type_name::<R::Target>());
+        self.register_dyn(id, Box::new(serializer))
+    }
+}
+
+// whether `message` fits in an output buffer of `available` bytes
+fn fits(message: &[u8], available: usize) -> Result<(), Error> {
+    if message.len() > available {
+        return Err(Error::BufferTooSmall {
+            required: message.len(),
+            available,
+        });
     }
+    Ok(())
 }
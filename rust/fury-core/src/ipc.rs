@@ -0,0 +1,277 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Passing messages between two processes of one host through shared memory.
+//!
+//! The memory is mapped by the application, e.g. with `shm_open` and `mmap`, fury only lays
+//! out a single producer single consumer ring in it:
+//!
+//! ```text
+//! | header (64 bytes) | ring of frames: u32 length + message |
+//! ```
+//!
+//! [`ShmWriter`] copies each message into the ring once, [`ShmReader`] deserializes it where it
+//! lies, also when it wraps around the end of the ring.
+//!
+//! The header doubles as a handshake: [`ShmReader::attach`] refuses a ring written with another
+//! mode, or in schema consistent mode by a peer with another [`Fury::schema_fingerprint`].
+
+use crate::ensure;
+use crate::error::Error;
+use crate::fury::Fury;
+use crate::serializer::Serializer;
+use crate::types::Mode;
+use anyhow::anyhow;
+use std::io::IoSlice;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+/// Bytes reserved at the start of the region, the ring follows them.
+pub const SHM_HEADER_SIZE: usize = 64;
+
+const MAGIC: u32 = u32::from_le_bytes(*b"FURY");
+const VERSION: u32 = 1;
+const FRAME_HEADER_SIZE: usize = 4;
+
+#[repr(C)]
+struct Header {
+    magic: AtomicU32,
+    version: AtomicU32,
+    mode: AtomicU32,
+    fingerprint: AtomicU32,
+    capacity: AtomicU64,
+    // total bytes ever written and read, the ring offset is taken modulo the capacity
+    write_pos: AtomicU64,
+    read_pos: AtomicU64,
+}
+
+fn mode_tag(mode: &Mode) -> u32 {
+    match mode {
+        Mode::SchemaConsistent => 0,
+        Mode::Compatible => 1,
+    }
+}
+
+struct Ring {
+    header: NonNull<Header>,
+    data: NonNull<u8>,
+    capacity: usize,
+}
+
+impl Ring {
+    unsafe fn new(region: *mut u8, len: usize) -> Result<Ring, Error> {
+        ensure!(
+            !region.is_null() && region as usize % std::mem::align_of::<Header>() == 0,
+            "Shared memory region must be {}-byte aligned",
+            std::mem::align_of::<Header>()
+        );
+        ensure!(
+            len > SHM_HEADER_SIZE + FRAME_HEADER_SIZE,
+            "Shared memory region of {len} bytes is too small"
+        );
+        Ok(Ring {
+            header: NonNull::new_unchecked(region.cast::<Header>()),
+            data: NonNull::new_unchecked(region.add(SHM_HEADER_SIZE)),
+            capacity: len - SHM_HEADER_SIZE,
+        })
+    }
+
+    fn header(&self) -> &Header {
+        unsafe { self.header.as_ref() }
+    }
+
+    /// The bytes at ring positions `pos..pos + len`, split in two where they wrap around.
+    ///
+    /// Only bytes the other side is done with may be asked for.
+    fn segments(&self, pos: u64, len: usize) -> (&[u8], &[u8]) {
+        let offset = (pos % self.capacity as u64) as usize;
+        let first = len.min(self.capacity - offset);
+        unsafe {
+            (
+                std::slice::from_raw_parts(self.data.as_ptr().add(offset), first),
+                std::slice::from_raw_parts(self.data.as_ptr(), len - first),
+            )
+        }
+    }
+
+    fn copy_in(&mut self, pos: u64, bytes: &[u8]) {
+        let offset = (pos % self.capacity as u64) as usize;
+        let first = bytes.len().min(self.capacity - offset);
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), self.data.as_ptr().add(offset), first);
+            std::ptr::copy_nonoverlapping(
+                bytes[first..].as_ptr(),
+                self.data.as_ptr(),
+                bytes.len() - first,
+            );
+        }
+    }
+}
+
+/// The sending side of a shared memory ring.
+pub struct ShmWriter {
+    ring: Ring,
+}
+
+// the writer only touches the region through atomics and the bytes the reader is done with
+unsafe impl Send for ShmWriter {}
+
+impl ShmWriter {
+    /// Lays out an empty ring in `len` bytes at `region` and publishes the handshake of `fury`.
+    ///
+    /// # Safety
+    ///
+    /// `region` must be valid for reads and writes of `len` bytes, 8-byte aligned, for as long as
+    /// the writer lives. Besides it, only a single [`ShmReader`] may access the region, and
+    /// only once `create` returned.
+    pub unsafe fn create(region: *mut u8, len: usize, fury: &Fury) -> Result<ShmWriter, Error> {
+        let ring = Ring::new(region, len)?;
+        let header = ring.header();
+        header.magic.store(0, Ordering::Relaxed);
+        header.version.store(VERSION, Ordering::Relaxed);
+        header
+            .mode
+            .store(mode_tag(fury.get_mode()), Ordering::Relaxed);
+        header
+            .fingerprint
+            .store(fury.schema_fingerprint(), Ordering::Relaxed);
+        header
+            .capacity
+            .store(ring.capacity as u64, Ordering::Relaxed);
+        header.write_pos.store(0, Ordering::Relaxed);
+        header.read_pos.store(0, Ordering::Relaxed);
+        header.magic.store(MAGIC, Ordering::Release);
+        Ok(ShmWriter { ring })
+    }
+
+    /// Serializes `value` into the ring. Returns `false` without writing anything when the
+    /// reader hasn't yet made room for it.
+    pub fn send<T: Serializer>(&mut self, fury: &Fury, value: &T) -> Result<bool, Error> {
+        self.send_bytes(&fury.serialize(value))
+    }
+
+    /// Like [`ShmWriter::send`] for a message serialized beforehand.
+    pub fn send_bytes(&mut self, message: &[u8]) -> Result<bool, Error> {
+        let frame_len = FRAME_HEADER_SIZE + message.len();
+        ensure!(
+            frame_len <= self.ring.capacity && message.len() <= u32::MAX as usize,
+            "Message of {} bytes doesn't fit in a ring of {} bytes",
+            message.len(),
+            self.ring.capacity
+        );
+        if frame_len > self.free_space() {
+            return Ok(false);
+        }
+        let write_pos = self.ring.header().write_pos.load(Ordering::Relaxed);
+        self.ring
+            .copy_in(write_pos, &(message.len() as u32).to_le_bytes());
+        self.ring
+            .copy_in(write_pos + FRAME_HEADER_SIZE as u64, message);
+        self.ring
+            .header()
+            .write_pos
+            .store(write_pos + frame_len as u64, Ordering::Release);
+        Ok(true)
+    }
+
+    /// Bytes available for frames, each message takes 4 more than its own length.
+    pub fn free_space(&self) -> usize {
+        let header = self.ring.header();
+        let used =
+            header.write_pos.load(Ordering::Relaxed) - header.read_pos.load(Ordering::Acquire);
+        self.ring.capacity - used as usize
+    }
+}
+
+/// The receiving side of a shared memory ring.
+pub struct ShmReader {
+    ring: Ring,
+}
+
+// see `ShmWriter`
+unsafe impl Send for ShmReader {}
+
+impl ShmReader {
+    /// Attaches to a ring laid out by [`ShmWriter::create`], checking that both peers can
+    /// understand each other.
+    ///
+    /// # Safety
+    ///
+    /// `region` must be valid for reads and writes of `len` bytes, 8-byte aligned, for as long as
+    /// the reader lives. Besides it, only the [`ShmWriter`] which created the ring may access it.
+    pub unsafe fn attach(region: *mut u8, len: usize, fury: &Fury) -> Result<ShmReader, Error> {
+        let ring = Ring::new(region, len)?;
+        let header = ring.header();
+        ensure!(
+            header.magic.load(Ordering::Acquire) == MAGIC,
+            "Shared memory region doesn't hold a fury ring yet"
+        );
+        let version = header.version.load(Ordering::Relaxed);
+        ensure!(
+            version == VERSION,
+            "Unsupported shared memory ring version {version}"
+        );
+        let capacity = header.capacity.load(Ordering::Relaxed);
+        ensure!(
+            capacity == ring.capacity as u64,
+            "Shared memory ring was created with {} bytes, but {} are mapped",
+            capacity + SHM_HEADER_SIZE as u64,
+            len
+        );
+        ensure!(
+            header.mode.load(Ordering::Relaxed) == mode_tag(fury.get_mode()),
+            "Shared memory peer uses another fury mode"
+        );
+        let fingerprint = header.fingerprint.load(Ordering::Relaxed);
+        if *fury.get_mode() == Mode::SchemaConsistent && fingerprint != fury.schema_fingerprint() {
+            return Err(anyhow!(
+                "Shared memory peer has schema fingerprint {fingerprint:#010x}, expected {:#010x}",
+                fury.schema_fingerprint()
+            )
+            .into());
+        }
+        Ok(ShmReader { ring })
+    }
+
+    /// Deserializes the next message in place, or returns `None` when the ring is empty.
+    ///
+    /// A message which fails to deserialize is still consumed, the error doesn't wedge the ring.
+    pub fn recv<T: Serializer>(&mut self, fury: &Fury) -> Result<Option<T>, Error> {
+        self.recv_with(|segments| fury.deserialize_from_segments(segments))
+            .transpose()
+    }
+
+    /// Hands the next message to `read` as one or two segments, then releases it to the writer.
+    pub fn recv_with<R>(&mut self, read: impl FnOnce(&[IoSlice]) -> R) -> Option<R> {
+        let header = self.ring.header();
+        let read_pos = header.read_pos.load(Ordering::Relaxed);
+        if header.write_pos.load(Ordering::Acquire) == read_pos {
+            return None;
+        }
+        let (head, tail) = self.ring.segments(read_pos, FRAME_HEADER_SIZE);
+        let mut len = [0u8; FRAME_HEADER_SIZE];
+        len[..head.len()].copy_from_slice(head);
+        len[head.len()..].copy_from_slice(tail);
+        let len = u32::from_le_bytes(len) as usize;
+        let (head, tail) = self.ring.segments(read_pos + FRAME_HEADER_SIZE as u64, len);
+        let result = read(&[IoSlice::new(head), IoSlice::new(tail)]);
+        header.read_pos.store(
+            read_pos + (FRAME_HEADER_SIZE + len) as u64,
+            Ordering::Release,
+        );
+        Some(result)
+    }
+}
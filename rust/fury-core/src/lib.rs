@@ -16,11 +16,31 @@
 // under the License.
 
 pub mod buffer;
+pub mod buffer_object;
+mod cache;
+pub mod cancel;
+pub mod clock;
 pub mod error;
 pub mod fury;
+#[cfg(feature = "ipc")]
+pub mod ipc;
+pub mod manifest;
 pub mod meta;
+pub mod profile;
+pub mod progress;
 pub mod resolver;
 pub mod row;
+pub mod schema_event;
+#[cfg(feature = "serde")]
+mod serde_bridge;
 pub mod serializer;
+pub mod stats;
+pub mod thread_safe;
+pub mod type_def;
 pub mod types;
+pub mod usage;
 pub mod util;
+pub mod value;
+
+#[cfg(feature = "serde")]
+pub use serde_bridge::{from_serde, to_serde, Serde};
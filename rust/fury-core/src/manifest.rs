@@ -0,0 +1,208 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Registration manifests: [`Fury::export_manifest`] lists the mode and the registered types of
+//! an instance, [`Fury::from_manifest`] builds one registering the same types with the same ids.
+//!
+//! Rust can't find a type by its name, so each type of a manifest is registered by the hook
+//! added for it with [`register_hook`], usually at startup before loading the manifest.
+//!
+//! A manifest is text, one entry per line:
+//!
+//! ```text
+//! fury manifest 1
+//! mode compatible
+//! fingerprint 2895128512
+//! type 100 my_app::Order
+//! type 101 my_app::Item
+//! name 27607 com.example Person
+//! ```
+//!
+//! `name` lines give the namespace and the name of types registered with
+//! [`Fury::register_by_name`], whose `type` lines list them under their id.
+//!
+//! The fingerprint is [`Fury::schema_fingerprint`] of the exporting instance. Aliases and
+//! ignored type ids aren't part of a manifest.
+
+use crate::ensure;
+use crate::error::Error;
+use crate::fury::Fury;
+use crate::serializer::StructSerializer;
+use crate::types::Mode;
+use anyhow::anyhow;
+use std::sync::Mutex;
+
+const HEADER: &str = "fury manifest 1";
+
+/// Registers the type of a hook with the given fury type id.
+pub type RegisterHook = fn(&mut Fury, u32) -> Result<(), Error>;
+
+// type name -> hook registering the type, shared by every `Fury` of the process
+static HOOKS: Mutex<Vec<(String, RegisterHook)>> = Mutex::new(Vec::new());
+
+/// Lets manifests register `T` with [`Fury::register`], `T` is listed by its
+/// `std::any::type_name`.
+pub fn register_hook<T: 'static + StructSerializer>() {
+    register_hook_with(std::any::type_name::<T>(), |fury, id| {
+        fury.register::<T>(id);
+        Ok(())
+    });
+}
+
+/// Lets manifests register the type listed as `type_name` with `hook`, for types registered
+/// otherwise, e.g. with [`Fury::register_dyn`]. Replaces an earlier hook of `type_name`.
+pub fn register_hook_with(type_name: &str, hook: RegisterHook) {
+    let mut hooks = HOOKS.lock().unwrap_or_else(|err| err.into_inner());
+    match hooks.iter_mut().find(|(name, _)| name == type_name) {
+        Some(entry) => entry.1 = hook,
+        None => hooks.push((type_name.to_string(), hook)),
+    }
+}
+
+fn find_hook(type_name: &str) -> Option<RegisterHook> {
+    let hooks = HOOKS.lock().unwrap_or_else(|err| err.into_inner());
+    hooks
+        .iter()
+        .find(|(name, _)| name == type_name)
+        .map(|(_, hook)| *hook)
+}
+
+/// Parsed form of a manifest.
+pub struct Manifest {
+    pub mode: Mode,
+    pub fingerprint: u32,
+    /// (fury type id, type name), ordered by id.
+    pub types: Vec<(u32, String)>,
+    /// (fury type id, namespace, name) of the types registered by name, ordered by id.
+    pub names: Vec<(u32, String, String)>,
+}
+
+impl Manifest {
+    pub fn of(fury: &Fury) -> Manifest {
+        let registrations = fury.get_class_resolver().registrations();
+        let names = registrations
+            .iter()
+            .filter_map(|(id, _)| {
+                let (namespace, name) = fury.get_class_resolver().get_type_name(*id)?;
+                Some((*id, namespace.to_string(), name.to_string()))
+            })
+            .collect();
+        Manifest {
+            mode: *fury.get_mode(),
+            fingerprint: fury.schema_fingerprint(),
+            types: registrations
+                .into_iter()
+                .map(|(id, name)| (id, name.to_string()))
+                .collect(),
+            names,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mode = match self.mode {
+            Mode::SchemaConsistent => "schema_consistent",
+            Mode::Compatible => "compatible",
+        };
+        let mut text = format!("{HEADER}\nmode {mode}\nfingerprint {}\n", self.fingerprint);
+        for (id, name) in &self.types {
+            text.push_str(&format!("type {id} {name}\n"));
+        }
+        for (id, namespace, name) in &self.names {
+            text.push_str(&format!("name {id} {namespace} {name}\n"));
+        }
+        text.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Manifest, Error> {
+        let text =
+            std::str::from_utf8(bytes).map_err(|err| anyhow!("Manifest isn't UTF-8: {err}"))?;
+        let mut lines = text.lines();
+        ensure!(
+            lines.next() == Some(HEADER),
+            "Manifest doesn't start with `{}`",
+            HEADER
+        );
+        let (mut mode, mut fingerprint, mut types, mut names) =
+            (None, None, Vec::new(), Vec::new());
+        for line in lines.filter(|line| !line.is_empty()) {
+            let invalid = || anyhow!("Invalid manifest line `{line}`");
+            let (key, value) = line.split_once(' ').ok_or_else(invalid)?;
+            match key {
+                "mode" => {
+                    mode = Some(match value {
+                        "schema_consistent" => Mode::SchemaConsistent,
+                        "compatible" => Mode::Compatible,
+                        _ => return Err(invalid().into()),
+                    })
+                }
+                "fingerprint" => fingerprint = Some(value.parse().map_err(|_| invalid())?),
+                "type" => {
+                    let (id, name) = value.split_once(' ').ok_or_else(invalid)?;
+                    types.push((id.parse().map_err(|_| invalid())?, name.to_string()));
+                }
+                "name" => {
+                    let mut parts = value.split(' ');
+                    let (Some(id), Some(namespace), Some(name), None) =
+                        (parts.next(), parts.next(), parts.next(), parts.next())
+                    else {
+                        return Err(invalid().into());
+                    };
+                    let id = id.parse().map_err(|_| invalid())?;
+                    names.push((id, namespace.to_string(), name.to_string()));
+                }
+                _ => return Err(invalid().into()),
+            }
+        }
+        Ok(Manifest {
+            mode: mode.ok_or_else(|| anyhow!("Manifest has no mode"))?,
+            fingerprint: fingerprint.ok_or_else(|| anyhow!("Manifest has no fingerprint"))?,
+            types,
+            names,
+        })
+    }
+
+    /// A `Fury` in the mode of the manifest with its types registered through their hooks.
+    ///
+    /// Fails if a type has no hook, or if the registered types differ from those of the
+    /// exporting instance, as told by the fingerprint.
+    pub fn to_fury(&self) -> Result<Fury, Error> {
+        let mut fury = Fury::default().mode(self.mode);
+        fury.reserve_types(self.types.len());
+        for (id, name) in &self.types {
+            let hook = find_hook(name).ok_or_else(|| {
+                anyhow!("Type `{name}` of the manifest has no registration hook, see `fury_core::manifest::register_hook`")
+            })?;
+            hook(&mut fury, *id)?;
+            ensure!(
+                fury.get_class_resolver().get_type_id_by_name(name) == Some(*id),
+                "Registration hook of `{}` didn't register it with id {}",
+                name,
+                id
+            );
+        }
+        for (id, namespace, name) in &self.names {
+            fury.name_type(*id, namespace, name)?;
+        }
+        ensure!(
+            fury.schema_fingerprint() == self.fingerprint,
+            "Registered types don't match the manifest, fingerprint {} instead of {}",
+            fury.schema_fingerprint(),
+            self.fingerprint
+        );
+        Ok(fury)
+    }
+}
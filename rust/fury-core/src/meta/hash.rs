@@ -0,0 +1,90 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Schema hashes computed bit for bit like Java's, which writes one before the fields of a struct
+//! in schema consistent mode to tell peers with another layout apart.
+
+use crate::types::FieldType;
+
+// every step of the hash is divided by 7 until it is below this, as Java keeps it in an `int`
+const MAX_HASH: i64 = i32::MAX as i64;
+
+fn combine(hash: i64, id: i64) -> i64 {
+    let mut hash = hash * 31 + id;
+    while hash >= MAX_HASH {
+        hash /= 7;
+    }
+    hash
+}
+
+/// Hash of `s` like Java's `TypeUtils.computeStringHash`, over its UTF-8 bytes taken as signed
+/// like Java's `byte`.
+pub fn string_hash(s: &str) -> u32 {
+    s.bytes().fold(17, |hash, b| combine(hash, b as i8 as i64)) as u32
+}
+
+/// Hash of a struct whose fields have the type ids `fields`, in the order they are written,
+/// like Java's `StructSerializer.computeStructHash`. The names of the fields don't count.
+///
+/// Java counts every map as `MAP` and the type ids of types written with their type info as
+/// positive, so do the ids here.
+pub fn struct_hash(fields: &[i16]) -> u32 {
+    fields
+        .iter()
+        .fold(17, |hash, type_id| combine(hash, hash_id(*type_id))) as u32
+}
+
+fn hash_id(type_id: i16) -> i64 {
+    match FieldType::try_from(type_id) {
+        Ok(FieldType::FuryLinkedHashMap | FieldType::FuryTreeMap) => FieldType::MAP as i64,
+        _ => (type_id as i64).abs(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{string_hash, struct_hash};
+    use crate::types::FieldType;
+
+    // computed by Java's `StructSerializer.computeStructHash` for classes with such fields
+    #[test]
+    fn struct_hashes() {
+        assert_eq!(struct_hash(&[]), 17);
+        assert_eq!(
+            struct_hash(&[FieldType::INT32 as i16, FieldType::STRING as i16]),
+            16567
+        );
+        assert_eq!(
+            struct_hash(&[1, 3, 5, 7, 9, 11, 12, 13, 14, 16, 18, 25, 30, 257, 259]),
+            1718363512
+        );
+        assert_eq!(struct_hash(&[30; 8]), 913431231);
+        // a `LinkedHashMap` field is a `Map` to Java, a struct written with its type info too
+        assert_eq!(
+            struct_hash(&[FieldType::FuryLinkedHashMap as i16, -101]),
+            struct_hash(&[FieldType::MAP as i16, 101])
+        );
+    }
+
+    // computed by Java's `TypeUtils.computeStringHash`
+    #[test]
+    fn string_hashes() {
+        assert_eq!(string_hash(""), 17);
+        assert_eq!(string_hash("example.Foo"), 1833736457);
+        assert_eq!(string_hash("é"), 14359);
+    }
+}
@@ -273,7 +273,7 @@ impl MetaStringEncoder {
                 '$' => Ok(28),
                 '|' => Ok(29),
                 _ => Err(anyhow!(
-                    "Unsupported character for LOWER_UPPER_DIGIT_SPECIAL encoding: {c}"
+                    "Unsupported character for LOWER_SPECIAL encoding: {c:?}"
                 ))?,
             },
             6 => match c {
@@ -287,7 +287,7 @@ impl MetaStringEncoder {
                         Ok(63)
                     } else {
                         Err(anyhow!(
-                            "Invalid character value for LOWER_SPECIAL decoding: {c:?}"
+                            "Unsupported character for LOWER_UPPER_DIGIT_SPECIAL encoding: {c:?}"
                         ))?
                     }
                 }
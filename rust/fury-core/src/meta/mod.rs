@@ -15,9 +15,18 @@
 // specific language governing permissions and limitations
 // under the License.
 
+mod hash;
 mod meta_string;
 mod string_util;
 mod type_meta;
 
+pub use hash::{string_hash, struct_hash};
 pub use meta_string::{Encoding, MetaString, MetaStringDecoder, MetaStringEncoder};
+#[cfg(feature = "testing")]
+pub use string_util::is_latin_paths;
+#[cfg(target_arch = "x86_64")]
+pub(crate) use string_util::MIN_DIM_SIZE_AVX;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+pub(crate) use string_util::MIN_DIM_SIZE_SIMD;
+pub(crate) use type_meta::{read_meta_string, write_meta_string};
 pub use type_meta::{FieldInfo, TypeMeta};
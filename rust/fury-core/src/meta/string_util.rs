@@ -15,26 +15,25 @@
 // specific language governing permissions and limitations
 // under the License.
 
-#[cfg(target_feature = "neon")]
+// Every SIMD path is built from stable `std::arch` intrinsics and picked at runtime, so that the
+// crate builds on the declared MSRV for any target, with or without `-C target-feature`.
+#[cfg(target_arch = "aarch64")]
 use std::arch::aarch64::*;
 
-#[cfg(target_feature = "avx2")]
-use std::arch::x86_64::*;
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
 
-#[cfg(target_feature = "sse2")]
+#[cfg(target_arch = "x86_64")]
 use std::arch::x86_64::*;
 
 #[cfg(target_arch = "x86_64")]
 pub const MIN_DIM_SIZE_AVX: usize = 32;
 
-#[cfg(any(
-    target_arch = "x86",
-    target_arch = "x86_64",
-    all(target_arch = "aarch64", target_feature = "neon")
-))]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
 pub const MIN_DIM_SIZE_SIMD: usize = 16;
 
 #[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
 unsafe fn is_latin_avx(s: &str) -> bool {
     let bytes = s.as_bytes();
     let len = bytes.len();
@@ -58,7 +57,8 @@ unsafe fn is_latin_avx(s: &str) -> bool {
     true
 }
 
-#[cfg(target_feature = "sse2")]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "sse2")]
 unsafe fn is_latin_sse(s: &str) -> bool {
     let bytes = s.as_bytes();
     let len = bytes.len();
@@ -81,7 +81,8 @@ unsafe fn is_latin_sse(s: &str) -> bool {
     true
 }
 
-#[cfg(target_feature = "neon")]
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
 unsafe fn is_latin_neon(s: &str) -> bool {
     let bytes = s.as_bytes();
     let len = bytes.len();
@@ -111,22 +112,19 @@ fn is_latin_standard(s: &str) -> bool {
 pub fn is_latin(s: &str) -> bool {
     #[cfg(target_arch = "x86_64")]
     {
-        if is_x86_feature_detected!("avx")
-            && is_x86_feature_detected!("fma")
-            && s.len() >= MIN_DIM_SIZE_AVX
-        {
+        if is_x86_feature_detected!("avx2") && s.len() >= MIN_DIM_SIZE_AVX {
             return unsafe { is_latin_avx(s) };
         }
     }
 
     #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
     {
-        if is_x86_feature_detected!("sse") && s.len() >= MIN_DIM_SIZE_SIMD {
+        if is_x86_feature_detected!("sse2") && s.len() >= MIN_DIM_SIZE_SIMD {
             return unsafe { is_latin_sse(s) };
         }
     }
 
-    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    #[cfg(target_arch = "aarch64")]
     {
         if std::arch::is_aarch64_feature_detected!("neon") && s.len() >= MIN_DIM_SIZE_SIMD {
             return unsafe { is_latin_neon(s) };
@@ -135,6 +133,36 @@ pub fn is_latin(s: &str) -> bool {
     is_latin_standard(s)
 }
 
+/// Answer of every `is_latin` path this CPU can run on `s` by name, the scalar one first, for
+/// differential tests of the SIMD paths. These run whatever the length of `s`, below their
+/// threshold only their scalar tail does the work.
+#[cfg(any(test, feature = "testing"))]
+pub fn is_latin_paths(s: &str) -> Vec<(&'static str, bool)> {
+    let mut paths = vec![("scalar", is_latin_standard(s))];
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            paths.push(("avx2", unsafe { is_latin_avx(s) }));
+        }
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if is_x86_feature_detected!("sse2") {
+            paths.push(("sse2", unsafe { is_latin_sse(s) }));
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            paths.push(("neon", unsafe { is_latin_neon(s) }));
+        }
+    }
+    paths
+}
+
 #[cfg(test)]
 mod tests {
     // 导入外部模块中的内容
@@ -162,7 +190,7 @@ mod tests {
 
         #[cfg(target_arch = "x86_64")]
         {
-            if is_x86_feature_detected!("avx") && is_x86_feature_detected!("fma") {
+            if is_x86_feature_detected!("avx2") {
                 assert!(unsafe { is_latin_avx(&s) });
                 assert!(!unsafe { is_latin_avx(&not_latin_str) });
             }
@@ -170,13 +198,13 @@ mod tests {
 
         #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
         {
-            if is_x86_feature_detected!("sse") && s.len() >= MIN_DIM_SIZE_SIMD {
+            if is_x86_feature_detected!("sse2") && s.len() >= MIN_DIM_SIZE_SIMD {
                 assert!(unsafe { is_latin_sse(&s) });
                 assert!(!unsafe { is_latin_sse(&not_latin_str) });
             }
         }
 
-        #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+        #[cfg(target_arch = "aarch64")]
         {
             if std::arch::is_aarch64_feature_detected!("neon") && s.len() >= MIN_DIM_SIZE_SIMD {
                 assert!(unsafe { is_latin_neon(&s) });
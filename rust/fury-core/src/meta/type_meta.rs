@@ -17,13 +17,25 @@
 
 use super::meta_string::MetaStringEncoder;
 use crate::buffer::{Reader, Writer};
+use crate::ensure;
 use crate::error::Error;
 use crate::meta::{Encoding, MetaStringDecoder};
 use anyhow::anyhow;
 
+// Set in the header of a field identified by a tag id rather than its name. The size bits hold
+// the tag id then and no name follows. The two encoding bits, which the spec uses to flag tag
+// ids, are all taken by the meta string encodings here.
+const TAG_ID_FLAG: u8 = 1;
+
+// Set in the header of a type meta of a type registered by name, whose namespace and name follow
+// the header as meta strings. Readers find the type by name then, its id is only the writer's.
+const NAMED_TYPE_FLAG: u64 = 1 << 4;
+
+#[derive(Clone)]
 pub struct FieldInfo {
     field_name: String,
     field_id: i16,
+    tag_id: Option<u32>,
 }
 
 impl FieldInfo {
@@ -31,42 +43,84 @@ impl FieldInfo {
         FieldInfo {
             field_name: field_name.to_string(),
             field_id: field_type,
+            tag_id: None,
         }
     }
 
+    /// A field identified by `tag_id` instead of its name, e.g. `#[fury(id = 3)]`.
+    pub fn with_tag_id(tag_id: u32, field_type: i16) -> FieldInfo {
+        FieldInfo {
+            field_name: format!("#{tag_id}"),
+            field_id: field_type,
+            tag_id: Some(tag_id),
+        }
+    }
+
+    /// Name of the field, `#` followed by the tag id for a field identified by one.
+    pub fn get_field_name(&self) -> &str {
+        &self.field_name
+    }
+
+    pub fn get_tag_id(&self) -> Option<u32> {
+        self.tag_id
+    }
+
+    pub fn get_field_id(&self) -> i16 {
+        self.field_id
+    }
+
     fn u8_to_encoding(value: u8) -> Result<Encoding, Error> {
         match value {
             0x00 => Ok(Encoding::Utf8),
-            0x01 => Ok(Encoding::AllToLowerSpecial),
+            0x01 => Ok(Encoding::LowerSpecial),
             0x02 => Ok(Encoding::LowerUpperDigitSpecial),
+            0x03 => Ok(Encoding::FirstToLowerSpecial),
+            0x04 => Ok(Encoding::AllToLowerSpecial),
             _ => Err(anyhow!(
-                "Unsupported encoding of field name in type meta, value:{value}"
+                "Unsupported encoding of meta string in type meta, value:{value}"
             ))?,
         }
     }
 
-    fn from_bytes(reader: &mut Reader) -> FieldInfo {
-        let header = reader.u8();
-        let encoding = Self::u8_to_encoding((header & 0b11000) >> 3).unwrap();
-        let mut size = (header & 0b11100000) as i32 >> 5;
-        size = if size == 0b111 {
-            reader.var_int32() + 7
-        } else {
-            size
-        };
-        let type_id = reader.i16();
-        let field_name = MetaStringDecoder::new()
-            .decode(reader.bytes(size as usize), encoding)
-            .unwrap();
-        FieldInfo {
-            field_name,
-            field_id: type_id,
+    fn from_bytes(reader: &mut Reader) -> Result<FieldInfo, Error> {
+        let header = reader.u8()?;
+        let encoding = Self::u8_to_encoding((header & 0b11000) >> 3)?;
+        let mut size = ((header & 0b11100000) >> 5) as u32;
+        if size == 0b111 {
+            size = u32::try_from(reader.var_int32()?)
+                .ok()
+                .and_then(|size| size.checked_add(7))
+                .ok_or_else(|| anyhow!("Invalid size of a field in type meta"))?;
         }
+        let type_id = reader.i16()?;
+        if header & TAG_ID_FLAG != 0 {
+            return Ok(FieldInfo::with_tag_id(size, type_id));
+        }
+        let field_name =
+            MetaStringDecoder::new().decode(&reader.bytes(size as usize)?, encoding)?;
+        Ok(FieldInfo::new(&field_name, type_id))
     }
 
     fn to_bytes(&self) -> Result<Vec<u8>, Error> {
         let mut writer = Writer::default();
-        let meta_string = MetaStringEncoder::new().encode(&self.field_name)?;
+        if let Some(tag_id) = self.tag_id {
+            let header = 1 << 2 | TAG_ID_FLAG;
+            if tag_id >= 7 {
+                writer.u8(header | 0b11100000);
+                writer.var_int32((tag_id - 7) as i32);
+            } else {
+                writer.u8(header | (tag_id << 5) as u8);
+            }
+            writer.i16(self.field_id);
+            return Ok(writer.dump());
+        }
+        let encoder = MetaStringEncoder::new();
+        let mut meta_string = encoder.encode(&self.field_name)?;
+        // the header has two bits for the encoding
+        if meta_string.encoding == Encoding::AllToLowerSpecial {
+            meta_string =
+                encoder.encode_with_encoding(&self.field_name, Encoding::LowerUpperDigitSpecial)?;
+        }
         let mut header = 1 << 2;
         let encoded = meta_string.bytes.as_slice();
         let size = encoded.len() as u32;
@@ -117,21 +171,41 @@ impl TypeMetaLayer {
         Ok(writer.dump())
     }
 
-    fn from_bytes(reader: &mut Reader) -> TypeMetaLayer {
-        let field_num = reader.var_int32();
-        let type_id = reader.var_int32() as u32;
-        let field_info = (0..field_num)
-            .map(|_| FieldInfo::from_bytes(reader))
-            .collect();
-        TypeMetaLayer::new(type_id, field_info)
+    fn from_bytes(reader: &mut Reader) -> Result<TypeMetaLayer, Error> {
+        let field_num = usize::try_from(reader.var_int32()?)
+            .map_err(|_| anyhow!("Invalid number of fields in type meta"))?;
+        let type_id = reader.var_int32()? as u32;
+        // each field takes at least three bytes, a corrupt count can't reserve much
+        let mut field_info = Vec::with_capacity(field_num.min(reader.remaining() / 3));
+        for _ in 0..field_num {
+            field_info.push(FieldInfo::from_bytes(reader)?);
+        }
+        Ok(TypeMetaLayer::new(type_id, field_info))
     }
 }
 
 pub struct TypeMeta {
     hash: u64,
+    // (namespace, name) of a type registered by name
+    type_name: Option<(String, String)>,
     layers: Vec<TypeMetaLayer>,
 }
 
+pub(crate) fn write_meta_string(writer: &mut Writer, value: &str) -> Result<(), Error> {
+    let meta_string = MetaStringEncoder::new().encode(value)?;
+    writer.u8(meta_string.encoding as u8);
+    writer.var_int32(meta_string.bytes.len() as i32);
+    writer.bytes(&meta_string.bytes);
+    Ok(())
+}
+
+pub(crate) fn read_meta_string(reader: &mut Reader) -> Result<String, Error> {
+    let encoding = FieldInfo::u8_to_encoding(reader.u8()?)?;
+    let len = usize::try_from(reader.var_int32()?)
+        .map_err(|_| anyhow!("Invalid length of a meta string in type meta"))?;
+    MetaStringDecoder::new().decode(&reader.bytes(len)?, encoding)
+}
+
 impl TypeMeta {
     pub fn get_field_info(&self) -> &Vec<FieldInfo> {
         self.layers.first().unwrap().get_field_info()
@@ -141,26 +215,67 @@ impl TypeMeta {
         self.layers.first().unwrap().get_type_id()
     }
 
+    pub fn set_type_id(&mut self, type_id: u32) {
+        if let Some(layer) = self.layers.first_mut() {
+            layer.type_id = type_id;
+        }
+    }
+
+    /// (namespace, name) of a type registered with [`Fury::register_by_name`].
+    ///
+    /// [`Fury::register_by_name`]: crate::fury::Fury::register_by_name
+    pub fn get_type_name(&self) -> Option<(&str, &str)> {
+        self.type_name
+            .as_ref()
+            .map(|(namespace, name)| (namespace.as_str(), name.as_str()))
+    }
+
+    pub fn set_type_name(&mut self, namespace: &str, name: &str) {
+        self.type_name = Some((namespace.to_string(), name.to_string()));
+    }
+
     pub fn from_fields(type_id: u32, field_info: Vec<FieldInfo>) -> TypeMeta {
         TypeMeta {
             hash: 0,
+            type_name: None,
             layers: vec![TypeMetaLayer::new(type_id, field_info)],
         }
     }
 
-    pub fn from_bytes(reader: &mut Reader) -> TypeMeta {
-        let header = reader.u64();
+    /// Reads what [`TypeMeta::to_bytes`] wrote. Fails on a corrupt type meta, e.g. one from a
+    /// message that was tampered with.
+    pub fn from_bytes(reader: &mut Reader) -> Result<TypeMeta, Error> {
+        let header = reader.u64()?;
         let hash = header >> 8; // high 56bits indicate hash
         let layer_count = header & 0b1111; // class count
-        let layers: Vec<TypeMetaLayer> = (0..layer_count)
+        ensure!(layer_count > 0, "Type meta without a layer");
+        let type_name = if header & NAMED_TYPE_FLAG != 0 {
+            let namespace = read_meta_string(reader)?;
+            Some((namespace, read_meta_string(reader)?))
+        } else {
+            None
+        };
+        let layers = (0..layer_count)
             .map(|_| TypeMetaLayer::from_bytes(reader))
-            .collect();
-        TypeMeta { hash, layers }
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(TypeMeta {
+            hash,
+            type_name,
+            layers,
+        })
     }
 
     pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
         let mut writer = Writer::default();
-        writer.u64((self.hash << 8) | (self.layers.len() as u64 & 0b1111));
+        let mut header = (self.hash << 8) | (self.layers.len() as u64 & 0b1111);
+        if self.type_name.is_some() {
+            header |= NAMED_TYPE_FLAG;
+        }
+        writer.u64(header);
+        if let Some((namespace, name)) = &self.type_name {
+            write_meta_string(&mut writer, namespace)?;
+            write_meta_string(&mut writer, name)?;
+        }
         for layer in self.layers.iter() {
             writer.bytes(layer.to_bytes()?.as_slice());
         }
@@ -0,0 +1,108 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Sizes of serialized messages per root type, see [`SizeProfiler`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+/// Distribution of the sizes of the messages written for one type.
+///
+/// `buckets[i]` counts messages of `2^i` up to `2^(i+1) - 1` bytes, the first bucket also
+/// counts empty ones.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SizeHistogram {
+    pub count: u64,
+    // in bytes, over all messages
+    pub total: u64,
+    pub min: usize,
+    pub max: usize,
+    pub buckets: Vec<u64>,
+}
+
+impl SizeHistogram {
+    fn record(&mut self, size: usize) {
+        if self.count == 0 || size < self.min {
+            self.min = size;
+        }
+        self.max = self.max.max(size);
+        self.count += 1;
+        self.total += size as u64;
+        let bucket = (usize::BITS - size.leading_zeros()).saturating_sub(1) as usize;
+        if self.buckets.len() <= bucket {
+            self.buckets.resize(bucket + 1, 0);
+        }
+        self.buckets[bucket] += 1;
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.total as f64 / self.count as f64
+    }
+
+    /// Upper bound of the size below which the fraction `quantile` of the messages are, at the
+    /// resolution of the buckets and never above [`SizeHistogram::max`].
+    pub fn quantile(&self, quantile: f64) -> usize {
+        let target = (quantile.clamp(0.0, 1.0) * self.count as f64).ceil() as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= target.max(1) {
+                return ((2usize << bucket) - 1).min(self.max);
+            }
+        }
+        self.max
+    }
+}
+
+/// Collects the sizes of the messages written by [`Fury::serialize`](crate::fury::Fury::serialize)
+/// per root type, to find the types worth compressing or storing by column.
+///
+/// Opt in with [`Fury::size_profiler`](crate::fury::Fury::size_profiler), which is meant for
+/// debug builds and load tests: every message then takes a lock.
+#[derive(Default)]
+pub struct SizeProfiler {
+    types: Mutex<HashMap<&'static str, SizeHistogram>>,
+}
+
+impl SizeProfiler {
+    pub fn new() -> SizeProfiler {
+        SizeProfiler::default()
+    }
+
+    pub fn record(&self, type_name: &'static str, size: usize) {
+        let mut types = self.types.lock().unwrap_or_else(|err| err.into_inner());
+        types.entry(type_name).or_default().record(size);
+    }
+
+    /// Histograms recorded so far, by type name.
+    pub fn snapshot(&self) -> BTreeMap<&'static str, SizeHistogram> {
+        let types = self.types.lock().unwrap_or_else(|err| err.into_inner());
+        types
+            .iter()
+            .map(|(type_name, histogram)| (*type_name, histogram.clone()))
+            .collect()
+    }
+
+    pub fn reset(&self) {
+        self.types
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+    }
+}
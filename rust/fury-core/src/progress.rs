@@ -0,0 +1,38 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Progress of long deserializations, see [`Fury::progress_listener`](crate::fury::Fury::progress_listener).
+
+/// Number of bytes read between two progress reports by default.
+pub const DEFAULT_PROGRESS_INTERVAL: usize = 1 << 20;
+
+/// Observes how far the reader got through a message, e.g. to show the decoding of a large
+/// snapshot in a UI.
+///
+/// Reports are made on the deserializing thread between elements of collections, once at
+/// least [`Fury::progress_interval`](crate::fury::Fury::progress_interval) bytes have been
+/// read since the last one, and once the value has been read. A single large string or
+/// primitive array is only reported once it has been read.
+pub trait ProgressListener: Send + Sync {
+    /// `consumed` bytes of the `total` bytes of the input have been read.
+    fn on_progress(&self, consumed: usize, total: usize);
+}
+
+impl<F: Fn(usize, usize) + Send + Sync> ProgressListener for F {
+    fn on_progress(&self, consumed: usize, total: usize) {
+        self(consumed, total)
+    }
+}
@@ -16,46 +16,55 @@
 // under the License.
 
 use super::context::{ReadContext, WriteContext};
+use crate::buffer::Reader;
+use crate::ensure;
 use crate::error::Error;
 use crate::fury::Fury;
-use crate::serializer::StructSerializer;
+use crate::meta::{MetaStringEncoder, TypeMeta};
+use crate::serializer::versioned::{VersionSerializer, VersionTable, Versioned};
+use crate::serializer::{DynSerializer, StructSerializer, TypedDynSerializer};
+use anyhow::anyhow;
+use std::any::Any;
 use std::any::TypeId;
-use std::{any::Any, collections::HashMap};
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
 
 pub struct Harness {
-    serializer: fn(&dyn Any, &mut WriteContext),
-    deserializer: fn(&mut ReadContext) -> Result<Box<dyn Any>, Error>,
+    serializer: Box<dyn DynSerializer>,
 }
 
 impl Harness {
-    pub fn new(
-        serializer: fn(&dyn Any, &mut WriteContext),
-        deserializer: fn(&mut ReadContext) -> Result<Box<dyn Any>, Error>,
-    ) -> Harness {
-        Harness {
-            serializer,
-            deserializer,
-        }
+    pub fn new(serializer: Box<dyn DynSerializer>) -> Harness {
+        Harness { serializer }
+    }
+
+    pub fn serialize(&self, value: &dyn Any, context: &mut WriteContext) {
+        self.serializer.serialize(value, context)
+    }
+
+    pub fn deserialize(&self, context: &mut ReadContext) -> Result<Box<dyn Any>, Error> {
+        self.serializer.deserialize(context)
     }
 
-    pub fn get_serializer(&self) -> fn(&dyn Any, &mut WriteContext) {
-        self.serializer
+    pub fn get_type_name(&self) -> &str {
+        self.serializer.type_name()
     }
 
-    pub fn get_deserializer(&self) -> fn(&mut ReadContext) -> Result<Box<dyn Any>, Error> {
-        self.deserializer
+    pub fn get_rust_type_id(&self) -> TypeId {
+        self.serializer.rust_type_id()
     }
 }
 
 pub struct ClassInfo {
-    type_def: Vec<u8>,
+    // built on first use, so that a type can be registered before the types of its fields
+    type_def: OnceLock<Vec<u8>>,
     type_id: u32,
 }
 
 impl ClassInfo {
-    pub fn new<T: StructSerializer>(fury: &Fury, type_id: u32) -> ClassInfo {
+    pub fn new(type_id: u32) -> ClassInfo {
         ClassInfo {
-            type_def: T::type_def(fury),
+            type_def: OnceLock::new(),
             type_id,
         }
     }
@@ -63,17 +72,60 @@ impl ClassInfo {
     pub fn get_type_id(&self) -> u32 {
         self.type_id
     }
+}
+
+// Type defs are generated without knowing the id their type gets registered with, the id and
+// the name of a named type are filled in here so that a reader can find the serializer of a
+// type meta.
+fn with_type_id(type_def: Vec<u8>, type_id: u32, type_name: Option<&(String, String)>) -> Vec<u8> {
+    if type_def.is_empty() {
+        return type_def;
+    }
+    let Ok(mut meta) = TypeMeta::from_bytes(&mut Reader::new(&type_def)) else {
+        return type_def;
+    };
+    meta.set_type_id(type_id);
+    if let Some((namespace, name)) = type_name {
+        meta.set_type_name(namespace, name);
+    }
+    meta.to_bytes().unwrap_or(type_def)
+}
 
-    pub fn get_type_def(&self) -> &Vec<u8> {
-        &self.type_def
+// Types registered by name get ids in this range, so that they don't take ids users pick.
+const NAMED_TYPE_IDS: std::ops::Range<u32> = 0x4000..0x8000;
+
+/// Id of the type registered as `name` in `namespace`, a hash of both, the same for every peer
+/// and build so that schema-consistent messages agree on it too.
+pub fn named_type_id(namespace: &str, name: &str) -> u32 {
+    // FNV-1a, with a separator so that ("a.b", "c") and ("a", "b.c") differ
+    let mut hash: u32 = 0x811c9dc5;
+    for b in namespace.bytes().chain([0]).chain(name.bytes()) {
+        hash ^= b as u32;
+        hash = hash.wrapping_mul(0x01000193);
     }
+    NAMED_TYPE_IDS.start + hash % (NAMED_TYPE_IDS.end - NAMED_TYPE_IDS.start)
 }
 
 #[derive(Default)]
 pub struct ClassResolver {
-    serialize_map: HashMap<u32, Harness>,
-    type_id_map: HashMap<TypeId, u32>,
+    harnesses: Vec<Harness>,
+    // fury type id -> index of the harness used to read values of that id
+    serialize_map: HashMap<u32, usize>,
+    // rust type id -> index of the harness used to write values of that type
+    type_id_map: HashMap<TypeId, usize>,
+    type_name_map: HashMap<String, u32>,
     class_info_map: HashMap<TypeId, ClassInfo>,
+    // legacy fury type id -> id of the type now read in its place
+    legacy_id_map: HashMap<u32, u32>,
+    // rust type id of `Versioned<T>` -> its serializers by version
+    version_tables: HashMap<TypeId, VersionTable>,
+    // fury type ids whose values are skipped on read
+    ignored_ids: HashSet<u32>,
+    // fury type id -> (namespace, name) of the types registered by name, and back
+    type_names: HashMap<u32, (String, String)>,
+    named_ids: HashMap<(String, String), u32>,
+    // whether values of unregistered types fail the read, see `Fury::require_registration`
+    require_registration: bool,
 }
 
 impl ClassResolver {
@@ -81,36 +133,258 @@ impl ClassResolver {
         self.class_info_map.get(&type_id).unwrap()
     }
 
+    /// Type definition of the registered rust type `type_id`, built by its serializer the first
+    /// time it is asked for. Panics if a type it has fields of isn't registered by then.
+    pub fn get_type_def(&self, type_id: TypeId, fury: &Fury) -> &[u8] {
+        let info = self.get_class_info(type_id);
+        info.type_def.get_or_init(|| {
+            let harness = self
+                .get_harness_by_type(type_id)
+                .expect("registered types have a harness");
+            with_type_id(
+                harness.serializer.type_def(fury),
+                info.type_id,
+                self.type_names.get(&info.type_id),
+            )
+        })
+    }
+
     pub fn register<T: StructSerializer>(&mut self, class_info: ClassInfo, id: u32) {
-        fn serializer<T2: 'static + StructSerializer>(this: &dyn Any, context: &mut WriteContext) {
-            let this = this.downcast_ref::<T2>();
-            match this {
-                Some(v) => {
-                    T2::serialize(v, context);
-                }
-                None => todo!(),
+        let serializer = TypedDynSerializer::<T>::new(std::any::type_name::<T>());
+        self.insert(class_info, id, Box::new(serializer));
+    }
+
+    /// Registers a serializer whose concrete type is only known through the trait object.
+    ///
+    /// Type names are used as a fallback identity: registering a name again under the same id
+    /// but with another `TypeId` (the same type compiled into another compilation unit) makes
+    /// both `TypeId`s resolve to that id. Reusing a name or a `TypeId` for another id is an error.
+    pub fn register_dyn(
+        &mut self,
+        class_info: ClassInfo,
+        id: u32,
+        serializer: Box<dyn DynSerializer>,
+    ) -> Result<(), Error> {
+        if let Some(registered_id) = self.type_name_map.get(serializer.type_name()) {
+            if *registered_id != id {
+                return Err(anyhow!(
+                    "Type `{}` is already registered with id {registered_id}, can't register it with id {id}",
+                    serializer.type_name()
+                )
+                .into());
             }
         }
-
-        fn deserializer<T2: 'static + StructSerializer>(
-            context: &mut ReadContext,
-        ) -> Result<Box<dyn Any>, Error> {
-            match T2::deserialize(context) {
-                Ok(v) => Ok(Box::new(v)),
-                Err(e) => Err(e),
+        if let Some(index) = self.type_id_map.get(&serializer.rust_type_id()) {
+            let registered = &self.harnesses[*index];
+            if registered.get_type_name() != serializer.type_name() {
+                return Err(anyhow!(
+                    "TypeId of `{}` is already registered as `{}`",
+                    serializer.type_name(),
+                    registered.get_type_name()
+                )
+                .into());
             }
         }
-        self.type_id_map.insert(TypeId::of::<T>(), id);
-        self.serialize_map
-            .insert(id, Harness::new(serializer::<T>, deserializer::<T>));
-        self.class_info_map.insert(TypeId::of::<T>(), class_info);
+        self.insert(class_info, id, serializer);
+        Ok(())
+    }
+
+    fn insert(&mut self, class_info: ClassInfo, id: u32, serializer: Box<dyn DynSerializer>) {
+        let index = self.harnesses.len();
+        let rust_type_id = serializer.rust_type_id();
+        let is_alias = self.type_name_map.get(serializer.type_name()) == Some(&id)
+            && self.serialize_map.contains_key(&id);
+        self.type_name_map
+            .insert(serializer.type_name().to_string(), id);
+        self.harnesses.push(Harness::new(serializer));
+        if !is_alias {
+            self.serialize_map.insert(id, index);
+        }
+        self.type_id_map.insert(rust_type_id, index);
+        self.class_info_map.insert(rust_type_id, class_info);
     }
 
     pub fn get_harness_by_type(&self, type_id: TypeId) -> Option<&Harness> {
-        self.get_harness(*self.type_id_map.get(&type_id).unwrap())
+        self.type_id_map
+            .get(&type_id)
+            .map(|index| &self.harnesses[*index])
     }
 
     pub fn get_harness(&self, id: u32) -> Option<&Harness> {
-        self.serialize_map.get(&id)
+        self.serialize_map
+            .get(&id)
+            .map(|index| &self.harnesses[*index])
+    }
+
+    /// Type definitions of all registered types, ordered by fury type id.
+    pub fn type_defs(&self, fury: &Fury) -> Vec<(u32, &[u8])> {
+        let mut type_defs: Vec<(u32, &[u8])> = self
+            .class_info_map
+            .iter()
+            .map(|(type_id, info)| (info.get_type_id(), self.get_type_def(*type_id, fury)))
+            .collect();
+        type_defs.sort_unstable();
+        type_defs
+    }
+
+    /// Registered types as (fury type id, type name), ordered by id. A type registered under
+    /// several `TypeId`s is listed once.
+    pub fn registrations(&self) -> Vec<(u32, &str)> {
+        let mut registrations: Vec<(u32, &str)> = self
+            .type_id_map
+            .iter()
+            .filter_map(|(type_id, index)| {
+                let info = self.class_info_map.get(type_id)?;
+                Some((info.get_type_id(), self.harnesses[*index].get_type_name()))
+            })
+            .collect();
+        registrations.sort_unstable();
+        registrations.dedup();
+        registrations
+    }
+
+    /// Makes room for `additional` more registered types.
+    pub fn reserve(&mut self, additional: usize) {
+        self.harnesses.reserve(additional);
+        self.serialize_map.reserve(additional);
+        self.type_id_map.reserve(additional);
+        self.type_name_map.reserve(additional);
+        self.class_info_map.reserve(additional);
+    }
+
+    /// Gives the registered type `id` the name `name` in `namespace`, see
+    /// [`Fury::register_by_name`].
+    pub fn name_type(&mut self, id: u32, namespace: &str, name: &str) -> Result<(), Error> {
+        // written as meta strings, which are limited in length
+        MetaStringEncoder::new().encode(namespace)?;
+        MetaStringEncoder::new().encode(name)?;
+        let key = (namespace.to_string(), name.to_string());
+        if let Some(named_id) = self.named_ids.get(&key) {
+            ensure!(
+                *named_id == id,
+                "Type name `{}.{}` is already registered with id {}",
+                namespace,
+                name,
+                named_id
+            );
+        }
+        self.type_names.insert(id, key.clone());
+        self.named_ids.insert(key, id);
+        Ok(())
+    }
+
+    /// Id of the type registered as `name` in `namespace`.
+    pub fn get_id_by_type_name(&self, namespace: &str, name: &str) -> Option<u32> {
+        self.named_ids
+            .get(&(namespace.to_string(), name.to_string()))
+            .copied()
+    }
+
+    /// (namespace, name) the type `id` is registered with, if registered by name.
+    pub fn get_type_name(&self, id: u32) -> Option<(&str, &str)> {
+        self.type_names
+            .get(&id)
+            .map(|(namespace, name)| (namespace.as_str(), name.as_str()))
+    }
+
+    pub fn get_type_id_by_name(&self, type_name: &str) -> Option<u32> {
+        self.type_name_map.get(type_name).copied()
+    }
+
+    /// Fury type id of a registered Rust type.
+    pub fn get_registered_id(&self, type_id: TypeId) -> Option<u32> {
+        self.class_info_map
+            .get(&type_id)
+            .map(ClassInfo::get_type_id)
+    }
+
+    /// Makes `name` resolve to the registered type `id`.
+    pub fn alias_name(&mut self, name: &str, id: u32) -> Result<(), Error> {
+        if let Some(registered_id) = self.type_name_map.get(name) {
+            if *registered_id != id {
+                return Err(anyhow!(
+                    "Type name `{name}` is already registered with id {registered_id}, can't alias it to id {id}"
+                )
+                .into());
+            }
+        }
+        self.type_name_map.insert(name.to_string(), id);
+        Ok(())
+    }
+
+    /// Makes values written with `legacy_id` read as the registered type `id`.
+    pub fn alias_id(&mut self, legacy_id: u32, id: u32) -> Result<(), Error> {
+        let index = *self
+            .serialize_map
+            .get(&id)
+            .ok_or_else(|| anyhow!("Type id {id} isn't registered"))?;
+        if self.serialize_map.contains_key(&legacy_id)
+            && self.legacy_id_map.get(&legacy_id) != Some(&id)
+        {
+            return Err(anyhow!(
+                "Type id {legacy_id} is already in use, can't alias it to id {id}"
+            )
+            .into());
+        }
+        self.serialize_map.insert(legacy_id, index);
+        self.legacy_id_map.insert(legacy_id, id);
+        Ok(())
+    }
+
+    pub fn require_registration(&mut self, require_registration: bool) {
+        self.require_registration = require_registration;
+    }
+
+    pub fn is_require_registration(&self) -> bool {
+        self.require_registration
+    }
+
+    /// Fails if a value of the type `id`, which has no harness here, may not be read, see
+    /// [`Fury::require_registration`].
+    pub fn check_unregistered(&self, id: u32) -> Result<(), Error> {
+        ensure!(
+            !self.require_registration,
+            "Type id {} isn't registered, `Fury::require_registration` only reads registered types",
+            id
+        );
+        Ok(())
+    }
+
+    pub fn ignore_id(&mut self, id: u32) {
+        self.ignored_ids.insert(id);
+    }
+
+    /// Whether values of the fury type `id` are skipped on read, see [`Fury::ignore_type_id`].
+    pub fn is_ignored(&self, id: u32) -> bool {
+        !self.ignored_ids.is_empty() && self.ignored_ids.contains(&id)
+    }
+
+    /// Adds the serializer of `version` to those of the versioned type `T`.
+    pub fn add_version<T: 'static>(
+        &mut self,
+        version: u32,
+        serializer: Box<dyn VersionSerializer<T>>,
+    ) -> Result<(), Error> {
+        self.version_tables
+            .entry(TypeId::of::<Versioned<T>>())
+            .or_insert_with(VersionTable::new)
+            .insert(version, serializer)
+    }
+
+    pub(crate) fn get_version_table(&self, type_id: TypeId) -> Option<&VersionTable> {
+        self.version_tables.get(&type_id)
+    }
+
+    /// Whether `id` is the id of a type registered with [`Fury::register_versioned`].
+    pub fn is_versioned(&self, id: u32) -> bool {
+        self.get_harness(id).is_some_and(|harness| {
+            self.version_tables
+                .contains_key(&harness.get_rust_type_id())
+        })
+    }
+
+    /// The id values written with `id` are read as, which differs from `id` for legacy ids.
+    pub fn resolve_id(&self, id: u32) -> u32 {
+        self.legacy_id_map.get(&id).copied().unwrap_or(id)
     }
 }
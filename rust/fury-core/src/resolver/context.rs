@@ -16,20 +16,69 @@
 // under the License.
 
 use crate::buffer::{Reader, Writer};
-use crate::error::Error;
+use crate::buffer_object::{BufferObject, IN_BAND_FLAG, OUT_OF_BAND_FLAG};
+use crate::cancel::{CancelToken, CANCEL_CHECK_INTERVAL};
+use crate::ensure;
+use crate::error::{short_type_name, Error};
 use crate::fury::Fury;
 use anyhow::anyhow;
+use std::borrow::Cow;
 
-use crate::meta::TypeMeta;
-use crate::resolver::meta_resolver::{MetaReaderResolver, MetaWriterResolver};
+use crate::meta::{read_meta_string, write_meta_string, FieldInfo, TypeMeta};
+use crate::resolver::meta_resolver::{MetaContext, MetaReaderResolver, MetaWriterResolver};
+use crate::resolver::ref_resolver::{RefReader, RefWriter};
+use crate::schema_event::SkippedField;
+use crate::serializer::{ensure_type_id, Serializer};
+use crate::stats::DecodeStats;
+use crate::types::{DecodeMode, FieldType, Mode, RefFlag, UnknownTypePolicy};
+use crate::value::{TypeLayout, Value};
 use std::any::TypeId;
-use std::rc::Rc;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::sync::Arc;
+
+/// How deeply values may nest unless configured otherwise, see [`Fury::max_depth`].
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// How many times the rest of the message a run-length array may decode to unless configured
+/// otherwise, see `Fury::max_rle_expansion`.
+pub const DEFAULT_MAX_RLE_EXPANSION: usize = 1024;
+
+/// Type of a value as [`ReadContext::read_type_info`] read it.
+#[derive(Clone)]
+pub struct TypeInfo {
+    /// Type id the value was written with, or the index of its type meta.
+    pub header: i16,
+    /// Type meta of a struct read in compatible mode, with the fields of the writer's struct.
+    pub meta: Option<Arc<TypeMeta>>,
+}
+
+// whether values of `T` are written with the index of their type meta rather than their type id
+fn writes_type_meta<T: Serializer>(fury: &Fury) -> bool {
+    let type_id = T::get_type_id(fury);
+    *fury.get_mode() == Mode::Compatible
+        && FieldType::try_from(type_id).is_err()
+        && matches!(
+            TypeLayout::of(fury, type_id as u32),
+            Some(TypeLayout::Struct(_))
+        )
+}
 
 pub struct WriteContext<'se> {
     pub writer: &'se mut Writer,
     pub tags: Vec<&'static str>,
+    // ids of the `Rc` and `Arc` pointees written so far
+    pub ref_writer: RefWriter,
     fury: &'se Fury,
     meta_resolver: MetaWriterResolver<'se>,
+    // whether an encoding of the `unstable-format` feature was written
+    experimental: bool,
+    // whether type defs sent with earlier messages are referred to, see `MetaContext`
+    shares_meta: bool,
+    // set when serializing with buffers, see `Fury::serialize_with_buffers`
+    buffer_callback: Option<&'se mut dyn FnMut(BufferObject) -> bool>,
+    // ids of the registered types written, only kept with a usage tracker
+    used_types: Option<HashSet<u32>>,
 }
 
 impl<'se> WriteContext<'se> {
@@ -37,8 +86,107 @@ impl<'se> WriteContext<'se> {
         WriteContext {
             writer,
             tags: Vec::new(),
+            ref_writer: RefWriter::new(),
             fury,
             meta_resolver: MetaWriterResolver::default(),
+            experimental: false,
+            shares_meta: false,
+            buffer_callback: None,
+            used_types: fury.get_usage_tracker().map(|_| HashSet::new()),
+        }
+    }
+
+    /// Offers the payloads of arrays to `callback` rather than always writing them, see
+    /// [`Fury::serialize_with_buffers`]. Called before anything is written.
+    pub fn with_buffer_callback(&mut self, callback: &'se mut dyn FnMut(BufferObject) -> bool) {
+        self.buffer_callback = Some(callback);
+    }
+
+    pub fn has_buffer_callback(&self) -> bool {
+        self.buffer_callback.is_some()
+    }
+
+    /// Offers the payload `bytes` of an array to the buffer callback, and writes whether it
+    /// stays in band. Returns `true` if the callback took it out of band, the caller writes it
+    /// as usual otherwise. Without a buffer callback nothing is written.
+    pub fn write_out_of_band(&mut self, bytes: &[u8]) -> bool {
+        let Some(callback) = &mut self.buffer_callback else {
+            return false;
+        };
+        let in_band = callback(BufferObject::new(bytes));
+        self.writer.u8(if in_band {
+            IN_BAND_FLAG
+        } else {
+            OUT_OF_BAND_FLAG
+        });
+        !in_band
+    }
+
+    /// Refers to the type defs sent with the earlier messages of `meta_context`, and writes only
+    /// the new ones. Called before anything is written, [`WriteContext::finish_meta_sharing`]
+    /// hands them back once the message is complete.
+    pub fn share_meta(&mut self, meta_context: &mut MetaContext) {
+        self.meta_resolver = meta_context.writer();
+        self.shares_meta = true;
+    }
+
+    pub fn finish_meta_sharing(&mut self, meta_context: &mut MetaContext) {
+        meta_context.finish_writing(std::mem::take(&mut self.meta_resolver));
+    }
+
+    pub fn is_sharing_meta(&self) -> bool {
+        self.shares_meta
+    }
+
+    /// Flags the message as using an experimental encoding, which readers built without the
+    /// `unstable-format` feature reject.
+    pub fn use_experimental_format(&mut self) {
+        self.experimental = true;
+    }
+
+    pub fn is_experimental(&self) -> bool {
+        self.experimental
+    }
+
+    /// Writes a ref flag, which precedes every value written with [`Serializer::serialize`].
+    ///
+    /// This and the other `write_*` methods below are for serializers of their own that write
+    /// what derived ones do, the matching `read_*` methods of [`ReadContext`] read it back.
+    pub fn write_ref_flag(&mut self, flag: RefFlag) {
+        self.writer.i8(flag as i8);
+    }
+
+    /// Writes what follows the ref flag of a `T` value: its type id, or in compatible mode the
+    /// index of the type meta of a registered struct, which is then sent with the message.
+    pub fn write_type_info<T: Serializer + 'static>(&mut self) {
+        if writes_type_meta::<T>(self.fury) {
+            let index = self.push_meta(TypeId::of::<T>()) as i16;
+            self.writer.i16(index);
+        } else {
+            self.writer.i16(T::get_type_id(self.fury));
+        }
+    }
+
+    /// Writes `value` like type metas write names, in one of the compact encodings of
+    /// [`MetaStringEncoder`](crate::meta::MetaStringEncoder). Fails if it's too long.
+    pub fn write_meta_string(&mut self, value: &str) -> Result<(), Error> {
+        write_meta_string(self.writer, value)
+    }
+
+    /// Notes that a value of the registered type `type_id` is written, see
+    /// [`Fury::usage_tracker`].
+    pub fn record_type(&mut self, type_id: TypeId) {
+        if let Some(used_types) = &mut self.used_types {
+            used_types.extend(self.fury.get_class_resolver().get_registered_id(type_id));
+        }
+    }
+
+    // hands the types the message holds to the usage tracker
+    pub(crate) fn report_usage(&mut self) {
+        if let (Some(tracker), Some(used_types)) =
+            (self.fury.get_usage_tracker(), self.used_types.take())
+        {
+            tracker.record_serialized(used_types);
         }
     }
 
@@ -52,7 +200,7 @@ impl<'se> WriteContext<'se> {
         self.meta_resolver.to_bytes(self.writer).unwrap()
     }
 
-    pub fn get_fury(&self) -> &Fury {
+    pub fn get_fury(&self) -> &'se Fury {
         self.fury
     }
 
@@ -77,10 +225,34 @@ impl<'se> WriteContext<'se> {
 }
 
 pub struct ReadContext<'de, 'bf: 'de> {
+    /// Input of the message, custom serializers can look ahead with [`Reader::peek_u8`].
     pub reader: Reader<'bf>,
-    pub tags: Vec<&'de str>,
+    pub tags: Vec<Cow<'de, str>>,
     pub fury: &'de Fury,
     pub meta_resolver: MetaReaderResolver,
+    pub ref_reader: RefReader,
+    pub decode_mode: DecodeMode,
+    // copy of the whole input that `FuryStr`s point into, if enabled
+    pub shared_buffer: Option<Arc<[u8]>>,
+    pub cancel: Option<&'de CancelToken>,
+    // elements read since the cancel token was last looked at
+    unchecked: u32,
+    // position of the reader from which progress is reported next
+    next_progress: usize,
+    // depth of unknown fields being skipped, see `ReadContext::is_skipping`
+    skipping: u32,
+    // set while reading a field marked `#[fury(unknown_types = "...")]`
+    unknown_types: Option<UnknownTypePolicy>,
+    // (type name, field name, field type) -> times skipped, only kept with a schema event listener
+    skipped_fields: HashMap<(&'static str, String, i16), usize>,
+    // only kept with a stats collector
+    stats: Option<DecodeStats>,
+    // buffers of a message written with out-of-band buffers, not yet read
+    out_of_band: Option<std::slice::Iter<'de, &'bf [u8]>>,
+    // ids of the registered types read, only kept with a usage tracker
+    used_types: Option<HashSet<u32>>,
+    // structs, enums and values being read around the current one
+    depth: usize,
 }
 
 impl<'de, 'bf: 'de> ReadContext<'de, 'bf> {
@@ -90,35 +262,344 @@ impl<'de, 'bf: 'de> ReadContext<'de, 'bf> {
             tags: Vec::new(),
             fury,
             meta_resolver: MetaReaderResolver::default(),
+            ref_reader: RefReader::new(fury.get_max_ref_count()),
+            decode_mode: DecodeMode::Owned,
+            shared_buffer: None,
+            cancel: None,
+            unchecked: 0,
+            next_progress: fury.get_progress_interval(),
+            skipping: 0,
+            unknown_types: None,
+            skipped_fields: HashMap::new(),
+            stats: fury.get_stats_collector().map(|_| DecodeStats::default()),
+            out_of_band: None,
+            used_types: fury.get_usage_tracker().map(|_| HashSet::new()),
+            depth: 0,
+        }
+    }
+
+    /// Reads a struct, enum or other value which may hold values of its own type with `read`,
+    /// failing with [`Error::LimitExceeded`] beyond [`Fury::max_depth`] nested values.
+    pub fn nested<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let max = self.fury.get_max_depth();
+        if self.depth >= max {
+            return Err(Error::LimitExceeded {
+                limit: "max_depth",
+                value: self.depth + 1,
+                max,
+            });
+        }
+        self.depth += 1;
+        let result = read(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Reads the number of elements of a collection, entries of a map or items of an array,
+    /// failing when it is negative or beyond [`Fury::max_collection_len`]. Custom serializers
+    /// of collections call it too.
+    pub fn read_collection_len(&mut self) -> Result<usize, Error> {
+        let len = self.reader.var_int32()?;
+        ensure!(len >= 0, "Negative length {len}");
+        self.check_collection_len(len as usize)?;
+        Ok(len as usize)
+    }
+
+    /// Like [`ReadContext::read_collection_len`] for collections whose elements take at least a
+    /// byte each, which also fails for lengths the rest of the input can't hold before room is
+    /// made for them.
+    pub fn read_elements_len(&mut self) -> Result<usize, Error> {
+        let len = self.read_collection_len()?;
+        self.reader.check_remaining(len)?;
+        Ok(len)
+    }
+
+    /// Fails when a length read otherwise than by [`ReadContext::read_collection_len`] is
+    /// beyond [`Fury::max_collection_len`].
+    pub fn check_collection_len(&self, len: usize) -> Result<(), Error> {
+        let max = self.fury.get_max_collection_len();
+        ensure!(
+            len <= max,
+            Error::LimitExceeded {
+                limit: "max_collection_len",
+                value: len,
+                max,
+            }
+        );
+        Ok(())
+    }
+
+    /// Reads the payloads of arrays handed out of band from `buffers`, for a message written
+    /// with [`Fury::serialize_with_buffers`].
+    pub fn with_buffers(&mut self, buffers: &'de [&'bf [u8]]) {
+        self.out_of_band = Some(buffers.iter());
+    }
+
+    /// Reads the flag [`WriteContext::write_out_of_band`] wrote before an array, returns the
+    /// buffer holding its payload if it was handed out of band. `None` if the payload follows in
+    /// the message, or the message wasn't written with buffers.
+    pub fn read_out_of_band(&mut self) -> Result<Option<&'bf [u8]>, Error> {
+        let Some(buffers) = &mut self.out_of_band else {
+            return Ok(None);
+        };
+        match self.reader.u8()? {
+            IN_BAND_FLAG => Ok(None),
+            OUT_OF_BAND_FLAG => match buffers.next() {
+                Some(buffer) => Ok(Some(buffer)),
+                None => Err(anyhow!(
+                    "The message refers to more out-of-band buffers than were given"
+                ))?,
+            },
+            flag => Err(anyhow!("Unknown out-of-band flag {flag}"))?,
         }
     }
 
-    pub fn get_fury(&self) -> &Fury {
+    pub fn get_fury(&self) -> &'de Fury {
         self.fury
     }
 
-    pub fn get_meta(&self, type_index: usize) -> &Rc<TypeMeta> {
-        self.meta_resolver.get(type_index)
+    /// Reads the ref flag written with [`WriteContext::write_ref_flag`].
+    pub fn read_ref_flag(&mut self) -> Result<RefFlag, Error> {
+        let flag = self.reader.i8()?;
+        RefFlag::try_from(flag).map_err(|_| anyhow!("Unknown ref flag, value:{flag}").into())
     }
 
-    pub fn load_meta(&mut self, offset: usize) {
+    /// Reads what [`WriteContext::write_type_info`] wrote for `T`. Fails if the value is of
+    /// another type, or refers to a type meta the message doesn't have.
+    pub fn read_type_info<T: Serializer>(&mut self) -> Result<TypeInfo, Error> {
+        let header = self.reader.i16()?;
+        if !writes_type_meta::<T>(self.fury) {
+            ensure_type_id(self.fury, T::get_type_id(self.fury), header)?;
+            return Ok(TypeInfo { header, meta: None });
+        }
+        let meta = self
+            .meta_resolver
+            .try_get(header as usize)
+            .ok_or_else(|| anyhow!("Unknown type meta index {header}"))?;
+        Ok(TypeInfo {
+            header,
+            meta: Some(meta.clone()),
+        })
+    }
+
+    /// Reads a string written with [`WriteContext::write_meta_string`].
+    pub fn read_meta_string(&mut self) -> Result<String, Error> {
+        read_meta_string(&mut self.reader)
+    }
+
+    pub fn get_meta(&self, type_index: usize) -> Result<&Arc<TypeMeta>, Error> {
         self.meta_resolver
-            .load(&mut Reader::new(&self.reader.slice()[offset..]))
+            .try_get(type_index)
+            .ok_or_else(|| anyhow!("Unknown type meta index {type_index}").into())
+    }
+
+    /// Loads the type metas written at `offset`, returns the position right after them.
+    pub fn load_meta(&mut self, offset: usize) -> Result<usize, Error> {
+        let mut reader = self.reader.at(offset);
+        self.meta_resolver.load(&mut reader, self.fury)?;
+        Ok(reader.get_cursor())
+    }
+
+    /// Safe point for cancellation, called once per element read by collections. Progress is
+    /// reported from here too, see [`Fury::progress_listener`].
+    ///
+    /// Fails with [`Error::Cancelled`] once the cancel token fires, which is only looked at
+    /// every [`CANCEL_CHECK_INTERVAL`] calls.
+    pub fn check_cancelled(&mut self) -> Result<(), Error> {
+        if let Some(listener) = self.fury.get_progress_listener() {
+            let consumed = self.reader.get_cursor();
+            if consumed >= self.next_progress {
+                listener.on_progress(consumed, self.reader.len());
+                self.next_progress = consumed + self.fury.get_progress_interval();
+            }
+        }
+        let Some(cancel) = self.cancel else {
+            return Ok(());
+        };
+        self.unchecked += 1;
+        if self.unchecked < CANCEL_CHECK_INTERVAL {
+            return Ok(());
+        }
+        self.unchecked = 0;
+        if cancel.is_cancelled_with(self.fury.get_clock()) {
+            return Err(Error::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Counts `len` elements of a collection of `T`s read, for the stats collector of
+    /// [`Fury::stats_collector`]. Custom serializers of collections call it too.
+    pub fn record_elements<T>(&mut self, len: usize) {
+        if let Some(stats) = &mut self.stats {
+            stats.elements += len as u64;
+            stats.allocated += len * mem::size_of::<T>();
+        }
+    }
+
+    /// Counts `bytes` allocated for a value read, e.g. a string, see
+    /// [`ReadContext::record_elements`].
+    pub fn record_allocation(&mut self, bytes: usize) {
+        if let Some(stats) = &mut self.stats {
+            stats.allocated += bytes;
+        }
+    }
+
+    /// Counts a derived struct read, see [`ReadContext::record_elements`].
+    pub fn record_struct(&mut self) {
+        if let Some(stats) = &mut self.stats {
+            stats.structs += 1;
+        }
+    }
+
+    // hands the stats of the message, which spans `bytes`, to the stats collector
+    pub(crate) fn report_stats<T>(&mut self, bytes: usize) {
+        if let (Some(collector), Some(mut stats)) =
+            (self.fury.get_stats_collector(), self.stats.take())
+        {
+            stats.bytes = bytes;
+            collector.on_decoded(std::any::type_name::<T>(), &stats);
+        }
+    }
+
+    /// Skips the value of a field the local type `type_name` doesn't have, which the
+    /// compatible-mode reader found in the peer's type meta.
+    pub fn skip_unknown_field(
+        &mut self,
+        type_name: &'static str,
+        field: &FieldInfo,
+    ) -> Result<(), Error> {
+        self.skipping += 1;
+        let skipped = Value::read(self, Some(field.get_field_id()));
+        self.skipping -= 1;
+        skipped?;
+        if self.fury.get_schema_event_listener().is_some() {
+            *self
+                .skipped_fields
+                .entry((
+                    type_name,
+                    field.get_field_name().to_string(),
+                    field.get_field_id(),
+                ))
+                .or_default() += 1;
+        }
+        Ok(())
+    }
+
+    /// Whether the value being read is part of an unknown field, which is dropped anyway. Values
+    /// of types unknown here are skipped then rather than failing, as far as the format allows.
+    pub fn is_skipping(&self) -> bool {
+        self.skipping > 0
+    }
+
+    /// `err`, raised reading the field `field`, with the field prepended to its path, see
+    /// [`Error::Read`]. The offset is where the reader is, unless `err` has one already.
+    pub fn field_error(&self, err: Error, field: &str) -> Error {
+        err.locate(self.reader.get_cursor(), field)
+    }
+
+    /// Like [`ReadContext::field_error`] for the element at `index` of a collection.
+    pub fn element_error(&self, err: Error, index: usize) -> Error {
+        err.locate(self.reader.get_cursor(), &format!("[{index}]"))
+    }
+
+    // `err` of reading the root `T`, with the name of `T` prepended to its path
+    pub(crate) fn root_error<T>(&self, err: Error) -> Error {
+        match err {
+            // not raised in the body
+            Error::TrailingBytes { .. } | Error::NotFuryPayload { .. } => err,
+            err => err.locate(
+                self.reader.get_cursor(),
+                &short_type_name(std::any::type_name::<T>()),
+            ),
+        }
+    }
+
+    /// What a `Box<dyn Any>` does with a value of an unregistered type: the policy of the field
+    /// being read if it has one, else [`DeserializeOptions::unknown_types`].
+    ///
+    /// Always [`UnknownTypePolicy::Error`] with [`Fury::require_registration`].
+    ///
+    /// [`DeserializeOptions::unknown_types`]: crate::fury::DeserializeOptions::unknown_types
+    pub fn get_unknown_types(&self) -> UnknownTypePolicy {
+        if self.fury.is_require_registration() {
+            return UnknownTypePolicy::Error;
+        }
+        self.unknown_types
+            .unwrap_or_else(|| self.fury.get_deserialize_options().get_unknown_types())
+    }
+
+    /// Runs `read` with `policy` for unregistered types, which also holds for the values nested
+    /// in what it reads, see `#[fury(unknown_types = "...")]`.
+    pub fn with_unknown_types<R>(
+        &mut self,
+        policy: UnknownTypePolicy,
+        read: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let outer = self.unknown_types.replace(policy);
+        let result = read(self);
+        self.unknown_types = outer;
+        result
+    }
+
+    /// Notes that a value of the registered type `type_id` is read, see
+    /// [`Fury::usage_tracker`].
+    pub fn record_type(&mut self, type_id: TypeId) {
+        if let Some(used_types) = &mut self.used_types {
+            used_types.extend(self.fury.get_class_resolver().get_registered_id(type_id));
+        }
+    }
+
+    // hands the types the message holds to the usage tracker
+    pub(crate) fn report_usage(&mut self) {
+        if let (Some(tracker), Some(used_types)) =
+            (self.fury.get_usage_tracker(), self.used_types.take())
+        {
+            tracker.record_deserialized(used_types);
+        }
+    }
+
+    /// Hands the fields skipped so far over to the schema event listener.
+    pub fn flush_schema_events(&mut self) {
+        let Some(listener) = self.fury.get_schema_event_listener() else {
+            return;
+        };
+        for ((type_name, field_name, field_type), count) in self.skipped_fields.drain() {
+            listener.on_skipped_field(&SkippedField {
+                type_name,
+                field_name: &field_name,
+                field_type,
+                count,
+            });
+        }
     }
 
     pub fn read_tag(&mut self) -> Result<&str, Error> {
         const USESTRINGVALUE: u8 = 0;
         const USESTRINGID: u8 = 1;
-        let tag_type = self.reader.u8();
+        let tag_type = self.reader.u8()?;
         if tag_type == USESTRINGID {
-            Ok(self.tags[self.reader.i16() as usize])
+            let index = self.reader.i16()?;
+            let tag = usize::try_from(index)
+                .ok()
+                .and_then(|index| self.tags.get(index));
+            Ok(tag.ok_or_else(|| anyhow!("Unknown tag index {index}"))?)
         } else if tag_type == USESTRINGVALUE {
-            self.reader.skip(8); // todo tag hash
-            let len = self.reader.i16();
-            let tag: &str =
-                unsafe { std::str::from_utf8_unchecked(self.reader.bytes(len as usize)) };
+            self.reader.skip(8)?; // todo tag hash
+            let len = self.reader.i16()?;
+            let len = usize::try_from(len).map_err(|_| anyhow!("Negative length {len}"))?;
+            let tag = match self.reader.bytes(len)? {
+                Cow::Borrowed(bytes) => Cow::Borrowed(
+                    std::str::from_utf8(bytes).map_err(|err| anyhow!("Invalid tag: {err}"))?,
+                ),
+                Cow::Owned(bytes) => Cow::Owned(
+                    String::from_utf8(bytes).map_err(|err| anyhow!("Invalid tag: {err}"))?,
+                ),
+            };
             self.tags.push(tag);
-            Ok(tag)
+            Ok(self.tags.last().unwrap())
         } else {
             Err(anyhow!("Unknown tag type, value:{tag_type}"))?
         }
@@ -16,60 +16,127 @@
 // under the License.
 
 use crate::buffer::{Reader, Writer};
+use crate::ensure;
 use crate::error::Error;
 use crate::fury::Fury;
 use crate::meta::TypeMeta;
 use std::any::TypeId;
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Default)]
 pub struct MetaReaderResolver {
-    reading_type_defs: Vec<Rc<TypeMeta>>,
+    reading_type_defs: Vec<Arc<TypeMeta>>,
+    // whether each type def comes with its index, which is the case with a `MetaContext`
+    indexed: bool,
+    // bound of the indices, `MetaContext::max_type_defs` of the reading side
+    max_type_defs: Option<usize>,
 }
 
 impl MetaReaderResolver {
-    pub fn get(&self, index: usize) -> &Rc<TypeMeta> {
-        unsafe { self.reading_type_defs.get_unchecked(index) }
+    pub fn get(&self, index: usize) -> &Arc<TypeMeta> {
+        &self.reading_type_defs[index]
     }
 
-    pub fn load(&mut self, reader: &mut Reader) {
-        let meta_size = reader.var_int32();
-        self.reading_type_defs.reserve(meta_size as usize);
+    /// Like [`MetaReaderResolver::get`], but `None` for an index the message didn't define.
+    pub fn try_get(&self, index: usize) -> Option<&Arc<TypeMeta>> {
+        self.reading_type_defs.get(index)
+    }
+
+    pub fn load(&mut self, reader: &mut Reader, fury: &Fury) -> Result<(), Error> {
+        let meta_size = reader.var_int32()?;
+        // each type meta takes at least a byte, a corrupt count can't reserve much
+        self.reading_type_defs
+            .reserve((meta_size as usize).min(reader.remaining()));
         for _ in 0..meta_size {
-            self.reading_type_defs
-                .push(Rc::new(TypeMeta::from_bytes(reader)));
+            let index = match self.indexed {
+                true => reader.var_int32()?,
+                false => self.reading_type_defs.len() as i32,
+            };
+            // a new index or one whose type def the writer evicted
+            ensure!(
+                index >= 0 && index as usize <= self.reading_type_defs.len(),
+                "Invalid type def index {index}, known type defs:{}",
+                self.reading_type_defs.len()
+            );
+            if let Some(max) = self.max_type_defs {
+                ensure!(
+                    (index as usize) < max,
+                    "Type def index {index} is beyond the {max} type defs `MetaContext::max_type_defs` keeps"
+                );
+            }
+            let mut meta = TypeMeta::from_bytes(reader)?;
+            // the id of a named type is the writer's, which needn't be ours
+            let local_id = meta.get_type_name().and_then(|(namespace, name)| {
+                fury.get_class_resolver()
+                    .get_id_by_type_name(namespace, name)
+            });
+            if let Some(id) = local_id {
+                meta.set_type_id(id);
+            }
+            match self.reading_type_defs.get_mut(index as usize) {
+                Some(evicted) => *evicted = Arc::new(meta),
+                None => self.reading_type_defs.push(Arc::new(meta)),
+            }
         }
+        Ok(())
     }
 }
 
+// type defs a `MetaContext` sent, by index
+struct SentTypeDefs {
+    // rust type of each type def and the number of the message that used it last
+    slots: Vec<(TypeId, u64)>,
+    // number of the message being written
+    message: u64,
+    max: usize,
+}
+
 #[derive(Default)]
 pub struct MetaWriterResolver<'a> {
-    type_defs: Vec<&'a Vec<u8>>,
+    // type defs written with this message, with their index
+    type_defs: Vec<(usize, &'a [u8])>,
     type_id_index_map: HashMap<TypeId, usize>,
+    // set when type defs are shared with earlier messages
+    sent: Option<SentTypeDefs>,
 }
 
 #[allow(dead_code)]
 impl<'a> MetaWriterResolver<'a> {
     pub fn push<'b: 'a>(&mut self, type_id: TypeId, fury: &'a Fury) -> usize {
-        match self.type_id_index_map.get(&type_id) {
-            None => {
-                let index = self.type_defs.len();
-                self.type_defs.push(
-                    fury.get_class_resolver()
-                        .get_class_info(type_id)
-                        .get_type_def(),
-                );
-                self.type_id_index_map.insert(type_id, index);
-                index
+        if let Some(&index) = self.type_id_index_map.get(&type_id) {
+            if let Some(sent) = &mut self.sent {
+                sent.slots[index].1 = sent.message;
             }
-            Some(index) => *index,
+            return index;
         }
+        let index = match &mut self.sent {
+            Some(sent) => {
+                let index = sent.next_index();
+                if let Some((evicted, _)) = sent.slots.get(index) {
+                    self.type_id_index_map.remove(evicted);
+                }
+                let slot = (type_id, sent.message);
+                match sent.slots.get_mut(index) {
+                    Some(evicted) => *evicted = slot,
+                    None => sent.slots.push(slot),
+                }
+                index
+            }
+            None => self.type_defs.len(),
+        };
+        self.type_defs
+            .push((index, fury.get_class_resolver().get_type_def(type_id, fury)));
+        self.type_id_index_map.insert(type_id, index);
+        index
     }
 
     pub fn to_bytes(&self, writer: &mut Writer) -> Result<(), Error> {
         writer.var_int32(self.type_defs.len() as i32);
-        for item in &self.type_defs {
+        for (index, item) in &self.type_defs {
+            if self.sent.is_some() {
+                writer.var_int32(*index as i32);
+            }
             writer.bytes(item)
         }
         Ok(())
@@ -79,3 +146,129 @@ impl<'a> MetaWriterResolver<'a> {
         self.type_defs.clear();
     }
 }
+
+impl SentTypeDefs {
+    // a new index below `max`, else the least recently used one that this message doesn't use,
+    // else a new one past `max`
+    fn next_index(&self) -> usize {
+        if self.slots.len() < self.max {
+            return self.slots.len();
+        }
+        self.slots
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, used))| *used < self.message)
+            .min_by_key(|(_, (_, used))| *used)
+            .map_or(self.slots.len(), |(index, _)| index)
+    }
+}
+
+/// Type metas shared by the messages of one connection, for each direction, like Java's
+/// `withMetaContextShare`: in compatible mode each type def is sent once, with the first
+/// message using it, later messages refer to it by index.
+///
+/// The messages of [`Fury::serialize_with_meta_context`] must be read with
+/// [`Fury::deserialize_with_meta_context`] in the order they were written, each exactly once,
+/// with a context of the reading side used for nothing else. After a reconnect both sides start
+/// over with new contexts, or [`MetaContext::reset`] ones. The same holds for the frames of
+/// [`Fury::serialize_framed_with_meta_context`] on a stream.
+///
+/// At most [`MetaContext::max_type_defs`] type defs are kept, beyond that the writing side
+/// evicts the least recently used one and sends the next new one under its index, which the
+/// reading side replaces.
+///
+/// [`Fury::serialize_with_meta_context`]: crate::fury::Fury::serialize_with_meta_context
+/// [`Fury::deserialize_with_meta_context`]: crate::fury::Fury::deserialize_with_meta_context
+/// [`Fury::serialize_framed_with_meta_context`]: crate::fury::Fury::serialize_framed_with_meta_context
+pub struct MetaContext {
+    // rust type -> index of its type def, of the messages written
+    sent: HashMap<TypeId, usize>,
+    // rust type and last use of each index sent, see `SentTypeDefs`
+    slots: Vec<(TypeId, u64)>,
+    // messages written so far
+    messages: u64,
+    max_type_defs: usize,
+    // type metas of the messages read, by index
+    received: MetaReaderResolver,
+}
+
+/// Type defs a [`MetaContext`] keeps unless configured otherwise.
+pub const DEFAULT_MAX_TYPE_DEFS: usize = 4096;
+
+impl Default for MetaContext {
+    fn default() -> Self {
+        MetaContext {
+            sent: HashMap::new(),
+            slots: Vec::new(),
+            messages: 0,
+            max_type_defs: DEFAULT_MAX_TYPE_DEFS,
+            received: MetaReaderResolver {
+                reading_type_defs: Vec::new(),
+                indexed: true,
+                max_type_defs: None,
+            },
+        }
+    }
+}
+
+impl MetaContext {
+    pub fn new() -> MetaContext {
+        MetaContext::default()
+    }
+
+    /// Keeps at most `max` type defs sent, evicting the least recently used one for a new one.
+    /// A message using more than `max` types at once still sends them all.
+    ///
+    /// Only the writing side evicts, the reading side replaces the type defs it is told to.
+    /// Reading fails for a message sending more type defs than `max` of the reading side, so
+    /// that a peer can't make it keep any number of them.
+    pub fn max_type_defs(mut self, max: usize) -> Self {
+        self.max_type_defs = max;
+        self
+    }
+
+    pub fn get_max_type_defs(&self) -> usize {
+        self.max_type_defs
+    }
+
+    /// Forgets the type defs sent and received so far.
+    pub fn reset(&mut self) {
+        *self = MetaContext::default().max_type_defs(self.max_type_defs);
+    }
+
+    /// Number of type defs sent with the messages written.
+    pub fn sent_count(&self) -> usize {
+        self.sent.len()
+    }
+
+    /// Number of type defs received with the messages read.
+    pub fn received_count(&self) -> usize {
+        self.received.reading_type_defs.len()
+    }
+
+    pub(crate) fn writer<'a>(&mut self) -> MetaWriterResolver<'a> {
+        self.messages += 1;
+        MetaWriterResolver {
+            type_defs: Vec::new(),
+            type_id_index_map: std::mem::take(&mut self.sent),
+            sent: Some(SentTypeDefs {
+                slots: std::mem::take(&mut self.slots),
+                message: self.messages,
+                max: self.max_type_defs,
+            }),
+        }
+    }
+
+    pub(crate) fn finish_writing(&mut self, resolver: MetaWriterResolver) {
+        self.sent = resolver.type_id_index_map;
+        if let Some(sent) = resolver.sent {
+            self.slots = sent.slots;
+        }
+    }
+
+    // swaps the type metas received so far with those of a read context
+    pub(crate) fn swap_received(&mut self, resolver: &mut MetaReaderResolver) {
+        self.received.max_type_defs = Some(self.max_type_defs);
+        std::mem::swap(&mut self.received, resolver);
+    }
+}
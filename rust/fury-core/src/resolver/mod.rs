@@ -18,3 +18,4 @@
 pub mod class_resolver;
 pub mod context;
 pub mod meta_resolver;
+pub mod ref_resolver;
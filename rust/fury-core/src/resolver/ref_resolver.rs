@@ -0,0 +1,208 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::buffer::Reader;
+use crate::ensure;
+use crate::error::Error;
+use anyhow::anyhow;
+use std::any::Any;
+use std::rc::Rc;
+
+/// Upper bound of referencable objects in one payload unless configured otherwise,
+/// see [`Fury::max_ref_count`](crate::fury::Fury::max_ref_count).
+pub const DEFAULT_MAX_REF_COUNT: usize = 1 << 20;
+
+/// Reading side of reference tracking.
+///
+/// Ref ids come from the payload, so they are never used as map keys or allocation sizes:
+/// ids are handed out sequentially into a dense table, every incoming id is checked against
+/// the table length before use, and the table can't grow past `max_ref_count` entries.
+pub struct RefReader {
+    refs: Vec<Option<Rc<dyn Any>>>,
+    max_ref_count: usize,
+    // id reserved by [`RefReader::reserve_ahead`], handed out by the next `reserve_ref_id`
+    ahead: Option<u32>,
+}
+
+impl RefReader {
+    pub fn new(max_ref_count: usize) -> RefReader {
+        RefReader {
+            refs: Vec::new(),
+            max_ref_count,
+            ahead: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.refs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.refs.is_empty()
+    }
+
+    /// Allocates the id of an object flagged `RefValue`, before the object itself is read.
+    pub fn reserve_ref_id(&mut self) -> Result<u32, Error> {
+        if let Some(id) = self.ahead.take() {
+            return Ok(id);
+        }
+        let id = self.refs.len();
+        ensure!(
+            id < self.max_ref_count,
+            "Too many referencable objects, max:{}",
+            self.max_ref_count
+        );
+        self.refs.push(None);
+        Ok(id as u32)
+    }
+
+    /// Allocates the id of an object flagged `RefValue` before its ref flag is read, e.g. by an
+    /// `Rc` that has to store a `Weak` to the object while it is read. The next
+    /// [`RefReader::reserve_ref_id`] returns this id instead of allocating another one.
+    pub fn reserve_ahead(&mut self) -> Result<u32, Error> {
+        let id = self.reserve_ref_id()?;
+        self.ahead = Some(id);
+        Ok(id)
+    }
+
+    /// Drops the id of [`RefReader::reserve_ahead`] if nothing took it, i.e. the object was read
+    /// by a serializer ignoring its ref flag.
+    pub fn clear_ahead(&mut self) {
+        self.ahead = None;
+    }
+
+    pub fn set_ref(&mut self, id: u32, value: Rc<dyn Any>) {
+        self.refs[id as usize] = Some(value);
+    }
+
+    /// Reads the id following a `Ref` flag and checks that it points to an already reserved slot.
+    pub fn read_ref_id(&self, reader: &mut Reader) -> Result<u32, Error> {
+        let id = reader.var_int32()?;
+        ensure!(
+            id >= 0 && (id as usize) < self.refs.len(),
+            "Invalid ref id, value:{id}, known refs:{}",
+            self.refs.len()
+        );
+        Ok(id as u32)
+    }
+
+    pub fn get_ref(&self, id: u32) -> Result<&Rc<dyn Any>, Error> {
+        match self.refs.get(id as usize) {
+            Some(Some(value)) => Ok(value),
+            Some(None) => Err(anyhow!("Ref id {id} is referenced before it is read"))?,
+            None => Err(anyhow!(
+                "Invalid ref id, value:{id}, known refs:{}",
+                self.refs.len()
+            ))?,
+        }
+    }
+}
+
+// the table is kept at most half full, so that probe sequences stay short
+const MIN_REF_SLOTS: usize = 16;
+
+/// Writing side of reference tracking, the ids of the objects written so far keyed by address.
+///
+/// Addresses are unique among live objects and mostly differ in their middle bits, so they are
+/// spread with a Fibonacci multiply instead of SipHash, into an open-addressing table probed
+/// linearly. The caller keeps the objects alive while the table is in use, e.g. by borrowing
+/// the graph being written.
+#[derive(Default)]
+pub struct RefWriter {
+    // (address, id), address 0 marks an empty slot
+    slots: Vec<(usize, u32)>,
+    len: usize,
+}
+
+impl RefWriter {
+    pub fn new() -> RefWriter {
+        RefWriter::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the id of the object at `ptr` and whether it is new, i.e. has to be written in
+    /// full rather than as a reference. Ids are handed out sequentially like [`RefReader`] does.
+    pub fn get_or_assign(&mut self, ptr: *const ()) -> (u32, bool) {
+        let address = ptr as usize;
+        assert!(address != 0, "null pointers can't be tracked");
+        if (self.len + 1) * 2 > self.slots.len() {
+            self.grow();
+        }
+        let mut index = self.slot_of(address);
+        loop {
+            match self.slots[index] {
+                (0, _) => {
+                    let id = self.len as u32;
+                    self.slots[index] = (address, id);
+                    self.len += 1;
+                    return (id, true);
+                }
+                (key, id) if key == address => return (id, false),
+                _ => index = (index + 1) & (self.slots.len() - 1),
+            }
+        }
+    }
+
+    /// Forgets the object at `ptr`, which must be the last one [`RefWriter::get_or_assign`]
+    /// handed an id, e.g. because it was written as null after all. Its id is handed out again.
+    pub fn unassign_last(&mut self, ptr: *const ()) {
+        let address = ptr as usize;
+        let mask = self.slots.len() - 1;
+        let mut index = self.slot_of(address);
+        while self.slots[index].0 != address {
+            index = (index + 1) & mask;
+        }
+        assert_eq!(
+            self.slots[index].1 as usize + 1,
+            self.len,
+            "not the last object"
+        );
+        // it took a slot that was empty when all others were inserted, none probed past it
+        self.slots[index] = (0, 0);
+        self.len -= 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = (0, 0));
+        self.len = 0;
+    }
+
+    fn slot_of(&self, address: usize) -> usize {
+        let bits = self.slots.len().trailing_zeros();
+        let hash = (address as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        (hash >> (64 - bits)) as usize
+    }
+
+    fn grow(&mut self) {
+        let capacity = (self.slots.len() * 2).max(MIN_REF_SLOTS);
+        let slots = std::mem::replace(&mut self.slots, vec![(0, 0); capacity]);
+        for (address, id) in slots.into_iter().filter(|(address, _)| *address != 0) {
+            let mut index = self.slot_of(address);
+            while self.slots[index].0 != 0 {
+                index = (index + 1) & (capacity - 1);
+            }
+            self.slots[index] = (address, id);
+        }
+    }
+}
@@ -0,0 +1,191 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use super::row::{ArrayGetter, Row};
+use super::writer::ArrayWriter;
+use crate::buffer::Writer;
+use byteorder::{ByteOrder, LittleEndian};
+use std::collections::HashMap;
+
+/// Default largest number of distinct values a [`StringColumn`] is dictionary encoded with.
+pub const DEFAULT_DICTIONARY_THRESHOLD: usize = 256;
+
+const PLAIN: u64 = 0;
+const DICTIONARY: u64 = 1;
+
+/// A string column which is written as a dictionary of its distinct values plus one `u32`
+/// index per value when it has few distinct values, or as a plain string array otherwise.
+///
+/// Dictionary layout, after the `u64` encoding tag:
+///
+/// ```text
+/// | u64 dictionary size in bytes | dictionary as string array | u64 count | u32 indices |
+/// ```
+pub struct StringColumn {
+    values: Vec<String>,
+    dictionary_threshold: usize,
+}
+
+impl StringColumn {
+    pub fn new(values: Vec<String>) -> StringColumn {
+        StringColumn {
+            values,
+            dictionary_threshold: DEFAULT_DICTIONARY_THRESHOLD,
+        }
+    }
+
+    /// Largest number of distinct values the column is dictionary encoded with.
+    pub fn dictionary_threshold(mut self, dictionary_threshold: usize) -> Self {
+        self.dictionary_threshold = dictionary_threshold;
+        self
+    }
+
+    pub fn get_dictionary_threshold(&self) -> usize {
+        self.dictionary_threshold
+    }
+
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+
+    // distinct values in order of appearance and the index of each value, `None` when the
+    // column has too many distinct values to be worth a dictionary
+    fn dictionary(&self) -> Option<(Vec<&str>, Vec<u32>)> {
+        let mut codes: HashMap<&str, u32> = HashMap::new();
+        let mut dictionary = Vec::new();
+        let mut indices = Vec::with_capacity(self.values.len());
+        for value in &self.values {
+            let code = *codes.entry(value.as_str()).or_insert_with(|| {
+                dictionary.push(value.as_str());
+                dictionary.len() as u32 - 1
+            });
+            if dictionary.len() > self.dictionary_threshold {
+                return None;
+            }
+            indices.push(code);
+        }
+        (dictionary.len() < self.values.len()).then_some((dictionary, indices))
+    }
+}
+
+impl From<Vec<String>> for StringColumn {
+    fn from(values: Vec<String>) -> Self {
+        StringColumn::new(values)
+    }
+}
+
+enum Encoded<'a> {
+    Plain(ArrayGetter<'a, String>),
+    Dictionary {
+        dictionary: ArrayGetter<'a, String>,
+        indices: &'a [u8],
+    },
+}
+
+/// Read view of a [`StringColumn`].
+pub struct StringColumnGetter<'a> {
+    encoded: Encoded<'a>,
+}
+
+impl<'a> StringColumnGetter<'a> {
+    pub fn size(&self) -> usize {
+        match &self.encoded {
+            Encoded::Plain(values) => values.size(),
+            Encoded::Dictionary { indices, .. } => indices.len() / 4,
+        }
+    }
+
+    pub fn get(&self, idx: usize) -> &'a str {
+        match &self.encoded {
+            Encoded::Plain(values) => values.get(idx),
+            Encoded::Dictionary { dictionary, .. } => {
+                dictionary.get(self.index(idx).unwrap() as usize)
+            }
+        }
+    }
+
+    pub fn is_dictionary_encoded(&self) -> bool {
+        matches!(self.encoded, Encoded::Dictionary { .. })
+    }
+
+    /// Distinct values in order of appearance, `None` for a plain column.
+    pub fn dictionary(&self) -> Option<&ArrayGetter<'a, String>> {
+        match &self.encoded {
+            Encoded::Plain(_) => None,
+            Encoded::Dictionary { dictionary, .. } => Some(dictionary),
+        }
+    }
+
+    /// Index in [`StringColumnGetter::dictionary`] of the value at `idx`, e.g. to group by it
+    /// without comparing strings. `None` for a plain column.
+    pub fn index(&self, idx: usize) -> Option<u32> {
+        match &self.encoded {
+            Encoded::Plain(_) => None,
+            Encoded::Dictionary { indices, .. } => {
+                if idx >= indices.len() / 4 {
+                    panic!("out of bound");
+                }
+                Some(LittleEndian::read_u32(&indices[idx * 4..idx * 4 + 4]))
+            }
+        }
+    }
+}
+
+impl<'a> Row<'a> for StringColumn {
+    type ReadResult = StringColumnGetter<'a>;
+
+    fn write(v: &Self, writer: &mut Writer) {
+        let Some((dictionary, indices)) = v.dictionary() else {
+            writer.u64(PLAIN);
+            <Vec<String> as Row>::write(&v.values, writer);
+            return;
+        };
+        writer.u64(DICTIONARY);
+        let size_offset = writer.len();
+        writer.skip(8);
+        let dictionary_start = writer.len();
+        let mut array_writer = ArrayWriter::new(dictionary.len(), writer);
+        for (idx, value) in dictionary.iter().enumerate() {
+            let callback_info = array_writer.write_start(idx);
+            array_writer.get_writer().bytes(value.as_bytes());
+            array_writer.write_end(callback_info);
+        }
+        let dictionary_size = writer.len() - dictionary_start;
+        writer.set_bytes(size_offset, &(dictionary_size as u64).to_le_bytes());
+        writer.u64(indices.len() as u64);
+        writer.reserve(indices.len() * 4);
+        for index in indices {
+            writer.u32(index);
+        }
+    }
+
+    fn cast(bytes: &'a [u8]) -> Self::ReadResult {
+        let encoded = match LittleEndian::read_u64(&bytes[0..8]) {
+            DICTIONARY => {
+                let dictionary_size = LittleEndian::read_u64(&bytes[8..16]) as usize;
+                let indices_start = 16 + dictionary_size;
+                let count = LittleEndian::read_u64(&bytes[indices_start..indices_start + 8]);
+                let indices = &bytes[indices_start + 8..];
+                Encoded::Dictionary {
+                    dictionary: <Vec<String> as Row>::cast(&bytes[16..indices_start]),
+                    indices: &indices[..count as usize * 4],
+                }
+            }
+            _ => Encoded::Plain(<Vec<String> as Row>::cast(&bytes[8..])),
+        };
+        StringColumnGetter { encoded }
+    }
+}
@@ -16,11 +16,17 @@
 // under the License.
 
 mod bit_util;
+mod dictionary;
 mod reader;
 #[allow(clippy::module_inception)]
 mod row;
+mod schema;
 mod writer;
 
+pub use dictionary::{StringColumn, StringColumnGetter, DEFAULT_DICTIONARY_THRESHOLD};
 pub use reader::{from_row, ArrayViewer, StructViewer};
-pub use row::Row;
+pub use row::{ArrayGetter, MapGetter, Row};
+pub use schema::{
+    RowArrayReader, RowField, RowMapReader, RowReader, RowSchema, RowType, RowValue, RowWriter,
+};
 pub use writer::{to_row, ArrayWriter, StructWriter};
@@ -20,6 +20,7 @@ use byteorder::{ByteOrder, LittleEndian};
 
 struct FieldAccessorHelper<'a> {
     row: &'a [u8],
+    bitmap_offset: usize,
     get_field_offset: Box<dyn Fn(usize) -> usize>,
 }
 
@@ -32,13 +33,22 @@ impl<'a> FieldAccessorHelper<'a> {
         (offset, size)
     }
 
-    pub fn new(row: &[u8], get_field_offset: Box<dyn Fn(usize) -> usize>) -> FieldAccessorHelper {
+    pub fn new(
+        row: &[u8],
+        bitmap_offset: usize,
+        get_field_offset: Box<dyn Fn(usize) -> usize>,
+    ) -> FieldAccessorHelper<'_> {
         FieldAccessorHelper {
             row,
+            bitmap_offset,
             get_field_offset,
         }
     }
 
+    pub fn is_null_at(&self, idx: usize) -> bool {
+        self.row[self.bitmap_offset + idx / 8] & (1 << (idx % 8)) != 0
+    }
+
     pub fn get_field_bytes(&self, idx: usize) -> &'a [u8] {
         let row = self.row;
         let (offset, size) = self.get_offset_size(idx);
@@ -56,6 +66,7 @@ impl<'r> StructViewer<'r> {
         StructViewer {
             field_accessor_helper: FieldAccessorHelper::new(
                 row,
+                0,
                 Box::new(move |idx: usize| bit_map_width_in_bytes + idx * 8),
             ),
         }
@@ -64,6 +75,11 @@ impl<'r> StructViewer<'r> {
     pub fn get_field_bytes(&self, idx: usize) -> &'r [u8] {
         self.field_accessor_helper.get_field_bytes(idx)
     }
+
+    /// Whether the value at `idx` was written as null.
+    pub fn is_null_at(&self, idx: usize) -> bool {
+        self.field_accessor_helper.is_null_at(idx)
+    }
 }
 
 pub struct ArrayViewer<'r> {
@@ -79,6 +95,7 @@ impl<'r> ArrayViewer<'r> {
             num_elements,
             field_accessor_helper: FieldAccessorHelper::new(
                 row,
+                8,
                 Box::new(move |idx: usize| 8 + bit_map_width_in_bytes + idx * 8),
             ),
         }
@@ -91,6 +108,11 @@ impl<'r> ArrayViewer<'r> {
     pub fn get_field_bytes(&self, idx: usize) -> &'r [u8] {
         self.field_accessor_helper.get_field_bytes(idx)
     }
+
+    /// Whether the value at `idx` was written as null.
+    pub fn is_null_at(&self, idx: usize) -> bool {
+        self.field_accessor_helper.is_null_at(idx)
+    }
 }
 
 pub struct MapViewer<'r> {
@@ -107,11 +129,11 @@ impl<'r> MapViewer<'r> {
         }
     }
 
-    pub fn get_key_row(&self) -> &[u8] {
+    pub fn get_key_row(&self) -> &'r [u8] {
         self.key_row
     }
 
-    pub fn get_value_row(&self) -> &[u8] {
+    pub fn get_value_row(&self) -> &'r [u8] {
         self.value_row
     }
 }
@@ -20,7 +20,8 @@ use crate::{buffer::Writer, error::Error};
 use anyhow::anyhow;
 use byteorder::{ByteOrder, LittleEndian};
 use chrono::{DateTime, Days, NaiveDate, NaiveDateTime};
-use std::collections::BTreeMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 use std::marker::PhantomData;
 
 use super::{
@@ -34,6 +35,16 @@ pub trait Row<'a> {
     fn write(v: &Self, writer: &mut Writer);
 
     fn cast(bytes: &'a [u8]) -> Self::ReadResult;
+
+    /// Whether `v` is left out and marked in the null bitmap of its struct or array instead.
+    fn is_null(_v: &Self) -> bool {
+        false
+    }
+
+    /// What a value marked null is read as, only nullable types such as `Option` have one.
+    fn cast_null() -> Self::ReadResult {
+        panic!("null value of a type that isn't nullable")
+    }
 }
 
 fn read_i8_from_bytes(bytes: &[u8]) -> i8 {
@@ -133,6 +144,29 @@ impl<'a> Row<'a> for Vec<u8> {
     }
 }
 
+/// A nullable value, `None` is only marked in the null bitmap of the enclosing struct or array.
+impl<'a, T: Row<'a>> Row<'a> for Option<T> {
+    type ReadResult = Option<T::ReadResult>;
+
+    fn write(v: &Self, writer: &mut Writer) {
+        if let Some(v) = v {
+            T::write(v, writer);
+        }
+    }
+
+    fn cast(bytes: &'a [u8]) -> Self::ReadResult {
+        Some(T::cast(bytes))
+    }
+
+    fn is_null(v: &Self) -> bool {
+        v.is_none()
+    }
+
+    fn cast_null() -> Self::ReadResult {
+        None
+    }
+}
+
 pub struct ArrayGetter<'a, T> {
     array_data: ArrayViewer<'a>,
     _marker: PhantomData<T>,
@@ -147,21 +181,36 @@ impl<'a, T: Row<'a>> ArrayGetter<'a, T> {
         if idx >= self.array_data.num_elements() {
             panic!("out of bound");
         }
+        if self.array_data.is_null_at(idx) {
+            return <T as Row>::cast_null();
+        }
         let bytes = self.array_data.get_field_bytes(idx);
         <T as Row>::cast(bytes)
     }
 }
 
+// writes `items` as an array, marking the null ones in its bitmap
+fn write_array<'a, 'v, T: Row<'a> + 'v>(
+    items: impl ExactSizeIterator<Item = &'v T>,
+    writer: &mut Writer,
+) {
+    let mut array_writer = ArrayWriter::new(items.len(), writer);
+    items.enumerate().for_each(|(idx, item)| {
+        if <T as Row>::is_null(item) {
+            array_writer.set_null_at(idx);
+            return;
+        }
+        let callback_info = array_writer.write_start(idx);
+        <T as Row>::write(item, array_writer.get_writer());
+        array_writer.write_end(callback_info);
+    });
+}
+
 impl<'a, T: Row<'a>> Row<'a> for Vec<T> {
     type ReadResult = ArrayGetter<'a, T>;
 
     fn write(v: &Self, writer: &mut Writer) {
-        let mut array_writer = ArrayWriter::new(v.len(), writer);
-        v.iter().enumerate().for_each(|(idx, item)| {
-            let callback_info = array_writer.write_start(idx);
-            <T as Row>::write(item, array_writer.get_writer());
-            array_writer.write_end(callback_info);
-        });
+        write_array(v.iter(), writer);
     }
 
     fn cast(row: &'a [u8]) -> Self::ReadResult {
@@ -172,69 +221,85 @@ impl<'a, T: Row<'a>> Row<'a> for Vec<T> {
     }
 }
 
-pub struct MapGetter<'a, T1, T2>
-where
-    T1: Ord,
-    T2: Ord,
-{
+/// Entries of a map, keys in ascending order, each found by an offset like array elements.
+pub struct MapGetter<'a, T1, T2> {
     map_data: MapViewer<'a>,
     _key_marker: PhantomData<T1>,
     _value_marker: PhantomData<T2>,
 }
 
-impl<'a, T1: Row<'a> + Ord, T2: Row<'a> + Ord> MapGetter<'a, T1, T2> {
-    pub fn to_btree_map(&'a self) -> Result<BTreeMap<T1::ReadResult, T2::ReadResult>, Error>
-    where
-        <T1 as Row<'a>>::ReadResult: Ord,
-    {
-        let mut map = BTreeMap::new();
-        let keys = self.keys();
-        let values = self.values();
-
-        for i in 0..self.keys().size() {
-            map.insert(keys.get(i), values.get(i));
-        }
-        Ok(map)
+impl<'a, T1: Row<'a>, T2: Row<'a>> MapGetter<'a, T1, T2> {
+    pub fn size(&self) -> usize {
+        self.keys().size()
     }
 
-    pub fn keys(&'a self) -> ArrayGetter<'a, T1> {
+    pub fn keys(&self) -> ArrayGetter<'a, T1> {
         ArrayGetter {
             array_data: ArrayViewer::new(self.map_data.get_key_row()),
             _marker: PhantomData::<T1>,
         }
     }
 
-    pub fn values(&'a self) -> ArrayGetter<'a, T2> {
+    pub fn values(&self) -> ArrayGetter<'a, T2> {
         ArrayGetter {
             array_data: ArrayViewer::new(self.map_data.get_value_row()),
             _marker: PhantomData::<T2>,
         }
     }
+
+    /// The value of `key`, found by a binary search over the keys without decoding the others.
+    pub fn get(&self, key: &T1::ReadResult) -> Option<T2::ReadResult>
+    where
+        T1::ReadResult: Ord,
+    {
+        let keys = self.keys();
+        let (mut low, mut high) = (0, keys.size());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match keys.get(mid).cmp(key) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Some(self.values().get(mid)),
+            }
+        }
+        None
+    }
+
+    pub fn to_btree_map(&self) -> Result<BTreeMap<T1::ReadResult, T2::ReadResult>, Error>
+    where
+        T1::ReadResult: Ord,
+    {
+        let keys = self.keys();
+        let values = self.values();
+        Ok((0..keys.size())
+            .map(|i| (keys.get(i), values.get(i)))
+            .collect())
+    }
+}
+
+// writes the entries of a map with their keys in ascending order
+fn write_map<'a, 'v, T1: Row<'a> + 'v, T2: Row<'a> + 'v>(
+    entries: &[(&'v T1, &'v T2)],
+    writer: &mut Writer,
+) {
+    let mut map_writter = MapWriter::new(writer);
+    let callback_info = map_writter.write_start(0);
+    write_array(
+        entries.iter().map(|(key, _)| *key),
+        map_writter.get_writer(),
+    );
+    map_writter.write_end(callback_info);
+    write_array(
+        entries.iter().map(|(_, value)| *value),
+        map_writter.get_writer(),
+    );
 }
 
-impl<'a, T1: Row<'a> + Ord, T2: Row<'a> + Ord> Row<'a> for BTreeMap<T1, T2> {
+impl<'a, T1: Row<'a> + Ord, T2: Row<'a>> Row<'a> for BTreeMap<T1, T2> {
     type ReadResult = MapGetter<'a, T1, T2>;
 
     fn write(v: &Self, writer: &mut Writer) {
-        let mut map_writter = MapWriter::new(writer);
-        {
-            let callback_info = map_writter.write_start(0);
-            let mut array_writer = ArrayWriter::new(v.len(), map_writter.get_writer());
-            v.keys().enumerate().for_each(|(idx, item)| {
-                let callback_info = array_writer.write_start(idx);
-                <T1 as Row>::write(item, array_writer.get_writer());
-                array_writer.write_end(callback_info);
-            });
-            map_writter.write_end(callback_info);
-        }
-        {
-            let mut array_writer = ArrayWriter::new(v.len(), map_writter.get_writer());
-            v.values().enumerate().for_each(|(idx, item)| {
-                let callback_info = array_writer.write_start(idx);
-                <T2 as Row>::write(item, array_writer.get_writer());
-                array_writer.write_end(callback_info);
-            });
-        }
+        write_map(&v.iter().collect::<Vec<_>>(), writer);
     }
 
     fn cast(row: &'a [u8]) -> Self::ReadResult {
@@ -245,3 +310,32 @@ impl<'a, T1: Row<'a> + Ord, T2: Row<'a> + Ord> Row<'a> for BTreeMap<T1, T2> {
         }
     }
 }
+
+/// Written like a `BTreeMap`, sorted by key.
+impl<'a, T1: Row<'a> + Ord, T2: Row<'a>, S> Row<'a> for HashMap<T1, T2, S> {
+    type ReadResult = MapGetter<'a, T1, T2>;
+
+    fn write(v: &Self, writer: &mut Writer) {
+        let mut entries = v.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+        write_map(&entries, writer);
+    }
+
+    fn cast(row: &'a [u8]) -> Self::ReadResult {
+        <BTreeMap<T1, T2> as Row>::cast(row)
+    }
+}
+
+/// The 16 bytes of the UUID, most significant first.
+#[cfg(feature = "uuid")]
+impl Row<'_> for uuid::Uuid {
+    type ReadResult = Self;
+
+    fn write(v: &Self, writer: &mut Writer) {
+        writer.bytes(v.as_bytes());
+    }
+
+    fn cast(bytes: &[u8]) -> Self::ReadResult {
+        uuid::Uuid::from_slice(&bytes[..16]).expect("16 bytes")
+    }
+}
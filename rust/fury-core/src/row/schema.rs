@@ -0,0 +1,499 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Row format schemas built at runtime, e.g. by query engines, see [`RowSchema`].
+
+use super::reader::{ArrayViewer, MapViewer, StructViewer};
+use super::row::Row;
+use super::writer::{ArrayWriter, MapWriter, StructWriter};
+use crate::buffer::Writer;
+use crate::ensure;
+use crate::error::Error;
+use anyhow::anyhow;
+use chrono::{NaiveDate, NaiveDateTime};
+
+/// Type of a field of a [`RowSchema`], written like the [`Row`] type it is named after.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RowType {
+    Bool,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    String,
+    Binary,
+    Date,
+    Timestamp,
+    Array(Box<RowType>),
+    // key type, value type
+    Map(Box<RowType>, Box<RowType>),
+    Struct(RowSchema),
+}
+
+/// Fields of a row, the runtime counterpart of `#[derive(FuryRow)]`.
+///
+/// Fields are ordered by name like derived structs order them, so a row written with a schema
+/// can be read through the derived struct with the same fields and the other way around.
+/// Indices refer to that order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RowSchema {
+    fields: Vec<(String, RowType)>,
+}
+
+impl RowSchema {
+    pub fn new() -> RowSchema {
+        RowSchema::default()
+    }
+
+    /// Adds a field, panics if the schema already has one named `name`.
+    pub fn field(mut self, name: &str, row_type: RowType) -> Self {
+        match self
+            .fields
+            .binary_search_by(|(field, _)| field.as_str().cmp(name))
+        {
+            Ok(_) => panic!("Row schema already has a field named `{name}`"),
+            Err(index) => self.fields.insert(index, (name.to_string(), row_type)),
+        }
+        self
+    }
+
+    pub fn num_fields(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.fields
+            .binary_search_by(|(field, _)| field.as_str().cmp(name))
+            .ok()
+    }
+
+    pub fn field_name(&self, index: usize) -> &str {
+        &self.fields[index].0
+    }
+
+    pub fn field_type(&self, index: usize) -> &RowType {
+        &self.fields[index].1
+    }
+}
+
+/// A field of a [`RowSchema`], by index or by name.
+pub trait RowField {
+    fn index_in(&self, schema: &RowSchema) -> Result<usize, Error>;
+}
+
+impl RowField for usize {
+    fn index_in(&self, schema: &RowSchema) -> Result<usize, Error> {
+        ensure!(
+            *self < schema.num_fields(),
+            "Field index {self} is out of bounds, the row has {} fields",
+            schema.num_fields()
+        );
+        Ok(*self)
+    }
+}
+
+impl RowField for &str {
+    fn index_in(&self, schema: &RowSchema) -> Result<usize, Error> {
+        schema
+            .field_index(self)
+            .ok_or_else(|| anyhow!("Row has no field named `{self}`").into())
+    }
+}
+
+/// Value of a field, owned for writing and decoded by [`RowReader::get`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum RowValue {
+    Bool(bool),
+    Int8(i8),
+    Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    Float32(f32),
+    Float64(f64),
+    String(String),
+    Binary(Vec<u8>),
+    Date(NaiveDate),
+    Timestamp(NaiveDateTime),
+    Array(Vec<RowValue>),
+    // entries in the order they are written, derived `MapGetter`s expect ascending keys
+    Map(Vec<(RowValue, RowValue)>),
+    // field values in schema order
+    Struct(Vec<RowValue>),
+}
+
+impl RowValue {
+    // checks the value against the type before anything is written
+    fn check(&self, row_type: &RowType) -> Result<(), Error> {
+        match (row_type, self) {
+            (RowType::Bool, RowValue::Bool(_))
+            | (RowType::Int8, RowValue::Int8(_))
+            | (RowType::Int16, RowValue::Int16(_))
+            | (RowType::Int32, RowValue::Int32(_))
+            | (RowType::Int64, RowValue::Int64(_))
+            | (RowType::Float32, RowValue::Float32(_))
+            | (RowType::Float64, RowValue::Float64(_))
+            | (RowType::String, RowValue::String(_))
+            | (RowType::Binary, RowValue::Binary(_))
+            | (RowType::Date, RowValue::Date(_))
+            | (RowType::Timestamp, RowValue::Timestamp(_)) => Ok(()),
+            (RowType::Array(element_type), RowValue::Array(elements)) => elements
+                .iter()
+                .try_for_each(|element| element.check(element_type)),
+            (RowType::Map(key_type, value_type), RowValue::Map(entries)) => {
+                entries.iter().try_for_each(|(key, value)| {
+                    key.check(key_type)?;
+                    value.check(value_type)
+                })
+            }
+            (RowType::Struct(schema), RowValue::Struct(values)) => {
+                ensure!(
+                    values.len() == schema.num_fields(),
+                    "Struct value has {} fields, its schema has {}",
+                    values.len(),
+                    schema.num_fields()
+                );
+                values
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(index, value)| value.check(schema.field_type(index)))
+            }
+            (row_type, value) => Err(anyhow!("Value {value:?} isn't of type {row_type:?}").into()),
+        }
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        match self {
+            RowValue::Bool(v) => <bool as Row>::write(v, writer),
+            RowValue::Int8(v) => <i8 as Row>::write(v, writer),
+            RowValue::Int16(v) => <i16 as Row>::write(v, writer),
+            RowValue::Int32(v) => <i32 as Row>::write(v, writer),
+            RowValue::Int64(v) => <i64 as Row>::write(v, writer),
+            RowValue::Float32(v) => <f32 as Row>::write(v, writer),
+            RowValue::Float64(v) => <f64 as Row>::write(v, writer),
+            RowValue::String(v) => <String as Row>::write(v, writer),
+            RowValue::Binary(v) => <Vec<u8> as Row>::write(v, writer),
+            RowValue::Date(v) => <NaiveDate as Row>::write(v, writer),
+            RowValue::Timestamp(v) => <NaiveDateTime as Row>::write(v, writer),
+            RowValue::Array(elements) => write_array(elements.iter(), writer),
+            RowValue::Map(entries) => {
+                let mut map_writer = MapWriter::new(writer);
+                let callback_info = map_writer.write_start(0);
+                write_array(entries.iter().map(|(key, _)| key), map_writer.get_writer());
+                map_writer.write_end(callback_info);
+                write_array(
+                    entries.iter().map(|(_, value)| value),
+                    map_writer.get_writer(),
+                );
+            }
+            RowValue::Struct(values) => write_struct(values.iter(), values.len(), writer),
+        }
+    }
+
+    fn read(row_type: &RowType, bytes: &[u8]) -> Result<RowValue, Error> {
+        Ok(match row_type {
+            RowType::Bool => RowValue::Bool(<bool as Row>::cast(bytes)),
+            RowType::Int8 => RowValue::Int8(<i8 as Row>::cast(bytes)),
+            RowType::Int16 => RowValue::Int16(<i16 as Row>::cast(bytes)),
+            RowType::Int32 => RowValue::Int32(<i32 as Row>::cast(bytes)),
+            RowType::Int64 => RowValue::Int64(<i64 as Row>::cast(bytes)),
+            RowType::Float32 => RowValue::Float32(<f32 as Row>::cast(bytes)),
+            RowType::Float64 => RowValue::Float64(<f64 as Row>::cast(bytes)),
+            RowType::String => RowValue::String(<String as Row>::cast(bytes).to_string()),
+            RowType::Binary => RowValue::Binary(bytes.to_vec()),
+            RowType::Date => RowValue::Date(<NaiveDate as Row>::cast(bytes)?),
+            RowType::Timestamp => RowValue::Timestamp(<NaiveDateTime as Row>::cast(bytes)?),
+            RowType::Array(element_type) => {
+                let array = RowArrayReader::new(element_type, bytes);
+                RowValue::Array(
+                    (0..array.size())
+                        .map(|index| array.get(index))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            RowType::Map(key_type, value_type) => {
+                let map = RowMapReader::new(key_type, value_type, bytes);
+                let (keys, values) = (map.keys(), map.values());
+                RowValue::Map(
+                    (0..map.size())
+                        .map(|index| Ok((keys.get(index)?, values.get(index)?)))
+                        .collect::<Result<_, Error>>()?,
+                )
+            }
+            RowType::Struct(schema) => {
+                let row = RowReader::new(schema, bytes);
+                RowValue::Struct(
+                    (0..schema.num_fields())
+                        .map(|index| row.get(index))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+        })
+    }
+}
+
+fn write_array<'v>(elements: impl ExactSizeIterator<Item = &'v RowValue>, writer: &mut Writer) {
+    let mut array_writer = ArrayWriter::new(elements.len(), writer);
+    for (index, element) in elements.enumerate() {
+        let callback_info = array_writer.write_start(index);
+        element.write(array_writer.get_writer());
+        array_writer.write_end(callback_info);
+    }
+}
+
+fn write_struct<'v>(
+    values: impl Iterator<Item = &'v RowValue>,
+    num_fields: usize,
+    writer: &mut Writer,
+) {
+    let mut struct_writer = StructWriter::new(num_fields, writer);
+    for (index, value) in values.enumerate() {
+        let callback_info = struct_writer.write_start(index);
+        value.write(struct_writer.get_writer());
+        struct_writer.write_end(callback_info);
+    }
+}
+
+/// Builds a row of a [`RowSchema`] field by field, in any order.
+pub struct RowWriter<'s> {
+    schema: &'s RowSchema,
+    values: Vec<Option<RowValue>>,
+}
+
+impl<'s> RowWriter<'s> {
+    pub fn new(schema: &'s RowSchema) -> RowWriter<'s> {
+        RowWriter {
+            schema,
+            values: vec![None; schema.num_fields()],
+        }
+    }
+
+    /// Sets a field, failing if the value doesn't match its type.
+    pub fn set(&mut self, field: impl RowField, value: RowValue) -> Result<&mut Self, Error> {
+        let index = field.index_in(self.schema)?;
+        value
+            .check(self.schema.field_type(index))
+            .map_err(|err| anyhow!("Field `{}`: {err}", self.schema.field_name(index)))?;
+        self.values[index] = Some(value);
+        Ok(self)
+    }
+
+    /// Writes the row, every field must have been set.
+    pub fn finish(&self) -> Result<Vec<u8>, Error> {
+        if let Some(index) = self.values.iter().position(Option::is_none) {
+            return Err(anyhow!("Field `{}` is not set", self.schema.field_name(index)).into());
+        }
+        let mut writer = Writer::default();
+        write_struct(self.values.iter().flatten(), self.values.len(), &mut writer);
+        Ok(writer.dump())
+    }
+}
+
+/// Reads the fields of a row written with a [`RowSchema`], typed accessors fail when the
+/// field has another type.
+pub struct RowReader<'a> {
+    schema: &'a RowSchema,
+    viewer: StructViewer<'a>,
+}
+
+macro_rules! impl_typed_getters {
+    ($($name: ident, $variant: ident, $ty: ty);* $(;)?) => {
+        $(
+            pub fn $name(&self, field: impl RowField) -> Result<<$ty as Row<'a>>::ReadResult, Error> {
+                Ok(<$ty as Row>::cast(self.field_bytes(field, |row_type| *row_type == RowType::$variant)?))
+            }
+        )*
+    };
+}
+
+impl<'a> RowReader<'a> {
+    pub fn new(schema: &'a RowSchema, row: &'a [u8]) -> RowReader<'a> {
+        RowReader {
+            schema,
+            viewer: StructViewer::new(row, schema.num_fields()),
+        }
+    }
+
+    pub fn schema(&self) -> &'a RowSchema {
+        self.schema
+    }
+
+    fn field_bytes(
+        &self,
+        field: impl RowField,
+        is_expected: impl Fn(&RowType) -> bool,
+    ) -> Result<&'a [u8], Error> {
+        let index = field.index_in(self.schema)?;
+        let row_type = self.schema.field_type(index);
+        ensure!(
+            is_expected(row_type),
+            "Field `{}` is of type {row_type:?}",
+            self.schema.field_name(index)
+        );
+        Ok(self.viewer.get_field_bytes(index))
+    }
+
+    impl_typed_getters!(
+        get_bool, Bool, bool;
+        get_i8, Int8, i8;
+        get_i16, Int16, i16;
+        get_i32, Int32, i32;
+        get_i64, Int64, i64;
+        get_f32, Float32, f32;
+        get_f64, Float64, f64;
+        get_str, String, String;
+        get_binary, Binary, Vec<u8>;
+    );
+
+    pub fn get_date(&self, field: impl RowField) -> Result<NaiveDate, Error> {
+        <NaiveDate as Row>::cast(self.field_bytes(field, |row_type| *row_type == RowType::Date)?)
+    }
+
+    pub fn get_timestamp(&self, field: impl RowField) -> Result<NaiveDateTime, Error> {
+        <NaiveDateTime as Row>::cast(
+            self.field_bytes(field, |row_type| *row_type == RowType::Timestamp)?,
+        )
+    }
+
+    pub fn get_struct(&self, field: impl RowField) -> Result<RowReader<'a>, Error> {
+        let index = field.index_in(self.schema)?;
+        match self.schema.field_type(index) {
+            RowType::Struct(schema) => {
+                Ok(RowReader::new(schema, self.viewer.get_field_bytes(index)))
+            }
+            row_type => Err(anyhow!(
+                "Field `{}` is of type {row_type:?}",
+                self.schema.field_name(index)
+            )
+            .into()),
+        }
+    }
+
+    pub fn get_array(&self, field: impl RowField) -> Result<RowArrayReader<'a>, Error> {
+        let index = field.index_in(self.schema)?;
+        match self.schema.field_type(index) {
+            RowType::Array(element_type) => Ok(RowArrayReader::new(
+                element_type,
+                self.viewer.get_field_bytes(index),
+            )),
+            row_type => Err(anyhow!(
+                "Field `{}` is of type {row_type:?}",
+                self.schema.field_name(index)
+            )
+            .into()),
+        }
+    }
+
+    pub fn get_map(&self, field: impl RowField) -> Result<RowMapReader<'a>, Error> {
+        let index = field.index_in(self.schema)?;
+        match self.schema.field_type(index) {
+            RowType::Map(key_type, value_type) => Ok(RowMapReader::new(
+                key_type,
+                value_type,
+                self.viewer.get_field_bytes(index),
+            )),
+            row_type => Err(anyhow!(
+                "Field `{}` is of type {row_type:?}",
+                self.schema.field_name(index)
+            )
+            .into()),
+        }
+    }
+
+    /// Decodes a field whatever its type, copying strings and binaries.
+    pub fn get(&self, field: impl RowField) -> Result<RowValue, Error> {
+        let index = field.index_in(self.schema)?;
+        RowValue::read(
+            self.schema.field_type(index),
+            self.viewer.get_field_bytes(index),
+        )
+    }
+}
+
+/// Elements of an array field, see [`RowReader::get_array`].
+pub struct RowArrayReader<'a> {
+    element_type: &'a RowType,
+    viewer: ArrayViewer<'a>,
+}
+
+impl<'a> RowArrayReader<'a> {
+    fn new(element_type: &'a RowType, bytes: &'a [u8]) -> RowArrayReader<'a> {
+        RowArrayReader {
+            element_type,
+            viewer: ArrayViewer::new(bytes),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.viewer.num_elements()
+    }
+
+    pub fn element_type(&self) -> &'a RowType {
+        self.element_type
+    }
+
+    pub fn get(&self, index: usize) -> Result<RowValue, Error> {
+        ensure!(
+            index < self.size(),
+            "Element index {index} is out of bounds, the array has {} elements",
+            self.size()
+        );
+        RowValue::read(self.element_type, self.viewer.get_field_bytes(index))
+    }
+
+    pub fn get_struct(&self, index: usize) -> Result<RowReader<'a>, Error> {
+        ensure!(
+            index < self.size(),
+            "Element index {index} is out of bounds, the array has {} elements",
+            self.size()
+        );
+        match self.element_type {
+            RowType::Struct(schema) => {
+                Ok(RowReader::new(schema, self.viewer.get_field_bytes(index)))
+            }
+            row_type => Err(anyhow!("Elements are of type {row_type:?}").into()),
+        }
+    }
+}
+
+/// Entries of a map field as two arrays of the same size, see [`RowReader::get_map`].
+pub struct RowMapReader<'a> {
+    keys: RowArrayReader<'a>,
+    values: RowArrayReader<'a>,
+}
+
+impl<'a> RowMapReader<'a> {
+    fn new(key_type: &'a RowType, value_type: &'a RowType, bytes: &'a [u8]) -> RowMapReader<'a> {
+        let viewer = MapViewer::new(bytes);
+        RowMapReader {
+            keys: RowArrayReader::new(key_type, viewer.get_key_row()),
+            values: RowArrayReader::new(value_type, viewer.get_value_row()),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.keys.size()
+    }
+
+    pub fn keys(&self) -> &RowArrayReader<'a> {
+        &self.keys
+    }
+
+    pub fn values(&self) -> &RowArrayReader<'a> {
+        &self.values
+    }
+}
@@ -27,6 +27,7 @@ pub struct WriteCallbackInfo {
 struct FieldWriterHelper<'a> {
     pub writer: &'a mut Writer,
     base_offset: usize,
+    bitmap_offset: usize,
     get_field_offset: Box<dyn Fn(usize) -> usize>,
 }
 
@@ -34,15 +35,24 @@ impl<'a> FieldWriterHelper<'a> {
     fn new(
         writer: &'a mut Writer,
         base_offset: usize,
+        bitmap_offset: usize,
         get_field_offset: Box<dyn Fn(usize) -> usize>,
     ) -> FieldWriterHelper<'a> {
         FieldWriterHelper {
             writer,
             base_offset,
+            bitmap_offset,
             get_field_offset,
         }
     }
 
+    // sets bit `idx % 8` of byte `idx / 8` of the null bitmap, the field itself stays empty
+    fn set_null_at(&mut self, idx: usize) {
+        let byte = self.bitmap_offset + idx / 8;
+        let bits = self.writer.as_slice()[byte] | 1 << (idx % 8);
+        self.writer.set_bytes(byte, &[bits]);
+    }
+
     fn write_start(&mut self, idx: usize) -> WriteCallbackInfo {
         let base_offset = self.base_offset;
         let field_offset = (self.get_field_offset)(idx);
@@ -71,7 +81,7 @@ impl StructWriter<'_> {
     fn get_fixed_size(bit_map_width_in_bytes: usize, num_fields: usize) -> usize {
         bit_map_width_in_bytes + num_fields * 8
     }
-    pub fn new(num_fields: usize, writer: &mut Writer) -> StructWriter {
+    pub fn new(num_fields: usize, writer: &mut Writer) -> StructWriter<'_> {
         let base_offset = writer.len();
         let bit_map_width_in_bytes = calculate_bitmap_width_in_bytes(num_fields);
 
@@ -79,6 +89,7 @@ impl StructWriter<'_> {
             field_writer_helper: FieldWriterHelper::new(
                 writer,
                 base_offset,
+                base_offset,
                 Box::new(move |idx| base_offset + bit_map_width_in_bytes + idx * 8),
             ),
         };
@@ -99,6 +110,11 @@ impl StructWriter<'_> {
     pub fn write_end(&mut self, callback_info: WriteCallbackInfo) {
         self.field_writer_helper.write_end(callback_info)
     }
+
+    /// Marks the value at `idx` as null instead of writing it.
+    pub fn set_null_at(&mut self, idx: usize) {
+        self.field_writer_helper.set_null_at(idx)
+    }
 }
 
 pub struct ArrayWriter<'a> {
@@ -110,13 +126,14 @@ impl ArrayWriter<'_> {
         8 + bit_map_width_in_bytes + num_fields * 8
     }
 
-    pub fn new(num_fields: usize, writer: &mut Writer) -> ArrayWriter {
+    pub fn new(num_fields: usize, writer: &mut Writer) -> ArrayWriter<'_> {
         let base_offset = writer.len();
         let bit_map_width_in_bytes = calculate_bitmap_width_in_bytes(num_fields);
         let array_writer = ArrayWriter {
             field_writer_helper: FieldWriterHelper::new(
                 writer,
                 base_offset,
+                base_offset + 8,
                 Box::new(move |idx| 8 + base_offset + bit_map_width_in_bytes + idx * 8),
             ),
         };
@@ -141,6 +158,11 @@ impl ArrayWriter<'_> {
     pub fn write_end(&mut self, callback_info: WriteCallbackInfo) {
         self.field_writer_helper.write_end(callback_info)
     }
+
+    /// Marks the value at `idx` as null instead of writing it.
+    pub fn set_null_at(&mut self, idx: usize) {
+        self.field_writer_helper.set_null_at(idx)
+    }
 }
 
 pub struct MapWriter<'a> {
@@ -154,7 +176,7 @@ impl MapWriter<'_> {
         8
     }
 
-    pub fn new(writer: &mut Writer) -> MapWriter {
+    pub fn new(writer: &mut Writer) -> MapWriter<'_> {
         let base_offset = writer.len();
         let array_writer = MapWriter {
             writer,
@@ -0,0 +1,35 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+/// A field of the peer's type that the local type doesn't have, skipped by the
+/// compatible-mode reader.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SkippedField<'a> {
+    pub type_name: &'a str,
+    pub field_name: &'a str,
+    pub field_type: i16,
+    // times it was skipped in the message, e.g. once per element of a list of structs
+    pub count: usize,
+}
+
+/// Observes schema drift between peers, see [`Fury::schema_event_listener`](crate::fury::Fury::schema_event_listener).
+///
+/// Events of a message are delivered on the deserializing thread once it has been read, so
+/// they can be attributed to the peer it came from.
+pub trait SchemaEventListener: Send + Sync {
+    fn on_skipped_field(&self, event: &SkippedField);
+}
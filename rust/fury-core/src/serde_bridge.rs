@@ -0,0 +1,643 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Fury data for types implementing serde's traits rather than deriving `Fury`, see [`Serde`].
+//!
+//! Values go through a [`Value`]: structs and maps are written as maps, keyed by field name for
+//! structs, sequences and tuples as lists, unit variants as their name and other variants as a
+//! map of their name to their fields. Peers read them like a Java `Map<String, Object>`.
+
+use crate::error::Error;
+use crate::fury::Fury;
+use crate::resolver::context::{ReadContext, WriteContext};
+use crate::serializer::{BorrowDeserialize, Serializer};
+use crate::types::{FieldType, FuryGeneralList};
+use crate::value::Value;
+use anyhow::anyhow;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::{de, forward_to_deserialize_any, ser, Serialize};
+use std::fmt;
+
+/// Writes `value` through its `serde::Serialize` impl, as described in the module docs.
+pub fn from_serde<T: Serialize + ?Sized>(
+    value: &T,
+    context: &mut WriteContext,
+) -> Result<(), Error> {
+    let value = value
+        .serialize(ValueSerializer)
+        .map_err(|err| anyhow!("Can't write `{}`: {err}", std::any::type_name::<T>()))?;
+    value.write(context)
+}
+
+/// Reads a `T` through its `serde::Deserialize` impl, from what [`from_serde`] wrote or any
+/// other value [`Value`] can read.
+pub fn to_serde<T: DeserializeOwned>(context: &mut ReadContext) -> Result<T, Error> {
+    let value = Value::read(context, None)?;
+    Ok(T::deserialize(ValueDeserializer(value))
+        .map_err(|err| anyhow!("Can't read `{}`: {err}", std::any::type_name::<T>()))?)
+}
+
+/// A `T` written and read through its serde impls, see [`from_serde`] and [`to_serde`], e.g.
+/// `fury.serialize(&Serde(config))` or a field `Serde<Config>` of a struct deriving `Fury`.
+///
+/// Like a `Box<dyn Any>` it declares nothing about the value, which carries its own type ids.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Serde<T>(pub T);
+
+impl<T: Serialize + DeserializeOwned> Serializer for Serde<T> {
+    fn reserved_space() -> usize {
+        0
+    }
+
+    fn write(&self, _context: &mut WriteContext) {
+        panic!("unreachable")
+    }
+
+    fn read(_context: &mut ReadContext) -> Result<Self, Error> {
+        panic!("unreachable")
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::FuryTypeTag.into()
+    }
+
+    fn serialize(&self, context: &mut WriteContext) {
+        if let Err(err) = from_serde(&self.0, context) {
+            panic!("{err}")
+        }
+    }
+
+    fn deserialize(context: &mut ReadContext) -> Result<Self, Error> {
+        to_serde(context).map(Serde)
+    }
+}
+
+impl<'bf, T: Serialize + DeserializeOwned> BorrowDeserialize<'bf> for Serde<T> {
+    fn read_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        <Self as Serializer>::read(context)
+    }
+
+    fn deserialize_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        <Self as Serializer>::deserialize(context)
+    }
+}
+
+impl<T> FuryGeneralList for Serde<T> {}
+
+// message of an error raised by a serde impl
+#[derive(Debug)]
+struct SerdeError(String);
+
+impl fmt::Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for SerdeError {}
+
+impl ser::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeError(msg.to_string())
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeError(msg.to_string())
+    }
+}
+
+// a variant with fields, as a map of its name to them
+fn variant(name: &str, fields: Value) -> Value {
+    Value::Map(vec![(Value::String(name.to_string()), fields)])
+}
+
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = SerdeError;
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeList;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, SerdeError> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, SerdeError> {
+        Ok(Value::I8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, SerdeError> {
+        Ok(Value::I16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, SerdeError> {
+        Ok(Value::I32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, SerdeError> {
+        Ok(Value::I64(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, SerdeError> {
+        Ok(Value::U8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, SerdeError> {
+        Ok(Value::U16(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, SerdeError> {
+        Ok(Value::U32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, SerdeError> {
+        Ok(Value::U64(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, SerdeError> {
+        Ok(Value::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, SerdeError> {
+        Ok(Value::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, SerdeError> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, SerdeError> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, SerdeError> {
+        Ok(Value::Binary(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Value, SerdeError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, SerdeError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, SerdeError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value, SerdeError> {
+        Ok(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value, SerdeError> {
+        Ok(variant(name, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList, SerdeError> {
+        Ok(SerializeList::new(len.unwrap_or(0), None))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeList, SerdeError> {
+        Ok(SerializeList::new(len, None))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, SerdeError> {
+        Ok(SerializeList::new(len, None))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, SerdeError> {
+        Ok(SerializeList::new(len, Some(name)))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, SerdeError> {
+        Ok(SerializeMap::new(len.unwrap_or(0), None))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap, SerdeError> {
+        Ok(SerializeMap::new(len, None))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeMap, SerdeError> {
+        Ok(SerializeMap::new(len, Some(name)))
+    }
+}
+
+// elements of a sequence, tuple or tuple variant
+struct SerializeList {
+    elements: Vec<Value>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeList {
+    fn new(len: usize, variant: Option<&'static str>) -> SerializeList {
+        SerializeList {
+            elements: Vec::with_capacity(len),
+            variant,
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.elements.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, SerdeError> {
+        let list = Value::List(self.elements);
+        Ok(match self.variant {
+            Some(name) => variant(name, list),
+            None => list,
+        })
+    }
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeList {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+// entries of a map, struct or struct variant
+struct SerializeMap {
+    entries: Vec<(Value, Value)>,
+    key: Option<Value>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeMap {
+    fn new(len: usize, variant: Option<&'static str>) -> SerializeMap {
+        SerializeMap {
+            entries: Vec::with_capacity(len),
+            key: None,
+            variant,
+        }
+    }
+
+    fn push_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        let value = value.serialize(ValueSerializer)?;
+        self.entries.push((Value::String(key.to_string()), value));
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, SerdeError> {
+        let map = Value::Map(self.entries);
+        Ok(match self.variant {
+            Some(name) => variant(name, map),
+            None => map,
+        })
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.key = Some(key.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| SerdeError("Map value without a key".to_string()))?;
+        self.entries.push((key, value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.push_field(key, value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.push_field(key, value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+struct ValueDeserializer(Value);
+
+impl<'de> IntoDeserializer<'de, SerdeError> for ValueDeserializer {
+    type Deserializer = ValueDeserializer;
+
+    fn into_deserializer(self) -> ValueDeserializer {
+        self
+    }
+}
+
+// visits `elements` as a sequence, failing if the visitor leaves some
+fn visit_seq<'de, V, I>(visitor: V, elements: I) -> Result<V::Value, SerdeError>
+where
+    V: Visitor<'de>,
+    I: Iterator,
+    I::Item: IntoDeserializer<'de, SerdeError>,
+{
+    let mut seq = SeqDeserializer::new(elements);
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+// visits `entries` as a map, failing if the visitor leaves some
+fn visit_map<'de, V, K>(
+    visitor: V,
+    entries: impl Iterator<Item = (K, ValueDeserializer)>,
+) -> Result<V::Value, SerdeError>
+where
+    V: Visitor<'de>,
+    K: IntoDeserializer<'de, SerdeError>,
+{
+    let mut map = MapDeserializer::new(entries);
+    let value = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(value)
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.0 {
+            Value::Null | Value::Ignored { .. } => visitor.visit_unit(),
+            Value::Bool(v) => visitor.visit_bool(v),
+            Value::I8(v) => visitor.visit_i8(v),
+            Value::U8(v) => visitor.visit_u8(v),
+            Value::I16(v) => visitor.visit_i16(v),
+            Value::U16(v) => visitor.visit_u16(v),
+            Value::I32(v) => visitor.visit_i32(v),
+            Value::U32(v) => visitor.visit_u32(v),
+            Value::I64(v) => visitor.visit_i64(v),
+            Value::U64(v) => visitor.visit_u64(v),
+            Value::F32(v) => visitor.visit_f32(v),
+            Value::F64(v) => visitor.visit_f64(v),
+            Value::String(v) => visitor.visit_string(v),
+            Value::Binary(v) => visitor.visit_byte_buf(v),
+            Value::Date(v) => visitor.visit_string(v.to_string()),
+            Value::Timestamp(v) => visitor.visit_string(v.to_string()),
+            Value::BoolArray(v) => visit_seq(visitor, v.into_iter()),
+            Value::I16Array(v) => visit_seq(visitor, v.into_iter()),
+            Value::I32Array(v) => visit_seq(visitor, v.into_iter()),
+            Value::I64Array(v) => visit_seq(visitor, v.into_iter()),
+            Value::F32Array(v) => visit_seq(visitor, v.into_iter()),
+            Value::F64Array(v) => visit_seq(visitor, v.into_iter()),
+            Value::List(v) | Value::Set(v) => {
+                visit_seq(visitor, v.into_iter().map(ValueDeserializer))
+            }
+            Value::Map(entries) => visit_map(
+                visitor,
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (ValueDeserializer(key), ValueDeserializer(value))),
+            ),
+            Value::Enum { variant, .. } => visitor.visit_string(variant),
+            Value::Struct { fields, .. } => visit_map(
+                visitor,
+                fields
+                    .into_iter()
+                    .map(|(name, value)| (name, ValueDeserializer(value))),
+            ),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.0 {
+            Value::String(name) | Value::Enum { variant: name, .. } => {
+                visitor.visit_enum(name.into_deserializer())
+            }
+            Value::Map(entries) if entries.len() == 1 => {
+                let (name, fields) = entries.into_iter().next().unwrap();
+                match name {
+                    Value::String(name) => visitor.visit_enum(VariantDeserializer { name, fields }),
+                    name => Err(de::Error::custom(format!(
+                        "Expected the name of a variant, got {name:?}"
+                    ))),
+                }
+            }
+            value => Err(de::Error::custom(format!(
+                "Expected a variant, got {value:?}"
+            ))),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+// a variant with fields, read from a map of its name to them
+struct VariantDeserializer {
+    name: String,
+    fields: Value,
+}
+
+impl<'de> de::EnumAccess<'de> for VariantDeserializer {
+    type Error = SerdeError;
+    type Variant = ValueDeserializer;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, ValueDeserializer), SerdeError> {
+        let variant = seed.deserialize(self.name.into_deserializer())?;
+        Ok((variant, ValueDeserializer(self.fields)))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for ValueDeserializer {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.0 {
+            Value::Null => Ok(()),
+            value => Err(de::Error::custom(format!(
+                "Expected a unit variant, got {value:?}"
+            ))),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
@@ -15,13 +15,30 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::buffer::Reader;
+use crate::ensure;
 use crate::error::Error;
 use crate::fury::Fury;
+use crate::meta::{read_meta_string, write_meta_string, TypeMeta};
+use crate::resolver::class_resolver::named_type_id;
 use crate::resolver::context::{ReadContext, WriteContext};
-use crate::serializer::Serializer;
-use crate::types::{FieldType, Mode, RefFlag};
+use crate::serializer::{deserialize_with, guards_struct_length, Serializer};
+use crate::types::{FieldType, FuryGeneralList, Mode, RefFlag, UnknownTypePolicy};
+use crate::value::{OpaqueValue, Value};
 use anyhow::anyhow;
-use std::any::Any;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+/// Header of a builtin value held by a `Box<dyn Any>` in compatible mode, followed by the value
+/// with its own ref flag and type id. There structs are written with the index of their type
+/// meta, which could be mistaken for the type id of a builtin.
+pub(crate) const DYN_BUILTIN: i16 = -1;
+
+/// Header of a value of a type registered by name held by a `Box<dyn Any>` in schema-consistent
+/// mode, followed by the namespace and name of the type, then the value with its own ref flag and
+/// type id. Peers look the type up by its name, the id is only meaningful to Rust.
+pub(crate) const DYN_NAMED: i16 = -2;
 
 impl Serializer for Box<dyn Any> {
     fn reserved_space() -> usize {
@@ -41,43 +58,64 @@ impl Serializer for Box<dyn Any> {
     }
 
     fn serialize(&self, context: &mut WriteContext) {
-        context
-            .get_fury()
-            .get_class_resolver()
-            .get_harness_by_type(self.as_ref().type_id())
-            .unwrap()
-            .get_serializer()(self.as_ref(), context);
+        serialize_dyn(self.as_ref(), context)
     }
 
     fn deserialize(context: &mut ReadContext) -> Result<Self, Error> {
         let reset_cursor = context.reader.reset_cursor_to_here();
         // ref flag
-        let ref_flag = context.reader.i8();
+        let ref_flag = context.reader.i8()?;
 
         if ref_flag == (RefFlag::NotNullValue as i8) || ref_flag == (RefFlag::RefValue as i8) {
-            if context.get_fury().get_mode().eq(&Mode::Compatible) {
-                let meta_index = context.reader.i16();
-                let type_id = context.meta_resolver.get(meta_index as usize).get_type_id();
-                reset_cursor(&mut context.reader);
-                context
-                    .get_fury()
-                    .get_class_resolver()
-                    .get_harness(type_id)
-                    .unwrap()
-                    .get_deserializer()(context)
+            let header = context.reader.i16()?;
+            let meta = if context.get_fury().get_mode().eq(&Mode::Compatible) {
+                if header == DYN_BUILTIN {
+                    return read_dyn_builtin(context);
+                }
+                context.meta_resolver.try_get(header as usize).cloned()
             } else {
-                let type_id = context.reader.i16();
-                reset_cursor(&mut context.reader);
-                context
-                    .get_fury()
-                    .get_class_resolver()
-                    .get_harness(type_id as u32)
-                    .unwrap()
-                    .get_deserializer()(context)
+                if header == DYN_NAMED {
+                    return read_dyn_named(context);
+                }
+                None
+            };
+            let type_id = meta
+                .as_ref()
+                .map_or(header as u32, |meta| meta.get_type_id());
+            match context.get_fury().get_class_resolver().get_harness(type_id) {
+                Some(harness) => {
+                    reset_cursor(&mut context.reader);
+                    harness.deserialize(context)
+                }
+                None if is_unknown_type(context, header, meta.as_deref()) => {
+                    context
+                        .get_fury()
+                        .get_class_resolver()
+                        .check_unregistered(type_id)?;
+                    match context.get_unknown_types() {
+                        UnknownTypePolicy::Error => {
+                            Err(anyhow!("`Box<dyn Any>` holds unregistered type id {type_id}"))?
+                        }
+                        UnknownTypePolicy::SkipAsNone => Err(anyhow!(
+                            "`Box<dyn Any>` holds unregistered type id {type_id}, only an `Option<Box<dyn Any>>` can skip it"
+                        ))?,
+                        UnknownTypePolicy::CaptureOpaque => {
+                            reset_cursor(&mut context.reader);
+                            capture_opaque(context, type_id, meta.as_deref())
+                        }
+                    }
+                }
+                None => {
+                    if ref_flag == (RefFlag::RefValue as i8) {
+                        context.ref_reader.reserve_ref_id()?;
+                    }
+                    read_builtin(context, header)
+                }
             }
         } else if ref_flag == (RefFlag::Null as i8) {
             Err(anyhow!("Try to deserialize `any` to null"))?
         } else if ref_flag == (RefFlag::Ref as i8) {
+            context.ref_reader.read_ref_id(&mut context.reader)?;
             reset_cursor(&mut context.reader);
             Err(Error::Ref)
         } else {
@@ -86,3 +124,277 @@ impl Serializer for Box<dyn Any> {
         }
     }
 }
+
+/// Writes `value` as a `Box<dyn Any>` holding it does, with the serializer registered for its
+/// type or as one of the builtins.
+pub(crate) fn serialize_dyn(value: &dyn Any, context: &mut WriteContext) {
+    let harness = context
+        .get_fury()
+        .get_class_resolver()
+        .get_harness_by_type(value.type_id());
+    let Some(harness) = harness else {
+        return serialize_builtin(value, context);
+    };
+    let fury = context.get_fury();
+    if fury.get_mode().eq(&Mode::SchemaConsistent) {
+        let class_resolver = fury.get_class_resolver();
+        let type_name = class_resolver
+            .get_registered_id(value.type_id())
+            .and_then(|id| class_resolver.get_type_name(id));
+        if let Some((namespace, name)) = type_name {
+            context.writer.i8(RefFlag::NotNullValue as i8);
+            context.writer.i16(DYN_NAMED);
+            write_meta_string(context.writer, namespace)
+                .and_then(|_| write_meta_string(context.writer, name))
+                .expect("type names are checked when registered");
+        }
+    }
+    harness.serialize(value, context)
+}
+
+// Types a `Box<dyn Any>` can hold without registering them, like the `Object` values of a Java
+// `Map<String, Object>`. Lists and maps of them nest, lists of other elements are read back as
+// `Vec<Box<dyn Any>>` and maps as `HashMap<String, Box<dyn Any>>`.
+macro_rules! serialize_builtin {
+    ($($ty: ty),* $(,)?) => {
+        fn serialize_builtin(value: &dyn Any, context: &mut WriteContext) {
+            if context.get_fury().get_mode().eq(&Mode::Compatible) {
+                context.writer.i8(RefFlag::NotNullValue as i8);
+                context.writer.i16(DYN_BUILTIN);
+            }
+            $(
+                if let Some(value) = value.downcast_ref::<$ty>() {
+                    return value.serialize(context);
+                }
+            )*
+            panic!("`Box<dyn Any>` holds a value of a type that isn't registered")
+        }
+    };
+}
+
+serialize_builtin!(
+    bool,
+    i8,
+    u8,
+    i16,
+    u16,
+    i32,
+    u32,
+    i64,
+    u64,
+    f32,
+    f64,
+    String,
+    NaiveDate,
+    NaiveDateTime,
+    Vec<u8>,
+    Vec<bool>,
+    Vec<i16>,
+    Vec<i32>,
+    Vec<i64>,
+    Vec<f32>,
+    Vec<f64>,
+    Vec<Box<dyn Any>>,
+    HashMap<String, Box<dyn Any>>,
+);
+
+// type id of the registered type whose type meta has index `header`, falls back to the header
+fn meta_type_id(context: &ReadContext, header: i16) -> u32 {
+    match context.meta_resolver.try_get(header as usize) {
+        Some(meta) => meta.get_type_id(),
+        None => header as u32,
+    }
+}
+
+/// Whether the value after `header` is of a type that isn't registered here, rather than a
+/// builtin. `meta` is the type meta `header` refers to in compatible mode.
+pub(crate) fn is_unknown_type(context: &ReadContext, header: i16, meta: Option<&TypeMeta>) -> bool {
+    if meta.is_none() && (header == DYN_BUILTIN || FieldType::try_from(header).is_ok()) {
+        return false;
+    }
+    let type_id = meta.map_or(header as u32, |meta| meta.get_type_id());
+    context
+        .get_fury()
+        .get_class_resolver()
+        .get_harness(type_id)
+        .is_none()
+}
+
+// reads a value of the unregistered type `type_id` as an `OpaqueValue`, from its ref flag
+fn capture_opaque(
+    context: &mut ReadContext,
+    type_id: u32,
+    meta: Option<&TypeMeta>,
+) -> Result<Box<dyn Any>, Error> {
+    if context.reader.i8()? == RefFlag::RefValue as i8 {
+        context.ref_reader.reserve_ref_id()?;
+    }
+    context.reader.i16()?;
+    let value = match meta {
+        Some(meta) => {
+            let mut fields = Vec::with_capacity(meta.get_field_info().len());
+            for field in meta.get_field_info() {
+                let value = Value::read(context, Some(field.get_field_id()))?;
+                fields.push((field.get_field_name().to_string(), value));
+            }
+            Value::Struct {
+                type_id: None,
+                fields,
+            }
+        }
+        None => {
+            ensure!(
+                guards_struct_length(context.get_fury()),
+                "Can't capture a value of the unregistered type {}, it must be written with its type meta or `Fury::struct_length_guard`",
+                type_id
+            );
+            let len = context.reader.u32()?;
+            Value::Binary(context.reader.bytes(len as usize)?.into_owned())
+        }
+    };
+    Ok(Box::new(OpaqueValue { type_id, value }))
+}
+
+/// Reads the namespace and name following `DYN_NAMED`.
+pub(crate) fn read_dyn_name(reader: &mut Reader) -> Result<(String, String), Error> {
+    let namespace = read_meta_string(reader)?;
+    let name = read_meta_string(reader)?;
+    Ok((namespace, name))
+}
+
+// reads what follows `DYN_NAMED` as the type registered here under that name
+fn read_dyn_named(context: &mut ReadContext) -> Result<Box<dyn Any>, Error> {
+    let (namespace, name) = read_dyn_name(&mut context.reader)?;
+    let fury = context.get_fury();
+    let class_resolver = fury.get_class_resolver();
+    let harness = class_resolver
+        .get_id_by_type_name(&namespace, &name)
+        .and_then(|id| class_resolver.get_harness(id));
+    match (harness, context.get_unknown_types()) {
+        (Some(harness), _) => harness.deserialize(context),
+        (None, UnknownTypePolicy::CaptureOpaque) => {
+            capture_opaque(context, named_type_id(&namespace, &name), None)
+        }
+        _ => Err(anyhow!(
+            "`Box<dyn Any>` holds `{namespace}.{name}`, which isn't registered here"
+        ))?,
+    }
+}
+
+// reads what follows `DYN_BUILTIN`
+fn read_dyn_builtin(context: &mut ReadContext) -> Result<Box<dyn Any>, Error> {
+    let ref_flag = context.reader.i8()?;
+    ensure!(
+        ref_flag == RefFlag::NotNullValue as i8,
+        "Unknown ref flag, value:{}",
+        ref_flag
+    );
+    let type_id = context.reader.i16()?;
+    read_builtin(context, type_id)
+}
+
+fn boxed<T: Serializer + 'static>(
+    context: &mut ReadContext,
+    type_id: i16,
+) -> Result<Box<dyn Any>, Error> {
+    Ok(Box::new(T::read_coerced(context, type_id)?))
+}
+
+// reads the body of a value of one of the types of `serialize_builtin`
+fn read_builtin(context: &mut ReadContext, type_id: i16) -> Result<Box<dyn Any>, Error> {
+    let field_type = FieldType::try_from(type_id)
+        .map_err(|_| anyhow!("`Box<dyn Any>` holds unregistered type id {type_id}"))?;
+    match field_type {
+        FieldType::BOOL => boxed::<bool>(context, type_id),
+        FieldType::INT8 => boxed::<i8>(context, type_id),
+        FieldType::UINT8 => boxed::<u8>(context, type_id),
+        FieldType::INT16 => boxed::<i16>(context, type_id),
+        FieldType::UINT16 => boxed::<u16>(context, type_id),
+        FieldType::INT32 => boxed::<i32>(context, type_id),
+        FieldType::UINT32 => boxed::<u32>(context, type_id),
+        FieldType::INT64 => boxed::<i64>(context, type_id),
+        FieldType::UINT64 => boxed::<u64>(context, type_id),
+        FieldType::FLOAT => boxed::<f32>(context, type_id),
+        FieldType::DOUBLE => boxed::<f64>(context, type_id),
+        FieldType::STRING => boxed::<String>(context, type_id),
+        FieldType::DATE => boxed::<NaiveDate>(context, type_id),
+        FieldType::TIMESTAMP => boxed::<NaiveDateTime>(context, type_id),
+        FieldType::BINARY => boxed::<Vec<u8>>(context, type_id),
+        FieldType::FuryPrimitiveBoolArray | FieldType::FuryPackedBoolArray => {
+            boxed::<Vec<bool>>(context, type_id)
+        }
+        FieldType::FuryPrimitiveShortArray => boxed::<Vec<i16>>(context, type_id),
+        FieldType::FuryPrimitiveIntArray => boxed::<Vec<i32>>(context, type_id),
+        FieldType::FuryPrimitiveLongArray => boxed::<Vec<i64>>(context, type_id),
+        FieldType::FuryPrimitiveFloatArray => boxed::<Vec<f32>>(context, type_id),
+        FieldType::FuryPrimitiveDoubleArray => boxed::<Vec<f64>>(context, type_id),
+        FieldType::ARRAY => boxed::<Vec<Box<dyn Any>>>(context, type_id),
+        FieldType::MAP | FieldType::FuryLinkedHashMap | FieldType::FuryTreeMap => {
+            boxed::<HashMap<String, Box<dyn Any>>>(context, type_id)
+        }
+        _ => Err(anyhow!(
+            "`Box<dyn Any>` can't hold values of type id {type_id}"
+        ))?,
+    }
+}
+
+impl FuryGeneralList for Box<dyn Any> {}
+
+/// Reads a `Box<dyn Any>` field restricted to some registered types, see
+/// `#[fury(allowed = [A, B])]`. The type is checked before its serializer runs.
+pub fn deserialize_any_allowed(
+    context: &mut ReadContext,
+    field: &str,
+    allowed: &[TypeId],
+) -> Result<Box<dyn Any>, Error> {
+    let reset_cursor = context.reader.reset_cursor_to_here();
+    let ref_flag = context.reader.i8()?;
+    if ref_flag == (RefFlag::NotNullValue as i8) || ref_flag == (RefFlag::RefValue as i8) {
+        let class_resolver = context.get_fury().get_class_resolver();
+        let type_id = if context.get_fury().get_mode().eq(&Mode::Compatible) {
+            let meta_index = context.reader.i16()?;
+            meta_type_id(context, meta_index)
+        } else {
+            match context.reader.i16()? {
+                DYN_NAMED => {
+                    let (namespace, name) = read_dyn_name(&mut context.reader)?;
+                    class_resolver
+                        .get_id_by_type_name(&namespace, &name)
+                        .ok_or_else(|| {
+                            anyhow!("Field `{field}` holds `{namespace}.{name}`, which isn't registered here")
+                        })?
+                }
+                type_id => type_id as u32,
+            }
+        };
+        let harness = class_resolver
+            .get_harness(type_id)
+            .ok_or_else(|| anyhow!("Field `{field}` holds unregistered type id {type_id}"))?;
+        ensure!(
+            allowed.contains(&harness.get_rust_type_id()),
+            "Field `{}` doesn't allow values of type `{}`",
+            field,
+            harness.get_type_name()
+        );
+    }
+    reset_cursor(&mut context.reader);
+    <Box<dyn Any> as Serializer>::deserialize(context)
+}
+
+/// [`deserialize_any_allowed`] for each element of a `Vec<Box<dyn Any>>` field.
+pub fn deserialize_any_vec_allowed(
+    context: &mut ReadContext,
+    field: &str,
+    allowed: &[TypeId],
+) -> Result<Vec<Box<dyn Any>>, Error> {
+    deserialize_with(context, |context| {
+        let len = context.read_elements_len()?;
+        context.record_elements::<Box<dyn Any>>(len);
+        (0..len)
+            .map(|_| {
+                context.check_cancelled()?;
+                deserialize_any_allowed(context, field, allowed)
+            })
+            .collect()
+    })
+}
@@ -0,0 +1,263 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Boolean arrays: the length as a varint, then a byte per element. With
+//! [`Fury::packed_bools`] they are packed instead, `(len + 7) / 8` bytes where bit `i % 8` of
+//! byte `i / 8` holds element `i`, padding bits are zero.
+
+use crate::error::Error;
+use crate::fury::Fury;
+use crate::resolver::context::ReadContext;
+use crate::resolver::context::WriteContext;
+use crate::serializer::Serializer;
+use crate::types::FieldType;
+use std::mem;
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+#[cfg(target_arch = "x86_64")]
+const MIN_DIM_SIZE_SIMD: usize = 16;
+
+fn packed_len(len: usize) -> usize {
+    (len + 7) / 8
+}
+
+fn pack_standard(bools: &[bool], bits: &mut [u8]) {
+    for (byte, chunk) in bits.iter_mut().zip(bools.chunks(8)) {
+        *byte = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |byte, (i, b)| byte | ((*b as u8) << i));
+    }
+}
+
+fn unpack_standard(bits: &[u8], bools: &mut [bool]) {
+    for (i, b) in bools.iter_mut().enumerate() {
+        *b = bits[i / 8] & (1 << (i % 8)) != 0;
+    }
+}
+
+// a bool is 0 or 1, so shifting each byte left by 7 moves it into the bit movemask collects
+#[cfg(target_arch = "x86_64")]
+unsafe fn pack_sse(bools: &[bool], bits: &mut [u8]) {
+    let range_end = bools.len() - bools.len() % MIN_DIM_SIZE_SIMD;
+    for i in (0..range_end).step_by(MIN_DIM_SIZE_SIMD) {
+        let chunk = _mm_loadu_si128(bools.as_ptr().add(i) as *const __m128i);
+        let mask = _mm_movemask_epi8(_mm_slli_epi16(chunk, 7)) as u16;
+        bits[i / 8..i / 8 + 2].copy_from_slice(&mask.to_le_bytes());
+    }
+    pack_standard(&bools[range_end..], &mut bits[range_end / 8..]);
+}
+
+// broadcasts each byte over 8 lanes and tests lane `i` against bit `i`
+#[cfg(target_arch = "x86_64")]
+unsafe fn unpack_sse(bits: &[u8], bools: &mut [bool]) {
+    let range_end = bools.len() - bools.len() % MIN_DIM_SIZE_SIMD;
+    let lane_bits = _mm_set1_epi64x(0x8040201008040201u64 as i64);
+    let one = _mm_set1_epi8(1);
+    for i in (0..range_end).step_by(MIN_DIM_SIZE_SIMD) {
+        let lo = bits[i / 8] as u64 * 0x0101010101010101;
+        let hi = bits[i / 8 + 1] as u64 * 0x0101010101010101;
+        let chunk = _mm_and_si128(_mm_set_epi64x(hi as i64, lo as i64), lane_bits);
+        let result = _mm_and_si128(_mm_cmpeq_epi8(chunk, lane_bits), one);
+        _mm_storeu_si128(bools.as_mut_ptr().add(i) as *mut __m128i, result);
+    }
+    unpack_standard(&bits[range_end / 8..], &mut bools[range_end..]);
+}
+
+/// Packs `bools` into `bits`, which must hold `(bools.len() + 7) / 8` zeroed bytes.
+pub fn pack_bools(bools: &[bool], bits: &mut [u8]) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("sse2") && bools.len() >= MIN_DIM_SIZE_SIMD {
+            return unsafe { pack_sse(bools, bits) };
+        }
+    }
+    pack_standard(bools, bits)
+}
+
+/// Unpacks the first `bools.len()` bits of `bits`.
+pub fn unpack_bools(bits: &[u8], bools: &mut [bool]) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("sse2") && bools.len() >= MIN_DIM_SIZE_SIMD {
+            return unsafe { unpack_sse(bits, bools) };
+        }
+    }
+    unpack_standard(bits, bools)
+}
+
+// the packed form is the only one with a type id of its own
+fn type_id(packed: bool) -> i16 {
+    if packed {
+        FieldType::FuryPackedBoolArray.into()
+    } else {
+        FieldType::FuryPrimitiveBoolArray.into()
+    }
+}
+
+fn write_bytes(context: &mut WriteContext, len: usize, bools: impl Iterator<Item = bool>) {
+    context.writer.var_int32(len as i32);
+    let bytes: Vec<u8> = bools.map(u8::from).collect();
+    context.writer.bytes(&bytes);
+}
+
+fn write_packed(context: &mut WriteContext, len: usize, bits: &[u8]) {
+    context.use_experimental_format();
+    context.writer.var_int32(len as i32);
+    context.writer.bytes(bits);
+}
+
+fn read_bools(context: &mut ReadContext, packed: bool) -> Result<Vec<bool>, Error> {
+    let len = context.read_collection_len()?;
+    if !packed {
+        return Ok(context.reader.bytes(len)?.iter().map(|b| *b != 0).collect());
+    }
+    let bits = context.reader.bytes(packed_len(len))?;
+    let mut bools = vec![false; len];
+    unpack_bools(&bits, &mut bools);
+    Ok(bools)
+}
+
+// either form is read whatever `Fury::packed_bools` is
+fn read_either<T: Serializer>(
+    context: &mut ReadContext,
+    actual_type_id: i16,
+    read: impl FnOnce(&mut ReadContext, bool) -> Result<T, Error>,
+) -> Result<T, Error> {
+    for packed in [false, true] {
+        if actual_type_id == type_id(packed) {
+            return read(context, packed);
+        }
+    }
+    Err(Error::TypeMismatch {
+        expected: T::get_type_id(context.get_fury()),
+        actual: actual_type_id,
+    })
+}
+
+impl Serializer for Vec<bool> {
+    fn write(&self, context: &mut WriteContext) {
+        if context.get_fury().is_packed_bools() {
+            let mut bits = vec![0; packed_len(self.len())];
+            pack_bools(self, &mut bits);
+            write_packed(context, self.len(), &bits);
+        } else {
+            write_bytes(context, self.len(), self.iter().copied());
+        }
+    }
+
+    fn reserved_space() -> usize {
+        mem::size_of::<u8>()
+    }
+
+    fn get_type_id(fury: &Fury) -> i16 {
+        type_id(fury.is_packed_bools())
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        let packed = context.get_fury().is_packed_bools();
+        read_bools(context, packed)
+    }
+
+    fn read_coerced(context: &mut ReadContext, actual_type_id: i16) -> Result<Self, Error> {
+        read_either(context, actual_type_id, read_bools)
+    }
+}
+
+#[cfg(feature = "fixedbitset")]
+fn read_bit_set(
+    context: &mut ReadContext,
+    packed: bool,
+) -> Result<fixedbitset::FixedBitSet, Error> {
+    if !packed {
+        let bools = read_bools(context, false)?;
+        let mut set = fixedbitset::FixedBitSet::with_capacity(bools.len());
+        for (i, b) in bools.into_iter().enumerate() {
+            set.set(i, b);
+        }
+        return Ok(set);
+    }
+    let len = context.read_collection_len()?;
+    let bits = context.reader.bytes(packed_len(len))?;
+    let blocks = bits.chunks(mem::size_of::<u32>()).map(|chunk| {
+        let mut block = [0; 4];
+        block[..chunk.len()].copy_from_slice(chunk);
+        u32::from_le_bytes(block)
+    });
+    Ok(fixedbitset::FixedBitSet::with_capacity_and_blocks(
+        len, blocks,
+    ))
+}
+
+#[cfg(feature = "fixedbitset")]
+impl Serializer for fixedbitset::FixedBitSet {
+    fn write(&self, context: &mut WriteContext) {
+        if context.get_fury().is_packed_bools() {
+            let bits: Vec<u8> = self
+                .as_slice()
+                .iter()
+                .flat_map(|block| block.to_le_bytes())
+                .take(packed_len(self.len()))
+                .collect();
+            write_packed(context, self.len(), &bits);
+        } else {
+            write_bytes(context, self.len(), (0..self.len()).map(|i| self[i]));
+        }
+    }
+
+    fn reserved_space() -> usize {
+        mem::size_of::<u8>()
+    }
+
+    fn get_type_id(fury: &Fury) -> i16 {
+        type_id(fury.is_packed_bools())
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        let packed = context.get_fury().is_packed_bools();
+        read_bit_set(context, packed)
+    }
+
+    fn read_coerced(context: &mut ReadContext, actual_type_id: i16) -> Result<Self, Error> {
+        read_either(context, actual_type_id, read_bit_set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_pack_unpack() {
+        let mut rng = rand::thread_rng();
+        for len in [0, 1, 7, 8, 15, 16, 17, 33, 100, 1000] {
+            let bools: Vec<bool> = (0..len).map(|_| rng.gen()).collect();
+            let mut expected = vec![0; packed_len(len)];
+            pack_standard(&bools, &mut expected);
+            let mut bits = vec![0; packed_len(len)];
+            pack_bools(&bools, &mut bits);
+            assert_eq!(bits, expected);
+
+            let mut unpacked = vec![false; len];
+            unpack_bools(&bits, &mut unpacked);
+            assert_eq!(unpacked, bools);
+        }
+    }
+}
@@ -33,10 +33,14 @@ impl Serializer for bool {
     }
 
     fn read(context: &mut ReadContext) -> Result<Self, Error> {
-        Ok(context.reader.u8() == 1)
+        Ok(context.reader.u8()? == 1)
     }
 
     fn get_type_id(_fury: &Fury) -> i16 {
         FieldType::BOOL.into()
     }
+
+    fn is_plain() -> bool {
+        true
+    }
 }
@@ -0,0 +1,158 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::error::Error;
+use crate::resolver::context::ReadContext;
+use crate::serializer::option::read_nested;
+use crate::serializer::{deserialize_flagged, ensure_type_id, MapWithKind, Serializer};
+use crate::types::{FuryGeneralList, RefFlag};
+use anyhow::anyhow;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList, VecDeque};
+
+/// Deserialization of values that may borrow from the input buffer, see
+/// [`Fury::deserialize_with_mode`](crate::fury::Fury::deserialize_with_mode).
+///
+/// Types without borrowed parts simply defer to [`Serializer`].
+pub trait BorrowDeserialize<'bf>: Serializer {
+    fn read_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error>;
+
+    fn deserialize_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        deserialize_borrowed(context)
+    }
+
+    /// Borrowing counterpart of [`Serializer::deserialize_declared`].
+    fn deserialize_declared_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        if Self::elides_type_id(context.get_fury()) {
+            deserialize_flagged(context, Self::read_borrowed)
+        } else {
+            Self::deserialize_borrowed(context)
+        }
+    }
+}
+
+pub fn deserialize_borrowed<'bf, T: BorrowDeserialize<'bf>>(
+    context: &mut ReadContext<'_, 'bf>,
+) -> Result<T, Error> {
+    // ref flag
+    let ref_flag = context.reader.i8()?;
+
+    if ref_flag == (RefFlag::NotNullValue as i8) || ref_flag == (RefFlag::RefValue as i8) {
+        if ref_flag == (RefFlag::RefValue as i8) {
+            context.ref_reader.reserve_ref_id()?;
+        }
+        let actual_type_id = context.reader.i16()?;
+        ensure_type_id(
+            context.get_fury(),
+            T::get_type_id(context.get_fury()),
+            actual_type_id,
+        )?;
+
+        T::read_borrowed(context)
+    } else if ref_flag == (RefFlag::Null as i8) {
+        Err(anyhow!("Try to deserialize non-option type to null"))?
+    } else if ref_flag == (RefFlag::Ref as i8) {
+        context.ref_reader.read_ref_id(&mut context.reader)?;
+        Err(Error::Ref)
+    } else {
+        Err(anyhow!("Unknown ref flag, value:{ref_flag}"))?
+    }
+}
+
+impl<'bf, T: BorrowDeserialize<'bf>> BorrowDeserialize<'bf> for Option<T> {
+    fn read_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        Ok(Some(T::read_borrowed(context)?))
+    }
+
+    fn deserialize_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        if T::is_nullable() {
+            return read_nested(context, T::deserialize_borrowed);
+        }
+        let reset_cursor = context.reader.reset_cursor_to_here();
+        if context.reader.i8()? == RefFlag::Null as i8 {
+            return Ok(None);
+        }
+        reset_cursor(&mut context.reader);
+        Ok(Some(T::deserialize_borrowed(context)?))
+    }
+
+    fn deserialize_declared_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        if !T::elides_type_id(context.get_fury()) {
+            return Self::deserialize_borrowed(context);
+        }
+        let reset_cursor = context.reader.reset_cursor_to_here();
+        if context.reader.i8()? == RefFlag::Null as i8 {
+            return Ok(None);
+        }
+        reset_cursor(&mut context.reader);
+        Ok(Some(T::deserialize_declared_borrowed(context)?))
+    }
+}
+
+macro_rules! impl_owned {
+    ($([$($generics: tt)*] $ty: ty),* $(,)?) => {
+        $(
+            impl<'bf, $($generics)*> BorrowDeserialize<'bf> for $ty {
+                fn read_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+                    <Self as Serializer>::read(context)
+                }
+
+                fn deserialize_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+                    <Self as Serializer>::deserialize(context)
+                }
+            }
+        )*
+    };
+}
+
+impl_owned!(
+    [] bool, [] i8, [] u8, [] i16, [] u16, [] i32, [] u32, [] i64, [] u64, [] f32, [] f64,
+    [] String, [] NaiveDate, [] NaiveDateTime, [] DateTime<Utc>, [] Box<dyn Any>,
+    [] Vec<bool>, [] Vec<u8>, [] Vec<i16>, [] Vec<i32>, [] Vec<i64>, [] Vec<f32>, [] Vec<f64>,
+    [T: Serializer + FuryGeneralList] Vec<T>,
+    [T: Serializer + FuryGeneralList + Clone] Cow<'bf, [T]>,
+    [T: Serializer + Eq + std::hash::Hash] HashSet<T>,
+    [T: Serializer + Ord] BTreeSet<T>,
+    [T: Serializer] VecDeque<T>,
+    [T: Serializer] LinkedList<T>,
+    [K: Serializer + Eq + std::hash::Hash, V: Serializer] HashMap<K, V>,
+    [K: Serializer + Ord, V: Serializer] BTreeMap<K, V>,
+    [M: Serializer] MapWithKind<M>,
+    [T: Serializer + 'static] std::rc::Rc<T>,
+    [T: Serializer + 'static] std::rc::Weak<T>,
+    [T: Serializer + 'static] std::sync::Arc<T>,
+    [T: Serializer + 'static] std::sync::Weak<T>,
+);
+
+impl_owned!(
+    [A: Serializer] (A,),
+    [A: Serializer, B: Serializer] (A, B),
+    [A: Serializer, B: Serializer, C: Serializer] (A, B, C),
+    [A: Serializer, B: Serializer, C: Serializer, D: Serializer] (A, B, C, D),
+    [A: Serializer, B: Serializer, C: Serializer, D: Serializer, E: Serializer] (A, B, C, D, E),
+    [A: Serializer, B: Serializer, C: Serializer, D: Serializer, E: Serializer, F: Serializer]
+        (A, B, C, D, E, F),
+    [A: Serializer, B: Serializer, C: Serializer, D: Serializer, E: Serializer, F: Serializer,
+        G: Serializer] (A, B, C, D, E, F, G),
+    [A: Serializer, B: Serializer, C: Serializer, D: Serializer, E: Serializer, F: Serializer,
+        G: Serializer, H: Serializer] (A, B, C, D, E, F, G, H),
+);
+
+#[cfg(feature = "indexmap")]
+impl_owned!([K: Serializer + Eq + std::hash::Hash, V: Serializer] indexmap::IndexMap<K, V>);
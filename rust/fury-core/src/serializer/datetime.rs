@@ -21,23 +21,27 @@ use crate::resolver::context::ReadContext;
 use crate::resolver::context::WriteContext;
 use crate::serializer::Serializer;
 use crate::types::{FieldType, FuryGeneralList};
-use crate::util::EPOCH;
+use crate::util::{timestamp_from_nanos, timestamp_to_nanos, EPOCH};
 use anyhow::anyhow;
-use chrono::{DateTime, Days, NaiveDate, NaiveDateTime};
+use chrono::{DateTime, Days, NaiveDate, NaiveDateTime, Utc};
 use std::mem;
 
+/// Written as nanoseconds since the epoch.
+///
+/// # Panics
+///
+/// Writing a timestamp [`timestamp_to_nanos`] can't count panics, check the ones that may be out
+/// of range with it first.
 impl Serializer for NaiveDateTime {
     fn read(context: &mut ReadContext) -> Result<Self, Error> {
-        let timestamp = context.reader.u64();
-        DateTime::from_timestamp_millis(timestamp as i64)
-            .map(|dt| dt.naive_utc())
-            .ok_or(Error::from(anyhow!(
-                "Date out of range, timestamp:{timestamp}"
-            )))
+        Ok(timestamp_from_nanos(context.reader.i64()?))
     }
 
     fn write(&self, context: &mut WriteContext) {
-        context.writer.u64(self.and_utc().timestamp_millis() as u64);
+        match timestamp_to_nanos(self) {
+            Ok(nanos) => context.writer.i64(nanos),
+            Err(err) => panic!("{err}"),
+        }
     }
 
     fn reserved_space() -> usize {
@@ -47,10 +51,44 @@ impl Serializer for NaiveDateTime {
     fn get_type_id(_fury: &Fury) -> i16 {
         FieldType::TIMESTAMP.into()
     }
+
+    fn is_plain() -> bool {
+        true
+    }
 }
 
 impl FuryGeneralList for NaiveDateTime {}
 
+/// The same nanoseconds since the epoch as [`NaiveDateTime`], which is how peers such as Python
+/// write timezone aware datetimes. Their offset isn't written, they are read back in UTC.
+///
+/// # Panics
+///
+/// Like [`NaiveDateTime`], when [`timestamp_to_nanos`] can't count the timestamp.
+impl Serializer for DateTime<Utc> {
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        Ok(NaiveDateTime::read(context)?.and_utc())
+    }
+
+    fn write(&self, context: &mut WriteContext) {
+        self.naive_utc().write(context)
+    }
+
+    fn reserved_space() -> usize {
+        mem::size_of::<u64>()
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::TIMESTAMP.into()
+    }
+
+    fn is_plain() -> bool {
+        true
+    }
+}
+
+impl FuryGeneralList for DateTime<Utc> {}
+
 impl Serializer for NaiveDate {
     fn write(&self, context: &mut WriteContext) {
         let days_since_epoch = self.signed_duration_since(EPOCH).num_days();
@@ -62,7 +100,7 @@ impl Serializer for NaiveDate {
     }
 
     fn read(context: &mut ReadContext) -> Result<Self, Error> {
-        let days = context.reader.u64();
+        let days = context.reader.u64()?;
         EPOCH
             .checked_add_days(Days::new(days))
             .ok_or(Error::from(anyhow!(
@@ -73,6 +111,10 @@ impl Serializer for NaiveDate {
     fn get_type_id(_fury: &Fury) -> i16 {
         FieldType::DATE.into()
     }
+
+    fn is_plain() -> bool {
+        true
+    }
 }
 
 impl FuryGeneralList for NaiveDate {}
@@ -0,0 +1,167 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Delta encoding of integer and timestamp vectors, `#[fury(encode = "delta")]` on a field.
+//!
+//! Consecutive values of a time series are close to each other, so the first value and then
+//! each difference to the previous one are written as ZigZag varint64:
+//!
+//! ```text
+//! | i16 element type id | varuint64 length | varint64 first | varint64 deltas ... |
+//! ```
+
+use crate::error::Error;
+use crate::resolver::context::ReadContext;
+use crate::serializer::Serializer;
+use crate::types::FieldType;
+use crate::util::{timestamp_from_nanos, timestamp_to_nanos, EPOCH};
+use crate::value::Value;
+#[cfg(feature = "unstable-format")]
+use crate::{
+    ensure, resolver::context::WriteContext, serializer::deserialize_typed, types::RefFlag,
+};
+use anyhow::anyhow;
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+
+/// Element of a delta encoded vector, mapped to the integer its deltas are taken on.
+pub trait DeltaElement: Serializer {
+    fn to_i64(&self) -> i64;
+
+    fn from_i64(value: i64) -> Result<Self, Error>;
+}
+
+macro_rules! impl_delta_int {
+    ($($ty: ty),*) => {
+        $(
+            impl DeltaElement for $ty {
+                fn to_i64(&self) -> i64 {
+                    *self as i64
+                }
+
+                fn from_i64(value: i64) -> Result<Self, Error> {
+                    <$ty>::try_from(value).map_err(|_| Error::NumericOverflow {
+                        value: value as i128,
+                        target: stringify!($ty),
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_delta_int!(i16, i32, i64);
+
+// nanoseconds since the epoch, like the plain encoding
+impl DeltaElement for NaiveDateTime {
+    fn to_i64(&self) -> i64 {
+        match timestamp_to_nanos(self) {
+            Ok(nanos) => nanos,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    fn from_i64(value: i64) -> Result<Self, Error> {
+        Ok(timestamp_from_nanos(value))
+    }
+}
+
+// days since the epoch, like the plain encoding
+impl DeltaElement for NaiveDate {
+    fn to_i64(&self) -> i64 {
+        self.signed_duration_since(EPOCH).num_days()
+    }
+
+    fn from_i64(value: i64) -> Result<Self, Error> {
+        TimeDelta::try_days(value)
+            .and_then(|days| EPOCH.checked_add_signed(days))
+            .ok_or_else(|| anyhow!("Date out of range, {value} days since epoch").into())
+    }
+}
+
+/// Writes `values` with their ref flag and the [`FieldType::FuryDeltaArray`] type id.
+#[cfg(feature = "unstable-format")]
+pub fn serialize_delta<T: DeltaElement>(values: &[T], context: &mut WriteContext) {
+    context.use_experimental_format();
+    context.writer.i8(RefFlag::NotNullValue as i8);
+    context.writer.i16(FieldType::FuryDeltaArray.into());
+    context.writer.i16(T::get_type_id(context.get_fury()));
+    context.writer.var_uint64(values.len() as u64);
+    let mut previous = 0i64;
+    for value in values {
+        let value = value.to_i64();
+        context.writer.var_int64(value.wrapping_sub(previous));
+        previous = value;
+    }
+}
+
+/// Reads a vector written by [`serialize_delta`], or written plainly before the field was
+/// switched to delta encoding.
+#[cfg(feature = "unstable-format")]
+pub fn deserialize_delta<T: DeltaElement>(context: &mut ReadContext) -> Result<Vec<T>, Error>
+where
+    Vec<T>: Serializer,
+{
+    deserialize_typed(context, |context, actual_type_id| {
+        if actual_type_id != FieldType::FuryDeltaArray as i16 {
+            return Vec::<T>::read_coerced(context, actual_type_id);
+        }
+        let element_type_id = context.reader.i16()?;
+        let expected_type_id = T::get_type_id(context.get_fury());
+        ensure!(
+            element_type_id == expected_type_id,
+            anyhow!(
+                "Invalid delta element type, expected:{expected_type_id}, actual:{element_type_id}"
+            )
+        );
+        read_values(context)
+    })
+}
+
+fn read_values<T: DeltaElement>(context: &mut ReadContext) -> Result<Vec<T>, Error> {
+    let len = context.reader.var_uint64()?;
+    context.check_collection_len(len.try_into().unwrap_or(usize::MAX))?;
+    context.record_elements::<T>(len as usize);
+    let mut values = Vec::new();
+    let mut previous = 0i64;
+    for _ in 0..len {
+        context.check_cancelled()?;
+        previous = previous.wrapping_add(context.reader.var_int64()?);
+        values.push(T::from_i64(previous)?);
+    }
+    Ok(values)
+}
+
+/// Reads the body of a delta encoded vector as a [`Value`] array or list.
+pub(crate) fn read_delta_value(context: &mut ReadContext) -> Result<Value, Error> {
+    let element_type_id = context.reader.i16()?;
+    Ok(match FieldType::try_from(element_type_id) {
+        Ok(FieldType::INT16) => Value::I16Array(read_values(context)?),
+        Ok(FieldType::INT32) => Value::I32Array(read_values(context)?),
+        Ok(FieldType::INT64) => Value::I64Array(read_values(context)?),
+        Ok(FieldType::TIMESTAMP) => Value::List(
+            read_values(context)?
+                .into_iter()
+                .map(Value::Timestamp)
+                .collect(),
+        ),
+        Ok(FieldType::DATE) => {
+            Value::List(read_values(context)?.into_iter().map(Value::Date).collect())
+        }
+        _ => {
+            return Err(anyhow!("Unsupported delta element type {element_type_id}").into());
+        }
+    })
+}
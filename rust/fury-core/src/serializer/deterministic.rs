@@ -0,0 +1,43 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::buffer::Writer;
+use crate::fury::Fury;
+use crate::resolver::context::WriteContext;
+
+/// Orders map/set entries by the bytes they encode to on their own, so that the output doesn't
+/// depend on the iteration order of the std hashers. Called before anything is written, the
+/// entries are then written in this order as usual.
+///
+/// Each entry is encoded by `encode` into a scratch context, where no `Rc` or `Arc` was written
+/// before, so that shared keys and values sort the same wherever they first appear. Distinct
+/// keys are assumed to encode to distinct bytes, entries that don't keep their iteration order.
+pub fn sort_entries<T>(
+    fury: &Fury,
+    entries: impl Iterator<Item = T>,
+    encode: impl Fn(&T, &mut WriteContext),
+) -> Vec<T> {
+    let mut encoded: Vec<(Vec<u8>, T)> = entries
+        .map(|entry| {
+            let mut writer = Writer::default();
+            encode(&entry, &mut WriteContext::new(fury, &mut writer));
+            (writer.dump(), entry)
+        })
+        .collect();
+    encoded.sort_by(|a, b| a.0.cmp(&b.0));
+    encoded.into_iter().map(|(_, entry)| entry).collect()
+}
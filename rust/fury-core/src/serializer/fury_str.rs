@@ -0,0 +1,143 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::error::Error;
+use crate::fury::Fury;
+use crate::resolver::context::{ReadContext, WriteContext};
+use crate::serializer::string::{decode_str, read_str_header, write_str};
+use crate::serializer::{BorrowDeserialize, Serializer};
+use crate::types::{FieldType, FuryGeneralList, StringFlag};
+use std::fmt;
+use std::mem;
+use std::ops::{Deref, Range};
+use std::sync::Arc;
+
+/// A string pointing into a shared buffer.
+///
+/// With [`DeserializeOptions::shared_string_buffer`](crate::fury::DeserializeOptions::shared_string_buffer)
+/// the input is copied once per message and every `FuryStr` read from it is a range of that
+/// copy, instead of one allocation per string. Otherwise each one gets its own buffer.
+#[derive(Clone)]
+pub struct FuryStr {
+    buf: Arc<[u8]>,
+    range: Range<usize>,
+}
+
+impl FuryStr {
+    pub fn as_str(&self) -> &str {
+        // the range was checked to be valid utf-8 when it was created
+        unsafe { std::str::from_utf8_unchecked(&self.buf[self.range.clone()]) }
+    }
+
+    fn new(buf: Arc<[u8]>, range: Range<usize>) -> FuryStr {
+        match std::str::from_utf8(&buf[range.clone()]) {
+            Ok(_) => FuryStr { buf, range },
+            Err(_) => FuryStr::from(String::from_utf8_lossy(&buf[range]).as_ref()),
+        }
+    }
+}
+
+impl From<&str> for FuryStr {
+    fn from(value: &str) -> Self {
+        FuryStr {
+            buf: Arc::from(value.as_bytes()),
+            range: 0..value.len(),
+        }
+    }
+}
+
+impl Deref for FuryStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for FuryStr {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for FuryStr {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for FuryStr {}
+
+impl std::hash::Hash for FuryStr {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl fmt::Debug for FuryStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for FuryStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl Serializer for FuryStr {
+    fn reserved_space() -> usize {
+        mem::size_of::<i32>()
+    }
+
+    fn write(&self, context: &mut WriteContext) {
+        write_str(context.writer, self);
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        let (flag, len) = read_str_header(&mut context.reader)?;
+        let bytes = context.reader.bytes(len)?;
+        // only UTF-8 bytes can be pointed at, other encodings are converted
+        if flag != StringFlag::UTF8 && !bytes.is_ascii() {
+            return Ok(FuryStr::from(decode_str(flag, bytes).as_ref()));
+        }
+        match &context.shared_buffer {
+            Some(buf) => {
+                let start = context.reader.get_cursor() - len;
+                Ok(FuryStr::new(buf.clone(), start..start + len))
+            }
+            None => Ok(FuryStr::new(Arc::from(bytes.as_ref()), 0..len)),
+        }
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::STRING.into()
+    }
+}
+
+impl<'bf> BorrowDeserialize<'bf> for FuryStr {
+    fn read_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        <Self as Serializer>::read(context)
+    }
+
+    fn deserialize_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        <Self as Serializer>::deserialize(context)
+    }
+}
+
+impl FuryGeneralList for FuryStr {}
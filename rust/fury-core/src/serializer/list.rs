@@ -19,30 +19,47 @@ use crate::error::Error;
 use crate::fury::Fury;
 use crate::resolver::context::ReadContext;
 use crate::resolver::context::WriteContext;
-use crate::serializer::Serializer;
-use crate::types::{FieldType, FuryGeneralList, SIZE_OF_REF_AND_TYPE};
+use crate::serializer::{deserialize_element, element_space, serialize_element, Serializer};
+use crate::type_def::FieldTypeDef;
+use crate::types::{FieldType, FuryGeneralList};
+use std::borrow::Cow;
+use std::collections::{LinkedList, VecDeque};
 use std::mem;
 
+fn write_list<'a, T: Serializer + 'a>(
+    items: impl ExactSizeIterator<Item = &'a T>,
+    context: &mut WriteContext,
+) {
+    context.writer.var_int32(items.len() as i32);
+    let element_space = element_space::<T>(context.get_fury());
+    context.writer.reserve(element_space * items.len());
+    for item in items {
+        serialize_element(item, context);
+    }
+}
+
+fn read_list<T: Serializer, C: FromIterator<T>>(context: &mut ReadContext) -> Result<C, Error> {
+    // list length
+    let len = context.read_elements_len()?;
+    context.record_elements::<T>(len);
+    (0..len)
+        .map(|index| {
+            context.check_cancelled()?;
+            deserialize_element(context).map_err(|err| context.element_error(err, index))
+        })
+        .collect()
+}
+
 impl<T> Serializer for Vec<T>
 where
     T: Serializer + FuryGeneralList,
 {
     fn write(&self, context: &mut WriteContext) {
-        context.writer.var_int32(self.len() as i32);
-        context
-            .writer
-            .reserve((<Self as Serializer>::reserved_space() + SIZE_OF_REF_AND_TYPE) * self.len());
-        for item in self.iter() {
-            item.serialize(context);
-        }
+        write_list(self.iter(), context);
     }
 
     fn read(context: &mut ReadContext) -> Result<Self, Error> {
-        // vec length
-        let len = context.reader.var_int32();
-        (0..len)
-            .map(|_| T::deserialize(context))
-            .collect::<Result<Vec<_>, Error>>()
+        read_list(context)
     }
 
     fn reserved_space() -> usize {
@@ -53,6 +70,74 @@ where
     fn get_type_id(_fury: &Fury) -> i16 {
         FieldType::ARRAY.into()
     }
+
+    fn missing_serializer(fury: &Fury) -> Option<&'static str> {
+        T::missing_serializer(fury)
+    }
+
+    fn generic_types(fury: &Fury) -> Vec<FieldTypeDef> {
+        vec![FieldTypeDef::of::<T>(fury)]
+    }
 }
 
 impl<T> FuryGeneralList for Vec<T> where T: Serializer {}
+
+/// Written like `Vec<T>` straight from the slice, e.g. a `Cow::Borrowed` constant table, and
+/// read back as `Cow::Owned`.
+impl<'a, T> Serializer for Cow<'a, [T]>
+where
+    T: Serializer + FuryGeneralList + Clone,
+{
+    fn write(&self, context: &mut WriteContext) {
+        write_list(self.iter(), context);
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        Ok(Cow::Owned(<Vec<T> as Serializer>::read(context)?))
+    }
+
+    fn reserved_space() -> usize {
+        <Vec<T> as Serializer>::reserved_space()
+    }
+
+    fn get_type_id(fury: &Fury) -> i16 {
+        <Vec<T> as Serializer>::get_type_id(fury)
+    }
+
+    fn generic_types(fury: &Fury) -> Vec<FieldTypeDef> {
+        vec![FieldTypeDef::of::<T>(fury)]
+    }
+}
+
+// other sequences are lists too, also of primitives which a `Vec` writes as a primitive array
+macro_rules! impl_sequence {
+    ($($ty: ident),*) => {
+        $(
+            impl<T: Serializer> Serializer for $ty<T> {
+                fn write(&self, context: &mut WriteContext) {
+                    write_list(self.iter(), context);
+                }
+
+                fn read(context: &mut ReadContext) -> Result<Self, Error> {
+                    read_list(context)
+                }
+
+                fn reserved_space() -> usize {
+                    mem::size_of::<u32>()
+                }
+
+                fn get_type_id(_fury: &Fury) -> i16 {
+                    FieldType::ARRAY.into()
+                }
+
+                fn generic_types(fury: &Fury) -> Vec<FieldTypeDef> {
+                    vec![FieldTypeDef::of::<T>(fury)]
+                }
+            }
+
+            impl<T: Serializer> FuryGeneralList for $ty<T> {}
+        )*
+    };
+}
+
+impl_sequence!(VecDeque, LinkedList);
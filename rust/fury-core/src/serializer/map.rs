@@ -15,41 +15,358 @@
 // specific language governing permissions and limitations
 // under the License.
 
+//! Maps, written as their length then their entries in chunks, see "map key-value chunk data"
+//! in the specification. A chunk starts with a KV header, whose bits tell for keys and values
+//! alike whether they carry a ref flag, are null or carry a type id, followed by the number of
+//! entries of the chunk. Entries with a null key or value make a chunk of their own, which has
+//! no size. Other keys and values keep their ref flag and type id, so that a
+//! [`Value`](crate::value::Value) can be read from them.
+//!
+//! `Option` keys and values stand for Java's null ones, and `Rc` or `Arc` ones for shared
+//! references, e.g. `HashMap<Option<String>, Option<Rc<V>>>`. Java and Python write the maps of
+//! their cross-language format entry by entry instead, each key and value behind its own ref
+//! flag, which [`Fury::per_entry_maps`] writes and reads.
+//!
+//! Peers such as Java write the implementation of a map with its type id, see [`MapKind`]. Any
+//! of these ids is read into whichever Rust map is declared, [`MapWithKind`] keeps the kind to
+//! write it back the same way.
+
+use crate::ensure;
 use crate::error::Error;
 use crate::fury::Fury;
 use crate::resolver::context::ReadContext;
 use crate::resolver::context::WriteContext;
-use crate::serializer::Serializer;
+use crate::serializer::{ensure_type_id, Serializer};
+use crate::type_def::FieldTypeDef;
 use crate::types::{FieldType, FuryGeneralList, SIZE_OF_REF_AND_TYPE};
-use std::collections::HashMap;
+use anyhow::anyhow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
 use std::mem;
 
-impl<T1: Serializer + Eq + std::hash::Hash, T2: Serializer> Serializer for HashMap<T1, T2> {
-    fn write(&self, context: &mut WriteContext) {
-        // length
-        context.writer.var_int32(self.len() as i32);
-
-        let reserved_space = (<T1 as Serializer>::reserved_space() + SIZE_OF_REF_AND_TYPE)
-            * self.len()
-            + (<T2 as Serializer>::reserved_space() + SIZE_OF_REF_AND_TYPE) * self.len();
-        context.writer.reserve(reserved_space);
-
-        // key-value
-        for i in self.iter() {
-            i.0.serialize(context);
-            i.1.serialize(context);
+/// Implementation of a map on the writing side, by type id.
+///
+/// | kind     | type id | Java            |
+/// |----------|---------|-----------------|
+/// | `Hash`   | 30      | `HashMap`       |
+/// | `Linked` | 267     | `LinkedHashMap` |
+/// | `Tree`   | 268     | `TreeMap`       |
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MapKind {
+    #[default]
+    Hash,
+    /// Iterated in insertion order.
+    Linked,
+    /// Iterated in key order.
+    Tree,
+}
+
+impl MapKind {
+    pub fn type_id(self) -> i16 {
+        match self {
+            MapKind::Hash => FieldType::MAP,
+            MapKind::Linked => FieldType::FuryLinkedHashMap,
+            MapKind::Tree => FieldType::FuryTreeMap,
+        }
+        .into()
+    }
+
+    pub fn from_type_id(type_id: i16) -> Option<MapKind> {
+        match FieldType::try_from(type_id).ok()? {
+            FieldType::MAP => Some(MapKind::Hash),
+            FieldType::FuryLinkedHashMap => Some(MapKind::Linked),
+            FieldType::FuryTreeMap => Some(MapKind::Tree),
+            _ => None,
+        }
+    }
+}
+
+fn write_entries<'a, K: Serializer + 'a, V: Serializer + 'a>(
+    context: &mut WriteContext,
+    len: usize,
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+) {
+    // length
+    context.writer.var_int32(len as i32);
+
+    let reserved_space = (<K as Serializer>::reserved_space() + SIZE_OF_REF_AND_TYPE) * len
+        + (<V as Serializer>::reserved_space() + SIZE_OF_REF_AND_TYPE) * len;
+    context.writer.reserve(reserved_space);
+
+    #[cfg(feature = "testing")]
+    if context.get_fury().is_deterministic() {
+        let sorted =
+            super::deterministic::sort_entries(context.get_fury(), entries, |(k, v), context| {
+                k.serialize(context);
+                v.serialize(context);
+            });
+        write_pairs(context, sorted.into_iter());
+        return;
+    }
+
+    write_pairs(context, entries);
+}
+
+fn write_pairs<'a, K: Serializer + 'a, V: Serializer + 'a>(
+    context: &mut WriteContext,
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+) {
+    if context.get_fury().is_per_entry_maps() {
+        for (k, v) in entries {
+            k.serialize(context);
+            v.serialize(context);
+        }
+        return;
+    }
+    let mut chunks = ChunkWriter::default();
+    for (k, v) in entries {
+        chunks.entry(context, k.is_null(), v.is_null());
+        if !k.is_null() {
+            k.serialize(context);
+        }
+        if !v.is_null() {
+            v.serialize(context);
+        }
+    }
+    chunks.close(context);
+}
+
+fn read_entries<K: Serializer, V: Serializer, M: FromIterator<(K, V)>>(
+    context: &mut ReadContext,
+) -> Result<M, Error> {
+    // map length
+    let len = context.read_elements_len()?;
+    context.record_elements::<(K, V)>(len);
+    let per_entry = context.get_fury().is_per_entry_maps();
+    let mut chunks = ChunkReader::default();
+    let entries = (0..len)
+        .map(|index| {
+            context.check_cancelled()?;
+            let entry = if per_entry {
+                <K as Serializer>::deserialize(context)
+                    .and_then(|k| <V as Serializer>::deserialize(context).map(|v| (k, v)))
+            } else {
+                chunks.next(context).and_then(|(null_key, null_value)| {
+                    let k = read_chunked(context, null_key)?;
+                    Ok((k, read_chunked(context, null_value)?))
+                })
+            };
+            entry.map_err(|err| context.element_error(err, index))
+        })
+        .collect::<Result<M, Error>>()?;
+    chunks.finish()?;
+    Ok(entries)
+}
+
+// bits of the KV header for keys, shifted by `VALUE_SHIFT` for values
+const TRACKING_REF: u8 = 0b1;
+const HAS_NULL: u8 = 0b10;
+const NOT_DECL_TYPE: u8 = 0b100;
+const VALUE_SHIFT: u8 = 3;
+
+/// Bits of keys and values that aren't null, which are written with their ref flag and type id.
+const NOT_NULL: u8 = TRACKING_REF | NOT_DECL_TYPE;
+
+/// Entries of a chunk at most, its size is a byte.
+const MAX_CHUNK_SIZE: u8 = 255;
+
+fn key_bits(header: u8) -> u8 {
+    header & 0b111
+}
+
+fn value_bits(header: u8) -> u8 {
+    (header >> VALUE_SHIFT) & 0b111
+}
+
+fn side_bits(null: bool) -> u8 {
+    if null {
+        HAS_NULL
+    } else {
+        NOT_NULL
+    }
+}
+
+/// Writes the KV headers and sizes of the chunks of a map, whose keys and values are written
+/// after each call of [`ChunkWriter::entry`], the null ones left out.
+#[derive(Default)]
+pub(crate) struct ChunkWriter {
+    // offset of the size of the open chunk and the entries written in it
+    open: Option<(usize, u8)>,
+}
+
+impl ChunkWriter {
+    pub(crate) fn entry(&mut self, context: &mut WriteContext, null_key: bool, null_value: bool) {
+        if null_key || null_value {
+            self.close(context);
+            let header = side_bits(null_key) | side_bits(null_value) << VALUE_SHIFT;
+            context.writer.u8(header);
+            return;
+        }
+        match &mut self.open {
+            Some((_, size)) if *size < MAX_CHUNK_SIZE => *size += 1,
+            _ => {
+                self.close(context);
+                context.writer.u8(NOT_NULL | NOT_NULL << VALUE_SHIFT);
+                self.open = Some((context.writer.len(), 1));
+                context.writer.u8(0);
+            }
+        }
+    }
+
+    /// Writes the size of the open chunk, once the map is written or before a null entry.
+    pub(crate) fn close(&mut self, context: &mut WriteContext) {
+        if let Some((offset, size)) = self.open.take() {
+            context.writer.set_bytes(offset, &[size]);
+        }
+    }
+}
+
+/// Reads the KV headers and sizes of the chunks [`ChunkWriter`] wrote.
+#[derive(Default)]
+pub(crate) struct ChunkReader {
+    header: u8,
+    // entries of the current chunk still to read
+    left: u8,
+}
+
+impl ChunkReader {
+    /// Whether the key and the value of the next entry are null, reading the KV header of its
+    /// chunk when the previous one is done.
+    pub(crate) fn next(&mut self, context: &mut ReadContext) -> Result<(bool, bool), Error> {
+        if self.left == 0 {
+            let header = context.reader.u8()?;
+            let known = |bits| bits == HAS_NULL || bits == NOT_NULL;
+            ensure!(
+                header >> (2 * VALUE_SHIFT) == 0
+                    && known(key_bits(header))
+                    && known(value_bits(header)),
+                anyhow!("Unsupported map chunk header {header:#010b}, keys and values must be null or carry their ref flag and type id")
+            );
+            self.header = header;
+            self.left = if (key_bits(header) | value_bits(header)) & HAS_NULL != 0 {
+                1
+            } else {
+                context.reader.u8()?
+            };
+            ensure!(self.left > 0, anyhow!("Empty map chunk"));
         }
+        self.left -= 1;
+        Ok((
+            key_bits(self.header) == HAS_NULL,
+            value_bits(self.header) == HAS_NULL,
+        ))
+    }
+
+    /// Fails if the last chunk holds more entries than the map.
+    pub(crate) fn finish(&self) -> Result<(), Error> {
+        ensure!(
+            self.left == 0,
+            anyhow!(
+                "Map chunk holds {} entries past the length of the map",
+                self.left
+            )
+        );
+        Ok(())
+    }
+}
+
+// a key or value of a chunk, the null one of `T` if the KV header says so
+fn read_chunked<T: Serializer>(context: &mut ReadContext, null: bool) -> Result<T, Error> {
+    if null {
+        return T::null().ok_or_else(|| {
+            anyhow!(
+                "Null key or value in a map of `{}`",
+                std::any::type_name::<T>()
+            )
+            .into()
+        });
+    }
+    T::deserialize(context)
+}
+
+// any map kind, or a legacy alias of the own type id
+fn read_map_coerced<M: Serializer>(
+    context: &mut ReadContext,
+    actual_type_id: i16,
+) -> Result<M, Error> {
+    if MapKind::from_type_id(actual_type_id).is_none() {
+        ensure_type_id(
+            context.get_fury(),
+            M::get_type_id(context.get_fury()),
+            actual_type_id,
+        )?;
+    }
+    M::read(context)
+}
+
+impl<T1: Serializer + Eq + Hash, T2: Serializer> Serializer for HashMap<T1, T2> {
+    fn write(&self, context: &mut WriteContext) {
+        write_entries(context, self.len(), self.iter());
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        read_entries(context)
+    }
+
+    fn read_coerced(context: &mut ReadContext, actual_type_id: i16) -> Result<Self, Error> {
+        read_map_coerced(context, actual_type_id)
+    }
+
+    fn reserved_space() -> usize {
+        mem::size_of::<i32>()
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::MAP.into()
+    }
+
+    fn generic_types(fury: &Fury) -> Vec<FieldTypeDef> {
+        vec![FieldTypeDef::of::<T1>(fury), FieldTypeDef::of::<T2>(fury)]
+    }
+}
+
+impl<T1: Serializer + Eq + Hash, T2: Serializer> FuryGeneralList for HashMap<T1, T2> {}
+
+impl<T1: Serializer + Ord, T2: Serializer> Serializer for BTreeMap<T1, T2> {
+    fn write(&self, context: &mut WriteContext) {
+        write_entries(context, self.len(), self.iter());
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        read_entries(context)
+    }
+
+    fn read_coerced(context: &mut ReadContext, actual_type_id: i16) -> Result<Self, Error> {
+        read_map_coerced(context, actual_type_id)
+    }
+
+    fn reserved_space() -> usize {
+        mem::size_of::<i32>()
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::MAP.into()
+    }
+
+    fn generic_types(fury: &Fury) -> Vec<FieldTypeDef> {
+        vec![FieldTypeDef::of::<T1>(fury), FieldTypeDef::of::<T2>(fury)]
+    }
+}
+
+impl<T1: Serializer + Ord, T2: Serializer> FuryGeneralList for BTreeMap<T1, T2> {}
+
+#[cfg(feature = "indexmap")]
+impl<T1: Serializer + Eq + Hash, T2: Serializer> Serializer for indexmap::IndexMap<T1, T2> {
+    fn write(&self, context: &mut WriteContext) {
+        write_entries(context, self.len(), self.iter());
     }
 
     fn read(context: &mut ReadContext) -> Result<Self, Error> {
-        // map length
-        let len = context.reader.var_int32();
-        (0..len)
-            .map(|_| {
-                <T1 as Serializer>::deserialize(context)
-                    .and_then(|k| <T2 as Serializer>::deserialize(context).map(|v| (k, v)))
-            })
-            .collect::<Result<HashMap<_, _>, Error>>()
+        read_entries(context)
+    }
+
+    fn read_coerced(context: &mut ReadContext, actual_type_id: i16) -> Result<Self, Error> {
+        read_map_coerced(context, actual_type_id)
     }
 
     fn reserved_space() -> usize {
@@ -59,6 +376,64 @@ impl<T1: Serializer + Eq + std::hash::Hash, T2: Serializer> Serializer for HashM
     fn get_type_id(_fury: &Fury) -> i16 {
         FieldType::MAP.into()
     }
+
+    fn generic_types(fury: &Fury) -> Vec<FieldTypeDef> {
+        vec![FieldTypeDef::of::<T1>(fury), FieldTypeDef::of::<T2>(fury)]
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<T1: Serializer + Eq + Hash, T2: Serializer> FuryGeneralList for indexmap::IndexMap<T1, T2> {}
+
+/// A map along with the [`MapKind`] it is written as, which is the one it was read as.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MapWithKind<M> {
+    pub kind: MapKind,
+    pub map: M,
+}
+
+impl<M> MapWithKind<M> {
+    pub fn new(kind: MapKind, map: M) -> Self {
+        MapWithKind { kind, map }
+    }
+}
+
+impl<M: Serializer> Serializer for MapWithKind<M> {
+    fn write(&self, context: &mut WriteContext) {
+        self.map.write(context);
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        Ok(MapWithKind::new(MapKind::Hash, M::read(context)?))
+    }
+
+    fn read_coerced(context: &mut ReadContext, actual_type_id: i16) -> Result<Self, Error> {
+        let kind = MapKind::from_type_id(actual_type_id);
+        ensure!(
+            kind.is_some(),
+            anyhow!("Invalid field type, expected a map, actual:{actual_type_id}")
+        );
+        Ok(MapWithKind::new(
+            kind.unwrap_or_default(),
+            M::read(context)?,
+        ))
+    }
+
+    fn reserved_space() -> usize {
+        M::reserved_space()
+    }
+
+    fn get_type_id(fury: &Fury) -> i16 {
+        M::get_type_id(fury)
+    }
+
+    fn generic_types(fury: &Fury) -> Vec<FieldTypeDef> {
+        M::generic_types(fury)
+    }
+
+    fn type_id_of(&self, _fury: &Fury) -> i16 {
+        self.kind.type_id()
+    }
 }
 
-impl<T1: Serializer + Eq + std::hash::Hash, T2: Serializer> FuryGeneralList for HashMap<T1, T2> {}
+impl<M: Serializer> FuryGeneralList for MapWithKind<M> {}
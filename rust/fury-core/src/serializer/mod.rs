@@ -15,54 +15,291 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::buffer::Reader;
 use crate::ensure;
 use crate::error::Error;
 use crate::fury::Fury;
+use crate::meta::{struct_hash, FieldInfo, TypeMeta};
 use crate::resolver::context::{ReadContext, WriteContext};
-use crate::types::RefFlag;
+use crate::type_def::{field_types, FieldTypeDef};
+use crate::types::{Mode, RefFlag, SIZE_OF_REF_AND_TYPE};
 use anyhow::anyhow;
+use std::any::{Any, TypeId};
+use std::marker::PhantomData;
 
-mod any;
+pub use any::{deserialize_any_allowed, deserialize_any_vec_allowed};
+pub use borrow::{deserialize_borrowed, BorrowDeserialize};
+pub use delta::DeltaElement;
+#[cfg(feature = "unstable-format")]
+pub use delta::{deserialize_delta, serialize_delta};
+pub use fury_str::FuryStr;
+pub use map::{MapKind, MapWithKind};
+pub use remote::{FuryExternal, RemoteSerializer};
+pub use trait_object::AsAny;
+#[doc(hidden)]
+pub use trait_object::{deserialize_trait_object, serialize_trait_object};
+pub use versioned::{VersionSerializer, Versioned};
+
+pub(crate) mod any;
+mod bitset;
 mod bool;
+mod borrow;
 mod datetime;
+pub(crate) mod delta;
+#[cfg(feature = "testing")]
+mod deterministic;
+mod fury_str;
 mod list;
-mod map;
+pub(crate) mod map;
 mod number;
 mod option;
 mod primitive_list;
+mod remote;
 mod set;
+mod shared;
 mod string;
+mod trait_object;
+mod tuple;
+pub(crate) mod versioned;
 
 pub fn serialize<T: Serializer>(this: &T, context: &mut WriteContext) {
     // ref flag
     context.writer.i8(RefFlag::NotNullValue as i8);
     // type
-    context.writer.i16(T::get_type_id(context.get_fury()));
+    context.writer.i16(this.type_id_of(context.get_fury()));
     this.write(context);
 }
 
 pub fn deserialize<T: Serializer>(context: &mut ReadContext) -> Result<T, Error> {
+    deserialize_typed(context, T::read_coerced)
+}
+
+/// Like [`deserialize`], but reads the value itself with `read` instead of `T::read`.
+pub fn deserialize_with<T: Serializer>(
+    context: &mut ReadContext,
+    read: impl FnOnce(&mut ReadContext) -> Result<T, Error>,
+) -> Result<T, Error> {
+    deserialize_typed(context, |context, actual_type_id| {
+        ensure_type_id(
+            context.get_fury(),
+            T::get_type_id(context.get_fury()),
+            actual_type_id,
+        )?;
+        read(context)
+    })
+}
+
+/// Fails unless a value declared with `actual_type_id` is of the type `expected_type_id`,
+/// directly or through a legacy id, see [`Fury::alias_id`].
+pub fn ensure_type_id(
+    fury: &Fury,
+    expected_type_id: i16,
+    actual_type_id: i16,
+) -> Result<(), Error> {
+    ensure!(
+        actual_type_id == expected_type_id
+            || fury.get_class_resolver().resolve_id(actual_type_id as u32)
+                == expected_type_id as u32,
+        Error::TypeMismatch {
+            expected: expected_type_id,
+            actual: actual_type_id,
+        }
+    );
+    Ok(())
+}
+
+// reads the ref flag and the type id, then hands the type id over to `read`
+pub(crate) fn deserialize_typed<T>(
+    context: &mut ReadContext,
+    read: impl FnOnce(&mut ReadContext, i16) -> Result<T, Error>,
+) -> Result<T, Error> {
+    deserialize_flagged(context, |context| {
+        let actual_type_id = context.reader.i16()?;
+        read(context, actual_type_id)
+    })
+}
+
+// reads the ref flag, then the value with `read` unless it is null or a reference
+pub(crate) fn deserialize_flagged<'de, 'bf, T>(
+    context: &mut ReadContext<'de, 'bf>,
+    read: impl FnOnce(&mut ReadContext<'de, 'bf>) -> Result<T, Error>,
+) -> Result<T, Error> {
     // ref flag
-    let ref_flag = context.reader.i8();
+    let ref_flag = context.reader.i8()?;
 
     if ref_flag == (RefFlag::NotNullValue as i8) || ref_flag == (RefFlag::RefValue as i8) {
-        let actual_type_id = context.reader.i16();
-        let expected_type_id = T::get_type_id(context.get_fury());
-        ensure!(
-            actual_type_id == expected_type_id,
-            anyhow!("Invalid field type, expected:{expected_type_id}, actual:{actual_type_id}")
-        );
-
-        T::read(context)
+        if ref_flag == (RefFlag::RefValue as i8) {
+            context.ref_reader.reserve_ref_id()?;
+        }
+        read(context)
     } else if ref_flag == (RefFlag::Null as i8) {
         Err(anyhow!("Try to deserialize non-option type to null"))?
     } else if ref_flag == (RefFlag::Ref as i8) {
+        context.ref_reader.read_ref_id(&mut context.reader)?;
         Err(Error::Ref)
     } else {
         Err(anyhow!("Unknown ref flag, value:{ref_flag}"))?
     }
 }
 
+pub(crate) fn guards_struct_length(fury: &Fury) -> bool {
+    fury.is_struct_length_guard() && *fury.get_mode() == Mode::SchemaConsistent
+}
+
+/// Writes the fields of a derived struct with `write`, behind their length in bytes with
+/// [`Fury::struct_length_guard`].
+pub fn write_struct_fields(context: &mut WriteContext, write: impl FnOnce(&mut WriteContext)) {
+    if !guards_struct_length(context.get_fury()) {
+        write(context);
+        return;
+    }
+    write_length_prefixed(context, write);
+}
+
+// `write` behind the number of bytes it wrote, as a little endian u32
+fn write_length_prefixed(context: &mut WriteContext, write: impl FnOnce(&mut WriteContext)) {
+    let start = context.writer.len();
+    context.writer.skip(4);
+    write(context);
+    let len = context.writer.len() - start - 4;
+    context.writer.set_bytes(start, &(len as u32).to_le_bytes());
+}
+
+/// Reads the fields of a derived struct `T` with `read`, failing when they don't take the length
+/// [`write_struct_fields`] wrote, i.e. the writer's struct has other fields.
+pub fn read_struct_fields<'de, 'bf, T>(
+    context: &mut ReadContext<'de, 'bf>,
+    read: impl FnOnce(&mut ReadContext<'de, 'bf>) -> Result<T, Error>,
+) -> Result<T, Error> {
+    context.record_struct();
+    context.nested(|context| {
+        if !guards_struct_length(context.get_fury()) {
+            return read(context);
+        }
+        let len = context.reader.u32()? as usize;
+        let start = context.reader.get_cursor();
+        let value = read(context);
+        let read_len = context.reader.get_cursor() - start;
+        // a reader with more fields may fail on the bytes after the struct first
+        ensure!(
+            read_len == len || (value.is_err() && read_len < len),
+            "Fields of `{}` take {read_len} bytes but were written with {len}, the writer's struct has other fields",
+            std::any::type_name::<T>()
+        );
+        value
+    })
+}
+
+/// Writes the fields of a variant of a derived enum with `write`, after their length in bytes in
+/// compatible mode, so that readers which don't know the variant can skip it.
+pub fn write_variant_fields(context: &mut WriteContext, write: impl FnOnce(&mut WriteContext)) {
+    if *context.get_fury().get_mode() == Mode::Compatible {
+        write_length_prefixed(context, write);
+    } else {
+        write(context);
+    }
+}
+
+/// Reads the fields of a variant of a derived enum `T` with `read`. In compatible mode fields
+/// appended to the variant by the writer are skipped.
+pub fn read_variant_fields<'de, 'bf, T>(
+    context: &mut ReadContext<'de, 'bf>,
+    read: impl FnOnce(&mut ReadContext<'de, 'bf>) -> Result<T, Error>,
+) -> Result<T, Error> {
+    if *context.get_fury().get_mode() != Mode::Compatible {
+        return read(context);
+    }
+    let len = context.reader.u32()? as usize;
+    let start = context.reader.get_cursor();
+    let value = read(context)?;
+    let read_len = context.reader.get_cursor() - start;
+    ensure!(
+        read_len <= len,
+        "Variant of `{}` takes {read_len} bytes but was written with {len}, the writer's variant has fewer fields",
+        std::any::type_name::<T>()
+    );
+    context.reader.skip((len - read_len) as u32)?;
+    Ok(value)
+}
+
+/// Error for the variant `tag` of a derived enum `T` that isn't known to this side.
+pub fn unknown_variant<T>(tag: i32) -> Error {
+    anyhow!("Unknown variant {tag} of `{}`", std::any::type_name::<T>()).into()
+}
+
+/// Skips the fields of the variant `tag` of a derived enum `T` that isn't known to this side,
+/// which is only possible in compatible mode.
+pub fn skip_variant<T>(context: &mut ReadContext, tag: i32) -> Result<(), Error> {
+    if *context.get_fury().get_mode() != Mode::Compatible {
+        return Err(unknown_variant::<T>(tag));
+    }
+    let len = context.reader.u32()?;
+    context.reader.skip(len)?;
+    Ok(())
+}
+
+/// Bound `#[derive(Fury)]` puts on the type of each field, so that a type without a serializer
+/// fails to compile at the field with this name in the error. The fix is usually to derive
+/// `Fury` for it.
+#[doc(hidden)]
+pub fn field_type_must_derive_fury<T: Serializer>() {}
+
+/// Fails with [`Error::MissingSerializer`] before the field `field` of the derived struct `P` is
+/// read, if its type `T` has no serializer in `fury`.
+#[inline]
+pub fn check_field_serializer<P, T: Serializer>(
+    fury: &Fury,
+    field: &'static str,
+) -> Result<(), Error> {
+    match T::missing_serializer(fury) {
+        None => Ok(()),
+        Some(field_type) => Err(Error::MissingSerializer {
+            parent_type: std::any::type_name::<P>(),
+            field,
+            field_type,
+        }),
+    }
+}
+
+/// Whether derived structs declared as a field or element type are written without their type
+/// id by `fury`, see [`Fury::elide_declared_types`].
+pub fn elides_declared_type(fury: &Fury) -> bool {
+    fury.is_elide_declared_types() && *fury.get_mode() == Mode::SchemaConsistent
+}
+
+/// Whether lists and sets of `T` write their elements without ref flag and type id, see
+/// [`Fury::compact_elements`].
+pub fn compacts_elements<T: Serializer>(fury: &Fury) -> bool {
+    T::is_plain() && fury.writes_compact_elements()
+}
+
+/// Space reserved per element of a list or set of `T`.
+pub(crate) fn element_space<T: Serializer>(fury: &Fury) -> usize {
+    if compacts_elements::<T>(fury) {
+        T::reserved_space()
+    } else {
+        T::reserved_space() + SIZE_OF_REF_AND_TYPE
+    }
+}
+
+/// Writes an element of a list.
+pub(crate) fn serialize_element<T: Serializer>(value: &T, context: &mut WriteContext) {
+    if compacts_elements::<T>(context.get_fury()) {
+        value.write(context);
+    } else {
+        value.serialize_declared(context);
+    }
+}
+
+/// Reads what [`serialize_element`] wrote.
+pub(crate) fn deserialize_element<T: Serializer>(context: &mut ReadContext) -> Result<T, Error> {
+    if compacts_elements::<T>(context.get_fury()) {
+        T::read(context)
+    } else {
+        T::deserialize_declared(context)
+    }
+}
+
 pub trait Serializer
 where
     Self: Sized,
@@ -84,13 +321,193 @@ where
 
     fn read(context: &mut ReadContext) -> Result<Self, Error>;
 
+    /// Reads a value the peer declared with `actual_type_id`.
+    ///
+    /// Only the own type id and its legacy aliases are accepted by default, integers also accept the other integer
+    /// types and narrow them according to [`DeserializeOptions::narrowing`](crate::fury::DeserializeOptions::narrowing).
+    fn read_coerced(context: &mut ReadContext, actual_type_id: i16) -> Result<Self, Error> {
+        ensure_type_id(
+            context.get_fury(),
+            Self::get_type_id(context.get_fury()),
+            actual_type_id,
+        )?;
+        Self::read(context)
+    }
+
     fn deserialize(context: &mut ReadContext) -> Result<Self, Error> {
         deserialize(context)
     }
 
     fn get_type_id(_fury: &Fury) -> i16;
+
+    /// Name of the type `fury` has no serializer for, which values of this type need, e.g. a
+    /// derived struct that isn't registered. Wrappers like `Option` ask the wrapped type.
+    fn missing_serializer(_fury: &Fury) -> Option<&'static str> {
+        None
+    }
+
+    /// Types of the elements, keys and values, e.g. `STRING` for a `Vec<String>`, see
+    /// [`FieldTypeDef`]. Wrappers like `Option` give those of the wrapped type.
+    fn generic_types(_fury: &Fury) -> Vec<FieldTypeDef> {
+        Vec::new()
+    }
+
+    /// Type id written before this value, [`Serializer::get_type_id`] unless the value is written
+    /// with one of several ids, e.g. a [`MapWithKind`].
+    fn type_id_of(&self, fury: &Fury) -> i16 {
+        Self::get_type_id(fury)
+    }
+
+    /// Whether values write their own `RefValue` and `Ref` flags, i.e. they are `Rc`, `Arc` or
+    /// `Weak`. An `Option` of them leaves its ref flag to them.
+    fn tracks_refs() -> bool {
+        false
+    }
+
+    /// Whether values may be null, i.e. they are an `Option`. An `Option` of them writes a
+    /// `NotNullValue` flag before a present value, so that `Some(None)` isn't read as `None`.
+    fn is_nullable() -> bool {
+        false
+    }
+
+    /// Whether this value is null, i.e. a `None`, which maps write as a chunk of its own.
+    fn is_null(&self) -> bool {
+        false
+    }
+
+    /// The null value of types that have one, i.e. `None`, for the null keys and values of maps.
+    fn null() -> Option<Self> {
+        None
+    }
+
+    /// Whether values are written by [`Serializer::write`] alone, never null nor shared, e.g.
+    /// numbers and strings, so that lists and sets may leave out their ref flag and type id, see
+    /// [`Fury::compact_elements`].
+    fn is_plain() -> bool {
+        false
+    }
+
+    /// Whether a value whose type is known from the schema, e.g. a field of a struct, is written
+    /// without its type id. Derived structs do so with [`Fury::elide_declared_types`].
+    fn elides_type_id(_fury: &Fury) -> bool {
+        false
+    }
+
+    /// Writes a value whose type the reader knows from the schema, like [`Serializer::serialize`]
+    /// but without the type id when [`Serializer::elides_type_id`].
+    fn serialize_declared(&self, context: &mut WriteContext) {
+        if Self::elides_type_id(context.get_fury()) {
+            context.writer.i8(RefFlag::NotNullValue as i8);
+            self.write(context);
+        } else {
+            self.serialize(context);
+        }
+    }
+
+    /// Reads what [`Serializer::serialize_declared`] wrote.
+    fn deserialize_declared(context: &mut ReadContext) -> Result<Self, Error> {
+        if Self::elides_type_id(context.get_fury()) {
+            deserialize_flagged(context, Self::read)
+        } else {
+            Self::deserialize(context)
+        }
+    }
 }
 
 pub trait StructSerializer: Serializer + 'static {
     fn type_def(fury: &Fury) -> Vec<u8>;
+
+    /// Types of the fields in the order of the type def, with their generics, which the type
+    /// def leaves out. Derived structs list them, the default has no generics.
+    fn field_types(fury: &Fury) -> Vec<FieldTypeDef> {
+        field_types(&Self::type_def(fury))
+    }
+
+    /// Hash of the type ids of the fields like Java's struct hash, see [`struct_hash`]. Derived
+    /// structs compute it without building their type def.
+    fn struct_hash(fury: &Fury) -> u32 {
+        let meta = TypeMeta::from_bytes(&mut Reader::new(&Self::type_def(fury)))
+            .expect("type defs of serializers are well-formed");
+        let type_ids: Vec<i16> = meta
+            .get_field_info()
+            .iter()
+            .map(FieldInfo::get_field_id)
+            .collect();
+        struct_hash(&type_ids)
+    }
+}
+
+/// Object-safe counterpart of [`Serializer`], used to register types whose concrete Rust type
+/// isn't visible to the crate owning the `Fury` instance, e.g. types living in a plugin loaded
+/// at runtime.
+///
+/// None of the methods are generic, so a `Box<dyn DynSerializer>` can be handed across a dylib
+/// boundary. Rust has no stable ABI though: the host and the plugin must still be built with the
+/// same compiler and the same fury version.
+///
+/// A registered serializer is shared by every thread using its `Fury`, hence `Send + Sync`:
+/// registering one mustn't keep that `Fury` from being shared or sent.
+pub trait DynSerializer: Send + Sync {
+    /// Stable name of the type, e.g. `com.example.Event`.
+    ///
+    /// `TypeId` is only guaranteed to be unique within one compilation unit, so the same type
+    /// compiled into the host and into a plugin may end up with different ids. Registrations
+    /// sharing a name are treated as the same type, see [`Fury::register_dyn`].
+    fn type_name(&self) -> &str;
+
+    /// `TypeId` of the concrete type as seen by the compilation unit implementing this trait.
+    fn rust_type_id(&self) -> TypeId;
+
+    fn type_def(&self, fury: &Fury) -> Vec<u8>;
+
+    fn serialize(&self, value: &dyn Any, context: &mut WriteContext);
+
+    fn deserialize(&self, context: &mut ReadContext) -> Result<Box<dyn Any>, Error>;
+}
+
+/// [`DynSerializer`] backed by the static [`StructSerializer`] implementation of `T`.
+///
+/// Plugins should create it on their side of the boundary, so that `T` is monomorphized with
+/// the plugin's own `TypeId`.
+pub struct TypedDynSerializer<T> {
+    type_name: String,
+    // doesn't own a `T`, so it is `Send + Sync` whatever `T` is
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: StructSerializer> TypedDynSerializer<T> {
+    pub fn new(type_name: &str) -> TypedDynSerializer<T> {
+        TypedDynSerializer {
+            type_name: type_name.to_string(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: StructSerializer> DynSerializer for TypedDynSerializer<T> {
+    fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    fn rust_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn type_def(&self, fury: &Fury) -> Vec<u8> {
+        T::type_def(fury)
+    }
+
+    fn serialize(&self, value: &dyn Any, context: &mut WriteContext) {
+        match value.downcast_ref::<T>() {
+            Some(v) => v.serialize(context),
+            None => panic!(
+                "value passed to the serializer of `{}` has another type",
+                self.type_name
+            ),
+        }
+    }
+
+    fn deserialize(&self, context: &mut ReadContext) -> Result<Box<dyn Any>, Error> {
+        Ok(Box::new(T::deserialize(context)?))
+    }
 }
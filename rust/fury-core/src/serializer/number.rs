@@ -20,41 +20,256 @@ use crate::fury::Fury;
 use crate::resolver::context::ReadContext;
 use crate::resolver::context::WriteContext;
 use crate::serializer::Serializer;
-use crate::types::{FieldType, FuryGeneralList};
+use crate::types::{FieldType, FuryGeneralList, NarrowingPolicy};
+use anyhow::anyhow;
 
 macro_rules! impl_num_serializer {
     ($name: ident, $ty:tt, $field_type: expr) => {
+        impl_num_serializer!($name, $ty, $field_type, {});
+    };
+    ($name: ident, $ty:tt, $field_type: expr, {$($coerce: tt)*}) => {
+        impl_num_serializer!(
+            |context, value| context.writer.$name(value),
+            |context| context.reader.$name(),
+            $ty,
+            $field_type,
+            {$($coerce)*}
+        );
+    };
+    (
+        |$write_context: ident, $value: ident| $write: expr,
+        |$read_context: ident| $read: expr,
+        $ty:tt,
+        $field_type: expr,
+        {$($coerce: tt)*}
+    ) => {
         impl Serializer for $ty {
-            fn write(&self, context: &mut WriteContext) {
-                context.writer.$name(*self);
+            fn write(&self, $write_context: &mut WriteContext) {
+                let $value = *self;
+                $write;
             }
 
-            fn read(context: &mut ReadContext) -> Result<Self, Error> {
-                Ok(context.reader.$name())
+            fn read($read_context: &mut ReadContext) -> Result<Self, Error> {
+                $read
             }
 
+            $($coerce)*
+
             fn reserved_space() -> usize {
-                std::mem::size_of::<$ty>()
+                // compressed 64 bit integers take one byte more at most
+                std::mem::size_of::<$ty>() + (std::mem::size_of::<$ty>() == 8) as usize
             }
 
             fn get_type_id(_fury: &Fury) -> i16 {
                 ($field_type).into()
             }
+
+            fn is_plain() -> bool {
+                true
+            }
         }
     };
 }
+
+/// Integers accept any other integer type on the wire, see [`read_integer_coerced`].
+macro_rules! impl_int_serializer {
+    ($name: ident, $ty:tt, $field_type: expr) => {
+        impl_int_serializer!(
+            |context, value| context.writer.$name(value),
+            |context| context.reader.$name(),
+            $ty,
+            $field_type
+        );
+    };
+    (|$write_context: ident, $value: ident| $write: expr, |$read_context: ident| $read: expr, $ty:tt, $field_type: expr) => {
+        impl_num_serializer!(
+            |$write_context, $value| $write,
+            |$read_context| $read,
+            $ty,
+            $field_type,
+            {
+                fn read_coerced(
+                    context: &mut ReadContext,
+                    actual_type_id: i16,
+                ) -> Result<Self, Error> {
+                    read_integer_coerced(context, actual_type_id)
+                }
+            }
+        );
+
+        impl Integer for $ty {
+            const MIN: i128 = $ty::MIN as i128;
+            const MAX: i128 = $ty::MAX as i128;
+
+            fn wrap(value: i128) -> Self {
+                value as $ty
+            }
+        }
+    };
+}
+
+trait Integer: Serializer + TryFrom<i128> {
+    const MIN: i128;
+    const MAX: i128;
+
+    fn wrap(value: i128) -> Self;
+}
+
+// reads an integer of any width, `None` if `field_type` isn't an integer type
+fn read_integer(context: &mut ReadContext, field_type: FieldType) -> Result<Option<i128>, Error> {
+    match field_type {
+        FieldType::INT64 => return Ok(Some(read_i64(context)? as i128)),
+        FieldType::UINT64 => return Ok(Some(read_u64(context)? as i128)),
+        _ => {}
+    }
+    let reader = &mut context.reader;
+    Ok(Some(match field_type {
+        FieldType::INT8 => reader.i8()? as i128,
+        FieldType::UINT8 => reader.u8()? as i128,
+        FieldType::INT16 => reader.i16()? as i128,
+        FieldType::UINT16 => reader.u16()? as i128,
+        FieldType::INT32 => reader.i32()? as i128,
+        FieldType::UINT32 => reader.u32()? as i128,
+        _ => return Ok(None),
+    }))
+}
+
+// 64 bit integers, compressed like Java does with `Fury::compress_number`
+fn write_i64(context: &mut WriteContext, value: i64) {
+    if context.get_fury().is_compress_number() {
+        context.writer.sli_int64(value);
+    } else {
+        context.writer.i64(value);
+    }
+}
+
+fn read_i64(context: &mut ReadContext) -> Result<i64, Error> {
+    if context.get_fury().is_compress_number() {
+        context.reader.sli_int64()
+    } else {
+        context.reader.i64()
+    }
+}
+
+fn write_u64(context: &mut WriteContext, value: u64) {
+    if context.get_fury().is_compress_number() {
+        context.writer.var_uint64(value);
+    } else {
+        context.writer.u64(value);
+    }
+}
+
+fn read_u64(context: &mut ReadContext) -> Result<u64, Error> {
+    if context.get_fury().is_compress_number() {
+        context.reader.var_uint64()
+    } else {
+        context.reader.u64()
+    }
+}
+
+/// Reads an integer sent as another integer type, narrowing it with the configured
+/// [`NarrowingPolicy`] when it's out of range.
+fn read_integer_coerced<T: Integer>(
+    context: &mut ReadContext,
+    actual_type_id: i16,
+) -> Result<T, Error> {
+    let expected_type_id = T::get_type_id(context.get_fury());
+    if actual_type_id == expected_type_id {
+        return T::read(context);
+    }
+    let value = match FieldType::try_from(actual_type_id) {
+        Ok(field_type) => read_integer(context, field_type)?,
+        Err(_) => None,
+    };
+    let value = value.ok_or(Error::TypeMismatch {
+        expected: expected_type_id,
+        actual: actual_type_id,
+    })?;
+    if let Ok(value) = T::try_from(value) {
+        return Ok(value);
+    }
+    match context.get_fury().get_deserialize_options().get_narrowing() {
+        NarrowingPolicy::Error => Err(Error::NumericOverflow {
+            value,
+            target: std::any::type_name::<T>(),
+        }),
+        NarrowingPolicy::Saturate => Ok(T::wrap(value.clamp(T::MIN, T::MAX))),
+        NarrowingPolicy::Wrap => Ok(T::wrap(value)),
+    }
+}
+
 impl FuryGeneralList for i8 {}
 impl FuryGeneralList for u16 {}
 impl FuryGeneralList for u32 {}
 impl FuryGeneralList for u64 {}
 
-impl_num_serializer!(i8, i8, FieldType::INT8);
-impl_num_serializer!(u8, u8, FieldType::UINT8);
-impl_num_serializer!(i16, i16, FieldType::INT16);
-impl_num_serializer!(u16, u16, FieldType::UINT16);
-impl_num_serializer!(i32, i32, FieldType::INT32);
-impl_num_serializer!(u32, u32, FieldType::UINT32);
-impl_num_serializer!(u64, u64, FieldType::UINT64);
-impl_num_serializer!(i64, i64, FieldType::INT64);
+impl_int_serializer!(i8, i8, FieldType::INT8);
+impl_int_serializer!(u8, u8, FieldType::UINT8);
+impl_int_serializer!(i16, i16, FieldType::INT16);
+impl_int_serializer!(u16, u16, FieldType::UINT16);
+impl_int_serializer!(i32, i32, FieldType::INT32);
+impl_int_serializer!(u32, u32, FieldType::UINT32);
+impl_int_serializer!(
+    |context, value| write_u64(context, value),
+    |context| read_u64(context),
+    u64,
+    FieldType::UINT64
+);
+impl_int_serializer!(
+    |context, value| write_i64(context, value),
+    |context| read_i64(context),
+    i64,
+    FieldType::INT64
+);
 impl_num_serializer!(f32, f32, FieldType::FLOAT);
-impl_num_serializer!(f64, f64, FieldType::DOUBLE);
+
+/// Flag of a compact `f64` followed by the `f32` holding the same value, see
+/// [`Fury::compact_floats`].
+pub(crate) const FLOAT32_FLAG: u8 = 0;
+/// Flag of a compact `f64` followed by the `f64` itself.
+pub(crate) const FLOAT64_FLAG: u8 = 1;
+
+/// Whether `value` survives a round trip through `f32` bit for bit, NaN payloads included.
+pub(crate) fn fits_f32(value: f64) -> bool {
+    ((value as f32) as f64).to_bits() == value.to_bits()
+}
+
+impl Serializer for f64 {
+    fn write(&self, context: &mut WriteContext) {
+        if !context.get_fury().is_compact_floats() {
+            context.writer.f64(*self);
+            return;
+        }
+        context.use_experimental_format();
+        if fits_f32(*self) {
+            context.writer.u8(FLOAT32_FLAG);
+            context.writer.f32(*self as f32);
+        } else {
+            context.writer.u8(FLOAT64_FLAG);
+            context.writer.f64(*self);
+        }
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        if !context.get_fury().is_compact_floats() {
+            return context.reader.f64();
+        }
+        match context.reader.u8()? {
+            FLOAT32_FLAG => Ok(context.reader.f32()? as f64),
+            FLOAT64_FLAG => Ok(context.reader.f64()?),
+            flag => Err(anyhow!("Unknown compact float flag {flag}"))?,
+        }
+    }
+
+    fn reserved_space() -> usize {
+        std::mem::size_of::<f64>() + 1
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::DOUBLE.into()
+    }
+
+    fn is_plain() -> bool {
+        true
+    }
+}
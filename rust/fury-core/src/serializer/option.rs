@@ -15,41 +15,105 @@
 // specific language governing permissions and limitations
 // under the License.
 
+//! `Option`s, `None` written as the `Null` ref flag where a nullable field of Java has its null.
+//!
+//! Only one level maps onto Java's null. An `Option` of an `Option` writes a `NotNullValue` flag
+//! of its own before a present value, which only readers declaring the nested `Option` expect,
+//! a [`Value`] can't be read from it.
+
 use crate::ensure;
 use crate::error::Error;
 use crate::fury::Fury;
 use crate::resolver::context::ReadContext;
 use crate::resolver::context::WriteContext;
+use crate::serializer::any::{is_unknown_type, read_dyn_name, DYN_BUILTIN, DYN_NAMED};
 use crate::serializer::Serializer;
-use crate::types::{FuryGeneralList, RefFlag};
+use crate::type_def::FieldTypeDef;
+use crate::types::{FieldType, FuryGeneralList, Mode, RefFlag, UnknownTypePolicy};
+use crate::value::Value;
 use anyhow::anyhow;
 
+/// Reads an `Option` of an `Option`, whose present values follow a `NotNullValue` flag of their
+/// own, see [`Serializer::is_nullable`].
+pub(crate) fn read_nested<'de, 'bf, T>(
+    context: &mut ReadContext<'de, 'bf>,
+    read: impl FnOnce(&mut ReadContext<'de, 'bf>) -> Result<T, Error>,
+) -> Result<Option<T>, Error> {
+    let ref_flag = context.reader.i8()?;
+    if ref_flag == RefFlag::Null as i8 {
+        return Ok(None);
+    }
+    ensure!(
+        ref_flag == RefFlag::NotNullValue as i8,
+        anyhow!("Invalid ref flag of a nested option, value:{ref_flag}")
+    );
+    Ok(Some(read(context)?))
+}
+
 impl<T: Serializer> Serializer for Option<T> {
     fn read(context: &mut ReadContext) -> Result<Self, Error> {
         Ok(Some(T::read(context)?))
     }
 
     fn deserialize(context: &mut ReadContext) -> Result<Self, Error> {
-        // ref flag
-        let ref_flag = context.reader.i8();
-
-        if ref_flag == (RefFlag::NotNullValue as i8) || ref_flag == (RefFlag::RefValue as i8) {
-            // type_id
-            let actual_type_id = context.reader.i16();
-            let expected_type_id = T::get_type_id(context.get_fury());
-            ensure!(
-                actual_type_id == expected_type_id,
-                anyhow!("Invalid field type, expected:{expected_type_id}, actual:{actual_type_id}")
-            );
-
-            Ok(Some(T::read(context)?))
-        } else if ref_flag == (RefFlag::Null as i8) {
-            Ok(None)
-        } else if ref_flag == (RefFlag::Ref as i8) {
-            Err(Error::Ref)
-        } else {
-            Err(anyhow!("Unknown ref flag, value:{ref_flag}"))?
+        if T::is_nullable() {
+            return read_nested(context, T::deserialize);
+        }
+        if T::tracks_refs() {
+            if context.reader.peek_u8() == Some(RefFlag::Null as i8 as u8) {
+                context.reader.i8()?;
+                return Ok(None);
+            }
+            return Ok(Some(T::deserialize(context)?));
+        }
+        let mut head = context.reader;
+        let ref_flag = head.i8()?;
+        if ref_flag == RefFlag::Null as i8 {
+            context.reader.i8()?;
+            return Ok(None);
         }
+        if ref_flag == RefFlag::NotNullValue as i8 || ref_flag == RefFlag::RefValue as i8 {
+            let mut header = head.i16()?;
+            // in compatible mode structs are written with the index of their type meta
+            let fury = context.get_fury();
+            let compatible = *fury.get_mode() == Mode::Compatible;
+            let skip_unknown = context.get_unknown_types() == UnknownTypePolicy::SkipAsNone;
+            // a `Box<dyn Any>`, asked only when needed as an unregistered `T` has no type id
+            let dynamic = (compatible || skip_unknown)
+                && T::get_type_id(fury) == FieldType::FuryTypeTag as i16;
+            if dynamic && !compatible && header == DYN_NAMED {
+                // skipped if no type has that name here, like a type id unknown here
+                let (namespace, name) = read_dyn_name(&mut head)?;
+                head.i8()?;
+                let type_id = head.i16()?;
+                header = fury
+                    .get_class_resolver()
+                    .get_id_by_type_name(&namespace, &name)
+                    .map_or(type_id, |id| id as i16);
+            }
+            let meta = if compatible
+                && (FieldType::try_from(T::get_type_id(fury)).is_err()
+                    || (dynamic && header != DYN_BUILTIN))
+            {
+                context.meta_resolver.try_get(header as usize).cloned()
+            } else {
+                None
+            };
+            let type_id = meta
+                .as_ref()
+                .map_or(header as u32, |meta| meta.get_type_id());
+            let skipped =
+                dynamic && skip_unknown && is_unknown_type(context, header, meta.as_deref());
+            if skipped || fury.get_class_resolver().is_ignored(type_id) {
+                context.reader = head;
+                if ref_flag == RefFlag::RefValue as i8 {
+                    context.ref_reader.reserve_ref_id()?;
+                }
+                Value::skip_ignored(context, type_id, meta.as_deref())?;
+                return Ok(None);
+            }
+        }
+        Ok(Some(T::deserialize(context)?))
     }
 
     fn write(&self, context: &mut WriteContext) {
@@ -63,12 +127,10 @@ impl<T: Serializer> Serializer for Option<T> {
     fn serialize(&self, context: &mut WriteContext) {
         match self {
             Some(v) => {
-                // ref flag
-                context.writer.i8(RefFlag::NotNullValue as i8);
-                // type
-                context.writer.i16(T::get_type_id(context.get_fury()));
-
-                v.write(context);
+                if T::is_nullable() {
+                    context.writer.i8(RefFlag::NotNullValue as i8);
+                }
+                v.serialize(context)
             }
             None => {
                 context.writer.i8(RefFlag::Null as i8);
@@ -76,6 +138,41 @@ impl<T: Serializer> Serializer for Option<T> {
         }
     }
 
+    fn serialize_declared(&self, context: &mut WriteContext) {
+        match self {
+            Some(v) if T::elides_type_id(context.get_fury()) => v.serialize_declared(context),
+            _ => self.serialize(context),
+        }
+    }
+
+    fn deserialize_declared(context: &mut ReadContext) -> Result<Self, Error> {
+        if !T::elides_type_id(context.get_fury()) {
+            return Self::deserialize(context);
+        }
+        let reset_cursor = context.reader.reset_cursor_to_here();
+        if context.reader.i8()? == RefFlag::Null as i8 {
+            return Ok(None);
+        }
+        reset_cursor(&mut context.reader);
+        Ok(Some(T::deserialize_declared(context)?))
+    }
+
+    fn tracks_refs() -> bool {
+        T::tracks_refs()
+    }
+
+    fn is_nullable() -> bool {
+        true
+    }
+
+    fn is_null(&self) -> bool {
+        self.is_none()
+    }
+
+    fn null() -> Option<Self> {
+        Some(None)
+    }
+
     fn reserved_space() -> usize {
         std::mem::size_of::<T>()
     }
@@ -83,6 +180,14 @@ impl<T: Serializer> Serializer for Option<T> {
     fn get_type_id(fury: &Fury) -> i16 {
         T::get_type_id(fury)
     }
+
+    fn missing_serializer(fury: &Fury) -> Option<&'static str> {
+        T::missing_serializer(fury)
+    }
+
+    fn generic_types(fury: &Fury) -> Vec<FieldTypeDef> {
+        T::generic_types(fury)
+    }
 }
 
 impl<T: Serializer> FuryGeneralList for Option<T> {}
@@ -15,12 +15,17 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::buffer::{Reader, Writer};
+use crate::ensure;
 use crate::error::Error;
 use crate::fury::Fury;
 use crate::resolver::context::ReadContext;
 use crate::resolver::context::WriteContext;
-use crate::serializer::Serializer;
-use crate::types::FieldType;
+use crate::serializer::number::{fits_f32, FLOAT32_FLAG, FLOAT64_FLAG};
+use crate::serializer::{BorrowDeserialize, Serializer};
+use crate::types::{DecodeMode, FieldType};
+use anyhow::anyhow;
+use std::borrow::Cow;
 use std::mem;
 
 pub fn to_u8_slice<T>(slice: &[T]) -> &[u8] {
@@ -28,36 +33,257 @@ pub fn to_u8_slice<T>(slice: &[T]) -> &[u8] {
     unsafe { std::slice::from_raw_parts(slice.as_ptr().cast::<u8>(), byte_len) }
 }
 
+/// Elements of primitive arrays, which are copied to and from the wire as a whole rather than one
+/// by one. The wire is little-endian, big-endian targets swap the bytes of the copy in a loop the
+/// compiler vectorizes.
+trait Primitive: Copy {
+    /// Converts between the wire and the target's byte order, in both directions.
+    fn swap_le(self) -> Self;
+}
+
+macro_rules! impl_primitive {
+    ($($ty:ty),*) => {
+        $(impl Primitive for $ty {
+            fn swap_le(self) -> Self {
+                self.to_le()
+            }
+        })*
+    };
+}
+
+impl_primitive!(u8, i16, i32, i64);
+
+impl Primitive for f32 {
+    fn swap_le(self) -> Self {
+        f32::from_bits(self.to_bits().to_le())
+    }
+}
+
+impl Primitive for f64 {
+    fn swap_le(self) -> Self {
+        f64::from_bits(self.to_bits().to_le())
+    }
+}
+
+/// `values` in the byte order of the wire, borrowed on little-endian targets.
+fn le_bytes<T: Primitive>(values: &[T]) -> Cow<'_, [u8]> {
+    if cfg!(target_endian = "little") {
+        Cow::Borrowed(to_u8_slice(values))
+    } else {
+        let swapped: Vec<T> = values.iter().map(|value| value.swap_le()).collect();
+        Cow::Owned(to_u8_slice(&swapped).to_vec())
+    }
+}
+
+fn write_elements<T: Primitive>(writer: &mut Writer, values: &[T]) {
+    writer.reserve(mem::size_of_val(values));
+    writer.bytes(&le_bytes(values));
+}
+
+/// Copies the next `len` elements at once, for input that can't be viewed in place, see
+/// [`read_in_place`].
+fn copy_elements<T: Primitive>(reader: &mut Reader, len: usize) -> Result<Vec<T>, Error> {
+    let size = len.saturating_mul(mem::size_of::<T>());
+    let bytes = reader.bytes(size)?;
+    let mut elements: Vec<T> = Vec::with_capacity(len);
+    // SAFETY: `bytes` holds `len` elements, which are numbers valid for any bits
+    unsafe {
+        std::ptr::copy_nonoverlapping(
+            bytes.as_ptr(),
+            elements.as_mut_ptr().cast::<u8>(),
+            bytes.len(),
+        );
+        elements.set_len(len);
+    }
+    if cfg!(target_endian = "big") {
+        elements
+            .iter_mut()
+            .for_each(|element| *element = element.swap_le());
+    }
+    Ok(elements)
+}
+
+/// Array payloads start at a multiple of this from the message start when
+/// [`Fury::aligned_arrays`] is set.
+const ARRAY_ALIGNMENT: usize = 8;
+
+fn write_padding(context: &mut WriteContext) {
+    if context.get_fury().is_aligned_arrays() {
+        context.writer.pad_to(ARRAY_ALIGNMENT);
+    }
+}
+
+fn read_padding(context: &mut ReadContext) -> Result<(), Error> {
+    if context.get_fury().is_aligned_arrays() {
+        context.reader.skip_padding(ARRAY_ALIGNMENT)?;
+    }
+    Ok(())
+}
+
+/// Views the next `len` elements in place when the input is little-endian like the wire format,
+/// aligned for `T` and not split across segments.
+fn read_in_place<'bf, T>(context: &mut ReadContext<'_, 'bf>, len: usize) -> Option<&'bf [T]> {
+    let size = len.checked_mul(mem::size_of::<T>())?;
+    if !cfg!(target_endian = "little")
+        || !context.reader.aligned::<T>()
+        || !context.reader.contiguous(size)
+    {
+        return None;
+    }
+    match context.reader.bytes(size).ok()? {
+        Cow::Borrowed(slice) => {
+            Some(unsafe { std::slice::from_raw_parts(slice.as_ptr().cast::<T>(), len) })
+        }
+        Cow::Owned(_) => unreachable!("contiguous bytes are borrowed"),
+    }
+}
+
+/// Flag of an array written element by element, when [`Fury::rle_arrays`] is set.
+const RAW_FLAG: u8 = 0;
+/// Flag of an array written as runs of equal elements.
+const RLE_FLAG: u8 = 1;
+
+// runs are worth it when they take less room than the elements, counting two bytes per run length
+fn worth_rle<T: PartialEq>(values: &[T]) -> bool {
+    let budget = mem::size_of_val(values) / (mem::size_of::<T>() + 2);
+    let mut runs = 0;
+    for (index, value) in values.iter().enumerate() {
+        if index == 0 || values[index - 1] != *value {
+            runs += 1;
+            if runs >= budget {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn write_array<T: Primitive + PartialEq>(
+    context: &mut WriteContext,
+    values: &[T],
+    padded: bool,
+    rle: bool,
+    write: impl Fn(&mut Writer, T),
+) {
+    if context.write_out_of_band(&le_bytes(values)) {
+        return;
+    }
+    context.writer.var_int32(values.len() as i32);
+    if rle && context.get_fury().is_rle_arrays() {
+        context.use_experimental_format();
+        if worth_rle(values) {
+            context.writer.u8(RLE_FLAG);
+            let mut start = 0;
+            while start < values.len() {
+                let value = values[start];
+                let run = values[start..].iter().take_while(|v| **v == value).count();
+                context.writer.var_uint64(run as u64);
+                write(context.writer, value);
+                start += run;
+            }
+            return;
+        }
+        context.writer.u8(RAW_FLAG);
+    }
+    if padded {
+        write_padding(context);
+    }
+    write_elements(context.writer, values);
+}
+
+fn read_array<'bf, T: Primitive>(
+    context: &mut ReadContext<'_, 'bf>,
+    padded: bool,
+    rle: bool,
+    read: impl Fn(&mut Reader<'bf>) -> Result<T, Error>,
+) -> Result<Cow<'bf, [T]>, Error> {
+    if let Some(buffer) = context.read_out_of_band()? {
+        return read_buffer(context, buffer);
+    }
+    let len = context.read_collection_len()?;
+    context.record_elements::<T>(len);
+    if rle && context.get_fury().is_rle_arrays() {
+        match context.reader.u8()? {
+            RAW_FLAG => {}
+            RLE_FLAG => return read_runs(context, len, read).map(Cow::Owned),
+            flag => return Err(anyhow!("Unknown array encoding flag {flag}").into()),
+        }
+    }
+    if padded {
+        read_padding(context)?;
+    }
+    Ok(match read_in_place::<T>(context, len) {
+        Some(slice) => Cow::Borrowed(slice),
+        None => Cow::Owned(copy_elements(&mut context.reader, len)?),
+    })
+}
+
+/// Elements of an array handed out of band in `buffer`, viewed in place like those in the
+/// message when they can be.
+fn read_buffer<'bf, T: Primitive>(
+    context: &mut ReadContext<'_, 'bf>,
+    buffer: &'bf [u8],
+) -> Result<Cow<'bf, [T]>, Error> {
+    ensure!(
+        buffer.len() % mem::size_of::<T>() == 0,
+        "Out-of-band buffer of {} bytes doesn't hold whole elements of `{}`",
+        buffer.len(),
+        std::any::type_name::<T>()
+    );
+    let len = buffer.len() / mem::size_of::<T>();
+    context.record_elements::<T>(len);
+    let reader = mem::replace(&mut context.reader, Reader::new(buffer));
+    let elements = match read_in_place::<T>(context, len) {
+        Some(slice) => Cow::Borrowed(slice),
+        None => Cow::Owned(copy_elements(&mut context.reader, len)?),
+    };
+    context.reader = reader;
+    Ok(elements)
+}
+
+fn read_runs<'bf, T: Copy>(
+    context: &mut ReadContext<'_, 'bf>,
+    len: usize,
+    read: impl Fn(&mut Reader<'bf>) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    // runs may be much longer than the input, so that `len` is bounded before making room
+    let expansion = context.get_fury().get_max_rle_expansion();
+    let max = context.reader.remaining().saturating_mul(expansion);
+    let size = len.saturating_mul(mem::size_of::<T>());
+    ensure!(
+        size <= max,
+        Error::LimitExceeded {
+            limit: "max_rle_expansion",
+            value: size,
+            max,
+        }
+    );
+    let mut result = Vec::with_capacity(len);
+    while result.len() < len {
+        let run = context.reader.var_uint64()? as usize;
+        ensure!(
+            run > 0 && run <= len - result.len(),
+            "Run of {run} elements doesn't fit in an array of {len}"
+        );
+        let value = read(&mut context.reader)?;
+        result.resize(result.len() + run, value);
+    }
+    Ok(result)
+}
+
 macro_rules! impl_primitive_vec {
-    ($name: ident, $ty:tt, $field_type: expr) => {
+    ($name: ident, $ty:tt, $field_type: expr, $padded: expr, $rle: expr) => {
         impl Serializer for Vec<$ty> {
             fn write(&self, context: &mut WriteContext) {
-                context.writer.var_int32(self.len() as i32);
-                context.writer.reserve(self.len() * mem::size_of::<$ty>());
-                context.writer.bytes(to_u8_slice(self));
+                write_array(context, self, $padded, $rle, Writer::$name);
             }
 
             fn read(context: &mut ReadContext) -> Result<Self, Error> {
-                // length
-                let len = (context.reader.var_int32() as usize);
-                let is_aligned = context.reader.aligned::<$ty>();
-                if is_aligned {
-                    let slice = context.reader.bytes(len * mem::size_of::<$ty>());
-                    Ok(
-                        unsafe { std::slice::from_raw_parts(slice.as_ptr().cast::<$ty>(), len) }
-                            .to_vec(),
-                    )
-                } else {
-                    let mut result = Vec::with_capacity(len);
-                    for _i in 0..len {
-                        result.push(context.reader.$name());
-                    }
-                    Ok(result)
-                }
+                Ok(read_array(context, $padded, $rle, Reader::$name)?.into_owned())
             }
 
             fn reserved_space() -> usize {
-                mem::size_of::<i32>()
+                mem::size_of::<i32>() + if $padded { ARRAY_ALIGNMENT } else { 0 }
             }
 
             fn get_type_id(_fury: &Fury) -> i16 {
@@ -67,30 +293,178 @@ macro_rules! impl_primitive_vec {
     };
 }
 
-impl Serializer for Vec<bool> {
+/// Written like `Vec<u8>`, reading borrows from the input like `&str` does.
+impl Serializer for &[u8] {
     fn write(&self, context: &mut WriteContext) {
-        context.writer.var_int32(self.len() as i32);
-        context.writer.bytes(to_u8_slice(self));
+        write_array(context, self, false, false, Writer::u8);
+    }
+
+    fn read(_context: &mut ReadContext) -> Result<Self, Error> {
+        Err(anyhow!(
+            "`&[u8]` can only be read with `Fury::deserialize_borrowed`, read it as `Vec<u8>`"
+        ))?
     }
 
     fn reserved_space() -> usize {
-        mem::size_of::<u8>()
+        <Vec<u8> as Serializer>::reserved_space()
+    }
+
+    fn get_type_id(fury: &Fury) -> i16 {
+        <Vec<u8> as Serializer>::get_type_id(fury)
+    }
+}
+
+impl<'bf> BorrowDeserialize<'bf> for &'bf [u8] {
+    fn read_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        if let Some(buffer) = context.read_out_of_band()? {
+            return Ok(buffer);
+        }
+        let len = context.read_collection_len()?;
+        match context.reader.bytes(len)? {
+            Cow::Borrowed(bytes) => Ok(bytes),
+            Cow::Owned(_) => Err(anyhow!(
+                "`&[u8]` can't borrow bytes split across segments, read them as `Vec<u8>`"
+            ))?,
+        }
+    }
+}
+
+/// `Cow<[T]>` shares the wire form of `Vec<T>` but borrows the elements from the input in
+/// [`DecodeMode::Borrowed`] when they can be viewed in place, see [`Fury::aligned_arrays`].
+macro_rules! impl_primitive_cow {
+    ($ty:tt, $rle: expr) => {
+        impl<'a> Serializer for Cow<'a, [$ty]> {
+            fn write(&self, context: &mut WriteContext) {
+                write_array(context, self, true, $rle, Writer::$ty);
+            }
+
+            fn read(context: &mut ReadContext) -> Result<Self, Error> {
+                Ok(Cow::Owned(<Vec<$ty> as Serializer>::read(context)?))
+            }
+
+            fn reserved_space() -> usize {
+                <Vec<$ty> as Serializer>::reserved_space()
+            }
+
+            fn get_type_id(fury: &Fury) -> i16 {
+                <Vec<$ty> as Serializer>::get_type_id(fury)
+            }
+        }
+
+        impl<'bf> BorrowDeserialize<'bf> for Cow<'bf, [$ty]> {
+            fn read_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+                if context.decode_mode == DecodeMode::Owned {
+                    return <Self as Serializer>::read(context);
+                }
+                read_array(context, true, $rle, Reader::$ty)
+            }
+        }
+    };
+}
+
+impl_primitive_vec!(u8, u8, FieldType::BINARY, false, false);
+impl_primitive_vec!(i16, i16, FieldType::FuryPrimitiveShortArray, true, true);
+impl_primitive_vec!(i32, i32, FieldType::FuryPrimitiveIntArray, true, true);
+impl_primitive_vec!(i64, i64, FieldType::FuryPrimitiveLongArray, true, true);
+impl_primitive_vec!(f32, f32, FieldType::FuryPrimitiveFloatArray, true, false);
+
+impl_primitive_cow!(i16, true);
+impl_primitive_cow!(i32, true);
+impl_primitive_cow!(i64, true);
+impl_primitive_cow!(f32, false);
+
+// `f64` arrays are written as `f32` when every element allows it, see `Fury::compact_floats`
+fn write_f64s(context: &mut WriteContext, values: &[f64]) {
+    if context.write_out_of_band(&le_bytes(values)) {
+        return;
+    }
+    context.writer.var_int32(values.len() as i32);
+    let compact = context.get_fury().is_compact_floats();
+    if compact {
+        context.use_experimental_format();
+    }
+    if compact && values.iter().all(|value| fits_f32(*value)) {
+        context.writer.u8(FLOAT32_FLAG);
+        write_padding(context);
+        let values: Vec<f32> = values.iter().map(|value| *value as f32).collect();
+        write_elements(context.writer, &values);
+        return;
+    }
+    if compact {
+        context.writer.u8(FLOAT64_FLAG);
+    }
+    write_padding(context);
+    write_elements(context.writer, values);
+}
+
+fn read_f64s<'bf>(context: &mut ReadContext<'_, 'bf>) -> Result<Cow<'bf, [f64]>, Error> {
+    if let Some(buffer) = context.read_out_of_band()? {
+        return read_buffer(context, buffer);
+    }
+    let len = context.read_collection_len()?;
+    let flag = if context.get_fury().is_compact_floats() {
+        context.reader.u8()?
+    } else {
+        FLOAT64_FLAG
+    };
+    read_padding(context)?;
+    match flag {
+        FLOAT32_FLAG => Ok(Cow::Owned(match read_in_place::<f32>(context, len) {
+            Some(slice) => slice.iter().map(|value| *value as f64).collect(),
+            None => copy_elements::<f32>(&mut context.reader, len)?
+                .into_iter()
+                .map(|value| value as f64)
+                .collect(),
+        })),
+        FLOAT64_FLAG => Ok(match read_in_place::<f64>(context, len) {
+            Some(slice) => Cow::Borrowed(slice),
+            None => Cow::Owned(copy_elements(&mut context.reader, len)?),
+        }),
+        flag => Err(anyhow!("Unknown compact float flag {flag}"))?,
+    }
+}
+
+impl Serializer for Vec<f64> {
+    fn write(&self, context: &mut WriteContext) {
+        write_f64s(context, self);
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        Ok(read_f64s(context)?.into_owned())
+    }
+
+    fn reserved_space() -> usize {
+        mem::size_of::<i32>() + ARRAY_ALIGNMENT
     }
 
     fn get_type_id(_fury: &Fury) -> i16 {
-        FieldType::FuryPrimitiveBoolArray.into()
+        FieldType::FuryPrimitiveDoubleArray.into()
+    }
+}
+
+impl<'a> Serializer for Cow<'a, [f64]> {
+    fn write(&self, context: &mut WriteContext) {
+        write_f64s(context, self);
     }
 
     fn read(context: &mut ReadContext) -> Result<Self, Error> {
-        let size = context.reader.var_int32();
-        let bytes = context.reader.bytes(size as usize).to_vec();
-        Ok(unsafe { mem::transmute::<Vec<u8>, Vec<bool>>(bytes) })
+        Ok(Cow::Owned(read_f64s(context)?.into_owned()))
+    }
+
+    fn reserved_space() -> usize {
+        <Vec<f64> as Serializer>::reserved_space()
+    }
+
+    fn get_type_id(fury: &Fury) -> i16 {
+        <Vec<f64> as Serializer>::get_type_id(fury)
     }
 }
 
-impl_primitive_vec!(u8, u8, FieldType::BINARY);
-impl_primitive_vec!(i16, i16, FieldType::FuryPrimitiveShortArray);
-impl_primitive_vec!(i32, i32, FieldType::FuryPrimitiveIntArray);
-impl_primitive_vec!(i64, i64, FieldType::FuryPrimitiveLongArray);
-impl_primitive_vec!(f32, f32, FieldType::FuryPrimitiveFloatArray);
-impl_primitive_vec!(f64, f64, FieldType::FuryPrimitiveDoubleArray);
+impl<'bf> BorrowDeserialize<'bf> for Cow<'bf, [f64]> {
+    fn read_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        if context.decode_mode == DecodeMode::Owned {
+            return <Self as Serializer>::read(context);
+        }
+        read_f64s(context)
+    }
+}
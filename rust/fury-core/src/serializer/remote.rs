@@ -0,0 +1,176 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::error::Error;
+use crate::fury::Fury;
+use crate::resolver::context::{ReadContext, WriteContext};
+use crate::serializer::{BorrowDeserialize, Serializer, StructSerializer};
+use crate::type_def::{field_types, FieldTypeDef};
+use crate::types::{FuryGeneralList, Mode, RefFlag};
+use anyhow::anyhow;
+use std::any::TypeId;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// Serializer of a type defined in another crate, which can't implement [`Serializer`] itself
+/// because of the orphan rules.
+///
+/// Generated by `#[derive(Fury)]` on a mirror struct declaring the same fields as the foreign
+/// type, marked `#[fury(remote = "other_crate::Type")]`. The mirror is only used as a marker,
+/// values are wrapped in [`FuryExternal`].
+pub trait RemoteSerializer: 'static {
+    type Target;
+
+    fn write(value: &Self::Target, context: &mut WriteContext);
+
+    fn read(context: &mut ReadContext) -> Result<Self::Target, Error>;
+
+    fn reserved_space() -> usize;
+
+    fn type_def(fury: &Fury) -> Vec<u8>;
+
+    /// See [`StructSerializer::field_types`].
+    fn field_types(fury: &Fury) -> Vec<FieldTypeDef> {
+        field_types(&Self::type_def(fury))
+    }
+}
+
+/// A value of a foreign type, serialized through its mirror `R`.
+///
+/// Use it as a field type or register it like any other struct, e.g.
+/// `fury.register::<FuryExternal<PointDef>>(1)`, see also [`Fury::register_external`].
+pub struct FuryExternal<R: RemoteSerializer>(pub R::Target);
+
+impl<R: RemoteSerializer> FuryExternal<R> {
+    pub fn into_inner(self) -> R::Target {
+        self.0
+    }
+}
+
+impl<R: RemoteSerializer> Deref for FuryExternal<R> {
+    type Target = R::Target;
+
+    fn deref(&self) -> &R::Target {
+        &self.0
+    }
+}
+
+impl<R: RemoteSerializer> DerefMut for FuryExternal<R> {
+    fn deref_mut(&mut self) -> &mut R::Target {
+        &mut self.0
+    }
+}
+
+impl<R: RemoteSerializer> fmt::Debug for FuryExternal<R>
+where
+    R::Target: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<R: RemoteSerializer> PartialEq for FuryExternal<R>
+where
+    R::Target: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<R: RemoteSerializer> Serializer for FuryExternal<R> {
+    fn reserved_space() -> usize {
+        R::reserved_space()
+    }
+
+    fn write(&self, context: &mut WriteContext) {
+        context.record_type(TypeId::of::<Self>());
+        R::write(&self.0, context)
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        context.record_type(TypeId::of::<Self>());
+        Ok(FuryExternal(R::read(context)?))
+    }
+
+    fn serialize(&self, context: &mut WriteContext) {
+        match context.get_fury().get_mode() {
+            Mode::SchemaConsistent => super::serialize(self, context),
+            Mode::Compatible => {
+                context.writer.i8(RefFlag::NotNullValue as i8);
+                let meta_index = context.push_meta(TypeId::of::<Self>()) as i16;
+                context.writer.i16(meta_index);
+                self.write(context);
+            }
+        }
+    }
+
+    fn deserialize(context: &mut ReadContext) -> Result<Self, Error> {
+        match context.get_fury().get_mode() {
+            Mode::SchemaConsistent => super::deserialize(context),
+            Mode::Compatible => {
+                let ref_flag = context.reader.i8()?;
+                if ref_flag == (RefFlag::NotNullValue as i8)
+                    || ref_flag == (RefFlag::RefValue as i8)
+                {
+                    if ref_flag == (RefFlag::RefValue as i8) {
+                        context.ref_reader.reserve_ref_id()?;
+                    }
+                    // fields are read in declaration order, like derived structs do
+                    let _meta_index = context.reader.i16()?;
+                    Self::read(context)
+                } else if ref_flag == (RefFlag::Null as i8) {
+                    Err(anyhow!("Try to deserialize non-option type to null"))?
+                } else if ref_flag == (RefFlag::Ref as i8) {
+                    context.ref_reader.read_ref_id(&mut context.reader)?;
+                    Err(Error::Ref)
+                } else {
+                    Err(anyhow!("Unknown ref flag, value:{ref_flag}"))?
+                }
+            }
+        }
+    }
+
+    fn get_type_id(fury: &Fury) -> i16 {
+        fury.get_class_resolver()
+            .get_class_info(TypeId::of::<Self>())
+            .get_type_id() as i16
+    }
+}
+
+impl<R: RemoteSerializer> StructSerializer for FuryExternal<R> {
+    fn type_def(fury: &Fury) -> Vec<u8> {
+        R::type_def(fury)
+    }
+
+    fn field_types(fury: &Fury) -> Vec<FieldTypeDef> {
+        R::field_types(fury)
+    }
+}
+
+impl<'bf, R: RemoteSerializer> BorrowDeserialize<'bf> for FuryExternal<R> {
+    fn read_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        <Self as Serializer>::read(context)
+    }
+
+    fn deserialize_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        <Self as Serializer>::deserialize(context)
+    }
+}
+
+impl<R: RemoteSerializer> FuryGeneralList for FuryExternal<R> {}
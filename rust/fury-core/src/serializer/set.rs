@@ -19,32 +19,68 @@ use crate::error::Error;
 use crate::fury::Fury;
 use crate::resolver::context::ReadContext;
 use crate::resolver::context::WriteContext;
-use crate::serializer::Serializer;
-use crate::types::{FieldType, FuryGeneralList, SIZE_OF_REF_AND_TYPE};
-use std::collections::HashSet;
+use crate::serializer::{compacts_elements, element_space, Serializer};
+use crate::type_def::FieldTypeDef;
+use crate::types::{FieldType, FuryGeneralList};
+use std::collections::{BTreeSet, HashSet};
 use std::mem;
 
+fn read_set<T: Serializer, C: FromIterator<T>>(context: &mut ReadContext) -> Result<C, Error> {
+    // length
+    let len = context.read_elements_len()?;
+    context.record_elements::<T>(len);
+    let compact = compacts_elements::<T>(context.get_fury());
+    (0..len)
+        .map(|index| {
+            context.check_cancelled()?;
+            if compact {
+                T::read(context)
+            } else {
+                T::deserialize(context)
+            }
+            .map_err(|err| context.element_error(err, index))
+        })
+        .collect()
+}
+
+// unlike list elements, set elements keep the type id of derived structs
+fn serialize_element<T: Serializer>(value: &T, context: &mut WriteContext) {
+    if compacts_elements::<T>(context.get_fury()) {
+        value.write(context);
+    } else {
+        value.serialize(context);
+    }
+}
+
 impl<T: Serializer + Eq + std::hash::Hash> Serializer for HashSet<T> {
     fn write(&self, context: &mut WriteContext) {
         // length
-        context.writer.i32(self.len() as i32);
+        context.writer.var_int32(self.len() as i32);
 
-        let reserved_space =
-            (<T as Serializer>::reserved_space() + SIZE_OF_REF_AND_TYPE) * self.len();
+        let reserved_space = element_space::<T>(context.get_fury()) * self.len();
         context.writer.reserve(reserved_space);
 
+        #[cfg(feature = "testing")]
+        if context.get_fury().is_deterministic() {
+            let sorted = super::deterministic::sort_entries(
+                context.get_fury(),
+                self.iter(),
+                |i, context| serialize_element(*i, context),
+            );
+            for i in sorted {
+                serialize_element(i, context);
+            }
+            return;
+        }
+
         // key-value
         for i in self.iter() {
-            i.serialize(context);
+            serialize_element(i, context);
         }
     }
 
     fn read(context: &mut ReadContext) -> Result<Self, Error> {
-        // length
-        let len = context.reader.var_int32();
-        (0..len)
-            .map(|_| T::deserialize(context))
-            .collect::<Result<HashSet<_>, Error>>()
+        read_set(context)
     }
 
     fn reserved_space() -> usize {
@@ -54,6 +90,40 @@ impl<T: Serializer + Eq + std::hash::Hash> Serializer for HashSet<T> {
     fn get_type_id(_fury: &Fury) -> i16 {
         FieldType::FurySet.into()
     }
+
+    fn generic_types(fury: &Fury) -> Vec<FieldTypeDef> {
+        vec![FieldTypeDef::of::<T>(fury)]
+    }
 }
 
 impl<T: Serializer + Eq + std::hash::Hash> FuryGeneralList for HashSet<T> {}
+
+/// Written like a `HashSet`, in the order of the elements, which is stable already.
+impl<T: Serializer + Ord> Serializer for BTreeSet<T> {
+    fn write(&self, context: &mut WriteContext) {
+        context.writer.var_int32(self.len() as i32);
+        let reserved_space = element_space::<T>(context.get_fury()) * self.len();
+        context.writer.reserve(reserved_space);
+        for i in self.iter() {
+            serialize_element(i, context);
+        }
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        read_set(context)
+    }
+
+    fn reserved_space() -> usize {
+        mem::size_of::<i32>()
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::FurySet.into()
+    }
+
+    fn generic_types(fury: &Fury) -> Vec<FieldTypeDef> {
+        vec![FieldTypeDef::of::<T>(fury)]
+    }
+}
+
+impl<T: Serializer + Ord> FuryGeneralList for BTreeSet<T> {}
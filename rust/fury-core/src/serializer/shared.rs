@@ -0,0 +1,388 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! `Rc`, `Arc` and their `Weak` pointers, written with reference tracking.
+//!
+//! The first time a pointee is written it is flagged `RefValue`, later pointers to it are a
+//! `Ref` flag followed by the id of the pointee, the position of its `RefValue` among all of
+//! them. Readers rebuild the sharing from these ids. Pointees are read inside `new_cyclic`, so
+//! that `Weak` pointers inside them can point back to them: cycles must go through a `Weak`,
+//! like they have to in Rust to be dropped at all.
+//!
+//! A pointee flagging itself, such as the inner pointer of an `Rc<Rc<T>>` or an `Rc<Weak<T>>`,
+//! follows the `RefValue` flag of the outer pointer with its own flag.
+//!
+//! Null is never tracked, an `Option` of them writes its `None`s as null and the pointers it
+//! holds with reference tracking, but a pointer to `None` can't be written.
+
+use crate::error::Error;
+use crate::fury::Fury;
+use crate::resolver::context::{ReadContext, WriteContext};
+use crate::serializer::Serializer;
+use crate::type_def::FieldTypeDef;
+use crate::types::{FuryGeneralList, RefFlag};
+use anyhow::anyhow;
+use std::mem::MaybeUninit;
+use std::rc::{self, Rc};
+use std::sync::{self, Arc};
+
+/// `Rc` or `Arc` of `T`.
+trait Shared<T>: Clone + 'static {
+    type Weak: Clone + 'static;
+
+    /// Article and name for error messages.
+    const NAME: &'static str;
+
+    fn new(value: T) -> Self;
+
+    /// `new_cyclic` with a `data_fn` that may fail, `Weak`s handed out by it never upgrade then.
+    fn try_new_cyclic(data_fn: impl FnOnce(&Self::Weak) -> Result<T, Error>)
+        -> Result<Self, Error>;
+
+    fn downgrade(&self) -> Self::Weak;
+
+    fn dangling() -> Self::Weak;
+}
+
+// `new_cyclic` has to get a value from its closure, an uninitialized one if reading fails. The
+// pointers to `MaybeUninit<T>` are cast to pointers to `T`, which has the same layout, only
+// once it is initialized, and the `Weak<T>`s handed out meanwhile can't be upgraded before.
+macro_rules! impl_try_new_cyclic {
+    ($ptr: ident, $weak: ident) => {
+        fn try_new_cyclic(
+            data_fn: impl FnOnce(&Self::Weak) -> Result<T, Error>,
+        ) -> Result<Self, Error> {
+            let mut result = Ok(());
+            let uninit = $ptr::<MaybeUninit<T>>::new_cyclic(|weak| {
+                // SAFETY: `MaybeUninit<T>` is `repr(transparent)`
+                let weak = unsafe { $weak::Weak::from_raw(weak.clone().into_raw() as *const T) };
+                match data_fn(&weak) {
+                    Ok(value) => MaybeUninit::new(value),
+                    Err(err) => {
+                        result = Err(err);
+                        MaybeUninit::uninit()
+                    }
+                }
+            });
+            result?;
+            // SAFETY: initialized when `data_fn` succeeded, the layout is the same as above
+            Ok(unsafe { $ptr::from_raw($ptr::into_raw(uninit) as *const T) })
+        }
+    };
+}
+
+impl<T: 'static> Shared<T> for Rc<T> {
+    type Weak = rc::Weak<T>;
+
+    const NAME: &'static str = "an `Rc`";
+
+    fn new(value: T) -> Self {
+        Rc::new(value)
+    }
+
+    impl_try_new_cyclic!(Rc, rc);
+
+    fn downgrade(&self) -> Self::Weak {
+        Rc::downgrade(self)
+    }
+
+    fn dangling() -> Self::Weak {
+        rc::Weak::new()
+    }
+}
+
+impl<T: 'static> Shared<T> for Arc<T> {
+    type Weak = sync::Weak<T>;
+
+    const NAME: &'static str = "an `Arc`";
+
+    fn new(value: T) -> Self {
+        Arc::new(value)
+    }
+
+    impl_try_new_cyclic!(Arc, sync);
+
+    fn downgrade(&self) -> Self::Weak {
+        Arc::downgrade(self)
+    }
+
+    fn dangling() -> Self::Weak {
+        sync::Weak::new()
+    }
+}
+
+// `write` writes the pointee with its ref flag, which is patched to `RefValue`. A pointee written
+// as null, e.g. the `None` of an `Rc<Option<T>>`, stays an untracked null, it reads back as a
+// new pointer to it.
+//
+// A pointee with ref flags of its own, e.g. the inner `Rc` of an `Rc<Rc<T>>`, follows a
+// `RefValue` flag of the outer pointer instead, `own_flag` is `T::tracks_refs()`.
+fn write_tracked(
+    ptr: *const (),
+    own_flag: bool,
+    context: &mut WriteContext,
+    write: impl FnOnce(&mut WriteContext),
+) {
+    let (id, is_new) = context.ref_writer.get_or_assign(ptr);
+    if !is_new {
+        context.writer.i8(RefFlag::Ref as i8);
+        context.writer.var_int32(id as i32);
+        return;
+    }
+    if own_flag {
+        context.writer.i8(RefFlag::RefValue as i8);
+        write(context);
+        return;
+    }
+    let start = context.writer.len();
+    write(context);
+    let flag = context.writer.as_slice()[start];
+    if flag == RefFlag::Null as i8 as u8 {
+        context.ref_writer.unassign_last(ptr);
+        return;
+    }
+    assert!(
+        flag == RefFlag::NotNullValue as i8 as u8,
+        "Serializers writing ref flags of their own must return true from `tracks_refs`"
+    );
+    context
+        .writer
+        .set_bytes(start, &[RefFlag::RefValue as i8 as u8]);
+}
+
+fn peek_flag(context: &ReadContext) -> Option<i8> {
+    context.reader.peek_u8().map(|flag| flag as i8)
+}
+
+/// Returns the id following a `Ref` flag.
+fn read_ref(context: &mut ReadContext) -> Result<u32, Error> {
+    context.reader.i8()?;
+    context.ref_reader.read_ref_id(&mut context.reader)
+}
+
+fn read_shared<'de, 'bf, T, P: Shared<T>>(
+    context: &mut ReadContext<'de, 'bf>,
+    own_flag: bool,
+    read: impl FnOnce(&mut ReadContext<'de, 'bf>) -> Result<T, Error>,
+) -> Result<P, Error> {
+    match peek_flag(context) {
+        Some(flag) if flag == RefFlag::Ref as i8 => {
+            let id = read_ref(context)?;
+            let shared = context.ref_reader.get_ref(id)?;
+            if let Some(shared) = shared.downcast_ref::<P>() {
+                return Ok(shared.clone());
+            }
+            if shared.is::<P::Weak>() {
+                Err(anyhow!(
+                    "Ref id {id} points to {} still being read, cycles must go through a `Weak`",
+                    P::NAME
+                ))?
+            }
+            Err(anyhow!(
+                "Ref id {id} doesn't point to a `{}`",
+                std::any::type_name::<P>()
+            ))?
+        }
+        Some(flag) if flag == RefFlag::RefValue as i8 => read_cyclic(context, own_flag, read),
+        // not tracked, e.g. by a peer without reference tracking
+        _ => Ok(P::new(read(context)?)),
+    }
+}
+
+/// Reads a pointee flagged `RefValue` inside `new_cyclic`, once. Its ref is a `Weak` until it is
+/// complete, which `Weak`s inside it pointing back to it get.
+fn read_cyclic<'de, 'bf, T, P: Shared<T>>(
+    context: &mut ReadContext<'de, 'bf>,
+    own_flag: bool,
+    read: impl FnOnce(&mut ReadContext<'de, 'bf>) -> Result<T, Error>,
+) -> Result<P, Error> {
+    let id = if own_flag {
+        context.reader.i8()?;
+        context.ref_reader.reserve_ref_id()?
+    } else {
+        context.ref_reader.reserve_ahead()?
+    };
+    let shared = P::try_new_cyclic(|weak| {
+        context.ref_reader.set_ref(id, Rc::new(weak.clone()));
+        let value = read(context);
+        context.ref_reader.clear_ahead();
+        value
+    })?;
+    context.ref_reader.set_ref(id, Rc::new(shared.clone()));
+    Ok(shared)
+}
+
+// `read` reads the pointee as a strong pointer
+fn read_weak<'de, 'bf, T, P: Shared<T>>(
+    context: &mut ReadContext<'de, 'bf>,
+    read: impl FnOnce(&mut ReadContext<'de, 'bf>) -> Result<P, Error>,
+) -> Result<P::Weak, Error> {
+    match peek_flag(context) {
+        Some(flag) if flag == RefFlag::Null as i8 => {
+            context.reader.i8()?;
+            Ok(P::dangling())
+        }
+        Some(flag) if flag == RefFlag::Ref as i8 => {
+            let id = read_ref(context)?;
+            let shared = context.ref_reader.get_ref(id)?.clone();
+            if let Some(weak) = shared.downcast_ref::<P::Weak>() {
+                return Ok(weak.clone());
+            }
+            match shared.downcast_ref::<P>() {
+                Some(shared) => Ok(shared.downgrade()),
+                None => Err(anyhow!(
+                    "Ref id {id} doesn't point to a `{}`",
+                    std::any::type_name::<P>()
+                ))?,
+            }
+        }
+        // the ref table keeps the pointee alive until the whole message is read
+        _ => Ok(read(context)?.downgrade()),
+    }
+}
+
+macro_rules! impl_shared {
+    ($ptr: ident, $weak: ty) => {
+        impl<T: Serializer + 'static> Serializer for $ptr<T> {
+            fn reserved_space() -> usize {
+                // the pointee may be a ref, and recursive types would never get to the end
+                0
+            }
+
+            fn write(&self, context: &mut WriteContext) {
+                T::write(self, context)
+            }
+
+            fn read(context: &mut ReadContext) -> Result<Self, Error> {
+                Ok($ptr::new(T::read(context)?))
+            }
+
+            fn serialize(&self, context: &mut WriteContext) {
+                let ptr = $ptr::as_ptr(self) as *const ();
+                write_tracked(ptr, T::tracks_refs(), context, |context| {
+                    T::serialize(self, context)
+                });
+            }
+
+            fn deserialize(context: &mut ReadContext) -> Result<Self, Error> {
+                read_shared(context, T::tracks_refs(), T::deserialize)
+            }
+
+            fn get_type_id(fury: &Fury) -> i16 {
+                T::get_type_id(fury)
+            }
+
+            fn missing_serializer(fury: &Fury) -> Option<&'static str> {
+                T::missing_serializer(fury)
+            }
+
+            fn generic_types(fury: &Fury) -> Vec<FieldTypeDef> {
+                T::generic_types(fury)
+            }
+
+            fn type_id_of(&self, fury: &Fury) -> i16 {
+                T::type_id_of(self, fury)
+            }
+
+            fn tracks_refs() -> bool {
+                true
+            }
+
+            fn elides_type_id(fury: &Fury) -> bool {
+                T::elides_type_id(fury)
+            }
+
+            fn serialize_declared(&self, context: &mut WriteContext) {
+                let ptr = $ptr::as_ptr(self) as *const ();
+                write_tracked(ptr, T::tracks_refs(), context, |context| {
+                    T::serialize_declared(self, context)
+                });
+            }
+
+            fn deserialize_declared(context: &mut ReadContext) -> Result<Self, Error> {
+                read_shared(context, T::tracks_refs(), T::deserialize_declared)
+            }
+        }
+
+        impl<T: Serializer + 'static> FuryGeneralList for $ptr<T> {}
+
+        /// Written like the pointer it was downgraded from, or as null once that was dropped.
+        impl<T: Serializer + 'static> Serializer for $weak {
+            fn reserved_space() -> usize {
+                0
+            }
+
+            fn write(&self, context: &mut WriteContext) {
+                match self.upgrade() {
+                    Some(shared) => T::write(&shared, context),
+                    None => panic!("A dangling `Weak` can only be written with its ref flag"),
+                }
+            }
+
+            fn read(context: &mut ReadContext) -> Result<Self, Error> {
+                Ok($ptr::downgrade(&$ptr::new(T::read(context)?)))
+            }
+
+            fn serialize(&self, context: &mut WriteContext) {
+                match self.upgrade() {
+                    Some(shared) => shared.serialize(context),
+                    None => context.writer.i8(RefFlag::Null as i8),
+                }
+            }
+
+            fn deserialize(context: &mut ReadContext) -> Result<Self, Error> {
+                read_weak(context, <$ptr<T> as Serializer>::deserialize)
+            }
+
+            fn get_type_id(fury: &Fury) -> i16 {
+                T::get_type_id(fury)
+            }
+
+            fn missing_serializer(fury: &Fury) -> Option<&'static str> {
+                T::missing_serializer(fury)
+            }
+
+            fn generic_types(fury: &Fury) -> Vec<FieldTypeDef> {
+                T::generic_types(fury)
+            }
+
+            fn tracks_refs() -> bool {
+                true
+            }
+
+            fn elides_type_id(fury: &Fury) -> bool {
+                T::elides_type_id(fury)
+            }
+
+            fn serialize_declared(&self, context: &mut WriteContext) {
+                match self.upgrade() {
+                    Some(shared) => shared.serialize_declared(context),
+                    None => context.writer.i8(RefFlag::Null as i8),
+                }
+            }
+
+            fn deserialize_declared(context: &mut ReadContext) -> Result<Self, Error> {
+                read_weak(context, <$ptr<T> as Serializer>::deserialize_declared)
+            }
+        }
+
+        impl<T: Serializer + 'static> FuryGeneralList for $weak {}
+    };
+}
+
+impl_shared!(Rc, rc::Weak<T>);
+impl_shared!(Arc, sync::Weak<T>);
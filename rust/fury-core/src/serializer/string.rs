@@ -15,32 +15,173 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::buffer::{Reader, Writer};
 use crate::error::Error;
 use crate::fury::Fury;
 use crate::resolver::context::ReadContext;
 use crate::resolver::context::WriteContext;
-use crate::serializer::Serializer;
-use crate::types::{FieldType, FuryGeneralList};
+use crate::serializer::{BorrowDeserialize, Serializer};
+use crate::types::{DecodeMode, FieldType, FuryGeneralList, StringFlag};
+use anyhow::anyhow;
+use std::borrow::Cow;
 use std::mem;
 
+/// Writes `value` behind a var_uint64 of its size in bytes shifted left by two, with its
+/// [`StringFlag`] in the low bits. ASCII text is flagged as Latin-1, which peers such as Java
+/// keep as is, anything else is UTF-8. `str::is_ascii` checks a word at a time.
+pub(crate) fn write_str(writer: &mut Writer, value: &str) {
+    let flag = if value.is_ascii() {
+        StringFlag::LATIN1
+    } else {
+        StringFlag::UTF8
+    };
+    writer.var_uint64((value.len() as u64) << 2 | flag as u64);
+    writer.bytes(value.as_bytes());
+}
+
+/// Reads the header written by [`write_str`]: the encoding and size in bytes of the string,
+/// which must be left in the input.
+pub(crate) fn read_str_header(reader: &mut Reader) -> Result<(StringFlag, usize), Error> {
+    let header = reader.var_uint64()?;
+    let flag = StringFlag::try_from((header & 0b11) as u8)
+        .map_err(|_| anyhow!("Unknown string encoding, value:{}", header & 0b11))?;
+    let len = usize::try_from(header >> 2).unwrap_or(usize::MAX);
+    reader.check_remaining(len)?;
+    Ok((flag, len))
+}
+
+/// Decodes the bytes of a string, borrowing them if they are UTF-8 as is. Invalid sequences are
+/// replaced.
+pub(crate) fn decode_str(flag: StringFlag, bytes: Cow<[u8]>) -> Cow<str> {
+    match flag {
+        StringFlag::LATIN1 if !bytes.is_ascii() => {
+            Cow::Owned(bytes.iter().map(|&byte| char::from(byte)).collect())
+        }
+        StringFlag::UTF16 => {
+            let units = bytes
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]));
+            Cow::Owned(
+                char::decode_utf16(units)
+                    .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect(),
+            )
+        }
+        _ => match bytes {
+            Cow::Borrowed(bytes) => String::from_utf8_lossy(bytes),
+            Cow::Owned(bytes) => Cow::Owned(String::from_utf8_lossy(&bytes).into_owned()),
+        },
+    }
+}
+
+fn read_str<'bf>(reader: &mut Reader<'bf>) -> Result<Cow<'bf, str>, Error> {
+    let (flag, len) = read_str_header(reader)?;
+    Ok(decode_str(flag, reader.bytes(len)?))
+}
+
 impl Serializer for String {
     fn reserved_space() -> usize {
         mem::size_of::<i32>()
     }
 
     fn write(&self, context: &mut WriteContext) {
-        context.writer.var_int32(self.len() as i32);
-        context.writer.bytes(self.as_bytes());
+        write_str(context.writer, self);
     }
 
     fn read(context: &mut ReadContext) -> Result<Self, Error> {
-        let len = context.reader.var_int32();
-        Ok(context.reader.string(len as usize))
+        let value = read_str(&mut context.reader)?.into_owned();
+        context.record_allocation(value.len());
+        Ok(value)
     }
 
     fn get_type_id(_fury: &Fury) -> i16 {
         FieldType::STRING.into()
     }
+
+    fn is_plain() -> bool {
+        true
+    }
 }
 
 impl FuryGeneralList for String {}
+
+impl<'a> Serializer for Cow<'a, str> {
+    fn reserved_space() -> usize {
+        mem::size_of::<i32>()
+    }
+
+    fn write(&self, context: &mut WriteContext) {
+        write_str(context.writer, self);
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        Ok(Cow::Owned(String::read(context)?))
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::STRING.into()
+    }
+
+    fn is_plain() -> bool {
+        true
+    }
+}
+
+impl<'bf> BorrowDeserialize<'bf> for Cow<'bf, str> {
+    fn read_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        if context.decode_mode == DecodeMode::Owned {
+            return <Self as Serializer>::read(context);
+        }
+        read_str(&mut context.reader)
+    }
+}
+
+impl<'a> FuryGeneralList for Cow<'a, str> {}
+
+/// Written like `String`. Reading borrows from the input, which only
+/// [`BorrowDeserialize::read_borrowed`] can do, whatever the [`DecodeMode`], and fails where the
+/// string is split across segments or isn't UTF-8 on the wire; peers holding on to the value
+/// declare `String` instead.
+impl Serializer for &str {
+    fn reserved_space() -> usize {
+        mem::size_of::<i32>()
+    }
+
+    fn write(&self, context: &mut WriteContext) {
+        write_str(context.writer, self);
+    }
+
+    fn read(_context: &mut ReadContext) -> Result<Self, Error> {
+        Err(anyhow!(
+            "`&str` can only be read with `Fury::deserialize_borrowed`, read it as `String` or `Cow<str>`"
+        ))?
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::STRING.into()
+    }
+
+    fn is_plain() -> bool {
+        true
+    }
+}
+
+impl<'bf> BorrowDeserialize<'bf> for &'bf str {
+    fn read_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        let (flag, len) = read_str_header(&mut context.reader)?;
+        match context.reader.bytes(len)? {
+            Cow::Borrowed(bytes) if flag == StringFlag::UTF8 || bytes.is_ascii() => {
+                Ok(std::str::from_utf8(bytes)
+                    .map_err(|err| anyhow!("`&str` can't borrow invalid UTF-8: {err}"))?)
+            }
+            Cow::Borrowed(_) => Err(anyhow!(
+                "`&str` can't borrow a {flag:?} string, read it as `Cow<str>`"
+            ))?,
+            Cow::Owned(_) => Err(anyhow!(
+                "`&str` can't borrow a string split across segments, read it as `Cow<str>`"
+            ))?,
+        }
+    }
+}
+
+impl FuryGeneralList for &str {}
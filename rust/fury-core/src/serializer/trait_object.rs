@@ -0,0 +1,143 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! `Box<dyn Trait>` fields for user traits, see [`fury_trait_object!`](crate::fury_trait_object).
+
+use crate::error::Error;
+use crate::resolver::context::{ReadContext, WriteContext};
+use crate::serializer::any::serialize_dyn;
+use crate::serializer::Serializer;
+use std::any::Any;
+
+/// Supertrait of the traits serialized as `Box<dyn Trait>`, which lets the value be looked at as
+/// its concrete type. It is implemented for every `'static` type, a `Box` included: call it on
+/// the trait object, `(*boxed).as_any()`.
+pub trait AsAny: Any {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Writes the value of a `Box<dyn Trait>` as a `Box<dyn Any>` holding it, for
+/// `fury_trait_object!`.
+pub fn serialize_trait_object(value: &dyn Any, context: &mut WriteContext) {
+    serialize_dyn(value, context)
+}
+
+/// Reads a value written by [`serialize_trait_object`], as a `Box<dyn Any>` for
+/// `fury_trait_object!` to downcast to the implementors of the trait.
+pub fn deserialize_trait_object(context: &mut ReadContext) -> Result<Box<dyn Any>, Error> {
+    <Box<dyn Any> as Serializer>::deserialize(context)
+}
+
+/// Implements the serializers of `Box<dyn Trait>` for a trait of yours, so that it can be the
+/// type of a field, an element or a root, like `Box<dyn Any>`. The trait must have
+/// [`AsAny`] as a supertrait, and its implementors must be registered, or builtins a
+/// `Box<dyn Any>` can hold.
+///
+/// Values are written with the type id of their concrete type and read back as the implementor
+/// registered with that id, which must be one of those listed. Reading another type fails.
+///
+/// ```
+/// use fury_core::fury::Fury;
+/// use fury_core::fury_trait_object;
+/// use fury_core::serializer::AsAny;
+///
+/// trait Label: AsAny {
+///     fn text(&self) -> &str;
+/// }
+///
+/// // a builtin here, usually derived structs
+/// impl Label for String {
+///     fn text(&self) -> &str {
+///         self
+///     }
+/// }
+///
+/// fury_trait_object!(Label: String);
+///
+/// let fury = Fury::default();
+/// let label: Box<dyn Label> = Box::new(String::from("fragile"));
+/// let bin = fury.serialize(&label);
+/// assert_eq!(fury.deserialize::<Box<dyn Label>>(&bin).unwrap().text(), "fragile");
+/// ```
+#[macro_export]
+macro_rules! fury_trait_object {
+    ($trait:path: $($implementor:ty),+ $(,)?) => {
+        impl $crate::serializer::Serializer for Box<dyn $trait> {
+            fn reserved_space() -> usize {
+                0
+            }
+
+            fn write(&self, _context: &mut $crate::resolver::context::WriteContext) {
+                unreachable!("trait objects are written by `serialize`")
+            }
+
+            fn read(
+                _context: &mut $crate::resolver::context::ReadContext,
+            ) -> Result<Self, $crate::error::Error> {
+                unreachable!("trait objects are read by `deserialize`")
+            }
+
+            fn get_type_id(_fury: &$crate::fury::Fury) -> i16 {
+                $crate::types::FieldType::FuryTypeTag.into()
+            }
+
+            fn serialize(&self, context: &mut $crate::resolver::context::WriteContext) {
+                let value = $crate::serializer::AsAny::as_any(self.as_ref());
+                $crate::serializer::serialize_trait_object(value, context)
+            }
+
+            fn deserialize(
+                context: &mut $crate::resolver::context::ReadContext,
+            ) -> Result<Self, $crate::error::Error> {
+                let value = $crate::serializer::deserialize_trait_object(context)?;
+                $(
+                    let value = match value.downcast::<$implementor>() {
+                        Ok(value) => return Ok(value),
+                        Err(value) => value,
+                    };
+                )+
+                let _ = value;
+                Err($crate::error::AnyhowError::msg(format!(
+                    "The value read isn't one of the implementors of `{}`",
+                    stringify!($trait)
+                )))?
+            }
+        }
+
+        impl<'bf> $crate::serializer::BorrowDeserialize<'bf> for Box<dyn $trait> {
+            fn read_borrowed(
+                context: &mut $crate::resolver::context::ReadContext<'_, 'bf>,
+            ) -> Result<Self, $crate::error::Error> {
+                <Self as $crate::serializer::Serializer>::read(context)
+            }
+
+            fn deserialize_borrowed(
+                context: &mut $crate::resolver::context::ReadContext<'_, 'bf>,
+            ) -> Result<Self, $crate::error::Error> {
+                <Self as $crate::serializer::Serializer>::deserialize(context)
+            }
+        }
+
+        impl $crate::types::FuryGeneralList for Box<dyn $trait> {}
+    };
+}
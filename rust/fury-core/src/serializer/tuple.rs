@@ -0,0 +1,63 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::ensure;
+use crate::error::Error;
+use crate::fury::Fury;
+use crate::resolver::context::{ReadContext, WriteContext};
+use crate::serializer::Serializer;
+use crate::types::{FieldType, SIZE_OF_REF_AND_TYPE};
+
+// Tuples are written as lists of their elements, each with its own type like the elements of
+// a Python tuple, so a list of exactly that many elements of those types reads back as one.
+macro_rules! impl_tuple {
+    ($len:expr; $($name:ident $index:tt),+) => {
+        impl<$($name: Serializer),+> Serializer for ($($name,)+) {
+            fn write(&self, context: &mut WriteContext) {
+                context.writer.var_int32($len);
+                $(self.$index.serialize(context);)+
+            }
+
+            fn read(context: &mut ReadContext) -> Result<Self, Error> {
+                let len = context.reader.var_int32()?;
+                ensure!(
+                    len == $len,
+                    "Expected a tuple of {} elements, the list has {len}",
+                    $len
+                );
+                Ok(($($name::deserialize(context)?,)+))
+            }
+
+            fn reserved_space() -> usize {
+                $(<$name as Serializer>::reserved_space() + SIZE_OF_REF_AND_TYPE +)+ 1
+            }
+
+            fn get_type_id(_fury: &Fury) -> i16 {
+                FieldType::ARRAY.into()
+            }
+        }
+    };
+}
+
+impl_tuple!(1; A 0);
+impl_tuple!(2; A 0, B 1);
+impl_tuple!(3; A 0, B 1, C 2);
+impl_tuple!(4; A 0, B 1, C 2, D 3);
+impl_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+impl_tuple!(7; A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_tuple!(8; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
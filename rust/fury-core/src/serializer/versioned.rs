@@ -0,0 +1,195 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Types whose encoding changes entirely between schema versions, see
+//! [`Fury::register_versioned`].
+//!
+//! A value is written with the type id like any registered type, then the version as a varint
+//! and the body produced by the serializer of that version. Readers dispatch on the version, so
+//! they keep reading data written by older peers once they register their serializers too.
+
+use crate::ensure;
+use crate::error::Error;
+use crate::fury::Fury;
+use crate::meta::TypeMeta;
+use crate::resolver::context::{ReadContext, WriteContext};
+use crate::serializer::{BorrowDeserialize, Serializer, StructSerializer};
+use crate::types::FuryGeneralList;
+use anyhow::anyhow;
+use std::any::{Any, TypeId};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+/// Encoding of `T` for one schema version.
+pub trait VersionSerializer<T>: Send + Sync {
+    fn write(&self, value: &T, context: &mut WriteContext);
+
+    fn read(&self, context: &mut ReadContext) -> Result<T, Error>;
+}
+
+/// Serializers of a versioned type by version, the newest one writes.
+pub(crate) struct VersionTable {
+    // `Box<dyn VersionSerializer<T>>` each
+    serializers: BTreeMap<u32, Box<dyn Any + Send + Sync>>,
+}
+
+impl VersionTable {
+    pub(crate) fn new() -> VersionTable {
+        VersionTable {
+            serializers: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn insert<T: 'static>(
+        &mut self,
+        version: u32,
+        serializer: Box<dyn VersionSerializer<T>>,
+    ) -> Result<(), Error> {
+        ensure!(
+            !self.serializers.contains_key(&version),
+            "Version {} of `{}` is already registered",
+            version,
+            std::any::type_name::<T>()
+        );
+        self.serializers.insert(version, Box::new(serializer));
+        Ok(())
+    }
+
+    pub(crate) fn versions(&self) -> impl Iterator<Item = u32> + '_ {
+        self.serializers.keys().copied()
+    }
+
+    fn get<T: 'static>(&self, version: u32) -> Option<&dyn VersionSerializer<T>> {
+        self.serializers
+            .get(&version)
+            .and_then(|serializer| serializer.downcast_ref::<Box<dyn VersionSerializer<T>>>())
+            .map(|serializer| serializer.as_ref())
+    }
+
+    fn newest<T: 'static>(&self) -> (u32, &dyn VersionSerializer<T>) {
+        let version = *self
+            .serializers
+            .keys()
+            .next_back()
+            .expect("registered with a first version");
+        let serializer = self.get(version).expect("registered for the same type");
+        (version, serializer)
+    }
+}
+
+fn version_table<T: 'static>(fury: &Fury) -> &VersionTable {
+    fury.get_class_resolver()
+        .get_version_table(TypeId::of::<Versioned<T>>())
+        .unwrap_or_else(|| {
+            panic!(
+                "`{}` is written as `Versioned` but isn't registered with `Fury::register_versioned`",
+                std::any::type_name::<T>()
+            )
+        })
+}
+
+/// A value of a type registered with [`Fury::register_versioned`], serialized by the
+/// serializer of its version. Use it as a field type or as the root type.
+pub struct Versioned<T>(pub T);
+
+impl<T> Versioned<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Versioned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Versioned<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Versioned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for Versioned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: 'static> Serializer for Versioned<T> {
+    fn reserved_space() -> usize {
+        mem::size_of::<u32>()
+    }
+
+    fn write(&self, context: &mut WriteContext) {
+        context.record_type(TypeId::of::<Self>());
+        let (version, serializer) = version_table::<T>(context.get_fury()).newest::<T>();
+        context.writer.var_int32(version as i32);
+        serializer.write(&self.0, context);
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        context.record_type(TypeId::of::<Self>());
+        let version = context.reader.var_int32()? as u32;
+        let table = version_table::<T>(context.get_fury());
+        let serializer = table.get::<T>(version).ok_or_else(|| {
+            anyhow!(
+                "Version {version} of `{}` has no registered serializer, registered versions: {:?}",
+                std::any::type_name::<T>(),
+                table.versions().collect::<Vec<_>>()
+            )
+        })?;
+        Ok(Versioned(serializer.read(context)?))
+    }
+
+    fn get_type_id(fury: &Fury) -> i16 {
+        fury.get_class_resolver()
+            .get_class_info(TypeId::of::<Self>())
+            .get_type_id() as i16
+    }
+}
+
+impl<T: 'static> StructSerializer for Versioned<T> {
+    // the layout is up to the version serializers
+    fn type_def(_fury: &Fury) -> Vec<u8> {
+        TypeMeta::from_fields(0, Vec::new())
+            .to_bytes()
+            .expect("no fields to encode")
+    }
+}
+
+impl<T: 'static> FuryGeneralList for Versioned<T> {}
+
+impl<'bf, T: 'static> BorrowDeserialize<'bf> for Versioned<T> {
+    fn read_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        <Self as Serializer>::read(context)
+    }
+
+    fn deserialize_borrowed(context: &mut ReadContext<'_, 'bf>) -> Result<Self, Error> {
+        <Self as Serializer>::deserialize(context)
+    }
+}
@@ -0,0 +1,49 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Complexity of the messages read, see [`StatsCollector`].
+
+/// What a message held beyond its size, which tells apart messages that take the same bytes
+/// but not the same work to read, e.g. a blob and as many small structs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DecodeStats {
+    /// Bytes of the input the message spans.
+    pub bytes: usize,
+    /// Elements of lists, sets and arrays, and entries of maps.
+    pub elements: u64,
+    /// Derived structs.
+    pub structs: u64,
+    /// Estimate of the heap memory the value takes: elements of collections at their size in
+    /// memory and the bytes of strings. Values borrowing from the input count as if owned.
+    pub allocated: usize,
+}
+
+/// Receives the [`DecodeStats`] of each message read, e.g. to feed an autoscaler with how much
+/// work the messages were rather than how many there were.
+///
+/// Opt in with [`Fury::stats_collector`](crate::fury::Fury::stats_collector). Called on the
+/// deserializing thread once a message has been read successfully.
+pub trait StatsCollector: Send + Sync {
+    /// `type_name` is the Rust type the message was read as.
+    fn on_decoded(&self, type_name: &'static str, stats: &DecodeStats);
+}
+
+impl<F: Fn(&'static str, &DecodeStats) + Send + Sync> StatsCollector for F {
+    fn on_decoded(&self, type_name: &'static str, stats: &DecodeStats) {
+        self(type_name, stats)
+    }
+}
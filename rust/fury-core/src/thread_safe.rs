@@ -0,0 +1,101 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! A [`Fury`] shared by the threads of a server, see [`ThreadSafeFury`].
+
+use crate::buffer::Writer;
+use crate::error::Error;
+use crate::fury::Fury;
+use crate::serializer::Serializer;
+use std::sync::Mutex;
+
+/// Write buffers grown past this are dropped instead of going back to the pool, so that one
+/// huge message doesn't pin its memory for good.
+pub const MAX_POOLED_BUFFER: usize = 1 << 20;
+
+/// A [`Fury`] for concurrent use, which keeps the write buffers of finished messages for the
+/// next ones instead of growing a new buffer for every message.
+///
+/// `Fury` itself is `Sync` and its registrations are read-only once built, so this only pools
+/// buffers: one per thread serializing at the same time. Reading needs no pooling, everything a
+/// message needs is dropped with it.
+///
+/// ```
+/// use fury_core::fury::Fury;
+/// use fury_core::thread_safe::ThreadSafeFury;
+/// use std::sync::Arc;
+///
+/// let fury = Arc::new(ThreadSafeFury::new(Fury::default()));
+/// let handles: Vec<_> = (0..4)
+///     .map(|i| {
+///         let fury = fury.clone();
+///         std::thread::spawn(move || {
+///             let bin = fury.serialize(&format!("worker {i}"));
+///             fury.deserialize::<String>(&bin).unwrap()
+///         })
+///     })
+///     .collect();
+/// for (i, handle) in handles.into_iter().enumerate() {
+///     assert_eq!(handle.join().unwrap(), format!("worker {i}"));
+/// }
+/// ```
+pub struct ThreadSafeFury {
+    fury: Fury,
+    writers: Mutex<Vec<Writer>>,
+}
+
+impl ThreadSafeFury {
+    /// Shares `fury`, which must have all its types registered already.
+    pub fn new(fury: Fury) -> ThreadSafeFury {
+        ThreadSafeFury {
+            fury,
+            writers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The shared instance, for the entry points other than `serialize` and `deserialize`.
+    pub fn get_fury(&self) -> &Fury {
+        &self.fury
+    }
+
+    /// [`Fury::serialize`] into a pooled buffer, the message is copied out of it.
+    pub fn serialize<T: Serializer>(&self, record: &T) -> Vec<u8> {
+        let mut writer = self.writers.lock().unwrap().pop().unwrap_or_default();
+        self.fury.write_message(&mut writer, record);
+        let bin = writer.dump();
+        writer.clear();
+        if writer.capacity() <= MAX_POOLED_BUFFER {
+            self.writers.lock().unwrap().push(writer);
+        }
+        bin
+    }
+
+    pub fn deserialize<T: Serializer>(&self, bf: &[u8]) -> Result<T, Error> {
+        self.fury.deserialize(bf)
+    }
+
+    /// Drops the idle buffers of the pool and trims the caches of the shared instance, see
+    /// [`Fury::trim_caches`].
+    pub fn trim_caches(&self) {
+        self.writers.lock().unwrap().clear();
+        self.fury.trim_caches();
+    }
+
+    /// Number of idle buffers in the pool.
+    pub fn pooled_buffers(&self) -> usize {
+        self.writers.lock().unwrap().len()
+    }
+}
@@ -0,0 +1,208 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Definitions of registered types as plain data, e.g. to push them into a schema registry, see
+//! [`Fury::type_def`].
+//!
+//! [`TypeDef::to_bytes`] writes the type meta peers exchange in compatible mode, followed by
+//! what it leaves out: the version and the generics of the fields. [`TypeDef::from_bytes`] reads
+//! both, and also a bare type meta as received from a peer.
+
+use crate::buffer::{Reader, Writer};
+use crate::ensure;
+use crate::error::Error;
+use crate::fury::Fury;
+use crate::meta::{FieldInfo, TypeMeta};
+use crate::serializer::Serializer;
+use crate::types::compute_schema_fingerprint;
+use anyhow::anyhow;
+
+// how deeply the generics of a field may nest in a type def read by `TypeDef::from_bytes`, far
+// beyond what any field declares
+const MAX_GENERICS_DEPTH: usize = 64;
+
+/// Type of a field along with the types of its elements, keys and values, e.g. a
+/// `HashMap<String, Vec<Point>>` is a `MAP` of `STRING` and `ARRAY`, the latter of the id `Point`
+/// is registered with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldTypeDef {
+    pub type_id: i16,
+    pub generics: Vec<FieldTypeDef>,
+}
+
+impl FieldTypeDef {
+    pub fn new(type_id: i16) -> FieldTypeDef {
+        FieldTypeDef {
+            type_id,
+            generics: Vec::new(),
+        }
+    }
+
+    /// Type of a field declared as `T`.
+    pub fn of<T: Serializer>(fury: &Fury) -> FieldTypeDef {
+        FieldTypeDef {
+            type_id: T::get_type_id(fury),
+            generics: T::generic_types(fury),
+        }
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.i16(self.type_id);
+        writer.var_uint32(self.generics.len() as u32);
+        for generic in &self.generics {
+            generic.write(writer);
+        }
+    }
+
+    fn read(reader: &mut Reader, depth: usize) -> Result<FieldTypeDef, Error> {
+        ensure!(
+            depth <= MAX_GENERICS_DEPTH,
+            "Generics of a type def nest deeper than {MAX_GENERICS_DEPTH} levels"
+        );
+        let type_id = reader.i16()?;
+        let count = reader.var_uint32()? as usize;
+        // each generic takes at least three bytes
+        reader.check_remaining(count.saturating_mul(3))?;
+        let generics = (0..count)
+            .map(|_| FieldTypeDef::read(reader, depth + 1))
+            .collect::<Result<_, Error>>()?;
+        Ok(FieldTypeDef { type_id, generics })
+    }
+}
+
+/// Types of the fields of the type meta `type_def`, without generics.
+pub fn field_types(type_def: &[u8]) -> Vec<FieldTypeDef> {
+    TypeMeta::from_bytes(&mut Reader::new(type_def))
+        .expect("type defs of serializers are well-formed")
+        .get_field_info()
+        .iter()
+        .map(|field| FieldTypeDef::new(field.get_field_id()))
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldDef {
+    /// Name of the field, `#` followed by the tag id for a field identified by one.
+    pub name: String,
+    pub tag_id: Option<u32>,
+    pub field_type: FieldTypeDef,
+}
+
+/// Definition of a registered type: its id, name and fields. Enums list their variants as
+/// fields, with the tag as the type id.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeDef {
+    pub type_id: u32,
+    /// (namespace, name) of a type registered with [`Fury::register_by_name`].
+    pub name: Option<(String, String)>,
+    pub fields: Vec<FieldDef>,
+    /// Newest version of a type registered with [`Fury::register_versioned`].
+    pub version: Option<u32>,
+    /// Hash of the type meta, like [`Fury::schema_fingerprint`] of an instance registering
+    /// only this type.
+    pub fingerprint: u32,
+}
+
+impl TypeDef {
+    pub(crate) fn from_type_meta(type_meta: &[u8]) -> Result<TypeDef, Error> {
+        let meta = TypeMeta::from_bytes(&mut Reader::new(type_meta))?;
+        let fields = meta
+            .get_field_info()
+            .iter()
+            .map(|field| FieldDef {
+                name: field.get_field_name().to_string(),
+                tag_id: field.get_tag_id(),
+                field_type: FieldTypeDef::new(field.get_field_id()),
+            })
+            .collect();
+        Ok(TypeDef {
+            type_id: meta.get_type_id(),
+            name: meta
+                .get_type_name()
+                .map(|(namespace, name)| (namespace.to_string(), name.to_string())),
+            fields,
+            version: None,
+            fingerprint: compute_schema_fingerprint(std::iter::once((
+                meta.get_type_id(),
+                type_meta,
+            ))),
+        })
+    }
+
+    /// The type meta, i.e. the field names and type ids, as [`TypeMeta::to_bytes`] writes it.
+    pub fn type_meta(&self) -> Result<Vec<u8>, Error> {
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| match field.tag_id {
+                Some(tag_id) => FieldInfo::with_tag_id(tag_id, field.field_type.type_id),
+                None => FieldInfo::new(&field.name, field.field_type.type_id),
+            })
+            .collect();
+        let mut meta = TypeMeta::from_fields(self.type_id, fields);
+        if let Some((namespace, name)) = &self.name {
+            meta.set_type_name(namespace, name);
+        }
+        meta.to_bytes()
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut writer = Writer::default();
+        writer.bytes(&self.type_meta()?);
+        // zero for none
+        writer.var_uint64(self.version.map_or(0, |version| version as u64 + 1));
+        for field in &self.fields {
+            field.field_type.write(&mut writer);
+        }
+        Ok(writer.dump())
+    }
+
+    /// Reads what [`TypeDef::to_bytes`] wrote, or a bare type meta, whose fields have no
+    /// generics then. Fails on truncated or corrupt bytes, e.g. from a registry gone wrong.
+    pub fn from_bytes(bytes: &[u8]) -> Result<TypeDef, Error> {
+        let mut reader = Reader::new(bytes);
+        TypeMeta::from_bytes(&mut reader)?;
+        let meta_len = reader.get_cursor();
+        let mut type_def = TypeDef::from_type_meta(&bytes[..meta_len])?;
+        if meta_len == bytes.len() {
+            return Ok(type_def);
+        }
+        type_def.version = match reader.var_uint64()? {
+            0 => None,
+            version => Some(
+                u32::try_from(version - 1)
+                    .map_err(|_| anyhow!("Invalid type def version {}", version - 1))?,
+            ),
+        };
+        for field in type_def.fields.iter_mut() {
+            let field_type = FieldTypeDef::read(&mut reader, 0)?;
+            ensure!(
+                field_type.type_id == field.field_type.type_id,
+                "Field `{}` has the type id {} in the type meta but {} in its generics",
+                field.name,
+                field.field_type.type_id,
+                field_type.type_id
+            );
+            field.field_type = field_type;
+        }
+        ensure!(
+            reader.get_cursor() == bytes.len(),
+            "{} bytes left after the type def",
+            bytes.len() - reader.get_cursor()
+        );
+        Ok(type_def)
+    }
+}
@@ -15,15 +15,22 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::buffer::Reader;
 use crate::error::Error;
+use crate::fury::Fury;
+use crate::meta::TypeMeta;
+use crate::serializer::StructSerializer;
 use anyhow::anyhow;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use std::mem;
 
-#[allow(dead_code)]
+/// Encoding of a string, the low two bits of its header.
+#[derive(Clone, Copy, Debug, PartialEq, TryFromPrimitive)]
+#[repr(u8)]
 pub enum StringFlag {
     LATIN1 = 0,
-    UTF8 = 1,
+    UTF16 = 1,
+    UTF8 = 2,
 }
 
 #[derive(TryFromPrimitive)]
@@ -68,38 +75,121 @@ pub enum FieldType {
     FuryPrimitiveFloatArray = 262,
     FuryPrimitiveDoubleArray = 263,
     FuryStringArray = 264,
+    // one bit per element, experimental, see `Fury::packed_bools`
+    FuryPackedBoolArray = 265,
+    // deltas between consecutive integers or timestamps, see `serializer::delta`
+    FuryDeltaArray = 266,
+    // maps read like `MAP`, the ids only tell which implementation the peer used, see
+    // `serializer::map::MapKind`
+    FuryLinkedHashMap = 267,
+    FuryTreeMap = 268,
 }
 
 pub trait FuryGeneralList {}
 
+/// Variant names of a derived enum, in tag order: a variant is written as its index here.
+pub trait EnumLayout {
+    const VARIANTS: &'static [&'static str];
+}
+
+/// Whether `actual` lists exactly the variant names of `expected`, in the same order.
+pub const fn enum_layout_matches(actual: &[&str], expected: &[&str]) -> bool {
+    if actual.len() != expected.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < actual.len() {
+        let (a, b) = (actual[i].as_bytes(), expected[i].as_bytes());
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut j = 0;
+        while j < a.len() {
+            if a[j] != b[j] {
+                return false;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Fails to compile when the variants of a derived enum are renamed, added, removed or
+/// reordered, any of which changes the tags of existing data.
+///
+/// ```ignore
+/// #[derive(Fury)]
+/// enum Color {
+///     Green,
+///     Red,
+/// }
+/// fury_core::fury_assert_enum_layout!(Color, ["Green", "Red"]);
+/// ```
+#[macro_export]
+macro_rules! fury_assert_enum_layout {
+    ($ty:ty, [$($variant:literal),* $(,)?]) => {
+        const _: () = assert!(
+            $crate::types::enum_layout_matches(
+                <$ty as $crate::types::EnumLayout>::VARIANTS,
+                &[$($variant),*]
+            ),
+            "enum variants don't match the asserted layout"
+        );
+    };
+}
+
+/// Fields of a derived struct as `fury` writes them, in that order: `name:type id` separated by
+/// commas, a field identified by a tag id named `#` and the id. Peers, e.g. Java, describe their
+/// side the same way, see [`fury_assert_compatible`].
+pub fn struct_layout<T: StructSerializer>(fury: &Fury) -> String {
+    let meta = TypeMeta::from_bytes(&mut Reader::new(&T::type_def(fury)))
+        .expect("type defs of serializers are well-formed");
+    meta.get_field_info()
+        .iter()
+        .map(|field| format!("{}:{}", field.get_field_name(), field.get_field_id()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Panics when the layout of a derived struct differs from the one checked in, see
+/// [`struct_layout`], e.g. after someone renamed a field or changed its type. Meant for a unit
+/// test, with the layout generated on the side the struct has to stay compatible with.
+///
+/// ```ignore
+/// #[derive(Fury)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// #[test]
+/// fn point_matches_java() {
+///     let mut fury = Fury::default();
+///     fury.register::<Point>(101);
+///     fury_core::fury_assert_compatible!(fury, Point, "x:7,y:7");
+/// }
+/// ```
+#[macro_export]
+macro_rules! fury_assert_compatible {
+    ($fury:expr, $ty:ty, $expected:expr $(,)?) => {
+        let actual = $crate::types::struct_layout::<$ty>(&$fury);
+        assert!(
+            actual == $expected,
+            "layout of `{}` changed incompatibly\n  expected: {}\n    actual: {}",
+            stringify!($ty),
+            $expected,
+            actual
+        );
+    };
+}
+
 const MAX_UNT32: u64 = (1 << 31) - 1;
 
-// todo: struct hash
-#[allow(dead_code)]
+/// Hash of `s` like Java's, see [`string_hash`](crate::meta::string_hash).
 pub fn compute_string_hash(s: &str) -> u32 {
-    let mut hash: u64 = 17;
-    s.as_bytes().iter().for_each(|b| {
-        hash = (hash * 31) + (*b as u64);
-        while hash >= MAX_UNT32 {
-            hash /= 7;
-        }
-    });
-    hash as u32
-}
-
-const BASIC_TYPES: [FieldType; 11] = [
-    FieldType::BOOL,
-    FieldType::INT8,
-    FieldType::INT16,
-    FieldType::INT32,
-    FieldType::INT64,
-    FieldType::FLOAT,
-    FieldType::DOUBLE,
-    FieldType::STRING,
-    FieldType::BINARY,
-    FieldType::DATE,
-    FieldType::TIMESTAMP,
-];
+    crate::meta::string_hash(s)
+}
 
 pub fn compute_field_hash(hash: u32, id: i16) -> u32 {
     let mut new_hash: u64 = (hash as u64) * 31 + (id as u64);
@@ -109,27 +199,47 @@ pub fn compute_field_hash(hash: u32, id: i16) -> u32 {
     new_hash as u32
 }
 
+/// Hash of a struct with the fields `props` like Java's, see
+/// [`struct_hash`](crate::meta::struct_hash).
 pub fn compute_struct_hash(props: Vec<(&str, FieldType)>) -> u32 {
+    let type_ids: Vec<i16> = props.iter().map(|(_, ty)| *ty as i16).collect();
+    crate::meta::struct_hash(&type_ids)
+}
+
+/// Hash of serialized type definitions, see [`Fury::schema_fingerprint`](crate::fury::Fury::schema_fingerprint).
+pub fn compute_schema_fingerprint<'a>(type_defs: impl Iterator<Item = (u32, &'a [u8])>) -> u32 {
     let mut hash = 17;
-    props.iter().for_each(|prop| {
-        let (_name, ty) = prop;
-        hash = match ty {
-            FieldType::ARRAY | FieldType::MAP => compute_field_hash(hash, *ty as i16),
-            _ => hash,
-        };
-        let is_basic_type = BASIC_TYPES.iter().any(|x| *x == *ty);
-        if is_basic_type {
-            hash = compute_field_hash(hash, *ty as i16);
+    for (type_id, type_def) in type_defs {
+        hash = compute_field_hash(hash, type_id as i16);
+        for b in type_def {
+            hash = compute_field_hash(hash, *b as i16);
         }
-    });
+    }
     hash
 }
 
+/// First two bytes of every message, telling Fury payloads apart from other data.
+pub const MAGIC_NUMBER: u16 = 0x62d4;
+
+/// Bits of the byte following the magic number. Messages without `IS_LITTLE_ENDIAN_FLAG` or
+/// `IS_CROSS_LANGUAGE_FLAG` are rejected.
 pub mod config_flags {
+    /// Set if the root is null, nothing follows the bitmap then.
     pub const IS_NULL_FLAG: u8 = 1 << 0;
+    /// Set on every message, which is little-endian whatever the target. Big-endian peers are
+    /// rejected, as Java and Python do, there is no setting to read or write their byte order.
     pub const IS_LITTLE_ENDIAN_FLAG: u8 = 2;
     pub const IS_CROSS_LANGUAGE_FLAG: u8 = 4;
+    /// Set if the message was written with out-of-band buffers, see
+    /// `Fury::serialize_with_buffers`.
     pub const IS_OUT_OF_BAND_FLAG: u8 = 8;
+    /// Set if the message uses an encoding of the `unstable-format` feature.
+    pub const IS_EXPERIMENTAL_FLAG: u8 = 16;
+    /// Set if the message refers to type metas sent with earlier messages, see `MetaContext`.
+    pub const IS_META_SHARED_FLAG: u8 = 32;
+    /// Set if lists and sets were written without the ref flag and type id of their elements,
+    /// see `Fury::compact_elements`.
+    pub const IS_COMPACT_ELEMENTS_FLAG: u8 = 64;
 }
 
 #[derive(Debug, PartialEq)]
@@ -143,7 +253,7 @@ pub enum Language {
     Rust = 6,
 }
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum Mode {
     // Type declaration must be consistent between serialization peer and deserialization peer.
     SchemaConsistent,
@@ -152,6 +262,53 @@ pub enum Mode {
     Compatible,
 }
 
+/// How values which may borrow from the input, e.g. `Cow<'a, str>`, are decoded.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DecodeMode {
+    // Copy everything out of the buffer, the value may outlive it.
+    #[default]
+    Owned,
+    // Borrow from the buffer whenever the bytes are contiguous in it.
+    Borrowed,
+}
+
+/// What to do with an integer which doesn't fit in the field it's read into, e.g. an `i64`
+/// sent by a Python peer for an `i16` field.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NarrowingPolicy {
+    // Fail with `Error::NumericOverflow`.
+    #[default]
+    Error,
+    // Clamp to the nearest bound of the field type.
+    Saturate,
+    // Keep the low bits, like an `as` cast.
+    Wrap,
+}
+
+/// What a `Box<dyn Any>` does with a value of a type that isn't registered on the reader, e.g.
+/// an event type only newer producers know.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum UnknownTypePolicy {
+    // Fail the read.
+    #[default]
+    Error,
+    // Skip the value, an `Option<Box<dyn Any>>` reads it as `None`. A bare `Box<dyn Any>` fails.
+    SkipAsNone,
+    // Read the value as an `OpaqueValue` held by the `Box<dyn Any>`.
+    CaptureOpaque,
+}
+
+/// What a panic of a serializer does while a message is read, e.g. one of a hand-written
+/// [`Serializer`](crate::serializer::Serializer) with a bug.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PanicPolicy {
+    // Unwind through the caller, like any other panic.
+    #[default]
+    Propagate,
+    // Fail the read with `Error::SerializerPanic`, for services that must survive bad messages.
+    Catch,
+}
+
 impl TryFrom<u8> for Language {
     type Error = Error;
 
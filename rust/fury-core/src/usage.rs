@@ -0,0 +1,95 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Registered types that messages actually hold, see [`UsageTracker`].
+
+use crate::fury::Fury;
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+/// Collects the ids of the registered types written and read by a [`Fury`], to find the
+/// registrations no message uses any more before pruning them.
+///
+/// Opt in with [`Fury::usage_tracker`](crate::fury::Fury::usage_tracker). Ids are gathered per
+/// message and merged under a lock once it is done.
+#[derive(Default)]
+pub struct UsageTracker {
+    serialized: Mutex<BTreeSet<u32>>,
+    deserialized: Mutex<BTreeSet<u32>>,
+}
+
+impl UsageTracker {
+    pub fn new() -> UsageTracker {
+        UsageTracker::default()
+    }
+
+    pub fn record_serialized(&self, ids: impl IntoIterator<Item = u32>) {
+        let mut serialized = self
+            .serialized
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        serialized.extend(ids);
+    }
+
+    pub fn record_deserialized(&self, ids: impl IntoIterator<Item = u32>) {
+        let mut deserialized = self
+            .deserialized
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        deserialized.extend(ids);
+    }
+
+    /// Ids of the registered types written so far.
+    pub fn serialized(&self) -> BTreeSet<u32> {
+        self.serialized
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Ids of the registered types read so far.
+    pub fn deserialized(&self) -> BTreeSet<u32> {
+        self.deserialized
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Types registered with `fury` that were neither written nor read so far, as (fury type
+    /// id, type name) ordered by id. Types only read as a [`Value`](crate::value::Value) count as
+    /// unused.
+    pub fn unused<'a>(&self, fury: &'a Fury) -> Vec<(u32, &'a str)> {
+        let serialized = self.serialized();
+        let deserialized = self.deserialized();
+        fury.get_class_resolver()
+            .registrations()
+            .into_iter()
+            .filter(|(id, _)| !serialized.contains(id) && !deserialized.contains(id))
+            .collect()
+    }
+
+    pub fn reset(&self) {
+        self.serialized
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+        self.deserialized
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+    }
+}
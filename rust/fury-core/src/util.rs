@@ -15,9 +15,24 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use chrono::NaiveDate;
+use crate::error::Error;
+#[cfg(target_arch = "x86_64")]
+use crate::meta::MIN_DIM_SIZE_AVX;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+use crate::meta::MIN_DIM_SIZE_SIMD;
+use anyhow::anyhow;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 use std::ptr;
 
+#[cfg(target_arch = "aarch64")]
+use std::arch::aarch64::*;
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
 pub const EPOCH: NaiveDate = match NaiveDate::from_ymd_opt(1970, 1, 1) {
     None => {
         panic!("Unreachable code")
@@ -25,93 +40,119 @@ pub const EPOCH: NaiveDate = match NaiveDate::from_ymd_opt(1970, 1, 1) {
     Some(epoch) => epoch,
 };
 
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
+/// Nanoseconds since the epoch of `timestamp`, the timestamp encoding. Only times from
+/// 1677-09-21 to 2262-04-11 fit in an `i64` count.
+pub fn timestamp_to_nanos(timestamp: &NaiveDateTime) -> Result<i64, Error> {
+    timestamp.and_utc().timestamp_nanos_opt().ok_or_else(|| {
+        anyhow!("Timestamp {timestamp} is out of the range of nanosecond timestamps, 1677-09-21 to 2262-04-11")
+            .into()
+    })
+}
+
+/// The timestamp `nanos` nanoseconds after the epoch, every `i64` count is one.
+pub fn timestamp_from_nanos(nanos: i64) -> NaiveDateTime {
+    let seconds = nanos.div_euclid(NANOS_PER_SECOND);
+    let subsec_nanos = nanos.rem_euclid(NANOS_PER_SECOND) as u32;
+    DateTime::from_timestamp(seconds, subsec_nanos)
+        .expect("i64 nanoseconds are within the range of chrono")
+        .naive_utc()
+}
+
 // Swapping the high 8 bits and the low 8 bits of a 16-bit value
 fn swap_endian(value: u16) -> u16 {
     value.rotate_right(8)
 }
 
+/// UTF-8 bytes of the UTF-16 code units `utf16`, which are byte-swapped when
+/// `is_little_endian`.
+///
+/// ASCII runs are narrowed with SSE2, AVX2 or NEON where this CPU has them, with the same output
+/// and errors as the scalar path, see `to_utf8_paths`.
 pub fn to_utf8(utf16: &[u16], is_little_endian: bool) -> Result<Vec<u8>, String> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") && utf16.len() >= MIN_DIM_SIZE_AVX {
+            return unsafe { to_utf8_avx(utf16, is_little_endian) };
+        }
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if is_x86_feature_detected!("sse2") && utf16.len() >= MIN_DIM_SIZE_SIMD {
+            return unsafe { to_utf8_sse(utf16, is_little_endian) };
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") && utf16.len() >= MIN_DIM_SIZE_SIMD {
+            return unsafe { to_utf8_neon(utf16, is_little_endian) };
+        }
+    }
+    to_utf8_standard(utf16, is_little_endian)
+}
+
+/// Output of every `to_utf8` path this CPU can run on `utf16` by name, the scalar one first,
+/// like [`to_utf16_paths`].
+#[cfg(any(test, feature = "testing"))]
+pub fn to_utf8_paths(
+    utf16: &[u16],
+    is_little_endian: bool,
+) -> Vec<(&'static str, Result<Vec<u8>, String>)> {
+    let mut paths = vec![("scalar", to_utf8_standard(utf16, is_little_endian))];
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            paths.push(("avx2", unsafe { to_utf8_avx(utf16, is_little_endian) }));
+        }
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if is_x86_feature_detected!("sse2") {
+            paths.push(("sse2", unsafe { to_utf8_sse(utf16, is_little_endian) }));
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            paths.push(("neon", unsafe { to_utf8_neon(utf16, is_little_endian) }));
+        }
+    }
+    paths
+}
+
+fn to_utf8_standard(utf16: &[u16], is_little_endian: bool) -> Result<Vec<u8>, String> {
     // Pre-allocating capacity to avoid dynamic resizing
     // Longest case: 1 u16 to 3 u8
-    let mut utf8_bytes: Vec<u8> = Vec::with_capacity(utf16.len() * 3);
+    to_utf8_from(
+        utf16,
+        0,
+        Vec::with_capacity(utf16.len() * 3),
+        is_little_endian,
+    )
+}
+
+// the scalar path from `index` on, appending to the bytes of the units before, which must have
+// left room for 3 bytes per unit from `index` on
+fn to_utf8_from(
+    utf16: &[u16],
+    mut index: usize,
+    mut utf8_bytes: Vec<u8>,
+    is_little_endian: bool,
+) -> Result<Vec<u8>, String> {
     // For unsafe write to Vec
     let ptr = utf8_bytes.as_mut_ptr();
-    let mut offset = 0;
-    let mut iter = utf16.iter();
-    while let Some(&wc) = iter.next() {
-        // Using big endian in this conversion
-        let wc = if is_little_endian {
-            swap_endian(wc)
-        } else {
-            wc
-        };
-        match wc {
-            code_point if code_point < 0x80 => {
-                // 1-byte UTF-8
-                // [0000|0000|0ccc|cccc] => [0ccc|cccc]
-                unsafe {
-                    ptr.add(offset).write(code_point as u8);
-                }
-                offset += 1;
-            }
-            code_point if code_point < 0x800 => {
-                // 2-byte UTF-8
-                // [0000|0bbb|bbcc|cccc] => [110|bbbbb], [10|cccccc]
-                let bytes = [
-                    (code_point >> 6 & 0b1_1111) as u8 | 0b1100_0000,
-                    (code_point & 0b11_1111) as u8 | 0b1000_0000,
-                ];
-                unsafe {
-                    ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.add(offset), 2);
-                }
-                offset += 2;
-            }
-            wc1 if (0xd800..=0xdbff).contains(&wc1) => {
-                // Surrogate pair (4-byte UTF-8)
-                // Need extra u16, 2 u16 -> 4 u8
-                if let Some(&wc2) = iter.next() {
-                    let wc2 = if is_little_endian {
-                        swap_endian(wc2)
-                    } else {
-                        wc2
-                    };
-                    if !(0xdc00..=0xdfff).contains(&wc2) {
-                        return Err("Invalid UTF-16 string: wrong surrogate pair".to_string());
-                    }
-                    // utf16 to unicode
-                    let code_point =
-                        ((((wc1 as u32) - 0xd800) << 10) | ((wc2 as u32) - 0xdc00)) + 0x10000;
-                    // 11110??? 10?????? 10?????? 10??????
-                    // Need 21 bit suffix of code_point
-                    let bytes = [
-                        (code_point >> 18 & 0b111) as u8 | 0b1111_0000,
-                        (code_point >> 12 & 0b11_1111) as u8 | 0b1000_0000,
-                        (code_point >> 6 & 0b11_1111) as u8 | 0b1000_0000,
-                        (code_point & 0b11_1111) as u8 | 0b1000_0000,
-                    ];
-                    unsafe {
-                        ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.add(offset), 4);
-                    }
-                    offset += 4;
-                } else {
-                    return Err("Invalid UTF-16 string: missing surrogate pair".to_string());
-                }
-            }
-            _ => {
-                // 3-byte UTF-8, 1 u16 -> 3 u8
-                // [aaaa|bbbb|bbcc|cccc] => [1110|aaaa], [10|bbbbbb], [10|cccccc]
-                // Need 16 bit suffix of wc, as same as wc itself
-                let bytes = [
-                    (wc >> 12 | 0b1110_0000) as u8,
-                    (wc >> 6 & 0b11_1111) as u8 | 0b1000_0000,
-                    (wc & 0b11_1111) as u8 | 0b1000_0000,
-                ];
-                unsafe {
-                    ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.add(offset), 3);
-                }
-                offset += 3;
-            }
-        }
+    let mut offset = utf8_bytes.len();
+    while index < utf16.len() {
+        let (units, bytes) =
+            unsafe { write_utf8_sequence(utf16, index, ptr.add(offset), is_little_endian)? };
+        index += units;
+        offset += bytes;
     }
     unsafe {
         // As ptr.write don't change the length
@@ -119,3 +160,465 @@ pub fn to_utf8(utf16: &[u16], is_little_endian: bool) -> Result<Vec<u8>, String>
     }
     Ok(utf8_bytes)
 }
+
+// Using big endian in this conversion
+fn big_endian(wc: u16, is_little_endian: bool) -> u16 {
+    if is_little_endian {
+        swap_endian(wc)
+    } else {
+        wc
+    }
+}
+
+// Writes the UTF-8 bytes of the code point at `utf16[index]` to `ptr`, which must have room
+// for 4 bytes, returns the count of units read and of bytes written.
+#[inline]
+unsafe fn write_utf8_sequence(
+    utf16: &[u16],
+    index: usize,
+    ptr: *mut u8,
+    is_little_endian: bool,
+) -> Result<(usize, usize), String> {
+    match big_endian(utf16[index], is_little_endian) {
+        code_point if code_point < 0x80 => {
+            // 1-byte UTF-8
+            // [0000|0000|0ccc|cccc] => [0ccc|cccc]
+            ptr.write(code_point as u8);
+            Ok((1, 1))
+        }
+        code_point if code_point < 0x800 => {
+            // 2-byte UTF-8
+            // [0000|0bbb|bbcc|cccc] => [110|bbbbb], [10|cccccc]
+            let bytes = [
+                (code_point >> 6 & 0b1_1111) as u8 | 0b1100_0000,
+                (code_point & 0b11_1111) as u8 | 0b1000_0000,
+            ];
+            ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, 2);
+            Ok((1, 2))
+        }
+        wc1 if (0xd800..=0xdbff).contains(&wc1) => {
+            // Surrogate pair (4-byte UTF-8)
+            // Need extra u16, 2 u16 -> 4 u8
+            let Some(&wc2) = utf16.get(index + 1) else {
+                return Err("Invalid UTF-16 string: missing surrogate pair".to_string());
+            };
+            let wc2 = big_endian(wc2, is_little_endian);
+            if !(0xdc00..=0xdfff).contains(&wc2) {
+                return Err("Invalid UTF-16 string: wrong surrogate pair".to_string());
+            }
+            // utf16 to unicode
+            let code_point = ((((wc1 as u32) - 0xd800) << 10) | ((wc2 as u32) - 0xdc00)) + 0x10000;
+            // 11110??? 10?????? 10?????? 10??????
+            // Need 21 bit suffix of code_point
+            let bytes = [
+                (code_point >> 18 & 0b111) as u8 | 0b1111_0000,
+                (code_point >> 12 & 0b11_1111) as u8 | 0b1000_0000,
+                (code_point >> 6 & 0b11_1111) as u8 | 0b1000_0000,
+                (code_point & 0b11_1111) as u8 | 0b1000_0000,
+            ];
+            ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, 4);
+            Ok((2, 4))
+        }
+        wc => {
+            // 3-byte UTF-8, 1 u16 -> 3 u8
+            // [aaaa|bbbb|bbcc|cccc] => [1110|aaaa], [10|bbbbbb], [10|cccccc]
+            // Need 16 bit suffix of wc, as same as wc itself
+            let bytes = [
+                (wc >> 12 | 0b1110_0000) as u8,
+                (wc >> 6 & 0b11_1111) as u8 | 0b1000_0000,
+                (wc & 0b11_1111) as u8 | 0b1000_0000,
+            ];
+            ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, 3);
+            Ok((1, 3))
+        }
+    }
+}
+
+// The SIMD paths narrow whole chunks of ASCII units to bytes. In a chunk with other units, the
+// scalar encoder takes the units up to and including the first of them. Bytes are written
+// straight into `utf8_bytes`, which has room for 3 bytes per unit.
+
+// the bits a unit of the input has set when it isn't ASCII
+fn non_ascii_mask(is_little_endian: bool) -> u16 {
+    if is_little_endian {
+        0x80ff
+    } else {
+        0xff80
+    }
+}
+
+// the scalar path for `utf16[index..chunk_end]` up to and including its first non-ASCII unit,
+// returns the index and offset after it
+unsafe fn write_utf8_non_ascii(
+    utf16: &[u16],
+    mut index: usize,
+    chunk_end: usize,
+    ptr: *mut u8,
+    mut offset: usize,
+    is_little_endian: bool,
+) -> Result<(usize, usize), String> {
+    let mask = non_ascii_mask(is_little_endian);
+    let end = index
+        + utf16[index..chunk_end]
+            .iter()
+            .position(|&wc| wc & mask != 0)
+            .unwrap();
+    while index <= end {
+        let (units, bytes) = write_utf8_sequence(utf16, index, ptr.add(offset), is_little_endian)?;
+        index += units;
+        offset += bytes;
+    }
+    Ok((index, offset))
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn to_utf8_avx(utf16: &[u16], is_little_endian: bool) -> Result<Vec<u8>, String> {
+    let mut utf8_bytes: Vec<u8> = Vec::with_capacity(utf16.len() * 3);
+    let ptr = utf8_bytes.as_mut_ptr();
+    let mask = _mm256_set1_epi16(non_ascii_mask(is_little_endian) as i16);
+    let (mut index, mut offset) = (0, 0);
+    while index + MIN_DIM_SIZE_AVX <= utf16.len() {
+        let input = utf16.as_ptr().add(index) as *const __m256i;
+        let mut low = _mm256_loadu_si256(input);
+        let mut high = _mm256_loadu_si256(input.add(1));
+        if _mm256_testz_si256(_mm256_or_si256(low, high), mask) == 0 {
+            (index, offset) = write_utf8_non_ascii(
+                utf16,
+                index,
+                index + MIN_DIM_SIZE_AVX,
+                ptr,
+                offset,
+                is_little_endian,
+            )?;
+            continue;
+        }
+        if is_little_endian {
+            low = _mm256_srli_epi16(low, 8);
+            high = _mm256_srli_epi16(high, 8);
+        }
+        // packing works within 128 bit lanes, the permutation puts the lanes back in order
+        let bytes = _mm256_permute4x64_epi64(_mm256_packus_epi16(low, high), 0b11_01_10_00);
+        _mm256_storeu_si256(ptr.add(offset) as *mut __m256i, bytes);
+        index += MIN_DIM_SIZE_AVX;
+        offset += MIN_DIM_SIZE_AVX;
+    }
+    utf8_bytes.set_len(offset);
+    to_utf8_from(utf16, index, utf8_bytes, is_little_endian)
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "sse2")]
+unsafe fn to_utf8_sse(utf16: &[u16], is_little_endian: bool) -> Result<Vec<u8>, String> {
+    let mut utf8_bytes: Vec<u8> = Vec::with_capacity(utf16.len() * 3);
+    let ptr = utf8_bytes.as_mut_ptr();
+    let mask = _mm_set1_epi16(non_ascii_mask(is_little_endian) as i16);
+    let zero = _mm_setzero_si128();
+    let (mut index, mut offset) = (0, 0);
+    while index + MIN_DIM_SIZE_SIMD <= utf16.len() {
+        let input = utf16.as_ptr().add(index) as *const __m128i;
+        let mut low = _mm_loadu_si128(input);
+        let mut high = _mm_loadu_si128(input.add(1));
+        let non_ascii = _mm_and_si128(_mm_or_si128(low, high), mask);
+        if _mm_movemask_epi8(_mm_cmpeq_epi16(non_ascii, zero)) != 0xffff {
+            (index, offset) = write_utf8_non_ascii(
+                utf16,
+                index,
+                index + MIN_DIM_SIZE_SIMD,
+                ptr,
+                offset,
+                is_little_endian,
+            )?;
+            continue;
+        }
+        if is_little_endian {
+            low = _mm_srli_epi16(low, 8);
+            high = _mm_srli_epi16(high, 8);
+        }
+        _mm_storeu_si128(ptr.add(offset) as *mut __m128i, _mm_packus_epi16(low, high));
+        index += MIN_DIM_SIZE_SIMD;
+        offset += MIN_DIM_SIZE_SIMD;
+    }
+    utf8_bytes.set_len(offset);
+    to_utf8_from(utf16, index, utf8_bytes, is_little_endian)
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
+unsafe fn to_utf8_neon(utf16: &[u16], is_little_endian: bool) -> Result<Vec<u8>, String> {
+    let mut utf8_bytes: Vec<u8> = Vec::with_capacity(utf16.len() * 3);
+    let ptr = utf8_bytes.as_mut_ptr();
+    let mask = vdupq_n_u16(non_ascii_mask(is_little_endian));
+    let (mut index, mut offset) = (0, 0);
+    while index + MIN_DIM_SIZE_SIMD <= utf16.len() {
+        let mut low = vld1q_u16(utf16.as_ptr().add(index));
+        let mut high = vld1q_u16(utf16.as_ptr().add(index + 8));
+        if vmaxvq_u16(vandq_u16(vorrq_u16(low, high), mask)) != 0 {
+            (index, offset) = write_utf8_non_ascii(
+                utf16,
+                index,
+                index + MIN_DIM_SIZE_SIMD,
+                ptr,
+                offset,
+                is_little_endian,
+            )?;
+            continue;
+        }
+        if is_little_endian {
+            low = vshrq_n_u16::<8>(low);
+            high = vshrq_n_u16::<8>(high);
+        }
+        vst1q_u8(
+            ptr.add(offset),
+            vcombine_u8(vmovn_u16(low), vmovn_u16(high)),
+        );
+        index += MIN_DIM_SIZE_SIMD;
+        offset += MIN_DIM_SIZE_SIMD;
+    }
+    utf8_bytes.set_len(offset);
+    to_utf8_from(utf16, index, utf8_bytes, is_little_endian)
+}
+
+/// The reverse of [`to_utf8`]: UTF-16 code units of `utf8`, byte-swapped when
+/// `is_little_endian` like the input of `to_utf8`. Code points above U+FFFF become surrogate
+/// pairs.
+///
+/// ASCII runs are widened with SSE2, AVX2 or NEON where this CPU has them, with the same output
+/// and errors as the scalar path, see `to_utf16_paths`.
+pub fn to_utf16(utf8: &[u8], is_little_endian: bool) -> Result<Vec<u16>, String> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") && utf8.len() >= MIN_DIM_SIZE_AVX {
+            return unsafe { to_utf16_avx(utf8, is_little_endian) };
+        }
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if is_x86_feature_detected!("sse2") && utf8.len() >= MIN_DIM_SIZE_SIMD {
+            return unsafe { to_utf16_sse(utf8, is_little_endian) };
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") && utf8.len() >= MIN_DIM_SIZE_SIMD {
+            return unsafe { to_utf16_neon(utf8, is_little_endian) };
+        }
+    }
+    to_utf16_standard(utf8, is_little_endian)
+}
+
+/// Output of every `to_utf16` path this CPU can run on `utf8` by name, the scalar one first,
+/// for differential tests of the SIMD paths, see [`is_latin_paths`](crate::meta::is_latin_paths).
+#[cfg(any(test, feature = "testing"))]
+pub fn to_utf16_paths(
+    utf8: &[u8],
+    is_little_endian: bool,
+) -> Vec<(&'static str, Result<Vec<u16>, String>)> {
+    let mut paths = vec![("scalar", to_utf16_standard(utf8, is_little_endian))];
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            paths.push(("avx2", unsafe { to_utf16_avx(utf8, is_little_endian) }));
+        }
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if is_x86_feature_detected!("sse2") {
+            paths.push(("sse2", unsafe { to_utf16_sse(utf8, is_little_endian) }));
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            paths.push(("neon", unsafe { to_utf16_neon(utf8, is_little_endian) }));
+        }
+    }
+    paths
+}
+
+fn to_utf16_standard(utf8: &[u8], is_little_endian: bool) -> Result<Vec<u16>, String> {
+    // Longest case: 1 u8 to 1 u16
+    to_utf16_from(utf8, 0, Vec::with_capacity(utf8.len()), is_little_endian)
+}
+
+// the scalar path from `offset` on, appending to the code units of the bytes before
+fn to_utf16_from(
+    utf8: &[u8],
+    mut offset: usize,
+    mut utf16: Vec<u16>,
+    is_little_endian: bool,
+) -> Result<Vec<u16>, String> {
+    while offset < utf8.len() {
+        // ASCII runs are widened 8 bytes at a time
+        if let Some(word) = utf8.get(offset..offset + 8) {
+            if u64::from_ne_bytes(word.try_into().unwrap()) & 0x8080_8080_8080_8080 == 0 {
+                push_ascii(word, &mut utf16, is_little_endian);
+                offset += 8;
+                continue;
+            }
+        }
+        offset += push_utf16_sequence(utf8, offset, &mut utf16, is_little_endian)?;
+    }
+    Ok(utf16)
+}
+
+fn push_ascii(ascii: &[u8], utf16: &mut Vec<u16>, is_little_endian: bool) {
+    let shift = if is_little_endian { 8 } else { 0 };
+    utf16.extend(ascii.iter().map(|&byte| (byte as u16) << shift));
+}
+
+// decodes the UTF-8 sequence at `offset` into `utf16`, returns its length
+fn push_utf16_sequence(
+    utf8: &[u8],
+    offset: usize,
+    utf16: &mut Vec<u16>,
+    is_little_endian: bool,
+) -> Result<usize, String> {
+    let unit = |wc: u16| {
+        if is_little_endian {
+            swap_endian(wc)
+        } else {
+            wc
+        }
+    };
+    let lead = utf8[offset];
+    let (len, bits, min) = match lead {
+        0x00..=0x7f => (1, lead as u32, 0),
+        // [110|bbbbb], [10|cccccc] => [0000|0bbb|bbcc|cccc]
+        0xc0..=0xdf => (2, (lead & 0b1_1111) as u32, 0x80),
+        // [1110|aaaa], [10|bbbbbb], [10|cccccc] => [aaaa|bbbb|bbcc|cccc]
+        0xe0..=0xef => (3, (lead & 0b1111) as u32, 0x800),
+        // 11110??? 10?????? 10?????? 10??????, a 21 bit code point
+        0xf0..=0xf7 => (4, (lead & 0b111) as u32, 0x10000),
+        _ => return Err("Invalid UTF-8 string: unexpected continuation byte".to_string()),
+    };
+    let Some(tail) = utf8.get(offset + 1..offset + len) else {
+        return Err("Invalid UTF-8 string: truncated sequence".to_string());
+    };
+    let mut code_point = bits;
+    for &byte in tail {
+        if byte & 0b1100_0000 != 0b1000_0000 {
+            return Err("Invalid UTF-8 string: missing continuation byte".to_string());
+        }
+        code_point = code_point << 6 | (byte & 0b11_1111) as u32;
+    }
+    // overlong encodings, surrogates and code points past U+10FFFF
+    if code_point < min || (0xd800..=0xdfff).contains(&code_point) || code_point > 0x10ffff {
+        return Err("Invalid UTF-8 string: invalid code point".to_string());
+    }
+    if code_point < 0x10000 {
+        utf16.push(unit(code_point as u16));
+    } else {
+        // Surrogate pair, 4 u8 -> 2 u16
+        let code_point = code_point - 0x10000;
+        utf16.push(unit(0xd800 | (code_point >> 10) as u16));
+        utf16.push(unit(0xdc00 | (code_point & 0x3ff) as u16));
+    }
+    Ok(len)
+}
+
+// The SIMD paths widen whole chunks of ASCII, and hand the first non-ASCII sequence of a chunk
+// to the scalar decoder along with the ASCII bytes before it. The code units are stored
+// straight into the spare capacity of `utf16`, which has a unit for every byte of `utf8`.
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn to_utf16_avx(utf8: &[u8], is_little_endian: bool) -> Result<Vec<u16>, String> {
+    let mut utf16: Vec<u16> = Vec::with_capacity(utf8.len());
+    let mut offset = 0;
+    while offset + MIN_DIM_SIZE_AVX <= utf8.len() {
+        let chunk = _mm256_loadu_si256(utf8.as_ptr().add(offset) as *const __m256i);
+        let non_ascii = _mm256_movemask_epi8(chunk) as u32;
+        if non_ascii != 0 {
+            let ascii = offset + non_ascii.trailing_zeros() as usize;
+            push_ascii(&utf8[offset..ascii], &mut utf16, is_little_endian);
+            offset = ascii + push_utf16_sequence(utf8, ascii, &mut utf16, is_little_endian)?;
+            continue;
+        }
+        let mut low = _mm256_cvtepu8_epi16(_mm256_castsi256_si128(chunk));
+        let mut high = _mm256_cvtepu8_epi16(_mm256_extracti128_si256(chunk, 1));
+        if is_little_endian {
+            low = _mm256_slli_epi16(low, 8);
+            high = _mm256_slli_epi16(high, 8);
+        }
+        let out = utf16.as_mut_ptr().add(utf16.len()) as *mut __m256i;
+        _mm256_storeu_si256(out, low);
+        _mm256_storeu_si256(out.add(1), high);
+        utf16.set_len(utf16.len() + MIN_DIM_SIZE_AVX);
+        offset += MIN_DIM_SIZE_AVX;
+    }
+    to_utf16_from(utf8, offset, utf16, is_little_endian)
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "sse2")]
+unsafe fn to_utf16_sse(utf8: &[u8], is_little_endian: bool) -> Result<Vec<u16>, String> {
+    let mut utf16: Vec<u16> = Vec::with_capacity(utf8.len());
+    let mut offset = 0;
+    while offset + MIN_DIM_SIZE_SIMD <= utf8.len() {
+        let chunk = _mm_loadu_si128(utf8.as_ptr().add(offset) as *const __m128i);
+        let non_ascii = _mm_movemask_epi8(chunk) as u32;
+        if non_ascii != 0 {
+            let ascii = offset + non_ascii.trailing_zeros() as usize;
+            push_ascii(&utf8[offset..ascii], &mut utf16, is_little_endian);
+            offset = ascii + push_utf16_sequence(utf8, ascii, &mut utf16, is_little_endian)?;
+            continue;
+        }
+        let zero = _mm_setzero_si128();
+        // the byte goes into the high half of each unit when swapped
+        let (low, high) = if is_little_endian {
+            (
+                _mm_unpacklo_epi8(zero, chunk),
+                _mm_unpackhi_epi8(zero, chunk),
+            )
+        } else {
+            (
+                _mm_unpacklo_epi8(chunk, zero),
+                _mm_unpackhi_epi8(chunk, zero),
+            )
+        };
+        let out = utf16.as_mut_ptr().add(utf16.len()) as *mut __m128i;
+        _mm_storeu_si128(out, low);
+        _mm_storeu_si128(out.add(1), high);
+        utf16.set_len(utf16.len() + MIN_DIM_SIZE_SIMD);
+        offset += MIN_DIM_SIZE_SIMD;
+    }
+    to_utf16_from(utf8, offset, utf16, is_little_endian)
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
+unsafe fn to_utf16_neon(utf8: &[u8], is_little_endian: bool) -> Result<Vec<u16>, String> {
+    let mut utf16: Vec<u16> = Vec::with_capacity(utf8.len());
+    let mut offset = 0;
+    while offset + MIN_DIM_SIZE_SIMD <= utf8.len() {
+        let chunk = vld1q_u8(utf8.as_ptr().add(offset));
+        if vmaxvq_u8(chunk) >= 0x80 {
+            let ascii = offset
+                + utf8[offset..offset + MIN_DIM_SIZE_SIMD]
+                    .iter()
+                    .position(|byte| !byte.is_ascii())
+                    .unwrap();
+            push_ascii(&utf8[offset..ascii], &mut utf16, is_little_endian);
+            offset = ascii + push_utf16_sequence(utf8, ascii, &mut utf16, is_little_endian)?;
+            continue;
+        }
+        let mut low = vmovl_u8(vget_low_u8(chunk));
+        let mut high = vmovl_u8(vget_high_u8(chunk));
+        if is_little_endian {
+            low = vshlq_n_u16::<8>(low);
+            high = vshlq_n_u16::<8>(high);
+        }
+        let out = utf16.as_mut_ptr().add(utf16.len());
+        vst1q_u16(out, low);
+        vst1q_u16(out.add(8), high);
+        utf16.set_len(utf16.len() + MIN_DIM_SIZE_SIMD);
+        offset += MIN_DIM_SIZE_SIMD;
+    }
+    to_utf16_from(utf8, offset, utf16, is_little_endian)
+}
@@ -0,0 +1,553 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Fury data read and written without its Rust types, see [`Value`].
+
+use crate::buffer::{Reader, Writer};
+use crate::ensure;
+use crate::error::Error;
+use crate::fury::Fury;
+use crate::meta::{FieldInfo, TypeMeta};
+use crate::resolver::context::{ReadContext, WriteContext};
+use crate::serializer::any::{read_dyn_name, DYN_BUILTIN, DYN_NAMED};
+use crate::serializer::delta::read_delta_value;
+use crate::serializer::map::{ChunkReader, ChunkWriter};
+use crate::serializer::{guards_struct_length, Serializer};
+use crate::types::{FieldType, Mode, RefFlag};
+use anyhow::anyhow;
+use chrono::{NaiveDate, NaiveDateTime};
+
+/// A value decoded from its type ids and the registered type definitions only, e.g. to show
+/// fury data to tools that don't know the Rust types, see [`Fury::deserialize_value`].
+///
+/// Numbers keep their fury type so that a value is written back the way it was read.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    I8(i8),
+    U8(u8),
+    I16(i16),
+    U16(u16),
+    I32(i32),
+    U32(u32),
+    I64(i64),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Binary(Vec<u8>),
+    Date(NaiveDate),
+    Timestamp(NaiveDateTime),
+    BoolArray(Vec<bool>),
+    I16Array(Vec<i16>),
+    I32Array(Vec<i32>),
+    I64Array(Vec<i64>),
+    F32Array(Vec<f32>),
+    F64Array(Vec<f64>),
+    List(Vec<Value>),
+    Set(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    // variants with fields can't be read
+    Enum {
+        type_id: u32,
+        variant: String,
+    },
+    Struct {
+        // `None` when read in compatible mode, where only the field layout is sent
+        type_id: Option<u32>,
+        fields: Vec<(String, Value)>,
+    },
+    /// A skipped value of a type ignored with [`Fury::ignore_type_id`], or of an enum unknown here
+    /// found in an unknown field, see [`ReadContext::is_skipping`].
+    Ignored {
+        type_id: u32,
+    },
+}
+
+/// A value of a type that isn't registered on the reader, held by a `Box<dyn Any>` read with
+/// [`UnknownTypePolicy::CaptureOpaque`]. It can't be written back.
+///
+/// [`UnknownTypePolicy::CaptureOpaque`]: crate::types::UnknownTypePolicy::CaptureOpaque
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpaqueValue {
+    pub type_id: u32,
+    /// A [`Value::Struct`] of the fields named in the type meta in compatible mode, else the
+    /// [`Value::Binary`] body of the value.
+    pub value: Value,
+}
+
+/// Field layout of a registered struct or variants of a registered enum.
+pub enum TypeLayout {
+    Struct(Vec<FieldInfo>),
+    Enum(Vec<String>),
+}
+
+impl TypeLayout {
+    /// Layout of the type registered with `type_id`, from its type definition.
+    ///
+    /// Enum definitions list their variants with the tags `0..n` as ids, which tells them apart.
+    /// Versioned types have no layout.
+    pub fn of(fury: &Fury, type_id: u32) -> Option<TypeLayout> {
+        let class_resolver = fury.get_class_resolver();
+        if class_resolver.is_versioned(type_id) {
+            return None;
+        }
+        let harness = class_resolver.get_harness(type_id)?;
+        let type_def = class_resolver.get_type_def(harness.get_rust_type_id(), fury);
+        let meta = TypeMeta::from_bytes(&mut Reader::new(type_def)).ok()?;
+        let fields = meta.get_field_info();
+        let is_enum = fields
+            .iter()
+            .enumerate()
+            .all(|(tag, field)| field.get_field_id() as usize == tag);
+        if is_enum && !fields.is_empty() {
+            Some(TypeLayout::Enum(
+                fields
+                    .iter()
+                    .map(|field| field.get_field_name().to_string())
+                    .collect(),
+            ))
+        } else {
+            Some(TypeLayout::Struct(fields.to_vec()))
+        }
+    }
+}
+
+fn is_builtin(type_id: i16) -> bool {
+    FieldType::try_from(type_id).is_ok()
+}
+
+impl Value {
+    /// Reads a value with its header.
+    ///
+    /// `declared` is the type id the enclosing struct declares for it, if any. In compatible
+    /// mode structs are written with the index of their type meta instead of a type id; where
+    /// nothing is declared, e.g. for list elements, a header below the number of type metas in
+    /// the message is taken as such an index.
+    pub fn read(context: &mut ReadContext, declared: Option<i16>) -> Result<Value, Error> {
+        context.nested(|context| Value::read_nested(context, declared))
+    }
+
+    fn read_nested(context: &mut ReadContext, declared: Option<i16>) -> Result<Value, Error> {
+        let ref_flag = context.reader.i8()?;
+        if ref_flag == RefFlag::Null as i8 {
+            return Ok(Value::Null);
+        } else if ref_flag == RefFlag::Ref as i8 {
+            context.ref_reader.read_ref_id(&mut context.reader)?;
+            if context.is_skipping() {
+                return Ok(Value::Null);
+            }
+            return Err(Error::Ref);
+        } else if ref_flag == RefFlag::RefValue as i8 {
+            context.ref_reader.reserve_ref_id()?;
+        } else if ref_flag != RefFlag::NotNullValue as i8 {
+            return Err(anyhow!("Unknown ref flag, value:{ref_flag}").into());
+        }
+        let mut header = context.reader.i16()?;
+        // `Box<dyn Any>` fields declare nothing about the value
+        let declared = declared.filter(|type_id| *type_id != FieldType::FuryTypeTag as i16);
+        let fury = context.get_fury();
+        if fury.get_mode() == &Mode::SchemaConsistent && header == DYN_NAMED {
+            // a type registered by name held by a `Box<dyn Any>`, read as the type of that name
+            // here, followed by its own ref flag and type id
+            let (namespace, name) = read_dyn_name(&mut context.reader)?;
+            context.reader.i8()?;
+            let type_id = context.reader.i16()?;
+            header = fury
+                .get_class_resolver()
+                .get_id_by_type_name(&namespace, &name)
+                .map_or(type_id, |id| id as i16);
+        }
+        if fury.get_mode() == &Mode::Compatible {
+            if header == DYN_BUILTIN {
+                // a builtin held by a `Box<dyn Any>`, followed by its own ref flag and type id
+                context.reader.i8()?;
+                let type_id = context.reader.i16()?;
+                return Value::read_body(context, type_id);
+            }
+            // only enums keep their type id, the declared type may be unknown to this side
+            let declared_struct = declared.is_some_and(|type_id| {
+                !is_builtin(type_id)
+                    && !matches!(
+                        TypeLayout::of(fury, type_id as u32),
+                        Some(TypeLayout::Enum(_))
+                    )
+            });
+            // an enum unknown here keeps its type id, which may also be the index of a meta
+            let unknown_enum = declared_struct
+                && declared == Some(header)
+                && TypeLayout::of(fury, header as u32).is_none();
+            let meta = context
+                .meta_resolver
+                .try_get(header as usize)
+                .filter(|meta| !unknown_enum || meta.get_type_id() == header as u32)
+                .cloned();
+            match meta {
+                Some(meta) if declared_struct || declared.is_none() => {
+                    let type_id = meta.get_type_id();
+                    if fury.get_class_resolver().is_ignored(type_id) {
+                        Value::skip_ignored(context, type_id, Some(&meta))?;
+                        return Ok(Value::Ignored { type_id });
+                    }
+                    let mut fields = Vec::with_capacity(meta.get_field_info().len());
+                    for field in meta.get_field_info() {
+                        let value = Value::read(context, Some(field.get_field_id()))?;
+                        fields.push((field.get_field_name().to_string(), value));
+                    }
+                    return Ok(Value::Struct {
+                        type_id: None,
+                        fields,
+                    });
+                }
+                None if declared_struct && !unknown_enum => {
+                    return Err(anyhow!("Unknown type meta index {header}").into());
+                }
+                _ => {}
+            }
+        }
+        if fury.get_class_resolver().is_ignored(header as u32) {
+            Value::skip_ignored(context, header as u32, None)?;
+            return Ok(Value::Ignored {
+                type_id: header as u32,
+            });
+        }
+        Value::read_body(context, header)
+    }
+
+    /// Skips the body of a value of the ignored type `type_id` after its header, with the fields
+    /// of its type meta if it was written with one, else by its length.
+    pub(crate) fn skip_ignored(
+        context: &mut ReadContext,
+        type_id: u32,
+        meta: Option<&TypeMeta>,
+    ) -> Result<(), Error> {
+        match meta {
+            Some(meta) => {
+                for field in meta.get_field_info() {
+                    Value::read(context, Some(field.get_field_id()))?;
+                }
+            }
+            None => {
+                ensure!(
+                    guards_struct_length(context.get_fury()),
+                    "Can't skip a value of the ignored type {}, it must be written with its type meta or `Fury::struct_length_guard`",
+                    type_id
+                );
+                let len = context.reader.u32()?;
+                context.reader.skip(len)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_body(context: &mut ReadContext, type_id: i16) -> Result<Value, Error> {
+        let Ok(field_type) = FieldType::try_from(type_id) else {
+            return Value::read_registered(context, type_id);
+        };
+        Ok(match field_type {
+            FieldType::BOOL => Value::Bool(bool::read(context)?),
+            FieldType::INT8 => Value::I8(i8::read(context)?),
+            FieldType::UINT8 => Value::U8(u8::read(context)?),
+            FieldType::INT16 => Value::I16(i16::read(context)?),
+            FieldType::UINT16 => Value::U16(u16::read(context)?),
+            FieldType::INT32 => Value::I32(i32::read(context)?),
+            FieldType::UINT32 => Value::U32(u32::read(context)?),
+            FieldType::INT64 => Value::I64(i64::read(context)?),
+            FieldType::UINT64 => Value::U64(u64::read(context)?),
+            FieldType::FLOAT => Value::F32(f32::read(context)?),
+            FieldType::DOUBLE => Value::F64(f64::read(context)?),
+            FieldType::STRING => Value::String(String::read(context)?),
+            FieldType::BINARY => Value::Binary(Vec::<u8>::read(context)?),
+            FieldType::DATE => Value::Date(NaiveDate::read(context)?),
+            FieldType::TIMESTAMP => Value::Timestamp(NaiveDateTime::read(context)?),
+            FieldType::FuryPrimitiveBoolArray | FieldType::FuryPackedBoolArray => {
+                Value::BoolArray(Vec::<bool>::read_coerced(context, type_id)?)
+            }
+            FieldType::FuryPrimitiveShortArray => Value::I16Array(Vec::<i16>::read(context)?),
+            FieldType::FuryPrimitiveIntArray => Value::I32Array(Vec::<i32>::read(context)?),
+            FieldType::FuryPrimitiveLongArray => Value::I64Array(Vec::<i64>::read(context)?),
+            FieldType::FuryPrimitiveFloatArray => Value::F32Array(Vec::<f32>::read(context)?),
+            FieldType::FuryPrimitiveDoubleArray => Value::F64Array(Vec::<f64>::read(context)?),
+            FieldType::FuryDeltaArray => read_delta_value(context)?,
+            FieldType::ARRAY => Value::List(Value::read_elements(context)?),
+            FieldType::FurySet => Value::Set(Value::read_elements(context)?),
+            FieldType::MAP | FieldType::FuryLinkedHashMap | FieldType::FuryTreeMap => {
+                let len = context.read_collection_len()?;
+                let per_entry = context.get_fury().is_per_entry_maps();
+                let mut chunks = ChunkReader::default();
+                let mut entries = Vec::new();
+                for _ in 0..len {
+                    context.check_cancelled()?;
+                    let (null_key, null_value) = if per_entry {
+                        (false, false)
+                    } else {
+                        chunks.next(context)?
+                    };
+                    let key = Value::read_in_map(context, null_key)?;
+                    entries.push((key, Value::read_in_map(context, null_value)?));
+                }
+                chunks.finish()?;
+                Value::Map(entries)
+            }
+            FieldType::FuryTypeTag | FieldType::FuryStringArray => {
+                return Err(
+                    anyhow!("Can't read a value of type {field_type:?} dynamically").into(),
+                );
+            }
+        })
+    }
+
+    // a key or value of a map, which a chunk leaves out when null
+    fn read_in_map(context: &mut ReadContext, null: bool) -> Result<Value, Error> {
+        if null {
+            return Ok(Value::Null);
+        }
+        Value::read(context, None)
+    }
+
+    fn read_elements(context: &mut ReadContext) -> Result<Vec<Value>, Error> {
+        let len = context.read_collection_len()?;
+        let mut elements = Vec::new();
+        for _ in 0..len {
+            context.check_cancelled()?;
+            elements.push(Value::read(context, None)?);
+        }
+        Ok(elements)
+    }
+
+    fn read_registered(context: &mut ReadContext, type_id: i16) -> Result<Value, Error> {
+        ensure!(
+            !context
+                .get_fury()
+                .get_class_resolver()
+                .is_versioned(type_id as u32),
+            "Type {} has versioned serializers, its values can't be read as `Value`",
+            type_id
+        );
+        let Some(layout) = TypeLayout::of(context.get_fury(), type_id as u32) else {
+            // in compatible mode only enums are written with their type id, and their fields
+            // with their length
+            if context.is_skipping() && *context.get_fury().get_mode() == Mode::Compatible {
+                context.reader.var_int32()?;
+                let len = context.reader.u32()?;
+                context.reader.skip(len)?;
+                return Ok(Value::Ignored {
+                    type_id: type_id as u32,
+                });
+            }
+            return Err(Error::UnknownTypeId { type_id });
+        };
+        match layout {
+            TypeLayout::Enum(variants) => {
+                let tag = context.reader.var_int32()?;
+                let variant = variants
+                    .get(tag as usize)
+                    .ok_or_else(|| anyhow!("Unknown tag {tag} of enum {type_id}"))?;
+                // the length of the fields of the variant, see `serializer::write_variant_fields`
+                if *context.get_fury().get_mode() == Mode::Compatible {
+                    ensure!(
+                        context.reader.u32()? == 0,
+                        "Variant `{}` of enum {} has fields, which can't be read as `Value`",
+                        variant,
+                        type_id
+                    );
+                }
+                Ok(Value::Enum {
+                    type_id: type_id as u32,
+                    variant: variant.clone(),
+                })
+            }
+            TypeLayout::Struct(field_infos) => {
+                let mut fields = Vec::with_capacity(field_infos.len());
+                for field in field_infos {
+                    let value = Value::read(context, Some(field.get_field_id()))?;
+                    fields.push((field.get_field_name().to_string(), value));
+                }
+                Ok(Value::Struct {
+                    type_id: Some(type_id as u32),
+                    fields,
+                })
+            }
+        }
+    }
+
+    /// Writes the value with its header, like [`Serializer::serialize`] does for typed values.
+    ///
+    /// Structs are written in the field order of their registered type, missing fields as null.
+    pub fn write(&self, context: &mut WriteContext) -> Result<(), Error> {
+        match self {
+            Value::Null => context.writer.i8(RefFlag::Null as i8),
+            Value::Bool(v) => v.serialize(context),
+            Value::I8(v) => v.serialize(context),
+            Value::U8(v) => v.serialize(context),
+            Value::I16(v) => v.serialize(context),
+            Value::U16(v) => v.serialize(context),
+            Value::I32(v) => v.serialize(context),
+            Value::U32(v) => v.serialize(context),
+            Value::I64(v) => v.serialize(context),
+            Value::U64(v) => v.serialize(context),
+            Value::F32(v) => v.serialize(context),
+            Value::F64(v) => v.serialize(context),
+            Value::String(v) => v.serialize(context),
+            Value::Binary(v) => v.serialize(context),
+            Value::Date(v) => v.serialize(context),
+            Value::Timestamp(v) => v.serialize(context),
+            Value::BoolArray(v) => v.serialize(context),
+            Value::I16Array(v) => v.serialize(context),
+            Value::I32Array(v) => v.serialize(context),
+            Value::I64Array(v) => v.serialize(context),
+            Value::F32Array(v) => v.serialize(context),
+            Value::F64Array(v) => v.serialize(context),
+            Value::List(elements) => {
+                Value::write_header(context, FieldType::ARRAY.into());
+                context.writer.var_int32(elements.len() as i32);
+                for element in elements {
+                    element.write(context)?;
+                }
+            }
+            Value::Set(elements) => {
+                Value::write_header(context, FieldType::FurySet.into());
+                context.writer.var_int32(elements.len() as i32);
+                for element in elements {
+                    element.write(context)?;
+                }
+            }
+            Value::Map(entries) => {
+                Value::write_header(context, FieldType::MAP.into());
+                context.writer.var_int32(entries.len() as i32);
+                let per_entry = context.get_fury().is_per_entry_maps();
+                let mut chunks = ChunkWriter::default();
+                for (key, value) in entries {
+                    let (null_key, null_value) = (*key == Value::Null, *value == Value::Null);
+                    if !per_entry {
+                        chunks.entry(context, null_key, null_value);
+                    }
+                    if per_entry || !null_key {
+                        key.write(context)?;
+                    }
+                    if per_entry || !null_value {
+                        value.write(context)?;
+                    }
+                }
+                chunks.close(context);
+            }
+            Value::Enum { type_id, variant } => {
+                let Some(TypeLayout::Enum(variants)) = TypeLayout::of(context.get_fury(), *type_id)
+                else {
+                    return Err(anyhow!("Type {type_id} isn't a registered enum").into());
+                };
+                let tag = variants
+                    .iter()
+                    .position(|v| v == variant)
+                    .ok_or_else(|| anyhow!("Enum {type_id} has no variant `{variant}`"))?;
+                Value::write_header(context, *type_id as i16);
+                context.writer.var_int32(tag as i32);
+                if *context.get_fury().get_mode() == Mode::Compatible {
+                    context.writer.u32(0);
+                }
+            }
+            Value::Struct { type_id, fields } => {
+                let type_id =
+                    type_id.ok_or_else(|| anyhow!("Can't write a struct without its type id"))?;
+                let fury = context.get_fury();
+                let Some(TypeLayout::Struct(field_infos)) = TypeLayout::of(fury, type_id) else {
+                    return Err(anyhow!("Type {type_id} isn't a registered struct").into());
+                };
+                match fury.get_mode() {
+                    Mode::SchemaConsistent => Value::write_header(context, type_id as i16),
+                    Mode::Compatible => {
+                        let rust_type_id = fury
+                            .get_class_resolver()
+                            .get_harness(type_id)
+                            .expect("registered above")
+                            .get_rust_type_id();
+                        let meta_index = context.push_meta(rust_type_id) as i16;
+                        Value::write_header(context, meta_index);
+                    }
+                }
+                for field in field_infos {
+                    let value = fields
+                        .iter()
+                        .find(|(name, _)| name == field.get_field_name())
+                        .map_or(&Value::Null, |(_, value)| value);
+                    value.write(context)?;
+                }
+            }
+            Value::Ignored { type_id } => {
+                return Err(
+                    anyhow!("Can't write a skipped value of the ignored type {type_id}").into(),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn write_header(context: &mut WriteContext, type_id: i16) {
+        context.writer.i8(RefFlag::NotNullValue as i8);
+        context.writer.i16(type_id);
+    }
+
+    /// The dynamic form of `value`, as [`Fury::deserialize_value`] would read it from the
+    /// message of `value`. It is written with its registered serializer and read back from those
+    /// bytes, so this costs about as much as serializing and deserializing, only without the
+    /// message head.
+    pub fn from_typed<T: Serializer>(value: &T, fury: &Fury) -> Result<Value, Error> {
+        convert(
+            fury,
+            |context| {
+                value.serialize(context);
+                Ok(())
+            },
+            |context| Value::read(context, None),
+        )
+    }
+
+    /// Converts the value to a `T` with its registered serializer, as if `T` was read from the
+    /// message [`Fury::serialize_value`] writes for it, and at about the same cost since the
+    /// value is written to bytes first. Like there, structs need their type id, which those read
+    /// in compatible mode don't have.
+    pub fn into_typed<T: Serializer>(self, fury: &Fury) -> Result<T, Error> {
+        convert(fury, |context| self.write(context), T::deserialize)
+    }
+}
+
+// values go from one side to the other in the body encoding, preceded by the offset of the type
+// metas written after it in compatible mode. Serializers only know how to write and read bytes,
+// converting without them would take a visitor over every serializer.
+fn convert<T>(
+    fury: &Fury,
+    write: impl FnOnce(&mut WriteContext) -> Result<(), Error>,
+    read: impl FnOnce(&mut ReadContext) -> Result<T, Error>,
+) -> Result<T, Error> {
+    fury.check_dynamic_type_ids()?;
+    let mut writer = Writer::default();
+    writer.skip(4);
+    let mut context = WriteContext::new(fury, &mut writer);
+    write(&mut context)?;
+    if fury.get_mode() == &Mode::Compatible {
+        context.write_meta(0);
+    }
+    let mut context = ReadContext::new(fury, Reader::new(writer.as_slice()));
+    let meta_offset = context.reader.u32()? as usize;
+    if meta_offset > 0 {
+        context.load_meta(meta_offset)?;
+    }
+    let value = read(&mut context);
+    context.flush_schema_events();
+    value
+}
@@ -15,14 +15,14 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use crate::util::sorted_fields;
+use crate::util::{sorted_fields, StructAttrs};
 use proc_macro::TokenStream;
 use quote::quote;
 
 pub fn derive_row(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
     let fields = match &ast.data {
-        syn::Data::Struct(s) => sorted_fields(&s.fields),
+        syn::Data::Struct(s) => sorted_fields(&s.fields, &StructAttrs::default()),
         _ => {
             panic!("only struct be supported")
         }
@@ -33,9 +33,13 @@ pub fn derive_row(ast: &syn::DeriveInput) -> TokenStream {
         let ident = field.ident.as_ref().expect("field should provide ident");
 
         quote! {
-            let mut callback_info = struct_writer.write_start(#index);
-            <#ty as fury_core::row::Row<'a>>::write(&v.#ident, struct_writer.get_writer());
-            struct_writer.write_end(callback_info);
+            if <#ty as fury_core::row::Row<'a>>::is_null(&v.#ident) {
+                struct_writer.set_null_at(#index);
+            } else {
+                let mut callback_info = struct_writer.write_start(#index);
+                <#ty as fury_core::row::Row<'a>>::write(&v.#ident, struct_writer.get_writer());
+                struct_writer.write_end(callback_info);
+            }
         }
     });
 
@@ -46,6 +50,9 @@ pub fn derive_row(ast: &syn::DeriveInput) -> TokenStream {
 
         quote! {
             pub fn #getter_name(&self) -> <#ty as fury_core::row::Row<'a>>::ReadResult {
+                if self.struct_data.is_null_at(#index) {
+                    return <#ty as fury_core::row::Row<'a>>::cast_null();
+                }
                 let bytes = self.struct_data.get_field_bytes(#index);
                 <#ty as fury_core::row::Row<'a>>::cast(bytes)
             }
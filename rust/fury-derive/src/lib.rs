@@ -21,12 +21,48 @@ use syn::{parse_macro_input, DeriveInput};
 
 mod fury_row;
 mod object;
+#[cfg(test)]
+mod tests;
 mod util;
 
-#[proc_macro_derive(Fury)]
+/// Derives fury serialization for a struct or an enum.
+///
+/// A variant of an enum is written as its index among the variants, then its fields in
+/// declaration order. New variants and new fields of a variant must be added at the end. In
+/// compatible mode readers skip the fields they don't know, and read variants they don't know
+/// as the variant marked `#[fury(other)]`, or fail without one.
+///
+/// Unit structs and structs without fields are written as their type id alone, in compatible
+/// mode with a type meta without fields, so they can serve as markers or commands. Fields of
+/// tuple structs are written in declaration order and named `_0`, `_1`... in the message.
+///
+/// Struct fields marked `#[fury(skip)]` aren't written and are read as `Default::default()`,
+/// or as the expression given with `#[fury(default = "...")]`. On a field that is written,
+/// `default` is the value for messages of peers that don't have the field.
+///
+/// `#[fury(rename = "userName")]` gives a field another name in the message, which also orders
+/// the fields. `#[fury(id = 3)]` identifies it by a tag id in compatible mode, so that renaming
+/// the Rust field doesn't change the message. `#[fury(java_naming)]` on the struct names all
+/// fields without a rename in camelCase, `user_name` as `userName`, like those of a Java class.
+///
+/// Types may have type parameters, which need a serializer, and at most one lifetime. Each
+/// instantiation, e.g. `Wrapper<i32>` and `Wrapper<String>`, is registered on its own and has
+/// the type meta and hash of its field types.
+///
+/// Field types need a serializer, the compiler reports one without it at the field. Reading a
+/// field whose struct type isn't registered fails with `Error::MissingSerializer`, which names
+/// the field. Types can be registered in any order.
+///
+/// The generated items are a stable API tools may rely on:
+/// - the trait impls `Serializer`, `StructSerializer`, `BorrowDeserialize` and `FuryGeneralList`,
+/// - an inherent `fn fury_type_id(fury: &Fury) -> Option<u32>` returning the registered id.
+///
+/// Anything else in the expansion is an implementation detail. `tests/expand` holds snapshots
+/// of it, so that changes show up in review.
+#[proc_macro_derive(Fury, attributes(fury))]
 pub fn proc_macro_derive_fury_object(input: proc_macro::TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    object::derive_serializer(&input)
+    object::derive_serializer(&input).into()
 }
 
 #[proc_macro_derive(FuryRow)]
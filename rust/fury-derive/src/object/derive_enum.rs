@@ -15,30 +15,87 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use proc_macro2::Ident;
 use proc_macro2::TokenStream;
-use quote::quote;
-use syn::DataEnum;
+use quote::{format_ident, quote};
+use syn::{DataEnum, Fields, Generics, Lifetime, Variant};
 
-pub fn gen_type_def(_data_enum: &DataEnum) -> TokenStream {
+use crate::util::parse_other_variant;
+
+// variants are recorded as fields named after them, with the tag as the field id
+pub fn gen_type_def(data_enum: &DataEnum) -> TokenStream {
+    let variant_infos = data_enum.variants.iter().enumerate().map(|(tag, v)| {
+        let name = v.ident.to_string();
+        let tag = tag as i16;
+        quote! {
+            fury_core::meta::FieldInfo::new(#name, #tag)
+        }
+    });
     quote! {
         fn type_def(fury: &fury_core::fury::Fury) -> Vec<u8> {
-            Vec::new()
+            fury_core::meta::TypeMeta::from_fields(
+                0,
+                vec![#(#variant_infos),*]
+            ).to_bytes().unwrap()
+        }
+    }
+}
+
+pub fn gen_layout(generics: &Generics, name: &Ident, data_enum: &DataEnum) -> TokenStream {
+    let variant_names = data_enum.variants.iter().map(|v| v.ident.to_string());
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics fury_core::types::EnumLayout for #name #ty_generics #where_clause {
+            const VARIANTS: &'static [&'static str] = &[#(#variant_names),*];
         }
     }
 }
 
-pub fn gen_write(data_enum: &DataEnum) -> TokenStream {
-    let variant_idents: Vec<_> = data_enum.variants.iter().map(|v| &v.ident).collect();
-    let variant_values: Vec<_> = (0..variant_idents.len()).map(|v| v as i32).collect();
+// binds the fields of a variant in declaration order as `field_0`, `field_1`, ...
+fn bindings(variant: &Variant) -> (TokenStream, Vec<Ident>) {
+    let ident = &variant.ident;
+    let names: Vec<Ident> = (0..variant.fields.len())
+        .map(|index| format_ident!("field_{}", index))
+        .collect();
+    let pattern = match &variant.fields {
+        Fields::Unit => quote! { Self::#ident },
+        Fields::Unnamed(_) => quote! { Self::#ident(#(#names),*) },
+        Fields::Named(fields) => {
+            let idents = fields.named.iter().map(|field| &field.ident);
+            quote! { Self::#ident { #(#idents: #names),* } }
+        }
+    };
+    (pattern, names)
+}
+
+// a variant is its tag, then its fields, see `serializer::write_variant_fields`
+pub fn gen_write(data_enum: &DataEnum, static_ty: &TokenStream) -> TokenStream {
+    let arms = data_enum.variants.iter().enumerate().map(|(tag, variant)| {
+        let tag = tag as i32;
+        let (pattern, names) = bindings(variant);
+        let types = variant.fields.iter().map(|field| &field.ty);
+        let context = if names.is_empty() {
+            quote! { _ }
+        } else {
+            quote! { context }
+        };
+        quote! {
+            #pattern => {
+                context.writer.var_int32(#tag);
+                fury_core::serializer::write_variant_fields(context, |#context| {
+                    #(
+                        <#types as fury_core::serializer::Serializer>::serialize_declared(#names, context);
+                    )*
+                });
+            }
+        }
+    });
 
     quote! {
         fn write(&self, context: &mut fury_core::resolver::context::WriteContext) {
+            context.record_type(std::any::TypeId::of::<#static_ty>());
             match self {
-                #(
-                    Self::#variant_idents => {
-                        context.writer.var_int32(#variant_values);
-                    }
-                )*
+                #(#arms)*
             }
         }
 
@@ -48,21 +105,81 @@ pub fn gen_write(data_enum: &DataEnum) -> TokenStream {
     }
 }
 
-pub fn gen_read(data_enum: &DataEnum) -> TokenStream {
-    let variant_idents: Vec<_> = data_enum.variants.iter().map(|v| &v.ident).collect();
-    let variant_values: Vec<_> = (0..variant_idents.len()).map(|v| v as i32).collect();
+pub fn gen_read(data_enum: &DataEnum, static_ty: &TokenStream) -> TokenStream {
+    let other = match parse_other_variant(data_enum) {
+        Ok(other) => other,
+        Err(err) => return err.to_compile_error(),
+    };
+    let arms = data_enum.variants.iter().enumerate().map(|(tag, variant)| {
+        let ident = &variant.ident;
+        let tag = tag as i32;
+        let values = variant.fields.iter().enumerate().map(|(index, field)| {
+            let ty = &field.ty;
+            // errors report `Variant.field`, or `Variant.0` for tuple variants
+            let path = match &field.ident {
+                Some(name) => format!("{ident}.{name}"),
+                None => format!("{ident}.{index}"),
+            };
+            quote! {
+                <#ty as fury_core::serializer::Serializer>::deserialize_declared(context)
+                    .map_err(|err| context.field_error(err, #path))?
+            }
+        });
+        let (value, context) = match &variant.fields {
+            Fields::Unit => (quote! { Self::#ident }, quote! { _ }),
+            Fields::Unnamed(_) => (quote! { Self::#ident(#(#values),*) }, quote! { context }),
+            Fields::Named(fields) => {
+                let idents = fields.named.iter().map(|field| &field.ident);
+                (
+                    quote! { Self::#ident { #(#idents: #values),* } },
+                    quote! { context },
+                )
+            }
+        };
+        quote! {
+            #tag => fury_core::serializer::read_variant_fields(context, |#context| Ok(#value)),
+        }
+    });
+    let unknown = match other {
+        Some(other) => {
+            let ident = &data_enum.variants[other].ident;
+            quote! {
+                tag => {
+                    fury_core::serializer::skip_variant::<Self>(context, tag)?;
+                    Ok(Self::#ident)
+                }
+            }
+        }
+        None => quote! {
+            tag => Err(fury_core::serializer::unknown_variant::<Self>(tag)),
+        },
+    };
 
     quote! {
        fn read(
            context: &mut fury_core::resolver::context::ReadContext,
        ) -> Result<Self, fury_core::error::Error> {
-           let v = context.reader.var_int32();
-           match v {
-               #(
-                   #variant_values => Ok(Self::#variant_idents),
-               )*
-               _ => panic!("unknown value"),
-           }
+           context.record_type(std::any::TypeId::of::<#static_ty>());
+           context.nested(|context| match context.reader.var_int32()? {
+               #(#arms)*
+               #unknown
+           })
        }
     }
 }
+
+pub fn gen_read_borrowed(bf: &Lifetime) -> TokenStream {
+    quote! {
+        fn read_borrowed(
+            context: &mut fury_core::resolver::context::ReadContext<'_, #bf>,
+        ) -> Result<Self, fury_core::error::Error> {
+            <Self as fury_core::serializer::Serializer>::read(context)
+        }
+
+        fn deserialize_borrowed(
+            context: &mut fury_core::resolver::context::ReadContext<'_, #bf>,
+        ) -> Result<Self, fury_core::error::Error> {
+            <Self as fury_core::serializer::Serializer>::deserialize(context)
+        }
+    }
+}
@@ -15,65 +15,100 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::util::{field_name, parse_field_attrs, StructAttrs};
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
 use syn::Field;
 
-fn hash(fields: &[&Field]) -> TokenStream {
-    let props = fields.iter().map(|field| {
-        let ty = &field.ty;
-        let name = format!("{}", field.ident.as_ref().expect("should be field name"));
+// type of the field with its generics, see `StructSerializer::field_types`
+fn field_type_def(field: &Field) -> TokenStream {
+    let ty = &field.ty;
+    if parse_field_attrs(field).unwrap_or_default().delta {
         quote! {
-            (#name, <#ty as fury_core::serializer::Serializer>::get_type_id())
+            fury_core::type_def::FieldTypeDef::new(fury_core::types::FieldType::FuryDeltaArray as i16)
         }
-    });
+    } else {
+        quote! { fury_core::type_def::FieldTypeDef::of::<#ty>(fury) }
+    }
+}
+
+// type id of the field in the type def
+fn field_type(field: &Field) -> TokenStream {
+    let ty = &field.ty;
+    if parse_field_attrs(field).unwrap_or_default().delta {
+        quote! { fury_core::types::FieldType::FuryDeltaArray as i16 }
+    } else {
+        quote! { <#ty as fury_core::serializer::Serializer>::get_type_id(fury) }
+    }
+}
 
+/// `StructSerializer::struct_hash` of the type ids of the fields, without building the type def.
+pub fn gen_struct_hash(fields: &[&Field]) -> TokenStream {
+    let field_types = fields.iter().map(|field| field_type(field));
     quote! {
-        fn fury_hash() -> u32 {
-            use std::sync::Once;
-            static mut name_hash: u32 = 0u32;
-            static name_hash_once: Once = Once::new();
-            unsafe {
-                name_hash_once.call_once(|| {
-                        name_hash = fury_core::types::compute_struct_hash(vec![#(#props),*]);
-                });
-                name_hash
-            }
+        fn struct_hash(fury: &fury_core::fury::Fury) -> u32 {
+            fury_core::meta::struct_hash(&[#(#field_types),*])
         }
     }
 }
 
-fn type_def(fields: &[&Field]) -> TokenStream {
+fn type_def(fields: &[&Field], struct_attrs: &StructAttrs) -> TokenStream {
     let field_infos = fields.iter().map(|field| {
+        let field_type = field_type(field);
+        match parse_field_attrs(field).unwrap_or_default().id {
+            Some(id) => quote! {
+                fury_core::meta::FieldInfo::with_tag_id(#id, #field_type)
+            },
+            None => {
+                let name = field_name(field, struct_attrs);
+                quote! {
+                    fury_core::meta::FieldInfo::new(#name, #field_type)
+                }
+            }
+        }
+    });
+    // spanned at the field types, so that one without a serializer is reported there
+    let bounds = fields.iter().map(|field| {
         let ty = &field.ty;
-        let name = format!("{}", field.ident.as_ref().expect("should be field name"));
-        quote! {
-            fury_core::meta::FieldInfo::new(#name, <#ty as fury_core::serializer::Serializer>::get_type_id(fury))
+        quote_spanned! {ty.span()=>
+            fury_core::serializer::field_type_must_derive_fury::<#ty>();
         }
     });
+    let field_type_defs = fields.iter().map(|field| field_type_def(field));
     quote! {
         fn type_def(fury: &fury_core::fury::Fury) -> Vec<u8> {
+            #(#bounds)*
             fury_core::meta::TypeMeta::from_fields(
                 0,
                 vec![#(#field_infos),*]
             ).to_bytes().unwrap()
         }
+
+        fn field_types(fury: &fury_core::fury::Fury) -> Vec<fury_core::type_def::FieldTypeDef> {
+            vec![#(#field_type_defs),*]
+        }
     }
 }
 
-pub fn gen_in_struct_impl(fields: &[&Field]) -> TokenStream {
-    let _hash_token_stream = hash(fields);
-    let type_def_token_stream = type_def(fields);
-
-    quote! {
-        #type_def_token_stream
-    }
+pub fn gen_in_struct_impl(fields: &[&Field], attrs: &StructAttrs) -> TokenStream {
+    type_def(fields, attrs)
 }
 
-pub fn gen() -> TokenStream {
+pub fn gen(static_ty: &TokenStream) -> TokenStream {
     quote! {
             fn get_type_id(fury: &fury_core::fury::Fury) -> i16 {
-                fury.get_class_resolver().get_class_info(std::any::TypeId::of::<Self>()).get_type_id() as i16
+                match fury.get_class_resolver().get_registered_id(std::any::TypeId::of::<#static_ty>()) {
+                    Some(id) => id as i16,
+                    None => panic!("`{}` isn't registered with this Fury", std::any::type_name::<#static_ty>()),
+                }
+            }
+
+            fn missing_serializer(fury: &fury_core::fury::Fury) -> Option<&'static str> {
+                fury.get_class_resolver()
+                    .get_registered_id(std::any::TypeId::of::<#static_ty>())
+                    .is_none()
+                    .then(std::any::type_name::<#static_ty>)
             }
     }
 }
@@ -18,6 +18,7 @@
 mod derive_enum;
 mod misc;
 mod read;
+mod remote;
 mod serializer;
 mod write;
 
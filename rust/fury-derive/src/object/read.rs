@@ -15,9 +15,14 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use proc_macro2::{Ident, TokenStream};
+use proc_macro2::{Ident, Literal, TokenStream};
 use quote::{format_ident, quote};
-use syn::Field;
+use syn::{Field, Lifetime};
+
+use crate::util::{
+    field_member, field_name, is_option, is_static, is_vec, parse_field_attrs, FieldAttrs,
+    StructAttrs,
+};
 
 fn create_private_field_name(field: &Field) -> Ident {
     format_ident!("_{}", field.ident.as_ref().expect(""))
@@ -29,37 +34,229 @@ fn bind(fields: &[&Field]) -> Vec<TokenStream> {
         .map(|field| {
             let ty = &field.ty;
             let var_name = create_private_field_name(field);
+            // named after the field, which may not be snake case
             quote! {
+                #[allow(non_snake_case)]
                 let mut #var_name: Option<#ty> = None;
             }
         })
         .collect()
 }
 
-fn create(fields: &[&Field]) -> Vec<TokenStream> {
+/// Value of a skipped field, or of one the message doesn't have if it is given explicitly.
+fn default_value(field: &Field) -> TokenStream {
+    match parse_field_attrs(field) {
+        Ok(FieldAttrs {
+            default: Some(default),
+            ..
+        }) => quote! { #default },
+        _ => quote! { Default::default() },
+    }
+}
+
+/// `name: value` of each `#[fury(skip)]` field, appended to the fields read.
+pub fn fill_skipped(skipped: &[&Field], attrs: &StructAttrs) -> Vec<TokenStream> {
+    skipped
+        .iter()
+        .map(|field| {
+            let name = field_member(field, attrs);
+            let default = default_value(field);
+            quote! {
+                #name: #default
+            }
+        })
+        .collect()
+}
+
+// fields the message doesn't have are `None` if optional, their `#[fury(default)]` if they have
+// one, an error otherwise
+fn create(fields: &[&Field], attrs: &StructAttrs) -> Vec<TokenStream> {
     fields
         .iter()
         .map(|field| {
-            let name = &field.ident;
+            let name = field_member(field, attrs);
             let var_name = create_private_field_name(field);
+            if parse_field_attrs(field).is_ok_and(|attrs| attrs.default.is_some()) {
+                let default = default_value(field);
+                return quote! {
+                    #name: #var_name.unwrap_or_else(|| #default)
+                };
+            }
+            if is_option(&field.ty) {
+                return quote! {
+                    #name: #var_name.unwrap_or_default()
+                };
+            }
+            let message = format!(
+                "Field `{}` of `{{}}` is missing from the message",
+                field.ident.as_ref().expect("should be field name")
+            );
             quote! {
-                #name: #var_name.unwrap()
+                #name: #var_name.ok_or_else(|| {
+                    fury_core::error::AnyhowError::msg(format!(#message, std::any::type_name::<Self>()))
+                })?
             }
         })
         .collect()
 }
 
-fn read(fields: &[&Field]) -> TokenStream {
-    let assign_stmt = fields.iter().map(|field| {
+/// Trait the generated code deserializes fields through.
+#[derive(Clone, Copy)]
+pub enum ReadPath {
+    Owned,
+    Borrowed,
+}
+
+impl ReadPath {
+    fn deserialize(self, field: &Field) -> TokenStream {
+        let name = field
+            .ident
+            .as_ref()
+            .expect("should be field name")
+            .to_string();
+        let mut deserialize = self.deserialize_value(field, &name);
         let ty = &field.ty;
-        let name = &field.ident;
+        let plain = matches!(
+            parse_field_attrs(field),
+            Ok(FieldAttrs {
+                allowed: None,
+                delta: false,
+                ..
+            })
+        );
+        if plain && (matches!(self, ReadPath::Owned) || is_static(ty)) {
+            // an unregistered struct fails here with the field it is in, rather than deep in its read
+            deserialize = quote! {
+                fury_core::serializer::check_field_serializer::<Self, #ty>(context.get_fury(), #name)
+                    .and_then(|_| #deserialize)
+            };
+        }
+        if let Ok(FieldAttrs {
+            unknown_types: Some(policy),
+            ..
+        }) = parse_field_attrs(field)
+        {
+            deserialize = quote! {
+                context.with_unknown_types(
+                    fury_core::types::UnknownTypePolicy::#policy,
+                    |context| #deserialize,
+                )
+            };
+        }
+        // errors report the path of the field, built while they bubble up
         quote! {
-            #name: <#ty as fury_core::serializer::Serializer>::deserialize(context)?
+            #deserialize.map_err(|err| context.field_error(err, #name))?
+        }
+    }
+
+    fn deserialize_value(self, field: &Field, name: &str) -> TokenStream {
+        let ty = &field.ty;
+        let attrs = match parse_field_attrs(field) {
+            Ok(attrs) => attrs,
+            Err(err) => return err.to_compile_error(),
+        };
+        if let Some(allowed) = attrs.allowed {
+            // checked before downcasting, whichever path reads the struct
+            let deserialize = if is_vec(ty) {
+                quote! { fury_core::serializer::deserialize_any_vec_allowed }
+            } else {
+                quote! { fury_core::serializer::deserialize_any_allowed }
+            };
+            return quote! {
+                #deserialize(context, #name, &[#(std::any::TypeId::of::<#allowed>()),*])
+            };
+        }
+        if attrs.delta {
+            return quote! {
+                fury_core::serializer::deserialize_delta(context)
+            };
+        }
+        // `'static` fields can't borrow from the input, they are read owned whichever the path
+        match self {
+            ReadPath::Borrowed if is_static(ty) => quote! {
+                <#ty as fury_core::serializer::Serializer>::deserialize_declared(context)
+            },
+            ReadPath::Owned => quote! {
+                <#ty as fury_core::serializer::Serializer>::deserialize_declared(context)
+            },
+            ReadPath::Borrowed => quote! {
+                <#ty as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(context)
+            },
+        }
+    }
+}
+
+fn read_with_presence_bitmap(
+    fields: &[&Field],
+    skipped: &[&Field],
+    attrs: &StructAttrs,
+    path: ReadPath,
+) -> TokenStream {
+    let bitmap_size = (fields.iter().filter(|field| is_option(&field.ty)).count() + 7) / 8;
+    let mut optional_index = 0;
+    let assign_stmt = fields.iter().map(|field| {
+        let ty = &field.ty;
+        let name = field_member(field, attrs);
+        let deserialize = path.deserialize(field);
+        if is_option(ty) {
+            let (byte, bit) = (
+                Literal::usize_suffixed(optional_index / 8),
+                (optional_index % 8) as u8,
+            );
+            optional_index += 1;
+            quote! {
+                #name: if presence[#byte] & (1 << #bit) != 0 {
+                    #deserialize
+                } else {
+                    None
+                }
+            }
+        } else {
+            quote! {
+                #name: #deserialize
+            }
         }
     });
+    let fill_skipped = fill_skipped(skipped, attrs);
 
     quote! {
-        fn read(context: &mut fury_core::resolver::context::ReadContext) -> Result<Self, fury_core::error::Error> {
+        let presence = context.reader.bytes(#bitmap_size)?;
+        Ok(Self {
+            #(#assign_stmt,)*
+            #(#fill_skipped,)*
+        })
+    }
+}
+
+fn read_fields(
+    fields: &[&Field],
+    skipped: &[&Field],
+    attrs: &StructAttrs,
+    path: ReadPath,
+) -> TokenStream {
+    let assign_stmt = fields
+        .iter()
+        .map(|field| {
+            let name = field_member(field, attrs);
+            let deserialize = path.deserialize(field);
+            quote! {
+                #name: #deserialize
+            }
+        })
+        .chain(fill_skipped(skipped, attrs));
+    if attrs.presence_bitmap {
+        let bitmap_token_stream = read_with_presence_bitmap(fields, skipped, attrs, path);
+        quote! {
+            if context.get_fury().get_mode() == &fury_core::types::Mode::SchemaConsistent {
+                #bitmap_token_stream
+            } else {
+                Ok(Self {
+                    #(#assign_stmt),*
+                })
+            }
+        }
+    } else {
+        quote! {
             Ok(Self {
                 #(#assign_stmt),*
             })
@@ -67,39 +264,63 @@ fn read(fields: &[&Field]) -> TokenStream {
     }
 }
 
-fn deserialize_compatible(fields: &[&Field]) -> TokenStream {
-    let pattern_item = fields.iter().enumerate().map(|(index, field)| {
-        let ty = &field.ty;
+fn deserialize_compatible(
+    fields: &[&Field],
+    skipped: &[&Field],
+    attrs: &StructAttrs,
+    static_ty: &TokenStream,
+    path: ReadPath,
+) -> TokenStream {
+    // fields are matched by tag id or name, the peer's type may have others or list them in
+    // another order. Fields with a tag id are still found by name in messages of peers without it.
+    let pattern_item = fields.iter().map(|field| {
         let var_name = create_private_field_name(field);
+        let name = field_name(field, attrs);
+        let deserialize = path.deserialize(field);
+        let pattern = match parse_field_attrs(field).ok().and_then(|attrs| attrs.id) {
+            Some(id) => quote! { (Some(#id), _) | (None, #name) },
+            None => quote! { (None, #name) },
+        };
         quote! {
-            #index => {
-                #var_name = Some(<#ty as fury_core::serializer::Serializer>::deserialize(context)?);
+            #pattern => {
+                #var_name = Some(#deserialize);
             }
         }
     });
     let bind: Vec<TokenStream> = bind(fields);
-    let create: Vec<TokenStream> = create(fields);
+    let create: Vec<TokenStream> = create(fields, attrs)
+        .into_iter()
+        .chain(fill_skipped(skipped, attrs))
+        .collect();
     quote! {
-        let ref_flag = context.reader.i8();
+        let ref_flag = context.reader.i8()?;
         if ref_flag == (fury_core::types::RefFlag::NotNullValue as i8) || ref_flag == (fury_core::types::RefFlag::RefValue as i8) {
-            let meta_index = context.reader.i16() as usize;
-            let meta = context.get_meta(meta_index).clone();
-            let fields = meta.get_field_info();
-            #(#bind)*
-            for (idx, _field_info) in fields.iter().enumerate() {
-                match idx {
-                    #(#pattern_item),*
-                    _ => {
-                        panic!("not implement yet");
+            if ref_flag == (fury_core::types::RefFlag::RefValue as i8) {
+                context.ref_reader.reserve_ref_id()?;
+            }
+            let meta_index = context.reader.i16()? as usize;
+            let meta = context.get_meta(meta_index)?.clone();
+            context.record_struct();
+            context.record_type(std::any::TypeId::of::<#static_ty>());
+            context.nested(|context| {
+                let fields = meta.get_field_info();
+                #(#bind)*
+                for field_info in fields.iter() {
+                    match (field_info.get_tag_id(), field_info.get_field_name()) {
+                        #(#pattern_item),*
+                        _ => {
+                            context.skip_unknown_field(std::any::type_name::<Self>(), field_info)?;
+                        }
                     }
                 }
-            }
-            Ok(Self {
-                #(#create),*
+                Ok(Self {
+                    #(#create),*
+                })
             })
         } else if ref_flag == (fury_core::types::RefFlag::Null as i8) {
             Err(fury_core::error::AnyhowError::msg("Try to deserialize non-option type to null"))?
         } else if ref_flag == (fury_core::types::RefFlag::Ref as i8) {
+            context.ref_reader.read_ref_id(&mut context.reader)?;
             Err(fury_core::error::Error::Ref)
         } else {
             Err(fury_core::error::AnyhowError::msg("Unknown ref flag, value:{ref_flag}"))?
@@ -107,9 +328,15 @@ fn deserialize_compatible(fields: &[&Field]) -> TokenStream {
     }
 }
 
-pub fn gen(fields: &[&Field]) -> TokenStream {
-    let read_token_stream = read(fields);
-    let compatible_token_stream = deserialize_compatible(fields);
+pub fn gen(
+    fields: &[&Field],
+    skipped: &[&Field],
+    attrs: &StructAttrs,
+    static_ty: &TokenStream,
+) -> TokenStream {
+    let read_token_stream = read_fields(fields, skipped, attrs, ReadPath::Owned);
+    let compatible_token_stream =
+        deserialize_compatible(fields, skipped, attrs, static_ty, ReadPath::Owned);
 
     quote! {
         fn deserialize(context: &mut fury_core::resolver::context::ReadContext) -> Result<Self, fury_core::error::Error> {
@@ -122,6 +349,45 @@ pub fn gen(fields: &[&Field]) -> TokenStream {
                 }
             }
         }
-        #read_token_stream
+
+        fn read(context: &mut fury_core::resolver::context::ReadContext) -> Result<Self, fury_core::error::Error> {
+            context.record_type(std::any::TypeId::of::<#static_ty>());
+            fury_core::serializer::read_struct_fields(context, |context| {
+                #read_token_stream
+            })
+        }
+    }
+}
+
+/// Body of the `BorrowDeserialize<'bf>` impl, fields are read through `BorrowDeserialize` too.
+pub fn gen_borrowed(
+    fields: &[&Field],
+    skipped: &[&Field],
+    attrs: &StructAttrs,
+    static_ty: &TokenStream,
+    bf: &Lifetime,
+) -> TokenStream {
+    let read_token_stream = read_fields(fields, skipped, attrs, ReadPath::Borrowed);
+    let compatible_token_stream =
+        deserialize_compatible(fields, skipped, attrs, static_ty, ReadPath::Borrowed);
+
+    quote! {
+        fn deserialize_borrowed(context: &mut fury_core::resolver::context::ReadContext<'_, #bf>) -> Result<Self, fury_core::error::Error> {
+            match context.get_fury().get_mode() {
+                fury_core::types::Mode::SchemaConsistent => {
+                    fury_core::serializer::deserialize_borrowed::<Self>(context)
+                },
+                fury_core::types::Mode::Compatible => {
+                    #compatible_token_stream
+                }
+            }
+        }
+
+        fn read_borrowed(context: &mut fury_core::resolver::context::ReadContext<'_, #bf>) -> Result<Self, fury_core::error::Error> {
+            context.record_type(std::any::TypeId::of::<#static_ty>());
+            fury_core::serializer::read_struct_fields(context, |context| {
+                #read_token_stream
+            })
+        }
     }
 }
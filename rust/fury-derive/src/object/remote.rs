@@ -0,0 +1,83 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{Field, Path};
+
+use crate::object::{misc, read};
+use crate::util::{field_member, field_name, StructAttrs};
+
+pub fn gen(
+    name: &Ident,
+    fields: &[&Field],
+    skipped: &[&Field],
+    attrs: &StructAttrs,
+    remote: &Path,
+) -> TokenStream {
+    let write_expr = fields.iter().map(|field| {
+        let ty = &field.ty;
+        let ident = field_member(field, attrs);
+        quote! {
+            <#ty as fury_core::serializer::Serializer>::serialize_declared(&value.#ident, context);
+        }
+    });
+    let assign_stmt = fields.iter().map(|field| {
+        let ty = &field.ty;
+        let ident = field_member(field, attrs);
+        let name = field_name(field, attrs);
+        quote! {
+            #ident: <#ty as fury_core::serializer::Serializer>::deserialize_declared(context)
+                .map_err(|err| context.field_error(err, #name))?
+        }
+    });
+    let fill_skipped = read::fill_skipped(skipped, attrs);
+    let reserved_size_expr = fields.iter().map(|field| {
+        let ty = &field.ty;
+        quote! {
+            <#ty as fury_core::serializer::Serializer>::reserved_space() + fury_core::types::SIZE_OF_REF_AND_TYPE
+        }
+    });
+    let type_def_token_stream = misc::gen_in_struct_impl(fields, attrs);
+
+    quote! {
+        impl fury_core::serializer::RemoteSerializer for #name {
+            type Target = #remote;
+
+            fn write(value: &#remote, context: &mut fury_core::resolver::context::WriteContext) {
+                fury_core::serializer::write_struct_fields(context, |context| {
+                    #(#write_expr)*
+                });
+            }
+
+            fn read(context: &mut fury_core::resolver::context::ReadContext) -> Result<#remote, fury_core::error::Error> {
+                fury_core::serializer::read_struct_fields(context, |context| {
+                    Ok(#remote {
+                        #(#assign_stmt,)*
+                        #(#fill_skipped,)*
+                    })
+                })
+            }
+
+            fn reserved_space() -> usize {
+                0 #(+ #reserved_size_expr)*
+            }
+
+            #type_def_token_stream
+        }
+    }
+}
@@ -15,44 +15,156 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use crate::object::{derive_enum, misc, read, write};
-use crate::util::sorted_fields;
-use proc_macro::TokenStream;
+use crate::object::{derive_enum, misc, read, remote, write};
+use crate::util::{
+    check_field_identities, is_skipped, name_tuple_fields, parse_struct_attrs, sorted_fields,
+};
+use proc_macro2::TokenStream;
 use quote::quote;
+use syn::{parse_quote, Fields, GenericParam, Generics, Lifetime, LifetimeParam};
 
 pub fn derive_serializer(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
-    let (type_def_token_stream, write_token_stream, read_token_stream) = match &ast.data {
-        syn::Data::Struct(s) => {
-            let fields = sorted_fields(&s.fields);
-            (
-                misc::gen_in_struct_impl(&fields),
-                write::gen(&fields),
-                read::gen(&fields),
-            )
-        }
-        syn::Data::Enum(s) => (
-            derive_enum::gen_type_def(s),
-            derive_enum::gen_write(s),
-            derive_enum::gen_read(s),
-        ),
-        syn::Data::Union(_) => {
-            panic!("Union is not supported")
-        }
+    // `Cow<'a, str>` and `&'a str` fields borrow from the input, so one lifetime parameter is allowed.
+    // `TypeId` and registration need a `'static` type, which is `#name<'static, T>`.
+    let lifetimes: Vec<&LifetimeParam> = ast.generics.lifetimes().collect();
+    if lifetimes.len() > 1 {
+        return syn::Error::new_spanned(
+            &ast.generics,
+            "Fury only supports structs with at most one lifetime parameter",
+        )
+        .to_compile_error();
+    }
+    if let Some(param) = ast.generics.const_params().next() {
+        return syn::Error::new_spanned(param, "Fury doesn't support const parameters")
+            .to_compile_error();
+    }
+    let lifetime = lifetimes.first().map(|param| param.lifetime.clone());
+    let type_params: Vec<_> = ast
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect();
+    // each instantiation is a type of its own, registered with an id and hashed on its own
+    let generics = with_predicates(
+        &ast.generics,
+        type_params
+            .iter()
+            .map(|param| parse_quote! { #param: fury_core::serializer::Serializer + 'static }),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let static_args = lifetime
+        .as_ref()
+        .map(|_| quote! { 'static })
+        .into_iter()
+        .chain(type_params.iter().map(|param| quote! { #param }));
+    let static_ty = if ast.generics.params.is_empty() {
+        quote! { #name }
+    } else {
+        quote! { #name<#(#static_args),*> }
     };
+    let static_generics = with_predicates(
+        &generics,
+        lifetime
+            .iter()
+            .map(|lifetime| parse_quote! { #lifetime: 'static }),
+    );
+    let static_bound = &static_generics.where_clause;
+    let bf = lifetime.unwrap_or_else(|| Lifetime::new("'bf", proc_macro2::Span::call_site()));
+    let mut borrow_generics = with_predicates(
+        &generics,
+        type_params
+            .iter()
+            .map(|param| parse_quote! { #param: fury_core::serializer::BorrowDeserialize<#bf> }),
+    );
+    if lifetimes.is_empty() {
+        borrow_generics
+            .params
+            .insert(0, GenericParam::Lifetime(LifetimeParam::new(bf.clone())));
+    }
+    let (borrow_generics, _, borrow_bound) = borrow_generics.split_for_impl();
 
-    let misc_token_stream = misc::gen();
+    let (type_def_token_stream, write_token_stream, read_token_stream, borrow_token_stream) =
+        match &ast.data {
+            syn::Data::Struct(s) => {
+                let attrs = match parse_struct_attrs(ast) {
+                    Ok(attrs) => attrs,
+                    Err(err) => return err.to_compile_error(),
+                };
+                let tuple_fields;
+                let fields = match &s.fields {
+                    Fields::Unnamed(fields) => {
+                        tuple_fields = name_tuple_fields(fields);
+                        &tuple_fields
+                    }
+                    fields => fields,
+                };
+                // skipped fields aren't part of the message, only the read side fills them in
+                let (fields, skipped): (Vec<_>, Vec<_>) = sorted_fields(fields, &attrs)
+                    .into_iter()
+                    .partition(|field| !is_skipped(field));
+                if let Err(err) = check_field_identities(&fields, &attrs) {
+                    return err.to_compile_error();
+                }
+                if let Some(remote) = &attrs.remote {
+                    return remote::gen(name, &fields, &skipped, &attrs, remote);
+                }
+                let type_def = misc::gen_in_struct_impl(&fields, &attrs);
+                let struct_hash = misc::gen_struct_hash(&fields);
+                (
+                    quote! { #type_def #struct_hash },
+                    write::gen(&fields, &attrs, &static_ty),
+                    read::gen(&fields, &skipped, &attrs, &static_ty),
+                    read::gen_borrowed(&fields, &skipped, &attrs, &static_ty, &bf),
+                )
+            }
+            syn::Data::Enum(s) => (
+                derive_enum::gen_type_def(s),
+                derive_enum::gen_write(s, &static_ty),
+                derive_enum::gen_read(s, &static_ty),
+                derive_enum::gen_read_borrowed(&bf),
+            ),
+            syn::Data::Union(_) => {
+                return syn::Error::new_spanned(name, "Fury doesn't support unions")
+                    .to_compile_error()
+            }
+        };
 
-    let gen = quote! {
-        impl fury_core::serializer::StructSerializer for #name {
+    let misc_token_stream = misc::gen(&static_ty);
+    let layout_token_stream = match &ast.data {
+        syn::Data::Enum(s) => derive_enum::gen_layout(&generics, name, s),
+        _ => quote! {},
+    };
+
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Id this type is registered with in `fury`, `None` if it isn't registered.
+            pub fn fury_type_id(fury: &fury_core::fury::Fury) -> Option<u32> {
+                fury.get_class_resolver().get_registered_id(std::any::TypeId::of::<#static_ty>())
+            }
+        }
+        impl #impl_generics fury_core::serializer::StructSerializer for #name #ty_generics #static_bound {
             #type_def_token_stream
         }
-        impl fury_core::types::FuryGeneralList for #name {}
-        impl fury_core::serializer::Serializer for #name {
+        impl #impl_generics fury_core::types::FuryGeneralList for #name #ty_generics #where_clause {}
+        impl #impl_generics fury_core::serializer::Serializer for #name #ty_generics #where_clause {
             #misc_token_stream
             #write_token_stream
             #read_token_stream
         }
-    };
-    gen.into()
+        impl #borrow_generics fury_core::serializer::BorrowDeserialize<#bf> for #name #ty_generics #borrow_bound {
+            #borrow_token_stream
+        }
+        #layout_token_stream
+    }
+}
+
+// `generics` with `predicates` added to its where clause
+fn with_predicates(
+    generics: &Generics,
+    predicates: impl Iterator<Item = syn::WherePredicate>,
+) -> Generics {
+    let mut generics = generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
 }
@@ -15,19 +15,80 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, TokenStream};
 use quote::quote;
 use syn::Field;
 
-pub fn gen(fields: &[&Field]) -> TokenStream {
-    let accessor_expr = fields.iter().map(|field| {
-        let ty = &field.ty;
-        let ident = &field.ident;
+use crate::util::{field_member, is_option, parse_field_attrs, StructAttrs};
+
+// attribute errors are reported by the read side
+fn serialize_field(field: &Field, attrs: &StructAttrs) -> TokenStream {
+    let ty = &field.ty;
+    let ident = field_member(field, attrs);
+    if parse_field_attrs(field).is_ok_and(|attrs| attrs.delta) {
+        quote! {
+            fury_core::serializer::serialize_delta(&self.#ident, context);
+        }
+    } else {
         quote! {
-            <#ty as fury_core::serializer::Serializer>::serialize(&self.#ident, context);
+            <#ty as fury_core::serializer::Serializer>::serialize_declared(&self.#ident, context);
+        }
+    }
+}
+
+// Schema consistent layout of `#[fury(presence_bitmap)]` structs: one bit per `Option` field in
+// field order, then the fields, skipping the absent ones.
+fn write_with_presence_bitmap(fields: &[&Field], attrs: &StructAttrs) -> TokenStream {
+    let optional: Vec<&&Field> = fields.iter().filter(|field| is_option(&field.ty)).collect();
+    let bitmap_size = (optional.len() + 7) / 8;
+    let set_bits = optional.iter().enumerate().map(|(index, field)| {
+        let ident = field_member(field, attrs);
+        let (byte, bit) = (Literal::usize_suffixed(index / 8), (index % 8) as u8);
+        quote! {
+            if self.#ident.is_some() {
+                presence[#byte] |= 1 << #bit;
+            }
+        }
+    });
+    let accessor_expr = fields.iter().map(|field| {
+        let ident = field_member(field, attrs);
+        let serialize = serialize_field(field, attrs);
+        if is_option(&field.ty) {
+            quote! {
+                if self.#ident.is_some() {
+                    #serialize
+                }
+            }
+        } else {
+            serialize
         }
     });
 
+    quote! {
+        let mut presence = [0u8; #bitmap_size];
+        #(#set_bits)*
+        context.writer.bytes(&presence);
+        #(#accessor_expr)*
+    }
+}
+
+pub fn gen(fields: &[&Field], attrs: &StructAttrs, static_ty: &TokenStream) -> TokenStream {
+    let accessor_expr = fields.iter().map(|field| serialize_field(field, attrs));
+    let write_fields = if attrs.presence_bitmap {
+        let bitmap_token_stream = write_with_presence_bitmap(fields, attrs);
+        quote! {
+            if context.get_fury().get_mode() == &fury_core::types::Mode::SchemaConsistent {
+                #bitmap_token_stream
+            } else {
+                #(#accessor_expr)*
+            }
+        }
+    } else {
+        quote! {
+            #(#accessor_expr)*
+        }
+    };
+
     let reserved_size_expr = fields.iter().map(|field| {
         let ty = &field.ty;
         // each field have one byte ref tag and two byte type id
@@ -45,7 +106,7 @@ pub fn gen(fields: &[&Field]) -> TokenStream {
                 fury_core::types::Mode::Compatible => {
                     context.writer.i8(fury_core::types::RefFlag::NotNullValue as i8);
                     let meta_index = context.push_meta(
-                            std::any::TypeId::of::<Self>()
+                            std::any::TypeId::of::<#static_ty>()
                         ) as i16;
                     context.writer.i16(meta_index);
                     self.write(context);
@@ -54,13 +115,20 @@ pub fn gen(fields: &[&Field]) -> TokenStream {
         }
 
 
+        fn elides_type_id(fury: &fury_core::fury::Fury) -> bool {
+            fury_core::serializer::elides_declared_type(fury)
+        }
+
         fn write(&self, context: &mut fury_core::resolver::context::WriteContext) {
+            context.record_type(std::any::TypeId::of::<#static_ty>());
             // write fields
-            #(#accessor_expr)*
+            fury_core::serializer::write_struct_fields(context, |context| {
+                #write_fields
+            });
         }
 
         fn reserved_space() -> usize {
-            #(#reserved_size_expr)+*
+            0 #(+ #reserved_size_expr)*
         }
     }
 }
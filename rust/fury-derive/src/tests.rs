@@ -0,0 +1,70 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Expansion snapshots: every `tests/expand/*.rs` holds items deriving `Fury` and the
+//! `*.expanded.rs` next to it what they expand to. Run with `FURY_BLESS=1` to update them
+//! after a deliberate change of the generated code.
+
+use crate::object::derive_serializer;
+use quote::ToTokens;
+use std::fs;
+use std::path::Path;
+
+fn expand(source: &str) -> String {
+    let file: syn::File = syn::parse_str(source).expect("expand input should parse");
+    let mut expanded = proc_macro2::TokenStream::new();
+    for item in file.items {
+        let input: syn::DeriveInput = match syn::parse2(item.to_token_stream()) {
+            Ok(input) => input,
+            Err(_) => continue,
+        };
+        if input
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("derive"))
+        {
+            expanded.extend(derive_serializer(&input));
+        }
+    }
+    let expanded: syn::File = syn::parse2(expanded).expect("expansion should parse");
+    prettyplease::unparse(&expanded)
+}
+
+#[test]
+fn expansion_snapshots() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/expand");
+    let bless = std::env::var_os("FURY_BLESS").is_some();
+    let mut checked = 0;
+    for entry in fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_str().unwrap().to_string();
+        if !name.ends_with(".rs") || name.ends_with(".expanded.rs") {
+            continue;
+        }
+        let expanded = expand(&fs::read_to_string(&path).unwrap());
+        let snapshot = path.with_extension("expanded.rs");
+        if bless || !snapshot.exists() {
+            fs::write(&snapshot, &expanded).unwrap();
+        } else {
+            assert!(
+                fs::read_to_string(&snapshot).unwrap() == expanded,
+                "expansion of {name} changed, rerun with FURY_BLESS=1 if that's intended"
+            );
+        }
+        checked += 1;
+    }
+    assert!(checked > 0);
+}
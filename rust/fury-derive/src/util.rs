@@ -15,10 +15,304 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use syn::{Field, Fields};
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    bracketed, Data, DataEnum, DeriveInput, Expr, Field, Fields, FieldsUnnamed, Index, LitInt,
+    LitStr, Member, Path, Token, Type,
+};
 
-pub fn sorted_fields(fields: &Fields) -> Vec<&Field> {
+/// Fields ordered by the name they have in the message, see [`field_name`]. Those of tuple
+/// structs keep their order.
+pub fn sorted_fields<'a>(fields: &'a Fields, struct_attrs: &StructAttrs) -> Vec<&'a Field> {
     let mut fields = fields.iter().collect::<Vec<&Field>>();
-    fields.sort_by(|a, b| a.ident.cmp(&b.ident));
+    if !struct_attrs.tuple {
+        fields.sort_by_cached_key(|field| field_name(field, struct_attrs));
+    }
     fields
 }
+
+/// Fields of a tuple struct named `_0`, `_1`... after their index, a name Java fields could
+/// have too, see [`field_member`].
+pub fn name_tuple_fields(fields: &FieldsUnnamed) -> Fields {
+    let mut fields = fields.clone();
+    for (index, field) in fields.unnamed.iter_mut().enumerate() {
+        field.ident = Some(Ident::new(&format!("_{index}"), field.ty.span()));
+    }
+    Fields::Unnamed(fields)
+}
+
+/// How the generated code gets at the field, `self.name` or `self.0` in a tuple struct.
+pub fn field_member(field: &Field, struct_attrs: &StructAttrs) -> Member {
+    let ident = field.ident.clone().expect("should be field name");
+    if struct_attrs.tuple {
+        let index = ident.to_string()[1..]
+            .parse::<u32>()
+            .expect("should be named after its index");
+        Member::Unnamed(Index {
+            index,
+            span: ident.span(),
+        })
+    } else {
+        Member::Named(ident)
+    }
+}
+
+/// Name of the field in the message, its `#[fury(rename = "...")]` if it has one, else its
+/// Rust name, in camelCase with `#[fury(java_naming)]` on the struct.
+pub fn field_name(field: &Field, struct_attrs: &StructAttrs) -> String {
+    if let Ok(FieldAttrs {
+        rename: Some(name), ..
+    }) = parse_field_attrs(field)
+    {
+        return name;
+    }
+    let name = field
+        .ident
+        .as_ref()
+        .expect("should be field name")
+        .to_string();
+    if struct_attrs.java_naming {
+        to_camel_case(&name)
+    } else {
+        name
+    }
+}
+
+/// `user_name` as `userName`, leading underscores are kept.
+fn to_camel_case(name: &str) -> String {
+    let trimmed = name.trim_start_matches('_');
+    let mut camel = name[..name.len() - trimmed.len()].to_string();
+    for (index, word) in trimmed
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .enumerate()
+    {
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) if index > 0 => camel.extend(first.to_uppercase().chain(chars)),
+            _ => camel.push_str(word),
+        }
+    }
+    camel
+}
+
+/// Fails on two fields with the same name in the message or the same tag id.
+pub fn check_field_identities(fields: &[&Field], struct_attrs: &StructAttrs) -> syn::Result<()> {
+    for (index, field) in fields.iter().enumerate() {
+        let attrs = parse_field_attrs(field)?;
+        let name = field_name(field, struct_attrs);
+        for other in &fields[..index] {
+            if field_name(other, struct_attrs) == name {
+                return Err(syn::Error::new_spanned(
+                    &field.ident,
+                    format!("another field is named `{name}` in the message"),
+                ));
+            }
+            if attrs.id.is_some() && parse_field_attrs(other)?.id == attrs.id {
+                return Err(syn::Error::new_spanned(
+                    &field.ident,
+                    "another field has the same fury id",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_path_to(ty: &Type, ident: &str) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == ident),
+        _ => false,
+    }
+}
+
+pub fn is_option(ty: &Type) -> bool {
+    is_path_to(ty, "Option")
+}
+
+pub fn is_vec(ty: &Type) -> bool {
+    is_path_to(ty, "Vec")
+}
+
+fn mentions_static(tokens: TokenStream) -> bool {
+    let mut after_quote = false;
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Group(group) => mentions_static(group.stream()),
+        TokenTree::Ident(ident) if after_quote => ident == "static",
+        TokenTree::Punct(punct) => {
+            after_quote = punct.as_char() == '\'';
+            false
+        }
+        _ => {
+            after_quote = false;
+            false
+        }
+    })
+}
+
+/// Whether the type names the `'static` lifetime anywhere, e.g. `&'static str` or
+/// `Cow<'static, [T]>`.
+pub fn is_static(ty: &Type) -> bool {
+    mentions_static(ty.to_token_stream())
+}
+
+/// Struct level options, e.g. `#[fury(presence_bitmap)]`.
+#[derive(Default)]
+pub struct StructAttrs {
+    /// Write one bit per `Option` field up front instead of a null flag for each absent one.
+    pub presence_bitmap: bool,
+    /// Foreign type mirrored by the struct, `#[fury(remote = "other_crate::Type")]`.
+    pub remote: Option<Path>,
+    /// Fields without a rename are named in camelCase in the message, like the fields of the
+    /// Java class, `#[fury(java_naming)]`.
+    pub java_naming: bool,
+    /// Set for tuple structs rather than by an attribute, their fields are named by
+    /// [`name_tuple_fields`].
+    pub tuple: bool,
+}
+
+pub fn parse_struct_attrs(ast: &DeriveInput) -> syn::Result<StructAttrs> {
+    let mut attrs = StructAttrs {
+        tuple: matches!(&ast.data, Data::Struct(s) if matches!(s.fields, Fields::Unnamed(_))),
+        ..Default::default()
+    };
+    for attr in ast.attrs.iter().filter(|attr| attr.path().is_ident("fury")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("presence_bitmap") {
+                attrs.presence_bitmap = true;
+                Ok(())
+            } else if meta.path.is_ident("java_naming") {
+                attrs.java_naming = true;
+                Ok(())
+            } else if meta.path.is_ident("remote") {
+                attrs.remote = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported fury attribute"))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+/// Field level options, e.g. `#[fury(allowed = [A, B])]`.
+#[derive(Default)]
+pub struct FieldAttrs {
+    /// Registered types a `Box<dyn Any>` or `Vec<Box<dyn Any>>` field accepts when read.
+    pub allowed: Option<Vec<Type>>,
+    /// Integer or timestamp vector written as deltas, `#[fury(encode = "delta")]`. Needs the
+    /// `unstable-format` feature of fury-core.
+    pub delta: bool,
+    /// `UnknownTypePolicy` variant the `Box<dyn Any>` values of the field are read with,
+    /// `#[fury(unknown_types = "skip")]`.
+    pub unknown_types: Option<Ident>,
+    /// Left out of the message and filled with the default when read, `#[fury(skip)]`.
+    pub skip: bool,
+    /// Value of a skipped field, or of one the peer's message doesn't have,
+    /// `#[fury(default = "Vec::with_capacity(16)")]`. `Default::default()` if not given.
+    pub default: Option<Expr>,
+    /// Name of the field in the message, e.g. that of its Java counterpart,
+    /// `#[fury(rename = "userName")]`.
+    pub rename: Option<String>,
+    /// Tag id identifying the field in compatible mode instead of its name, `#[fury(id = 3)]`.
+    pub id: Option<u32>,
+}
+
+pub fn parse_field_attrs(field: &Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("fury"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("allowed") {
+                let value = meta.value()?;
+                let content;
+                bracketed!(content in value);
+                let types = Punctuated::<Type, Token![,]>::parse_terminated(&content)?;
+                attrs.allowed = Some(types.into_iter().collect());
+                Ok(())
+            } else if meta.path.is_ident("encode") {
+                let encoding = meta.value()?.parse::<LitStr>()?;
+                if encoding.value() != "delta" {
+                    return Err(syn::Error::new_spanned(
+                        encoding,
+                        "unsupported fury encoding, expected \"delta\"",
+                    ));
+                }
+                attrs.delta = true;
+                Ok(())
+            } else if meta.path.is_ident("unknown_types") {
+                let policy = meta.value()?.parse::<LitStr>()?;
+                let variant = match policy.value().as_str() {
+                    "error" => "Error",
+                    "skip" => "SkipAsNone",
+                    "capture" => "CaptureOpaque",
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            policy,
+                            "unsupported unknown types policy, expected \"error\", \"skip\" or \"capture\"",
+                        ))
+                    }
+                };
+                attrs.unknown_types = Some(Ident::new(variant, Span::call_site()));
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                attrs.skip = true;
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                attrs.default = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("rename") {
+                attrs.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else if meta.path.is_ident("id") {
+                attrs.id = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported fury attribute"))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+/// Whether the field is `#[fury(skip)]`, attribute errors are reported by the read side.
+pub fn is_skipped(field: &Field) -> bool {
+    parse_field_attrs(field).is_ok_and(|attrs| attrs.skip)
+}
+
+/// Index of the `#[fury(other)]` variant of an enum, which unknown variants are read as.
+pub fn parse_other_variant(data_enum: &DataEnum) -> syn::Result<Option<usize>> {
+    let mut other = None;
+    for (tag, variant) in data_enum.variants.iter().enumerate() {
+        for attr in variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("fury"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if !meta.path.is_ident("other") {
+                    return Err(meta.error("unsupported fury attribute"));
+                }
+                if !matches!(variant.fields, Fields::Unit) {
+                    return Err(meta.error("`#[fury(other)]` must be on a variant without fields"));
+                }
+                if other.is_some() {
+                    return Err(meta.error("only one variant can be `#[fury(other)]`"));
+                }
+                other = Some(tag);
+                Ok(())
+            })?;
+        }
+    }
+    Ok(other)
+}
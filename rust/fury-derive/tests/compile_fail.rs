@@ -0,0 +1,90 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Cases in `tests/compile-fail` must fail to compile with the errors in the `.stderr` next to
+//! them, run with `TRYBUILD=overwrite` to update those. The errors of cases in
+//! `tests/compile-fail-primary` list the types implementing a trait, which changes with rustc
+//! and the impls in `fury-core`, so only their first error is checked.
+
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn compile_fail() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/compile-fail/*.rs");
+}
+
+// the first error `cargo check` reports for the case `name`, with the location that follows it
+fn primary_error(name: &str) -> String {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let dir = root.join("../target/tests/compile-fail-primary").join(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    let manifest = format!(
+        r#"[package]
+name = "{name}"
+version = "0.0.0"
+edition = "2021"
+publish = false
+
+[[bin]]
+name = "{name}"
+path = "{case}"
+
+[dependencies]
+fury-core = {{ path = "{root}/../fury-core" }}
+fury-derive = {{ path = "{root}" }}
+
+[workspace]
+"#,
+        case = root
+            .join(format!("tests/compile-fail-primary/{name}.rs"))
+            .display(),
+        root = root.display(),
+    );
+    std::fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+    // the versions the workspace builds with, which are at hand offline
+    std::fs::copy(root.join("../Cargo.lock"), dir.join("Cargo.lock")).unwrap();
+    let output = Command::new(env!("CARGO"))
+        .args(["check", "--quiet", "--offline", "--color=never"])
+        .current_dir(&dir)
+        // shared with trybuild, which builds the same dependencies
+        .env("CARGO_TARGET_DIR", root.join("../target/tests/trybuild"))
+        .output()
+        .unwrap();
+    assert!(!output.status.success(), "`{name}` compiled");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let start = stderr.find("error").expect("no error reported");
+    stderr[start..]
+        .lines()
+        .take(2)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn field_without_fury() {
+    let error = primary_error("field_without_fury");
+    assert_eq!(
+        error.lines().next().unwrap(),
+        "error[E0277]: the trait bound `Item: Serializer` is not satisfied"
+    );
+    assert!(
+        error.ends_with("tests/compile-fail-primary/field_without_fury.rs:27:11"),
+        "{error}"
+    );
+}
@@ -0,0 +1,238 @@
+impl Status {
+    /// Id this type is registered with in `fury`, `None` if it isn't registered.
+    pub fn fury_type_id(fury: &fury_core::fury::Fury) -> Option<u32> {
+        fury.get_class_resolver().get_registered_id(std::any::TypeId::of::<Status>())
+    }
+}
+impl fury_core::serializer::StructSerializer for Status {
+    fn type_def(fury: &fury_core::fury::Fury) -> Vec<u8> {
+        fury_core::meta::TypeMeta::from_fields(
+                0,
+                vec![
+                    fury_core::meta::FieldInfo::new("Pending", 0i16),
+                    fury_core::meta::FieldInfo::new("Shipped", 1i16)
+                ],
+            )
+            .to_bytes()
+            .unwrap()
+    }
+}
+impl fury_core::types::FuryGeneralList for Status {}
+impl fury_core::serializer::Serializer for Status {
+    fn get_type_id(fury: &fury_core::fury::Fury) -> i16 {
+        match fury
+            .get_class_resolver()
+            .get_registered_id(std::any::TypeId::of::<Status>())
+        {
+            Some(id) => id as i16,
+            None => {
+                panic!(
+                    "`{}` isn't registered with this Fury", std::any::type_name:: <
+                    Status > ()
+                )
+            }
+        }
+    }
+    fn missing_serializer(fury: &fury_core::fury::Fury) -> Option<&'static str> {
+        fury.get_class_resolver()
+            .get_registered_id(std::any::TypeId::of::<Status>())
+            .is_none()
+            .then(std::any::type_name::<Status>)
+    }
+    fn write(&self, context: &mut fury_core::resolver::context::WriteContext) {
+        context.record_type(std::any::TypeId::of::<Status>());
+        match self {
+            Self::Pending => {
+                context.writer.var_int32(0i32);
+                fury_core::serializer::write_variant_fields(context, |_| {});
+            }
+            Self::Shipped => {
+                context.writer.var_int32(1i32);
+                fury_core::serializer::write_variant_fields(context, |_| {});
+            }
+        }
+    }
+    fn reserved_space() -> usize {
+        4
+    }
+    fn read(
+        context: &mut fury_core::resolver::context::ReadContext,
+    ) -> Result<Self, fury_core::error::Error> {
+        context.record_type(std::any::TypeId::of::<Status>());
+        context
+            .nested(|context| match context.reader.var_int32()? {
+                0i32 => {
+                    fury_core::serializer::read_variant_fields(
+                        context,
+                        |_| Ok(Self::Pending),
+                    )
+                }
+                1i32 => {
+                    fury_core::serializer::read_variant_fields(
+                        context,
+                        |_| Ok(Self::Shipped),
+                    )
+                }
+                tag => Err(fury_core::serializer::unknown_variant::<Self>(tag)),
+            })
+    }
+}
+impl<'bf> fury_core::serializer::BorrowDeserialize<'bf> for Status {
+    fn read_borrowed(
+        context: &mut fury_core::resolver::context::ReadContext<'_, 'bf>,
+    ) -> Result<Self, fury_core::error::Error> {
+        <Self as fury_core::serializer::Serializer>::read(context)
+    }
+    fn deserialize_borrowed(
+        context: &mut fury_core::resolver::context::ReadContext<'_, 'bf>,
+    ) -> Result<Self, fury_core::error::Error> {
+        <Self as fury_core::serializer::Serializer>::deserialize(context)
+    }
+}
+impl fury_core::types::EnumLayout for Status {
+    const VARIANTS: &'static [&'static str] = &["Pending", "Shipped"];
+}
+impl Shape {
+    /// Id this type is registered with in `fury`, `None` if it isn't registered.
+    pub fn fury_type_id(fury: &fury_core::fury::Fury) -> Option<u32> {
+        fury.get_class_resolver().get_registered_id(std::any::TypeId::of::<Shape>())
+    }
+}
+impl fury_core::serializer::StructSerializer for Shape {
+    fn type_def(fury: &fury_core::fury::Fury) -> Vec<u8> {
+        fury_core::meta::TypeMeta::from_fields(
+                0,
+                vec![
+                    fury_core::meta::FieldInfo::new("Circle", 0i16),
+                    fury_core::meta::FieldInfo::new("Rect", 1i16),
+                    fury_core::meta::FieldInfo::new("Unknown", 2i16)
+                ],
+            )
+            .to_bytes()
+            .unwrap()
+    }
+}
+impl fury_core::types::FuryGeneralList for Shape {}
+impl fury_core::serializer::Serializer for Shape {
+    fn get_type_id(fury: &fury_core::fury::Fury) -> i16 {
+        match fury
+            .get_class_resolver()
+            .get_registered_id(std::any::TypeId::of::<Shape>())
+        {
+            Some(id) => id as i16,
+            None => {
+                panic!(
+                    "`{}` isn't registered with this Fury", std::any::type_name:: < Shape
+                    > ()
+                )
+            }
+        }
+    }
+    fn missing_serializer(fury: &fury_core::fury::Fury) -> Option<&'static str> {
+        fury.get_class_resolver()
+            .get_registered_id(std::any::TypeId::of::<Shape>())
+            .is_none()
+            .then(std::any::type_name::<Shape>)
+    }
+    fn write(&self, context: &mut fury_core::resolver::context::WriteContext) {
+        context.record_type(std::any::TypeId::of::<Shape>());
+        match self {
+            Self::Circle { r: field_0 } => {
+                context.writer.var_int32(0i32);
+                fury_core::serializer::write_variant_fields(
+                    context,
+                    |context| {
+                        <f64 as fury_core::serializer::Serializer>::serialize_declared(
+                            field_0,
+                            context,
+                        );
+                    },
+                );
+            }
+            Self::Rect(field_0, field_1) => {
+                context.writer.var_int32(1i32);
+                fury_core::serializer::write_variant_fields(
+                    context,
+                    |context| {
+                        <f64 as fury_core::serializer::Serializer>::serialize_declared(
+                            field_0,
+                            context,
+                        );
+                        <f64 as fury_core::serializer::Serializer>::serialize_declared(
+                            field_1,
+                            context,
+                        );
+                    },
+                );
+            }
+            Self::Unknown => {
+                context.writer.var_int32(2i32);
+                fury_core::serializer::write_variant_fields(context, |_| {});
+            }
+        }
+    }
+    fn reserved_space() -> usize {
+        4
+    }
+    fn read(
+        context: &mut fury_core::resolver::context::ReadContext,
+    ) -> Result<Self, fury_core::error::Error> {
+        context.record_type(std::any::TypeId::of::<Shape>());
+        context
+            .nested(|context| match context.reader.var_int32()? {
+                0i32 => {
+                    fury_core::serializer::read_variant_fields(
+                        context,
+                        |context| Ok(Self::Circle {
+                            r: <f64 as fury_core::serializer::Serializer>::deserialize_declared(
+                                    context,
+                                )
+                                .map_err(|err| context.field_error(err, "Circle.r"))?,
+                        }),
+                    )
+                }
+                1i32 => {
+                    fury_core::serializer::read_variant_fields(
+                        context,
+                        |context| Ok(
+                            Self::Rect(
+                                <f64 as fury_core::serializer::Serializer>::deserialize_declared(
+                                        context,
+                                    )
+                                    .map_err(|err| context.field_error(err, "Rect.0"))?,
+                                <f64 as fury_core::serializer::Serializer>::deserialize_declared(
+                                        context,
+                                    )
+                                    .map_err(|err| context.field_error(err, "Rect.1"))?,
+                            ),
+                        ),
+                    )
+                }
+                2i32 => {
+                    fury_core::serializer::read_variant_fields(
+                        context,
+                        |_| Ok(Self::Unknown),
+                    )
+                }
+                tag => {
+                    fury_core::serializer::skip_variant::<Self>(context, tag)?;
+                    Ok(Self::Unknown)
+                }
+            })
+    }
+}
+impl<'bf> fury_core::serializer::BorrowDeserialize<'bf> for Shape {
+    fn read_borrowed(
+        context: &mut fury_core::resolver::context::ReadContext<'_, 'bf>,
+    ) -> Result<Self, fury_core::error::Error> {
+        <Self as fury_core::serializer::Serializer>::read(context)
+    }
+    fn deserialize_borrowed(
+        context: &mut fury_core::resolver::context::ReadContext<'_, 'bf>,
+    ) -> Result<Self, fury_core::error::Error> {
+        <Self as fury_core::serializer::Serializer>::deserialize(context)
+    }
+}
+impl fury_core::types::EnumLayout for Shape {
+    const VARIANTS: &'static [&'static str] = &["Circle", "Rect", "Unknown"];
+}
@@ -0,0 +1,384 @@
+impl<'a, T> Wrapper<'a, T>
+where
+    T: fury_core::serializer::Serializer + 'static,
+{
+    /// Id this type is registered with in `fury`, `None` if it isn't registered.
+    pub fn fury_type_id(fury: &fury_core::fury::Fury) -> Option<u32> {
+        fury.get_class_resolver()
+            .get_registered_id(std::any::TypeId::of::<Wrapper<'static, T>>())
+    }
+}
+impl<'a, T> fury_core::serializer::StructSerializer for Wrapper<'a, T>
+where
+    T: fury_core::serializer::Serializer + 'static,
+    'a: 'static,
+{
+    fn type_def(fury: &fury_core::fury::Fury) -> Vec<u8> {
+        fury_core::serializer::field_type_must_derive_fury::<T>();
+        fury_core::serializer::field_type_must_derive_fury::<Cow<'a, str>>();
+        fury_core::meta::TypeMeta::from_fields(
+                0,
+                vec![
+                    fury_core::meta::FieldInfo::new("inner", < T as
+                    fury_core::serializer::Serializer > ::get_type_id(fury)),
+                    fury_core::meta::FieldInfo::new("name", < Cow < 'a, str > as
+                    fury_core::serializer::Serializer > ::get_type_id(fury))
+                ],
+            )
+            .to_bytes()
+            .unwrap()
+    }
+    fn field_types(
+        fury: &fury_core::fury::Fury,
+    ) -> Vec<fury_core::type_def::FieldTypeDef> {
+        vec![
+            fury_core::type_def::FieldTypeDef::of:: < T > (fury),
+            fury_core::type_def::FieldTypeDef::of:: < Cow < 'a, str > > (fury)
+        ]
+    }
+    fn struct_hash(fury: &fury_core::fury::Fury) -> u32 {
+        fury_core::meta::struct_hash(
+            &[
+                <T as fury_core::serializer::Serializer>::get_type_id(fury),
+                <Cow<'a, str> as fury_core::serializer::Serializer>::get_type_id(fury),
+            ],
+        )
+    }
+}
+impl<'a, T> fury_core::types::FuryGeneralList for Wrapper<'a, T>
+where
+    T: fury_core::serializer::Serializer + 'static,
+{}
+impl<'a, T> fury_core::serializer::Serializer for Wrapper<'a, T>
+where
+    T: fury_core::serializer::Serializer + 'static,
+{
+    fn get_type_id(fury: &fury_core::fury::Fury) -> i16 {
+        match fury
+            .get_class_resolver()
+            .get_registered_id(std::any::TypeId::of::<Wrapper<'static, T>>())
+        {
+            Some(id) => id as i16,
+            None => {
+                panic!(
+                    "`{}` isn't registered with this Fury", std::any::type_name:: <
+                    Wrapper < 'static, T > > ()
+                )
+            }
+        }
+    }
+    fn missing_serializer(fury: &fury_core::fury::Fury) -> Option<&'static str> {
+        fury.get_class_resolver()
+            .get_registered_id(std::any::TypeId::of::<Wrapper<'static, T>>())
+            .is_none()
+            .then(std::any::type_name::<Wrapper<'static, T>>)
+    }
+    fn serialize(&self, context: &mut fury_core::resolver::context::WriteContext) {
+        match context.get_fury().get_mode() {
+            fury_core::types::Mode::SchemaConsistent => {
+                fury_core::serializer::serialize(self, context);
+            }
+            fury_core::types::Mode::Compatible => {
+                context.writer.i8(fury_core::types::RefFlag::NotNullValue as i8);
+                let meta_index = context
+                    .push_meta(std::any::TypeId::of::<Wrapper<'static, T>>()) as i16;
+                context.writer.i16(meta_index);
+                self.write(context);
+            }
+        }
+    }
+    fn elides_type_id(fury: &fury_core::fury::Fury) -> bool {
+        fury_core::serializer::elides_declared_type(fury)
+    }
+    fn write(&self, context: &mut fury_core::resolver::context::WriteContext) {
+        context.record_type(std::any::TypeId::of::<Wrapper<'static, T>>());
+        fury_core::serializer::write_struct_fields(
+            context,
+            |context| {
+                <T as fury_core::serializer::Serializer>::serialize_declared(
+                    &self.inner,
+                    context,
+                );
+                <Cow<
+                    'a,
+                    str,
+                > as fury_core::serializer::Serializer>::serialize_declared(
+                    &self.name,
+                    context,
+                );
+            },
+        );
+    }
+    fn reserved_space() -> usize {
+        0 + <T as fury_core::serializer::Serializer>::reserved_space()
+            + fury_core::types::SIZE_OF_REF_AND_TYPE
+            + <Cow<'a, str> as fury_core::serializer::Serializer>::reserved_space()
+            + fury_core::types::SIZE_OF_REF_AND_TYPE
+    }
+    fn deserialize(
+        context: &mut fury_core::resolver::context::ReadContext,
+    ) -> Result<Self, fury_core::error::Error> {
+        match context.get_fury().get_mode() {
+            fury_core::types::Mode::SchemaConsistent => {
+                fury_core::serializer::deserialize::<Self>(context)
+            }
+            fury_core::types::Mode::Compatible => {
+                let ref_flag = context.reader.i8()?;
+                if ref_flag == (fury_core::types::RefFlag::NotNullValue as i8)
+                    || ref_flag == (fury_core::types::RefFlag::RefValue as i8)
+                {
+                    if ref_flag == (fury_core::types::RefFlag::RefValue as i8) {
+                        context.ref_reader.reserve_ref_id()?;
+                    }
+                    let meta_index = context.reader.i16()? as usize;
+                    let meta = context.get_meta(meta_index)?.clone();
+                    context.record_struct();
+                    context.record_type(std::any::TypeId::of::<Wrapper<'static, T>>());
+                    context
+                        .nested(|context| {
+                            let fields = meta.get_field_info();
+                            #[allow(non_snake_case)]
+                            let mut _inner: Option<T> = None;
+                            #[allow(non_snake_case)]
+                            let mut _name: Option<Cow<'a, str>> = None;
+                            for field_info in fields.iter() {
+                                match (
+                                    field_info.get_tag_id(),
+                                    field_info.get_field_name(),
+                                ) {
+                                    (None, "inner") => {
+                                        _inner = Some(
+                                            fury_core::serializer::check_field_serializer::<
+                                                Self,
+                                                T,
+                                            >(context.get_fury(), "inner")
+                                                .and_then(|_| <T as fury_core::serializer::Serializer>::deserialize_declared(
+                                                    context,
+                                                ))
+                                                .map_err(|err| context.field_error(err, "inner"))?,
+                                        );
+                                    }
+                                    (None, "name") => {
+                                        _name = Some(
+                                            fury_core::serializer::check_field_serializer::<
+                                                Self,
+                                                Cow<'a, str>,
+                                            >(context.get_fury(), "name")
+                                                .and_then(|_| <Cow<
+                                                    'a,
+                                                    str,
+                                                > as fury_core::serializer::Serializer>::deserialize_declared(
+                                                    context,
+                                                ))
+                                                .map_err(|err| context.field_error(err, "name"))?,
+                                        );
+                                    }
+                                    _ => {
+                                        context
+                                            .skip_unknown_field(
+                                                std::any::type_name::<Self>(),
+                                                field_info,
+                                            )?;
+                                    }
+                                }
+                            }
+                            Ok(Self {
+                                inner: _inner
+                                    .ok_or_else(|| {
+                                        fury_core::error::AnyhowError::msg(
+                                            format!(
+                                                "Field `inner` of `{}` is missing from the message",
+                                                std::any::type_name:: < Self > ()
+                                            ),
+                                        )
+                                    })?,
+                                name: _name
+                                    .ok_or_else(|| {
+                                        fury_core::error::AnyhowError::msg(
+                                            format!(
+                                                "Field `name` of `{}` is missing from the message",
+                                                std::any::type_name:: < Self > ()
+                                            ),
+                                        )
+                                    })?,
+                            })
+                        })
+                } else if ref_flag == (fury_core::types::RefFlag::Null as i8) {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Try to deserialize non-option type to null",
+                        ),
+                    )?
+                } else if ref_flag == (fury_core::types::RefFlag::Ref as i8) {
+                    context.ref_reader.read_ref_id(&mut context.reader)?;
+                    Err(fury_core::error::Error::Ref)
+                } else {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Unknown ref flag, value:{ref_flag}",
+                        ),
+                    )?
+                }
+            }
+        }
+    }
+    fn read(
+        context: &mut fury_core::resolver::context::ReadContext,
+    ) -> Result<Self, fury_core::error::Error> {
+        context.record_type(std::any::TypeId::of::<Wrapper<'static, T>>());
+        fury_core::serializer::read_struct_fields(
+            context,
+            |context| {
+                Ok(Self {
+                    inner: fury_core::serializer::check_field_serializer::<
+                        Self,
+                        T,
+                    >(context.get_fury(), "inner")
+                        .and_then(|_| <T as fury_core::serializer::Serializer>::deserialize_declared(
+                            context,
+                        ))
+                        .map_err(|err| context.field_error(err, "inner"))?,
+                    name: fury_core::serializer::check_field_serializer::<
+                        Self,
+                        Cow<'a, str>,
+                    >(context.get_fury(), "name")
+                        .and_then(|_| <Cow<
+                            'a,
+                            str,
+                        > as fury_core::serializer::Serializer>::deserialize_declared(
+                            context,
+                        ))
+                        .map_err(|err| context.field_error(err, "name"))?,
+                })
+            },
+        )
+    }
+}
+impl<'a, T> fury_core::serializer::BorrowDeserialize<'a> for Wrapper<'a, T>
+where
+    T: fury_core::serializer::Serializer + 'static,
+    T: fury_core::serializer::BorrowDeserialize<'a>,
+{
+    fn deserialize_borrowed(
+        context: &mut fury_core::resolver::context::ReadContext<'_, 'a>,
+    ) -> Result<Self, fury_core::error::Error> {
+        match context.get_fury().get_mode() {
+            fury_core::types::Mode::SchemaConsistent => {
+                fury_core::serializer::deserialize_borrowed::<Self>(context)
+            }
+            fury_core::types::Mode::Compatible => {
+                let ref_flag = context.reader.i8()?;
+                if ref_flag == (fury_core::types::RefFlag::NotNullValue as i8)
+                    || ref_flag == (fury_core::types::RefFlag::RefValue as i8)
+                {
+                    if ref_flag == (fury_core::types::RefFlag::RefValue as i8) {
+                        context.ref_reader.reserve_ref_id()?;
+                    }
+                    let meta_index = context.reader.i16()? as usize;
+                    let meta = context.get_meta(meta_index)?.clone();
+                    context.record_struct();
+                    context.record_type(std::any::TypeId::of::<Wrapper<'static, T>>());
+                    context
+                        .nested(|context| {
+                            let fields = meta.get_field_info();
+                            #[allow(non_snake_case)]
+                            let mut _inner: Option<T> = None;
+                            #[allow(non_snake_case)]
+                            let mut _name: Option<Cow<'a, str>> = None;
+                            for field_info in fields.iter() {
+                                match (
+                                    field_info.get_tag_id(),
+                                    field_info.get_field_name(),
+                                ) {
+                                    (None, "inner") => {
+                                        _inner = Some(
+                                            <T as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                                                    context,
+                                                )
+                                                .map_err(|err| context.field_error(err, "inner"))?,
+                                        );
+                                    }
+                                    (None, "name") => {
+                                        _name = Some(
+                                            <Cow<
+                                                'a,
+                                                str,
+                                            > as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                                                    context,
+                                                )
+                                                .map_err(|err| context.field_error(err, "name"))?,
+                                        );
+                                    }
+                                    _ => {
+                                        context
+                                            .skip_unknown_field(
+                                                std::any::type_name::<Self>(),
+                                                field_info,
+                                            )?;
+                                    }
+                                }
+                            }
+                            Ok(Self {
+                                inner: _inner
+                                    .ok_or_else(|| {
+                                        fury_core::error::AnyhowError::msg(
+                                            format!(
+                                                "Field `inner` of `{}` is missing from the message",
+                                                std::any::type_name:: < Self > ()
+                                            ),
+                                        )
+                                    })?,
+                                name: _name
+                                    .ok_or_else(|| {
+                                        fury_core::error::AnyhowError::msg(
+                                            format!(
+                                                "Field `name` of `{}` is missing from the message",
+                                                std::any::type_name:: < Self > ()
+                                            ),
+                                        )
+                                    })?,
+                            })
+                        })
+                } else if ref_flag == (fury_core::types::RefFlag::Null as i8) {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Try to deserialize non-option type to null",
+                        ),
+                    )?
+                } else if ref_flag == (fury_core::types::RefFlag::Ref as i8) {
+                    context.ref_reader.read_ref_id(&mut context.reader)?;
+                    Err(fury_core::error::Error::Ref)
+                } else {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Unknown ref flag, value:{ref_flag}",
+                        ),
+                    )?
+                }
+            }
+        }
+    }
+    fn read_borrowed(
+        context: &mut fury_core::resolver::context::ReadContext<'_, 'a>,
+    ) -> Result<Self, fury_core::error::Error> {
+        context.record_type(std::any::TypeId::of::<Wrapper<'static, T>>());
+        fury_core::serializer::read_struct_fields(
+            context,
+            |context| {
+                Ok(Self {
+                    inner: <T as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                            context,
+                        )
+                        .map_err(|err| context.field_error(err, "inner"))?,
+                    name: <Cow<
+                        'a,
+                        str,
+                    > as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                            context,
+                        )
+                        .map_err(|err| context.field_error(err, "name"))?,
+                })
+            },
+        )
+    }
+}
@@ -0,0 +1,432 @@
+impl Sparse {
+    /// Id this type is registered with in `fury`, `None` if it isn't registered.
+    pub fn fury_type_id(fury: &fury_core::fury::Fury) -> Option<u32> {
+        fury.get_class_resolver().get_registered_id(std::any::TypeId::of::<Sparse>())
+    }
+}
+impl fury_core::serializer::StructSerializer for Sparse {
+    fn type_def(fury: &fury_core::fury::Fury) -> Vec<u8> {
+        fury_core::serializer::field_type_must_derive_fury::<Option<i32>>();
+        fury_core::serializer::field_type_must_derive_fury::<Option<String>>();
+        fury_core::meta::TypeMeta::from_fields(
+                0,
+                vec![
+                    fury_core::meta::FieldInfo::new("a", < Option < i32 > as
+                    fury_core::serializer::Serializer > ::get_type_id(fury)),
+                    fury_core::meta::FieldInfo::new("b", < Option < String > as
+                    fury_core::serializer::Serializer > ::get_type_id(fury))
+                ],
+            )
+            .to_bytes()
+            .unwrap()
+    }
+    fn field_types(
+        fury: &fury_core::fury::Fury,
+    ) -> Vec<fury_core::type_def::FieldTypeDef> {
+        vec![
+            fury_core::type_def::FieldTypeDef::of:: < Option < i32 > > (fury),
+            fury_core::type_def::FieldTypeDef::of:: < Option < String > > (fury)
+        ]
+    }
+    fn struct_hash(fury: &fury_core::fury::Fury) -> u32 {
+        fury_core::meta::struct_hash(
+            &[
+                <Option<i32> as fury_core::serializer::Serializer>::get_type_id(fury),
+                <Option<String> as fury_core::serializer::Serializer>::get_type_id(fury),
+            ],
+        )
+    }
+}
+impl fury_core::types::FuryGeneralList for Sparse {}
+impl fury_core::serializer::Serializer for Sparse {
+    fn get_type_id(fury: &fury_core::fury::Fury) -> i16 {
+        match fury
+            .get_class_resolver()
+            .get_registered_id(std::any::TypeId::of::<Sparse>())
+        {
+            Some(id) => id as i16,
+            None => {
+                panic!(
+                    "`{}` isn't registered with this Fury", std::any::type_name:: <
+                    Sparse > ()
+                )
+            }
+        }
+    }
+    fn missing_serializer(fury: &fury_core::fury::Fury) -> Option<&'static str> {
+        fury.get_class_resolver()
+            .get_registered_id(std::any::TypeId::of::<Sparse>())
+            .is_none()
+            .then(std::any::type_name::<Sparse>)
+    }
+    fn serialize(&self, context: &mut fury_core::resolver::context::WriteContext) {
+        match context.get_fury().get_mode() {
+            fury_core::types::Mode::SchemaConsistent => {
+                fury_core::serializer::serialize(self, context);
+            }
+            fury_core::types::Mode::Compatible => {
+                context.writer.i8(fury_core::types::RefFlag::NotNullValue as i8);
+                let meta_index = context.push_meta(std::any::TypeId::of::<Sparse>())
+                    as i16;
+                context.writer.i16(meta_index);
+                self.write(context);
+            }
+        }
+    }
+    fn elides_type_id(fury: &fury_core::fury::Fury) -> bool {
+        fury_core::serializer::elides_declared_type(fury)
+    }
+    fn write(&self, context: &mut fury_core::resolver::context::WriteContext) {
+        context.record_type(std::any::TypeId::of::<Sparse>());
+        fury_core::serializer::write_struct_fields(
+            context,
+            |context| {
+                if context.get_fury().get_mode()
+                    == &fury_core::types::Mode::SchemaConsistent
+                {
+                    let mut presence = [0u8; 1usize];
+                    if self.a.is_some() {
+                        presence[0usize] |= 1 << 0u8;
+                    }
+                    if self.b.is_some() {
+                        presence[0usize] |= 1 << 1u8;
+                    }
+                    context.writer.bytes(&presence);
+                    if self.a.is_some() {
+                        <Option<
+                            i32,
+                        > as fury_core::serializer::Serializer>::serialize_declared(
+                            &self.a,
+                            context,
+                        );
+                    }
+                    if self.b.is_some() {
+                        <Option<
+                            String,
+                        > as fury_core::serializer::Serializer>::serialize_declared(
+                            &self.b,
+                            context,
+                        );
+                    }
+                } else {
+                    <Option<
+                        i32,
+                    > as fury_core::serializer::Serializer>::serialize_declared(
+                        &self.a,
+                        context,
+                    );
+                    <Option<
+                        String,
+                    > as fury_core::serializer::Serializer>::serialize_declared(
+                        &self.b,
+                        context,
+                    );
+                }
+            },
+        );
+    }
+    fn reserved_space() -> usize {
+        0 + <Option<i32> as fury_core::serializer::Serializer>::reserved_space()
+            + fury_core::types::SIZE_OF_REF_AND_TYPE
+            + <Option<String> as fury_core::serializer::Serializer>::reserved_space()
+            + fury_core::types::SIZE_OF_REF_AND_TYPE
+    }
+    fn deserialize(
+        context: &mut fury_core::resolver::context::ReadContext,
+    ) -> Result<Self, fury_core::error::Error> {
+        match context.get_fury().get_mode() {
+            fury_core::types::Mode::SchemaConsistent => {
+                fury_core::serializer::deserialize::<Self>(context)
+            }
+            fury_core::types::Mode::Compatible => {
+                let ref_flag = context.reader.i8()?;
+                if ref_flag == (fury_core::types::RefFlag::NotNullValue as i8)
+                    || ref_flag == (fury_core::types::RefFlag::RefValue as i8)
+                {
+                    if ref_flag == (fury_core::types::RefFlag::RefValue as i8) {
+                        context.ref_reader.reserve_ref_id()?;
+                    }
+                    let meta_index = context.reader.i16()? as usize;
+                    let meta = context.get_meta(meta_index)?.clone();
+                    context.record_struct();
+                    context.record_type(std::any::TypeId::of::<Sparse>());
+                    context
+                        .nested(|context| {
+                            let fields = meta.get_field_info();
+                            #[allow(non_snake_case)]
+                            let mut _a: Option<Option<i32>> = None;
+                            #[allow(non_snake_case)]
+                            let mut _b: Option<Option<String>> = None;
+                            for field_info in fields.iter() {
+                                match (
+                                    field_info.get_tag_id(),
+                                    field_info.get_field_name(),
+                                ) {
+                                    (None, "a") => {
+                                        _a = Some(
+                                            fury_core::serializer::check_field_serializer::<
+                                                Self,
+                                                Option<i32>,
+                                            >(context.get_fury(), "a")
+                                                .and_then(|_| <Option<
+                                                    i32,
+                                                > as fury_core::serializer::Serializer>::deserialize_declared(
+                                                    context,
+                                                ))
+                                                .map_err(|err| context.field_error(err, "a"))?,
+                                        );
+                                    }
+                                    (None, "b") => {
+                                        _b = Some(
+                                            fury_core::serializer::check_field_serializer::<
+                                                Self,
+                                                Option<String>,
+                                            >(context.get_fury(), "b")
+                                                .and_then(|_| <Option<
+                                                    String,
+                                                > as fury_core::serializer::Serializer>::deserialize_declared(
+                                                    context,
+                                                ))
+                                                .map_err(|err| context.field_error(err, "b"))?,
+                                        );
+                                    }
+                                    _ => {
+                                        context
+                                            .skip_unknown_field(
+                                                std::any::type_name::<Self>(),
+                                                field_info,
+                                            )?;
+                                    }
+                                }
+                            }
+                            Ok(Self {
+                                a: _a.unwrap_or_default(),
+                                b: _b.unwrap_or_default(),
+                            })
+                        })
+                } else if ref_flag == (fury_core::types::RefFlag::Null as i8) {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Try to deserialize non-option type to null",
+                        ),
+                    )?
+                } else if ref_flag == (fury_core::types::RefFlag::Ref as i8) {
+                    context.ref_reader.read_ref_id(&mut context.reader)?;
+                    Err(fury_core::error::Error::Ref)
+                } else {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Unknown ref flag, value:{ref_flag}",
+                        ),
+                    )?
+                }
+            }
+        }
+    }
+    fn read(
+        context: &mut fury_core::resolver::context::ReadContext,
+    ) -> Result<Self, fury_core::error::Error> {
+        context.record_type(std::any::TypeId::of::<Sparse>());
+        fury_core::serializer::read_struct_fields(
+            context,
+            |context| {
+                if context.get_fury().get_mode()
+                    == &fury_core::types::Mode::SchemaConsistent
+                {
+                    let presence = context.reader.bytes(1usize)?;
+                    Ok(Self {
+                        a: if presence[0usize] & (1 << 0u8) != 0 {
+                            fury_core::serializer::check_field_serializer::<
+                                Self,
+                                Option<i32>,
+                            >(context.get_fury(), "a")
+                                .and_then(|_| <Option<
+                                    i32,
+                                > as fury_core::serializer::Serializer>::deserialize_declared(
+                                    context,
+                                ))
+                                .map_err(|err| context.field_error(err, "a"))?
+                        } else {
+                            None
+                        },
+                        b: if presence[0usize] & (1 << 1u8) != 0 {
+                            fury_core::serializer::check_field_serializer::<
+                                Self,
+                                Option<String>,
+                            >(context.get_fury(), "b")
+                                .and_then(|_| <Option<
+                                    String,
+                                > as fury_core::serializer::Serializer>::deserialize_declared(
+                                    context,
+                                ))
+                                .map_err(|err| context.field_error(err, "b"))?
+                        } else {
+                            None
+                        },
+                    })
+                } else {
+                    Ok(Self {
+                        a: fury_core::serializer::check_field_serializer::<
+                            Self,
+                            Option<i32>,
+                        >(context.get_fury(), "a")
+                            .and_then(|_| <Option<
+                                i32,
+                            > as fury_core::serializer::Serializer>::deserialize_declared(
+                                context,
+                            ))
+                            .map_err(|err| context.field_error(err, "a"))?,
+                        b: fury_core::serializer::check_field_serializer::<
+                            Self,
+                            Option<String>,
+                        >(context.get_fury(), "b")
+                            .and_then(|_| <Option<
+                                String,
+                            > as fury_core::serializer::Serializer>::deserialize_declared(
+                                context,
+                            ))
+                            .map_err(|err| context.field_error(err, "b"))?,
+                    })
+                }
+            },
+        )
+    }
+}
+impl<'bf> fury_core::serializer::BorrowDeserialize<'bf> for Sparse {
+    fn deserialize_borrowed(
+        context: &mut fury_core::resolver::context::ReadContext<'_, 'bf>,
+    ) -> Result<Self, fury_core::error::Error> {
+        match context.get_fury().get_mode() {
+            fury_core::types::Mode::SchemaConsistent => {
+                fury_core::serializer::deserialize_borrowed::<Self>(context)
+            }
+            fury_core::types::Mode::Compatible => {
+                let ref_flag = context.reader.i8()?;
+                if ref_flag == (fury_core::types::RefFlag::NotNullValue as i8)
+                    || ref_flag == (fury_core::types::RefFlag::RefValue as i8)
+                {
+                    if ref_flag == (fury_core::types::RefFlag::RefValue as i8) {
+                        context.ref_reader.reserve_ref_id()?;
+                    }
+                    let meta_index = context.reader.i16()? as usize;
+                    let meta = context.get_meta(meta_index)?.clone();
+                    context.record_struct();
+                    context.record_type(std::any::TypeId::of::<Sparse>());
+                    context
+                        .nested(|context| {
+                            let fields = meta.get_field_info();
+                            #[allow(non_snake_case)]
+                            let mut _a: Option<Option<i32>> = None;
+                            #[allow(non_snake_case)]
+                            let mut _b: Option<Option<String>> = None;
+                            for field_info in fields.iter() {
+                                match (
+                                    field_info.get_tag_id(),
+                                    field_info.get_field_name(),
+                                ) {
+                                    (None, "a") => {
+                                        _a = Some(
+                                            <Option<
+                                                i32,
+                                            > as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                                                    context,
+                                                )
+                                                .map_err(|err| context.field_error(err, "a"))?,
+                                        );
+                                    }
+                                    (None, "b") => {
+                                        _b = Some(
+                                            <Option<
+                                                String,
+                                            > as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                                                    context,
+                                                )
+                                                .map_err(|err| context.field_error(err, "b"))?,
+                                        );
+                                    }
+                                    _ => {
+                                        context
+                                            .skip_unknown_field(
+                                                std::any::type_name::<Self>(),
+                                                field_info,
+                                            )?;
+                                    }
+                                }
+                            }
+                            Ok(Self {
+                                a: _a.unwrap_or_default(),
+                                b: _b.unwrap_or_default(),
+                            })
+                        })
+                } else if ref_flag == (fury_core::types::RefFlag::Null as i8) {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Try to deserialize non-option type to null",
+                        ),
+                    )?
+                } else if ref_flag == (fury_core::types::RefFlag::Ref as i8) {
+                    context.ref_reader.read_ref_id(&mut context.reader)?;
+                    Err(fury_core::error::Error::Ref)
+                } else {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Unknown ref flag, value:{ref_flag}",
+                        ),
+                    )?
+                }
+            }
+        }
+    }
+    fn read_borrowed(
+        context: &mut fury_core::resolver::context::ReadContext<'_, 'bf>,
+    ) -> Result<Self, fury_core::error::Error> {
+        context.record_type(std::any::TypeId::of::<Sparse>());
+        fury_core::serializer::read_struct_fields(
+            context,
+            |context| {
+                if context.get_fury().get_mode()
+                    == &fury_core::types::Mode::SchemaConsistent
+                {
+                    let presence = context.reader.bytes(1usize)?;
+                    Ok(Self {
+                        a: if presence[0usize] & (1 << 0u8) != 0 {
+                            <Option<
+                                i32,
+                            > as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                                    context,
+                                )
+                                .map_err(|err| context.field_error(err, "a"))?
+                        } else {
+                            None
+                        },
+                        b: if presence[0usize] & (1 << 1u8) != 0 {
+                            <Option<
+                                String,
+                            > as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                                    context,
+                                )
+                                .map_err(|err| context.field_error(err, "b"))?
+                        } else {
+                            None
+                        },
+                    })
+                } else {
+                    Ok(Self {
+                        a: <Option<
+                            i32,
+                        > as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                                context,
+                            )
+                            .map_err(|err| context.field_error(err, "a"))?,
+                        b: <Option<
+                            String,
+                        > as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                                context,
+                            )
+                            .map_err(|err| context.field_error(err, "b"))?,
+                    })
+                }
+            },
+        )
+    }
+}
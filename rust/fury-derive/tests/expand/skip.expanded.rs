@@ -0,0 +1,343 @@
+impl Session {
+    /// Id this type is registered with in `fury`, `None` if it isn't registered.
+    pub fn fury_type_id(fury: &fury_core::fury::Fury) -> Option<u32> {
+        fury.get_class_resolver().get_registered_id(std::any::TypeId::of::<Session>())
+    }
+}
+impl fury_core::serializer::StructSerializer for Session {
+    fn type_def(fury: &fury_core::fury::Fury) -> Vec<u8> {
+        fury_core::serializer::field_type_must_derive_fury::<u32>();
+        fury_core::serializer::field_type_must_derive_fury::<String>();
+        fury_core::meta::TypeMeta::from_fields(
+                0,
+                vec![
+                    fury_core::meta::FieldInfo::new("timeout", < u32 as
+                    fury_core::serializer::Serializer > ::get_type_id(fury)),
+                    fury_core::meta::FieldInfo::new("user", < String as
+                    fury_core::serializer::Serializer > ::get_type_id(fury))
+                ],
+            )
+            .to_bytes()
+            .unwrap()
+    }
+    fn field_types(
+        fury: &fury_core::fury::Fury,
+    ) -> Vec<fury_core::type_def::FieldTypeDef> {
+        vec![
+            fury_core::type_def::FieldTypeDef::of:: < u32 > (fury),
+            fury_core::type_def::FieldTypeDef::of:: < String > (fury)
+        ]
+    }
+    fn struct_hash(fury: &fury_core::fury::Fury) -> u32 {
+        fury_core::meta::struct_hash(
+            &[
+                <u32 as fury_core::serializer::Serializer>::get_type_id(fury),
+                <String as fury_core::serializer::Serializer>::get_type_id(fury),
+            ],
+        )
+    }
+}
+impl fury_core::types::FuryGeneralList for Session {}
+impl fury_core::serializer::Serializer for Session {
+    fn get_type_id(fury: &fury_core::fury::Fury) -> i16 {
+        match fury
+            .get_class_resolver()
+            .get_registered_id(std::any::TypeId::of::<Session>())
+        {
+            Some(id) => id as i16,
+            None => {
+                panic!(
+                    "`{}` isn't registered with this Fury", std::any::type_name:: <
+                    Session > ()
+                )
+            }
+        }
+    }
+    fn missing_serializer(fury: &fury_core::fury::Fury) -> Option<&'static str> {
+        fury.get_class_resolver()
+            .get_registered_id(std::any::TypeId::of::<Session>())
+            .is_none()
+            .then(std::any::type_name::<Session>)
+    }
+    fn serialize(&self, context: &mut fury_core::resolver::context::WriteContext) {
+        match context.get_fury().get_mode() {
+            fury_core::types::Mode::SchemaConsistent => {
+                fury_core::serializer::serialize(self, context);
+            }
+            fury_core::types::Mode::Compatible => {
+                context.writer.i8(fury_core::types::RefFlag::NotNullValue as i8);
+                let meta_index = context.push_meta(std::any::TypeId::of::<Session>())
+                    as i16;
+                context.writer.i16(meta_index);
+                self.write(context);
+            }
+        }
+    }
+    fn elides_type_id(fury: &fury_core::fury::Fury) -> bool {
+        fury_core::serializer::elides_declared_type(fury)
+    }
+    fn write(&self, context: &mut fury_core::resolver::context::WriteContext) {
+        context.record_type(std::any::TypeId::of::<Session>());
+        fury_core::serializer::write_struct_fields(
+            context,
+            |context| {
+                <u32 as fury_core::serializer::Serializer>::serialize_declared(
+                    &self.timeout,
+                    context,
+                );
+                <String as fury_core::serializer::Serializer>::serialize_declared(
+                    &self.user,
+                    context,
+                );
+            },
+        );
+    }
+    fn reserved_space() -> usize {
+        0 + <u32 as fury_core::serializer::Serializer>::reserved_space()
+            + fury_core::types::SIZE_OF_REF_AND_TYPE
+            + <String as fury_core::serializer::Serializer>::reserved_space()
+            + fury_core::types::SIZE_OF_REF_AND_TYPE
+    }
+    fn deserialize(
+        context: &mut fury_core::resolver::context::ReadContext,
+    ) -> Result<Self, fury_core::error::Error> {
+        match context.get_fury().get_mode() {
+            fury_core::types::Mode::SchemaConsistent => {
+                fury_core::serializer::deserialize::<Self>(context)
+            }
+            fury_core::types::Mode::Compatible => {
+                let ref_flag = context.reader.i8()?;
+                if ref_flag == (fury_core::types::RefFlag::NotNullValue as i8)
+                    || ref_flag == (fury_core::types::RefFlag::RefValue as i8)
+                {
+                    if ref_flag == (fury_core::types::RefFlag::RefValue as i8) {
+                        context.ref_reader.reserve_ref_id()?;
+                    }
+                    let meta_index = context.reader.i16()? as usize;
+                    let meta = context.get_meta(meta_index)?.clone();
+                    context.record_struct();
+                    context.record_type(std::any::TypeId::of::<Session>());
+                    context
+                        .nested(|context| {
+                            let fields = meta.get_field_info();
+                            #[allow(non_snake_case)]
+                            let mut _timeout: Option<u32> = None;
+                            #[allow(non_snake_case)]
+                            let mut _user: Option<String> = None;
+                            for field_info in fields.iter() {
+                                match (
+                                    field_info.get_tag_id(),
+                                    field_info.get_field_name(),
+                                ) {
+                                    (None, "timeout") => {
+                                        _timeout = Some(
+                                            fury_core::serializer::check_field_serializer::<
+                                                Self,
+                                                u32,
+                                            >(context.get_fury(), "timeout")
+                                                .and_then(|_| <u32 as fury_core::serializer::Serializer>::deserialize_declared(
+                                                    context,
+                                                ))
+                                                .map_err(|err| context.field_error(err, "timeout"))?,
+                                        );
+                                    }
+                                    (None, "user") => {
+                                        _user = Some(
+                                            fury_core::serializer::check_field_serializer::<
+                                                Self,
+                                                String,
+                                            >(context.get_fury(), "user")
+                                                .and_then(|_| <String as fury_core::serializer::Serializer>::deserialize_declared(
+                                                    context,
+                                                ))
+                                                .map_err(|err| context.field_error(err, "user"))?,
+                                        );
+                                    }
+                                    _ => {
+                                        context
+                                            .skip_unknown_field(
+                                                std::any::type_name::<Self>(),
+                                                field_info,
+                                            )?;
+                                    }
+                                }
+                            }
+                            Ok(Self {
+                                timeout: _timeout.unwrap_or_else(|| 30),
+                                user: _user
+                                    .ok_or_else(|| {
+                                        fury_core::error::AnyhowError::msg(
+                                            format!(
+                                                "Field `user` of `{}` is missing from the message",
+                                                std::any::type_name:: < Self > ()
+                                            ),
+                                        )
+                                    })?,
+                                cache: Default::default(),
+                                opened: Instant::now(),
+                            })
+                        })
+                } else if ref_flag == (fury_core::types::RefFlag::Null as i8) {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Try to deserialize non-option type to null",
+                        ),
+                    )?
+                } else if ref_flag == (fury_core::types::RefFlag::Ref as i8) {
+                    context.ref_reader.read_ref_id(&mut context.reader)?;
+                    Err(fury_core::error::Error::Ref)
+                } else {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Unknown ref flag, value:{ref_flag}",
+                        ),
+                    )?
+                }
+            }
+        }
+    }
+    fn read(
+        context: &mut fury_core::resolver::context::ReadContext,
+    ) -> Result<Self, fury_core::error::Error> {
+        context.record_type(std::any::TypeId::of::<Session>());
+        fury_core::serializer::read_struct_fields(
+            context,
+            |context| {
+                Ok(Self {
+                    timeout: fury_core::serializer::check_field_serializer::<
+                        Self,
+                        u32,
+                    >(context.get_fury(), "timeout")
+                        .and_then(|_| <u32 as fury_core::serializer::Serializer>::deserialize_declared(
+                            context,
+                        ))
+                        .map_err(|err| context.field_error(err, "timeout"))?,
+                    user: fury_core::serializer::check_field_serializer::<
+                        Self,
+                        String,
+                    >(context.get_fury(), "user")
+                        .and_then(|_| <String as fury_core::serializer::Serializer>::deserialize_declared(
+                            context,
+                        ))
+                        .map_err(|err| context.field_error(err, "user"))?,
+                    cache: Default::default(),
+                    opened: Instant::now(),
+                })
+            },
+        )
+    }
+}
+impl<'bf> fury_core::serializer::BorrowDeserialize<'bf> for Session {
+    fn deserialize_borrowed(
+        context: &mut fury_core::resolver::context::ReadContext<'_, 'bf>,
+    ) -> Result<Self, fury_core::error::Error> {
+        match context.get_fury().get_mode() {
+            fury_core::types::Mode::SchemaConsistent => {
+                fury_core::serializer::deserialize_borrowed::<Self>(context)
+            }
+            fury_core::types::Mode::Compatible => {
+                let ref_flag = context.reader.i8()?;
+                if ref_flag == (fury_core::types::RefFlag::NotNullValue as i8)
+                    || ref_flag == (fury_core::types::RefFlag::RefValue as i8)
+                {
+                    if ref_flag == (fury_core::types::RefFlag::RefValue as i8) {
+                        context.ref_reader.reserve_ref_id()?;
+                    }
+                    let meta_index = context.reader.i16()? as usize;
+                    let meta = context.get_meta(meta_index)?.clone();
+                    context.record_struct();
+                    context.record_type(std::any::TypeId::of::<Session>());
+                    context
+                        .nested(|context| {
+                            let fields = meta.get_field_info();
+                            #[allow(non_snake_case)]
+                            let mut _timeout: Option<u32> = None;
+                            #[allow(non_snake_case)]
+                            let mut _user: Option<String> = None;
+                            for field_info in fields.iter() {
+                                match (
+                                    field_info.get_tag_id(),
+                                    field_info.get_field_name(),
+                                ) {
+                                    (None, "timeout") => {
+                                        _timeout = Some(
+                                            <u32 as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                                                    context,
+                                                )
+                                                .map_err(|err| context.field_error(err, "timeout"))?,
+                                        );
+                                    }
+                                    (None, "user") => {
+                                        _user = Some(
+                                            <String as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                                                    context,
+                                                )
+                                                .map_err(|err| context.field_error(err, "user"))?,
+                                        );
+                                    }
+                                    _ => {
+                                        context
+                                            .skip_unknown_field(
+                                                std::any::type_name::<Self>(),
+                                                field_info,
+                                            )?;
+                                    }
+                                }
+                            }
+                            Ok(Self {
+                                timeout: _timeout.unwrap_or_else(|| 30),
+                                user: _user
+                                    .ok_or_else(|| {
+                                        fury_core::error::AnyhowError::msg(
+                                            format!(
+                                                "Field `user` of `{}` is missing from the message",
+                                                std::any::type_name:: < Self > ()
+                                            ),
+                                        )
+                                    })?,
+                                cache: Default::default(),
+                                opened: Instant::now(),
+                            })
+                        })
+                } else if ref_flag == (fury_core::types::RefFlag::Null as i8) {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Try to deserialize non-option type to null",
+                        ),
+                    )?
+                } else if ref_flag == (fury_core::types::RefFlag::Ref as i8) {
+                    context.ref_reader.read_ref_id(&mut context.reader)?;
+                    Err(fury_core::error::Error::Ref)
+                } else {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Unknown ref flag, value:{ref_flag}",
+                        ),
+                    )?
+                }
+            }
+        }
+    }
+    fn read_borrowed(
+        context: &mut fury_core::resolver::context::ReadContext<'_, 'bf>,
+    ) -> Result<Self, fury_core::error::Error> {
+        context.record_type(std::any::TypeId::of::<Session>());
+        fury_core::serializer::read_struct_fields(
+            context,
+            |context| {
+                Ok(Self {
+                    timeout: <u32 as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                            context,
+                        )
+                        .map_err(|err| context.field_error(err, "timeout"))?,
+                    user: <String as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                            context,
+                        )
+                        .map_err(|err| context.field_error(err, "user"))?,
+                    cache: Default::default(),
+                    opened: Instant::now(),
+                })
+            },
+        )
+    }
+}
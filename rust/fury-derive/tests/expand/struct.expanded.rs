@@ -0,0 +1,420 @@
+impl Order {
+    /// Id this type is registered with in `fury`, `None` if it isn't registered.
+    pub fn fury_type_id(fury: &fury_core::fury::Fury) -> Option<u32> {
+        fury.get_class_resolver().get_registered_id(std::any::TypeId::of::<Order>())
+    }
+}
+impl fury_core::serializer::StructSerializer for Order {
+    fn type_def(fury: &fury_core::fury::Fury) -> Vec<u8> {
+        fury_core::serializer::field_type_must_derive_fury::<i64>();
+        fury_core::serializer::field_type_must_derive_fury::<Vec<String>>();
+        fury_core::serializer::field_type_must_derive_fury::<Option<String>>();
+        fury_core::meta::TypeMeta::from_fields(
+                0,
+                vec![
+                    fury_core::meta::FieldInfo::new("id", < i64 as
+                    fury_core::serializer::Serializer > ::get_type_id(fury)),
+                    fury_core::meta::FieldInfo::new("items", < Vec < String > as
+                    fury_core::serializer::Serializer > ::get_type_id(fury)),
+                    fury_core::meta::FieldInfo::new("note", < Option < String > as
+                    fury_core::serializer::Serializer > ::get_type_id(fury))
+                ],
+            )
+            .to_bytes()
+            .unwrap()
+    }
+    fn field_types(
+        fury: &fury_core::fury::Fury,
+    ) -> Vec<fury_core::type_def::FieldTypeDef> {
+        vec![
+            fury_core::type_def::FieldTypeDef::of:: < i64 > (fury),
+            fury_core::type_def::FieldTypeDef::of:: < Vec < String > > (fury),
+            fury_core::type_def::FieldTypeDef::of:: < Option < String > > (fury)
+        ]
+    }
+    fn struct_hash(fury: &fury_core::fury::Fury) -> u32 {
+        fury_core::meta::struct_hash(
+            &[
+                <i64 as fury_core::serializer::Serializer>::get_type_id(fury),
+                <Vec<String> as fury_core::serializer::Serializer>::get_type_id(fury),
+                <Option<String> as fury_core::serializer::Serializer>::get_type_id(fury),
+            ],
+        )
+    }
+}
+impl fury_core::types::FuryGeneralList for Order {}
+impl fury_core::serializer::Serializer for Order {
+    fn get_type_id(fury: &fury_core::fury::Fury) -> i16 {
+        match fury
+            .get_class_resolver()
+            .get_registered_id(std::any::TypeId::of::<Order>())
+        {
+            Some(id) => id as i16,
+            None => {
+                panic!(
+                    "`{}` isn't registered with this Fury", std::any::type_name:: < Order
+                    > ()
+                )
+            }
+        }
+    }
+    fn missing_serializer(fury: &fury_core::fury::Fury) -> Option<&'static str> {
+        fury.get_class_resolver()
+            .get_registered_id(std::any::TypeId::of::<Order>())
+            .is_none()
+            .then(std::any::type_name::<Order>)
+    }
+    fn serialize(&self, context: &mut fury_core::resolver::context::WriteContext) {
+        match context.get_fury().get_mode() {
+            fury_core::types::Mode::SchemaConsistent => {
+                fury_core::serializer::serialize(self, context);
+            }
+            fury_core::types::Mode::Compatible => {
+                context.writer.i8(fury_core::types::RefFlag::NotNullValue as i8);
+                let meta_index = context.push_meta(std::any::TypeId::of::<Order>())
+                    as i16;
+                context.writer.i16(meta_index);
+                self.write(context);
+            }
+        }
+    }
+    fn elides_type_id(fury: &fury_core::fury::Fury) -> bool {
+        fury_core::serializer::elides_declared_type(fury)
+    }
+    fn write(&self, context: &mut fury_core::resolver::context::WriteContext) {
+        context.record_type(std::any::TypeId::of::<Order>());
+        fury_core::serializer::write_struct_fields(
+            context,
+            |context| {
+                <i64 as fury_core::serializer::Serializer>::serialize_declared(
+                    &self.id,
+                    context,
+                );
+                <Vec<
+                    String,
+                > as fury_core::serializer::Serializer>::serialize_declared(
+                    &self.items,
+                    context,
+                );
+                <Option<
+                    String,
+                > as fury_core::serializer::Serializer>::serialize_declared(
+                    &self.note,
+                    context,
+                );
+            },
+        );
+    }
+    fn reserved_space() -> usize {
+        0 + <i64 as fury_core::serializer::Serializer>::reserved_space()
+            + fury_core::types::SIZE_OF_REF_AND_TYPE
+            + <Vec<String> as fury_core::serializer::Serializer>::reserved_space()
+            + fury_core::types::SIZE_OF_REF_AND_TYPE
+            + <Option<String> as fury_core::serializer::Serializer>::reserved_space()
+            + fury_core::types::SIZE_OF_REF_AND_TYPE
+    }
+    fn deserialize(
+        context: &mut fury_core::resolver::context::ReadContext,
+    ) -> Result<Self, fury_core::error::Error> {
+        match context.get_fury().get_mode() {
+            fury_core::types::Mode::SchemaConsistent => {
+                fury_core::serializer::deserialize::<Self>(context)
+            }
+            fury_core::types::Mode::Compatible => {
+                let ref_flag = context.reader.i8()?;
+                if ref_flag == (fury_core::types::RefFlag::NotNullValue as i8)
+                    || ref_flag == (fury_core::types::RefFlag::RefValue as i8)
+                {
+                    if ref_flag == (fury_core::types::RefFlag::RefValue as i8) {
+                        context.ref_reader.reserve_ref_id()?;
+                    }
+                    let meta_index = context.reader.i16()? as usize;
+                    let meta = context.get_meta(meta_index)?.clone();
+                    context.record_struct();
+                    context.record_type(std::any::TypeId::of::<Order>());
+                    context
+                        .nested(|context| {
+                            let fields = meta.get_field_info();
+                            #[allow(non_snake_case)]
+                            let mut _id: Option<i64> = None;
+                            #[allow(non_snake_case)]
+                            let mut _items: Option<Vec<String>> = None;
+                            #[allow(non_snake_case)]
+                            let mut _note: Option<Option<String>> = None;
+                            for field_info in fields.iter() {
+                                match (
+                                    field_info.get_tag_id(),
+                                    field_info.get_field_name(),
+                                ) {
+                                    (None, "id") => {
+                                        _id = Some(
+                                            fury_core::serializer::check_field_serializer::<
+                                                Self,
+                                                i64,
+                                            >(context.get_fury(), "id")
+                                                .and_then(|_| <i64 as fury_core::serializer::Serializer>::deserialize_declared(
+                                                    context,
+                                                ))
+                                                .map_err(|err| context.field_error(err, "id"))?,
+                                        );
+                                    }
+                                    (None, "items") => {
+                                        _items = Some(
+                                            fury_core::serializer::check_field_serializer::<
+                                                Self,
+                                                Vec<String>,
+                                            >(context.get_fury(), "items")
+                                                .and_then(|_| <Vec<
+                                                    String,
+                                                > as fury_core::serializer::Serializer>::deserialize_declared(
+                                                    context,
+                                                ))
+                                                .map_err(|err| context.field_error(err, "items"))?,
+                                        );
+                                    }
+                                    (None, "note") => {
+                                        _note = Some(
+                                            fury_core::serializer::check_field_serializer::<
+                                                Self,
+                                                Option<String>,
+                                            >(context.get_fury(), "note")
+                                                .and_then(|_| <Option<
+                                                    String,
+                                                > as fury_core::serializer::Serializer>::deserialize_declared(
+                                                    context,
+                                                ))
+                                                .map_err(|err| context.field_error(err, "note"))?,
+                                        );
+                                    }
+                                    _ => {
+                                        context
+                                            .skip_unknown_field(
+                                                std::any::type_name::<Self>(),
+                                                field_info,
+                                            )?;
+                                    }
+                                }
+                            }
+                            Ok(Self {
+                                id: _id
+                                    .ok_or_else(|| {
+                                        fury_core::error::AnyhowError::msg(
+                                            format!(
+                                                "Field `id` of `{}` is missing from the message",
+                                                std::any::type_name:: < Self > ()
+                                            ),
+                                        )
+                                    })?,
+                                items: _items
+                                    .ok_or_else(|| {
+                                        fury_core::error::AnyhowError::msg(
+                                            format!(
+                                                "Field `items` of `{}` is missing from the message",
+                                                std::any::type_name:: < Self > ()
+                                            ),
+                                        )
+                                    })?,
+                                note: _note.unwrap_or_default(),
+                            })
+                        })
+                } else if ref_flag == (fury_core::types::RefFlag::Null as i8) {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Try to deserialize non-option type to null",
+                        ),
+                    )?
+                } else if ref_flag == (fury_core::types::RefFlag::Ref as i8) {
+                    context.ref_reader.read_ref_id(&mut context.reader)?;
+                    Err(fury_core::error::Error::Ref)
+                } else {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Unknown ref flag, value:{ref_flag}",
+                        ),
+                    )?
+                }
+            }
+        }
+    }
+    fn read(
+        context: &mut fury_core::resolver::context::ReadContext,
+    ) -> Result<Self, fury_core::error::Error> {
+        context.record_type(std::any::TypeId::of::<Order>());
+        fury_core::serializer::read_struct_fields(
+            context,
+            |context| {
+                Ok(Self {
+                    id: fury_core::serializer::check_field_serializer::<
+                        Self,
+                        i64,
+                    >(context.get_fury(), "id")
+                        .and_then(|_| <i64 as fury_core::serializer::Serializer>::deserialize_declared(
+                            context,
+                        ))
+                        .map_err(|err| context.field_error(err, "id"))?,
+                    items: fury_core::serializer::check_field_serializer::<
+                        Self,
+                        Vec<String>,
+                    >(context.get_fury(), "items")
+                        .and_then(|_| <Vec<
+                            String,
+                        > as fury_core::serializer::Serializer>::deserialize_declared(
+                            context,
+                        ))
+                        .map_err(|err| context.field_error(err, "items"))?,
+                    note: fury_core::serializer::check_field_serializer::<
+                        Self,
+                        Option<String>,
+                    >(context.get_fury(), "note")
+                        .and_then(|_| <Option<
+                            String,
+                        > as fury_core::serializer::Serializer>::deserialize_declared(
+                            context,
+                        ))
+                        .map_err(|err| context.field_error(err, "note"))?,
+                })
+            },
+        )
+    }
+}
+impl<'bf> fury_core::serializer::BorrowDeserialize<'bf> for Order {
+    fn deserialize_borrowed(
+        context: &mut fury_core::resolver::context::ReadContext<'_, 'bf>,
+    ) -> Result<Self, fury_core::error::Error> {
+        match context.get_fury().get_mode() {
+            fury_core::types::Mode::SchemaConsistent => {
+                fury_core::serializer::deserialize_borrowed::<Self>(context)
+            }
+            fury_core::types::Mode::Compatible => {
+                let ref_flag = context.reader.i8()?;
+                if ref_flag == (fury_core::types::RefFlag::NotNullValue as i8)
+                    || ref_flag == (fury_core::types::RefFlag::RefValue as i8)
+                {
+                    if ref_flag == (fury_core::types::RefFlag::RefValue as i8) {
+                        context.ref_reader.reserve_ref_id()?;
+                    }
+                    let meta_index = context.reader.i16()? as usize;
+                    let meta = context.get_meta(meta_index)?.clone();
+                    context.record_struct();
+                    context.record_type(std::any::TypeId::of::<Order>());
+                    context
+                        .nested(|context| {
+                            let fields = meta.get_field_info();
+                            #[allow(non_snake_case)]
+                            let mut _id: Option<i64> = None;
+                            #[allow(non_snake_case)]
+                            let mut _items: Option<Vec<String>> = None;
+                            #[allow(non_snake_case)]
+                            let mut _note: Option<Option<String>> = None;
+                            for field_info in fields.iter() {
+                                match (
+                                    field_info.get_tag_id(),
+                                    field_info.get_field_name(),
+                                ) {
+                                    (None, "id") => {
+                                        _id = Some(
+                                            <i64 as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                                                    context,
+                                                )
+                                                .map_err(|err| context.field_error(err, "id"))?,
+                                        );
+                                    }
+                                    (None, "items") => {
+                                        _items = Some(
+                                            <Vec<
+                                                String,
+                                            > as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                                                    context,
+                                                )
+                                                .map_err(|err| context.field_error(err, "items"))?,
+                                        );
+                                    }
+                                    (None, "note") => {
+                                        _note = Some(
+                                            <Option<
+                                                String,
+                                            > as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                                                    context,
+                                                )
+                                                .map_err(|err| context.field_error(err, "note"))?,
+                                        );
+                                    }
+                                    _ => {
+                                        context
+                                            .skip_unknown_field(
+                                                std::any::type_name::<Self>(),
+                                                field_info,
+                                            )?;
+                                    }
+                                }
+                            }
+                            Ok(Self {
+                                id: _id
+                                    .ok_or_else(|| {
+                                        fury_core::error::AnyhowError::msg(
+                                            format!(
+                                                "Field `id` of `{}` is missing from the message",
+                                                std::any::type_name:: < Self > ()
+                                            ),
+                                        )
+                                    })?,
+                                items: _items
+                                    .ok_or_else(|| {
+                                        fury_core::error::AnyhowError::msg(
+                                            format!(
+                                                "Field `items` of `{}` is missing from the message",
+                                                std::any::type_name:: < Self > ()
+                                            ),
+                                        )
+                                    })?,
+                                note: _note.unwrap_or_default(),
+                            })
+                        })
+                } else if ref_flag == (fury_core::types::RefFlag::Null as i8) {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Try to deserialize non-option type to null",
+                        ),
+                    )?
+                } else if ref_flag == (fury_core::types::RefFlag::Ref as i8) {
+                    context.ref_reader.read_ref_id(&mut context.reader)?;
+                    Err(fury_core::error::Error::Ref)
+                } else {
+                    Err(
+                        fury_core::error::AnyhowError::msg(
+                            "Unknown ref flag, value:{ref_flag}",
+                        ),
+                    )?
+                }
+            }
+        }
+    }
+    fn read_borrowed(
+        context: &mut fury_core::resolver::context::ReadContext<'_, 'bf>,
+    ) -> Result<Self, fury_core::error::Error> {
+        context.record_type(std::any::TypeId::of::<Order>());
+        fury_core::serializer::read_struct_fields(
+            context,
+            |context| {
+                Ok(Self {
+                    id: <i64 as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                            context,
+                        )
+                        .map_err(|err| context.field_error(err, "id"))?,
+                    items: <Vec<
+                        String,
+                    > as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                            context,
+                        )
+                        .map_err(|err| context.field_error(err, "items"))?,
+                    note: <Option<
+                        String,
+                    > as fury_core::serializer::BorrowDeserialize>::deserialize_declared_borrowed(
+                            context,
+                        )
+                        .map_err(|err| context.field_error(err, "note"))?,
+                })
+            },
+        )
+    }
+}
@@ -15,4 +15,60 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::sync::OnceLock;
+
 pub use fury_core::{error::Error, fury::Fury, row::from_row, row::to_row};
+pub use fury_core::{fury_assert_compatible, fury_assert_enum_layout, fury_trait_object};
+pub use fury_derive::{Fury, FuryRow};
+
+use fury_core::serializer::Serializer;
+
+#[cfg(feature = "transcode")]
+pub mod transcode;
+
+/// The primitive encodings fury messages are made of, for framing of your own, see
+/// [`fury_core::buffer`] for what is guaranteed.
+pub mod buffer {
+    pub use fury_core::buffer::{Reader, Writer};
+}
+
+/// Everything needed for the common cases, `use fury::prelude::*;`.
+pub mod prelude {
+    pub use fury_core::error::Error;
+    pub use fury_core::fury::Fury;
+    pub use fury_core::serializer::Serializer;
+    pub use fury_core::types::Mode;
+    pub use fury_derive::Fury;
+}
+
+static DEFAULT_FURY: OnceLock<Fury> = OnceLock::new();
+
+/// Installs the instance used by [`to_bytes`] and [`from_bytes`], e.g. one with the
+/// application's structs registered.
+///
+/// Fails once the default instance has been installed or used.
+pub fn set_default(fury: Fury) -> Result<(), Error> {
+    DEFAULT_FURY
+        .set(fury)
+        .map_err(|_| anyhow::anyhow!("The default Fury instance is already initialized").into())
+}
+
+/// The instance used by [`to_bytes`] and [`from_bytes`], `Fury::default()` unless
+/// [`set_default`] was called first.
+///
+/// Nothing is registered with the default one, the hooks of [`fury_core::manifest`] only take
+/// effect with the type ids of a manifest: install [`Manifest::to_fury`] with [`set_default`]
+/// to use them.
+///
+/// [`Manifest::to_fury`]: fury_core::manifest::Manifest::to_fury
+pub fn default_fury() -> &'static Fury {
+    DEFAULT_FURY.get_or_init(Fury::default)
+}
+
+pub fn to_bytes<T: Serializer>(value: &T) -> Vec<u8> {
+    default_fury().serialize(value)
+}
+
+pub fn from_bytes<T: Serializer>(bf: &[u8]) -> Result<T, Error> {
+    default_fury().deserialize(bf)
+}
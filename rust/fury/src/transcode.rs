@@ -0,0 +1,350 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! JSON and MessagePack views of fury messages, for tools which can't read fury.
+//!
+//! Messages are read as a [`Value`] with the types registered on the given [`Fury`], see
+//! [`Fury::deserialize_value`] for what can be read that way. The other way around, a message
+//! is written for the registered struct `type_id`, its field types decide how the JSON values
+//! are encoded. Elements of lists and maps only have JSON types to go by: integers are written
+//! as `i64`, which readers narrow to the declared type.
+
+use crate::{Error, Fury};
+use anyhow::anyhow;
+use chrono::{NaiveDate, NaiveDateTime};
+use fury_core::types::FieldType;
+use fury_core::value::{TypeLayout, Value};
+use serde_json::{Map, Number, Value as Json};
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+pub fn to_json(bf: &[u8], fury: &Fury) -> Result<String, Error> {
+    Ok(to_json_value(fury.deserialize_value(bf)?).to_string())
+}
+
+pub fn to_msgpack(bf: &[u8], fury: &Fury) -> Result<Vec<u8>, Error> {
+    let mut msgpack = Vec::new();
+    rmpv::encode::write_value(&mut msgpack, &to_msgpack_value(fury.deserialize_value(bf)?))
+        .map_err(|err| anyhow!("Failed to write MessagePack: {err}"))?;
+    Ok(msgpack)
+}
+
+/// Writes a JSON object as a message holding the registered struct `type_id`.
+pub fn from_json(json: &str, fury: &Fury, type_id: u32) -> Result<Vec<u8>, Error> {
+    let json: Json = serde_json::from_str(json).map_err(|err| anyhow!("Invalid JSON: {err}"))?;
+    fury.serialize_value(&from_json_value(&json, fury, type_id as i16)?)
+}
+
+/// Like [`from_json`], map keys must be strings.
+pub fn from_msgpack(msgpack: &[u8], fury: &Fury, type_id: u32) -> Result<Vec<u8>, Error> {
+    let value = rmpv::decode::read_value(&mut &msgpack[..])
+        .map_err(|err| anyhow!("Invalid MessagePack: {err}"))?;
+    let json = msgpack_to_json(value)?;
+    fury.serialize_value(&from_json_value(&json, fury, type_id as i16)?)
+}
+
+fn float(value: f64) -> Json {
+    Number::from_f64(value).map_or(Json::Null, Json::Number)
+}
+
+fn to_json_value(value: Value) -> Json {
+    let array = |items: Vec<Json>| Json::Array(items);
+    match value {
+        Value::Null | Value::Ignored { .. } => Json::Null,
+        Value::Bool(v) => Json::Bool(v),
+        Value::I8(v) => v.into(),
+        Value::U8(v) => v.into(),
+        Value::I16(v) => v.into(),
+        Value::U16(v) => v.into(),
+        Value::I32(v) => v.into(),
+        Value::U32(v) => v.into(),
+        Value::I64(v) => v.into(),
+        Value::U64(v) => v.into(),
+        Value::F32(v) => float(v as f64),
+        Value::F64(v) => float(v),
+        Value::String(v) => Json::String(v),
+        Value::Binary(v) => v.into(),
+        Value::Date(v) => Json::String(v.to_string()),
+        Value::Timestamp(v) => Json::String(v.format(TIMESTAMP_FORMAT).to_string()),
+        Value::BoolArray(v) => v.into(),
+        Value::I16Array(v) => v.into(),
+        Value::I32Array(v) => v.into(),
+        Value::I64Array(v) => v.into(),
+        Value::F32Array(v) => array(v.into_iter().map(|v| float(v as f64)).collect()),
+        Value::F64Array(v) => array(v.into_iter().map(float).collect()),
+        Value::List(v) | Value::Set(v) => array(v.into_iter().map(to_json_value).collect()),
+        Value::Map(entries) => {
+            if entries
+                .iter()
+                .all(|(key, _)| matches!(key, Value::String(_)))
+            {
+                let object = entries.into_iter().map(|(key, value)| match key {
+                    Value::String(key) => (key, to_json_value(value)),
+                    _ => unreachable!(),
+                });
+                Json::Object(object.collect())
+            } else {
+                // JSON keys are strings, other maps become `[key, value]` pairs
+                let pairs = entries
+                    .into_iter()
+                    .map(|(key, value)| array(vec![to_json_value(key), to_json_value(value)]));
+                array(pairs.collect())
+            }
+        }
+        Value::Enum { variant, .. } => Json::String(variant),
+        Value::Struct { fields, .. } => Json::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name, to_json_value(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn to_msgpack_value(value: Value) -> rmpv::Value {
+    use rmpv::Value as Msgpack;
+    let array = |items: Vec<Msgpack>| Msgpack::Array(items);
+    match value {
+        Value::Null | Value::Ignored { .. } => Msgpack::Nil,
+        Value::Bool(v) => v.into(),
+        Value::I8(v) => v.into(),
+        Value::U8(v) => v.into(),
+        Value::I16(v) => v.into(),
+        Value::U16(v) => v.into(),
+        Value::I32(v) => v.into(),
+        Value::U32(v) => v.into(),
+        Value::I64(v) => v.into(),
+        Value::U64(v) => v.into(),
+        Value::F32(v) => v.into(),
+        Value::F64(v) => v.into(),
+        Value::String(v) => v.into(),
+        Value::Binary(v) => v.into(),
+        Value::Date(v) => v.to_string().into(),
+        Value::Timestamp(v) => v.format(TIMESTAMP_FORMAT).to_string().into(),
+        Value::BoolArray(v) => array(v.into_iter().map(Msgpack::from).collect()),
+        Value::I16Array(v) => array(v.into_iter().map(Msgpack::from).collect()),
+        Value::I32Array(v) => array(v.into_iter().map(Msgpack::from).collect()),
+        Value::I64Array(v) => array(v.into_iter().map(Msgpack::from).collect()),
+        Value::F32Array(v) => array(v.into_iter().map(Msgpack::from).collect()),
+        Value::F64Array(v) => array(v.into_iter().map(Msgpack::from).collect()),
+        Value::List(v) | Value::Set(v) => array(v.into_iter().map(to_msgpack_value).collect()),
+        Value::Map(entries) => Msgpack::Map(
+            entries
+                .into_iter()
+                .map(|(key, value)| (to_msgpack_value(key), to_msgpack_value(value)))
+                .collect(),
+        ),
+        Value::Enum { variant, .. } => variant.into(),
+        Value::Struct { fields, .. } => Msgpack::Map(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.into(), to_msgpack_value(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn msgpack_to_json(value: rmpv::Value) -> Result<Json, Error> {
+    use rmpv::Value as Msgpack;
+    Ok(match value {
+        Msgpack::Nil => Json::Null,
+        Msgpack::Boolean(v) => Json::Bool(v),
+        Msgpack::Integer(v) => match (v.as_i64(), v.as_u64()) {
+            (Some(v), _) => v.into(),
+            (_, Some(v)) => v.into(),
+            _ => unreachable!("MessagePack integers are i64 or u64"),
+        },
+        Msgpack::F32(v) => float(v as f64),
+        Msgpack::F64(v) => float(v),
+        Msgpack::String(v) => Json::String(
+            v.into_str()
+                .ok_or_else(|| anyhow!("MessagePack string isn't UTF-8"))?,
+        ),
+        Msgpack::Binary(v) => v.into(),
+        Msgpack::Array(items) => Json::Array(
+            items
+                .into_iter()
+                .map(msgpack_to_json)
+                .collect::<Result<_, _>>()?,
+        ),
+        Msgpack::Map(entries) => {
+            let mut object = Map::new();
+            for (key, value) in entries {
+                let Msgpack::String(key) = key else {
+                    return Err(anyhow!("MessagePack map key {key} isn't a string").into());
+                };
+                let key = key
+                    .into_str()
+                    .ok_or_else(|| anyhow!("MessagePack string isn't UTF-8"))?;
+                object.insert(key, msgpack_to_json(value)?);
+            }
+            Json::Object(object)
+        }
+        Msgpack::Ext(..) => Err(anyhow!("MessagePack extension types aren't supported"))?,
+    })
+}
+
+fn mismatch(json: &Json, expected: &str) -> Error {
+    anyhow!("Expected {expected}, got {json}").into()
+}
+
+fn int<T: TryFrom<i64> + TryFrom<u64>>(json: &Json) -> Result<T, Error> {
+    let value = match (json.as_i64(), json.as_u64()) {
+        (Some(v), _) => T::try_from(v).ok(),
+        (_, Some(v)) => T::try_from(v).ok(),
+        _ => None,
+    };
+    value.ok_or_else(|| mismatch(json, std::any::type_name::<T>()))
+}
+
+fn items(json: &Json) -> Result<&Vec<Json>, Error> {
+    json.as_array().ok_or_else(|| mismatch(json, "an array"))
+}
+
+fn typed_items<T>(json: &Json, item: impl Fn(&Json) -> Result<T, Error>) -> Result<Vec<T>, Error> {
+    items(json)?.iter().map(item).collect()
+}
+
+// the element type of a delta encoded field isn't part of the type meta: numbers are taken as
+// `i64`, strings as timestamps or dates
+fn delta_items(json: &Json) -> Result<Value, Error> {
+    if items(json)?.iter().all(Json::is_number) {
+        return Ok(Value::I64Array(typed_items(json, int)?));
+    }
+    Ok(Value::List(typed_items(json, |json| {
+        let text = json.as_str().ok_or_else(|| mismatch(json, "a timestamp"))?;
+        if let Ok(ts) = NaiveDateTime::parse_from_str(text, TIMESTAMP_FORMAT) {
+            return Ok(Value::Timestamp(ts));
+        }
+        text.parse::<NaiveDate>()
+            .map(Value::Date)
+            .map_err(|_| mismatch(json, "a timestamp"))
+    })?))
+}
+
+fn from_json_value(json: &Json, fury: &Fury, type_id: i16) -> Result<Value, Error> {
+    if json.is_null() {
+        return Ok(Value::Null);
+    }
+    let float = |json: &Json| json.as_f64().ok_or_else(|| mismatch(json, "a number"));
+    let Ok(field_type) = FieldType::try_from(type_id) else {
+        return from_json_registered(json, fury, type_id as u32);
+    };
+    Ok(match field_type {
+        FieldType::BOOL => Value::Bool(json.as_bool().ok_or_else(|| mismatch(json, "a bool"))?),
+        FieldType::INT8 => Value::I8(int(json)?),
+        FieldType::UINT8 => Value::U8(int(json)?),
+        FieldType::INT16 => Value::I16(int(json)?),
+        FieldType::UINT16 => Value::U16(int(json)?),
+        FieldType::INT32 => Value::I32(int(json)?),
+        FieldType::UINT32 => Value::U32(int(json)?),
+        FieldType::INT64 => Value::I64(int(json)?),
+        FieldType::UINT64 => Value::U64(int(json)?),
+        FieldType::FLOAT => Value::F32(float(json)? as f32),
+        FieldType::DOUBLE => Value::F64(float(json)?),
+        FieldType::STRING => Value::String(
+            json.as_str()
+                .ok_or_else(|| mismatch(json, "a string"))?
+                .to_string(),
+        ),
+        FieldType::BINARY => Value::Binary(typed_items(json, int)?),
+        FieldType::DATE => Value::Date(
+            json.as_str()
+                .and_then(|date| date.parse::<NaiveDate>().ok())
+                .ok_or_else(|| mismatch(json, "a date"))?,
+        ),
+        FieldType::TIMESTAMP => Value::Timestamp(
+            json.as_str()
+                .and_then(|ts| NaiveDateTime::parse_from_str(ts, TIMESTAMP_FORMAT).ok())
+                .ok_or_else(|| mismatch(json, "a timestamp"))?,
+        ),
+        FieldType::FuryPrimitiveBoolArray | FieldType::FuryPackedBoolArray => {
+            Value::BoolArray(typed_items(json, |json| {
+                json.as_bool().ok_or_else(|| mismatch(json, "a bool"))
+            })?)
+        }
+        FieldType::FuryPrimitiveShortArray => Value::I16Array(typed_items(json, int)?),
+        FieldType::FuryPrimitiveIntArray => Value::I32Array(typed_items(json, int)?),
+        FieldType::FuryPrimitiveLongArray => Value::I64Array(typed_items(json, int)?),
+        FieldType::FuryPrimitiveFloatArray => {
+            Value::F32Array(typed_items(json, |json| Ok(float(json)? as f32))?)
+        }
+        FieldType::FuryPrimitiveDoubleArray => Value::F64Array(typed_items(json, float)?),
+        FieldType::FuryDeltaArray => delta_items(json)?,
+        FieldType::ARRAY => Value::List(typed_items(json, inferred)?),
+        FieldType::FurySet => Value::Set(typed_items(json, inferred)?),
+        FieldType::MAP | FieldType::FuryLinkedHashMap | FieldType::FuryTreeMap => Value::Map(
+            json.as_object()
+                .ok_or_else(|| mismatch(json, "an object"))?
+                .iter()
+                .map(|(key, value)| Ok((Value::String(key.clone()), inferred(value)?)))
+                .collect::<Result<_, Error>>()?,
+        ),
+        FieldType::FuryTypeTag | FieldType::FuryStringArray => {
+            return Err(anyhow!("Can't write a value of type {field_type:?} from JSON").into());
+        }
+    })
+}
+
+fn from_json_registered(json: &Json, fury: &Fury, type_id: u32) -> Result<Value, Error> {
+    match TypeLayout::of(fury, type_id) {
+        Some(TypeLayout::Enum(_)) => Ok(Value::Enum {
+            type_id,
+            variant: json
+                .as_str()
+                .ok_or_else(|| mismatch(json, "a variant name"))?
+                .to_string(),
+        }),
+        Some(TypeLayout::Struct(field_infos)) => {
+            let object = json
+                .as_object()
+                .ok_or_else(|| mismatch(json, "an object"))?;
+            let fields = field_infos
+                .iter()
+                .map(|field| {
+                    let value = object.get(field.get_field_name()).unwrap_or(&Json::Null);
+                    let value = from_json_value(value, fury, field.get_field_id())
+                        .map_err(|err| anyhow!("Field `{}`: {err}", field.get_field_name()))?;
+                    Ok((field.get_field_name().to_string(), value))
+                })
+                .collect::<Result<_, Error>>()?;
+            Ok(Value::Struct {
+                type_id: Some(type_id),
+                fields,
+            })
+        }
+        None => Err(anyhow!("Unknown type id {type_id}"))?,
+    }
+}
+
+// elements of lists and maps, which only have a JSON type
+fn inferred(json: &Json) -> Result<Value, Error> {
+    Ok(match json {
+        Json::Null => Value::Null,
+        Json::Bool(v) => Value::Bool(*v),
+        Json::Number(v) => match (v.as_i64(), v.as_u64()) {
+            (Some(v), _) => Value::I64(v),
+            (_, Some(v)) => Value::U64(v),
+            _ => Value::F64(v.as_f64().unwrap_or(f64::NAN)),
+        },
+        Json::String(v) => Value::String(v.clone()),
+        Json::Array(items) => Value::List(items.iter().map(inferred).collect::<Result<_, _>>()?),
+        Json::Object(_) => Err(anyhow!(
+            "Can't tell the type of {json}, only fields can hold structs"
+        ))?,
+    })
+}
@@ -0,0 +1,30 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+#![no_main]
+
+use fury_core::meta::is_latin_paths;
+use libfuzzer_sys::fuzz_target;
+
+// Runs the scalar and every SIMD `is_latin` path of this CPU on the input, invalid UTF-8 read
+// the way a reader would, and checks that they all agree with `str::is_ascii`.
+fuzz_target!(|data: &[u8]| {
+    let s = String::from_utf8_lossy(data);
+    for (path, is_latin) in is_latin_paths(&s) {
+        assert_eq!(is_latin, s.is_ascii(), "`{path}` path on {s:?}");
+    }
+});
@@ -0,0 +1,47 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+#![no_main]
+
+use fury_core::buffer::Reader;
+use fury_core::resolver::ref_resolver::RefReader;
+use libfuzzer_sys::fuzz_target;
+
+// Interprets the input as a sequence of ref flags, the way nested objects would present them,
+// and checks that the ref table never grows past its cap nor accepts an unknown id.
+fuzz_target!(|data: &[u8]| {
+    const MAX_REF_COUNT: usize = 64;
+    let mut refs = RefReader::new(MAX_REF_COUNT);
+    // one ref flag followed by room for a 5 byte var_int32
+    for chunk in data.chunks_exact(6) {
+        let mut reader = Reader::new(chunk);
+        match reader.i8() {
+            Ok(0) => {
+                if refs.reserve_ref_id().is_err() {
+                    assert_eq!(refs.len(), MAX_REF_COUNT);
+                }
+            }
+            Ok(-2) => {
+                if let Ok(id) = refs.read_ref_id(&mut reader) {
+                    assert!((id as usize) < refs.len());
+                }
+            }
+            _ => {}
+        }
+        assert!(refs.len() <= MAX_REF_COUNT);
+    }
+});
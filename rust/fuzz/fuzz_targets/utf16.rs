@@ -0,0 +1,41 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+#![no_main]
+
+use fury_core::util::{to_utf16_paths, to_utf8_paths};
+use libfuzzer_sys::fuzz_target;
+
+// Runs the scalar and every SIMD path of this CPU of both string converters, on the input as
+// UTF-16 units and as UTF-8, in both byte orders, and checks that they all agree on the output
+// or the error.
+fuzz_target!(|data: &[u8]| {
+    let utf16: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|unit| u16::from_ne_bytes([unit[0], unit[1]]))
+        .collect();
+    for is_little_endian in [false, true] {
+        let paths = to_utf8_paths(&utf16, is_little_endian);
+        for (path, utf8) in &paths[1..] {
+            assert_eq!(utf8, &paths[0].1, "`{path}` path on {utf16:x?}");
+        }
+        let paths = to_utf16_paths(data, is_little_endian);
+        for (path, utf16) in &paths[1..] {
+            assert_eq!(utf16, &paths[0].1, "`{path}` path on {data:x?}");
+        }
+    }
+});
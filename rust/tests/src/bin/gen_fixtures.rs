@@ -0,0 +1,49 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Writes the golden-bytes fixtures, see `fury_tests::fixtures`.
+//!
+//! `cargo run -p fury-tests --bin gen_fixtures [dir]`, after a deliberate wire format change.
+
+use fury_tests::fixtures::{configs, corpus, fixture_path, fixtures_dir, manifest_line, MANIFEST};
+use std::fs;
+use std::path::PathBuf;
+
+fn main() -> std::io::Result<()> {
+    let dir = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(fixtures_dir);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    let corpus = corpus();
+    let mut manifest = String::new();
+    for (config, fury) in configs() {
+        fs::create_dir_all(dir.join(&config))?;
+        for case in &corpus {
+            let path = fixture_path(&config, &case.name);
+            let bytes = case.write(&fury);
+            fs::write(dir.join(&path), &bytes)?;
+            manifest.push_str(&manifest_line(&path, &bytes));
+            manifest.push('\n');
+        }
+    }
+    fs::write(dir.join(MANIFEST), manifest)?;
+    println!("Wrote the fixtures to {}", dir.display());
+    Ok(())
+}
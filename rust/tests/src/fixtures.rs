@@ -0,0 +1,317 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Golden-bytes fixtures: a seeded corpus of typed values written under every configuration in
+//! [`CONFIGS`]. `gen_fixtures` writes them to [`fixtures_dir`] along with a manifest, and
+//! `test_fixtures` fails when the bytes written today differ from the committed ones.
+//!
+//! Values come from [`Rng`] rather than the `rand` crate, whose algorithms may change between
+//! releases, and maps are written with [`Fury::deterministic`], so the bytes only depend on
+//! the wire format.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_core::serializer::Serializer;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+/// Seed of the corpus, changing it changes every fixture.
+pub const SEED: u64 = 0x5eed_f00d;
+
+/// Name of the manifest file in [`fixtures_dir`].
+pub const MANIFEST: &str = "MANIFEST";
+
+/// SplitMix64, small and stable across platforms and crate versions.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// A length in `0..=max_len`.
+    pub fn len(&mut self, max_len: u64) -> u64 {
+        self.below(max_len + 1)
+    }
+
+    pub fn string(&mut self, max_len: u64) -> String {
+        const CHARS: [char; 12] = ['a', 'b', 'c', 'x', 'y', 'z', '0', '7', ' ', 'é', '中', '🦀'];
+        (0..self.len(max_len))
+            .map(|_| CHARS[self.below(CHARS.len() as u64) as usize])
+            .collect()
+    }
+}
+
+#[derive(Fury, Debug, PartialEq)]
+pub enum Level {
+    Low,
+    Mid,
+    High,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+pub struct Record {
+    pub id: i64,
+    pub small: i8,
+    pub unsigned: u32,
+    pub ratio: f64,
+    pub score: f32,
+    pub flag: bool,
+    pub name: String,
+    pub note: Option<String>,
+    pub day: NaiveDate,
+    pub at: NaiveDateTime,
+    pub level: Level,
+    pub origin: Point,
+    pub path: Vec<Point>,
+    pub samples: Vec<i64>,
+    pub weights: Vec<f64>,
+    pub mask: Vec<bool>,
+    pub tags: Vec<String>,
+    pub attrs: HashMap<String, i32>,
+    pub blob: Vec<u8>,
+}
+
+/// A marker type, written as its type id alone.
+#[derive(Fury, Debug, PartialEq)]
+pub struct Ping;
+
+#[derive(Fury, Debug, PartialEq)]
+pub struct Empty {}
+
+fn point(rng: &mut Rng) -> Point {
+    Point {
+        x: rng.next_u64() as i32,
+        y: rng.below(1000) as i32 - 500,
+    }
+}
+
+fn record(rng: &mut Rng, max_len: u64) -> Record {
+    let level = match rng.below(3) {
+        0 => Level::Low,
+        1 => Level::Mid,
+        _ => Level::High,
+    };
+    // runs of equal samples, so that `rle_arrays` has something to do
+    let run = rng.below(5) as i64;
+    Record {
+        id: rng.next_u64() as i64,
+        small: rng.next_u64() as i8,
+        unsigned: rng.next_u64() as u32,
+        // multiples of 1/8 are exact as `f32`, so that `compact_floats` has something to do
+        ratio: rng.below(1 << 20) as f64 / 8.0,
+        score: rng.below(1 << 20) as f32 / 3.0,
+        flag: rng.below(2) == 1,
+        name: rng.string(max_len),
+        note: (rng.below(2) == 1).then(|| rng.string(max_len)),
+        day: NaiveDate::from_ymd_opt(1970 + rng.below(200) as i32, 1, 1).unwrap()
+            + chrono::Days::new(rng.below(365)),
+        at: DateTime::from_timestamp(rng.below(1 << 33) as i64, rng.below(1_000_000_000) as u32)
+            .unwrap()
+            .naive_utc(),
+        level,
+        origin: point(rng),
+        path: (0..rng.len(max_len)).map(|_| point(rng)).collect(),
+        samples: (0..rng.len(max_len))
+            .map(|i| i as i64 / (run + 1))
+            .collect(),
+        weights: (0..rng.len(max_len))
+            .map(|_| rng.next_u64() as f64 / 3.0)
+            .collect(),
+        mask: (0..rng.len(max_len)).map(|_| rng.below(2) == 1).collect(),
+        tags: (0..rng.len(max_len)).map(|_| rng.string(8)).collect(),
+        attrs: (0..rng.len(max_len))
+            .map(|i| (format!("{i}{}", rng.string(4)), rng.next_u64() as i32))
+            .collect(),
+        blob: (0..rng.len(max_len))
+            .map(|_| rng.next_u64() as u8)
+            .collect(),
+    }
+}
+
+/// A value of the corpus, written and read back through its own type.
+pub struct Case {
+    pub name: String,
+    write: Box<WriteFn>,
+    read: Box<ReadFn>,
+}
+
+type WriteFn = dyn Fn(&Fury) -> Vec<u8>;
+type ReadFn = dyn Fn(&Fury, &[u8]) -> Result<bool, Error>;
+
+impl Case {
+    fn new<T: Serializer + PartialEq + 'static>(name: impl Into<String>, value: T) -> Case {
+        let value = std::rc::Rc::new(value);
+        let expected = value.clone();
+        Case {
+            name: name.into(),
+            write: Box::new(move |fury| fury.serialize(value.as_ref())),
+            read: Box::new(move |fury, bytes| Ok(fury.deserialize::<T>(bytes)? == *expected)),
+        }
+    }
+
+    pub fn write(&self, fury: &Fury) -> Vec<u8> {
+        (self.write)(fury)
+    }
+
+    /// Whether `bytes` read back as this value.
+    pub fn read(&self, fury: &Fury, bytes: &[u8]) -> Result<bool, Error> {
+        (self.read)(fury, bytes)
+    }
+}
+
+/// The corpus, the same for every run.
+pub fn corpus() -> Vec<Case> {
+    let mut rng = Rng::new(SEED);
+    let mut cases = vec![
+        Case::new("record_empty", record(&mut rng, 0)),
+        Case::new("record_small", record(&mut rng, 4)),
+        Case::new("record_large", record(&mut rng, 40)),
+    ];
+    let records: Vec<Record> = (0..3).map(|_| record(&mut rng, 6)).collect();
+    cases.push(Case::new("records", records));
+    cases.push(Case::new("string", rng.string(64)));
+    cases.push(Case::new(
+        "i64_list",
+        (0..32).map(|_| rng.next_u64() as i64).collect::<Vec<_>>(),
+    ));
+    cases.push(Case::new(
+        "nested_map",
+        (0..8i32)
+            .map(|i| {
+                let values: Vec<String> = (0..i).map(|_| rng.string(6)).collect();
+                (i, values)
+            })
+            .collect::<HashMap<_, _>>(),
+    ));
+    // what Python peers write for a `set`, a `tuple` and a timezone aware `datetime`
+    cases.push(Case::new(
+        "set",
+        (0..16).map(|_| rng.string(6)).collect::<HashSet<_>>(),
+    ));
+    cases.push(Case::new(
+        "tuple",
+        (
+            rng.next_u64() as i64,
+            rng.string(8),
+            rng.below(1 << 20) as f64 / 8.0,
+        ),
+    ));
+    cases.push(Case::new(
+        "datetime_utc",
+        DateTime::<Utc>::from_timestamp(rng.below(1 << 33) as i64, rng.below(1_000_000_000) as u32)
+            .unwrap(),
+    ));
+    cases.push(Case::new("unit_struct", Ping));
+    cases.push(Case::new("empty_struct", vec![Empty {}, Empty {}]));
+    cases
+}
+
+/// Options turned on one at a time, then all together.
+const OPTIONS: [&str; 11] = [
+    "default",
+    "aligned_arrays",
+    "compact_floats",
+    "packed_bools",
+    "compress_number",
+    "rle_arrays",
+    "elide_declared_types",
+    "struct_length_guard",
+    "compact_elements",
+    "per_entry_maps",
+    "all",
+];
+
+fn with_option(fury: Fury, option: &str) -> Fury {
+    let all = option == "all";
+    fury.aligned_arrays(all || option == "aligned_arrays")
+        .compact_floats(all || option == "compact_floats")
+        .packed_bools(all || option == "packed_bools")
+        .compress_number(all || option == "compress_number")
+        .rle_arrays(all || option == "rle_arrays")
+        .elide_declared_types(all || option == "elide_declared_types")
+        .struct_length_guard(all || option == "struct_length_guard")
+        .compact_elements(all || option == "compact_elements")
+        .per_entry_maps(all || option == "per_entry_maps")
+}
+
+/// Named configurations the corpus is written with, each of [`OPTIONS`] in both modes.
+pub fn configs() -> Vec<(String, Fury)> {
+    let mut configs = Vec::new();
+    for mode_name in ["schema_consistent", "compatible"] {
+        for option in OPTIONS {
+            let mode = match mode_name {
+                "compatible" => Mode::Compatible,
+                _ => Mode::SchemaConsistent,
+            };
+            let fury = Fury::default().mode(mode).deterministic(true);
+            let mut fury = with_option(fury, option);
+            fury.register::<Level>(101);
+            fury.register::<Point>(102);
+            fury.register::<Record>(103);
+            fury.register::<Ping>(104);
+            fury.register::<Empty>(105);
+            configs.push((format!("{mode_name}-{option}"), fury));
+        }
+    }
+    configs
+}
+
+/// Where the fixtures are committed.
+pub fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures")
+}
+
+/// Path of a fixture relative to [`fixtures_dir`].
+pub fn fixture_path(config: &str, case: &str) -> String {
+    format!("{config}/{case}.bin")
+}
+
+/// FNV-1a, listed in the manifest to spot changed fixtures in a diff.
+pub fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Line of the manifest for a fixture.
+pub fn manifest_line(path: &str, bytes: &[u8]) -> String {
+    format!("{path} {} {:016x}", bytes.len(), checksum(bytes))
+}
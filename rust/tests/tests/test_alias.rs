@@ -0,0 +1,107 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_derive::Fury;
+use std::any::Any;
+
+mod legacy {
+    use fury_derive::Fury;
+
+    #[derive(Fury, Debug, PartialEq)]
+    pub struct OrderV2 {
+        pub id: i64,
+        pub items: Vec<String>,
+    }
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Order {
+    id: i64,
+    items: Vec<String>,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Invoice {
+    id: i64,
+}
+
+fn legacy_order() -> legacy::OrderV2 {
+    legacy::OrderV2 {
+        id: 42,
+        items: vec!["pen".to_string()],
+    }
+}
+
+fn order() -> Order {
+    Order {
+        id: 42,
+        items: vec!["pen".to_string()],
+    }
+}
+
+fn migrated() -> Fury {
+    let mut fury = Fury::default();
+    fury.register::<Order>(8);
+    fury.alias_id::<Order>(7).unwrap();
+    fury.alias::<Order>("com.foo.OrderV2").unwrap();
+    fury
+}
+
+#[test]
+fn reads_legacy_ids() {
+    let mut old = Fury::default();
+    old.register::<legacy::OrderV2>(7);
+    let bin = old.serialize(&legacy_order());
+
+    let fury = migrated();
+    let obj: Order = fury.deserialize(&bin).unwrap();
+    assert_eq!(obj, order());
+
+    let bin = old.serialize(&(Box::new(legacy_order()) as Box<dyn Any>));
+    let any: Box<dyn Any> = fury.deserialize(&bin).unwrap();
+    assert_eq!(any.downcast_ref::<Order>(), Some(&order()));
+
+    // the current id is written
+    let obj: Order = fury.deserialize(&fury.serialize(&order())).unwrap();
+    assert_eq!(obj, order());
+    assert!(old
+        .deserialize::<legacy::OrderV2>(&fury.serialize(&order()))
+        .is_err());
+}
+
+#[test]
+fn resolves_legacy_names() {
+    let fury = migrated();
+    let resolver = fury.get_class_resolver();
+    assert_eq!(resolver.get_type_id_by_name("com.foo.OrderV2"), Some(8));
+    assert_eq!(resolver.resolve_id(7), 8);
+    assert_eq!(resolver.resolve_id(8), 8);
+}
+
+#[test]
+fn conflicting_aliases() {
+    let mut fury = migrated();
+    fury.register::<Invoice>(9);
+    assert!(fury.alias_id::<Invoice>(7).is_err());
+    assert!(fury.alias_id::<Invoice>(8).is_err());
+    assert!(fury.alias::<Invoice>("com.foo.OrderV2").is_err());
+    // repeating an alias is fine
+    assert!(fury.alias_id::<Order>(7).is_ok());
+
+    let mut unregistered = Fury::default();
+    assert!(unregistered.alias_id::<Order>(7).is_err());
+}
@@ -0,0 +1,134 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_core::types::{DecodeMode, Mode};
+use fury_derive::Fury;
+use std::borrow::Cow;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Samples<'a> {
+    channel: i8,
+    values: Cow<'a, [f64]>,
+    counts: Cow<'a, [i32]>,
+}
+
+fn samples() -> Samples<'static> {
+    Samples {
+        channel: 3,
+        values: Cow::Owned((0..50).map(|i| i as f64 * 0.5).collect()),
+        counts: Cow::Owned((0..7).collect()),
+    }
+}
+
+fn mode(compatible: bool) -> Mode {
+    if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    }
+}
+
+/// Copies `bin` into 8-byte aligned memory, `offset` bytes past the aligned start.
+fn aligned_copy(bin: &[u8], offset: usize) -> Vec<u64> {
+    let mut words = vec![0u64; (bin.len() + offset) / 8 + 1];
+    let bytes =
+        unsafe { std::slice::from_raw_parts_mut(words.as_mut_ptr().cast::<u8>(), words.len() * 8) };
+    bytes[offset..offset + bin.len()].copy_from_slice(bin);
+    words
+}
+
+fn view(words: &[u64], offset: usize, len: usize) -> &[u8] {
+    let bytes = unsafe { std::slice::from_raw_parts(words.as_ptr().cast::<u8>(), words.len() * 8) };
+    &bytes[offset..offset + len]
+}
+
+#[test]
+fn borrows_aligned_arrays() {
+    for compatible in [false, true] {
+        let mut fury = Fury::default().mode(mode(compatible)).aligned_arrays(true);
+        fury.register::<Samples>(1);
+        let bin = fury.serialize(&samples());
+        let words = aligned_copy(&bin, 0);
+        let input = view(&words, 0, bin.len());
+
+        let obj: Samples = fury
+            .deserialize_with_mode(input, DecodeMode::Borrowed)
+            .unwrap();
+        assert_eq!(obj, samples());
+        assert!(matches!(obj.values, Cow::Borrowed(_)));
+        assert!(matches!(obj.counts, Cow::Borrowed(_)));
+    }
+}
+
+#[test]
+fn copies_misaligned_input() {
+    let mut fury = Fury::default().aligned_arrays(true);
+    fury.register::<Samples>(1);
+    let bin = fury.serialize(&samples());
+    let words = aligned_copy(&bin, 1);
+    let input = view(&words, 1, bin.len());
+
+    let obj: Samples = fury
+        .deserialize_with_mode(input, DecodeMode::Borrowed)
+        .unwrap();
+    assert_eq!(obj, samples());
+    assert!(matches!(obj.values, Cow::Owned(_)));
+}
+
+#[test]
+fn padding_is_relative_to_message_start() {
+    let fury = Fury::default().aligned_arrays(true);
+    for len in [0, 1, 5, 100] {
+        let values: Vec<i64> = (0..len).collect();
+        let bin = fury.serialize(&values);
+        let payload = bin.len() - len as usize * 8;
+        assert_eq!(payload % 8, 0);
+        let obj: Vec<i64> = fury.deserialize(&bin).unwrap();
+        assert_eq!(obj, values);
+    }
+}
+
+#[test]
+fn unaligned_peers_keep_the_compact_form() {
+    let aligned = Fury::default().aligned_arrays(true);
+    let compact = Fury::default();
+    let values = vec![1.5f32, 2.5, 3.5];
+    assert!(compact.serialize(&values).len() <= aligned.serialize(&values).len());
+    let obj: Vec<f32> = compact.deserialize(&compact.serialize(&values)).unwrap();
+    assert_eq!(obj, values);
+}
+
+#[test]
+fn copies_arrays_split_across_slices() {
+    let fury = Fury::default();
+    // little-endian on the wire whatever the target
+    let bin = fury.serialize(&vec![1i32, -2]);
+    assert_eq!(&bin[bin.len() - 8..], [1, 0, 0, 0, 0xfe, 0xff, 0xff, 0xff]);
+
+    let values: Vec<i64> = (0..40).map(|i| i * 1_000_003 - 7).collect();
+    let bin = fury.serialize(&values);
+    let floats: Vec<f64> = (0..40).map(|i| i as f64 / 3.0).collect();
+    let float_bin = fury.serialize(&floats);
+    for split in [12, 13, 100, bin.len() - 1] {
+        let (head, tail) = bin.split_at(split);
+        let obj: Vec<i64> = fury.deserialize_from_slices(&[head, tail]).unwrap();
+        assert_eq!(obj, values);
+        let (head, tail) = float_bin.split_at(split);
+        let obj: Vec<f64> = fury.deserialize_from_slices(&[head, tail]).unwrap();
+        assert_eq!(obj, floats);
+    }
+}
@@ -0,0 +1,96 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_derive::Fury;
+use std::any::Any;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Circle {
+    radius: f64,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Square {
+    side: f64,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Secret {
+    key: String,
+}
+
+#[derive(Fury)]
+struct Drawing {
+    #[fury(allowed = [Circle, Square])]
+    shapes: Vec<Box<dyn Any>>,
+    #[fury(allowed = [Circle])]
+    focus: Box<dyn Any>,
+}
+
+fn fury() -> Fury {
+    let mut fury = Fury::default();
+    fury.register::<Circle>(1);
+    fury.register::<Square>(2);
+    fury.register::<Secret>(3);
+    fury.register::<Drawing>(4);
+    fury
+}
+
+#[test]
+fn allowed_types_are_read() {
+    let fury = fury();
+    let drawing = Drawing {
+        shapes: vec![
+            Box::new(Circle { radius: 1.0 }),
+            Box::new(Square { side: 2.0 }),
+        ],
+        focus: Box::new(Circle { radius: 3.0 }),
+    };
+    let obj: Drawing = fury.deserialize(&fury.serialize(&drawing)).unwrap();
+    assert_eq!(obj.shapes.len(), 2);
+    assert_eq!(
+        obj.shapes[1].downcast_ref::<Square>(),
+        Some(&Square { side: 2.0 })
+    );
+    assert_eq!(
+        obj.focus.downcast_ref::<Circle>(),
+        Some(&Circle { radius: 3.0 })
+    );
+}
+
+#[test]
+fn other_types_are_rejected() {
+    let fury = fury();
+    let drawing = Drawing {
+        shapes: vec![
+            Box::new(Circle { radius: 1.0 }),
+            Box::new(Secret {
+                key: "k".to_string(),
+            }),
+        ],
+        focus: Box::new(Circle { radius: 3.0 }),
+    };
+    let err = fury
+        .deserialize::<Drawing>(&fury.serialize(&drawing))
+        .err()
+        .expect("should be rejected");
+    assert!(
+        err.to_string().contains("Field `shapes` doesn't allow"),
+        "{err}"
+    );
+}
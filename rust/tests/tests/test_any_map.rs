@@ -0,0 +1,160 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_core::value::Value;
+use fury_derive::Fury;
+use std::any::Any;
+use std::collections::HashMap;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Endpoint {
+    host: String,
+    port: i32,
+}
+
+// plugin settings, like a Java `Map<String, Object>`
+#[derive(Fury)]
+struct PluginConfig {
+    name: String,
+    settings: HashMap<String, Box<dyn Any>>,
+}
+
+fn fury(compatible: bool) -> Fury {
+    let mode = if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    };
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Endpoint>(101);
+    fury.register::<PluginConfig>(102);
+    fury
+}
+
+fn settings() -> HashMap<String, Box<dyn Any>> {
+    let mut limits: HashMap<String, Box<dyn Any>> = HashMap::new();
+    limits.insert(String::from("rps"), Box::new(100i64));
+    let mut settings: HashMap<String, Box<dyn Any>> = HashMap::new();
+    settings.insert(String::from("enabled"), Box::new(true));
+    settings.insert(String::from("retries"), Box::new(3i32));
+    settings.insert(String::from("ratio"), Box::new(0.25f64));
+    settings.insert(String::from("label"), Box::new(String::from("edge")));
+    settings.insert(String::from("weights"), Box::new(vec![1i64, 2, 3]));
+    settings.insert(
+        String::from("endpoint"),
+        Box::new(Endpoint {
+            host: String::from("localhost"),
+            port: 8080,
+        }),
+    );
+    settings.insert(
+        String::from("tags"),
+        Box::new(vec![
+            Box::new(String::from("a")) as Box<dyn Any>,
+            Box::new(7i32),
+        ]),
+    );
+    settings.insert(String::from("limits"), Box::new(limits));
+    settings
+}
+
+fn get<'a, T: 'static>(settings: &'a HashMap<String, Box<dyn Any>>, key: &str) -> &'a T {
+    settings[key].downcast_ref::<T>().unwrap()
+}
+
+fn check(settings: &HashMap<String, Box<dyn Any>>) {
+    assert_eq!(settings.len(), 8);
+    assert!(*get::<bool>(settings, "enabled"));
+    assert_eq!(*get::<i32>(settings, "retries"), 3);
+    assert_eq!(*get::<f64>(settings, "ratio"), 0.25);
+    assert_eq!(get::<String>(settings, "label"), "edge");
+    assert_eq!(get::<Vec<i64>>(settings, "weights"), &[1, 2, 3]);
+    assert_eq!(
+        get::<Endpoint>(settings, "endpoint"),
+        &Endpoint {
+            host: String::from("localhost"),
+            port: 8080
+        }
+    );
+    let tags = get::<Vec<Box<dyn Any>>>(settings, "tags");
+    assert_eq!(tags[0].downcast_ref::<String>().unwrap(), "a");
+    assert_eq!(*tags[1].downcast_ref::<i32>().unwrap(), 7);
+    let limits = get::<HashMap<String, Box<dyn Any>>>(settings, "limits");
+    assert_eq!(*get::<i64>(limits, "rps"), 100);
+}
+
+#[test]
+fn dynamic_values() {
+    for compatible in [false, true] {
+        let fury = fury(compatible);
+        let bin = fury.serialize(&settings());
+        check(&fury.deserialize(&bin).unwrap());
+    }
+}
+
+#[test]
+fn dynamic_values_in_struct() {
+    for compatible in [false, true] {
+        let fury = fury(compatible);
+        let config = PluginConfig {
+            name: String::from("rate-limiter"),
+            settings: settings(),
+        };
+        let read: PluginConfig = fury.deserialize(&fury.serialize(&config)).unwrap();
+        assert_eq!(read.name, "rate-limiter");
+        check(&read.settings);
+    }
+}
+
+#[test]
+fn dynamic_values_as_value() {
+    for compatible in [false, true] {
+        let fury = fury(compatible);
+        let mut settings: HashMap<String, Box<dyn Any>> = HashMap::new();
+        settings.insert(String::from("retries"), Box::new(3i32));
+        let value = fury.deserialize_value(&fury.serialize(&settings)).unwrap();
+        assert_eq!(
+            value,
+            Value::Map(vec![(
+                Value::String(String::from("retries")),
+                Value::I32(3)
+            )])
+        );
+    }
+}
+
+#[test]
+fn unknown_type_ids() {
+    let fury = fury(false);
+    let mut settings: HashMap<String, Box<dyn Any>> = HashMap::new();
+    settings.insert(
+        String::from("endpoint"),
+        Box::new(Endpoint {
+            host: String::from("localhost"),
+            port: 8080,
+        }),
+    );
+    let bin = fury.serialize(&settings);
+    let err = Fury::default()
+        .deserialize::<HashMap<String, Box<dyn Any>>>(&bin)
+        .unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        "`Box<dyn Any>` holds unregistered type id 101"
+    );
+}
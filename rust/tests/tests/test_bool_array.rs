@@ -0,0 +1,103 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fixedbitset::FixedBitSet;
+use fury_core::fury::Fury;
+use fury_core::types::{config_flags, FieldType};
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Flags {
+    enabled: Vec<bool>,
+    name: String,
+}
+
+fn pattern(len: usize) -> Vec<bool> {
+    (0..len).map(|i| i % 3 == 0 || i % 7 == 0).collect()
+}
+
+#[test]
+fn round_trip() {
+    for packed in [false, true] {
+        let fury = Fury::default().packed_bools(packed);
+        for len in [0, 1, 8, 15, 16, 17, 40, 127] {
+            let bools = pattern(len);
+            let obj: Vec<bool> = fury.deserialize(&fury.serialize(&bools)).unwrap();
+            assert_eq!(obj, bools);
+        }
+
+        let mut fury = Fury::default().packed_bools(packed);
+        fury.register::<Flags>(1);
+        let flags = Flags {
+            enabled: pattern(100),
+            name: "flags".to_string(),
+        };
+        let obj: Flags = fury.deserialize(&fury.serialize(&flags)).unwrap();
+        assert_eq!(obj, flags);
+    }
+}
+
+#[test]
+fn one_byte_per_element_by_default() {
+    let fury = Fury::default();
+    let bin = fury.serialize(&pattern(100));
+    let header = fury.serialize(&Vec::<bool>::new()).len();
+    assert_eq!(bin.len(), header + 100);
+    assert_eq!(
+        bin[9..11],
+        i16::from(FieldType::FuryPrimitiveBoolArray).to_le_bytes()
+    );
+    assert_eq!(bin[2] & config_flags::IS_EXPERIMENTAL_FLAG, 0);
+}
+
+#[test]
+fn packed_bools() {
+    let fury = Fury::default().packed_bools(true);
+    let bin = fury.serialize(&pattern(100));
+    let header = fury.serialize(&Vec::<bool>::new()).len();
+    assert_eq!(bin.len(), header + 13);
+    assert_ne!(bin[2] & config_flags::IS_EXPERIMENTAL_FLAG, 0);
+}
+
+#[test]
+fn read_either_form() {
+    let bools = pattern(20);
+    let plain = Fury::default();
+    let packed = Fury::default().packed_bools(true);
+    for (writer, reader) in [(&plain, &packed), (&packed, &plain)] {
+        let obj: Vec<bool> = reader.deserialize(&writer.serialize(&bools)).unwrap();
+        assert_eq!(obj, bools);
+    }
+}
+
+#[test]
+fn fixed_bit_set() {
+    let bools = pattern(70);
+    let mut set = FixedBitSet::with_capacity(bools.len());
+    for (i, b) in bools.iter().enumerate() {
+        set.set(i, *b);
+    }
+
+    // same wire form as `Vec<bool>`, either can be read as the other
+    for packed in [false, true] {
+        let fury = Fury::default().packed_bools(packed);
+        let bin = fury.serialize(&set);
+        assert_eq!(bin, fury.serialize(&bools));
+        assert_eq!(fury.deserialize::<FixedBitSet>(&bin).unwrap(), set);
+        assert_eq!(fury.deserialize::<Vec<bool>>(&bin).unwrap(), bools);
+    }
+}
@@ -0,0 +1,113 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::types::{DecodeMode, Mode};
+use fury_derive::Fury;
+use std::borrow::Cow;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Message<'a> {
+    id: i32,
+    body: Cow<'a, str>,
+    tag: Option<Cow<'a, str>>,
+    tags: Vec<String>,
+}
+
+fn message() -> Message<'static> {
+    Message {
+        id: 1,
+        body: Cow::Borrowed("hello"),
+        tag: Some(Cow::Borrowed("greeting")),
+        tags: vec!["a".to_string()],
+    }
+}
+
+#[test]
+fn borrowed_and_owned() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default().mode(mode);
+        fury.register::<Message>(1);
+        let bin = fury.serialize(&message());
+
+        let borrowed: Message = fury
+            .deserialize_with_mode(&bin, DecodeMode::Borrowed)
+            .unwrap();
+        assert_eq!(borrowed, message());
+        assert!(matches!(borrowed.body, Cow::Borrowed(_)));
+        assert!(matches!(borrowed.tag, Some(Cow::Borrowed(_))));
+
+        let owned: Message = fury.deserialize_with_mode(&bin, DecodeMode::Owned).unwrap();
+        assert_eq!(owned, message());
+        assert!(matches!(owned.body, Cow::Owned(_)));
+
+        // the plain entry point always owns, so the value may outlive the buffer
+        let detached: Message<'static> = fury.deserialize(&bin.clone()).unwrap();
+        assert_eq!(detached, message());
+    }
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Request<'a> {
+    method: &'a str,
+    payload: &'a [u8],
+    trace_id: Option<&'a str>,
+}
+
+fn within(slice: &[u8], bin: &[u8]) -> bool {
+    bin.as_ptr_range().contains(&slice.as_ptr())
+}
+
+#[test]
+fn slices() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default().mode(mode);
+        fury.register::<Request>(2);
+        let request = Request {
+            method: "get",
+            payload: &[1, 2, 3],
+            trace_id: Some("t-1"),
+        };
+        let bin = fury.serialize(&request);
+
+        let read: Request = fury.deserialize_borrowed(&bin).unwrap();
+        assert_eq!(read, request);
+        assert!(within(read.method.as_bytes(), &bin));
+        assert!(within(read.payload, &bin));
+
+        let err = fury.deserialize::<Request>(&bin).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("`&str` can only be read with `Fury::deserialize_borrowed`"),
+            "{err}"
+        );
+    }
+}
+
+#[test]
+fn invalid_utf8_slice() {
+    let fury = Fury::default();
+    // ASCII is written as Latin-1, where any byte is valid
+    let mut bin = fury.serialize(&"aé");
+    *bin.last_mut().unwrap() = 0xff;
+    let err = fury.deserialize_borrowed::<&str>(&bin).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("`&str` can't borrow invalid UTF-8"),
+        "{err}"
+    );
+}
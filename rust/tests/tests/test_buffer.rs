@@ -0,0 +1,155 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury::buffer::{Reader, Writer};
+use fury::Error;
+use std::io::IoSlice;
+
+#[test]
+fn round_trip() {
+    let var_int32s = [
+        0,
+        1,
+        127,
+        128,
+        300,
+        16_383,
+        16_384,
+        1 << 21,
+        1 << 28,
+        i32::MAX,
+        -1,
+        i32::MIN,
+    ];
+    let var_int64s = [0, 63, -64, 64, 1 << 40, i64::MAX, i64::MIN];
+    let sli_int64s = [
+        0,
+        -1,
+        (1 << 30) - 1,
+        -(1 << 30),
+        1 << 30,
+        i64::MAX,
+        i64::MIN,
+    ];
+    let mut writer = Writer::default();
+    writer.u8(7);
+    writer.i16(-2);
+    writer.u32(0xdead_beef);
+    writer.f64(1.5);
+    for value in var_int32s {
+        writer.var_int32(value);
+    }
+    for value in var_int64s {
+        writer.var_int64(value);
+    }
+    for value in var_int32s {
+        writer.var_zigzag_int32(value);
+    }
+    for value in sli_int64s {
+        writer.sli_int64(value);
+    }
+    writer.var_uint32(u32::MAX);
+    writer.var_uint64(u64::MAX);
+    writer.bytes(b"frame");
+    let bin = writer.dump();
+
+    // a frame split anywhere reads the same
+    for split in 0..=bin.len() {
+        let (head, tail) = bin.split_at(split);
+        let segments = [IoSlice::new(head), IoSlice::new(tail)];
+        let mut reader = Reader::from_io_slices(&segments);
+        assert_eq!(reader.u8().unwrap(), 7);
+        assert_eq!(reader.i16().unwrap(), -2);
+        assert_eq!(reader.u32().unwrap(), 0xdead_beef);
+        assert_eq!(reader.f64().unwrap(), 1.5);
+        for value in var_int32s {
+            assert_eq!(reader.var_int32().unwrap(), value);
+        }
+        for value in var_int64s {
+            assert_eq!(reader.var_int64().unwrap(), value);
+        }
+        for value in var_int32s {
+            assert_eq!(reader.var_zigzag_int32().unwrap(), value);
+        }
+        for value in sli_int64s {
+            assert_eq!(reader.sli_int64().unwrap(), value);
+        }
+        assert_eq!(reader.var_uint32().unwrap(), u32::MAX);
+        assert_eq!(reader.var_uint64().unwrap(), u64::MAX);
+        assert_eq!(reader.string(5).unwrap(), "frame");
+        assert_eq!(reader.get_cursor(), reader.len());
+    }
+}
+
+#[test]
+fn var_int32_layout() {
+    let mut writer = Writer::default();
+    writer.var_int32(300);
+    writer.var_int32(-1);
+    assert_eq!(
+        writer.as_slice(),
+        [0xac, 0x02, 0xff, 0xff, 0xff, 0xff, 0x0f]
+    );
+}
+
+#[test]
+fn compressed_layouts() {
+    let mut writer = Writer::default();
+    writer.var_zigzag_int32(-1);
+    writer.var_zigzag_int32(64);
+    writer.sli_int64(-2);
+    writer.sli_int64(1 << 30);
+    assert_eq!(
+        writer.as_slice(),
+        [
+            0x01, 0x80, 0x01, // zigzag
+            0xfc, 0xff, 0xff, 0xff, // -2 << 1
+            0x01, 0, 0, 0, 0x40, 0, 0, 0, 0, // flag, then the i64
+        ]
+    );
+}
+
+#[test]
+fn little_endian() {
+    let mut writer = Writer::default();
+    writer.u32(0x0102_0304);
+    writer.skip(2);
+    writer.set_bytes(4, &[9, 9]);
+    assert_eq!(writer.as_slice(), [4, 3, 2, 1, 9, 9]);
+}
+
+#[test]
+fn look_ahead() {
+    let bin = [1, 2, 3];
+    for split in 0..=bin.len() {
+        let (head, tail) = bin.split_at(split);
+        let mut reader = Reader::from_segments(head, tail);
+        assert_eq!(reader.remaining(), 3);
+        assert_eq!(reader.peek_u8(), Some(1));
+        assert_eq!(reader.peek_u8(), Some(1));
+        assert_eq!(reader.u16().unwrap(), 0x0201);
+        assert_eq!(reader.offset(), 2);
+        assert_eq!(reader.remaining(), 1);
+        assert_eq!(reader.peek_u8(), Some(3));
+        reader.skip(1).unwrap();
+        assert_eq!(reader.remaining(), 0);
+        assert_eq!(reader.peek_u8(), None);
+        // reads past the end fail
+        assert!(matches!(reader.skip(4), Err(Error::Truncated { .. })));
+        assert!(matches!(reader.u8(), Err(Error::Truncated { .. })));
+        assert_eq!(reader.offset(), 3);
+    }
+}
@@ -0,0 +1,178 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::cancel::CancelToken;
+use fury_core::clock::{Clock, ManualClock};
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_core::resolver::context::{ReadContext, WriteContext};
+use fury_core::serializer::Serializer;
+use fury_core::types::{FieldType, FuryGeneralList};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+static TRIPPED: OnceLock<CancelToken> = OnceLock::new();
+
+fn tripped() -> &'static CancelToken {
+    TRIPPED.get_or_init(CancelToken::new)
+}
+
+// cancels `tripped()` as soon as one is read, as if another thread had
+#[derive(Debug, PartialEq)]
+struct Tripwire(i8);
+
+impl Serializer for Tripwire {
+    fn reserved_space() -> usize {
+        1
+    }
+
+    fn write(&self, context: &mut WriteContext) {
+        context.writer.i8(self.0);
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        tripped().cancel();
+        Ok(Tripwire(context.reader.i8()?))
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::INT8.into()
+    }
+}
+
+impl FuryGeneralList for Tripwire {}
+
+static CLOCK: OnceLock<Arc<ManualClock>> = OnceLock::new();
+
+fn clock() -> &'static Arc<ManualClock> {
+    CLOCK.get_or_init(Default::default)
+}
+
+// moves `clock()` a second forward per value read, as if reading them were slow
+#[derive(Debug, PartialEq)]
+struct Tick(i8);
+
+impl Serializer for Tick {
+    fn reserved_space() -> usize {
+        1
+    }
+
+    fn write(&self, context: &mut WriteContext) {
+        context.writer.i8(self.0);
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        clock().advance(Duration::from_secs(1));
+        Ok(Tick(context.reader.i8()?))
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::INT8.into()
+    }
+}
+
+impl FuryGeneralList for Tick {}
+
+// lengths are kept below 128, nesting gets past `CANCEL_CHECK_INTERVAL` elements
+fn nested<T>(outer: usize, inner: usize, item: impl Fn() -> T) -> Vec<Vec<T>> {
+    (0..outer)
+        .map(|_| (0..inner).map(|_| item()).collect())
+        .collect()
+}
+
+#[test]
+fn not_cancelled() {
+    let fury = Fury::default();
+    let token = CancelToken::new().deadline(Instant::now() + Duration::from_secs(3600));
+    let list = nested(30, 100, || 7i32);
+    let obj: Vec<Vec<i32>> = fury
+        .deserialize_with_cancel(&fury.serialize(&list), &token)
+        .unwrap();
+    assert_eq!(obj, list);
+}
+
+#[test]
+fn cancelled_before_start() {
+    let fury = Fury::default();
+    let bin = fury.serialize(&"payload".to_string());
+    let token = CancelToken::new();
+    token.clone().cancel();
+    assert!(matches!(
+        fury.deserialize_with_cancel::<String>(&bin, &token),
+        Err(Error::Cancelled)
+    ));
+
+    let expired = CancelToken::new().deadline(Instant::now());
+    assert!(matches!(
+        fury.deserialize_with_cancel::<String>(&bin, &expired),
+        Err(Error::Cancelled)
+    ));
+}
+
+#[test]
+fn cancelled_while_reading_elements() {
+    let fury = Fury::default();
+    let list = nested(20, 100, || Tripwire(1));
+    let bin = fury.serialize(&list);
+    assert!(!tripped().is_cancelled());
+
+    // the first element cancels, which is noticed at the next safe point
+    assert!(matches!(
+        fury.deserialize_with_cancel::<Vec<Vec<Tripwire>>>(&bin, tripped()),
+        Err(Error::Cancelled)
+    ));
+    assert_eq!(fury.deserialize::<Vec<Vec<Tripwire>>>(&bin).unwrap(), list);
+}
+
+#[test]
+fn deadline_from_clock() {
+    let manual = Arc::new(ManualClock::new());
+    let fury = Fury::default().clock(manual.clone());
+    let bin = fury.serialize(&"payload".to_string());
+    let token = CancelToken::new().deadline(manual.now() + Duration::from_secs(10));
+
+    // the system clock has moved on, the manual one hasn't
+    std::thread::sleep(Duration::from_millis(5));
+    let token_now = CancelToken::new().deadline(manual.now() + Duration::from_millis(1));
+    assert!(token_now.is_cancelled());
+    assert!(!token_now.is_cancelled_with(manual.as_ref()));
+    assert_eq!(
+        fury.deserialize_with_cancel::<String>(&bin, &token)
+            .unwrap(),
+        "payload"
+    );
+
+    manual.advance(Duration::from_secs(10));
+    assert!(matches!(
+        fury.deserialize_with_cancel::<String>(&bin, &token),
+        Err(Error::Cancelled)
+    ));
+}
+
+#[test]
+fn deadline_passed_while_reading_elements() {
+    let fury = Fury::default().clock(clock().clone());
+    let list = nested(20, 100, || Tick(1));
+    let bin = fury.serialize(&list);
+    // a second per element, the deadline passes long before the first safe point
+    let token = CancelToken::new().deadline(clock().now() + Duration::from_secs(100));
+    assert!(matches!(
+        fury.deserialize_with_cancel::<Vec<Vec<Tick>>>(&bin, &token),
+        Err(Error::Cancelled)
+    ));
+    assert_eq!(fury.deserialize::<Vec<Vec<Tick>>>(&bin).unwrap(), list);
+}
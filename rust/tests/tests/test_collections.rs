@@ -0,0 +1,79 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_core::value::Value;
+use fury_derive::Fury;
+use std::collections::{BTreeMap, BTreeSet, HashSet, LinkedList, VecDeque};
+
+#[derive(Fury, Debug, PartialEq)]
+struct Schedule {
+    slots: BTreeMap<String, i32>,
+    owners: BTreeSet<String>,
+    days: HashSet<i32>,
+    queue: VecDeque<String>,
+    retries: VecDeque<i64>,
+    history: LinkedList<i32>,
+}
+
+fn schedule() -> Schedule {
+    Schedule {
+        slots: BTreeMap::from([(String::from("09:00"), 1), (String::from("10:00"), 2)]),
+        owners: BTreeSet::from([String::from("bo"), String::from("al")]),
+        days: HashSet::from([1, 3, 5]),
+        queue: VecDeque::from([String::from("first"), String::from("second")]),
+        retries: VecDeque::from([3, 2, 1]),
+        history: LinkedList::from([7, 8]),
+    }
+}
+
+#[test]
+fn round_trip() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default().mode(mode);
+        fury.register::<Schedule>(600);
+        let bin = fury.serialize(&schedule());
+        assert_eq!(fury.deserialize::<Schedule>(&bin).unwrap(), schedule());
+    }
+}
+
+#[test]
+fn same_wire_types() {
+    let fury = Fury::default();
+    let queue = VecDeque::from([String::from("a"), String::from("b")]);
+    let read: Vec<String> = fury.deserialize(&fury.serialize(&queue)).unwrap();
+    assert_eq!(read, vec!["a", "b"]);
+    let read: LinkedList<String> = fury.deserialize(&fury.serialize(&read)).unwrap();
+    assert_eq!(
+        read,
+        LinkedList::from([String::from("a"), String::from("b")])
+    );
+
+    let set = BTreeSet::from([3i32, 1, 2]);
+    let read: HashSet<i32> = fury.deserialize(&fury.serialize(&set)).unwrap();
+    assert_eq!(read, HashSet::from([1, 2, 3]));
+    assert_eq!(
+        fury.deserialize_value(&fury.serialize(&set)).unwrap(),
+        Value::Set(vec![Value::I32(1), Value::I32(2), Value::I32(3)])
+    );
+    // unlike `Vec<i64>`, written as a list rather than a primitive array
+    assert_eq!(
+        fury.deserialize_value(&fury.serialize(&VecDeque::from([4i64])))
+            .unwrap(),
+        Value::List(vec![Value::I64(4)])
+    );
+}
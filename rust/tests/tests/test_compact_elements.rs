@@ -0,0 +1,89 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::types::{config_flags, Mode};
+use fury_derive::Fury;
+use std::collections::{BTreeSet, VecDeque};
+
+#[derive(Fury, Debug, PartialEq)]
+struct Inventory {
+    names: Vec<String>,
+    counts: VecDeque<i32>,
+    tags: BTreeSet<String>,
+    notes: Vec<Option<String>>,
+}
+
+fn fury(mode: Mode, compact: bool) -> Fury {
+    let mut fury = Fury::default().mode(mode).compact_elements(compact);
+    fury.register::<Inventory>(101);
+    fury
+}
+
+fn inventory() -> Inventory {
+    Inventory {
+        names: vec!["bolt".into(), "nut".into(), "washer".into()],
+        counts: VecDeque::from([12, 7, 0, -1]),
+        tags: BTreeSet::from(["metal".into(), "small".into()]),
+        notes: vec![None, Some("recount".into())],
+    }
+}
+
+#[test]
+fn plain_elements_without_ref_and_type() {
+    let full = fury(Mode::SchemaConsistent, false).serialize(&inventory());
+    let fury = fury(Mode::SchemaConsistent, true);
+    let bin = fury.serialize(&inventory());
+    // three bytes saved on each of the nine strings and integers, the optional notes keep theirs
+    assert_eq!(bin.len(), full.len() - 9 * 3);
+    assert_ne!(bin[2] & config_flags::IS_COMPACT_ELEMENTS_FLAG, 0);
+    assert_eq!(fury.deserialize::<Inventory>(&bin).unwrap(), inventory());
+}
+
+#[test]
+fn peers_must_agree() {
+    let compact = fury(Mode::SchemaConsistent, true);
+    let full = fury(Mode::SchemaConsistent, false);
+    let err = full
+        .deserialize::<Inventory>(&compact.serialize(&inventory()))
+        .unwrap_err();
+    assert!(err.to_string().contains("written with compact elements"));
+    let err = compact
+        .deserialize::<Inventory>(&full.serialize(&inventory()))
+        .unwrap_err();
+    assert!(err.to_string().contains("written without compact elements"));
+}
+
+#[test]
+fn compatible_mode_keeps_elements_whole() {
+    let bin = fury(Mode::Compatible, true).serialize(&inventory());
+    assert_eq!(bin, fury(Mode::Compatible, false).serialize(&inventory()));
+    assert_eq!(
+        fury(Mode::Compatible, true)
+            .deserialize::<Inventory>(&bin)
+            .unwrap(),
+        inventory()
+    );
+}
+
+#[test]
+fn values_are_rejected() {
+    let fury = fury(Mode::SchemaConsistent, true);
+    let bin = fury.serialize(&inventory());
+    let err = fury.deserialize_value(&bin).unwrap_err();
+    assert!(err.to_string().contains("compact elements"));
+}
@@ -0,0 +1,114 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_core::types::{DecodeMode, Mode};
+use fury_derive::Fury;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Metrics {
+    cpu: f64,
+    load: f64,
+    memory: Option<f64>,
+    samples: Vec<f64>,
+    ratios: Vec<f64>,
+    labels: HashMap<String, f64>,
+}
+
+fn metrics() -> Metrics {
+    Metrics {
+        cpu: 0.75,
+        load: 0.1,
+        memory: Some(1024.0),
+        samples: (0..100).map(|i| i as f64 * 0.25).collect(),
+        ratios: vec![0.5, 0.1, 0.3],
+        labels: HashMap::from([("p99".to_string(), 12.5)]),
+    }
+}
+
+fn mode(compatible: bool) -> Mode {
+    if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    }
+}
+
+#[test]
+fn round_trip_and_shrink() {
+    for compatible in [false, true] {
+        let mut compact = Fury::default().mode(mode(compatible)).compact_floats(true);
+        compact.register::<Metrics>(1);
+        let mut plain = Fury::default().mode(mode(compatible));
+        plain.register::<Metrics>(1);
+
+        let bin = compact.serialize(&metrics());
+        let obj: Metrics = compact.deserialize(&bin).unwrap();
+        assert_eq!(obj, metrics());
+        assert!(bin.len() + 400 < plain.serialize(&metrics()).len());
+    }
+}
+
+#[test]
+fn exact_values() {
+    let fury = Fury::default().compact_floats(true);
+    for value in [
+        0.0,
+        -0.0,
+        0.1,
+        f64::MAX,
+        f64::MIN_POSITIVE,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NAN,
+        f32::MAX as f64,
+        1e-40,
+    ] {
+        let obj: f64 = fury.deserialize(&fury.serialize(&value)).unwrap();
+        assert_eq!(obj.to_bits(), value.to_bits());
+        let obj: Vec<f64> = fury
+            .deserialize(&fury.serialize(&vec![value, 2.0]))
+            .unwrap();
+        assert_eq!(obj[0].to_bits(), value.to_bits());
+    }
+}
+
+#[test]
+fn per_value_flags() {
+    let fury = Fury::default().compact_floats(true);
+    let fits = fury.serialize(&1.5f64).len();
+    let wide = fury.serialize(&0.1f64).len();
+    assert_eq!(wide - fits, 4);
+
+    let fits = fury.serialize(&vec![1.5f64; 10]).len();
+    let wide = fury.serialize(&vec![0.1f64; 10]).len();
+    assert_eq!(wide - fits, 40);
+}
+
+#[test]
+fn borrowed_arrays() {
+    let fury = Fury::default().compact_floats(true);
+    let values: Cow<[f64]> = Cow::Owned(vec![1.5, 2.5]);
+    let bin = fury.serialize(&values);
+    let obj: Cow<[f64]> = fury
+        .deserialize_with_mode(&bin, DecodeMode::Borrowed)
+        .unwrap();
+    // widened from f32, so it can't point into the input
+    assert!(matches!(obj, Cow::Owned(_)));
+    assert_eq!(obj, values);
+}
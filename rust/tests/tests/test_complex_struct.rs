@@ -152,3 +152,51 @@ fn encode_to_obin() {
 
     print!("{:?}", bin);
 }
+
+#[test]
+fn enum_layout_in_fingerprint() {
+    mod v1 {
+        use fury_derive::Fury;
+
+        #[derive(Fury)]
+        pub enum Color {
+            Green,
+            Red,
+        }
+        fury_core::fury_assert_enum_layout!(Color, ["Green", "Red"]);
+    }
+    mod v2 {
+        use fury_derive::Fury;
+
+        #[derive(Fury)]
+        pub enum Color {
+            Red,
+            Green,
+        }
+    }
+    let mut fury1 = Fury::default();
+    fury1.register::<v1::Color>(999);
+    let mut fury2 = Fury::default();
+    fury2.register::<v2::Color>(999);
+    assert_ne!(fury1.schema_fingerprint(), fury2.schema_fingerprint());
+
+    let mut fury3 = Fury::default();
+    fury3.register::<v1::Color>(999);
+    assert_eq!(fury1.schema_fingerprint(), fury3.schema_fingerprint());
+}
+
+#[test]
+fn generated_type_id_accessor() {
+    #[derive(Fury)]
+    struct Registered {
+        id: i32,
+    }
+    #[derive(Fury)]
+    struct Unregistered {
+        id: i32,
+    }
+    let mut fury = Fury::default();
+    fury.register::<Registered>(999);
+    assert_eq!(Registered::fury_type_id(&fury), Some(999));
+    assert_eq!(Unregistered::fury_type_id(&fury), None);
+}
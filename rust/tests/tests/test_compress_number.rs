@@ -0,0 +1,81 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_core::value::Value;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Counter {
+    id: i64,
+    hits: u64,
+}
+
+fn fury(mode: Mode, compress_number: bool) -> Fury {
+    let mut fury = Fury::default().mode(mode).compress_number(compress_number);
+    fury.register::<Counter>(101);
+    fury
+}
+
+#[test]
+fn java_layout() {
+    let fury = fury(Mode::SchemaConsistent, true);
+    // a compressed Java `long`: 4 bytes of the value shifted left by one, or a flag and 8 bytes
+    assert_eq!(&fury.serialize(&5i64)[11..], [10, 0, 0, 0]);
+    assert_eq!(
+        &fury.serialize(&(1i64 << 40))[11..],
+        [1, 0, 0, 0, 0, 0, 1, 0, 0]
+    );
+    assert_eq!(&fury.serialize(&300u64)[11..], [0xac, 0x02]);
+}
+
+#[test]
+fn fields() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let counters = [
+            Counter { id: 7, hits: 3 },
+            Counter {
+                id: i64::MIN,
+                hits: u64::MAX,
+            },
+        ];
+        for counter in counters {
+            let compressed = fury(mode, true);
+            let bytes = compressed.serialize(&counter);
+            assert_eq!(compressed.deserialize::<Counter>(&bytes).unwrap(), counter);
+            assert!(matches!(
+                compressed.deserialize_value(&bytes).unwrap(),
+                Value::Struct { .. }
+            ));
+        }
+        let small = Counter { id: 7, hits: 3 };
+        assert_eq!(
+            fury(mode, false).serialize(&small).len() - fury(mode, true).serialize(&small).len(),
+            4 + 7
+        );
+    }
+}
+
+#[test]
+fn narrowed() {
+    let fury = fury(Mode::SchemaConsistent, true);
+    let bytes = fury.serialize(&-3i64);
+    assert_eq!(fury.deserialize::<i32>(&bytes).unwrap(), -3);
+    let bytes = fury.serialize(&9u64);
+    assert_eq!(fury.deserialize::<u8>(&bytes).unwrap(), 9);
+}
@@ -0,0 +1,108 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::stats::DecodeStats;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Fury, Debug, PartialEq)]
+struct Sample {
+    name: String,
+    values: Vec<i32>,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Batch {
+    samples: Vec<Sample>,
+    labels: HashMap<String, i64>,
+}
+
+type Reports = Arc<Mutex<Vec<(&'static str, DecodeStats)>>>;
+
+fn fury(mode: Mode) -> (Fury, Reports) {
+    let reports: Reports = Arc::default();
+    let collected = reports.clone();
+    let mut fury = Fury::default().mode(mode).stats_collector(Arc::new(
+        move |type_name, stats: &DecodeStats| {
+            collected.lock().unwrap().push((type_name, stats.clone()));
+        },
+    ));
+    fury.register::<Sample>(101);
+    fury.register::<Batch>(102);
+    (fury, reports)
+}
+
+fn batch(samples: usize) -> Batch {
+    Batch {
+        samples: (0..samples)
+            .map(|i| Sample {
+                name: String::from("abcd"),
+                values: vec![i as i32; 10],
+            })
+            .collect(),
+        labels: HashMap::from([(String::from("ab"), 1)]),
+    }
+}
+
+#[test]
+fn per_message() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let (fury, reports) = fury(mode);
+        let bytes = fury.serialize(&batch(3));
+        fury.deserialize::<Batch>(&bytes).unwrap();
+        let reports = reports.lock().unwrap();
+        let [(type_name, stats)] = &reports[..] else {
+            panic!("{} reports", reports.len());
+        };
+        assert!(type_name.ends_with("Batch"));
+        assert_eq!(stats.bytes, bytes.len());
+        // 3 samples, 10 values each and a label
+        assert_eq!(stats.elements, 3 + 3 * 10 + 1);
+        assert_eq!(stats.structs, 1 + 3);
+        let allocated = 3 * std::mem::size_of::<Sample>()
+            + 3 * (4 + 10 * 4)
+            + std::mem::size_of::<(String, i64)>()
+            + 2;
+        assert_eq!(stats.allocated, allocated);
+    }
+}
+
+#[test]
+fn tells_work_apart() {
+    let (fury, reports) = fury(Mode::SchemaConsistent);
+    fury.deserialize::<Batch>(&fury.serialize(&batch(1)))
+        .unwrap();
+    fury.deserialize::<Batch>(&fury.serialize(&batch(100)))
+        .unwrap();
+    // failed reads aren't reported
+    assert!(fury.deserialize::<Batch>(&fury.serialize(&1i32)).is_err());
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 2);
+    assert!(reports[1].1.elements > 50 * reports[0].1.elements);
+}
+
+#[test]
+fn without_collector() {
+    let mut fury = Fury::default();
+    fury.register::<Sample>(101);
+    fury.register::<Batch>(102);
+    let read: Batch = fury.deserialize(&fury.serialize(&batch(2))).unwrap();
+    assert_eq!(read, batch(2));
+}
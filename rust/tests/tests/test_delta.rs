@@ -0,0 +1,155 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use fury_core::fury::Fury;
+use fury_core::types::{config_flags, Mode};
+use fury_core::value::Value;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Series {
+    name: String,
+    #[fury(encode = "delta")]
+    at: Vec<NaiveDateTime>,
+    #[fury(encode = "delta")]
+    values: Vec<i64>,
+    #[fury(encode = "delta")]
+    days: Vec<NaiveDate>,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct PlainSeries {
+    name: String,
+    at: Vec<NaiveDateTime>,
+    values: Vec<i64>,
+    days: Vec<NaiveDate>,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Narrow {
+    #[fury(encode = "delta")]
+    values: Vec<i32>,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Wide {
+    #[fury(encode = "delta")]
+    values: Vec<i64>,
+}
+
+fn timestamp(millis: i64) -> NaiveDateTime {
+    DateTime::from_timestamp_millis(millis).unwrap().naive_utc()
+}
+
+fn series() -> Series {
+    let start = 1_700_000_000_000;
+    Series {
+        name: "cpu".to_string(),
+        at: (0..100).map(|i| timestamp(start + i * 1000)).collect(),
+        values: vec![i64::MIN, i64::MAX, -1, 0, 1, 300, 299],
+        days: (0..20)
+            .map(|i| NaiveDate::from_ymd_opt(2024, 1, 1 + i).unwrap())
+            .collect(),
+    }
+}
+
+fn mode(compatible: bool) -> Mode {
+    if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    }
+}
+
+#[test]
+fn round_trip() {
+    for compatible in [false, true] {
+        let mut fury = Fury::default().mode(mode(compatible));
+        fury.register::<Series>(1);
+        let bytes = fury.serialize(&series());
+        assert_ne!(bytes[2] & config_flags::IS_EXPERIMENTAL_FLAG, 0);
+        let obj: Series = fury.deserialize(&bytes).unwrap();
+        assert_eq!(obj, series());
+    }
+}
+
+#[test]
+fn shrinks_time_series() {
+    let mut fury = Fury::default();
+    fury.register::<Series>(1);
+    fury.register::<PlainSeries>(2);
+    let series = series();
+    let plain = PlainSeries {
+        name: series.name.clone(),
+        at: series.at.clone(),
+        values: series.values.clone(),
+        days: series.days.clone(),
+    };
+    // each timestamp after the first is a six byte delta instead of eight bytes
+    assert!(fury.serialize(&series).len() + 500 < fury.serialize(&plain).len());
+}
+
+#[test]
+fn reads_plain_vectors() {
+    let mut writer = Fury::default().mode(Mode::Compatible);
+    writer.register::<PlainSeries>(1);
+    let mut reader = Fury::default().mode(Mode::Compatible);
+    reader.register::<Series>(1);
+    let plain = PlainSeries {
+        name: "mem".to_string(),
+        at: vec![timestamp(0), timestamp(5)],
+        values: vec![3, 2, 1],
+        days: vec![],
+    };
+    let obj: Series = reader.deserialize(&writer.serialize(&plain)).unwrap();
+    assert_eq!(obj.at, plain.at);
+    assert_eq!(obj.values, plain.values);
+}
+
+#[test]
+fn checks_element_type() {
+    let mut writer = Fury::default().mode(Mode::Compatible);
+    writer.register::<Wide>(1);
+    let mut reader = Fury::default().mode(Mode::Compatible);
+    reader.register::<Narrow>(1);
+    let bin = writer.serialize(&Wide {
+        values: vec![1, 2, 3],
+    });
+    assert!(reader.deserialize::<Narrow>(&bin).is_err());
+
+    let mut fury = Fury::default();
+    fury.register::<Narrow>(1);
+    let narrow = Narrow {
+        values: vec![i32::MIN, i32::MAX, 0],
+    };
+    let obj: Narrow = fury.deserialize(&fury.serialize(&narrow)).unwrap();
+    assert_eq!(obj, narrow);
+}
+
+#[test]
+fn reads_as_value() {
+    let mut fury = Fury::default().mode(Mode::Compatible);
+    fury.register::<Series>(101);
+    let Value::Struct { fields, .. } = fury.deserialize_value(&fury.serialize(&series())).unwrap()
+    else {
+        panic!("expected a struct");
+    };
+    let values = &fields.iter().find(|(name, _)| name == "values").unwrap().1;
+    assert_eq!(values, &Value::I64Array(series().values));
+    let at = &fields.iter().find(|(name, _)| name == "at").unwrap().1;
+    assert!(matches!(at, Value::List(list) if list.len() == 100));
+}
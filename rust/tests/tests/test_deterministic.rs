@@ -0,0 +1,96 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_derive::Fury;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Index {
+    words: HashMap<String, HashMap<i32, String>>,
+}
+
+fn index(keys: impl Iterator<Item = i32>) -> Index {
+    Index {
+        words: keys
+            .map(|i| {
+                let positions = (0..i % 7).map(|p| (p, format!("{i}:{p}"))).collect();
+                (format!("word{i}"), positions)
+            })
+            .collect(),
+    }
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Holder {
+    m: HashMap<Rc<String>, Rc<String>>,
+    keys: HashSet<Rc<String>>,
+}
+
+fn holder(order: impl Iterator<Item = i32>) -> Holder {
+    let shared = Rc::new("shared".to_string());
+    let mut m = HashMap::new();
+    let mut keys = HashSet::new();
+    for i in order {
+        let key = Rc::new(format!("key{i}"));
+        // the key of one entry is the value of the next one
+        m.insert(key.clone(), shared.clone());
+        m.insert(Rc::new(format!("value{i}")), key.clone());
+        keys.insert(key);
+    }
+    m.insert(shared.clone(), shared);
+    Holder { m, keys }
+}
+
+#[test]
+fn shared_refs_are_stable() {
+    let mut fury = Fury::default().deterministic(true);
+    fury.register::<Holder>(1);
+
+    let first = fury.serialize(&holder(0..16));
+    let second = fury.serialize(&holder((0..16).rev()));
+    assert_eq!(first, second);
+
+    let obj: Holder = fury.deserialize(&first).unwrap();
+    assert_eq!(obj, holder(0..16));
+    // still shared after the round trip
+    let shared = obj.m.keys().find(|k| k.as_str() == "shared").unwrap();
+    assert!(Rc::ptr_eq(shared, &obj.m[shared]));
+}
+
+#[test]
+fn map_bytes_are_stable() {
+    let mut fury = Fury::default().deterministic(true);
+    fury.register::<Index>(1);
+
+    // different insertion orders and hasher seeds
+    let first = fury.serialize(&index(0..64));
+    let second = fury.serialize(&index((0..64).rev()));
+    assert_eq!(first, second);
+
+    let obj: Index = fury.deserialize(&first).unwrap();
+    assert_eq!(obj, index(0..64));
+}
+
+#[test]
+fn set_bytes_are_stable() {
+    let fury = Fury::default().deterministic(true);
+    let first: HashSet<String> = (0..64).map(|i| i.to_string()).collect();
+    let second: HashSet<String> = (0..64).rev().map(|i| i.to_string()).collect();
+    assert_eq!(fury.serialize(&first), fury.serialize(&second));
+}
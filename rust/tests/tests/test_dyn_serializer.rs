@@ -0,0 +1,110 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::serializer::{DynSerializer, TypedDynSerializer};
+use fury_derive::Fury;
+use std::any::Any;
+
+mod plugin {
+    use fury_core::serializer::{DynSerializer, TypedDynSerializer};
+    use fury_derive::Fury;
+
+    #[derive(Fury, Debug, PartialEq)]
+    pub struct Event {
+        pub name: String,
+        pub count: i32,
+    }
+
+    // What a dylib would export through an `extern` function.
+    pub fn serializers() -> Vec<Box<dyn DynSerializer>> {
+        vec![Box::new(TypedDynSerializer::<Event>::new("plugin.Event"))]
+    }
+}
+
+#[derive(Fury, Debug)]
+struct Envelope {
+    payload: Box<dyn Any>,
+}
+
+#[test]
+fn shared_across_threads() {
+    let mut fury = Fury::default();
+    for serializer in plugin::serializers() {
+        fury.register_dyn(100, serializer).expect("should register");
+    }
+    let event = plugin::Event {
+        name: "click".to_string(),
+        count: 3,
+    };
+    let bin = std::thread::scope(|scope| scope.spawn(|| fury.serialize(&event)).join().unwrap());
+    assert_eq!(fury.deserialize::<plugin::Event>(&bin).unwrap(), event);
+}
+
+#[test]
+fn register_dyn_roundtrip() {
+    let mut fury = Fury::default();
+    for serializer in plugin::serializers() {
+        fury.register_dyn(100, serializer).expect("should register");
+    }
+    fury.register::<Envelope>(101);
+
+    let bin = fury.serialize(&Envelope {
+        payload: Box::new(plugin::Event {
+            name: "click".to_string(),
+            count: 3,
+        }),
+    });
+    let obj: Envelope = fury.deserialize(&bin).expect("should deserialize");
+    assert_eq!(
+        obj.payload.downcast_ref::<plugin::Event>(),
+        Some(&plugin::Event {
+            name: "click".to_string(),
+            count: 3,
+        })
+    );
+}
+
+#[test]
+fn register_dyn_conflicts() {
+    let mut fury = Fury::default();
+    fury.register_dyn(
+        100,
+        Box::new(TypedDynSerializer::<plugin::Event>::new("plugin.Event")),
+    )
+    .unwrap();
+
+    // same name, another id
+    let serializer: Box<dyn DynSerializer> =
+        Box::new(TypedDynSerializer::<Envelope>::new("plugin.Event"));
+    assert!(fury.register_dyn(200, serializer).is_err());
+
+    // same TypeId, another name
+    let serializer: Box<dyn DynSerializer> =
+        Box::new(TypedDynSerializer::<plugin::Event>::new("plugin.Other"));
+    assert!(fury.register_dyn(200, serializer).is_err());
+
+    // same name and id with another TypeId is treated as the same type
+    let serializer: Box<dyn DynSerializer> =
+        Box::new(TypedDynSerializer::<Envelope>::new("plugin.Event"));
+    assert!(fury.register_dyn(100, serializer).is_ok());
+    assert_eq!(
+        fury.get_class_resolver()
+            .get_type_id_by_name("plugin.Event"),
+        Some(100)
+    );
+}
@@ -0,0 +1,90 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_core::types::{DecodeMode, Mode};
+use fury_derive::Fury;
+use std::borrow::Cow;
+
+#[derive(Fury, Debug, PartialEq, Clone)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Shape<'a> {
+    name: Cow<'a, str>,
+    origin: Point,
+    anchor: Option<Point>,
+    missing: Option<Point>,
+    points: Vec<Point>,
+}
+
+fn fury(elide: bool) -> Fury {
+    let mut fury = Fury::default().elide_declared_types(elide);
+    fury.register::<Point>(101);
+    fury.register::<Shape>(102);
+    fury
+}
+
+fn shape() -> Shape<'static> {
+    let point = Point { x: 1, y: -1 };
+    Shape {
+        name: Cow::Borrowed("square"),
+        origin: point.clone(),
+        anchor: Some(point.clone()),
+        missing: None,
+        points: vec![point; 4],
+    }
+}
+
+#[test]
+fn nested_structs_without_type_ids() {
+    let full = fury(false).serialize(&shape());
+    let fury = fury(true);
+    let bin = fury.serialize(&shape());
+    // two bytes saved on each of the six points
+    assert_eq!(bin.len(), full.len() - 6 * 2);
+    assert_eq!(fury.deserialize::<Shape>(&bin).unwrap(), shape());
+    let borrowed: Shape = fury
+        .deserialize_with_mode(&bin, DecodeMode::Borrowed)
+        .unwrap();
+    assert_eq!(borrowed, shape());
+}
+
+#[test]
+fn compatible_mode_keeps_type_info() {
+    let write = |elide: bool| {
+        let mut fury = Fury::default()
+            .mode(Mode::Compatible)
+            .elide_declared_types(elide);
+        fury.register::<Point>(101);
+        fury.register::<Shape>(102);
+        fury
+    };
+    let bin = write(true).serialize(&shape());
+    assert_eq!(bin, write(false).serialize(&shape()));
+    assert_eq!(write(true).deserialize::<Shape>(&bin).unwrap(), shape());
+}
+
+#[test]
+fn values_are_rejected() {
+    let fury = fury(true);
+    let bin = fury.serialize(&shape());
+    let err = fury.deserialize_value(&bin).unwrap_err();
+    assert!(err.to_string().contains("elided declared types"));
+}
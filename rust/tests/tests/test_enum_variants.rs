@@ -0,0 +1,137 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+enum Shape {
+    Circle { r: f64 },
+    Rect(f64, f64),
+    Empty,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Drawing {
+    name: String,
+    shapes: Vec<Shape>,
+    background: Option<Shape>,
+}
+
+mod v2 {
+    use fury_derive::Fury;
+
+    // `Shape` with a new variant and a new field of `Rect`
+    #[derive(Fury, Debug, PartialEq)]
+    pub enum Shape {
+        Circle { r: f64 },
+        Rect(f64, f64, String),
+        Empty,
+        Line { from: i32, to: i32 },
+    }
+
+    // `Shape` of a reader knowing fewer variants
+    #[derive(Fury, Debug, PartialEq)]
+    pub enum OldShape {
+        Circle {
+            r: f64,
+        },
+        #[fury(other)]
+        Other,
+    }
+}
+
+fn fury(compatible: bool) -> Fury {
+    let mode = if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    };
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Shape>(101);
+    fury.register::<Drawing>(102);
+    fury
+}
+
+fn fury_v2(register: impl FnOnce(&mut Fury)) -> Fury {
+    let mut fury = Fury::default().mode(Mode::Compatible);
+    register(&mut fury);
+    fury
+}
+
+#[test]
+fn variants_with_fields() {
+    for compatible in [false, true] {
+        let fury = fury(compatible);
+        let drawing = Drawing {
+            name: String::from("house"),
+            shapes: vec![
+                Shape::Rect(4.0, 3.0),
+                Shape::Circle { r: 0.5 },
+                Shape::Empty,
+            ],
+            background: Some(Shape::Circle { r: 10.0 }),
+        };
+        let bin = fury.serialize(&drawing);
+        assert_eq!(fury.deserialize::<Drawing>(&bin).unwrap(), drawing);
+    }
+}
+
+#[test]
+fn unknown_variants() {
+    let writer = fury_v2(|fury| fury.register::<v2::Shape>(101));
+    let line = writer.serialize(&vec![
+        v2::Shape::Line { from: 1, to: 2 },
+        v2::Shape::Circle { r: 1.5 },
+    ]);
+
+    let reader = fury_v2(|fury| fury.register::<v2::OldShape>(101));
+    assert_eq!(
+        reader.deserialize::<Vec<v2::OldShape>>(&line).unwrap(),
+        [v2::OldShape::Other, v2::OldShape::Circle { r: 1.5 }]
+    );
+
+    let err = fury(true).deserialize::<Vec<Shape>>(&line).unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        "Unknown variant 3 of `test_enum_variants::Shape`"
+    );
+}
+
+#[test]
+fn appended_fields() {
+    let writer = fury_v2(|fury| fury.register::<v2::Shape>(101));
+    let rect = writer.serialize(&v2::Shape::Rect(4.0, 3.0, String::from("red")));
+    assert_eq!(
+        fury(true).deserialize::<Shape>(&rect).unwrap(),
+        Shape::Rect(4.0, 3.0)
+    );
+}
+
+#[test]
+fn unknown_variants_without_lengths() {
+    let mut writer = Fury::default();
+    writer.register::<v2::Shape>(101);
+    let line = writer.serialize(&v2::Shape::Line { from: 1, to: 2 });
+    let mut reader = Fury::default();
+    reader.register::<v2::OldShape>(101);
+    let err = reader.deserialize::<v2::OldShape>(&line).unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        "Unknown variant 3 of `test_enum_variants::v2::OldShape`"
+    );
+}
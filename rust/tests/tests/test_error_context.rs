@@ -0,0 +1,129 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_core::types::{FieldType, Mode};
+use fury_derive::Fury;
+use std::collections::HashMap;
+
+// what a peer declaring 64-bit categories writes
+mod writer {
+    use fury_derive::Fury;
+
+    #[derive(Fury)]
+    pub struct Animal {
+        pub category: i64,
+    }
+
+    #[derive(Fury)]
+    pub struct Person {
+        pub name: String,
+        pub animal: Vec<Animal>,
+    }
+}
+
+#[derive(Fury, Debug)]
+struct Animal {
+    category: i16,
+}
+
+#[derive(Fury, Debug)]
+struct Person {
+    name: String,
+    animal: Vec<Animal>,
+}
+
+fn person(categories: &[i64]) -> writer::Person {
+    writer::Person {
+        name: String::from("Ann"),
+        animal: categories
+            .iter()
+            .map(|category| writer::Animal {
+                category: *category,
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn field_path_and_offset() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut writer = Fury::default().mode(mode);
+        writer.register::<writer::Animal>(1);
+        writer.register::<writer::Person>(2);
+        let mut reader = Fury::default().mode(mode);
+        reader.register::<Animal>(1);
+        reader.register::<Person>(2);
+
+        let bin = writer.serialize(&person(&[1, 2, 70000]));
+        let err = reader.deserialize::<Person>(&bin).unwrap_err();
+        assert_eq!(err.path(), Some("Person.animal[2].category"));
+        assert!(matches!(
+            err.cause(),
+            Error::NumericOverflow {
+                value: 70000,
+                target: "i16"
+            }
+        ));
+        // right after the value
+        let offset = err.offset().unwrap();
+        assert!(offset > bin.len() / 2 && offset <= bin.len(), "{offset}");
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Value 70000 doesn't fit in i16 at byte {offset} in `Person.animal[2].category`"
+            )
+        );
+
+        // the offset moves with the value
+        let bin = writer.serialize(&person(&[70000, 1, 2]));
+        let earlier = reader.deserialize::<Person>(&bin).unwrap_err();
+        assert_eq!(earlier.path(), Some("Person.animal[0].category"));
+        assert!(earlier.offset().unwrap() < offset);
+    }
+}
+
+#[test]
+fn type_mismatch() {
+    let fury = Fury::default();
+    let mut entries = HashMap::new();
+    entries.insert(String::from("a"), vec![1i32]);
+    let bin = fury.serialize(&entries);
+    let err = fury
+        .deserialize::<HashMap<String, String>>(&bin)
+        .unwrap_err();
+    assert_eq!(err.path(), Some("HashMap<String, String>[0]"));
+    match err.cause() {
+        Error::TypeMismatch { expected, actual } => {
+            assert_eq!(FieldType::try_from(*expected), Ok(FieldType::STRING));
+            assert_eq!(
+                FieldType::try_from(*actual),
+                Ok(FieldType::FuryPrimitiveIntArray)
+            );
+        }
+        other => panic!("unexpected error {other}"),
+    }
+}
+
+#[test]
+fn errors_outside_the_body() {
+    let fury = Fury::default();
+    let err = fury.deserialize::<i32>(b"{}").unwrap_err();
+    assert!(matches!(err, Error::NotFuryPayload { .. }));
+    assert_eq!(err.path(), None);
+    assert!(std::ptr::eq(err.cause(), &err));
+}
@@ -0,0 +1,169 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_core::value::Value;
+use fury_derive::Fury;
+
+fn fury(compatible: bool) -> Fury {
+    if compatible {
+        Fury::default().mode(Mode::Compatible)
+    } else {
+        Fury::default()
+    }
+}
+
+#[test]
+fn renamed_fields() {
+    // the field names of the peer, e.g. a Java class
+    #[derive(Fury, Debug)]
+    #[allow(non_snake_case)]
+    struct JavaUser {
+        age: i32,
+        userName: String,
+        zoneId: Option<String>,
+    }
+
+    #[derive(Fury, Debug)]
+    struct User {
+        age: i32,
+        #[fury(rename = "userName")]
+        user_name: String,
+        // ordered by the name in the message, after `userName`
+        #[fury(rename = "zoneId")]
+        a_zone: Option<String>,
+    }
+
+    for compatible in [false, true] {
+        let mut java = fury(compatible);
+        java.register::<JavaUser>(100);
+        let mut rust = fury(compatible);
+        rust.register::<User>(100);
+        let bytes = java.serialize(&JavaUser {
+            age: 41,
+            userName: String::from("ada"),
+            zoneId: Some(String::from("UTC")),
+        });
+        let user: User = rust.deserialize(&bytes).unwrap();
+        assert_eq!(user.age, 41);
+        assert_eq!(user.user_name, "ada");
+        assert_eq!(user.a_zone.as_deref(), Some("UTC"));
+        assert_eq!(rust.serialize(&user), bytes);
+    }
+}
+
+#[test]
+fn java_naming() {
+    #[derive(Fury, Debug)]
+    #[allow(non_snake_case)]
+    struct JavaOrder {
+        createdAt: i64,
+        customerId: String,
+        id: i32,
+        lineCount2: i32,
+        note: String,
+    }
+
+    #[derive(Fury, Debug)]
+    #[fury(java_naming)]
+    struct Order {
+        id: i32,
+        customer_id: String,
+        created_at: i64,
+        line_count_2: i32,
+        // a rename wins over the naming of the struct
+        #[fury(rename = "note")]
+        comment: String,
+    }
+
+    for compatible in [false, true] {
+        let mut java = fury(compatible);
+        java.register::<JavaOrder>(100);
+        let mut rust = fury(compatible);
+        rust.register::<Order>(100);
+        let bytes = java.serialize(&JavaOrder {
+            createdAt: 1_700_000_000,
+            customerId: String::from("c-7"),
+            id: 12,
+            lineCount2: 3,
+            note: String::from("fragile"),
+        });
+        let order: Order = rust.deserialize(&bytes).unwrap();
+        assert_eq!(order.id, 12);
+        assert_eq!(order.customer_id, "c-7");
+        assert_eq!(order.created_at, 1_700_000_000);
+        assert_eq!(order.line_count_2, 3);
+        assert_eq!(order.comment, "fragile");
+        assert_eq!(rust.serialize(&order), bytes);
+    }
+}
+
+#[test]
+fn fields_with_ids() {
+    #[derive(Fury, Debug)]
+    struct Before {
+        #[fury(id = 1)]
+        name: String,
+        #[fury(id = 9)]
+        visits: i64,
+    }
+
+    // both fields renamed in a refactor, the ids keep them apart from other fields
+    #[derive(Fury, Debug)]
+    struct After {
+        #[fury(id = 1)]
+        display_name: String,
+        #[fury(id = 9)]
+        visit_count: i64,
+    }
+
+    #[derive(Fury, Debug)]
+    struct Untagged {
+        display_name: String,
+        visit_count: i64,
+    }
+
+    let mut before = fury(true);
+    before.register::<Before>(100);
+    let mut after = fury(true);
+    after.register::<After>(100);
+    let bytes = before.serialize(&Before {
+        name: String::from("ada"),
+        visits: 3,
+    });
+    let obj: After = after.deserialize(&bytes).unwrap();
+    assert_eq!((obj.display_name.as_str(), obj.visit_count), ("ada", 3));
+
+    // peers without the ids are matched by name
+    let mut untagged = fury(true);
+    untagged.register::<Untagged>(100);
+    let obj: After = after
+        .deserialize(&untagged.serialize(&Untagged {
+            display_name: String::from("bob"),
+            visit_count: 4,
+        }))
+        .unwrap();
+    assert_eq!((obj.display_name.as_str(), obj.visit_count), ("bob", 4));
+
+    // dynamic readers only know the ids
+    let Value::Struct { fields, .. } = before.deserialize_value(&bytes).unwrap() else {
+        panic!("should be a struct");
+    };
+    let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["#1", "#9"]);
+}
@@ -0,0 +1,78 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::types::config_flags;
+use fury_tests::fixtures::{configs, corpus, fixture_path, fixtures_dir, manifest_line, MANIFEST};
+use std::fs;
+
+const REGENERATE: &str =
+    "regenerate them with `cargo run -p fury-tests --bin gen_fixtures` if the change is deliberate";
+
+#[test]
+fn bytes_match_fixtures() {
+    let dir = fixtures_dir();
+    let corpus = corpus();
+    let mut manifest = String::new();
+    for (config, fury) in configs() {
+        for case in &corpus {
+            let path = fixture_path(&config, &case.name);
+            let bytes = case.write(&fury);
+            let fixture = fs::read(dir.join(&path))
+                .unwrap_or_else(|err| panic!("Missing fixture {path}: {err}, {REGENERATE}"));
+            assert!(bytes == fixture, "Bytes of {path} changed, {REGENERATE}");
+            manifest.push_str(&manifest_line(&path, &bytes));
+            manifest.push('\n');
+        }
+    }
+    let committed = fs::read_to_string(dir.join(MANIFEST)).unwrap();
+    assert!(committed == manifest, "The manifest changed, {REGENERATE}");
+}
+
+#[test]
+fn fixtures_read_back() {
+    let dir = fixtures_dir();
+    let corpus = corpus();
+    for (config, fury) in configs() {
+        for case in &corpus {
+            let path = fixture_path(&config, &case.name);
+            let fixture = fs::read(dir.join(&path)).unwrap();
+            assert!(
+                case.read(&fury, &fixture).unwrap(),
+                "{path} reads back as another value"
+            );
+        }
+    }
+}
+
+#[test]
+fn only_unstable_options_are_experimental() {
+    let corpus = corpus();
+    for (config, fury) in configs() {
+        let unstable = ["-compact_floats", "-packed_bools", "-rle_arrays", "-all"]
+            .iter()
+            .any(|option| config.ends_with(option));
+        for case in &corpus {
+            let bytes = case.write(&fury);
+            if bytes[2] & config_flags::IS_EXPERIMENTAL_FLAG != 0 {
+                assert!(
+                    unstable,
+                    "{} is flagged experimental",
+                    fixture_path(&config, &case.name)
+                );
+            }
+        }
+    }
+}
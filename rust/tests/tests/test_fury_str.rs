@@ -0,0 +1,56 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::{DeserializeOptions, Fury};
+use fury_core::serializer::FuryStr;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Report {
+    title: FuryStr,
+    columns: Vec<FuryStr>,
+}
+
+fn report() -> Report {
+    Report {
+        title: FuryStr::from("sales"),
+        columns: (0..100)
+            .map(|i| FuryStr::from(format!("column {i}").as_str()))
+            .collect(),
+    }
+}
+
+#[test]
+fn fury_str_roundtrip() {
+    for shared in [false, true] {
+        let mut fury = Fury::default()
+            .deserialize_options(DeserializeOptions::default().shared_string_buffer(shared));
+        fury.register::<Report>(1);
+        let obj: Report = fury.deserialize(&fury.serialize(&report())).unwrap();
+        assert_eq!(obj, report());
+        assert_eq!(&*obj.columns[42], "column 42");
+    }
+}
+
+#[test]
+fn fury_str_interoperates_with_string() {
+    let fury = Fury::default()
+        .deserialize_options(DeserializeOptions::default().shared_string_buffer(true));
+    let strings: Vec<String> = vec!["a".to_string(), "bc".to_string()];
+    let obj: Vec<FuryStr> = fury.deserialize(&fury.serialize(&strings)).unwrap();
+    assert_eq!(obj, vec![FuryStr::from("a"), FuryStr::from("bc")]);
+}
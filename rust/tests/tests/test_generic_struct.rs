@@ -0,0 +1,131 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::serializer::StructSerializer;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+use std::borrow::Cow;
+
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Wrapper<T> {
+    inner: T,
+    version: i32,
+}
+
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Pair<K, V>
+where
+    K: Default,
+{
+    key: K,
+    value: V,
+}
+
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Named<'a, T> {
+    name: Cow<'a, str>,
+    value: T,
+}
+
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn fury(mode: Mode) -> Fury {
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Point>(101);
+    fury.register::<Wrapper<i64>>(102);
+    fury.register::<Wrapper<String>>(103);
+    fury.register::<Wrapper<Point>>(104);
+    fury.register::<Pair<String, Vec<Point>>>(105);
+    fury.register::<Named<'static, Point>>(106);
+    fury
+}
+
+#[test]
+fn round_trip() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let fury = fury(mode);
+        let number = Wrapper {
+            inner: 7i64,
+            version: 1,
+        };
+        let bin = fury.serialize(&number);
+        assert_eq!(fury.deserialize::<Wrapper<i64>>(&bin).unwrap(), number);
+
+        let text = Wrapper {
+            inner: "text".to_string(),
+            version: 2,
+        };
+        let bin = fury.serialize(&text);
+        assert_eq!(fury.deserialize::<Wrapper<String>>(&bin).unwrap(), text);
+
+        let point = Wrapper {
+            inner: Point { x: 1, y: 2 },
+            version: 3,
+        };
+        let bin = fury.serialize(&point);
+        assert_eq!(fury.deserialize::<Wrapper<Point>>(&bin).unwrap(), point);
+
+        let pair = Pair {
+            key: "points".to_string(),
+            value: vec![Point { x: 3, y: 4 }],
+        };
+        let bin = fury.serialize(&pair);
+        assert_eq!(
+            fury.deserialize::<Pair<String, Vec<Point>>>(&bin).unwrap(),
+            pair
+        );
+
+        let named = Named {
+            name: Cow::Borrowed("origin"),
+            value: Point::default(),
+        };
+        let bin = fury.serialize(&named);
+        let read: Named<Point> = fury.deserialize_borrowed(&bin).unwrap();
+        assert_eq!(read, named);
+        assert!(matches!(read.name, Cow::Borrowed(_)));
+    }
+}
+
+#[test]
+fn instantiations_are_types_of_their_own() {
+    let fury = fury(Mode::SchemaConsistent);
+    assert_eq!(Wrapper::<i64>::fury_type_id(&fury), Some(102));
+    assert_eq!(Wrapper::<String>::fury_type_id(&fury), Some(103));
+    assert_eq!(Wrapper::<i32>::fury_type_id(&fury), None);
+
+    // hashed with the type of `inner` they have
+    let hashes = [
+        Wrapper::<i64>::struct_hash(&fury),
+        Wrapper::<String>::struct_hash(&fury),
+        Wrapper::<Point>::struct_hash(&fury),
+    ];
+    assert_ne!(hashes[0], hashes[1]);
+    assert_ne!(hashes[1], hashes[2]);
+    assert_ne!(hashes[0], hashes[2]);
+
+    // one instantiation can't be read as another
+    let bin = fury.serialize(&Wrapper {
+        inner: 7i64,
+        version: 1,
+    });
+    assert!(fury.deserialize::<Wrapper<String>>(&bin).is_err());
+}
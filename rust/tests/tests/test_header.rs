@@ -0,0 +1,97 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::types::{config_flags, Mode, MAGIC_NUMBER};
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Point {
+    x: i32,
+}
+
+fn fury(mode: Mode) -> Fury {
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Point>(101);
+    fury
+}
+
+#[test]
+fn flags() {
+    let bytes = fury(Mode::SchemaConsistent).serialize(&Point { x: 1 });
+    assert_eq!(u16::from_le_bytes([bytes[0], bytes[1]]), MAGIC_NUMBER);
+    assert_eq!(
+        bytes[2],
+        config_flags::IS_LITTLE_ENDIAN_FLAG | config_flags::IS_CROSS_LANGUAGE_FLAG
+    );
+}
+
+#[test]
+fn null_root() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let fury = fury(mode);
+        // only the magic number and the bitmap, as Java writes a null root
+        let bytes = fury.serialize(&None::<Point>);
+        assert_eq!(
+            bytes,
+            [
+                0xd4,
+                0x62,
+                config_flags::IS_NULL_FLAG
+                    | config_flags::IS_LITTLE_ENDIAN_FLAG
+                    | config_flags::IS_CROSS_LANGUAGE_FLAG
+            ]
+        );
+        assert_eq!(fury.deserialize::<Option<Point>>(&bytes).unwrap(), None);
+        assert_eq!(fury.deserialize::<Option<String>>(&bytes).unwrap(), None);
+        assert!(fury.deserialize::<Point>(&bytes).is_err());
+        // Java leaves the other flags out of a null root
+        assert_eq!(
+            fury.deserialize::<Option<Point>>(&[0xd4, 0x62, config_flags::IS_NULL_FLAG])
+                .unwrap(),
+            None
+        );
+    }
+}
+
+fn with_bitmap(set: u8, cleared: u8) -> Vec<u8> {
+    let mut bytes = fury(Mode::SchemaConsistent).serialize(&Point { x: 1 });
+    bytes[2] = (bytes[2] | set) & !cleared;
+    bytes
+}
+
+#[test]
+fn rejected() {
+    let fury = fury(Mode::SchemaConsistent);
+    for (bytes, error) in [
+        (
+            with_bitmap(0, config_flags::IS_LITTLE_ENDIAN_FLAG),
+            "big-endian",
+        ),
+        (
+            with_bitmap(0, config_flags::IS_CROSS_LANGUAGE_FLAG),
+            "Java's native format",
+        ),
+        (
+            with_bitmap(config_flags::IS_OUT_OF_BAND_FLAG, 0),
+            "out-of-band",
+        ),
+    ] {
+        let message = fury.deserialize::<Point>(&bytes).unwrap_err().to_string();
+        assert!(message.contains(error), "{message}");
+    }
+}
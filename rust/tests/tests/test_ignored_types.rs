@@ -0,0 +1,148 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_core::value::Value;
+use fury_derive::Fury;
+use std::any::Any;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Order {
+    id: i64,
+    item: String,
+}
+
+mod retired {
+    use fury_derive::Fury;
+
+    // a message type the reader no longer models
+    #[derive(Fury, Debug, PartialEq)]
+    pub struct Heartbeat {
+        pub seq: i64,
+        pub tags: Vec<String>,
+    }
+
+    #[derive(Fury, Debug, PartialEq)]
+    pub struct Batch {
+        pub first: Option<Heartbeat>,
+        pub second: Option<super::Order>,
+    }
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Batch {
+    first: Option<Order>,
+    second: Option<Order>,
+}
+
+// compatible mode writes type metas, the fields of ignored values are skipped with those
+fn writer(mode: Mode) -> Fury {
+    let guard = mode == Mode::SchemaConsistent;
+    let mut fury = Fury::default().mode(mode).struct_length_guard(guard);
+    fury.register::<retired::Heartbeat>(42);
+    fury.register::<Order>(101);
+    fury.register::<retired::Batch>(102);
+    fury
+}
+
+fn reader(mode: Mode) -> Fury {
+    let guard = mode == Mode::SchemaConsistent;
+    let mut fury = Fury::default().mode(mode).struct_length_guard(guard);
+    fury.register::<Order>(101);
+    fury.register::<Batch>(102);
+    fury.ignore_type_id(42);
+    fury
+}
+
+fn heartbeat() -> retired::Heartbeat {
+    retired::Heartbeat {
+        seq: 9,
+        tags: vec![String::from("edge")],
+    }
+}
+
+fn order() -> Order {
+    Order {
+        id: 1,
+        item: String::from("tea"),
+    }
+}
+
+#[test]
+fn root_messages() {
+    let writer = writer(Mode::SchemaConsistent);
+    let reader = reader(Mode::SchemaConsistent);
+    let stream = [
+        writer.serialize(&Some(heartbeat())),
+        writer.serialize(&Some(order())),
+    ];
+    let read: Vec<Option<Order>> = stream
+        .iter()
+        .map(|bin| reader.deserialize(bin).unwrap())
+        .collect();
+    assert_eq!(read, vec![None, Some(order())]);
+}
+
+#[test]
+fn nested_messages() {
+    let writer = writer(Mode::SchemaConsistent);
+    let batch = retired::Batch {
+        first: Some(heartbeat()),
+        second: Some(order()),
+    };
+    let read: Batch = reader(Mode::SchemaConsistent)
+        .deserialize(&writer.serialize(&batch))
+        .unwrap();
+    assert_eq!(
+        read,
+        Batch {
+            first: None,
+            second: Some(order()),
+        }
+    );
+}
+
+#[test]
+fn ignored_values() {
+    let writer = writer(Mode::Compatible);
+    let messages: Vec<Box<dyn Any>> = vec![Box::new(heartbeat()), Box::new(order())];
+    let value = reader(Mode::Compatible)
+        .deserialize_value(&writer.serialize(&messages))
+        .unwrap();
+    let Value::List(items) = value else {
+        panic!("{value:?}")
+    };
+    assert_eq!(items[0], Value::Ignored { type_id: 42 });
+    assert!(matches!(items[1], Value::Struct { .. }));
+    assert!(Fury::default().serialize_value(&items[0]).is_err());
+}
+
+#[test]
+fn unskippable_values() {
+    let mut writer = Fury::default();
+    writer.register::<retired::Heartbeat>(42);
+    let mut reader = Fury::default();
+    reader.ignore_type_id(42);
+    let err = reader
+        .deserialize::<Option<Order>>(&writer.serialize(&Some(heartbeat())))
+        .unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        "Can't skip a value of the ignored type 42, it must be written with its type meta or `Fury::struct_length_guard`"
+    );
+}
@@ -0,0 +1,142 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_core::ipc::{ShmReader, ShmWriter, SHM_HEADER_SIZE};
+use fury_core::types::Mode;
+use fury_derive::Fury;
+use std::thread;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Tick {
+    seq: i64,
+    symbol: String,
+    prices: Vec<f64>,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Other {
+    id: i32,
+}
+
+fn tick(seq: i64) -> Tick {
+    Tick {
+        seq,
+        symbol: format!("SYM{}", seq % 7),
+        prices: (0..seq % 5).map(|i| i as f64).collect(),
+    }
+}
+
+fn fury(mode: Mode) -> Fury {
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Tick>(1);
+    fury
+}
+
+/// Stands in for a shared mapping, 8-byte aligned.
+fn region(len: usize) -> Vec<u64> {
+    vec![0; len / 8]
+}
+
+#[test]
+fn wraps_around_the_ring() {
+    let fury = fury(Mode::SchemaConsistent);
+    let mut memory = region(SHM_HEADER_SIZE + 256);
+    let len = memory.len() * 8;
+    let ptr = memory.as_mut_ptr().cast::<u8>();
+    let mut writer = unsafe { ShmWriter::create(ptr, len, &fury) }.unwrap();
+    let mut reader = unsafe { ShmReader::attach(ptr, len, &fury) }.unwrap();
+
+    assert_eq!(reader.recv::<Tick>(&fury).unwrap(), None);
+    for seq in 0..100 {
+        assert!(writer.send(&fury, &tick(seq)).unwrap());
+        assert!(writer.send(&fury, &tick(seq + 1000)).unwrap());
+        assert_eq!(reader.recv::<Tick>(&fury).unwrap(), Some(tick(seq)));
+        assert_eq!(reader.recv::<Tick>(&fury).unwrap(), Some(tick(seq + 1000)));
+    }
+    assert_eq!(writer.free_space(), 256);
+}
+
+#[test]
+fn full_ring_refuses_messages() {
+    let fury = fury(Mode::SchemaConsistent);
+    let mut memory = region(SHM_HEADER_SIZE + 128);
+    let len = memory.len() * 8;
+    let ptr = memory.as_mut_ptr().cast::<u8>();
+    let mut writer = unsafe { ShmWriter::create(ptr, len, &fury) }.unwrap();
+    let mut reader = unsafe { ShmReader::attach(ptr, len, &fury) }.unwrap();
+
+    let mut sent = 0;
+    while writer.send(&fury, &tick(sent)).unwrap() {
+        sent += 1;
+    }
+    assert!(sent > 0);
+    assert_eq!(reader.recv::<Tick>(&fury).unwrap(), Some(tick(0)));
+    assert!(writer.send(&fury, &tick(sent)).unwrap());
+    assert!(writer.send_bytes(&[0; 200]).is_err());
+}
+
+#[test]
+fn handshake() {
+    let writer_fury = fury(Mode::SchemaConsistent);
+    let mut memory = region(SHM_HEADER_SIZE + 128);
+    let len = memory.len() * 8;
+    let ptr = memory.as_mut_ptr().cast::<u8>();
+    assert!(unsafe { ShmReader::attach(ptr, len, &writer_fury) }.is_err());
+
+    let _writer = unsafe { ShmWriter::create(ptr, len, &writer_fury) }.unwrap();
+    let mut drifted = fury(Mode::SchemaConsistent);
+    drifted.register::<Other>(2);
+    assert!(unsafe { ShmReader::attach(ptr, len, &drifted) }.is_err());
+    assert!(unsafe { ShmReader::attach(ptr, len, &fury(Mode::Compatible)) }.is_err());
+    assert!(unsafe { ShmReader::attach(ptr, len - 8, &writer_fury) }.is_err());
+
+    let writer_fury = fury(Mode::Compatible);
+    let _writer = unsafe { ShmWriter::create(ptr, len, &writer_fury) }.unwrap();
+    let mut drifted = fury(Mode::Compatible);
+    drifted.register::<Other>(2);
+    assert!(unsafe { ShmReader::attach(ptr, len, &drifted) }.is_ok());
+}
+
+#[test]
+fn across_threads() {
+    let mut memory = region(SHM_HEADER_SIZE + 1024);
+    let len = memory.len() * 8;
+    let ptr = memory.as_mut_ptr().cast::<u8>();
+    let fury_w = fury(Mode::Compatible);
+    let fury_r = fury(Mode::Compatible);
+    let mut writer = unsafe { ShmWriter::create(ptr, len, &fury_w) }.unwrap();
+    let mut reader = unsafe { ShmReader::attach(ptr, len, &fury_r) }.unwrap();
+
+    let producer = thread::spawn(move || {
+        for seq in 0..2000 {
+            while !writer.send(&fury_w, &tick(seq)).unwrap() {
+                thread::yield_now();
+            }
+        }
+    });
+    let mut seq = 0;
+    while seq < 2000 {
+        match reader.recv::<Tick>(&fury_r).unwrap() {
+            Some(obj) => {
+                assert_eq!(obj, tick(seq));
+                seq += 1;
+            }
+            None => thread::yield_now(),
+        }
+    }
+    producer.join().unwrap();
+}
@@ -0,0 +1,84 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::serializer::StructSerializer;
+use fury_core::types::struct_layout;
+use fury_derive::Fury;
+use std::collections::HashMap;
+
+// `Order` as the Java side declares it, the layout below generated there
+#[derive(Fury, Debug, PartialEq)]
+struct Order {
+    id: i64,
+    #[fury(rename = "customerName")]
+    customer_name: String,
+    lines: Vec<Line>,
+    #[fury(id = 7)]
+    tags: HashMap<String, String>,
+    note: Option<String>,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Line {
+    sku: String,
+    quantity: i32,
+}
+
+const ORDER_LAYOUT: &str = "customerName:13,id:9,lines:25,note:13,#7:30";
+
+fn fury() -> Fury {
+    let mut fury = Fury::default();
+    fury.register::<Order>(101);
+    fury.register::<Line>(102);
+    fury
+}
+
+#[test]
+fn matches_checked_in() {
+    let fury = fury();
+    assert_eq!(struct_layout::<Order>(&fury), ORDER_LAYOUT);
+    fury_core::fury_assert_compatible!(fury, Order, ORDER_LAYOUT);
+    fury_core::fury_assert_compatible!(fury, Line, "quantity:7,sku:13");
+}
+
+#[test]
+fn struct_hash() {
+    let fury = fury();
+    // what Java computes for the classes, over the type ids of the layouts in the same order
+    assert_eq!(Order::struct_hash(&fury), 498993917);
+    assert_eq!(Line::struct_hash(&fury), 16567);
+}
+
+mod edited {
+    use fury_derive::Fury;
+
+    // `quantity` widened to `i64`, which Java's `int` can't read
+    #[derive(Fury)]
+    pub struct Line {
+        pub sku: String,
+        pub quantity: i64,
+    }
+}
+
+#[test]
+#[should_panic(expected = "layout of `edited::Line` changed incompatibly")]
+fn edited_type() {
+    let mut fury = Fury::default();
+    fury.register::<edited::Line>(102);
+    fury_core::fury_assert_compatible!(fury, edited::Line, "quantity:7,sku:13");
+}
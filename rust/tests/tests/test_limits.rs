@@ -0,0 +1,231 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::buffer::Writer;
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+use std::collections::HashMap;
+
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Node {
+    children: Vec<Node>,
+}
+
+fn chain(depth: usize) -> Node {
+    (1..depth).fold(Node::default(), |node, _| Node {
+        children: vec![node],
+    })
+}
+
+fn fury(mode: Mode) -> Fury {
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Node>(101);
+    fury
+}
+
+#[test]
+fn deep_nesting_fails() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let bin = fury(mode).serialize(&chain(10));
+        assert_eq!(fury(mode).deserialize::<Node>(&bin).unwrap(), chain(10));
+        let shallow = {
+            let mut fury = Fury::default().mode(mode).max_depth(5);
+            fury.register::<Node>(101);
+            fury
+        };
+        assert!(matches!(
+            shallow.deserialize::<Node>(&bin).unwrap_err().cause(),
+            Error::LimitExceeded {
+                limit: "max_depth",
+                value: 6,
+                max: 5
+            }
+        ));
+        // values nest as deeply as the structs they hold
+        let err = shallow.deserialize_value(&bin).unwrap_err();
+        assert!(matches!(err.cause(), Error::LimitExceeded { max: 5, .. }));
+    }
+}
+
+#[test]
+fn default_depth_is_bounded() {
+    let fury = fury(Mode::SchemaConsistent);
+    let bin = fury.serialize(&chain(1000));
+    let err = fury.deserialize::<Node>(&bin).unwrap_err();
+    assert!(matches!(
+        err.cause(),
+        Error::LimitExceeded {
+            limit: "max_depth",
+            ..
+        }
+    ));
+    assert_eq!(
+        err.cause().to_string(),
+        "129 exceeds the limit of 128 set by `Fury::max_depth`"
+    );
+}
+
+#[test]
+fn corrupt_lengths_fail() {
+    let fury = Fury::default().max_collection_len(1000);
+    let mut bin = fury.serialize(&vec![String::from("a")]);
+    // the length follows the head, the ref flag and the type id, claim `i32::MAX` elements
+    assert_eq!(bin[11], 1);
+    bin.splice(11..12, [0xff, 0xff, 0xff, 0xff, 0x07]);
+    assert!(matches!(
+        fury.deserialize::<Vec<String>>(&bin).unwrap_err().cause(),
+        Error::LimitExceeded {
+            limit: "max_collection_len",
+            value: 2147483647,
+            max: 1000
+        }
+    ));
+    // negative lengths never pass
+    bin.splice(11..16, [0xff, 0xff, 0xff, 0xff, 0x0f]);
+    let err = fury.deserialize::<Vec<String>>(&bin).unwrap_err();
+    assert_eq!(err.cause().to_string(), "Negative length -1");
+    assert!(Fury::default()
+        .max_collection_len(2)
+        .deserialize::<Vec<i64>>(&fury.serialize(&vec![1i64, 2, 3]))
+        .is_err());
+}
+
+#[test]
+fn large_messages_fail() {
+    let fury = Fury::default().max_buffer_size(64);
+    let small = fury.serialize(&String::from("short"));
+    assert_eq!(fury.deserialize::<String>(&small).unwrap(), "short");
+    let large = fury.serialize(&"long".repeat(100));
+    assert!(matches!(
+        fury.deserialize::<String>(&large),
+        Err(Error::LimitExceeded {
+            limit: "max_buffer_size",
+            max: 64,
+            ..
+        })
+    ));
+    // a frame claiming 4 GB is rejected before anything is read
+    let frame = [0xff, 0xff, 0xff, 0xff];
+    assert!(matches!(
+        fury.deserialize_from_reader::<String, _>(&mut &frame[..]),
+        Err(Error::LimitExceeded {
+            value: 4294967295,
+            ..
+        })
+    ));
+}
+
+// the message of `value` with the length following the head, the ref flag and the type id
+// replaced by `len`, written as a varint
+fn forged<T: fury_core::serializer::Serializer>(fury: &Fury, value: &T, len: u64) -> Vec<u8> {
+    let bin = fury.serialize(value);
+    let mut header = Writer::default();
+    header.var_uint64(len);
+    let mut forged = bin[..11].to_vec();
+    forged.extend_from_slice(&header.dump());
+    forged.extend_from_slice(&bin[12..]);
+    forged
+}
+
+#[test]
+fn forged_lengths_fail_before_allocating() {
+    let fury = Fury::default()
+        .max_collection_len(1000)
+        .max_buffer_size(1 << 20);
+    // a UTF-8 string of 2^34 bytes
+    let bin = forged(&fury, &String::from("a"), (1 << 34 << 2) | 2);
+    let err = fury.deserialize::<String>(&bin).unwrap_err();
+    assert!(matches!(
+        err.cause(),
+        Error::Truncated {
+            needed: 17179869184,
+            ..
+        }
+    ));
+
+    // within `max_collection_len` but more than the input holds
+    let fury = Fury::default();
+    let bin = forged(&fury, &vec![String::from("a")], 1 << 30);
+    let err = fury.deserialize::<Vec<String>>(&bin).unwrap_err();
+    assert!(matches!(err.cause(), Error::Truncated { .. }), "{err}");
+    let bin = forged(&fury, &vec![1u8], 1 << 30);
+    let err = fury.deserialize::<Vec<u8>>(&bin).unwrap_err();
+    assert!(matches!(err.cause(), Error::Truncated { .. }), "{err}");
+    let bin = forged(&fury, &vec![1i64], 1 << 30);
+    let err = fury.deserialize::<Vec<i64>>(&bin).unwrap_err();
+    assert!(matches!(err.cause(), Error::Truncated { .. }), "{err}");
+}
+
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Order {
+    id: i64,
+    customer: String,
+    lines: Vec<Line>,
+    tags: HashMap<String, Vec<i32>>,
+    note: Option<String>,
+}
+
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Line {
+    sku: String,
+    quantity: u32,
+    price: f64,
+}
+
+fn order() -> Order {
+    Order {
+        id: 42,
+        customer: String::from("ada"),
+        lines: vec![
+            Line {
+                sku: String::from("a-1"),
+                quantity: 3,
+                price: 2.5,
+            },
+            Line {
+                sku: String::from("b-22"),
+                quantity: 1,
+                price: 10.0,
+            },
+        ],
+        tags: HashMap::from([(String::from("rush"), vec![1, 2, 3])]),
+        note: Some(String::from("leave at the door")),
+    }
+}
+
+#[test]
+fn truncated_and_corrupt_messages_dont_panic() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default().mode(mode);
+        fury.register::<Order>(102);
+        fury.register::<Line>(103);
+        let bin = fury.serialize(&order());
+        for len in 0..bin.len() {
+            assert!(fury.deserialize::<Order>(&bin[..len]).is_err(), "{len}");
+        }
+        for at in 0..bin.len() {
+            for byte in [0x00, 0x7f, 0x80, 0xff] {
+                let mut corrupt = bin.clone();
+                corrupt[at] = byte;
+                // may still read, if only into a different order
+                let _ = fury.deserialize::<Order>(&corrupt);
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::manifest::{register_hook, register_hook_with, Manifest};
+use fury_core::types::Mode;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Order {
+    id: i64,
+    items: Vec<Item>,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Item {
+    sku: String,
+    count: i32,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Unhooked {
+    id: i64,
+}
+
+fn order_fury() -> Fury {
+    let mut fury = Fury::default().mode(Mode::Compatible);
+    fury.register::<Order>(100);
+    fury.register::<Item>(101);
+    fury
+}
+
+#[test]
+fn from_manifest() {
+    register_hook::<Order>();
+    register_hook::<Item>();
+    let exporter = order_fury();
+    let manifest = exporter.export_manifest();
+    let text = String::from_utf8(manifest.clone()).unwrap();
+    assert!(text.starts_with("fury manifest 1\nmode compatible\n"));
+    assert!(text.ends_with("type 100 test_manifest::Order\ntype 101 test_manifest::Item\n"));
+
+    let fury = Fury::from_manifest(&manifest).unwrap();
+    assert!(fury.get_mode() == &Mode::Compatible);
+    assert_eq!(fury.schema_fingerprint(), exporter.schema_fingerprint());
+    assert_eq!(Item::fury_type_id(&fury), Some(101));
+    let order = Order {
+        id: 1,
+        items: vec![Item {
+            sku: String::from("a-1"),
+            count: 2,
+        }],
+    };
+    let obj: Order = fury.deserialize(&exporter.serialize(&order)).unwrap();
+    assert_eq!(obj, order);
+}
+
+#[test]
+fn manifest_mismatches() {
+    register_hook::<Order>();
+    register_hook::<Item>();
+    let mut exporter = order_fury();
+    exporter.register::<Unhooked>(102);
+    assert_eq!(
+        Fury::from_manifest(&exporter.export_manifest())
+            .err()
+            .unwrap()
+            .to_string(),
+        "Type `test_manifest::Unhooked` of the manifest has no registration hook, see `fury_core::manifest::register_hook`"
+    );
+
+    // a hook registering another type than the one listed
+    register_hook_with("my_app::Renamed", |fury, id| {
+        fury.register::<Unhooked>(id);
+        Ok(())
+    });
+    let mut manifest = Manifest::of(&order_fury());
+    manifest.types.push((102, String::from("my_app::Renamed")));
+    assert_eq!(
+        manifest.to_fury().err().unwrap().to_string(),
+        "Registration hook of `my_app::Renamed` didn't register it with id 102"
+    );
+
+    // the peer's `Item` has other fields
+    let mut manifest = Manifest::of(&order_fury());
+    manifest.fingerprint += 1;
+    assert!(manifest
+        .to_fury()
+        .err()
+        .unwrap()
+        .to_string()
+        .starts_with("Registered types don't match the manifest"));
+
+    assert!(Fury::from_manifest(b"fury manifest 1\nmode lenient\n").is_err());
+    assert!(Fury::from_manifest(b"mode compatible\n").is_err());
+}
@@ -0,0 +1,93 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_core::serializer::{MapKind, MapWithKind};
+use fury_core::value::Value;
+use fury_derive::Fury;
+use indexmap::IndexMap;
+use std::collections::{BTreeMap, HashMap};
+
+fn entries() -> Vec<(String, i32)> {
+    vec![(String::from("b"), 2), (String::from("a"), 1)]
+}
+
+fn written_as(kind: MapKind) -> Vec<u8> {
+    let map: IndexMap<String, i32> = entries().into_iter().collect();
+    Fury::default().serialize(&MapWithKind::new(kind, map))
+}
+
+#[test]
+fn any_kind_into_any_map() {
+    let fury = Fury::default();
+    for kind in [MapKind::Hash, MapKind::Linked, MapKind::Tree] {
+        let bytes = written_as(kind);
+        let hash: HashMap<String, i32> = fury.deserialize(&bytes).unwrap();
+        assert_eq!(hash, entries().into_iter().collect());
+        let tree: BTreeMap<String, i32> = fury.deserialize(&bytes).unwrap();
+        assert_eq!(tree, entries().into_iter().collect());
+        let index: IndexMap<String, i32> = fury.deserialize(&bytes).unwrap();
+        assert_eq!(index.into_iter().collect::<Vec<_>>(), entries());
+        let value = fury.deserialize_value(&bytes).unwrap();
+        assert!(matches!(value, Value::Map(entries) if entries.len() == 2));
+    }
+}
+
+#[test]
+fn kind_is_kept() {
+    let fury = Fury::default();
+    for kind in [MapKind::Hash, MapKind::Linked, MapKind::Tree] {
+        let bytes = written_as(kind);
+        let read: MapWithKind<IndexMap<String, i32>> = fury.deserialize(&bytes).unwrap();
+        assert_eq!(read.kind, kind);
+        assert_eq!(fury.serialize(&read), bytes);
+    }
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Config {
+    ordered: MapWithKind<IndexMap<String, i32>>,
+    sorted: Option<MapWithKind<BTreeMap<String, i32>>>,
+}
+
+#[test]
+fn kinds_of_fields() {
+    let mut fury = Fury::default();
+    fury.register::<Config>(101);
+    let config = Config {
+        ordered: MapWithKind::new(MapKind::Linked, entries().into_iter().collect()),
+        sorted: Some(MapWithKind::new(
+            MapKind::Tree,
+            entries().into_iter().collect(),
+        )),
+    };
+    let read: Config = fury.deserialize(&fury.serialize(&config)).unwrap();
+    assert_eq!(read, config);
+}
+
+#[test]
+fn not_a_map() {
+    let fury = Fury::default();
+    let bytes = fury.serialize(&vec![String::from("a")]);
+    let err = fury
+        .deserialize::<MapWithKind<HashMap<String, i32>>>(&bytes)
+        .unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        "Invalid field type, expected a map, actual:25"
+    );
+    assert!(fury.deserialize::<BTreeMap<String, i32>>(&bytes).is_err());
+}
@@ -0,0 +1,214 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_core::value::Value;
+use fury_derive::Fury;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+const NULL: u8 = 253;
+const REF: u8 = 254;
+const NOT_NULL: u8 = 255;
+const REF_VALUE: u8 = 0;
+
+fn string(s: &str) -> Vec<u8> {
+    let mut bytes = vec![13, 0, (s.len() << 2) as u8];
+    bytes.extend(s.bytes());
+    bytes
+}
+
+// a `LinkedHashMap` as Java writes it with reference tracking, each key and each value
+// behind its own ref flag: {null: "x", "a": null, "b": shared, "c": shared}
+fn java_map() -> Vec<u8> {
+    let fury = Fury::default().per_entry_maps(true);
+    let mut bytes = fury.serialize(&HashMap::<String, String>::new());
+    bytes.truncate(bytes.len() - 4);
+    bytes.extend([NOT_NULL, 11, 1, 4]);
+    bytes.push(NULL);
+    bytes.push(REF_VALUE);
+    bytes.extend(string("x"));
+    bytes.push(NOT_NULL);
+    bytes.extend(string("a"));
+    bytes.push(NULL);
+    bytes.push(NOT_NULL);
+    bytes.extend(string("b"));
+    bytes.push(REF_VALUE);
+    bytes.extend(string("shared"));
+    bytes.push(NOT_NULL);
+    bytes.extend(string("c"));
+    // the second value the shared one had, the ref id of "x" is 0
+    bytes.extend([REF, 1]);
+    bytes
+}
+
+#[test]
+fn java_nulls_and_refs() {
+    let fury = Fury::default().per_entry_maps(true);
+    let map: IndexMap<Option<String>, Option<Rc<String>>> = fury.deserialize(&java_map()).unwrap();
+    let keys: Vec<_> = map.keys().map(Option::as_deref).collect();
+    assert_eq!(keys, [None, Some("a"), Some("b"), Some("c")]);
+    assert_eq!(map[&None].as_deref().map(String::as_str), Some("x"));
+    assert_eq!(map[&Some(String::from("a"))], None);
+    let (b, c) = (
+        &map[&Some(String::from("b"))],
+        &map[&Some(String::from("c"))],
+    );
+    assert!(Rc::ptr_eq(b.as_ref().unwrap(), c.as_ref().unwrap()));
+}
+
+#[test]
+fn null_entries_as_values() {
+    for per_entry in [false, true] {
+        let fury = Fury::default().per_entry_maps(per_entry);
+        let mut map: IndexMap<Option<String>, Option<i32>> = IndexMap::new();
+        map.insert(None, Some(1));
+        map.insert(Some(String::from("a")), None);
+        map.insert(Some(String::from("b")), Some(2));
+        let bytes = fury.serialize(&map);
+        let value = fury.deserialize_value(&bytes).unwrap();
+        let Value::Map(entries) = &value else {
+            panic!("not a map");
+        };
+        assert_eq!(
+            entries,
+            &[
+                (Value::Null, Value::I32(1)),
+                (Value::String(String::from("a")), Value::Null),
+                (Value::String(String::from("b")), Value::I32(2)),
+            ]
+        );
+        assert_eq!(fury.serialize_value(&value).unwrap(), bytes);
+    }
+}
+
+// keys and values that aren't null carry their ref flag and type id
+const KV_HEADER: u8 = 0b101101;
+const NULL_KEY: u8 = 0b101010;
+const NULL_VALUE: u8 = 0b010101;
+
+#[test]
+fn chunks() {
+    let fury = Fury::default();
+    let mut map: IndexMap<Option<String>, Option<String>> = IndexMap::new();
+    map.insert(Some(String::from("a")), Some(String::from("x")));
+    map.insert(Some(String::from("b")), Some(String::from("y")));
+    map.insert(None, Some(String::from("z")));
+    map.insert(Some(String::from("c")), None);
+    map.insert(Some(String::from("d")), Some(String::from("w")));
+    let bytes = fury.serialize(&map);
+    let mut expected = vec![5, KV_HEADER, 2];
+    for s in ["a", "x", "b", "y"] {
+        expected.push(NOT_NULL);
+        expected.extend(string(s));
+    }
+    expected.push(NULL_KEY);
+    expected.push(NOT_NULL);
+    expected.extend(string("z"));
+    expected.push(NULL_VALUE);
+    expected.push(NOT_NULL);
+    expected.extend(string("c"));
+    expected.extend([KV_HEADER, 1]);
+    for s in ["d", "w"] {
+        expected.push(NOT_NULL);
+        expected.extend(string(s));
+    }
+    // after the header and the map type id
+    assert_eq!(bytes[11..], expected);
+    assert_eq!(
+        fury.deserialize::<IndexMap<Option<String>, Option<String>>>(&bytes)
+            .unwrap(),
+        map
+    );
+}
+
+#[test]
+fn chunks_hold_255_entries_at_most() {
+    let fury = Fury::default();
+    let map: IndexMap<i32, i32> = (0..600).map(|i| (i, -i)).collect();
+    let bytes = fury.serialize(&map);
+    assert_eq!(fury.deserialize::<IndexMap<i32, i32>>(&bytes).unwrap(), map);
+    // 600 as a var int, then the first chunk
+    assert_eq!(bytes[11..15], [0xd8, 0x04, KV_HEADER, 255]);
+    let value = fury.deserialize_value(&bytes).unwrap();
+    assert_eq!(fury.serialize_value(&value).unwrap(), bytes);
+}
+
+#[test]
+fn null_entries_need_options() {
+    let fury = Fury::default();
+    let mut map: IndexMap<Option<String>, i32> = IndexMap::new();
+    map.insert(None, 1);
+    let bytes = fury.serialize(&map);
+    let err = fury
+        .deserialize::<IndexMap<String, i32>>(&bytes)
+        .unwrap_err();
+    assert!(err.to_string().contains("Null key or value"), "{err}");
+}
+
+#[test]
+fn corrupt_chunks() {
+    let fury = Fury::default();
+    let map: IndexMap<i32, i32> = (0..3).map(|i| (i, i)).collect();
+    let bytes = fury.serialize(&map);
+    let chunk = 12;
+    assert_eq!(bytes[chunk..chunk + 2], [KV_HEADER, 3]);
+    for (offset, byte) in [(chunk, 0b1), (chunk + 1, 0), (chunk + 1, 4)] {
+        let mut corrupt = bytes.clone();
+        corrupt[offset] = byte;
+        assert!(fury.deserialize::<IndexMap<i32, i32>>(&corrupt).is_err());
+    }
+}
+
+#[test]
+fn written_as_java_reads_them() {
+    let fury = Fury::default().per_entry_maps(true);
+    let mut map: IndexMap<Option<String>, Option<Rc<String>>> = IndexMap::new();
+    let shared = Rc::new(String::from("shared"));
+    map.insert(None, Some(Rc::new(String::from("x"))));
+    map.insert(Some(String::from("a")), None);
+    map.insert(Some(String::from("b")), Some(shared.clone()));
+    map.insert(Some(String::from("c")), Some(shared));
+    let bytes = fury.serialize(&map);
+    let java = java_map();
+    // the same entries, only the map type id differs
+    assert_eq!(bytes[..9], java[..9]);
+    assert_eq!(bytes[11..], java[11..]);
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Settings {
+    values: HashMap<String, Option<i64>>,
+}
+
+#[test]
+fn optional_values_of_fields() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default().mode(mode);
+        fury.register::<Settings>(101);
+        let settings = Settings {
+            values: HashMap::from([
+                (String::from("timeout"), Some(30)),
+                (String::from("retries"), None),
+            ]),
+        };
+        let bytes = fury.serialize(&settings);
+        assert_eq!(fury.deserialize::<Settings>(&bytes).unwrap(), settings);
+    }
+}
@@ -0,0 +1,310 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::resolver::meta_resolver::MetaContext;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Request {
+    method: String,
+    args: Vec<Arg>,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Arg {
+    name: String,
+    value: i64,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Ping {
+    seq: i64,
+}
+
+fn fury(mode: Mode) -> Fury {
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Request>(100);
+    fury.register::<Arg>(101);
+    fury.register::<Ping>(102);
+    fury
+}
+
+fn request(n: i64) -> Request {
+    Request {
+        method: String::from("get"),
+        args: vec![Arg {
+            name: String::from("id"),
+            value: n,
+        }],
+    }
+}
+
+#[test]
+fn type_defs_are_sent_once() {
+    let fury = fury(Mode::Compatible);
+    let (mut sender, mut receiver) = (MetaContext::new(), MetaContext::new());
+
+    let first = fury.serialize_with_meta_context(&request(1), &mut sender);
+    let second = fury.serialize_with_meta_context(&request(2), &mut sender);
+    assert_eq!(sender.sent_count(), 2);
+    assert!(second.len() < first.len());
+    // the first message carries the type defs like one written without a context, each after
+    // its one byte index
+    assert_eq!(first.len(), fury.serialize(&request(1)).len() + 2);
+
+    // a type the connection hasn't seen yet is appended to those sent
+    let ping = fury.serialize_with_meta_context(&Ping { seq: 3 }, &mut sender);
+    assert_eq!(sender.sent_count(), 3);
+
+    let read = |bin: &[u8], receiver: &mut MetaContext| {
+        fury.deserialize_with_meta_context::<Request>(bin, receiver)
+            .unwrap()
+    };
+    assert_eq!(read(&first, &mut receiver), request(1));
+    assert_eq!(read(&second, &mut receiver), request(2));
+    assert_eq!(
+        fury.deserialize_with_meta_context::<Ping>(&ping, &mut receiver)
+            .unwrap(),
+        Ping { seq: 3 }
+    );
+    assert_eq!(receiver.received_count(), 3);
+}
+
+#[test]
+fn needs_a_context() {
+    let fury = fury(Mode::Compatible);
+    let mut sender = MetaContext::new();
+    fury.serialize_with_meta_context(&request(1), &mut sender);
+    let second = fury.serialize_with_meta_context(&request(2), &mut sender);
+    let err = fury.deserialize::<Request>(&second).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("must be read with a `MetaContext`"));
+
+    // messages without shared metas are read as usual, leaving the context alone
+    let mut receiver = MetaContext::new();
+    let plain = fury.serialize(&request(4));
+    assert_eq!(
+        fury.deserialize_with_meta_context::<Request>(&plain, &mut receiver)
+            .unwrap(),
+        request(4)
+    );
+    assert_eq!(receiver.received_count(), 0);
+
+    sender.reset();
+    let first = fury.serialize_with_meta_context(&request(5), &mut sender);
+    assert_eq!(
+        fury.deserialize_with_meta_context::<Request>(&first, &mut receiver)
+            .unwrap(),
+        request(5)
+    );
+}
+
+#[test]
+fn schema_consistent_messages_are_unchanged() {
+    let fury = fury(Mode::SchemaConsistent);
+    let mut sender = MetaContext::new();
+    let bin = fury.serialize_with_meta_context(&request(1), &mut sender);
+    assert_eq!(bin, fury.serialize(&request(1)));
+    assert_eq!(sender.sent_count(), 0);
+    assert_eq!(fury.deserialize::<Request>(&bin).unwrap(), request(1));
+}
+
+// the writer's `Ping`, which gained a field the reader doesn't know yet
+mod evolved {
+    use fury_derive::Fury;
+
+    #[derive(Fury, Debug, PartialEq)]
+    pub struct Ping {
+        pub seq: i64,
+        pub sent_at: i64,
+    }
+}
+
+#[test]
+fn framed_stream() {
+    let mut writer_fury = Fury::default().mode(Mode::Compatible);
+    writer_fury.register::<Request>(100);
+    writer_fury.register::<Arg>(101);
+    writer_fury.register::<evolved::Ping>(102);
+    let reader_fury = fury(Mode::Compatible);
+
+    let mut sender = MetaContext::new();
+    let mut stream = Vec::new();
+    let mut frames = Vec::new();
+    for n in 0..3 {
+        let len = writer_fury
+            .serialize_framed_with_meta_context(&request(n), &mut stream, &mut sender)
+            .unwrap();
+        frames.push(len);
+    }
+    // a type first seen mid-stream
+    let ping = evolved::Ping {
+        seq: 7,
+        sent_at: 1_700_000_000,
+    };
+    writer_fury
+        .serialize_framed_with_meta_context(&ping, &mut stream, &mut sender)
+        .unwrap();
+    let late = writer_fury
+        .serialize_framed_with_meta_context(&ping, &mut stream, &mut sender)
+        .unwrap();
+    assert_eq!(sender.sent_count(), 3);
+    assert!(frames[1] < frames[0]);
+    assert_eq!(frames[1], frames[2]);
+    let plain = writer_fury
+        .serialize_framed_to_writer(&ping, &mut Vec::new())
+        .unwrap();
+    assert!(late < plain);
+
+    let mut receiver = MetaContext::new();
+    let mut source = stream.as_slice();
+    for n in 0..3 {
+        let read: Request = reader_fury
+            .deserialize_from_reader_with_meta_context(&mut source, &mut receiver)
+            .unwrap();
+        assert_eq!(read, request(n));
+    }
+    for _ in 0..2 {
+        let read: Ping = reader_fury
+            .deserialize_from_reader_with_meta_context(&mut source, &mut receiver)
+            .unwrap();
+        assert_eq!(read, Ping { seq: 7 });
+    }
+    assert!(source.is_empty());
+    assert_eq!(receiver.received_count(), 3);
+}
+
+macro_rules! many_types {
+    ($($name: ident = $id: literal),*) => {
+        $(
+            #[derive(Fury, Debug, PartialEq)]
+            struct $name {
+                value: i64,
+            }
+        )*
+
+        const MANY_IDS: &[u32] = &[$($id),*];
+
+        fn register_many(fury: &mut Fury) {
+            $(fury.register::<$name>($id);)*
+        }
+
+        fn write_many(fury: &Fury, id: u32, sender: &mut MetaContext) -> Vec<u8> {
+            match id {
+                $($id => fury.serialize_with_meta_context(&$name { value: $id }, sender),)*
+                _ => unreachable!(),
+            }
+        }
+
+        fn read_many(fury: &Fury, id: u32, bin: &[u8], receiver: &mut MetaContext) -> i64 {
+            match id {
+                $($id => {
+                    fury.deserialize_with_meta_context::<$name>(bin, receiver)
+                        .unwrap()
+                        .value
+                })*
+                _ => unreachable!(),
+            }
+        }
+    };
+}
+
+many_types!(
+    T0 = 200,
+    T1 = 201,
+    T2 = 202,
+    T3 = 203,
+    T4 = 204,
+    T5 = 205,
+    T6 = 206,
+    T7 = 207,
+    T8 = 208,
+    T9 = 209,
+    T10 = 210,
+    T11 = 211,
+    T12 = 212,
+    T13 = 213,
+    T14 = 214,
+    T15 = 215
+);
+
+#[test]
+fn bounded_by_max_type_defs() {
+    let mut fury = fury(Mode::Compatible);
+    register_many(&mut fury);
+    let mut sender = MetaContext::new().max_type_defs(4);
+    let mut receiver = MetaContext::new();
+
+    let mut request_lens = Vec::new();
+    for round in 0..3 {
+        for &id in MANY_IDS {
+            // a request between each of the others keeps its type defs in use
+            let bin = fury.serialize_with_meta_context(&request(round), &mut sender);
+            request_lens.push(bin.len());
+            let read: Request = fury
+                .deserialize_with_meta_context(&bin, &mut receiver)
+                .unwrap();
+            assert_eq!(read, request(round));
+
+            let bin = write_many(&fury, id, &mut sender);
+            assert_eq!(read_many(&fury, id, &bin, &mut receiver), id as i64);
+            assert!(sender.sent_count() <= 4);
+            assert!(receiver.received_count() <= 4);
+        }
+    }
+    // only the first request sent its type defs, the others were evicted in its place
+    assert!(request_lens[1] < request_lens[0]);
+    assert!(request_lens[1..].iter().all(|len| *len == request_lens[1]));
+}
+
+#[test]
+fn messages_beyond_max_type_defs() {
+    let mut fury = fury(Mode::Compatible);
+    register_many(&mut fury);
+    // a request needs two type defs at once
+    let mut sender = MetaContext::new().max_type_defs(1);
+    let mut receiver = MetaContext::new();
+    for round in 0..3 {
+        let bin = fury.serialize_with_meta_context(&request(round), &mut sender);
+        assert_eq!(sender.sent_count(), 2);
+        let read: Request = fury
+            .deserialize_with_meta_context(&bin, &mut receiver)
+            .unwrap();
+        assert_eq!(read, request(round));
+
+        let bin = write_many(&fury, 200, &mut sender);
+        assert_eq!(read_many(&fury, 200, &bin, &mut receiver), 200);
+    }
+    assert_eq!(receiver.received_count(), 2);
+}
+
+#[test]
+fn readers_bound_type_defs() {
+    let fury = fury(Mode::Compatible);
+    let mut sender = MetaContext::new();
+    // a request sends two type defs at once
+    let mut receiver = MetaContext::new().max_type_defs(1);
+    let bin = fury.serialize_with_meta_context(&request(1), &mut sender);
+    let err = fury
+        .deserialize_with_meta_context::<Request>(&bin, &mut receiver)
+        .unwrap_err();
+    assert!(err.to_string().contains("max_type_defs"), "{err}");
+    assert!(receiver.received_count() <= 1);
+}
@@ -241,3 +241,14 @@ fn test_non_ascii_encoding_and_non_utf8() {
         Ok(_) => panic!("Expected an error due to non-ASCII character with non-UTF8 encoding"),
     }
 }
+
+#[test]
+fn unsupported_characters() {
+    let encoder = MetaStringEncoder::new();
+    let err = encoder.encode_lower_special("a-b").unwrap_err();
+    assert!(err.to_string().contains("LOWER_SPECIAL encoding: '-'"));
+    let err = encoder.encode_lower_upper_digit_special("A$").unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("LOWER_UPPER_DIGIT_SPECIAL encoding: '$'"));
+}
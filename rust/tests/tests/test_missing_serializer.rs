@@ -0,0 +1,81 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, Default, PartialEq)]
+struct Item {
+    sku: String,
+}
+
+#[derive(Fury, Debug, Default, PartialEq)]
+struct Order {
+    id: i64,
+    item: Item,
+    backup: Option<Item>,
+}
+
+fn fury(mode: Mode, with_item: bool) -> Fury {
+    let mut fury = Fury::default().mode(mode);
+    // the parent first, its type def is only built once a message needs it
+    fury.register::<Order>(100);
+    if with_item {
+        fury.register::<Item>(101);
+    }
+    fury
+}
+
+#[test]
+fn parent_registered_before_field_type() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let fury = fury(mode, true);
+        let order = Order {
+            id: 7,
+            item: Item {
+                sku: String::from("A-1"),
+            },
+            backup: None,
+        };
+        let bin = fury.serialize(&order);
+        assert_eq!(fury.deserialize::<Order>(&bin).unwrap(), order);
+    }
+}
+
+#[test]
+fn unregistered_field_type() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let bin = fury(mode, true).serialize(&Order::default());
+        let err = fury(mode, false).deserialize::<Order>(&bin).unwrap_err();
+        match &err {
+            Error::MissingSerializer {
+                parent_type,
+                field,
+                field_type,
+            } => {
+                assert!(parent_type.ends_with("Order"));
+                // the first field read, an `Option` needs the serializer of what it wraps
+                assert_eq!(*field, "backup");
+                assert!(field_type.ends_with("Item"));
+            }
+            other => panic!("unexpected error {other}"),
+        }
+        assert!(err.to_string().starts_with("Field `backup` of `"));
+    }
+}
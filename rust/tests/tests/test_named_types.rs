@@ -0,0 +1,211 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::any::Any;
+
+use fury_core::buffer::{Reader, Writer};
+use fury_core::fury::{DeserializeOptions, Fury};
+use fury_core::manifest::{register_hook, Manifest};
+use fury_core::meta::{FieldInfo, TypeMeta};
+use fury_core::resolver::class_resolver::named_type_id;
+use fury_core::resolver::meta_resolver::MetaReaderResolver;
+use fury_core::types::{FieldType, Mode, UnknownTypePolicy};
+use fury_core::value::Value;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Person {
+    name: String,
+    age: i32,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Team {
+    lead: Person,
+    members: Vec<Person>,
+}
+
+fn fury(mode: Mode) -> Fury {
+    let mut fury = Fury::default().mode(mode);
+    fury.register_by_name::<Person>("com.example", "Person")
+        .unwrap();
+    fury.register_by_name::<Team>("com.example", "Team")
+        .unwrap();
+    fury
+}
+
+fn team() -> Team {
+    let person = |name: &str, age| Person {
+        name: name.to_string(),
+        age,
+    };
+    Team {
+        lead: person("ada", 36),
+        members: vec![person("bob", 29), person("cy", 41)],
+    }
+}
+
+#[test]
+fn round_trip() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let bin = fury(mode).serialize(&team());
+        assert_eq!(fury(mode).deserialize::<Team>(&bin).unwrap(), team());
+        let any: Box<dyn Any> = fury(mode).deserialize(&bin).unwrap();
+        assert_eq!(any.downcast_ref::<Team>(), Some(&team()));
+    }
+}
+
+#[test]
+fn names_are_registered() {
+    let fury = fury(Mode::Compatible);
+    let resolver = fury.get_class_resolver();
+    let id = named_type_id("com.example", "Person");
+    assert!((0x4000..0x8000).contains(&id));
+    assert_ne!(id, named_type_id("com.example", "Team"));
+    assert_ne!(id, named_type_id("com", "example.Person"));
+    assert_eq!(
+        resolver.get_id_by_type_name("com.example", "Person"),
+        Some(id)
+    );
+    assert_eq!(resolver.get_type_name(id), Some(("com.example", "Person")));
+    assert_eq!(resolver.get_id_by_type_name("com.example", "Nobody"), None);
+
+    let mut fury = Fury::default();
+    fury.register_by_name::<Person>("com.example", "Person")
+        .unwrap();
+    assert!(fury
+        .register_by_name::<Team>("com.example", "Person")
+        .is_err());
+}
+
+#[test]
+fn peer_ids_of_named_types_are_ignored() {
+    // a type meta as another language writes it, with an id of its own
+    let mut meta = TypeMeta::from_fields(
+        7,
+        vec![
+            FieldInfo::new("age", FieldType::INT32 as i16),
+            FieldInfo::new("name", FieldType::STRING as i16),
+        ],
+    );
+    meta.set_type_name("com.example", "Person");
+    let mut writer = Writer::default();
+    writer.var_int32(1);
+    writer.bytes(&meta.to_bytes().unwrap());
+    let bytes = writer.dump();
+
+    let fury = fury(Mode::Compatible);
+    let mut metas = MetaReaderResolver::default();
+    metas.load(&mut Reader::new(&bytes), &fury).unwrap();
+    let loaded = metas.get(0);
+    assert_eq!(loaded.get_type_name(), Some(("com.example", "Person")));
+    assert_eq!(loaded.get_type_id(), named_type_id("com.example", "Person"));
+    assert_eq!(loaded.get_field_info()[1].get_field_name(), "name");
+}
+
+#[test]
+fn corrupt_type_metas_fail() {
+    let mut meta = TypeMeta::from_fields(7, vec![FieldInfo::new("age", FieldType::INT32 as i16)]);
+    meta.set_type_name("com.example", "Person");
+    let bytes = meta.to_bytes().unwrap();
+    assert!(TypeMeta::from_bytes(&mut Reader::new(&bytes)).is_ok());
+    // the header is a u64 with the layer count in its low bits, the namespace follows it
+    let corrupt = |at: usize, byte: u8| {
+        let mut bytes = bytes.clone();
+        bytes[at] = byte;
+        TypeMeta::from_bytes(&mut Reader::new(&bytes)).err()
+    };
+    let no_layers = corrupt(0, bytes[0] & !0b1111).unwrap();
+    assert!(
+        no_layers.to_string().contains("without a layer"),
+        "{no_layers}"
+    );
+    assert!(corrupt(8, 0x07).is_some(), "unknown encoding");
+    assert!(corrupt(9, 0x7f).is_some(), "namespace past the end");
+
+    // a message carrying such a type meta fails to read instead of panicking
+    let fury = fury(Mode::Compatible);
+    let bin = fury.serialize(&team());
+    let type_def = fury
+        .get_class_resolver()
+        .get_type_def(std::any::TypeId::of::<Team>(), &fury);
+    let at = bin
+        .windows(type_def.len())
+        .position(|window| window == type_def)
+        .unwrap();
+    let mut corrupt = bin.clone();
+    corrupt[at] &= !0b1111;
+    assert!(fury.deserialize::<Team>(&corrupt).is_err());
+}
+
+#[test]
+fn manifest_keeps_names() {
+    register_hook::<Person>();
+    register_hook::<Team>();
+    let exported = fury(Mode::Compatible).export_manifest();
+    let manifest = Manifest::from_bytes(&exported).unwrap();
+    assert_eq!(manifest.names.len(), 2);
+    let fury = Fury::from_manifest(&exported).unwrap();
+    let id = fury
+        .get_class_resolver()
+        .get_id_by_type_name("com.example", "Team");
+    assert_eq!(id, Some(named_type_id("com.example", "Team")));
+    let bin = self::fury(Mode::Compatible).serialize(&team());
+    assert_eq!(fury.deserialize::<Team>(&bin).unwrap(), team());
+}
+
+#[test]
+fn dyn_values_carry_names() {
+    let fury = fury(Mode::SchemaConsistent);
+    let any: Box<dyn Any> = Box::new(team());
+    let bin = fury.serialize(&any);
+    let read: Box<dyn Any> = fury.deserialize(&bin).unwrap();
+    assert_eq!(read.downcast_ref::<Team>(), Some(&team()));
+    assert!(matches!(
+        fury.deserialize_value(&bin).unwrap(),
+        Value::Struct { .. }
+    ));
+
+    // peers know the type by its name only
+    let err = Fury::default()
+        .deserialize::<Box<dyn Any>>(&bin)
+        .unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        "`Box<dyn Any>` holds `com.example.Team`, which isn't registered here"
+    );
+
+    let values: Vec<Option<Box<dyn Any>>> = vec![
+        Some(Box::new(Person {
+            name: "ada".to_string(),
+            age: 36,
+        })),
+        Some(Box::new(String::from("text"))),
+    ];
+    // skipped by their length
+    let bin = fury.struct_length_guard(true).serialize(&values);
+    let read: Vec<Option<Box<dyn Any>>> = Fury::default()
+        .struct_length_guard(true)
+        .deserialize_options(
+            DeserializeOptions::default().unknown_types(UnknownTypePolicy::SkipAsNone),
+        )
+        .deserialize(&bin)
+        .unwrap();
+    assert!(read[0].is_none());
+    let text = read[1].as_ref().unwrap().downcast_ref::<String>();
+    assert_eq!(text.map(String::as_str), Some("text"));
+}
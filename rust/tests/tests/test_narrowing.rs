@@ -0,0 +1,135 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::error::Error;
+use fury_core::fury::{DeserializeOptions, Fury};
+use fury_core::types::{Mode, NarrowingPolicy};
+use fury_derive::Fury;
+
+// written by a peer declaring 64-bit integers
+#[derive(Fury, Debug)]
+struct WideReading {
+    value: i64,
+}
+
+#[derive(Fury, Debug)]
+struct WideSample {
+    count: i64,
+    reading: WideReading,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Reading {
+    value: i16,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Sample {
+    count: Option<i32>,
+    reading: Reading,
+}
+
+fn sample(value: i64) -> WideSample {
+    WideSample {
+        count: 3,
+        reading: WideReading { value },
+    }
+}
+
+fn mode(compatible: bool) -> Mode {
+    if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    }
+}
+
+fn read(compatible: bool, narrowing: NarrowingPolicy, sent: &WideSample) -> Result<Sample, Error> {
+    let mut writer = Fury::default().mode(mode(compatible));
+    writer.register::<WideReading>(1);
+    writer.register::<WideSample>(2);
+    let mut reader = Fury::default()
+        .mode(mode(compatible))
+        .deserialize_options(DeserializeOptions::default().narrowing(narrowing));
+    reader.register::<Reading>(1);
+    reader.register::<Sample>(2);
+    reader.deserialize(&writer.serialize(sent))
+}
+
+#[test]
+fn values_in_range_are_converted() {
+    for compatible in [false, true] {
+        let obj = read(compatible, NarrowingPolicy::Error, &sample(-42)).unwrap();
+        assert_eq!(
+            obj,
+            Sample {
+                count: Some(3),
+                reading: Reading { value: -42 }
+            }
+        );
+    }
+}
+
+#[test]
+fn overflow_reports_field_path() {
+    for compatible in [false, true] {
+        let err = read(compatible, NarrowingPolicy::Error, &sample(70000)).unwrap_err();
+        assert!(matches!(
+            err.cause(),
+            Error::NumericOverflow {
+                value: 70000,
+                target: "i16"
+            }
+        ));
+        assert_eq!(err.path(), Some("Sample.reading.value"));
+        let offset = err.offset().unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!("Value 70000 doesn't fit in i16 at byte {offset} in `Sample.reading.value`")
+        );
+    }
+}
+
+#[test]
+fn overflow_saturates_or_wraps() {
+    for compatible in [false, true] {
+        let obj = read(compatible, NarrowingPolicy::Saturate, &sample(70000)).unwrap();
+        assert_eq!(obj.reading.value, i16::MAX);
+        let obj = read(compatible, NarrowingPolicy::Saturate, &sample(-70000)).unwrap();
+        assert_eq!(obj.reading.value, i16::MIN);
+        let obj = read(compatible, NarrowingPolicy::Wrap, &sample(70000)).unwrap();
+        assert_eq!(obj.reading.value, 70000i64 as i16);
+    }
+}
+
+#[test]
+fn root_value() {
+    let fury = Fury::default();
+    let err = fury.deserialize::<u8>(&fury.serialize(&-1i32)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Value -1 doesn't fit in u8 at byte 15 in `u8`"
+    );
+    let err = fury.deserialize::<f32>(&fury.serialize(&1i32)).unwrap_err();
+    assert!(matches!(
+        err.cause(),
+        Error::TypeMismatch {
+            expected: 11,
+            actual: 7
+        }
+    ));
+}
@@ -0,0 +1,95 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::types::{DecodeMode, Mode};
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Contact {
+    name: Option<String>,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Patch {
+    // `None` leaves the nickname as it is, `Some(None)` clears it
+    nickname: Option<Option<String>>,
+    scores: Vec<Option<Option<i32>>>,
+}
+
+fn fury(mode: Mode) -> Fury {
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Contact>(101);
+    fury.register::<Patch>(102);
+    fury
+}
+
+#[test]
+fn null_flags_of_fields() {
+    let fury = fury(Mode::SchemaConsistent);
+    // a nullable Java `String` field: the null flag, or the not-null flag and the string
+    let bytes = fury.serialize(&Contact { name: None });
+    assert_eq!(&bytes[11..], [253]);
+    let bytes = fury.serialize(&Contact {
+        name: Some(String::from("x")),
+    });
+    assert_eq!(&bytes[11..], [255, 13, 0, 4, b'x']);
+}
+
+#[test]
+fn nested() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let fury = fury(mode);
+        for patch in [
+            Patch {
+                nickname: None,
+                scores: vec![],
+            },
+            Patch {
+                nickname: Some(None),
+                scores: vec![None, Some(None), Some(Some(3))],
+            },
+            Patch {
+                nickname: Some(Some(String::from("Bo"))),
+                scores: vec![Some(None)],
+            },
+        ] {
+            let bytes = fury.serialize(&patch);
+            assert_eq!(fury.deserialize::<Patch>(&bytes).unwrap(), patch);
+            let borrowed: Patch = fury
+                .deserialize_with_mode(&bytes, DecodeMode::Borrowed)
+                .unwrap();
+            assert_eq!(borrowed, patch);
+        }
+    }
+}
+
+#[test]
+fn nested_flags() {
+    let fury = Fury::default();
+    for (value, flags) in [
+        (Some(None), &[255, 253][..]),
+        (Some(Some(true)), &[255, 255, 1, 0, 1][..]),
+    ] {
+        let bytes = fury.serialize(&value);
+        assert_eq!(&bytes[8..], flags);
+        assert_eq!(
+            fury.deserialize::<Option<Option<bool>>>(&bytes).unwrap(),
+            value
+        );
+    }
+}
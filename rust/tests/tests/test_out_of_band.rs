@@ -0,0 +1,96 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::types::{config_flags, Mode};
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Frame {
+    name: String,
+    image: Vec<u8>,
+    depths: Vec<i32>,
+    weights: Vec<f64>,
+}
+
+fn frame() -> Frame {
+    Frame {
+        name: String::from("cam-1"),
+        image: (0..=255).collect(),
+        depths: vec![-3, 7],
+        weights: vec![0.5; 64],
+    }
+}
+
+fn fury(mode: Mode) -> Fury {
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Frame>(100);
+    fury
+}
+
+#[test]
+fn large_payloads_out_of_band() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let fury = fury(mode);
+        let frame = frame();
+        // payloads of 64 bytes or more go to "shared memory"
+        let mut buffers: Vec<Vec<u8>> = Vec::new();
+        let bytes = fury.serialize_with_buffers(&frame, &mut |buffer| {
+            if buffer.total_bytes() < 64 {
+                return true;
+            }
+            buffers.push(buffer.as_bytes().to_vec());
+            false
+        });
+        assert_eq!(buffers.len(), 2);
+        assert_eq!(buffers[0], frame.image);
+        assert_eq!(buffers[1].len(), 64 * 8);
+        assert!(bytes.len() < 100, "{}", bytes.len());
+        assert_ne!(bytes[2] & config_flags::IS_OUT_OF_BAND_FLAG, 0);
+
+        let buffers: Vec<&[u8]> = buffers.iter().map(Vec::as_slice).collect();
+        let read: Frame = fury.deserialize_with_buffers(&bytes, &buffers).unwrap();
+        assert_eq!(read, frame);
+
+        let message = fury.deserialize::<Frame>(&bytes).unwrap_err().to_string();
+        assert!(message.contains("deserialize_with_buffers"), "{message}");
+        let message = fury
+            .deserialize_with_buffers::<Frame>(&bytes, &buffers[..1])
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("more out-of-band buffers"), "{message}");
+    }
+}
+
+#[test]
+fn in_band() {
+    let fury = fury(Mode::SchemaConsistent);
+    let frame = frame();
+    let bytes = fury.serialize_with_buffers(&frame, &mut |_| true);
+    // one flag per array
+    assert_eq!(bytes.len(), fury.serialize(&frame).len() + 3);
+    assert_eq!(
+        fury.deserialize_with_buffers::<Frame>(&bytes, &[]).unwrap(),
+        frame
+    );
+    // messages without buffers are read as usual
+    let bytes = fury.serialize(&frame);
+    assert_eq!(
+        fury.deserialize_with_buffers::<Frame>(&bytes, &[]).unwrap(),
+        frame
+    );
+}
@@ -0,0 +1,95 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::error::Error;
+use fury_core::fury::{DeserializeOptions, Fury};
+use fury_core::resolver::context::{ReadContext, WriteContext};
+use fury_core::serializer::Serializer;
+use fury_core::types::{FieldType, FuryGeneralList, PanicPolicy};
+use std::panic::{self, AssertUnwindSafe};
+
+// a hand-written serializer with a bug: it can't take negative values
+#[derive(Debug, PartialEq)]
+struct Fragile(i8);
+
+impl Serializer for Fragile {
+    fn reserved_space() -> usize {
+        1
+    }
+
+    fn write(&self, context: &mut WriteContext) {
+        context.writer.i8(self.0);
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        let value = context.reader.i8()?;
+        if value == i8::MIN {
+            panic!("no room for i8::MIN");
+        }
+        assert!(value >= 0, "negative value {value}");
+        Ok(Fragile(value))
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::INT8.into()
+    }
+}
+
+impl FuryGeneralList for Fragile {}
+
+fn fury(policy: PanicPolicy) -> Fury {
+    Fury::default().deserialize_options(DeserializeOptions::default().panics(policy))
+}
+
+#[test]
+fn caught() {
+    let fury = fury(PanicPolicy::Catch);
+    let bin = fury.serialize(&vec![Fragile(1), Fragile(-2)]);
+    match fury.deserialize::<Vec<Fragile>>(&bin) {
+        Err(Error::SerializerPanic { type_name, message }) => {
+            assert!(type_name.ends_with("Vec<test_panic_policy::Fragile>"));
+            assert_eq!(message, "negative value -2");
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+    // static messages too
+    let bin = fury.serialize(&vec![Fragile(i8::MIN)]);
+    let err = fury.deserialize::<Vec<Fragile>>(&bin).unwrap_err();
+    assert!(err.to_string().ends_with("panicked: no room for i8::MIN"));
+
+    // the instance keeps working
+    let bin = fury.serialize(&vec![Fragile(1), Fragile(2)]);
+    assert_eq!(
+        fury.deserialize::<Vec<Fragile>>(&bin).unwrap(),
+        [Fragile(1), Fragile(2)]
+    );
+}
+
+#[test]
+fn propagated_by_default() {
+    let fury = fury(PanicPolicy::default());
+    let bin = fury.serialize(&vec![Fragile(-2)]);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| fury.deserialize::<Vec<Fragile>>(&bin)));
+    assert!(result.is_err());
+}
+
+#[test]
+fn propagated_when_registration_is_required() {
+    let fury = fury(PanicPolicy::Propagate).require_registration(true);
+    let bin = fury.serialize(&vec![Fragile(-2)]);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| fury.deserialize::<Vec<Fragile>>(&bin)));
+    assert!(result.is_err());
+}
@@ -0,0 +1,43 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury::prelude::*;
+use std::collections::HashMap;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn roundtrip<T: Serializer>(value: &T) -> Result<T, Error> {
+    fury::from_bytes(&fury::to_bytes(value))
+}
+
+#[test]
+fn default_instance() {
+    let mut fury = Fury::default().mode(Mode::SchemaConsistent);
+    fury.register::<Point>(1);
+    assert!(fury::set_default(fury).is_ok());
+    assert!(fury::set_default(Fury::default()).is_err());
+
+    let point = Point { x: 1, y: -1 };
+    assert_eq!(roundtrip(&point).unwrap(), point);
+
+    let map = HashMap::from([("k".to_string(), 1i64)]);
+    assert_eq!(roundtrip(&map).unwrap(), map);
+}
@@ -0,0 +1,94 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+
+macro_rules! sparse_dto {
+    ($name: ident $(, #[$attr: meta])?) => {
+        #[derive(Fury, Debug, Default, PartialEq)]
+        $(#[$attr])?
+        struct $name {
+            id: i64,
+            a0: Option<i32>,
+            a1: Option<String>,
+            a2: Option<i64>,
+            a3: Option<i32>,
+            a4: Option<String>,
+            a5: Option<i64>,
+            a6: Option<i32>,
+            a7: Option<String>,
+            a8: Option<i64>,
+            a9: Option<Vec<i32>>,
+        }
+    };
+}
+
+sparse_dto!(Flagged);
+sparse_dto!(Packed, #[fury(presence_bitmap)]);
+
+#[test]
+fn presence_bitmap_roundtrip() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default().mode(mode);
+        fury.register::<Packed>(1);
+        let dto = Packed {
+            id: 7,
+            a1: Some("x".to_string()),
+            a8: Some(-1),
+            a9: Some(vec![1, 2]),
+            ..Default::default()
+        };
+        let obj: Packed = fury.deserialize(&fury.serialize(&dto)).unwrap();
+        assert_eq!(obj, dto);
+    }
+}
+
+#[test]
+fn presence_bitmap_is_smaller() {
+    let mut fury = Fury::default();
+    fury.register::<Flagged>(1);
+    fury.register::<Packed>(2);
+    let flagged = fury.serialize(&Flagged {
+        id: 1,
+        ..Default::default()
+    });
+    let packed = fury.serialize(&Packed {
+        id: 1,
+        ..Default::default()
+    });
+    // ten null flags vs a two byte bitmap
+    assert_eq!(flagged.len() - packed.len(), 8);
+}
+
+#[test]
+fn presence_bitmap_truncated() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default().mode(mode);
+        fury.register::<Packed>(1);
+        let bytes = fury.serialize(&Packed {
+            id: 1,
+            a0: Some(2),
+            ..Default::default()
+        });
+        // every cut, including the ones ending right before or inside the bitmap
+        for len in 0..bytes.len() {
+            assert!(fury.deserialize::<Packed>(&bytes[..len]).is_err());
+        }
+    }
+}
@@ -0,0 +1,59 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use std::sync::{Arc, Mutex};
+
+fn records() -> Vec<String> {
+    (0..10_000).map(|i| format!("record-{i:08}")).collect()
+}
+
+#[test]
+fn reports_progress() {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let listener = reports.clone();
+    let fury = Fury::default()
+        .progress_listener(Arc::new(move |consumed, total| {
+            listener.lock().unwrap().push((consumed, total))
+        }))
+        .progress_interval(16 << 10);
+    let bin = fury.serialize(&records());
+    let read: Vec<String> = fury.deserialize(&bin).unwrap();
+    assert_eq!(read, records());
+
+    let reports = reports.lock().unwrap();
+    // about one report per 16 KiB, then the final one
+    assert_eq!(reports.len(), bin.len() / (16 << 10) + 1);
+    assert!(reports.iter().all(|(_, total)| *total == bin.len()));
+    for pair in reports.windows(2) {
+        assert!(pair[1].0 >= pair[0].0 + (16 << 10) || pair[1].0 == bin.len());
+    }
+    assert_eq!(reports.last(), Some(&(bin.len(), bin.len())));
+}
+
+#[test]
+fn small_messages() {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let listener = reports.clone();
+    let fury = Fury::default().progress_listener(Arc::new(move |consumed, total| {
+        listener.lock().unwrap().push((consumed, total))
+    }));
+    let bin = fury.serialize(&records());
+    let _: Vec<String> = fury.deserialize(&bin).unwrap();
+    // below the default interval only the end is reported
+    assert_eq!(*reports.lock().unwrap(), vec![(bin.len(), bin.len())]);
+}
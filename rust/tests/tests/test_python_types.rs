@@ -0,0 +1,135 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use chrono::{DateTime, Utc};
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_core::value::Value;
+use fury_derive::Fury;
+use std::collections::HashSet;
+
+// payloads shaped like those of a Python peer, which writes every element with its own type
+fn python(value: Value) -> Vec<u8> {
+    Fury::default().serialize_value(&value).unwrap()
+}
+
+#[test]
+fn set() {
+    let fury = Fury::default();
+    let bin = python(Value::Set(vec![
+        Value::String(String::from("a")),
+        Value::String(String::from("b")),
+    ]));
+    let set: HashSet<String> = fury.deserialize(&bin).unwrap();
+    assert_eq!(set, HashSet::from([String::from("a"), String::from("b")]));
+
+    let set: HashSet<i32> = (0..200).collect();
+    assert_eq!(
+        fury.deserialize::<HashSet<i32>>(&fury.serialize(&set))
+            .unwrap(),
+        set
+    );
+    match fury.deserialize_value(&fury.serialize(&HashSet::from([7i32]))) {
+        Ok(Value::Set(elements)) => assert_eq!(elements, vec![Value::I32(7)]),
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+#[test]
+fn tuple() {
+    let fury = Fury::default();
+    let bin = python(Value::List(vec![
+        Value::I64(1),
+        Value::String(String::from("one")),
+        Value::F64(1.5),
+    ]));
+    let tuple: (i64, String, f64) = fury.deserialize(&bin).unwrap();
+    assert_eq!(tuple, (1, String::from("one"), 1.5));
+
+    let value = fury
+        .deserialize_value(&fury.serialize(&(2i64, String::from("two"))))
+        .unwrap();
+    assert_eq!(
+        value,
+        Value::List(vec![Value::I64(2), Value::String(String::from("two"))])
+    );
+
+    let err = fury.deserialize::<(i64, String)>(&bin).unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        "Expected a tuple of 2 elements, the list has 3"
+    );
+    assert!(fury.deserialize::<(i64, i64, f64)>(&bin).is_err());
+}
+
+#[test]
+fn timezone_aware_datetime() {
+    let fury = Fury::default();
+    let at = DateTime::from_timestamp(1_700_000_000, 123_456_789).unwrap();
+    // the offset of a Python `datetime` isn't written, only the instant
+    let bin = python(Value::Timestamp(at.naive_utc()));
+    assert_eq!(fury.deserialize::<DateTime<Utc>>(&bin).unwrap(), at);
+    assert_eq!(
+        fury.deserialize::<DateTime<Utc>>(&fury.serialize(&at))
+            .unwrap(),
+        at
+    );
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Event {
+    members: HashSet<i64>,
+    span: (i64, i64),
+    at: DateTime<Utc>,
+}
+
+#[test]
+fn fields() {
+    let mut fury = Fury::default();
+    fury.register::<Event>(701);
+    let event = Event {
+        members: HashSet::from([1, 2]),
+        span: (3, 4),
+        at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+    };
+    assert_eq!(
+        fury.deserialize::<Event>(&fury.serialize(&event)).unwrap(),
+        event
+    );
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Timezone {
+    offset_seconds: i32,
+}
+
+#[test]
+fn unknown_types() {
+    // e.g. a `datetime.timezone` the Python peer registered, unknown here
+    let mut peer = Fury::default();
+    peer.register::<Timezone>(700);
+    let bin = peer.serialize(&vec![Timezone {
+        offset_seconds: 3600,
+    }]);
+    match Fury::default()
+        .deserialize_value(&bin)
+        .as_ref()
+        .map_err(Error::cause)
+    {
+        Err(Error::UnknownTypeId { type_id }) => assert_eq!(*type_id, 700),
+        other => panic!("unexpected {other:?}"),
+    }
+}
@@ -0,0 +1,121 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_core::resolver::context::{ReadContext, WriteContext};
+use fury_core::serializer::Serializer;
+use fury_core::types::{FieldType, FuryGeneralList, Mode, RefFlag};
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+// a point behind the name of the frame it is in, or no point at all, written by hand
+#[derive(Debug, PartialEq)]
+struct Located {
+    frame: String,
+    point: Option<Point>,
+}
+
+impl Serializer for Located {
+    fn reserved_space() -> usize {
+        0
+    }
+
+    fn write(&self, context: &mut WriteContext) {
+        context.write_meta_string(&self.frame).unwrap();
+        match &self.point {
+            Some(point) => {
+                context.write_ref_flag(RefFlag::NotNullValue);
+                context.write_type_info::<Point>();
+                point.write(context);
+            }
+            None => context.write_ref_flag(RefFlag::Null),
+        }
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        let frame = context.read_meta_string()?;
+        let point = match context.read_ref_flag()? {
+            RefFlag::Null => None,
+            _ => {
+                let info = context.read_type_info::<Point>()?;
+                let compatible = *context.get_fury().get_mode() == Mode::Compatible;
+                assert_eq!(info.meta.is_some(), compatible);
+                Some(Point::read(context)?)
+            }
+        };
+        Ok(Located { frame, point })
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::BINARY.into()
+    }
+}
+
+impl FuryGeneralList for Located {}
+
+#[test]
+fn hand_written_layout_round_trips() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default().mode(mode);
+        fury.register::<Point>(101);
+        for located in [
+            Located {
+                frame: "world".to_string(),
+                point: Some(Point { x: 1, y: -2 }),
+            },
+            Located {
+                frame: "screen".to_string(),
+                point: None,
+            },
+        ] {
+            let bin = fury.serialize(&located);
+            assert_eq!(fury.deserialize::<Located>(&bin).unwrap(), located);
+        }
+
+        // the nested point is written as a derived field would be
+        let bin = fury.serialize(&Point { x: 1, y: -2 });
+        let mut writer = fury_core::buffer::Writer::default();
+        let mut context = WriteContext::new(&fury, &mut writer);
+        context.write_ref_flag(RefFlag::NotNullValue);
+        context.write_type_info::<Point>();
+        Point { x: 1, y: -2 }.write(&mut context);
+        assert!(bin.windows(writer.len()).any(|part| part == writer.dump()));
+    }
+}
+
+#[test]
+fn type_info_of_another_type_fails() {
+    let mut fury = Fury::default();
+    fury.register::<Point>(101);
+    let bin = fury.serialize(&Located {
+        frame: "world".to_string(),
+        point: Some(Point::default()),
+    });
+    let mut fury = Fury::default();
+    fury.register::<Point>(102);
+    let err = fury.deserialize::<Located>(&bin).unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        "Invalid field type, expected:102, actual:101"
+    );
+}
@@ -0,0 +1,112 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::buffer::{Reader, Writer};
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_core::resolver::ref_resolver::{RefReader, RefWriter};
+use fury_core::types::{config_flags, FieldType, Language, RefFlag, MAGIC_NUMBER};
+use std::rc::Rc;
+
+fn payload(body: impl FnOnce(&mut Writer)) -> Vec<u8> {
+    let mut writer = Writer::default();
+    writer.u16(MAGIC_NUMBER);
+    writer.u8(config_flags::IS_LITTLE_ENDIAN_FLAG | config_flags::IS_CROSS_LANGUAGE_FLAG);
+    writer.u8(Language::Rust as u8);
+    writer.u32(0);
+    body(&mut writer);
+    writer.dump()
+}
+
+#[test]
+fn ref_ids_are_validated() {
+    let mut refs = RefReader::new(2);
+    assert_eq!(refs.reserve_ref_id().unwrap(), 0);
+    assert_eq!(refs.reserve_ref_id().unwrap(), 1);
+    assert!(refs.reserve_ref_id().is_err());
+    assert_eq!(refs.len(), 2);
+
+    // slots exist but nothing was stored yet
+    assert!(refs.get_ref(1).is_err());
+    assert!(refs.get_ref(2).is_err());
+
+    assert_eq!(refs.read_ref_id(&mut Reader::new(&[1])).unwrap(), 1);
+    assert!(refs.read_ref_id(&mut Reader::new(&[2])).is_err());
+}
+
+#[test]
+fn huge_ref_id() {
+    // 2^31 - 1 and a negative id, both as a 5 byte var_int32
+    for id in [
+        [0xff, 0xff, 0xff, 0xff, 0x07],
+        [0xff, 0xff, 0xff, 0xff, 0x0f],
+    ] {
+        let bin = payload(|writer| {
+            writer.i8(RefFlag::Ref as i8);
+            writer.bytes(&id);
+        });
+        let result: Result<Option<i32>, Error> = Fury::default().deserialize(&bin);
+        assert!(matches!(result.unwrap_err().cause(), Error::Other(_)));
+    }
+}
+
+#[test]
+fn ref_count_cap() {
+    let bin = payload(|writer| {
+        writer.i8(RefFlag::RefValue as i8);
+        writer.i16(FieldType::INT32.into());
+        writer.i32(42);
+    });
+    let value: i32 = Fury::default().max_ref_count(1).deserialize(&bin).unwrap();
+    assert_eq!(value, 42);
+    assert!(Fury::default()
+        .max_ref_count(0)
+        .deserialize::<i32>(&bin)
+        .is_err());
+}
+
+#[test]
+fn ref_writer_ids() {
+    let nodes: Vec<Rc<i32>> = (0..1000).map(Rc::new).collect();
+    let ptr = |node: &Rc<i32>| Rc::as_ptr(node) as *const ();
+    let mut refs = RefWriter::new();
+    for (id, node) in nodes.iter().enumerate() {
+        assert_eq!(refs.get_or_assign(ptr(node)), (id as u32, true));
+    }
+    // seen again after the table grew
+    for (id, node) in nodes.iter().enumerate().rev() {
+        assert_eq!(refs.get_or_assign(ptr(node)), (id as u32, false));
+    }
+    assert_eq!(refs.len(), nodes.len());
+
+    // forgotten right after being assigned, its id is handed out again
+    let more: Vec<Rc<i32>> = (0..1000).map(Rc::new).collect();
+    for node in &more {
+        let (id, _) = refs.get_or_assign(ptr(node));
+        refs.unassign_last(ptr(node));
+        assert_eq!(refs.get_or_assign(ptr(node)), (id, true));
+        refs.unassign_last(ptr(node));
+    }
+    assert_eq!(refs.len(), nodes.len());
+    for (id, node) in nodes.iter().enumerate() {
+        assert_eq!(refs.get_or_assign(ptr(node)), (id as u32, false));
+    }
+
+    refs.clear();
+    assert!(refs.is_empty());
+    assert_eq!(refs.get_or_assign(ptr(&nodes[7])), (0, true));
+}
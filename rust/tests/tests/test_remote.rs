@@ -0,0 +1,68 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::serializer::FuryExternal;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+
+// stands for a crate we don't own
+mod geo {
+    #[derive(Debug, PartialEq)]
+    pub struct Point {
+        pub x: f64,
+        pub y: f64,
+    }
+}
+
+#[derive(Fury)]
+#[fury(remote = "geo::Point")]
+#[allow(dead_code)]
+struct PointDef {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Route {
+    name: String,
+    start: FuryExternal<PointDef>,
+}
+
+#[test]
+fn remote_roundtrip() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default().mode(mode);
+        fury.register_external::<PointDef>(1).unwrap();
+        fury.register::<Route>(2);
+
+        let route = Route {
+            name: "home".to_string(),
+            start: FuryExternal(geo::Point { x: 1.0, y: -2.5 }),
+        };
+        let obj: Route = fury.deserialize(&fury.serialize(&route)).unwrap();
+        assert_eq!(obj, route);
+        assert_eq!(obj.start.x, 1.0);
+    }
+}
+
+#[test]
+fn register_external_twice() {
+    let mut fury = Fury::default();
+    fury.register_external::<PointDef>(1).unwrap();
+    assert!(fury.register_external::<PointDef>(2).is_err());
+}
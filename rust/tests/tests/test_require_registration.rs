@@ -0,0 +1,137 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::{DeserializeOptions, Fury};
+use fury_core::types::{Mode, UnknownTypePolicy};
+use fury_derive::Fury;
+use std::any::Any;
+use std::collections::HashMap;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Click {
+    x: i32,
+    y: i32,
+}
+
+// a type the reader doesn't know
+#[derive(Fury, Debug, PartialEq)]
+struct Exec {
+    command: String,
+}
+
+#[derive(Fury)]
+struct Feed {
+    #[fury(unknown_types = "capture")]
+    events: Vec<Option<Box<dyn Any>>>,
+}
+
+fn mode(compatible: bool) -> Mode {
+    if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    }
+}
+
+fn producer(compatible: bool) -> Fury {
+    let mut fury = Fury::default()
+        .mode(mode(compatible))
+        .struct_length_guard(!compatible);
+    fury.register::<Click>(101);
+    fury.register::<Exec>(102);
+    fury.register::<Feed>(103);
+    fury
+}
+
+fn consumer(compatible: bool, policy: UnknownTypePolicy) -> Fury {
+    let mut fury = Fury::default()
+        .mode(mode(compatible))
+        .struct_length_guard(!compatible)
+        .deserialize_options(DeserializeOptions::default().unknown_types(policy))
+        .require_registration(true);
+    fury.register::<Click>(101);
+    fury.register::<Feed>(103);
+    fury
+}
+
+fn events() -> Vec<Option<Box<dyn Any>>> {
+    vec![
+        Some(Box::new(Click { x: 1, y: 2 })),
+        Some(Box::new(Exec {
+            command: String::from("rm -rf /"),
+        })),
+    ]
+}
+
+#[test]
+fn rejects_unregistered() {
+    for compatible in [false, true] {
+        let bin = producer(compatible).serialize(&events());
+        for policy in [
+            UnknownTypePolicy::Error,
+            UnknownTypePolicy::SkipAsNone,
+            UnknownTypePolicy::CaptureOpaque,
+        ] {
+            let err = consumer(compatible, policy)
+                .deserialize::<Vec<Option<Box<dyn Any>>>>(&bin)
+                .unwrap_err();
+            assert_eq!(
+                err.cause().to_string(),
+                "Type id 102 isn't registered, `Fury::require_registration` only reads registered types"
+            );
+        }
+
+        // nor does the policy of a field let it through
+        let bin = producer(compatible).serialize(&Feed { events: events() });
+        assert!(consumer(compatible, UnknownTypePolicy::Error)
+            .deserialize::<Feed>(&bin)
+            .is_err());
+    }
+}
+
+#[test]
+fn reads_registered_and_builtins() {
+    for compatible in [false, true] {
+        let mut values: HashMap<String, Box<dyn Any>> = HashMap::new();
+        values.insert(String::from("click"), Box::new(Click { x: 3, y: 4 }));
+        values.insert(String::from("count"), Box::new(7i32));
+        values.insert(String::from("name"), Box::new(String::from("fury")));
+        let bin = producer(compatible).serialize(&values);
+        let read: HashMap<String, Box<dyn Any>> = consumer(compatible, UnknownTypePolicy::Error)
+            .deserialize(&bin)
+            .unwrap();
+        assert_eq!(
+            read["click"].downcast_ref::<Click>(),
+            Some(&Click { x: 3, y: 4 })
+        );
+        assert_eq!(read["count"].downcast_ref::<i32>(), Some(&7));
+        assert_eq!(
+            read["name"].downcast_ref::<String>().map(String::as_str),
+            Some("fury")
+        );
+    }
+}
+
+#[test]
+fn bad_meta_index() {
+    let fury = producer(true);
+    let mut bin = fury.serialize(&Click { x: 1, y: 2 });
+    // the ref flag of the root, followed by the index of its type meta
+    let at = bin.windows(3).position(|w| w == [0xff, 0, 0]).unwrap();
+    bin[at + 1] = 7;
+    let err = fury.deserialize::<Click>(&bin).unwrap_err();
+    assert_eq!(err.cause().to_string(), "Unknown type meta index 7");
+}
@@ -0,0 +1,130 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_core::types::{config_flags, DecodeMode, Mode};
+use fury_derive::Fury;
+use std::borrow::Cow;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Responses {
+    status: Vec<i16>,
+    latency: Vec<i64>,
+    codes: Vec<i32>,
+}
+
+fn status_codes() -> Vec<i16> {
+    (0..100)
+        .map(|i| if i % 40 == 39 { 500 } else { 200 })
+        .collect()
+}
+
+fn responses() -> Responses {
+    Responses {
+        status: status_codes(),
+        latency: (0..20).collect(),
+        codes: vec![],
+    }
+}
+
+fn mode(compatible: bool) -> Mode {
+    if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    }
+}
+
+#[test]
+fn round_trip() {
+    for compatible in [false, true] {
+        for aligned in [false, true] {
+            let mut fury = Fury::default()
+                .mode(mode(compatible))
+                .rle_arrays(true)
+                .aligned_arrays(aligned);
+            fury.register::<Responses>(1);
+            let obj: Responses = fury.deserialize(&fury.serialize(&responses())).unwrap();
+            assert_eq!(obj, responses());
+        }
+    }
+}
+
+#[test]
+fn chosen_when_shorter() {
+    let rle = Fury::default().rle_arrays(true);
+    let plain = Fury::default();
+    // five runs instead of 200 bytes
+    assert!(rle.serialize(&status_codes()).len() + 150 < plain.serialize(&status_codes()).len());
+    // distinct values stay raw, behind the flag byte
+    let distinct: Vec<i64> = (0..20).collect();
+    assert_eq!(
+        rle.serialize(&distinct).len(),
+        plain.serialize(&distinct).len() + 1
+    );
+}
+
+#[test]
+fn borrowed_arrays() {
+    let fury = Fury::default().rle_arrays(true);
+    let runs: Cow<[i32]> = Cow::Owned(vec![7; 100]);
+    let bin = fury.serialize(&runs);
+    let obj: Cow<[i32]> = fury
+        .deserialize_with_mode(&bin, DecodeMode::Borrowed)
+        .unwrap();
+    assert_eq!(obj, runs);
+}
+
+#[test]
+fn rejects_overlong_runs() {
+    let fury = Fury::default().rle_arrays(true);
+    let mut bin = fury.serialize(&vec![9i32; 4]);
+    // the run length follows the length and the flag
+    let run = bin.len() - 5;
+    assert_eq!(bin[run], 4);
+    bin[run] = 5;
+    assert!(fury.deserialize::<Vec<i32>>(&bin).is_err());
+}
+
+#[test]
+fn flagged_experimental() {
+    let fury = Fury::default().rle_arrays(true);
+    let bin = fury.serialize(&status_codes());
+    assert_ne!(bin[2] & config_flags::IS_EXPERIMENTAL_FLAG, 0);
+}
+
+#[test]
+fn bounds_the_decoded_size() {
+    // 8 MiB of zeros in a single run of a few bytes
+    let fury = Fury::default().rle_arrays(true);
+    let bin = fury.serialize(&vec![0i64; 1 << 20]);
+    assert!(bin.len() < 32);
+    assert!(matches!(
+        fury.deserialize::<Vec<i64>>(&bin).unwrap_err().cause(),
+        Error::LimitExceeded {
+            limit: "max_rle_expansion",
+            value: 8388608,
+            ..
+        }
+    ));
+
+    let fury = fury.max_rle_expansion(1 << 20);
+    assert_eq!(
+        fury.deserialize::<Vec<i64>>(&bin).unwrap(),
+        vec![0; 1 << 20]
+    );
+}
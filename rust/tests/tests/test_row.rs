@@ -15,10 +15,13 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
-use fury_core::row::{from_row, to_row};
+use fury_core::row::{
+    from_row, to_row, RowReader, RowSchema, RowType, RowValue, RowWriter, StringColumn,
+};
 use fury_derive::FuryRow;
+use uuid::Uuid;
 
 #[test]
 fn row() {
@@ -75,3 +78,290 @@ fn row() {
     assert_eq!(f5.get("k1").expect("should exists"), &"v1");
     assert_eq!(f5.get("k2").expect("should exists"), &"v2");
 }
+
+#[test]
+fn nullable_fields() {
+    #[derive(FuryRow)]
+    struct Account {
+        id: i64,
+        nickname: Option<String>,
+        referrer: Option<i64>,
+        scores: Vec<Option<i32>>,
+    }
+
+    let row = to_row(&Account {
+        id: 7,
+        nickname: None,
+        referrer: Some(3),
+        scores: vec![Some(10), None, Some(-2)],
+    });
+    let obj = from_row::<Account>(&row);
+    assert_eq!(obj.id(), 7);
+    assert_eq!(obj.nickname(), None);
+    assert_eq!(obj.referrer(), Some(3));
+    let scores = obj.scores();
+    assert_eq!(scores.size(), 3);
+    assert_eq!(scores.get(0), Some(10));
+    assert_eq!(scores.get(1), None);
+    assert_eq!(scores.get(2), Some(-2));
+
+    let row = to_row(&Account {
+        id: 8,
+        nickname: Some(String::from("kit")),
+        referrer: None,
+        scores: vec![],
+    });
+    let obj = from_row::<Account>(&row);
+    assert_eq!(obj.nickname(), Some("kit"));
+    assert_eq!(obj.referrer(), None);
+    assert_eq!(obj.scores().size(), 0);
+}
+
+#[test]
+fn string_column() {
+    #[derive(FuryRow)]
+    struct Batch {
+        region: StringColumn,
+        name: StringColumn,
+    }
+
+    let regions = ["eu", "us", "eu", "ap", "us", "eu"];
+    let names = ["a", "b", "c", "d", "e", "f"];
+    let column =
+        |values: &[&str]| -> Vec<String> { values.iter().map(|v| v.to_string()).collect() };
+    let row = to_row(&Batch {
+        region: StringColumn::new(column(&regions)),
+        name: StringColumn::new(column(&names)),
+    });
+    let obj = from_row::<Batch>(&row);
+
+    let region = obj.region();
+    assert!(region.is_dictionary_encoded());
+    assert_eq!(region.size(), regions.len());
+    for (idx, value) in regions.iter().enumerate() {
+        assert_eq!(region.get(idx), *value);
+    }
+    let dictionary = region.dictionary().unwrap();
+    assert_eq!(dictionary.size(), 3);
+    assert_eq!(dictionary.get(1), "us");
+    assert_eq!(region.index(4), Some(1));
+
+    // every value is distinct, a dictionary wouldn't save anything
+    let name = obj.name();
+    assert!(!name.is_dictionary_encoded());
+    assert_eq!(name.index(0), None);
+    for (idx, value) in names.iter().enumerate() {
+        assert_eq!(name.get(idx), *value);
+    }
+
+    let row = to_row(&StringColumn::new(column(&regions)).dictionary_threshold(2));
+    let region = from_row::<StringColumn>(&row);
+    assert!(!region.is_dictionary_encoded());
+    assert_eq!(region.get(3), "ap");
+}
+
+#[test]
+fn runtime_schema() {
+    #[derive(FuryRow)]
+    struct Address {
+        city: String,
+        zip: i32,
+    }
+
+    #[derive(FuryRow)]
+    struct Person {
+        address: Address,
+        id: i64,
+        name: String,
+        scores: Vec<i32>,
+    }
+
+    let address = RowSchema::new()
+        .field("zip", RowType::Int32)
+        .field("city", RowType::String);
+    let schema = RowSchema::new()
+        .field("name", RowType::String)
+        .field("id", RowType::Int64)
+        .field("scores", RowType::Array(Box::new(RowType::Int32)))
+        .field("address", RowType::Struct(address));
+    assert_eq!(schema.field_index("id"), Some(1));
+    assert_eq!(schema.field_name(3), "scores");
+
+    let mut writer = RowWriter::new(&schema);
+    writer
+        .set("name", RowValue::String(String::from("ada")))
+        .unwrap()
+        .set(1, RowValue::Int64(7))
+        .unwrap()
+        .set(
+            "scores",
+            RowValue::Array(vec![RowValue::Int32(90), RowValue::Int32(85)]),
+        )
+        .unwrap()
+        .set(
+            "address",
+            RowValue::Struct(vec![
+                RowValue::String(String::from("london")),
+                RowValue::Int32(1815),
+            ]),
+        )
+        .unwrap();
+    let row = writer.finish().unwrap();
+
+    let reader = RowReader::new(&schema, &row);
+    assert_eq!(reader.get_i64("id").unwrap(), 7);
+    assert_eq!(reader.get_str(2).unwrap(), "ada");
+    assert_eq!(
+        reader
+            .get_struct("address")
+            .unwrap()
+            .get_i32("zip")
+            .unwrap(),
+        1815
+    );
+    let scores = reader.get_array("scores").unwrap();
+    assert_eq!(scores.size(), 2);
+    assert_eq!(scores.get(1).unwrap(), RowValue::Int32(85));
+    assert_eq!(
+        reader.get("address").unwrap(),
+        RowValue::Struct(vec![
+            RowValue::String(String::from("london")),
+            RowValue::Int32(1815)
+        ])
+    );
+
+    // the same layout as the derived struct, both ways
+    let person = from_row::<Person>(&row);
+    assert_eq!(person.name(), "ada");
+    assert_eq!(person.address().city(), "london");
+    assert_eq!(person.scores().get(0), 90);
+    let derived = to_row(&Person {
+        address: Address {
+            city: String::from("paris"),
+            zip: 75,
+        },
+        id: 8,
+        name: String::from("bob"),
+        scores: vec![],
+    });
+    let reader = RowReader::new(&schema, &derived);
+    assert_eq!(reader.get_str("name").unwrap(), "bob");
+    assert_eq!(
+        reader
+            .get_struct("address")
+            .unwrap()
+            .get_str("city")
+            .unwrap(),
+        "paris"
+    );
+    assert_eq!(reader.get_array("scores").unwrap().size(), 0);
+}
+
+#[test]
+fn runtime_schema_errors() {
+    let schema = RowSchema::new()
+        .field("id", RowType::Int64)
+        .field("name", RowType::String);
+    let mut writer = RowWriter::new(&schema);
+    assert!(writer.set("id", RowValue::Int32(1)).is_err());
+    assert!(writer.set("missing", RowValue::Int64(1)).is_err());
+    assert!(writer.set(2, RowValue::Int64(1)).is_err());
+    writer.set("id", RowValue::Int64(1)).unwrap();
+    assert_eq!(
+        writer.finish().unwrap_err().to_string(),
+        "Field `name` is not set"
+    );
+
+    writer
+        .set("name", RowValue::String(String::from("x")))
+        .unwrap();
+    let row = writer.finish().unwrap();
+    let reader = RowReader::new(&schema, &row);
+    assert_eq!(
+        reader.get_str("id").unwrap_err().to_string(),
+        "Field `id` is of type Int64"
+    );
+}
+
+#[test]
+fn non_string_keys() {
+    #[derive(FuryRow)]
+    struct Inventory {
+        by_id: HashMap<i64, i32>,
+        by_uuid: BTreeMap<Uuid, String>,
+        notes: BTreeMap<i32, Option<String>>,
+    }
+
+    let first = Uuid::from_u128(0x1234_5678_9abc_def0_1234_5678_9abc_def0);
+    let second = Uuid::from_u128(7);
+    let row = to_row(&Inventory {
+        by_id: HashMap::from([(30, 3), (-10, 1), (20, 2)]),
+        by_uuid: BTreeMap::from([
+            (first, String::from("first")),
+            (second, String::from("second")),
+        ]),
+        notes: BTreeMap::from([(1, None), (2, Some(String::from("fragile")))]),
+    });
+    let obj = from_row::<Inventory>(&row);
+
+    // hash maps are written sorted, so that keys can be searched
+    let by_id = obj.by_id();
+    assert_eq!(by_id.size(), 3);
+    assert_eq!(by_id.keys().get(0), -10);
+    assert_eq!(by_id.get(&20), Some(2));
+    assert_eq!(by_id.get(&25), None);
+
+    let by_uuid = obj.by_uuid();
+    assert_eq!(by_uuid.get(&first), Some("first"));
+    assert_eq!(by_uuid.keys().get(0), second);
+    assert_eq!(
+        by_uuid.to_btree_map().unwrap(),
+        BTreeMap::from([(first, "first"), (second, "second")])
+    );
+
+    let notes = obj.notes();
+    assert_eq!(notes.get(&1), Some(None));
+    assert_eq!(notes.get(&2), Some(Some("fragile")));
+}
+
+#[test]
+fn runtime_schema_maps() {
+    #[derive(FuryRow)]
+    struct Stock {
+        levels: BTreeMap<i32, i64>,
+    }
+
+    let schema = RowSchema::new().field(
+        "levels",
+        RowType::Map(Box::new(RowType::Int32), Box::new(RowType::Int64)),
+    );
+    let mut writer = RowWriter::new(&schema);
+    let levels = RowValue::Map(vec![
+        (RowValue::Int32(1), RowValue::Int64(100)),
+        (RowValue::Int32(4), RowValue::Int64(400)),
+    ]);
+    assert!(writer
+        .set(
+            "levels",
+            RowValue::Map(vec![(RowValue::Int64(1), RowValue::Int64(1))])
+        )
+        .is_err());
+    writer.set("levels", levels.clone()).unwrap();
+    let row = writer.finish().unwrap();
+
+    let reader = RowReader::new(&schema, &row);
+    let map = reader.get_map("levels").unwrap();
+    assert_eq!(map.size(), 2);
+    assert_eq!(map.keys().get(1).unwrap(), RowValue::Int32(4));
+    assert_eq!(map.values().get(1).unwrap(), RowValue::Int64(400));
+    assert_eq!(reader.get("levels").unwrap(), levels);
+    assert_eq!(from_row::<Stock>(&row).levels().get(&4), Some(400));
+
+    let derived = to_row(&Stock {
+        levels: BTreeMap::from([(2, 20)]),
+    });
+    assert_eq!(
+        RowReader::new(&schema, &derived).get("levels").unwrap(),
+        RowValue::Map(vec![(RowValue::Int32(2), RowValue::Int64(20))])
+    );
+}
@@ -0,0 +1,154 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::schema_event::{SchemaEventListener, SkippedField};
+use fury_core::types::{FieldType, Mode};
+use fury_derive::Fury;
+use std::sync::{Arc, Mutex};
+
+// the sender's newer version of the types
+mod v2 {
+    use fury_derive::Fury;
+
+    #[derive(Fury, Debug)]
+    pub struct Gift {
+        pub message: String,
+    }
+
+    #[derive(Fury, Debug)]
+    pub struct Item {
+        pub sku: String,
+        pub discount: f64,
+        pub labels: Vec<String>,
+        pub gift: Gift,
+        pub quantity: i32,
+    }
+
+    #[derive(Fury, Debug)]
+    pub struct Batch {
+        pub items: Vec<Item>,
+        pub id: i64,
+    }
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Item {
+    sku: String,
+    quantity: i32,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Batch {
+    id: i64,
+    items: Vec<Item>,
+}
+
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<(String, String, i16, usize)>>,
+}
+
+impl SchemaEventListener for Recorder {
+    fn on_skipped_field(&self, event: &SkippedField) {
+        self.events.lock().unwrap().push((
+            event.type_name.to_string(),
+            event.field_name.to_string(),
+            event.field_type,
+            event.count,
+        ));
+    }
+}
+
+fn v2_batch() -> v2::Batch {
+    let item = |sku: &str| v2::Item {
+        sku: sku.to_string(),
+        discount: 0.5,
+        labels: vec!["sale".to_string()],
+        gift: v2::Gift {
+            message: "enjoy".to_string(),
+        },
+        quantity: 2,
+    };
+    v2::Batch {
+        items: vec![item("a"), item("b"), item("c")],
+        id: 7,
+    }
+}
+
+#[test]
+fn unknown_fields_are_skipped_and_reported() {
+    let mut sender = Fury::default().mode(Mode::Compatible);
+    sender.register::<v2::Gift>(101);
+    sender.register::<v2::Item>(102);
+    sender.register::<v2::Batch>(103);
+
+    let recorder = Arc::new(Recorder::default());
+    let mut receiver = Fury::default()
+        .mode(Mode::Compatible)
+        .schema_event_listener(recorder.clone());
+    receiver.register::<Item>(102);
+    receiver.register::<Batch>(103);
+
+    let obj: Batch = receiver
+        .deserialize(&sender.serialize(&v2_batch()))
+        .unwrap();
+    let item = |sku: &str| Item {
+        sku: sku.to_string(),
+        quantity: 2,
+    };
+    assert_eq!(
+        obj,
+        Batch {
+            id: 7,
+            items: vec![item("a"), item("b"), item("c")],
+        }
+    );
+
+    let mut events = recorder.events.lock().unwrap().clone();
+    events.sort();
+    let item_type = std::any::type_name::<Item>().to_string();
+    assert_eq!(
+        events,
+        vec![
+            (
+                item_type.clone(),
+                "discount".to_string(),
+                FieldType::DOUBLE.into(),
+                3
+            ),
+            (item_type.clone(), "gift".to_string(), 101, 3),
+            (item_type, "labels".to_string(), FieldType::ARRAY.into(), 3),
+        ]
+    );
+}
+
+#[test]
+fn same_schema_reports_nothing() {
+    let recorder = Arc::new(Recorder::default());
+    let mut fury = Fury::default()
+        .mode(Mode::Compatible)
+        .schema_event_listener(recorder.clone());
+    fury.register::<Item>(102);
+    let item = Item {
+        sku: "a".to_string(),
+        quantity: 1,
+    };
+    let obj: Item = fury.deserialize(&fury.serialize(&item)).unwrap();
+    assert_eq!(obj, item);
+    assert!(recorder.events.lock().unwrap().is_empty());
+}
@@ -0,0 +1,134 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::buffer::{Reader, Writer};
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+use std::collections::HashMap;
+use std::io::IoSlice;
+
+#[test]
+fn primitives_straddle_boundary() {
+    let mut writer = Writer::default();
+    writer.u8(7);
+    writer.i16(-2);
+    writer.u32(0xdead_beef);
+    writer.i64(i64::MIN + 1);
+    writer.f64(1.5);
+    writer.var_int32(100);
+    writer.bytes(b"fury");
+    let bin = writer.dump();
+
+    for split in 0..=bin.len() {
+        let (head, tail) = bin.split_at(split);
+        let mut reader = Reader::from_segments(head, tail);
+        assert_eq!(reader.u8().unwrap(), 7);
+        assert_eq!(reader.i16().unwrap(), -2);
+        assert_eq!(reader.u32().unwrap(), 0xdead_beef);
+        assert_eq!(reader.i64().unwrap(), i64::MIN + 1);
+        assert_eq!(reader.f64().unwrap(), 1.5);
+        assert_eq!(reader.var_int32().unwrap(), 100);
+        assert_eq!(&*reader.bytes(4).unwrap(), b"fury");
+    }
+}
+
+#[test]
+fn primitives_straddle_many_segments() {
+    let mut writer = Writer::default();
+    writer.u64(u64::MAX - 1);
+    writer.var_uint64(u64::MAX);
+    writer.bytes(b"scattered");
+    writer.f32(-0.5);
+    let bin = writer.dump();
+
+    // every byte in a segment of its own, with empty ones in between
+    let segments: Vec<&[u8]> = bin.chunks(1).flat_map(|byte| [byte, &[]]).collect();
+    let mut reader = Reader::from_slices(&segments);
+    assert_eq!(reader.len(), bin.len());
+    assert_eq!(reader.u64().unwrap(), u64::MAX - 1);
+    assert_eq!(reader.var_uint64().unwrap(), u64::MAX);
+    assert_eq!(&*reader.bytes(9).unwrap(), b"scattered");
+    assert_eq!(reader.f32().unwrap(), -0.5);
+    assert_eq!(reader.remaining(), 0);
+    assert_eq!(reader.peek_u8(), None);
+
+    // positioned readers find their segment again, backwards too
+    assert_eq!(reader.at(0).u64().unwrap(), u64::MAX - 1);
+    assert_eq!(reader.at(bin.len() - 4).f32().unwrap(), -0.5);
+    assert_eq!(reader.to_vec(), bin);
+
+    let io_slices: Vec<IoSlice> = segments.iter().map(|s| IoSlice::new(s)).collect();
+    assert_eq!(
+        Reader::from_io_slices(&io_slices).u64().unwrap(),
+        u64::MAX - 1
+    );
+    assert!(Reader::from_slices(&[]).is_empty());
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Packet {
+    name: String,
+    values: Vec<i64>,
+    attrs: HashMap<String, String>,
+}
+
+#[test]
+fn deserialize_from_ring_buffer() {
+    let packet = Packet {
+        name: "ring".to_string(),
+        values: vec![1, -2, 3],
+        attrs: HashMap::from([("k".to_string(), "v".to_string())]),
+    };
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default().mode(mode);
+        fury.register::<Packet>(999);
+        let bin = fury.serialize(&packet);
+        for split in 0..=bin.len() {
+            let (head, tail) = bin.split_at(split);
+            let obj: Packet = fury
+                .deserialize_from_segments(&[IoSlice::new(head), IoSlice::new(tail)])
+                .expect("should deserialize");
+            assert_eq!(obj, packet);
+        }
+    }
+}
+
+#[test]
+fn deserialize_from_slices() {
+    let packet = Packet {
+        name: "scatter".to_string(),
+        values: vec![i64::MAX, 0, -7],
+        attrs: HashMap::from([("header".to_string(), "body".to_string())]),
+    };
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default().mode(mode);
+        fury.register::<Packet>(999);
+        let bin = fury.serialize(&packet);
+        for chunk in 1..=4 {
+            let segments: Vec<&[u8]> = bin.chunks(chunk).collect();
+            let obj: Packet = fury.deserialize_from_slices(&segments).unwrap();
+            assert_eq!(obj, packet);
+        }
+        let (header, body) = bin.split_at(3);
+        let (body, trailer) = body.split_at(body.len() / 2);
+        let obj: Packet = fury
+            .deserialize_from_slices(&[header, body, trailer])
+            .unwrap();
+        assert_eq!(obj, packet);
+    }
+}
@@ -0,0 +1,152 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_core::value::Value;
+use fury_core::Serde;
+use fury_derive::Fury;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+enum Shape {
+    #[default]
+    Empty,
+    Circle(f64),
+    Rect {
+        width: u32,
+        height: u32,
+    },
+    Line(i16, i16),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+struct Limits(u8, Option<i64>);
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+struct Config {
+    name: String,
+    retries: u32,
+    ratio: Option<f64>,
+    tags: Vec<String>,
+    weights: BTreeMap<String, i64>,
+    shapes: Vec<Shape>,
+    limits: Limits,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Size {
+    width: u32,
+    height: u32,
+}
+
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Envelope {
+    id: i32,
+    config: Serde<Config>,
+}
+
+fn config() -> Config {
+    Config {
+        name: "ingest".to_string(),
+        retries: 3,
+        ratio: Some(0.5),
+        tags: vec!["a".to_string(), "b".to_string()],
+        weights: BTreeMap::from([("x".to_string(), -1), ("y".to_string(), 2)]),
+        shapes: vec![
+            Shape::Empty,
+            Shape::Circle(1.5),
+            Shape::Rect {
+                width: 2,
+                height: 3,
+            },
+            Shape::Line(-4, 5),
+        ],
+        limits: Limits(7, None),
+    }
+}
+
+#[test]
+fn round_trip() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default().mode(mode);
+        fury.register::<Envelope>(101);
+
+        let bin = fury.serialize(&Serde(config()));
+        assert_eq!(fury.deserialize::<Serde<Config>>(&bin).unwrap().0, config());
+
+        let envelope = Envelope {
+            id: 1,
+            config: Serde(config()),
+        };
+        let bin = fury.serialize(&envelope);
+        assert_eq!(fury.deserialize::<Envelope>(&bin).unwrap(), envelope);
+    }
+}
+
+#[test]
+fn written_as_maps() {
+    let fury = Fury::default();
+    let bin = fury.serialize(&Serde(Limits(7, None)));
+    assert_eq!(
+        fury.deserialize_value(&bin).unwrap(),
+        Value::List(vec![Value::U8(7), Value::Null])
+    );
+
+    let bin = fury.serialize(&Serde(Shape::Rect {
+        width: 2,
+        height: 3,
+    }));
+    let rect = Value::Map(vec![
+        (Value::String("width".to_string()), Value::U32(2)),
+        (Value::String("height".to_string()), Value::U32(3)),
+    ]);
+    assert_eq!(
+        fury.deserialize_value(&bin).unwrap(),
+        Value::Map(vec![(Value::String("Rect".to_string()), rect)])
+    );
+
+    // what other peers write for a `Map<String, Object>` reads the same
+    let value = Value::Map(vec![
+        (Value::String("width".to_string()), Value::I64(2)),
+        (Value::String("height".to_string()), Value::I32(3)),
+    ]);
+    let bin = fury.serialize_value(&value).unwrap();
+    let size = fury.deserialize::<Serde<Size>>(&bin).unwrap().0;
+    assert_eq!(
+        size,
+        Size {
+            width: 2,
+            height: 3
+        }
+    );
+}
+
+#[test]
+fn mismatched_data_fails() {
+    let fury = Fury::default();
+    let bin = fury.serialize(&Serde(5));
+    let err = fury.deserialize::<Serde<Config>>(&bin).unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        format!(
+            "Can't read `{}`: invalid type: integer `5`, expected struct Config",
+            std::any::type_name::<Config>()
+        )
+    );
+}
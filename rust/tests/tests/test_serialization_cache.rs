@@ -0,0 +1,144 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::sync::Arc;
+
+use fury_core::fury::Fury;
+use fury_core::thread_safe::ThreadSafeFury;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, Default, PartialEq)]
+struct Config {
+    name: String,
+    retries: i32,
+}
+
+fn fury(capacity: usize) -> Fury {
+    let mut fury = Fury::default()
+        .mode(Mode::Compatible)
+        .serialization_cache(capacity);
+    fury.register::<Config>(100);
+    fury
+}
+
+fn config(name: &str) -> Config {
+    Config {
+        name: name.to_string(),
+        retries: 3,
+    }
+}
+
+#[test]
+fn cached_by_address() {
+    let fury = fury(8);
+    let mut config = config("a");
+    let first = fury.serialize_cached(&config, 0);
+    assert!(Arc::ptr_eq(&first, &fury.serialize_cached(&config, 0)));
+    assert_eq!(fury.deserialize::<Config>(&first).unwrap(), config);
+
+    // a change needs a new generation
+    config.retries = 5;
+    assert_eq!(
+        fury.deserialize::<Config>(&fury.serialize_cached(&config, 0))
+            .unwrap()
+            .retries,
+        3
+    );
+    let second = fury.serialize_cached(&config, 1);
+    assert_eq!(fury.deserialize::<Config>(&second).unwrap(), config);
+    assert_eq!(fury.get_cached_count(), 2);
+
+    fury.invalidate_cached_value(&config);
+    assert_eq!(fury.get_cached_count(), 0);
+}
+
+#[test]
+fn cached_by_key() {
+    let fury = fury(8);
+    let a = fury.serialize_cached_with_key(&config("a"), 7);
+    // the key identifies the value, whatever is passed along with it
+    let again = fury.serialize_cached_with_key(&config("b"), 7);
+    assert!(Arc::ptr_eq(&a, &again));
+    assert_eq!(&*a, fury.serialize(&config("a")).as_slice());
+
+    fury.invalidate_cached::<Config>(7);
+    let b = fury.serialize_cached_with_key(&config("b"), 7);
+    assert_eq!(fury.deserialize::<Config>(&b).unwrap(), config("b"));
+
+    // keys are per type
+    fury.serialize_cached_with_key(&String::from("x"), 7);
+    assert_eq!(fury.get_cached_count(), 2);
+    fury.clear_serialization_cache();
+    assert_eq!(fury.get_cached_count(), 0);
+}
+
+#[test]
+fn bounded() {
+    let fury = fury(2);
+    for key in 0..5 {
+        fury.serialize_cached_with_key(&config("a"), key);
+    }
+    assert_eq!(fury.get_cached_count(), 2);
+
+    // without a cache every call serializes
+    let mut uncached = Fury::default();
+    uncached.register::<Config>(100);
+    let config = config("a");
+    let first = uncached.serialize_cached(&config, 0);
+    assert!(!Arc::ptr_eq(&first, &uncached.serialize_cached(&config, 0)));
+    assert_eq!(uncached.get_cached_count(), 0);
+}
+
+#[test]
+fn least_recently_used_first() {
+    let fury = fury(2);
+    let first = fury.serialize_cached_with_key(&config("a"), 1);
+    let second = fury.serialize_cached_with_key(&config("b"), 2);
+    // used again, so the message of key 2 is dropped for the next one
+    fury.serialize_cached_with_key(&config("a"), 1);
+    fury.serialize_cached_with_key(&config("c"), 3);
+    assert_eq!(fury.get_cached_count(), 2);
+    assert!(Arc::ptr_eq(
+        &first,
+        &fury.serialize_cached_with_key(&config("a"), 1)
+    ));
+    assert!(!Arc::ptr_eq(
+        &second,
+        &fury.serialize_cached_with_key(&config("b"), 2)
+    ));
+}
+
+#[test]
+fn trim_caches() {
+    let fury = fury(8);
+    for key in 0..5 {
+        fury.serialize_cached_with_key(&config("a"), key);
+    }
+    let mut out = [0; 64];
+    fury.serialize_to_slice(&config("a"), &mut out).unwrap();
+    fury.trim_caches();
+    assert_eq!(fury.get_cached_count(), 0);
+    // still usable afterwards
+    assert!(fury.serialize_to_slice(&config("a"), &mut out).is_ok());
+
+    let pooled = ThreadSafeFury::new(fury);
+    pooled.serialize(&config("a"));
+    assert_eq!(pooled.pooled_buffers(), 1);
+    pooled.trim_caches();
+    assert_eq!(pooled.pooled_buffers(), 0);
+}
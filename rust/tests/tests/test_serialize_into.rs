@@ -0,0 +1,141 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+use std::collections::HashMap;
+use std::io::{Cursor, ErrorKind};
+use std::net::{TcpListener, TcpStream};
+
+#[derive(Fury, Debug, PartialEq)]
+struct Order {
+    id: i64,
+    items: Vec<String>,
+    prices: HashMap<String, f64>,
+}
+
+fn order(id: i64) -> Order {
+    Order {
+        id,
+        items: vec![String::from("apple"), String::from("pear")],
+        prices: HashMap::from([(String::from("apple"), 1.5)]),
+    }
+}
+
+fn fury(mode: Mode) -> Fury {
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Order>(300);
+    fury
+}
+
+#[test]
+fn reuses_buffer() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let fury = fury(mode);
+        let mut bf = Vec::new();
+        let len = fury.serialize_into(&order(1), &mut bf);
+        assert_eq!(len, bf.len());
+        assert_eq!(bf, fury.serialize(&order(1)));
+        let capacity = bf.capacity();
+
+        bf.clear();
+        let len = fury.serialize_into(&order(2), &mut bf);
+        assert_eq!(len, bf.len());
+        assert_eq!(bf.capacity(), capacity);
+        assert_eq!(fury.deserialize::<Order>(&bf).unwrap(), order(2));
+    }
+}
+
+#[test]
+fn appends_after_existing_bytes() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let fury = fury(mode);
+        // length-prefixed frames, the offsets in each message count from its own start
+        let mut bf = Vec::new();
+        for id in 0..3 {
+            let at = bf.len();
+            bf.extend_from_slice(&[0; 4]);
+            let len = fury.serialize_into(&order(id), &mut bf);
+            bf[at..at + 4].copy_from_slice(&(len as u32).to_le_bytes());
+        }
+
+        let mut rest = &bf[..];
+        for id in 0..3 {
+            let len = u32::from_le_bytes(rest[..4].try_into().unwrap()) as usize;
+            let obj: Order = fury.deserialize(&rest[4..4 + len]).unwrap();
+            assert_eq!(obj, order(id));
+            rest = &rest[4 + len..];
+        }
+        assert!(rest.is_empty());
+    }
+}
+
+#[test]
+fn writes_to_sink() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let fury = fury(mode);
+        let mut sink = std::io::Cursor::new(vec![0xff]);
+        sink.set_position(1);
+        let len = fury.serialize_to_writer(&order(5), &mut sink).unwrap();
+        let bf = sink.into_inner();
+        assert_eq!(len, bf.len() - 1);
+        assert_eq!(fury.deserialize::<Order>(&bf[1..]).unwrap(), order(5));
+    }
+}
+
+#[test]
+fn frames_over_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let sender = std::thread::spawn(move || {
+        let fury = fury(Mode::Compatible);
+        let mut stream = TcpStream::connect(addr).unwrap();
+        for id in 0..10 {
+            fury.serialize_framed_to_writer(&order(id), &mut stream)
+                .unwrap();
+        }
+    });
+
+    let fury = fury(Mode::Compatible);
+    let (mut stream, _) = listener.accept().unwrap();
+    for id in 0..10 {
+        let obj: Order = fury.deserialize_from_reader(&mut stream).unwrap();
+        assert_eq!(obj, order(id));
+    }
+    sender.join().unwrap();
+    // the sender hung up between two frames
+    match fury.deserialize_from_reader::<Order, _>(&mut stream) {
+        Err(Error::Io(err)) => assert_eq!(err.kind(), ErrorKind::UnexpectedEof),
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+#[test]
+fn truncated_frame() {
+    let fury = fury(Mode::SchemaConsistent);
+    let mut bf = Vec::new();
+    let len = fury.serialize_framed_to_writer(&order(1), &mut bf).unwrap();
+    assert_eq!(len, bf.len());
+    assert_eq!(&bf[4..], fury.serialize(&order(1)).as_slice());
+
+    bf.truncate(len - 3);
+    match fury.deserialize_from_reader::<Order, _>(&mut Cursor::new(bf)) {
+        Err(Error::Io(err)) => assert_eq!(err.kind(), ErrorKind::UnexpectedEof),
+        other => panic!("unexpected {other:?}"),
+    }
+}
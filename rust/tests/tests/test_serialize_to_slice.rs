@@ -0,0 +1,167 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_derive::Fury;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::mem::MaybeUninit;
+
+// counts the allocations of the current thread, tests run on threads of their own
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|count| count.get())
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Quote {
+    symbol: i32,
+    bid: f64,
+    ask: f64,
+    size: i64,
+}
+
+fn quote(n: i32) -> Quote {
+    Quote {
+        symbol: n,
+        bid: 100.25,
+        ask: 100.5,
+        size: 1_000,
+    }
+}
+
+fn fury() -> Fury {
+    let mut fury = Fury::default();
+    fury.register::<Quote>(400);
+    fury
+}
+
+#[test]
+fn stack_buffer() {
+    let fury = fury();
+    let mut out = [0u8; 256];
+    let len = fury.serialize_to_slice(&quote(1), &mut out).unwrap();
+    assert_eq!(&out[..len], fury.serialize(&quote(1)).as_slice());
+    assert_eq!(fury.deserialize::<Quote>(&out[..len]).unwrap(), quote(1));
+
+    let before = allocations();
+    for n in 2..100 {
+        assert_eq!(fury.serialize_to_slice(&quote(n), &mut out).unwrap(), len);
+    }
+    assert_eq!(allocations(), before);
+    assert_eq!(fury.deserialize::<Quote>(&out[..len]).unwrap(), quote(99));
+}
+
+#[test]
+fn buffer_too_small() {
+    let fury = fury();
+    let required = fury.serialize(&quote(1)).len();
+    let mut out = [0xaau8; 8];
+    match fury.serialize_to_slice(&quote(1), &mut out) {
+        Err(Error::BufferTooSmall {
+            required: r,
+            available,
+        }) => {
+            assert_eq!(r, required);
+            assert_eq!(available, 8);
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+    assert_eq!(out, [0xaa; 8]);
+
+    let mut out = vec![0u8; required];
+    assert_eq!(
+        fury.serialize_to_slice(&quote(1), &mut out).unwrap(),
+        required
+    );
+}
+
+fn initialized(out: &[MaybeUninit<u8>]) -> Vec<u8> {
+    // only called on the prefix `serialize_into_uninit` reported as initialized
+    out.iter().map(|b| unsafe { b.assume_init() }).collect()
+}
+
+#[test]
+fn uninit_buffer() {
+    let fury = fury();
+    let mut out = [MaybeUninit::<u8>::uninit(); 256];
+    let len = fury.serialize_into_uninit(&quote(1), &mut out).unwrap();
+    assert_eq!(initialized(&out[..len]), fury.serialize(&quote(1)));
+
+    let before = allocations();
+    for n in 2..100 {
+        assert_eq!(
+            fury.serialize_into_uninit(&quote(n), &mut out).unwrap(),
+            len
+        );
+    }
+    assert_eq!(allocations(), before);
+    let bin = initialized(&out[..len]);
+    assert_eq!(fury.deserialize::<Quote>(&bin).unwrap(), quote(99));
+
+    let mut small = [MaybeUninit::<u8>::uninit(); 8];
+    assert!(matches!(
+        fury.serialize_into_uninit(&quote(1), &mut small),
+        Err(Error::BufferTooSmall { required, available: 8 }) if required == len
+    ));
+}
+
+#[test]
+fn exact_size_slots() {
+    let fury = fury();
+    let mut ring = [0u8; 1024];
+    let mut head = 0;
+    let size = fury.serialized_size(&quote(0));
+    let before = allocations();
+    for n in 0..100 {
+        if ring.len() - head < size {
+            head = 0;
+        }
+        let len = fury.serialize_exact(&quote(n), &mut ring[head..]).unwrap();
+        assert_eq!(len, size);
+        assert_eq!(
+            fury.deserialize::<Quote>(&ring[head..head + len]).unwrap(),
+            quote(n)
+        );
+        head += len;
+    }
+    assert_eq!(allocations(), before);
+
+    assert!(matches!(
+        fury.serialize_exact(&quote(1), &mut ring[..size - 1]),
+        Err(Error::BufferTooSmall { required, .. }) if required == size
+    ));
+}
@@ -0,0 +1,382 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_core::resolver::context::{ReadContext, WriteContext};
+use fury_core::serializer::{BorrowDeserialize, Serializer};
+use fury_core::types::{FieldType, FuryGeneralList, Mode, RefFlag};
+use fury_derive::Fury;
+use std::cell::Cell;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Item {
+    id: i32,
+    name: String,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Basket {
+    items: Vec<Rc<Item>>,
+    favorite: Option<Rc<Item>>,
+    shared: Arc<Item>,
+    again: Arc<Item>,
+}
+
+// children point back to their parent
+#[derive(Fury, Debug)]
+struct Node {
+    name: String,
+    parent: Weak<Node>,
+    children: Vec<Rc<Node>>,
+}
+
+fn fury(compatible: bool) -> Fury {
+    let mode = if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    };
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Item>(101);
+    fury.register::<Basket>(102);
+    fury.register::<Node>(103);
+    fury.register::<MeFirst>(104);
+    fury.register::<MeLast>(105);
+    fury
+}
+
+fn item(id: i32) -> Rc<Item> {
+    Rc::new(Item {
+        id,
+        name: format!("item {id}"),
+    })
+}
+
+#[test]
+fn shared_values() {
+    for compatible in [false, true] {
+        let fury = fury(compatible);
+        let (apple, pear) = (item(1), item(2));
+        let shared = Arc::new(Item {
+            id: 3,
+            name: String::from("plum"),
+        });
+        let basket = Basket {
+            items: vec![apple.clone(), pear, apple.clone()],
+            favorite: Some(apple),
+            shared: shared.clone(),
+            again: shared,
+        };
+        let read: Basket = fury.deserialize(&fury.serialize(&basket)).unwrap();
+        assert_eq!(read, basket);
+        assert!(Rc::ptr_eq(&read.items[0], &read.items[2]));
+        assert!(Rc::ptr_eq(&read.items[0], read.favorite.as_ref().unwrap()));
+        assert!(!Rc::ptr_eq(&read.items[0], &read.items[1]));
+        assert!(Arc::ptr_eq(&read.shared, &read.again));
+    }
+}
+
+#[test]
+fn refs_are_written_once() {
+    let fury = fury(false);
+    let apple = item(1);
+    let once = fury.serialize(&vec![apple.clone()]);
+    let twice = fury.serialize(&vec![apple.clone(), apple]);
+    // the second one is a ref flag and ref id 0
+    assert_eq!(twice.len(), once.len() + 2);
+    assert_eq!(twice[twice.len() - 2..], [RefFlag::Ref as i8 as u8, 0]);
+    assert!(once.contains(&(RefFlag::RefValue as i8 as u8)));
+}
+
+fn tree() -> Rc<Node> {
+    Rc::new_cyclic(|root| Node {
+        name: String::from("root"),
+        parent: Weak::new(),
+        children: ["left", "right"]
+            .into_iter()
+            .map(|name| {
+                Rc::new(Node {
+                    name: String::from(name),
+                    parent: root.clone(),
+                    children: Vec::new(),
+                })
+            })
+            .collect(),
+    })
+}
+
+#[test]
+fn cycles_through_weak() {
+    for compatible in [false, true] {
+        let fury = fury(compatible);
+        let root: Rc<Node> = fury.deserialize(&fury.serialize(&tree())).unwrap();
+        assert_eq!(root.name, "root");
+        assert!(root.parent.upgrade().is_none());
+        let names: Vec<&str> = root.children.iter().map(|child| &*child.name).collect();
+        assert_eq!(names, ["left", "right"]);
+        for child in &root.children {
+            assert!(Rc::ptr_eq(&child.parent.upgrade().unwrap(), &root));
+        }
+    }
+}
+
+#[test]
+fn dangling_weak() {
+    let fury = fury(false);
+    let node = Node {
+        name: String::from("orphan"),
+        parent: Rc::downgrade(&tree()),
+        children: Vec::new(),
+    };
+    let read: Node = fury.deserialize(&fury.serialize(&node)).unwrap();
+    assert!(read.parent.upgrade().is_none());
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Labels {
+    labels: Vec<Rc<Option<String>>>,
+    main: Rc<Option<String>>,
+}
+
+#[test]
+fn pointers_to_null() {
+    let fury = fury(false);
+    let none = Rc::new(None::<String>);
+    let read: Rc<Option<String>> = fury.deserialize(&fury.serialize(&none)).unwrap();
+    assert_eq!(*read, None);
+
+    // nulls aren't tracked, the refs after them keep their ids
+    let some = Rc::new(Some(String::from("a")));
+    let labels = Labels {
+        labels: vec![none.clone(), some.clone(), none, some.clone()],
+        main: some,
+    };
+    let mut fury = fury;
+    fury.register::<Labels>(104);
+    let read: Labels = fury.deserialize(&fury.serialize(&labels)).unwrap();
+    assert_eq!(read, labels);
+    assert!(Rc::ptr_eq(&read.labels[1], &read.labels[3]));
+    assert!(Rc::ptr_eq(&read.labels[1], &read.main));
+}
+
+#[test]
+fn untracked_values_read_into_rc() {
+    let fury = fury(false);
+    let bin = fury.serialize(&Item {
+        id: 7,
+        name: String::from("plain"),
+    });
+    let read: Rc<Item> = fury.deserialize(&bin).unwrap();
+    assert_eq!(read.id, 7);
+}
+
+#[test]
+fn refs_into_untracked_types() {
+    let fury = fury(false);
+    let apple = item(1);
+    let bin = fury.serialize(&vec![apple.clone(), apple]);
+    let err = fury.deserialize::<Vec<Item>>(&bin).unwrap_err();
+    assert!(matches!(err.cause(), Error::Ref));
+}
+
+#[test]
+fn errors_inside_cyclic_reads() {
+    let fury = fury(false);
+    let mut bin = fury.serialize(&item(1));
+    // the type id after the 8 byte header and the ref flag
+    bin[9..11].copy_from_slice(&i16::from(FieldType::INT32).to_le_bytes());
+    let err = fury.deserialize::<Rc<Item>>(&bin).unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        "Invalid field type, expected:101, actual:7"
+    );
+}
+
+// the outer pointers are shared as well as the inner ones
+#[allow(clippy::redundant_allocation)]
+#[derive(Fury, Debug)]
+struct Nested {
+    counters: Vec<Rc<Rc<i32>>>,
+    outer: Rc<Rc<i32>>,
+    weak: Rc<Weak<Item>>,
+    items: Vec<Rc<Item>>,
+    maybe: Rc<Option<Rc<Item>>>,
+}
+
+#[test]
+fn pointers_to_pointers() {
+    for compatible in [false, true] {
+        for compact in [false, true] {
+            let mut fury = fury(compatible).compact_elements(compact);
+            fury.register::<Nested>(105);
+            let counter = Rc::new(7);
+            let outer = Rc::new(counter.clone());
+            let apple = item(1);
+            let nested = Nested {
+                counters: vec![outer.clone(), Rc::new(counter), outer.clone()],
+                outer,
+                weak: Rc::new(Rc::downgrade(&apple)),
+                items: vec![apple.clone()],
+                maybe: Rc::new(Some(apple)),
+            };
+            let read: Nested = fury.deserialize(&fury.serialize(&nested)).unwrap();
+            assert_eq!(**read.outer, 7);
+            // both levels keep their sharing
+            assert!(Rc::ptr_eq(&read.counters[0], &read.counters[2]));
+            assert!(Rc::ptr_eq(&read.counters[0], &read.outer));
+            assert!(!Rc::ptr_eq(&read.counters[0], &read.counters[1]));
+            assert!(Rc::ptr_eq(&*read.counters[0], &*read.counters[1]));
+            let apple = read.weak.upgrade().unwrap();
+            assert!(Rc::ptr_eq(&apple, &read.items[0]));
+            assert!(Rc::ptr_eq(&apple, read.maybe.as_ref().as_ref().unwrap()));
+
+            #[allow(clippy::redundant_allocation)]
+            let top: Rc<Rc<i32>> = fury.deserialize(&fury.serialize(&read.outer)).unwrap();
+            assert_eq!(**top, 7);
+        }
+    }
+}
+
+fn chain(depth: usize, parent: Weak<Node>) -> Rc<Node> {
+    Rc::new_cyclic(|node| Node {
+        name: format!("node {depth}"),
+        parent,
+        children: match depth {
+            0 => Vec::new(),
+            _ => vec![chain(depth - 1, node.clone())],
+        },
+    })
+}
+
+#[test]
+fn deep_cycles_through_weak() {
+    let fury = fury(false);
+    // every level points back to the one above, the levels below aren't read again for each
+    let root: Rc<Node> = fury
+        .deserialize(&fury.serialize(&chain(60, Weak::new())))
+        .unwrap();
+    let mut node = root;
+    while let Some(child) = node.children.first().cloned() {
+        assert!(Rc::ptr_eq(&child.parent.upgrade().unwrap(), &node));
+        node = child;
+    }
+    assert_eq!(node.name, "node 0");
+}
+
+#[test]
+fn errors_after_weak_back_refs() {
+    let fury = fury(false);
+    let bin = fury.serialize(&tree());
+    // fails rather than unwinding out of `new_cyclic` wherever the input stops
+    for len in 0..bin.len() {
+        assert!(fury.deserialize::<Rc<Node>>(&bin[..len]).is_err());
+    }
+}
+
+thread_local! {
+    static READS: Cell<usize> = const { Cell::new(0) };
+}
+
+// counts how often the struct holding it is read
+#[derive(Debug)]
+struct ReadCount;
+
+impl Serializer for ReadCount {
+    fn reserved_space() -> usize {
+        1
+    }
+
+    fn write(&self, context: &mut WriteContext) {
+        context.writer.i8(0);
+    }
+
+    fn read(context: &mut ReadContext) -> Result<Self, Error> {
+        context.reader.i8()?;
+        READS.with(|reads| reads.set(reads.get() + 1));
+        Ok(ReadCount)
+    }
+
+    fn get_type_id(_fury: &Fury) -> i16 {
+        FieldType::INT8.into()
+    }
+}
+
+impl FuryGeneralList for ReadCount {}
+
+impl BorrowDeserialize<'_> for ReadCount {
+    fn read_borrowed(context: &mut ReadContext) -> Result<Self, Error> {
+        Self::read(context)
+    }
+}
+
+// nodes pointing to themselves, before their child or after it, as fields are sorted by name
+#[derive(Fury, Debug)]
+struct MeFirst {
+    a_me: Weak<MeFirst>,
+    count: ReadCount,
+    z_child: Option<Rc<MeFirst>>,
+}
+
+#[derive(Fury, Debug)]
+struct MeLast {
+    a_child: Option<Rc<MeLast>>,
+    count: ReadCount,
+    z_me: Weak<MeLast>,
+}
+
+#[test]
+fn nested_weak_pointers_to_self() {
+    const DEPTH: usize = 30;
+    for compatible in [false, true] {
+        let fury = fury(compatible);
+        let mut first: Option<Rc<MeFirst>> = None;
+        let mut last: Option<Rc<MeLast>> = None;
+        for _ in 0..DEPTH {
+            first = Some(Rc::new_cyclic(|me| MeFirst {
+                a_me: me.clone(),
+                count: ReadCount,
+                z_child: first.take(),
+            }));
+            last = Some(Rc::new_cyclic(|me| MeLast {
+                a_child: last.take(),
+                count: ReadCount,
+                z_me: me.clone(),
+            }));
+        }
+
+        // every node is read once, rather than again for each weak pointer around it
+        READS.with(|reads| reads.set(0));
+        let mut node: Rc<MeFirst> = fury.deserialize(&fury.serialize(&first)).unwrap();
+        assert_eq!(READS.with(Cell::get), DEPTH);
+        for _ in 1..DEPTH {
+            assert!(Rc::ptr_eq(&node.a_me.upgrade().unwrap(), &node));
+            node = node.z_child.clone().unwrap();
+        }
+        assert!(node.z_child.is_none());
+
+        READS.with(|reads| reads.set(0));
+        let mut node: Rc<MeLast> = fury.deserialize(&fury.serialize(&last)).unwrap();
+        assert_eq!(READS.with(Cell::get), DEPTH);
+        for _ in 1..DEPTH {
+            assert!(Rc::ptr_eq(&node.z_me.upgrade().unwrap(), &node));
+            node = node.a_child.clone().unwrap();
+        }
+        assert!(node.a_child.is_none());
+    }
+}
@@ -0,0 +1,215 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::meta::is_latin_paths;
+use fury_core::util::{to_utf16_paths, to_utf8_paths};
+use fury_tests::fixtures::Rng;
+
+// every path must agree with `str::is_ascii`
+fn check(s: &str) {
+    for (path, is_latin) in is_latin_paths(s) {
+        assert_eq!(is_latin, s.is_ascii(), "`{path}` path on {s:?}");
+    }
+}
+
+#[test]
+fn non_ascii_at_every_position() {
+    // lengths around the 16 and 32 byte chunks, the non-ASCII char in each chunk and the tail
+    for len in 0..=100 {
+        // 0x7f is the highest ASCII byte
+        let ascii: String = (0..len).map(|i| ['\u{7f}', 'a'][i % 2]).collect();
+        check(&ascii);
+        for pos in 0..len {
+            for c in ['\u{80}', 'é', '中', '🦀'] {
+                let mut s = ascii.clone();
+                s.replace_range(pos..pos + 1, c.encode_utf8(&mut [0; 4]));
+                check(&s);
+            }
+        }
+    }
+}
+
+#[test]
+fn random_strings() {
+    let mut rng = Rng::new(255);
+    for _ in 0..2000 {
+        check(&rng.string(200));
+        // mostly ASCII, so that whole chunks pass before a non-ASCII char
+        let s: String = (0..rng.len(200))
+            .map(|_| match rng.below(64) {
+                0 => 'é',
+                _ => char::from(rng.below(128) as u8),
+            })
+            .collect();
+        check(&s);
+    }
+}
+
+#[test]
+fn invalid_sequences() {
+    // what a reader makes of invalid UTF-8, replacement chars among the rest
+    let mut rng = Rng::new(16);
+    for _ in 0..2000 {
+        let bytes: Vec<u8> = (0..rng.len(200))
+            .map(|_| match rng.below(8) {
+                0 => 0x80 | rng.below(0x80) as u8,
+                _ => rng.below(0x80) as u8,
+            })
+            .collect();
+        check(&String::from_utf8_lossy(&bytes));
+    }
+}
+
+// every path must agree with the scalar one, and with `str::encode_utf16` on valid UTF-8
+fn check_to_utf16(utf8: &[u8]) {
+    for is_little_endian in [false, true] {
+        let paths = to_utf16_paths(utf8, is_little_endian);
+        let expected = match std::str::from_utf8(utf8) {
+            Ok(s) => Ok(s
+                .encode_utf16()
+                .map(|unit| {
+                    if is_little_endian {
+                        unit.swap_bytes()
+                    } else {
+                        unit
+                    }
+                })
+                .collect()),
+            Err(_) => {
+                assert!(paths[0].1.is_err(), "scalar path on {utf8:?}");
+                paths[0].1.clone()
+            }
+        };
+        for (path, utf16) in paths {
+            assert_eq!(
+                utf16, expected,
+                "`{path}` path on {utf8:?}, {is_little_endian}"
+            );
+        }
+    }
+}
+
+#[test]
+fn to_utf16_sequence_at_every_position() {
+    // lengths around the 16 and 32 byte chunks, multi-byte and invalid sequences in each
+    // chunk, across the chunk ends and in the tail
+    let sequences: [&[u8]; 8] = [
+        "é".as_bytes(),
+        "中".as_bytes(),
+        "🦀".as_bytes(),
+        b"\x80",
+        b"\xc3",
+        b"\xe4\xb8",
+        b"\xe4\x41\x80",
+        b"\xed\xa0\x80",
+    ];
+    for len in 0..=100 {
+        // every ASCII byte, in an order that tells the units of a chunk apart
+        let ascii: Vec<u8> = (0..len).map(|i| (i * 7 % 128) as u8).collect();
+        check_to_utf16(&ascii);
+        for pos in 0..len {
+            for sequence in sequences {
+                let mut utf8 = ascii.clone();
+                utf8.splice(pos..pos + 1, sequence.iter().copied());
+                check_to_utf16(&utf8);
+            }
+        }
+    }
+}
+
+#[test]
+fn to_utf16_random() {
+    let mut rng = Rng::new(262);
+    for _ in 0..2000 {
+        check_to_utf16(rng.string(200).as_bytes());
+        // mostly ASCII, so that whole chunks pass before a multi-byte or invalid sequence
+        let utf8: Vec<u8> = (0..rng.len(200))
+            .map(|_| match rng.below(64) {
+                0 => 0x80 | rng.below(0x80) as u8,
+                _ => rng.below(0x80) as u8,
+            })
+            .collect();
+        check_to_utf16(&utf8);
+    }
+}
+
+// every path must agree with the scalar one, and with `String::from_utf16` on valid UTF-16
+fn check_to_utf8(units: &[u16]) {
+    for is_little_endian in [false, true] {
+        let utf16: Vec<u16> = if is_little_endian {
+            units.iter().map(|unit| unit.swap_bytes()).collect()
+        } else {
+            units.to_vec()
+        };
+        let paths = to_utf8_paths(&utf16, is_little_endian);
+        let expected = match String::from_utf16(units) {
+            Ok(s) => Ok(s.into_bytes()),
+            // lone low surrogates are written as they are, all else is an error
+            Err(_) => paths[0].1.clone(),
+        };
+        for (path, utf8) in paths {
+            assert_eq!(
+                utf8, expected,
+                "`{path}` path on {units:x?}, {is_little_endian}"
+            );
+        }
+    }
+}
+
+#[test]
+fn to_utf8_unit_at_every_position() {
+    // lengths around the 16 and 32 unit chunks, other units in each chunk, across the chunk ends
+    // and in the tail
+    let sequences: [&[u16]; 8] = [
+        &[0x80],
+        &[0x7ff],
+        &[0x4e2d],
+        &[0xd83e, 0xdd80],
+        &[0xd83e],
+        &[0xdd80],
+        &[0xd83e, 0x41],
+        &[0xffff],
+    ];
+    for len in 0..=100 {
+        let ascii: Vec<u16> = (0..len).map(|i| (i * 7 % 128) as u16).collect();
+        check_to_utf8(&ascii);
+        for pos in 0..len {
+            for sequence in sequences {
+                let mut units = ascii.clone();
+                units.splice(pos..pos + 1, sequence.iter().copied());
+                check_to_utf8(&units);
+            }
+        }
+    }
+}
+
+#[test]
+fn to_utf8_random() {
+    let mut rng = Rng::new(8);
+    for _ in 0..2000 {
+        check_to_utf8(&rng.string(200).encode_utf16().collect::<Vec<_>>());
+        // mostly ASCII, with surrogates that may or may not pair up
+        let units: Vec<u16> = (0..rng.len(200))
+            .map(|_| match rng.below(64) {
+                0 => 0xd800 | rng.below(0x800) as u16,
+                1 => rng.below(0x10000) as u16,
+                _ => rng.below(0x80) as u16,
+            })
+            .collect();
+        check_to_utf8(&units);
+    }
+}
@@ -0,0 +1,73 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_core::profile::SizeProfiler;
+use fury_derive::Fury;
+use std::sync::Arc;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Order {
+    id: i64,
+    note: String,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Ping {
+    seq: i32,
+}
+
+#[test]
+fn histograms_per_root_type() {
+    let profiler = Arc::new(SizeProfiler::new());
+    let mut fury = Fury::default().size_profiler(profiler.clone());
+    fury.register::<Order>(101);
+    fury.register::<Ping>(102);
+
+    let mut sizes = Vec::new();
+    for len in [0, 10, 100, 1000] {
+        let order = Order {
+            id: 1,
+            note: "x".repeat(len),
+        };
+        sizes.push(fury.serialize(&order).len());
+    }
+    let ping = fury.serialize(&Ping { seq: 1 }).len();
+    fury.serialize(&Ping { seq: 2 });
+
+    let profile = profiler.snapshot();
+    assert_eq!(profile.len(), 2);
+    let orders = &profile[std::any::type_name::<Order>()];
+    assert_eq!(orders.count, 4);
+    assert_eq!(orders.total, sizes.iter().sum::<usize>() as u64);
+    assert_eq!(orders.min, sizes[0]);
+    assert_eq!(orders.max, sizes[3]);
+    assert_eq!(orders.buckets.iter().sum::<u64>(), 4);
+    assert_eq!(orders.quantile(1.0), sizes[3]);
+    assert!(orders.quantile(0.5) >= sizes[1] && orders.quantile(0.5) < sizes[2]);
+
+    let pings = &profile[std::any::type_name::<Ping>()];
+    assert_eq!((pings.count, pings.min, pings.max), (2, ping, ping));
+    assert_eq!(pings.mean(), ping as f64);
+
+    profiler.reset();
+    assert!(profiler.snapshot().is_empty());
+}
+
+#[test]
+fn off_by_default() {
+    assert!(Fury::default().get_size_profiler().is_none());
+}
@@ -0,0 +1,127 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, Default)]
+struct Catalog {
+    name: String,
+    prices: Vec<i64>,
+    #[fury(skip)]
+    lookups: Mutex<HashMap<String, usize>>,
+    #[fury(skip, default = "String::from(\"unsaved\")")]
+    state: String,
+}
+
+#[derive(Fury, Debug, Default)]
+#[fury(presence_bitmap)]
+struct Sparse {
+    a: Option<i32>,
+    #[fury(skip, default = "Some(-1)")]
+    cached: Option<i32>,
+    b: Option<String>,
+}
+
+fn fury(compatible: bool) -> Fury {
+    let mode = if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    };
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Catalog>(100);
+    fury.register::<Sparse>(101);
+    fury
+}
+
+#[test]
+fn skipped_fields_get_defaults() {
+    for compatible in [false, true] {
+        let fury = fury(compatible);
+        let catalog = Catalog {
+            name: String::from("spring"),
+            prices: vec![3, 5],
+            lookups: Mutex::new(HashMap::from([(String::from("a"), 1)])),
+            state: String::from("dirty"),
+        };
+        let obj: Catalog = fury.deserialize(&fury.serialize(&catalog)).unwrap();
+        assert_eq!(obj.name, "spring");
+        assert_eq!(obj.prices, vec![3, 5]);
+        assert!(obj.lookups.lock().unwrap().is_empty());
+        assert_eq!(obj.state, "unsaved");
+
+        let sparse = Sparse {
+            a: None,
+            cached: Some(9),
+            b: Some(String::from("x")),
+        };
+        let obj: Sparse = fury.deserialize(&fury.serialize(&sparse)).unwrap();
+        assert_eq!((obj.a, obj.cached, obj.b), (None, Some(-1), sparse.b));
+    }
+}
+
+#[test]
+fn skipped_fields_are_not_written() {
+    #[derive(Fury, Debug, Default)]
+    struct Plain {
+        name: String,
+        prices: Vec<i64>,
+    }
+
+    for compatible in [false, true] {
+        let mut fury = fury(compatible);
+        fury.register::<Plain>(102);
+        let catalog = Catalog {
+            name: String::from("spring"),
+            prices: vec![3, 5],
+            lookups: Mutex::new(HashMap::from([(String::from("a"), 1)])),
+            state: String::from("dirty"),
+        };
+        let plain = Plain {
+            name: String::from("spring"),
+            prices: vec![3, 5],
+        };
+        assert_eq!(fury.serialize(&catalog).len(), fury.serialize(&plain).len());
+    }
+}
+
+#[test]
+fn default_for_missing_field() {
+    #[derive(Fury, Debug)]
+    struct Old {
+        id: i64,
+    }
+
+    #[derive(Fury, Debug)]
+    struct New {
+        id: i64,
+        #[fury(default = "30")]
+        timeout: u32,
+    }
+
+    let mut old = Fury::default().mode(Mode::Compatible);
+    old.register::<Old>(100);
+    let mut new = Fury::default().mode(Mode::Compatible);
+    new.register::<New>(100);
+    let obj: New = new.deserialize(&old.serialize(&Old { id: 4 })).unwrap();
+    assert_eq!((obj.id, obj.timeout), (4, 30));
+}
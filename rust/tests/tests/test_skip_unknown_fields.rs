@@ -0,0 +1,177 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+// types only the writer knows
+mod writer {
+    use super::*;
+
+    #[derive(Fury, Debug, PartialEq, Eq, Hash)]
+    pub enum Color {
+        Red,
+        Green,
+    }
+
+    #[derive(Fury, Debug, PartialEq)]
+    pub enum Shape {
+        Dot,
+        Circle(i32),
+        Rect { w: i32, h: i32 },
+    }
+
+    #[derive(Fury, Debug, PartialEq)]
+    pub struct Leaf {
+        pub name: String,
+        pub color: Color,
+        pub shapes: Vec<Shape>,
+    }
+
+    #[derive(Fury, Debug, PartialEq)]
+    pub struct Branch {
+        pub leaves: Vec<Leaf>,
+        pub by_color: HashMap<Color, Vec<Leaf>>,
+    }
+
+    #[derive(Fury, Debug, PartialEq)]
+    pub struct Point {
+        pub x: i32,
+        pub y: i32,
+        pub label: String,
+    }
+
+    #[derive(Fury)]
+    pub struct Tree {
+        pub id: i64,
+        pub color: Color,
+        pub shape: Shape,
+        pub colors: Vec<Color>,
+        pub branch: Branch,
+        pub branches: Vec<Option<Branch>>,
+        pub shared: Vec<Rc<Leaf>>,
+        pub set: HashSet<i32>,
+        pub span: (i32, String),
+        pub any: HashMap<String, Box<dyn Any>>,
+        pub origin: Option<Point>,
+        pub name: String,
+    }
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Tree {
+    id: i64,
+    origin: Option<Point>,
+    name: String,
+}
+
+fn leaf(name: &str) -> writer::Leaf {
+    writer::Leaf {
+        name: name.to_string(),
+        color: writer::Color::Green,
+        shapes: vec![
+            writer::Shape::Circle(3),
+            writer::Shape::Dot,
+            writer::Shape::Rect { w: 1, h: 2 },
+        ],
+    }
+}
+
+fn branch() -> writer::Branch {
+    writer::Branch {
+        leaves: vec![leaf("a"), leaf("b")],
+        by_color: HashMap::from([(writer::Color::Red, vec![leaf("c")])]),
+    }
+}
+
+#[test]
+fn nested_unknown_fields() {
+    for guard in [false, true] {
+        let mut fury = Fury::default()
+            .mode(Mode::Compatible)
+            .struct_length_guard(guard);
+        fury.register::<writer::Color>(201);
+        fury.register::<writer::Shape>(202);
+        fury.register::<writer::Leaf>(203);
+        fury.register::<writer::Branch>(204);
+        fury.register::<writer::Point>(205);
+        fury.register::<writer::Tree>(206);
+        let shared = Rc::new(leaf("d"));
+        let mut any: HashMap<String, Box<dyn Any>> = HashMap::new();
+        any.insert(String::from("leaf"), Box::new(leaf("e")));
+        any.insert(String::from("n"), Box::new(7i32));
+        let bin = fury.serialize(&writer::Tree {
+            id: 1,
+            color: writer::Color::Red,
+            shape: writer::Shape::Rect { w: 3, h: 4 },
+            colors: vec![writer::Color::Green, writer::Color::Red],
+            branch: branch(),
+            branches: vec![Some(branch()), None],
+            shared: vec![shared.clone(), shared],
+            set: HashSet::from([5, 6]),
+            span: (7, String::from("span")),
+            any,
+            origin: Some(writer::Point {
+                x: 8,
+                y: 9,
+                label: String::from("origin"),
+            }),
+            name: String::from("tree"),
+        });
+
+        // none of the writer's enums and nested structs are registered here
+        let mut fury = Fury::default()
+            .mode(Mode::Compatible)
+            .struct_length_guard(guard);
+        fury.register::<Point>(205);
+        fury.register::<Tree>(206);
+        assert_eq!(
+            fury.deserialize::<Tree>(&bin).unwrap(),
+            Tree {
+                id: 1,
+                origin: Some(Point { x: 8, y: 9 }),
+                name: String::from("tree"),
+            }
+        );
+    }
+}
+
+#[test]
+fn unknown_values_outside_unknown_fields() {
+    let mut writer = Fury::default().mode(Mode::Compatible);
+    writer.register::<writer::Color>(201);
+    let bin = writer.serialize(&vec![writer::Color::Red]);
+    // only values of fields the reader dropped anyway are skipped
+    let reader = Fury::default().mode(Mode::Compatible);
+    assert_eq!(
+        reader
+            .deserialize_value(&bin)
+            .unwrap_err()
+            .cause()
+            .to_string(),
+        "Unknown type id 201"
+    );
+}
@@ -0,0 +1,56 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+
+fn sniff(bin: &[u8]) -> String {
+    match Fury::default().deserialize::<String>(bin) {
+        Err(err @ Error::NotFuryPayload { .. }) => err.to_string(),
+        other => panic!("expected a non-Fury payload error, got {other:?}"),
+    }
+}
+
+#[test]
+fn json() {
+    let message = sniff(br#"{"id": 1, "name": "fury", "tags": []}"#);
+    assert_eq!(
+        message,
+        r#"input does not look like a Fury payload (no magic header); first bytes: 7b 22 69 64 22 3a 20 31 2c 20 22 6e 61 6d 65 22 ("{\"id\": 1, \"name\"")"#
+    );
+}
+
+#[test]
+fn protobuf() {
+    // field 1 varint 150, field 2 string "hi"
+    let message = sniff(&[0x08, 0x96, 0x01, 0x12, 0x02, b'h', b'i']);
+    assert!(message.ends_with(r#"first bytes: 08 96 01 12 02 68 69 (".....hi")"#));
+}
+
+#[test]
+fn short_input() {
+    assert!(sniff(&[]).ends_with("first bytes: none, the input is empty"));
+    assert!(sniff(&[0xd4]).ends_with("first bytes: d4 (\".\")"));
+}
+
+#[test]
+fn truncated_header() {
+    let bin = Fury::default().serialize(&String::from("hello"));
+    let err = Fury::default()
+        .deserialize::<String>(&bin[..5])
+        .unwrap_err();
+    assert!(err.to_string().contains("header is truncated"));
+}
@@ -0,0 +1,138 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+use std::borrow::Cow;
+
+#[derive(Fury, Debug, PartialEq, Clone)]
+struct Route {
+    prefix: String,
+    weight: i32,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct RoutingTable {
+    name: Cow<'static, str>,
+    routes: Cow<'static, [Route]>,
+    regions: Cow<'static, [String]>,
+    weights: Cow<'static, [i32]>,
+}
+
+fn routes() -> Vec<Route> {
+    vec![
+        Route {
+            prefix: String::from("/api"),
+            weight: 3,
+        },
+        Route {
+            prefix: String::from("/static"),
+            weight: 1,
+        },
+    ]
+}
+
+fn fury(compatible: bool) -> Fury {
+    let mode = if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    };
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Route>(101);
+    fury.register::<RoutingTable>(102);
+    fury
+}
+
+#[test]
+fn borrowed_tables_read_owned() {
+    static ROUTES: [Route; 0] = [];
+    static WEIGHTS: [i32; 3] = [5, 10, 20];
+    let routes: &'static [Route] = Box::leak(routes().into_boxed_slice());
+    for compatible in [false, true] {
+        let table = RoutingTable {
+            name: Cow::Borrowed("default"),
+            routes: Cow::Borrowed(routes),
+            regions: Cow::Borrowed(&[]),
+            weights: Cow::Borrowed(&WEIGHTS),
+        };
+        let bytes = fury(compatible).serialize(&table);
+        let read: RoutingTable = fury(compatible).deserialize(&bytes).unwrap();
+        assert_eq!(read, table);
+        assert!(matches!(read.routes, Cow::Owned(_)));
+        assert!(matches!(read.weights, Cow::Owned(_)));
+
+        let empty = RoutingTable {
+            name: Cow::Borrowed(""),
+            routes: Cow::Borrowed(&ROUTES),
+            regions: Cow::Owned(vec![String::from("eu")]),
+            weights: Cow::Owned(Vec::new()),
+        };
+        let bytes = fury(compatible).serialize(&empty);
+        assert_eq!(
+            fury(compatible)
+                .deserialize::<RoutingTable>(&bytes)
+                .unwrap(),
+            empty
+        );
+    }
+}
+
+#[test]
+fn same_wire_form_as_vec() {
+    let fury = fury(false);
+    let routes = routes();
+    assert_eq!(
+        fury.serialize(&Cow::Borrowed(routes.as_slice())),
+        fury.serialize(&routes)
+    );
+    let read: Cow<[Route]> = fury.deserialize(&fury.serialize(&routes)).unwrap();
+    assert_eq!(read, Cow::<[Route]>::Owned(routes));
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Service {
+    name: &'static str,
+    tags: Vec<&'static str>,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct OwnedService {
+    name: String,
+    tags: Vec<String>,
+}
+
+#[test]
+fn static_str_fields() {
+    let mut writer = Fury::default().mode(Mode::Compatible);
+    writer.register::<Service>(103);
+    let mut reader = Fury::default().mode(Mode::Compatible);
+    reader.register::<OwnedService>(103);
+    let bytes = writer.serialize(&Service {
+        name: "billing",
+        tags: vec!["internal", "eu"],
+    });
+    assert_eq!(
+        reader.deserialize::<OwnedService>(&bytes).unwrap(),
+        OwnedService {
+            name: String::from("billing"),
+            tags: vec![String::from("internal"), String::from("eu")],
+        }
+    );
+    let err = writer.deserialize::<Service>(&bytes).unwrap_err();
+    assert!(err.to_string().contains("read it as `String`"), "{err}");
+}
@@ -0,0 +1,115 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::buffer::{Reader, Writer};
+use fury_core::fury::Fury;
+use fury_core::resolver::context::{ReadContext, WriteContext};
+use fury_core::serializer::{BorrowDeserialize, FuryStr, Serializer};
+use fury_core::types::DecodeMode;
+use std::borrow::Cow;
+
+const LATIN1: u64 = 0;
+const UTF16: u64 = 1;
+const UTF8: u64 = 2;
+
+// a string body as a peer would write it
+fn body(flag: u64, bytes: &[u8]) -> Vec<u8> {
+    let mut writer = Writer::default();
+    writer.var_uint64((bytes.len() as u64) << 2 | flag);
+    writer.bytes(bytes);
+    writer.dump()
+}
+
+fn utf16(value: &str) -> Vec<u8> {
+    value.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn read<T: Serializer>(bin: &[u8]) -> T {
+    let fury = Fury::default();
+    let mut context = ReadContext::new(&fury, Reader::new(bin));
+    let value = T::read(&mut context).unwrap();
+    assert_eq!(context.reader.remaining(), 0);
+    value
+}
+
+fn read_borrowed<'bf, T: BorrowDeserialize<'bf>>(fury: &Fury, bin: &'bf [u8]) -> T {
+    let mut context = ReadContext::new(fury, Reader::new(bin));
+    context.decode_mode = DecodeMode::Borrowed;
+    T::read_borrowed(&mut context).unwrap()
+}
+
+#[test]
+fn reads_every_encoding() {
+    let cases = [
+        (body(LATIN1, b"plain"), "plain"),
+        (body(LATIN1, b"caf\xe9 \xa3"), "café £"),
+        (body(UTF16, &utf16("日本 😀")), "日本 😀"),
+        (body(UTF8, "Grüße 😀".as_bytes()), "Grüße 😀"),
+        (body(UTF16, &[]), ""),
+    ];
+    for (bin, expected) in cases {
+        assert_eq!(read::<String>(&bin), expected);
+        assert_eq!(read::<Cow<str>>(&bin), expected);
+        assert_eq!(read::<FuryStr>(&bin).as_str(), expected);
+    }
+}
+
+#[test]
+fn writes_ascii_as_latin1() {
+    let fury = Fury::default();
+    for (value, expected) in [
+        ("abc", body(LATIN1, b"abc")),
+        ("é", body(UTF8, "é".as_bytes())),
+    ] {
+        let mut writer = Writer::default();
+        value.write(&mut WriteContext::new(&fury, &mut writer));
+        assert_eq!(writer.dump(), expected);
+    }
+}
+
+#[test]
+fn borrows_only_utf8_bytes() {
+    let fury = Fury::default();
+    let ascii = body(LATIN1, b"plain");
+    assert!(matches!(
+        read_borrowed::<Cow<str>>(&fury, &ascii),
+        Cow::Borrowed("plain")
+    ));
+    assert_eq!(read_borrowed::<&str>(&fury, &ascii), "plain");
+
+    let latin1 = body(LATIN1, b"caf\xe9");
+    assert!(matches!(
+        read_borrowed::<Cow<str>>(&fury, &latin1),
+        Cow::Owned(value) if value == "café"
+    ));
+    let mut context = ReadContext::new(&fury, Reader::new(&latin1));
+    let err = <&str>::read_borrowed(&mut context).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`&str` can't borrow a LATIN1 string, read it as `Cow<str>`"
+    );
+}
+
+#[test]
+fn round_trip() {
+    let fury = Fury::default();
+    for value in ["", "ascii", "Grüße", "日本 😀"] {
+        let read: String = fury
+            .deserialize(&fury.serialize(&value.to_string()))
+            .unwrap();
+        assert_eq!(read, value);
+    }
+}
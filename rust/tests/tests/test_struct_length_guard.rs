@@ -0,0 +1,128 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Item {
+    id: i32,
+    name: String,
+    price: i64,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Basket {
+    items: Vec<Item>,
+    total: i64,
+}
+
+mod edited {
+    use fury_derive::Fury;
+
+    // `Item` without `price`
+    #[derive(Fury, Debug, PartialEq)]
+    pub struct Item {
+        pub id: i32,
+        pub name: String,
+    }
+
+    // `Item` with an extra `weight`
+    #[derive(Fury, Debug, PartialEq)]
+    pub struct Item2 {
+        pub id: i32,
+        pub name: String,
+        pub price: i64,
+        pub weight: i64,
+    }
+}
+
+fn fury() -> Fury {
+    let mut fury = Fury::default().struct_length_guard(true);
+    fury.register::<Item>(101);
+    fury.register::<Basket>(102);
+    fury
+}
+
+fn reader<T: fury_core::serializer::StructSerializer>() -> Fury {
+    let mut fury = Fury::default().struct_length_guard(true);
+    fury.register::<T>(101);
+    fury
+}
+
+fn item() -> Item {
+    Item {
+        id: 7,
+        name: String::from("tea"),
+        price: 350,
+    }
+}
+
+#[test]
+fn round_trip() {
+    let basket = Basket {
+        items: vec![item(), item()],
+        total: 700,
+    };
+    let fury = fury();
+    assert_eq!(
+        fury.deserialize::<Basket>(&fury.serialize(&basket))
+            .unwrap(),
+        basket
+    );
+    let mut unguarded = Fury::default();
+    unguarded.register::<Item>(101);
+    assert_eq!(
+        fury.serialize(&item()).len(),
+        unguarded.serialize(&item()).len() + 4
+    );
+}
+
+#[test]
+fn fewer_fields() {
+    let err = reader::<edited::Item>()
+        .deserialize::<edited::Item>(&fury().serialize(&item()))
+        .unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        "Fields of `test_struct_length_guard::edited::Item` take 14 bytes but were written with 25, the writer's struct has other fields"
+    );
+}
+
+#[test]
+fn more_fields() {
+    // the extra field is read from the next item
+    let err = reader::<edited::Item2>()
+        .deserialize::<Vec<edited::Item2>>(&fury().serialize(&vec![item(), item()]))
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Fields of `test_struct_length_guard::edited::Item2` take"),
+        "{err}"
+    );
+}
+
+#[test]
+fn values_rejected() {
+    let fury = fury();
+    let err = fury
+        .deserialize_value(&fury.serialize(&item()))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Messages with struct length guards can't be read or written as `Value`"
+    );
+}
@@ -0,0 +1,72 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::thread_safe::{ThreadSafeFury, MAX_POOLED_BUFFER};
+use fury_derive::Fury;
+use std::sync::Arc;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Event {
+    id: i64,
+    source: String,
+}
+
+fn fury() -> ThreadSafeFury {
+    let mut fury = Fury::default();
+    fury.register::<Event>(101);
+    ThreadSafeFury::new(fury)
+}
+
+#[test]
+fn concurrent_use() {
+    let fury = Arc::new(fury());
+    let handles: Vec<_> = (0..8)
+        .map(|worker| {
+            let fury = fury.clone();
+            std::thread::spawn(move || {
+                for id in 0..200 {
+                    let event = Event {
+                        id,
+                        source: format!("worker-{worker}"),
+                    };
+                    let bin = fury.serialize(&event);
+                    assert_eq!(bin, fury.get_fury().serialize(&event));
+                    assert_eq!(fury.deserialize::<Event>(&bin).unwrap(), event);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    // one buffer per thread that was writing at the same time
+    assert!((1..=8).contains(&fury.pooled_buffers()));
+}
+
+#[test]
+fn large_buffers_dropped() {
+    let fury = fury();
+    fury.serialize(&String::from("small"));
+    assert_eq!(fury.pooled_buffers(), 1);
+    let large = "x".repeat(MAX_POOLED_BUFFER * 2);
+    assert_eq!(
+        fury.deserialize::<String>(&fury.serialize(&large)).unwrap(),
+        large
+    );
+    assert_eq!(fury.pooled_buffers(), 0);
+}
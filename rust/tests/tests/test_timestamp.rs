@@ -0,0 +1,107 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use fury::transcode::{from_json, to_json};
+use fury_core::fury::Fury;
+use fury_core::util::{timestamp_from_nanos, timestamp_to_nanos};
+use fury_derive::Fury;
+
+fn at(date: (i32, u32, u32), nanos: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(date.0, date.1, date.2)
+        .unwrap()
+        .and_hms_nano_opt(12, 30, 15, nanos)
+        .unwrap()
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Event {
+    at: NaiveDateTime,
+    #[fury(encode = "delta")]
+    samples: Vec<NaiveDateTime>,
+}
+
+#[test]
+fn nanosecond_precision() {
+    let mut fury = Fury::default();
+    fury.register::<Event>(101);
+    for ts in [
+        at((2024, 2, 29), 123_456_789),
+        at((1969, 12, 31), 999_999_999),
+        at((1900, 1, 1), 1),
+        at((2262, 4, 10), 0),
+    ] {
+        let event = Event {
+            at: ts,
+            samples: vec![ts, at((2000, 1, 1), 7), ts],
+        };
+        let read: Event = fury.deserialize(&fury.serialize(&event)).unwrap();
+        assert_eq!(read, event);
+    }
+}
+
+#[test]
+fn wire_form() {
+    let fury = Fury::default();
+    let ts = DateTime::from_timestamp(1, 5).unwrap().naive_utc();
+    let bytes = fury.serialize(&ts);
+    assert_eq!(bytes[bytes.len() - 8..], 1_000_000_005i64.to_le_bytes());
+    let before_epoch = DateTime::from_timestamp(-1, 999_999_999)
+        .unwrap()
+        .naive_utc();
+    assert_eq!(timestamp_to_nanos(&before_epoch).unwrap(), -1);
+}
+
+#[test]
+fn range() {
+    assert_eq!(
+        timestamp_from_nanos(i64::MIN).to_string(),
+        "1677-09-21 00:12:43.145224192"
+    );
+    assert_eq!(
+        timestamp_from_nanos(i64::MAX).to_string(),
+        "2262-04-11 23:47:16.854775807"
+    );
+    let err = timestamp_to_nanos(&at((2263, 1, 1), 0)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Timestamp 2263-01-01 12:30:15 is out of the range of nanosecond timestamps, 1677-09-21 to 2262-04-11"
+    );
+    assert!(timestamp_to_nanos(&at((1677, 1, 1), 0)).is_err());
+}
+
+#[test]
+#[should_panic(expected = "out of the range of nanosecond timestamps")]
+fn writing_out_of_range_panics() {
+    Fury::default().serialize(&at((3000, 1, 1), 0));
+}
+
+#[test]
+fn json() {
+    let fury = Fury::default();
+    for (ts, text) in [
+        (
+            at((2024, 2, 29), 123_456_789),
+            "\"2024-02-29T12:30:15.123456789\"",
+        ),
+        (at((2024, 2, 29), 0), "\"2024-02-29T12:30:15\""),
+    ] {
+        let json = to_json(&fury.serialize(&ts), &fury).unwrap();
+        assert_eq!(json, text);
+        let bytes = from_json(&json, &fury, 18).unwrap();
+        assert_eq!(fury.deserialize::<NaiveDateTime>(&bytes).unwrap(), ts);
+    }
+}
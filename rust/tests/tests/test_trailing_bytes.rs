@@ -0,0 +1,83 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::error::Error;
+use fury_core::fury::{DeserializeOptions, Fury};
+use fury_core::types::Mode;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Frame {
+    seq: i32,
+    payload: String,
+}
+
+fn frame(seq: i32) -> Frame {
+    Frame {
+        seq,
+        payload: format!("frame {seq}"),
+    }
+}
+
+#[test]
+fn strict_trailing() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default()
+            .mode(mode)
+            .deserialize_options(DeserializeOptions::default().strict_trailing(true));
+        fury.register::<Frame>(1);
+        let mut bin = fury.serialize(&frame(1));
+        assert_eq!(fury.deserialize::<Frame>(&bin).unwrap(), frame(1));
+
+        bin.extend_from_slice(&[0, 0, 0]);
+        assert!(matches!(
+            fury.deserialize::<Frame>(&bin),
+            Err(Error::TrailingBytes { remaining: 3 })
+        ));
+    }
+}
+
+#[test]
+fn lenient_by_default() {
+    let mut fury = Fury::default();
+    fury.register::<Frame>(1);
+    let mut bin = fury.serialize(&frame(1));
+    bin.push(0);
+    assert_eq!(fury.deserialize::<Frame>(&bin).unwrap(), frame(1));
+}
+
+#[test]
+fn partial_from_framed_stream() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = Fury::default().mode(mode);
+        fury.register::<Frame>(1);
+        let mut stream = Vec::new();
+        for seq in 0..3 {
+            stream.extend(fury.serialize(&frame(seq)));
+        }
+
+        let mut offset = 0;
+        for seq in 0..3 {
+            let (obj, consumed) = fury
+                .deserialize_partial_from::<Frame>(&stream[offset..])
+                .unwrap();
+            assert_eq!(obj, frame(seq));
+            offset += consumed;
+        }
+        assert_eq!(offset, stream.len());
+    }
+}
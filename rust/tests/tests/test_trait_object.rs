@@ -0,0 +1,130 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::any::Any;
+
+use fury_core::fury::Fury;
+use fury_core::fury_trait_object;
+use fury_core::serializer::AsAny;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+
+trait Shape: AsAny {
+    fn area(&self) -> f64;
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Circle {
+    radius: f64,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Square {
+    side: f64,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Point {
+    x: i32,
+}
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        3.0 * self.radius * self.radius
+    }
+}
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+}
+
+fury_trait_object!(Shape: Circle, Square);
+
+#[derive(Fury)]
+struct Drawing {
+    main: Box<dyn Shape>,
+    shapes: Vec<Box<dyn Shape>>,
+    hidden: Option<Box<dyn Shape>>,
+}
+
+// the same message, with a field of any type in place of `main`
+#[derive(Fury)]
+#[allow(dead_code)]
+struct AnyDrawing {
+    main: Box<dyn Any>,
+    shapes: Vec<Box<dyn Shape>>,
+    hidden: Option<Box<dyn Shape>>,
+}
+
+fn fury(mode: Mode) -> Fury {
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Circle>(100);
+    fury.register::<Square>(101);
+    fury.register::<Point>(102);
+    fury
+}
+
+fn areas(shapes: &[Box<dyn Shape>]) -> Vec<f64> {
+    shapes.iter().map(|shape| shape.area()).collect()
+}
+
+#[test]
+fn round_trip() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let mut fury = fury(mode);
+        fury.register::<Drawing>(103);
+        let drawing = Drawing {
+            main: Box::new(Square { side: 2.0 }),
+            shapes: vec![
+                Box::new(Circle { radius: 1.0 }),
+                Box::new(Square { side: 3.0 }),
+            ],
+            hidden: None,
+        };
+        let read: Drawing = fury.deserialize(&fury.serialize(&drawing)).unwrap();
+        assert_eq!(read.main.area(), 4.0);
+        assert_eq!(areas(&read.shapes), [3.0, 9.0]);
+        assert!(read.hidden.is_none());
+        let square = (*read.main).as_any().downcast_ref::<Square>();
+        assert_eq!(square, Some(&Square { side: 2.0 }));
+
+        let root: Box<dyn Shape> = Box::new(Circle { radius: 2.0 });
+        let read: Box<dyn Shape> = fury.deserialize(&fury.serialize(&root)).unwrap();
+        assert_eq!(read.area(), 12.0);
+    }
+}
+
+#[test]
+fn other_types_fail() {
+    let mut writer = fury(Mode::Compatible);
+    writer.register::<AnyDrawing>(103);
+    let drawing = AnyDrawing {
+        main: Box::new(Point { x: 1 }),
+        shapes: vec![],
+        hidden: Some(Box::new(Circle { radius: 1.0 })),
+    };
+    let bin = writer.serialize(&drawing);
+
+    let mut reader = fury(Mode::Compatible);
+    reader.register::<Drawing>(103);
+    let err = reader.deserialize::<Drawing>(&bin).err().unwrap();
+    assert!(err
+        .to_string()
+        .contains("isn't one of the implementors of `Shape`"));
+}
@@ -0,0 +1,164 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury::transcode::{from_json, from_msgpack, to_json, to_msgpack};
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_core::value::Value;
+use fury_derive::Fury;
+use serde_json::json;
+use std::collections::HashMap;
+
+#[derive(Fury, Debug, PartialEq)]
+enum Status {
+    Open,
+    Shipped,
+    // more than one upper case letter in a type meta name
+    BackOrdered,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Line {
+    sku: String,
+    price: f64,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Order {
+    id: i64,
+    customer: String,
+    note: Option<String>,
+    tags: Vec<String>,
+    quantities: Vec<i32>,
+    attrs: HashMap<String, i32>,
+    status: Status,
+    line: Line,
+}
+
+fn fury(compatible: bool) -> Fury {
+    let mode = if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    };
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Status>(101);
+    fury.register::<Line>(102);
+    fury.register::<Order>(103);
+    fury
+}
+
+fn order() -> Order {
+    Order {
+        id: 42,
+        customer: "ada".to_string(),
+        note: None,
+        tags: vec!["express".to_string(), "gift".to_string()],
+        quantities: vec![1, 2, 3],
+        attrs: HashMap::from([("priority".to_string(), 7)]),
+        status: Status::BackOrdered,
+        line: Line {
+            sku: "A-1".to_string(),
+            price: 9.5,
+        },
+    }
+}
+
+fn order_json() -> serde_json::Value {
+    json!({
+        "id": 42,
+        "customer": "ada",
+        "note": null,
+        "tags": ["express", "gift"],
+        "quantities": [1, 2, 3],
+        "attrs": {"priority": 7},
+        "status": "BackOrdered",
+        "line": {"sku": "A-1", "price": 9.5},
+    })
+}
+
+#[test]
+fn fury_to_json() {
+    for compatible in [false, true] {
+        let fury = fury(compatible);
+        let json = to_json(&fury.serialize(&order()), &fury).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json, order_json());
+    }
+}
+
+#[test]
+fn json_to_fury() {
+    for compatible in [false, true] {
+        let fury = fury(compatible);
+        let bin = from_json(&order_json().to_string(), &fury, 103).unwrap();
+        assert_eq!(fury.deserialize::<Order>(&bin).unwrap(), order());
+    }
+}
+
+#[test]
+fn msgpack_round_trip() {
+    for compatible in [false, true] {
+        let fury = fury(compatible);
+        let msgpack = to_msgpack(&fury.serialize(&order()), &fury).unwrap();
+        let value = rmpv::decode::read_value(&mut msgpack.as_slice()).unwrap();
+        let customer = value
+            .as_map()
+            .unwrap()
+            .iter()
+            .find(|(key, _)| key.as_str() == Some("customer"))
+            .map(|(_, value)| value.as_str());
+        assert_eq!(customer, Some(Some("ada")));
+
+        let bin = from_msgpack(&msgpack, &fury, 103).unwrap();
+        assert_eq!(fury.deserialize::<Order>(&bin).unwrap(), order());
+    }
+}
+
+#[test]
+fn value_keeps_fury_types() {
+    let fury = fury(false);
+    let value = fury.deserialize_value(&fury.serialize(&order())).unwrap();
+    let Value::Struct { type_id, fields } = &value else {
+        panic!("{value:?}");
+    };
+    assert_eq!(*type_id, Some(103));
+    let quantities = fields.iter().find(|(name, _)| name == "quantities");
+    assert_eq!(quantities.unwrap().1, Value::I32Array(vec![1, 2, 3]));
+    assert_eq!(
+        fury.serialize_value(&value).unwrap(),
+        fury.serialize(&order())
+    );
+}
+
+#[test]
+fn invalid_input() {
+    let fury = fury(false);
+    let mut json = order_json();
+    json["id"] = json!("forty-two");
+    let err = from_json(&json.to_string(), &fury, 103).unwrap_err();
+    assert!(err.to_string().contains("Field `id`"), "{err}");
+
+    // registered under the id of a built-in type
+    let mut fury = Fury::default();
+    fury.register::<Line>(13);
+    let bin = fury.serialize(&Line {
+        sku: "A-1".to_string(),
+        price: 1.0,
+    });
+    assert!(fury.deserialize_value(&bin).is_err());
+}
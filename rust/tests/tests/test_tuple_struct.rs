@@ -0,0 +1,94 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::types::{struct_layout, DecodeMode, Mode};
+use fury_derive::Fury;
+use std::borrow::Cow;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Point(f64, f64);
+
+#[derive(Fury, Debug, PartialEq)]
+struct Marker;
+
+// what a peer without tuples declares
+#[derive(Fury, Debug, PartialEq)]
+struct NamedPoint {
+    _0: f64,
+    _1: f64,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Label<'a>(Cow<'a, str>, Option<Point>, #[fury(skip)] u32, Vec<Marker>);
+
+fn fury(mode: Mode) -> Fury {
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Point>(100);
+    fury.register::<Marker>(101);
+    fury.register::<Label>(102);
+    fury
+}
+
+#[test]
+fn fields_named_after_their_index() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let fury = fury(mode);
+        let mut named = Fury::default().mode(mode);
+        named.register::<NamedPoint>(100);
+        let bin = fury.serialize(&Point(1.5, -2.0));
+        assert_eq!(bin, named.serialize(&NamedPoint { _0: 1.5, _1: -2.0 }));
+        assert_eq!(fury.deserialize::<Point>(&bin).unwrap(), Point(1.5, -2.0));
+    }
+    assert_eq!(
+        struct_layout::<Point>(&fury(Mode::SchemaConsistent)),
+        "_0:12,_1:12"
+    );
+}
+
+#[test]
+fn in_declaration_order() {
+    #[derive(Fury, Debug, PartialEq)]
+    struct Wide(i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8);
+
+    let mut fury = Fury::default();
+    fury.register::<Wide>(100);
+    assert!(struct_layout::<Wide>(&fury).starts_with("_0:3,_1:3,_2:3"));
+    let wide = Wide(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11);
+    let bin = fury.serialize(&wide);
+    assert_eq!(fury.deserialize::<Wide>(&bin).unwrap(), wide);
+}
+
+#[test]
+fn nested_and_borrowed() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let fury = fury(mode);
+        let label = Label(
+            Cow::Borrowed("origin"),
+            Some(Point(0.0, 0.0)),
+            7,
+            vec![Marker, Marker],
+        );
+        let bin = fury.serialize(&label);
+        let read: Label = fury.deserialize(&bin).unwrap();
+        assert_eq!(read, Label(label.0.clone(), label.1, 0, label.3));
+        let read: Label = fury
+            .deserialize_with_mode(&bin, DecodeMode::Borrowed)
+            .unwrap();
+        assert!(matches!(read.0, Cow::Borrowed("origin")));
+    }
+}
@@ -0,0 +1,186 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_core::resolver::context::{ReadContext, WriteContext};
+use fury_core::serializer::{StructSerializer, VersionSerializer, Versioned};
+use fury_core::type_def::{FieldDef, FieldTypeDef, TypeDef};
+use fury_core::types::FieldType;
+use fury_derive::Fury;
+use std::collections::HashMap;
+
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Route {
+    name: String,
+    #[fury(id = 3)]
+    stops: Vec<Point>,
+    tolls: Option<HashMap<String, Vec<i64>>>,
+}
+
+fn fury() -> Fury {
+    let mut fury = Fury::default();
+    fury.register::<Point>(101);
+    fury.register_by_name::<Route>("com.example", "Route")
+        .unwrap();
+    fury
+}
+
+fn field(name: &str, tag_id: Option<u32>, field_type: FieldTypeDef) -> FieldDef {
+    FieldDef {
+        name: name.to_string(),
+        tag_id,
+        field_type,
+    }
+}
+
+fn type_of(field_type: FieldType, generics: Vec<FieldTypeDef>) -> FieldTypeDef {
+    FieldTypeDef {
+        type_id: field_type as i16,
+        generics,
+    }
+}
+
+#[test]
+fn fields_with_generics() {
+    let fury = fury();
+    let route = fury.type_def::<Route>().unwrap();
+    assert_eq!(
+        route.name,
+        Some(("com.example".to_string(), "Route".to_string()))
+    );
+    assert_eq!(route.version, None);
+    let i64_list = FieldTypeDef::new(FieldType::FuryPrimitiveLongArray as i16);
+    let mut fields = vec![
+        field("name", None, FieldTypeDef::new(FieldType::STRING as i16)),
+        field(
+            "#3",
+            Some(3),
+            type_of(FieldType::ARRAY, vec![FieldTypeDef::new(101)]),
+        ),
+        field(
+            "tolls",
+            None,
+            type_of(
+                FieldType::MAP,
+                vec![FieldTypeDef::new(FieldType::STRING as i16), i64_list],
+            ),
+        ),
+    ];
+    fields.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut actual = route.fields.clone();
+    actual.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(actual, fields);
+}
+
+#[test]
+fn bytes_round_trip() {
+    let fury = fury();
+    let route = fury.type_def::<Route>().unwrap();
+    assert_eq!(
+        TypeDef::from_bytes(&route.to_bytes().unwrap()).unwrap(),
+        route
+    );
+    // the bytes start with the type meta peers exchange
+    let type_meta = route.type_meta().unwrap();
+    assert!(route.to_bytes().unwrap().starts_with(&type_meta));
+    let bare = TypeDef::from_bytes(&type_meta).unwrap();
+    assert_eq!(bare.fingerprint, route.fingerprint);
+    assert_eq!(bare.fields.len(), route.fields.len());
+    assert!(bare
+        .fields
+        .iter()
+        .all(|field| field.field_type.generics.is_empty()));
+}
+
+#[test]
+fn corrupt_bytes_fail() {
+    let route = fury().type_def::<Route>().unwrap();
+    let bytes = route.to_bytes().unwrap();
+    let meta_len = route.type_meta().unwrap().len();
+    for len in (0..bytes.len()).filter(|len| *len != meta_len) {
+        assert!(TypeDef::from_bytes(&bytes[..len]).is_err(), "{len}");
+    }
+    for at in 0..bytes.len() {
+        let mut corrupt = bytes.clone();
+        corrupt[at] = 0xff;
+        let _ = TypeDef::from_bytes(&corrupt);
+    }
+
+    // generics nested without end
+    let mut deep = route.clone();
+    let field_type = &mut deep.fields[0].field_type;
+    for _ in 0..100 {
+        let inner = std::mem::replace(field_type, FieldTypeDef::new(field_type.type_id));
+        field_type.generics.push(inner);
+    }
+    let err = TypeDef::from_bytes(&deep.to_bytes().unwrap()).unwrap_err();
+    assert!(err.to_string().contains("nest deeper than 64"), "{err}");
+}
+
+#[test]
+fn fingerprints_follow_the_layout() {
+    let point = fury().type_def::<Point>().unwrap();
+    assert_eq!(point.type_id, 101);
+    assert_eq!(point.type_meta().unwrap(), {
+        let mut fury = Fury::default();
+        fury.register::<Point>(101);
+        fury.get_class_resolver()
+            .get_type_def(std::any::TypeId::of::<Point>(), &fury)
+            .to_vec()
+    });
+    let mut other = Fury::default();
+    other.register::<Point>(102);
+    assert_ne!(
+        other.type_def::<Point>().unwrap().fingerprint,
+        point.fingerprint
+    );
+    assert!(Fury::default().type_def::<Point>().is_err());
+}
+
+struct Label;
+
+impl VersionSerializer<String> for Label {
+    fn write(&self, value: &String, context: &mut WriteContext) {
+        context.writer.bytes(value.as_bytes());
+    }
+
+    fn read(&self, _context: &mut ReadContext) -> Result<String, Error> {
+        Ok(String::new())
+    }
+}
+
+#[test]
+fn versions() {
+    let mut fury = Fury::default();
+    fury.register_versioned(105, 1, Label).unwrap();
+    fury.register_versioned(105, 4, Label).unwrap();
+    let label = fury.type_def::<Versioned<String>>().unwrap();
+    assert_eq!(label.version, Some(4));
+    assert!(label.fields.is_empty());
+    assert_eq!(
+        TypeDef::from_bytes(&label.to_bytes().unwrap()).unwrap(),
+        label
+    );
+    assert!(<Versioned<String> as StructSerializer>::field_types(&fury).is_empty());
+}
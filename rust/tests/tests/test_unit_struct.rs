@@ -0,0 +1,112 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::any::Any;
+
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Ping;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Shutdown {}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Resize {
+    rows: i32,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Session {
+    ping: Ping,
+    last: Option<Shutdown>,
+    pings: Vec<Ping>,
+}
+
+fn fury(mode: Mode) -> Fury {
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Ping>(100);
+    fury.register::<Shutdown>(101);
+    fury.register::<Resize>(102);
+    fury.register::<Session>(103);
+    fury
+}
+
+#[test]
+fn written_as_type_id() {
+    let fury = fury(Mode::SchemaConsistent);
+    // the head, then the not-null flag and the type id, no fields
+    let bin = fury.serialize(&Ping);
+    assert_eq!(&bin[8..], [0xff, 100, 0]);
+    assert_eq!(&fury.serialize(&Shutdown {})[8..], [0xff, 101, 0]);
+
+    // in compatible mode a type meta without fields
+    let fury = self::fury(Mode::Compatible);
+    let bin = fury.serialize(&Ping);
+    let meta = fury
+        .get_class_resolver()
+        .get_type_def(std::any::TypeId::of::<Ping>(), &fury);
+    assert_eq!(&bin[bin.len() - meta.len()..], meta);
+    assert_eq!(fury.deserialize::<Ping>(&bin).unwrap(), Ping);
+}
+
+#[test]
+fn as_fields() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let fury = fury(mode);
+        let session = Session {
+            ping: Ping,
+            last: Some(Shutdown {}),
+            pings: vec![Ping, Ping, Ping],
+        };
+        assert_eq!(
+            fury.deserialize::<Session>(&fury.serialize(&session))
+                .unwrap(),
+            session
+        );
+    }
+}
+
+#[test]
+fn as_commands() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let fury = fury(mode);
+        let commands: Vec<Box<dyn Any>> = vec![
+            Box::new(Ping),
+            Box::new(Resize { rows: 24 }),
+            Box::new(Shutdown {}),
+        ];
+        let read: Vec<Box<dyn Any>> = fury.deserialize(&fury.serialize(&commands)).unwrap();
+        let handled: Vec<String> = read
+            .iter()
+            .map(|command| {
+                if command.is::<Ping>() {
+                    String::from("pong")
+                } else if let Some(resize) = command.downcast_ref::<Resize>() {
+                    format!("resized to {}", resize.rows)
+                } else if command.is::<Shutdown>() {
+                    String::from("bye")
+                } else {
+                    unreachable!()
+                }
+            })
+            .collect();
+        assert_eq!(handled, ["pong", "resized to 24", "bye"]);
+    }
+}
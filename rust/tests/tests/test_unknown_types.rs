@@ -0,0 +1,185 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::{DeserializeOptions, Fury};
+use fury_core::types::{Mode, UnknownTypePolicy};
+use fury_core::value::{OpaqueValue, Value};
+use fury_derive::Fury;
+use std::any::Any;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Click {
+    x: i32,
+    y: i32,
+}
+
+mod producer {
+    use fury_derive::Fury;
+    use std::any::Any;
+
+    // an event type only newer producers know
+    #[derive(Fury, Debug, PartialEq)]
+    pub struct Scroll {
+        pub delta: i32,
+    }
+
+    #[derive(Fury)]
+    pub struct Feed {
+        pub events: Vec<Option<Box<dyn Any>>>,
+        pub latest: Option<Box<dyn Any>>,
+    }
+}
+
+#[derive(Fury)]
+struct Feed {
+    #[fury(unknown_types = "skip")]
+    events: Vec<Option<Box<dyn Any>>>,
+    latest: Option<Box<dyn Any>>,
+}
+
+fn mode(compatible: bool) -> Mode {
+    if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    }
+}
+
+// without type metas values are skipped by their length
+fn producer(compatible: bool) -> Fury {
+    let mut fury = Fury::default()
+        .mode(mode(compatible))
+        .struct_length_guard(!compatible);
+    fury.register::<Click>(101);
+    fury.register::<producer::Scroll>(102);
+    fury.register::<producer::Feed>(103);
+    fury
+}
+
+fn consumer(compatible: bool, policy: UnknownTypePolicy) -> Fury {
+    let mut fury = Fury::default()
+        .mode(mode(compatible))
+        .struct_length_guard(!compatible)
+        .deserialize_options(DeserializeOptions::default().unknown_types(policy));
+    fury.register::<Click>(101);
+    fury.register::<Feed>(103);
+    fury
+}
+
+fn events() -> Vec<Box<dyn Any>> {
+    vec![
+        Box::new(Click { x: 1, y: 2 }),
+        Box::new(producer::Scroll { delta: -3 }),
+        Box::new(String::from("text")),
+    ]
+}
+
+#[test]
+fn error_by_default() {
+    for compatible in [false, true] {
+        let bin = producer(compatible).serialize(&events());
+        let err = consumer(compatible, UnknownTypePolicy::default())
+            .deserialize::<Vec<Box<dyn Any>>>(&bin)
+            .unwrap_err();
+        assert_eq!(
+            err.cause().to_string(),
+            "`Box<dyn Any>` holds unregistered type id 102"
+        );
+    }
+}
+
+#[test]
+fn skip_as_none() {
+    for compatible in [false, true] {
+        let optional: Vec<Option<Box<dyn Any>>> = events().into_iter().map(Some).collect();
+        let bin = producer(compatible).serialize(&optional);
+        let consumer = consumer(compatible, UnknownTypePolicy::SkipAsNone);
+        let read: Vec<Option<Box<dyn Any>>> = consumer.deserialize(&bin).unwrap();
+        assert_eq!(read.len(), 3);
+        let click = read[0].as_ref().unwrap().downcast_ref::<Click>();
+        assert_eq!(click, Some(&Click { x: 1, y: 2 }));
+        assert!(read[1].is_none());
+        let text = read[2].as_ref().unwrap().downcast_ref::<String>();
+        assert_eq!(text.map(String::as_str), Some("text"));
+
+        // a bare `Box<dyn Any>` has nowhere to put `None`
+        let err = consumer
+            .deserialize::<Vec<Box<dyn Any>>>(&producer(compatible).serialize(&events()))
+            .unwrap_err();
+        assert_eq!(
+            err.cause().to_string(),
+            "`Box<dyn Any>` holds unregistered type id 102, only an `Option<Box<dyn Any>>` can skip it"
+        );
+    }
+}
+
+#[test]
+fn capture_opaque() {
+    let bin = producer(true).serialize(&events());
+    let read: Vec<Box<dyn Any>> = consumer(true, UnknownTypePolicy::CaptureOpaque)
+        .deserialize(&bin)
+        .unwrap();
+    assert_eq!(
+        read[1].downcast_ref::<OpaqueValue>(),
+        Some(&OpaqueValue {
+            type_id: 102,
+            value: Value::Struct {
+                type_id: None,
+                fields: vec![(String::from("delta"), Value::I32(-3))],
+            },
+        })
+    );
+    assert!(read[0].downcast_ref::<Click>().is_some());
+
+    // without type metas only the body is kept
+    let bin = producer(false).serialize(&events());
+    let read: Vec<Box<dyn Any>> = consumer(false, UnknownTypePolicy::CaptureOpaque)
+        .deserialize(&bin)
+        .unwrap();
+    let opaque = read[1].downcast_ref::<OpaqueValue>().unwrap();
+    assert_eq!(opaque.type_id, 102);
+    assert!(matches!(opaque.value, Value::Binary(_)));
+    assert!(read[2].downcast_ref::<String>().is_some());
+}
+
+#[test]
+fn field_policy() {
+    for compatible in [false, true] {
+        let feed = producer::Feed {
+            events: events().into_iter().map(Some).collect(),
+            latest: Some(Box::new(Click { x: 5, y: 6 })),
+        };
+        let bin = producer(compatible).serialize(&feed);
+        let read: Feed = consumer(compatible, UnknownTypePolicy::Error)
+            .deserialize(&bin)
+            .unwrap();
+        assert!(read.events[1].is_none());
+        assert_eq!(
+            read.latest.unwrap().downcast_ref::<Click>(),
+            Some(&Click { x: 5, y: 6 })
+        );
+
+        // the field's policy doesn't reach the other fields
+        let feed = producer::Feed {
+            events: Vec::new(),
+            latest: Some(Box::new(producer::Scroll { delta: 1 })),
+        };
+        let bin = producer(compatible).serialize(&feed);
+        assert!(consumer(compatible, UnknownTypePolicy::Error)
+            .deserialize::<Feed>(&bin)
+            .is_err());
+    }
+}
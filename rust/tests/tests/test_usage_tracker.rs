@@ -0,0 +1,91 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_core::usage::UsageTracker;
+use fury_derive::Fury;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+#[derive(Fury, Debug, PartialEq, Default)]
+enum Status {
+    #[default]
+    Open,
+    Closed,
+}
+
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Item {
+    sku: String,
+}
+
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Order {
+    items: Vec<Item>,
+    status: Status,
+}
+
+// registered long ago, no message holds it any more
+#[derive(Fury, Debug, PartialEq, Default)]
+struct Coupon {
+    code: String,
+}
+
+fn fury(mode: Mode, tracker: &Arc<UsageTracker>) -> Fury {
+    let mut fury = Fury::default().mode(mode).usage_tracker(tracker.clone());
+    fury.register::<Status>(101);
+    fury.register::<Item>(102);
+    fury.register::<Order>(103);
+    fury.register::<Coupon>(104);
+    fury
+}
+
+#[test]
+fn reports_unused_registrations() {
+    for mode in [Mode::SchemaConsistent, Mode::Compatible] {
+        let tracker = Arc::new(UsageTracker::new());
+        let fury = fury(mode, &tracker);
+        assert_eq!(tracker.unused(&fury).len(), 4);
+
+        let order = Order {
+            items: vec![Item {
+                sku: "a-1".to_string(),
+            }],
+            status: Status::Closed,
+        };
+        let bin = fury.serialize(&order);
+        assert_eq!(tracker.serialized(), BTreeSet::from([101, 102, 103]));
+        assert!(tracker.deserialized().is_empty());
+
+        assert_eq!(fury.deserialize::<Order>(&bin).unwrap(), order);
+        assert_eq!(tracker.deserialized(), BTreeSet::from([101, 102, 103]));
+        let unused = tracker.unused(&fury);
+        assert_eq!(unused, vec![(104, std::any::type_name::<Coupon>())]);
+
+        tracker.reset();
+        fury.deserialize::<Item>(&fury.serialize(&Item::default()))
+            .unwrap();
+        assert_eq!(tracker.deserialized(), BTreeSet::from([102]));
+        assert_eq!(tracker.unused(&fury).len(), 3);
+    }
+}
+
+#[test]
+fn off_by_default() {
+    assert!(Fury::default().get_usage_tracker().is_none());
+}
@@ -15,7 +15,7 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use fury_core::util::to_utf8;
+use fury_core::util::{to_utf16, to_utf8};
 
 #[test]
 fn test_to_utf8() {
@@ -111,3 +111,37 @@ fn test_to_utf8_missing_surrogate_pair() {
         "Invalid UTF-16 string: wrong surrogate pair"
     );
 }
+
+#[test]
+fn test_to_utf16() {
+    // long enough for whole ASCII words, with multi-byte characters in between
+    let s = "Hello, world! Hé€lo, 世界!😀 𝄞💡 and some ASCII to close";
+    let expected = s.encode_utf16().collect::<Vec<u16>>();
+    assert_eq!(to_utf16(s.as_bytes(), false).unwrap(), expected);
+    let expected_le = expected
+        .iter()
+        .map(|&unit| swap_endian(unit))
+        .collect::<Vec<u16>>();
+    let result_le = to_utf16(s.as_bytes(), true).unwrap();
+    assert_eq!(result_le, expected_le);
+    assert_eq!(to_utf8(&result_le, true).unwrap(), s.as_bytes());
+    assert_eq!(to_utf16(b"", false).unwrap(), Vec::<u16>::new());
+}
+
+#[test]
+fn test_to_utf16_invalid() {
+    let cases: [(&[u8], &str); 5] = [
+        (
+            b"ab\x80",
+            "Invalid UTF-8 string: unexpected continuation byte",
+        ),
+        (b"\xe4\xb8", "Invalid UTF-8 string: truncated sequence"),
+        (b"\xe4ab", "Invalid UTF-8 string: missing continuation byte"),
+        // overlong '/' and an encoded surrogate
+        (b"\xc0\xaf", "Invalid UTF-8 string: invalid code point"),
+        (b"\xed\xa0\x80", "Invalid UTF-8 string: invalid code point"),
+    ];
+    for (utf8, err) in cases {
+        assert_eq!(to_utf16(utf8, false).unwrap_err(), err);
+    }
+}
@@ -0,0 +1,129 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_core::value::Value;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Address {
+    city: String,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Person {
+    name: String,
+    age: i32,
+    nickname: Option<String>,
+    address: Address,
+    scores: Vec<i64>,
+}
+
+fn fury(compatible: bool) -> Fury {
+    let mode = if compatible {
+        Mode::Compatible
+    } else {
+        Mode::SchemaConsistent
+    };
+    let mut fury = Fury::default().mode(mode);
+    fury.register::<Address>(101);
+    fury.register::<Person>(102);
+    fury
+}
+
+fn person() -> Person {
+    Person {
+        name: String::from("ada"),
+        age: 36,
+        nickname: None,
+        address: Address {
+            city: String::from("London"),
+        },
+        scores: vec![3, 5],
+    }
+}
+
+#[test]
+fn same_as_from_message() {
+    for compatible in [false, true] {
+        let fury = fury(compatible);
+        let value = Value::from_typed(&person(), &fury).unwrap();
+        let from_message = fury.deserialize_value(&fury.serialize(&person())).unwrap();
+        assert_eq!(value, from_message);
+    }
+    let fury = fury(false);
+    let value = Value::from_typed(&person(), &fury).unwrap();
+    assert_eq!(value.into_typed::<Person>(&fury).unwrap(), person());
+}
+
+#[test]
+fn compatible_values_lack_type_ids() {
+    let fury = fury(true);
+    let value = Value::from_typed(&person(), &fury).unwrap();
+    let err = value.into_typed::<Person>(&fury).unwrap_err();
+    assert_eq!(err.to_string(), "Can't write a struct without its type id");
+}
+
+#[test]
+fn from_gateway_value() {
+    let value = Value::Struct {
+        type_id: Some(102),
+        fields: vec![
+            (String::from("name"), Value::String(String::from("grace"))),
+            (String::from("age"), Value::I32(45)),
+            (
+                String::from("address"),
+                Value::Struct {
+                    type_id: Some(101),
+                    fields: vec![(String::from("city"), Value::String(String::from("NYC")))],
+                },
+            ),
+            (String::from("scores"), Value::I64Array(vec![])),
+        ],
+    };
+    for compatible in [false, true] {
+        assert_eq!(
+            value
+                .clone()
+                .into_typed::<Person>(&fury(compatible))
+                .unwrap(),
+            Person {
+                name: String::from("grace"),
+                age: 45,
+                nickname: None,
+                address: Address {
+                    city: String::from("NYC")
+                },
+                scores: vec![],
+            }
+        );
+    }
+}
+
+#[test]
+fn mismatched_value() {
+    let fury = fury(false);
+    assert!(Value::String(String::from("ada"))
+        .into_typed::<Person>(&fury)
+        .is_err());
+    let value = Value::Struct {
+        type_id: Some(104),
+        fields: vec![],
+    };
+    let err = value.into_typed::<Person>(&fury).unwrap_err();
+    assert_eq!(err.to_string(), "Type 104 isn't a registered struct");
+}
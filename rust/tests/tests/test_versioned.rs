@@ -0,0 +1,143 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::error::Error;
+use fury_core::fury::Fury;
+use fury_core::resolver::context::{ReadContext, WriteContext};
+use fury_core::serializer::{Serializer, VersionSerializer, Versioned};
+use fury_derive::Fury;
+
+// amounts were floats in cents first, then a currency code and an integer count of cents
+#[derive(Debug, PartialEq)]
+struct Money {
+    currency: String,
+    cents: i64,
+}
+
+struct MoneyV1;
+
+impl VersionSerializer<Money> for MoneyV1 {
+    fn write(&self, value: &Money, context: &mut WriteContext) {
+        context.writer.f64(value.cents as f64);
+    }
+
+    fn read(&self, context: &mut ReadContext) -> Result<Money, Error> {
+        Ok(Money {
+            currency: String::from("USD"),
+            cents: context.reader.f64()? as i64,
+        })
+    }
+}
+
+struct MoneyV2;
+
+impl VersionSerializer<Money> for MoneyV2 {
+    fn write(&self, value: &Money, context: &mut WriteContext) {
+        value.currency.serialize(context);
+        context.writer.var_int64(value.cents);
+    }
+
+    fn read(&self, context: &mut ReadContext) -> Result<Money, Error> {
+        Ok(Money {
+            currency: String::deserialize(context)?,
+            cents: context.reader.var_int64()?,
+        })
+    }
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Invoice {
+    id: i32,
+    total: Versioned<Money>,
+    lines: Vec<Versioned<Money>>,
+}
+
+fn v1() -> Fury {
+    let mut fury = Fury::default();
+    fury.register_versioned(102, 1, MoneyV1).unwrap();
+    fury.register::<Invoice>(101);
+    fury
+}
+
+fn v2() -> Fury {
+    let mut fury = v1();
+    fury.register_versioned(102, 2, MoneyV2).unwrap();
+    fury
+}
+
+fn usd(cents: i64) -> Versioned<Money> {
+    Versioned(Money {
+        currency: String::from("USD"),
+        cents,
+    })
+}
+
+#[test]
+fn newest_version_writes() {
+    let invoice = Invoice {
+        id: 1,
+        total: Versioned(Money {
+            currency: String::from("EUR"),
+            cents: 1250,
+        }),
+        lines: vec![usd(1000), usd(250)],
+    };
+    let fury = v2();
+    let bytes = fury.serialize(&invoice);
+    assert_eq!(fury.deserialize::<Invoice>(&bytes).unwrap(), invoice);
+    // a peer only knowing version 1 can't read it
+    let err = v1().deserialize::<Invoice>(&bytes).unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        "Version 2 of `test_versioned::Money` has no registered serializer, registered versions: [1]"
+    );
+}
+
+#[test]
+fn reads_older_versions() {
+    let bytes = v1().serialize(&usd(1999));
+    let money: Versioned<Money> = v2().deserialize(&bytes).unwrap();
+    assert_eq!(money, usd(1999));
+}
+
+#[test]
+fn registration_errors() {
+    let mut fury = v2();
+    assert_eq!(
+        fury.register_versioned(102, 2, MoneyV2)
+            .unwrap_err()
+            .to_string(),
+        "Version 2 of `test_versioned::Money` is already registered"
+    );
+    assert_eq!(
+        fury.register_versioned(103, 3, MoneyV2)
+            .unwrap_err()
+            .to_string(),
+        "`test_versioned::Money` is already registered with id 102, can't register a version of it with id 103"
+    );
+}
+
+#[test]
+fn not_a_value() {
+    let fury = v2();
+    let err = fury
+        .deserialize_value(&fury.serialize(&usd(1)))
+        .unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        "Type 102 has versioned serializers, its values can't be read as `Value`"
+    );
+}
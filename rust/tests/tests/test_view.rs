@@ -0,0 +1,126 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use fury_core::fury::Fury;
+use fury_core::types::Mode;
+use fury_derive::Fury;
+
+#[derive(Fury, Debug, PartialEq)]
+struct Line {
+    sku: String,
+    quantity: i32,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct Order {
+    id: i64,
+    ts: i64,
+    note: String,
+    lines: Vec<Line>,
+}
+
+// views of `Order`, read from its messages without decoding the rest
+#[derive(Fury, Debug, PartialEq)]
+struct OrderHeader {
+    id: i64,
+    ts: i64,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct LineView {
+    sku: String,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct OrderLines {
+    lines: Vec<LineView>,
+    discount: Option<i32>,
+}
+
+#[derive(Fury, Debug, PartialEq)]
+struct OrderTotal {
+    id: i64,
+    total: i64,
+}
+
+fn fury() -> Fury {
+    let mut fury = Fury::default().mode(Mode::Compatible);
+    fury.register::<Line>(101);
+    fury.register::<Order>(102);
+    fury.register::<OrderHeader>(103);
+    fury.register::<LineView>(104);
+    fury.register::<OrderLines>(105);
+    fury.register::<OrderTotal>(106);
+    fury
+}
+
+fn order() -> Vec<u8> {
+    fury().serialize(&Order {
+        id: 7,
+        ts: 1_700_000_000,
+        note: String::from("leave at the door"),
+        lines: vec![
+            Line {
+                sku: String::from("apple"),
+                quantity: 3,
+            },
+            Line {
+                sku: String::from("pear"),
+                quantity: 1,
+            },
+        ],
+    })
+}
+
+#[test]
+fn subset_of_fields() {
+    let header: OrderHeader = fury().deserialize(&order()).unwrap();
+    assert_eq!(
+        header,
+        OrderHeader {
+            id: 7,
+            ts: 1_700_000_000
+        }
+    );
+}
+
+#[test]
+fn nested_views() {
+    let lines: OrderLines = fury().deserialize(&order()).unwrap();
+    assert_eq!(
+        lines,
+        OrderLines {
+            lines: vec![
+                LineView {
+                    sku: String::from("apple")
+                },
+                LineView {
+                    sku: String::from("pear")
+                },
+            ],
+            discount: None,
+        }
+    );
+}
+
+#[test]
+fn missing_field() {
+    let err = fury().deserialize::<OrderTotal>(&order()).unwrap_err();
+    assert_eq!(
+        err.cause().to_string(),
+        "Field `total` of `test_view::OrderTotal` is missing from the message"
+    );
+}